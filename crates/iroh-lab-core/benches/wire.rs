@@ -0,0 +1,92 @@
+//! Compares frames encoded with and without compression.
+//!
+//! Besides the encoding time measured by criterion, the benchmark prints the
+//! bytes on the wire for each sample message, as that is what compression is
+//! meant to save:
+//!
+//! ```text
+//! cargo bench --bench wire
+//! ```
+use chrono::Utc;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use iroh::SecretKey;
+use iroh_gossip::proto::TopicId;
+use iroh_lab_core::client::ChatMessage;
+use iroh_lab_core::crypto::TopicKey;
+use iroh_lab_core::wire::{self, Compression, Payload};
+
+// Sample messages from a short greeting to a large paste
+fn samples() -> Vec<(&'static str, Payload)> {
+    let message = |content: String| {
+        Payload::Chat(ChatMessage {
+            id: "3f0c8a52-5d4e-4a57-9a53-1f9e4c1d2b7a".to_string(),
+            author: "alice".to_string(),
+            content,
+            timestamp: Utc::now(),
+            topic_hash: "bench-topic".to_string(),
+            sequence: 1,
+            lamport: 1,
+            verified_sender: None,
+            edited_at: None,
+            deleted_at: None,
+        })
+    };
+
+    vec![
+        ("short", message("Hello, world!".to_string())),
+        (
+            "paragraph",
+            message("The quick brown fox jumps over the lazy dog. ".repeat(10)),
+        ),
+        (
+            "log paste",
+            message(
+                (0..200)
+                    .map(|i| {
+                        format!(
+                            "2025-01-01T12:00:{:02}Z INFO request {} served in 3ms\n",
+                            i % 60,
+                            i
+                        )
+                    })
+                    .collect(),
+            ),
+        ),
+    ]
+}
+
+fn bench_encode(c: &mut Criterion) {
+    let secret_key = SecretKey::generate(rand::rngs::OsRng);
+    let topic_key = TopicKey::derive(&rand::random(), &TopicId::from_bytes(rand::random()));
+
+    println!(
+        "{:<12} {:>12} {:>12}",
+        "message", "plain bytes", "zstd bytes"
+    );
+    for (name, payload) in samples() {
+        let size = |compression| {
+            wire::encode_with(&payload, &secret_key, &topic_key, compression)
+                .expect("Failed to encode payload")
+                .len()
+        };
+        println!(
+            "{:<12} {:>12} {:>12}",
+            name,
+            size(Compression::Never),
+            size(Compression::Auto)
+        );
+    }
+
+    let mut group = c.benchmark_group("encode");
+    for (name, payload) in samples() {
+        for (label, compression) in [("plain", Compression::Never), ("zstd", Compression::Auto)] {
+            group.bench_with_input(BenchmarkId::new(label, name), &payload, |b, payload| {
+                b.iter(|| wire::encode_with(payload, &secret_key, &topic_key, compression))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_encode);
+criterion_main!(benches);
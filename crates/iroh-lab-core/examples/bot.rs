@@ -0,0 +1,66 @@
+//! A bot answering `!time` with the current time and `!roll` with a die
+//! roll, built on [`ChatEngine`].
+//!
+//! ```bash
+//! cargo run --example bot -- <ticket or invite>
+//! ```
+//!
+//! Without a ticket, the bot creates a topic and prints its ticket.
+//! `!roll 20` rolls a die of 20 sides instead of 6.
+use chrono::Utc;
+use futures::StreamExt;
+use iroh_lab_core::config::Config;
+use iroh_lab_core::engine::ChatEngine;
+use iroh_lab_core::ClientEvent;
+use rand::Rng;
+
+// Sides of the die rolled by `!roll` alone
+const DEFAULT_SIDES: u32 = 6;
+
+#[tokio::main]
+async fn main() -> Result<(), String> {
+    let engine = ChatEngine::start("bot", &Config::default()).await?;
+    let mut events = engine.events()?;
+    let topic = match std::env::args().nth(1) {
+        Some(ticket) => engine.join(ticket).await?,
+        None => {
+            let topic = engine.create("bot").await?;
+            println!("Join with {}", topic.ticket);
+            topic
+        }
+    };
+    println!("Answering !time and !roll on {}", topic.name);
+
+    while let Some(event) = events.next().await {
+        let ClientEvent::Message(message) = event else {
+            continue;
+        };
+        if let Some(answer) = answer(&message.content) {
+            engine.reply(&message, answer).await?;
+        }
+    }
+    Ok(())
+}
+
+// The answer to a command, if the message is one
+fn answer(content: &str) -> Option<String> {
+    let mut words = content.split_whitespace();
+    match words.next()? {
+        "!time" => Some(format!(
+            "It is {}",
+            Utc::now().format("%H:%M UTC on %Y-%m-%d")
+        )),
+        "!roll" => {
+            let sides = match words.next() {
+                None => DEFAULT_SIDES,
+                Some(sides) => match sides.parse::<u32>() {
+                    Ok(sides) if sides > 0 => sides,
+                    _ => return Some(format!("Cannot roll a die of {} sides", sides)),
+                },
+            };
+            let roll = rand::thread_rng().gen_range(1..=sides);
+            Some(format!("Rolled {} on a d{}", roll, sides))
+        }
+        _ => None,
+    }
+}
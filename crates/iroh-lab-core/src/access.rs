@@ -0,0 +1,72 @@
+//! Who takes part in a private topic.
+//!
+//! The [ticket](crate::ticket::Ticket) of a topic names the node that
+//! created it. The creator may restrict the topic to the nodes of an
+//! [`Allowlist`], which it signs like everything else it sends. Members keep
+//! the latest allowlist of the creator, and drop what other nodes send along
+//! with unsigned payloads. Anyone holding the ticket can still read the
+//! topic: the allowlist keeps others from being heard, not from listening.
+use crate::client::Allowlist;
+use iroh::NodeId;
+use std::collections::BTreeSet;
+
+/// The creator of a topic, and whom it allows to take part.
+#[derive(Debug, Clone, Default)]
+pub struct Access {
+    creator: Option<NodeId>,
+    allowlist: Option<Allowlist>,
+}
+
+impl Access {
+    /// Access to a topic created by `creator`, open until it sends an
+    /// allowlist. Topics without a known creator stay open.
+    pub fn new(creator: Option<NodeId>) -> Self {
+        Self {
+            creator,
+            allowlist: None,
+        }
+    }
+
+    /// The node that created the topic, if its ticket names it.
+    pub fn creator(&self) -> Option<NodeId> {
+        self.creator
+    }
+
+    /// The latest allowlist of the creator.
+    pub fn allowlist(&self) -> Option<&Allowlist> {
+        self.allowlist.as_ref()
+    }
+
+    /// The nodes allowed to take part besides the creator, `None` while the
+    /// topic is open to everyone with the ticket.
+    pub fn members(&self) -> Option<&BTreeSet<NodeId>> {
+        self.allowlist.as_ref()?.members.as_ref()
+    }
+
+    /// Whether what `sender` signed is accepted. Unsigned payloads are only
+    /// accepted while the topic is open.
+    pub fn admits(&self, sender: Option<NodeId>) -> bool {
+        let Some(members) = self.members() else {
+            return true;
+        };
+        sender.is_some_and(|sender| Some(sender) == self.creator || members.contains(&sender))
+    }
+
+    /// Takes on `allowlist`, signed by `sender`, if it is newer than the one
+    /// kept. Returns whether it was taken on, and fails unless the creator
+    /// signed it.
+    pub fn accept(&mut self, allowlist: Allowlist, sender: Option<NodeId>) -> Result<bool, String> {
+        if self.creator.is_none() || sender != self.creator {
+            return Err("Only the creator of the topic decides who takes part".to_string());
+        }
+        if self
+            .allowlist
+            .as_ref()
+            .is_some_and(|kept| kept.lamport >= allowlist.lamport)
+        {
+            return Ok(false);
+        }
+        self.allowlist = Some(allowlist);
+        Ok(true)
+    }
+}
@@ -0,0 +1,341 @@
+//! Append-only, hash-chained archive of the messages of a topic.
+//!
+//! Archiving is opt-in per topic. Each archived message is written as one
+//! JSON line holding its position in the archive, the hash of the entry
+//! before it and its own hash, which covers the previous hash, the position
+//! and the message. Changing, removing or reordering entries breaks the
+//! chain, and [`verify`] reports the first entry where it breaks. Entries
+//! cut off the end of the archive leave no trace in the chain, so compare
+//! the number of entries with an earlier verification to notice those.
+//!
+//! Archives live in the `archive` directory next to the configuration, one
+//! file per topic, e.g. `~/.config/iroh-lab/archive/<topic-hash>.jsonl`.
+use crate::client::ChatMessage;
+use crate::config::Config;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Hash the first entry of every archive links to.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+// Directory below the configuration directory holding the archives
+const ARCHIVE_DIR: &str = "archive";
+
+// Extension of archive files
+const ARCHIVE_EXTENSION: &str = "jsonl";
+
+/// A line of an archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// Position in the archive, starting at 0.
+    pub index: u64,
+    /// Hash of the entry before, [`GENESIS_HASH`] for the first one.
+    pub prev: String,
+    /// Hash of this entry, see [`Entry::compute_hash`].
+    pub hash: String,
+    pub message: ChatMessage,
+}
+
+impl Entry {
+    // Links `message` to the entry at `index - 1` with hash `prev`
+    fn new(index: u64, prev: String, message: ChatMessage) -> Result<Self, String> {
+        let hash = Self::compute_hash(index, &prev, &message)?;
+        Ok(Self {
+            index,
+            prev,
+            hash,
+            message,
+        })
+    }
+
+    /// The hash of an entry at `index` holding `message`, following the
+    /// entry with hash `prev`.
+    pub fn compute_hash(index: u64, prev: &str, message: &ChatMessage) -> Result<String, String> {
+        let message = serde_json::to_vec(message)
+            .map_err(|e| format!("Failed to encode archived message: {}", e))?;
+        let mut hasher = Sha256::new();
+        hasher.update(prev.as_bytes());
+        hasher.update(index.to_be_bytes());
+        hasher.update(&message);
+        Ok(data_encoding::HEXLOWER.encode(&hasher.finalize()))
+    }
+}
+
+/// The archive of a topic, open for appending.
+#[derive(Debug)]
+pub struct Archive {
+    path: PathBuf,
+    // Index and hash of the next entry's predecessor
+    next_index: u64,
+    last_hash: String,
+}
+
+impl Archive {
+    /// Directory holding the archives of the current user, if the platform
+    /// has a configuration directory.
+    pub fn default_dir() -> Option<PathBuf> {
+        Config::directory().map(|dir| dir.join(ARCHIVE_DIR))
+    }
+
+    /// Location of the archive of the topic `topic_hash` in `dir`.
+    pub fn path_in(dir: &Path, topic_hash: &str) -> PathBuf {
+        dir.join(format!("{}.{}", topic_hash, ARCHIVE_EXTENSION))
+    }
+
+    /// The archives in `dir`, ordered by path.
+    pub fn list(dir: &Path) -> Result<Vec<PathBuf>, String> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to read {}: {}", dir.display(), e)),
+        };
+        let mut paths = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+                .path();
+            if path.extension().and_then(|ext| ext.to_str()) == Some(ARCHIVE_EXTENSION) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Opens the archive at `path`, continuing the chain after its last
+    /// entry. The archive is created on the first append.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let mut archive = Self {
+            path: path.to_path_buf(),
+            next_index: 0,
+            last_hash: GENESIS_HASH.to_string(),
+        };
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(archive),
+            Err(e) => return Err(format!("Failed to open {}: {}", path.display(), e)),
+        };
+
+        let mut last = None;
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            if !line.trim().is_empty() {
+                last = Some(line);
+            }
+        }
+        if let Some(line) = last {
+            let entry: Entry = serde_json::from_str(&line).map_err(|e| {
+                format!(
+                    "The last entry of {} is damaged, verify the archive: {}",
+                    path.display(),
+                    e
+                )
+            })?;
+            archive.next_index = entry.index + 1;
+            archive.last_hash = entry.hash;
+        }
+        Ok(archive)
+    }
+
+    /// Appends `message` to the archive.
+    pub fn append(&mut self, message: &ChatMessage) -> Result<(), String> {
+        let entry = Entry::new(self.next_index, self.last_hash.clone(), message.clone())?;
+        let mut line = serde_json::to_string(&entry)
+            .map_err(|e| format!("Failed to encode archive entry: {}", e))?;
+        line.push('\n');
+
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))?;
+
+        self.next_index += 1;
+        self.last_hash = entry.hash;
+        Ok(())
+    }
+
+    /// Number of entries in the archive.
+    pub fn len(&self) -> u64 {
+        self.next_index
+    }
+
+    /// Whether nothing was archived yet.
+    pub fn is_empty(&self) -> bool {
+        self.next_index == 0
+    }
+}
+
+/// What is wrong with an archive entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// The line is not an archive entry.
+    Malformed(String),
+    /// Entries are missing before this one.
+    Hole { expected: u64 },
+    /// The entry does not link to the hash of the entry before.
+    BrokenLink,
+    /// The entry was changed after it was written.
+    Tampered,
+}
+
+/// The first entry of an archive that does not continue the chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Line of the entry in the archive, starting at 1.
+    pub line: usize,
+    pub problem: Problem,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.problem {
+            Problem::Malformed(e) => write!(f, "Line {} is not an archive entry: {}", self.line, e),
+            Problem::Hole { expected } => write!(
+                f,
+                "Line {} skips entries, entry {} is missing",
+                self.line, expected
+            ),
+            Problem::BrokenLink => {
+                write!(f, "Line {} does not follow the entry before it", self.line)
+            }
+            Problem::Tampered => write!(f, "Line {} was changed after it was written", self.line),
+        }
+    }
+}
+
+/// The outcome of verifying an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verification {
+    /// Number of entries that continue the chain.
+    pub intact: u64,
+    /// Where the chain breaks, if it does.
+    pub divergence: Option<Divergence>,
+}
+
+impl Verification {
+    /// Whether the whole archive continues the chain.
+    pub fn is_intact(&self) -> bool {
+        self.divergence.is_none()
+    }
+}
+
+/// Checks that every entry of the archive at `path` continues the chain,
+/// stopping at the first one that does not.
+pub fn verify(path: &Path) -> Result<Verification, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+
+    let mut intact = 0;
+    let mut prev = GENESIS_HASH.to_string();
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match check_entry(&line, intact, &prev) {
+            Ok(entry) => prev = entry.hash,
+            Err(problem) => {
+                return Ok(Verification {
+                    intact,
+                    divergence: Some(Divergence {
+                        line: number + 1,
+                        problem,
+                    }),
+                })
+            }
+        }
+        intact += 1;
+    }
+
+    Ok(Verification {
+        intact,
+        divergence: None,
+    })
+}
+
+// The entry on `line`, if it is the entry at `index` following the hash `prev`
+fn check_entry(line: &str, index: u64, prev: &str) -> Result<Entry, Problem> {
+    let entry: Entry = serde_json::from_str(line).map_err(|e| Problem::Malformed(e.to_string()))?;
+    if entry.index > index {
+        return Err(Problem::Hole { expected: index });
+    }
+    if entry.index < index || entry.prev != prev {
+        return Err(Problem::BrokenLink);
+    }
+    let hash = Entry::compute_hash(entry.index, &entry.prev, &entry.message)
+        .map_err(Problem::Malformed)?;
+    if entry.hash != hash {
+        return Err(Problem::Tampered);
+    }
+    Ok(entry)
+}
+
+/// The messages of the archive at `path`, in the order they were archived.
+/// Fails unless every entry continues the chain, so a damaged archive is
+/// never passed on.
+pub fn read(path: &Path) -> Result<Vec<ChatMessage>, String> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to open {}: {}", path.display(), e)),
+    };
+
+    let mut messages = Vec::new();
+    let mut prev = GENESIS_HASH.to_string();
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = check_entry(&line, messages.len() as u64, &prev).map_err(|problem| {
+            let divergence = Divergence {
+                line: number + 1,
+                problem,
+            };
+            format!("{} is broken: {}", path.display(), divergence)
+        })?;
+        prev = entry.hash;
+        messages.push(entry.message);
+    }
+    Ok(messages)
+}
+
+/// Removes the messages of the archive at `path` that `keep` rejects, and
+/// links the remaining ones into a new chain. Returns how many messages were
+/// removed. Damaged archives are left alone.
+pub fn prune(path: &Path, keep: impl Fn(&ChatMessage) -> bool) -> Result<usize, String> {
+    let messages = read(path)?;
+    let (kept, removed): (Vec<_>, Vec<_>) = messages.into_iter().partition(|m| keep(m));
+    if removed.is_empty() {
+        return Ok(0);
+    }
+
+    // Write the new chain next to the archive first, so a crash never leaves
+    // half an archive
+    let partial = path.with_extension("partial");
+    match std::fs::remove_file(&partial) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to remove {}: {}", partial.display(), e)),
+    }
+    let mut archive = Archive::open(&partial)?;
+    for message in &kept {
+        archive.append(message)?;
+    }
+    if kept.is_empty() {
+        File::create(&partial)
+            .map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+    }
+    std::fs::rename(&partial, path)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(removed.len())
+}
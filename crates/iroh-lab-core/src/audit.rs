@@ -0,0 +1,88 @@
+//! Audit log of changes to how long messages are kept.
+//!
+//! Placing a topic on hold exempts its archive from pruning until the hold
+//! is released, e.g. to preserve the record of a dispute. Placing and
+//! releasing holds, and prunes skipped because of them, are appended to the
+//! audit log next to the configuration, e.g. `~/.config/iroh-lab/audit.jsonl`
+//! on Linux, one JSON line per event.
+use crate::config::Config;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+// Name of the audit log file
+const AUDIT_FILE: &str = "audit.jsonl";
+
+/// What happened to a topic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// The topic was placed on hold.
+    HoldPlaced,
+    /// The hold on the topic was released.
+    HoldReleased,
+    /// A scheduled export did not prune the archive, as the topic is on hold.
+    PruneSkipped,
+}
+
+/// A line of the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub at: DateTime<Utc>,
+    pub topic_hash: String,
+    #[serde(flatten)]
+    pub event: AuditEvent,
+}
+
+/// The audit log of the current user, if the platform has a configuration
+/// directory.
+pub fn default_path() -> Option<PathBuf> {
+    Config::directory().map(|dir| dir.join(AUDIT_FILE))
+}
+
+/// Appends `entry` to the audit log at `path`.
+pub fn append(path: &Path, entry: &AuditEntry) -> Result<(), String> {
+    let mut line =
+        serde_json::to_string(entry).map_err(|e| format!("Failed to encode audit entry: {}", e))?;
+    line.push('\n');
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// The entries of the audit log at `path` about the topic `topic_hash`,
+/// oldest first.
+pub fn entries(path: &Path, topic_hash: &str) -> Result<Vec<AuditEntry>, String> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let mut entries = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: AuditEntry = serde_json::from_str(line).map_err(|e| {
+            format!(
+                "Line {} of {} is damaged: {}",
+                number + 1,
+                path.display(),
+                e
+            )
+        })?;
+        if entry.topic_hash == topic_hash {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
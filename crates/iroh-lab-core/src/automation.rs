@@ -0,0 +1,279 @@
+//! Scheduled exports of archived topics.
+//!
+//! A topic can have an [`ExportRule`], e.g. "every Sunday at 9:00, export
+//! the last 7 days to Markdown in `~/Documents/chat`, then prune". Rules
+//! read the topic's [archive](crate::archive), so saving a rule turns on
+//! archiving. The application checks the rules regularly and runs every
+//! rule whose scheduled time passed since its last run, recording each run
+//! in a [`RunHistory`] next to the configuration, e.g.
+//! `~/.config/iroh-lab/export-runs.json` on Linux. Topics on
+//! [hold](crate::audit) are exported, but never pruned.
+use crate::archive::{self, Archive};
+use crate::client::ChatMessage;
+use crate::config::Config;
+use chrono::{DateTime, Datelike, Duration, NaiveTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Runs kept in the history of each topic.
+pub const MAX_RUNS_PER_TOPIC: usize = 20;
+
+/// Days of the week a rule can run on, for choosing one.
+pub const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+// Name of the file holding the run history
+const RUNS_FILE: &str = "export-runs.json";
+
+/// When and where to export the messages of a topic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportRule {
+    /// Name of the topic, used as heading and file name of the exports.
+    pub topic_name: String,
+    /// Day of the week to export on, in local time.
+    pub weekday: Weekday,
+    /// Hour of the day to export at, in local time.
+    pub hour: u32,
+    /// How many days of messages to export.
+    pub days: u32,
+    /// Directory receiving the exports.
+    pub folder: PathBuf,
+    /// Whether to remove the exported and older messages from the archive.
+    pub prune: bool,
+    /// When the rule was saved. Scheduled times before are not run.
+    pub since: DateTime<Utc>,
+}
+
+impl ExportRule {
+    /// The latest time at or before `now` the rule is scheduled for, in the
+    /// time zone of `now`.
+    pub fn last_scheduled<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let time = NaiveTime::from_hms_opt(self.hour, 0, 0)?;
+        (0..=7)
+            .map(|days_back| now.date_naive() - Duration::days(days_back))
+            .filter(|date| date.weekday() == self.weekday)
+            // Times skipped by a daylight saving change are not run that week
+            .filter_map(|date| {
+                now.timezone()
+                    .from_local_datetime(&date.and_time(time))
+                    .earliest()
+            })
+            .find(|scheduled| scheduled <= now)
+    }
+
+    /// Whether the rule is to run at `now`, after last running at
+    /// `last_run`.
+    pub fn is_due<Tz: TimeZone>(
+        &self,
+        last_run: Option<DateTime<Utc>>,
+        now: &DateTime<Tz>,
+    ) -> bool {
+        let Some(scheduled) = self.last_scheduled(now) else {
+            return false;
+        };
+        let scheduled = scheduled.with_timezone(&Utc);
+        let covered = last_run.map_or(self.since, |last_run| last_run.max(self.since));
+        scheduled > covered
+    }
+
+    /// Location of the export made at `at`.
+    pub fn export_path(&self, at: DateTime<Utc>) -> PathBuf {
+        self.folder.join(format!(
+            "{}-{}.md",
+            safe_file_name(&self.topic_name),
+            at.format("%Y-%m-%d")
+        ))
+    }
+}
+
+/// `name` with every character but letters and digits replaced by a dash,
+/// to name files after topics.
+pub fn safe_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// The outcome of running a rule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Run {
+    pub at: DateTime<Utc>,
+    /// File the messages were exported to.
+    pub file: Option<PathBuf>,
+    /// Number of messages exported.
+    pub exported: usize,
+    /// Number of messages removed from the archive.
+    pub pruned: usize,
+    /// Whether pruning was skipped, as the topic is on hold.
+    #[serde(default)]
+    pub prune_held: bool,
+    /// Why the run failed, if it did.
+    pub error: Option<String>,
+}
+
+impl Run {
+    /// Runs `rule` for the topic `topic_hash`, whose archive is in
+    /// `archive_dir`, at `at`. The messages of the last `rule.days` days are
+    /// exported, and if the rule says so, every message up to `at` is pruned,
+    /// unless the topic is `held`.
+    pub fn execute(
+        rule: &ExportRule,
+        topic_hash: &str,
+        archive_dir: &Path,
+        at: DateTime<Utc>,
+        held: bool,
+    ) -> Self {
+        let mut run = Self {
+            at,
+            file: None,
+            exported: 0,
+            pruned: 0,
+            prune_held: false,
+            error: None,
+        };
+        if let Err(e) = run.export(rule, topic_hash, archive_dir, held) {
+            run.error = Some(e);
+        }
+        run
+    }
+
+    fn export(
+        &mut self,
+        rule: &ExportRule,
+        topic_hash: &str,
+        archive_dir: &Path,
+        held: bool,
+    ) -> Result<(), String> {
+        let path = Archive::path_in(archive_dir, topic_hash);
+        let from = self.at - Duration::days(rule.days.into());
+        let messages: Vec<_> = archive::read(&path)?
+            .into_iter()
+            .filter(|m| m.timestamp >= from && m.timestamp < self.at)
+            .collect();
+
+        let file = rule.export_path(self.at);
+        std::fs::create_dir_all(&rule.folder)
+            .map_err(|e| format!("Failed to create {}: {}", rule.folder.display(), e))?;
+        std::fs::write(
+            &file,
+            to_markdown(&rule.topic_name, from, self.at, &messages),
+        )
+        .map_err(|e| format!("Failed to write {}: {}", file.display(), e))?;
+        self.file = Some(file);
+        self.exported = messages.len();
+
+        if rule.prune && held {
+            self.prune_held = true;
+        } else if rule.prune {
+            let at = self.at;
+            self.pruned = archive::prune(&path, |m| m.timestamp >= at)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders `messages` of `topic_name`, sent between `from` and `to`, as a
+/// Markdown document with a section per day.
+pub fn to_markdown(
+    topic_name: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    messages: &[ChatMessage],
+) -> String {
+    let mut markdown = format!(
+        "# {}\n\nMessages from {} to {} UTC.\n",
+        topic_name,
+        from.format("%Y-%m-%d %H:%M"),
+        to.format("%Y-%m-%d %H:%M")
+    );
+    let mut messages: Vec<_> = messages.iter().collect();
+    messages.sort_by_key(|m| m.order_key());
+
+    let mut day = None;
+    for message in messages {
+        let date = message.timestamp.date_naive();
+        if day != Some(date) {
+            day = Some(date);
+            let _ = write!(markdown, "\n## {}\n\n", date.format("%Y-%m-%d"));
+        }
+        let content = message.content.replace('\n', "\n  ");
+        let _ = writeln!(
+            markdown,
+            "- **{}** {}: {}",
+            message.author,
+            message.timestamp.format("%H:%M"),
+            content
+        );
+    }
+    markdown
+}
+
+/// The latest runs of the rules of every topic.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunHistory {
+    // Runs of each topic by topic hash, oldest first
+    topics: BTreeMap<String, Vec<Run>>,
+}
+
+impl RunHistory {
+    /// Location of the run history of the current user, if the platform has
+    /// a configuration directory.
+    pub fn default_path() -> Option<PathBuf> {
+        Config::directory().map(|dir| dir.join(RUNS_FILE))
+    }
+
+    /// Loads the run history from `path`, or an empty one if the file does
+    /// not exist.
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    /// Saves the run history to `path`, creating its directory if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to encode run history: {}", e))?;
+        std::fs::write(path, contents)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Records a run for the topic `topic_hash`, forgetting the oldest runs
+    /// beyond [`MAX_RUNS_PER_TOPIC`].
+    pub fn record(&mut self, topic_hash: &str, run: Run) {
+        let runs = self.topics.entry(topic_hash.to_string()).or_default();
+        runs.push(run);
+        if runs.len() > MAX_RUNS_PER_TOPIC {
+            runs.drain(..runs.len() - MAX_RUNS_PER_TOPIC);
+        }
+    }
+
+    /// The runs of the topic `topic_hash`, oldest first.
+    pub fn runs(&self, topic_hash: &str) -> &[Run] {
+        self.topics.get(topic_hash).map_or(&[], Vec::as_slice)
+    }
+
+    /// When a rule last ran for the topic `topic_hash`.
+    pub fn last_run(&self, topic_hash: &str) -> Option<DateTime<Utc>> {
+        self.runs(topic_hash).last().map(|run| run.at)
+    }
+}
@@ -0,0 +1,206 @@
+//! Chat on a topic from the terminal, without the desktop application.
+//!
+//! Messages of the others are printed to stdout, one per line, and every
+//! line read from stdin is sent as a message, so the client can run on a
+//! server or be driven by a script. Everything else, like the ticket of a
+//! created topic, goes to stderr. At the end of stdin, messages still
+//! waiting for a neighbor are given some time to go out before exiting.
+use chrono::Local;
+use clap::Parser;
+use futures::StreamExt;
+use iroh_lab_core::client::{ChatMessage, ClientEvent, IrohClient};
+use iroh_lab_core::commands::{self, SlashCommand};
+use iroh_lab_core::config::Config;
+use iroh_lab_core::delivery::DeliveryState;
+use iroh_lab_core::filter::Filters;
+use iroh_lab_core::roster::PresenceStatus;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::time::Instant;
+
+// How long messages still queued at the end of stdin may wait for a neighbor
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+// How often the queued messages are checked while flushing
+const FLUSH_CHECK_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Chat on a topic from the terminal: messages of the others go to stdout,
+/// and lines read from stdin are sent.
+#[derive(Debug, Parser)]
+#[command(name = "chat_cli")]
+struct Args {
+    /// Name to chat under, instead of the one in the settings.
+    #[arg(long)]
+    username: Option<String>,
+    /// Ticket or invite of the topic to join.
+    #[arg(long, value_name = "TICKET", required_unless_present = "create")]
+    join: Option<String>,
+    /// Name of a topic to create.
+    #[arg(long, value_name = "TOPIC", conflicts_with = "join")]
+    create: Option<String>,
+    /// Configuration file to use instead of the one of the current user.
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run(Args::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: Args) -> Result<(), String> {
+    let config = match &args.config {
+        Some(path) => Config::load_from(path)?,
+        None => Config::load()?,
+    };
+    let mut username = args
+        .username
+        .or_else(|| Some(config.username.clone()))
+        .map(|username| username.trim().to_string())
+        .filter(|username| !username.is_empty())
+        .ok_or_else(|| "No name to chat under, pass one with --username".to_string())?;
+
+    let mut events = IrohClient::subscribe(None);
+
+    let mut client = IrohClient::new();
+    client.set_relay_mode(config.relay_mode);
+    client.set_binding(config.binding);
+    client.set_filters(Filters::from_config(&config)?);
+    client.initialize_network().await?;
+
+    let topic_hash = match (args.join, args.create) {
+        (Some(ticket), _) => {
+            let (topic_name, topic_hash) = client.join_topic(ticket).await?;
+            eprintln!("Joined {}", topic_name);
+            topic_hash
+        }
+        (None, Some(topic_name)) => {
+            let (topic_name, ticket, topic_hash) = client.create_topic(topic_name).await?;
+            eprintln!("Created {}, join it with:\n{}", topic_name, ticket);
+            topic_hash
+        }
+        (None, None) => unreachable!("clap requires --join or --create"),
+    };
+    client.set_presence(username.clone(), PresenceStatus::Online);
+
+    // Messages are sent again when members catch up, and ours are printed
+    // as they are sent
+    let mut seen = HashSet::new();
+    let mut sent = Vec::new();
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let Some(line) = line.map_err(|e| format!("Failed to read stdin: {}", e))? else {
+                    break;
+                };
+                let content = match commands::parse(&line) {
+                    None => line,
+                    Some(Ok(SlashCommand::Me(action))) => format!("*{} {}*", username, action),
+                    Some(Ok(SlashCommand::Say(text))) => text,
+                    Some(Ok(SlashCommand::Nick(name))) => {
+                        username = name;
+                        client.set_presence(username.clone(), PresenceStatus::Online);
+                        continue;
+                    }
+                    Some(Ok(SlashCommand::Invite)) => {
+                        match client.create_invite() {
+                            Ok(invite) => eprintln!("{}", invite),
+                            Err(e) => eprintln!("{}", e),
+                        }
+                        continue;
+                    }
+                    Some(Ok(SlashCommand::Leave)) => break,
+                    Some(Ok(_)) => {
+                        eprintln!("Only /nick, /me, /invite and /leave work in the terminal");
+                        continue;
+                    }
+                    Some(Err(e)) => {
+                        eprintln!("{}", e);
+                        continue;
+                    }
+                };
+                if content.trim().is_empty() {
+                    continue;
+                }
+                if let Err(e) = config.message_limit.check(&content) {
+                    eprintln!("{}", e);
+                    continue;
+                }
+                let sequence = sent.len() as u64 + 1;
+                match client.send_message(username.clone(), content, sequence).await {
+                    Ok(message) => {
+                        seen.insert(message.id.clone());
+                        sent.push(message.id.clone());
+                        print_message(&message);
+                    }
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            event = events.next() => {
+                let Some(event) = event else {
+                    return Err("The client stopped".to_string());
+                };
+                if let ClientEvent::Message(message) = event {
+                    if message.topic_hash == topic_hash && seen.insert(message.id.clone()) {
+                        print_message(&message);
+                    }
+                }
+            }
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+
+    flush(&client, &topic_hash, &sent).await;
+    client.shutdown().await;
+    Ok(())
+}
+
+// Prints chat messages to stdout and what the client tells to stderr
+fn print_message(message: &ChatMessage) {
+    if message.author == "System" {
+        eprintln!("{}", message.content);
+    } else if !message.is_deleted() {
+        println!(
+            "[{}] {}: {}",
+            message.timestamp.with_timezone(&Local).format("%H:%M"),
+            message.author,
+            message.content
+        );
+    }
+}
+
+// Waits until none of the messages `sent` is queued, or the flush times out
+async fn flush(client: &IrohClient, topic_hash: &str, sent: &[String]) {
+    let deadline = Instant::now() + FLUSH_TIMEOUT;
+    loop {
+        let queued = sent
+            .iter()
+            .filter(|id| {
+                client
+                    .delivery_report(topic_hash, id)
+                    .is_some_and(|report| report.state == DeliveryState::Queued)
+            })
+            .count();
+        if queued == 0 {
+            return;
+        }
+        if Instant::now() >= deadline {
+            eprintln!(
+                "{} message(s) not sent, as no other member connected",
+                queued
+            );
+            return;
+        }
+        tokio::time::sleep(FLUSH_CHECK_INTERVAL).await;
+    }
+}
@@ -0,0 +1,175 @@
+//! Load test of the gossip integration: runs several clients in this
+//! process on one topic, has them send messages in turn at a steady rate,
+//! and reports how many arrived and how long they took.
+//!
+//! See [`iroh_lab_core::load`] for how arrivals are counted.
+use clap::Parser;
+use futures::stream::{self, StreamExt};
+use iroh::NodeId;
+use iroh_lab_core::client::{ClientEvent, IrohClient};
+use iroh_lab_core::load::LoadRun;
+use std::process::ExitCode;
+use std::time::Duration;
+use tokio::time::{Instant, MissedTickBehavior};
+
+// How often the clients are checked for neighbors while the topic forms
+const NEIGHBOR_CHECK_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Run several clients on one topic, send messages at a steady rate, and
+/// report their delivery latency and loss.
+#[derive(Debug, Parser)]
+#[command(name = "loadtest")]
+struct Args {
+    /// Number of clients, one creating the topic and the others joining it.
+    #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u16).range(2..))]
+    clients: u16,
+    /// Number of messages to send, by the clients in turn.
+    #[arg(long, default_value_t = 100)]
+    messages: usize,
+    /// Messages sent per second, by all clients together.
+    #[arg(long, default_value_t = 10.0)]
+    rate: f64,
+    /// Size of the content of each message, in bytes.
+    #[arg(long, default_value_t = 64)]
+    size: usize,
+    /// Seconds to wait for the clients to find a neighbor on the topic.
+    #[arg(long, value_name = "SECONDS", default_value_t = 30)]
+    join_timeout: u64,
+    /// Seconds to wait for the last messages to arrive after sending them.
+    #[arg(long, value_name = "SECONDS", default_value_t = 10)]
+    settle: u64,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run(Args::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: Args) -> Result<(), String> {
+    if !(args.rate > 0.0 && args.rate.is_finite()) {
+        return Err("--rate must be a positive number".to_string());
+    }
+
+    let clients = start_clients(usize::from(args.clients)).await?;
+    let topic_hash = clients[0]
+        .session()
+        .topic_hash
+        .ok_or_else(|| "The topic was not created".to_string())?;
+    // The events of each client, to tell which of them received a message
+    let mut subscriptions = Vec::new();
+    for client in &clients {
+        let receiver = client
+            .own_node_id()
+            .ok_or_else(|| "A client is not online".to_string())?;
+        subscriptions.push(
+            client
+                .events(Some(&topic_hash))
+                .map(move |event| (receiver, event)),
+        );
+    }
+    let mut events = stream::select_all(subscriptions);
+    wait_for_neighbors(
+        &clients,
+        &topic_hash,
+        Duration::from_secs(args.join_timeout),
+    )
+    .await?;
+
+    eprintln!(
+        "Sending {} message(s) of {} byte(s) at {} per second",
+        args.messages, args.size, args.rate
+    );
+    let content = "x".repeat(args.size);
+    let mut load = LoadRun::new(clients.len());
+    let mut ticks = tokio::time::interval(Duration::from_secs_f64(1.0 / args.rate));
+    // Sending late is part of the load, not a reason to send in bursts
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let started = Instant::now();
+    let mut sent = 0;
+    while sent < args.messages {
+        tokio::select! {
+            _ = ticks.tick() => {
+                let index = sent % clients.len();
+                sent += 1;
+                let sent_at = Instant::now().into_std();
+                let message = clients[index]
+                    .send_message(format!("load-{}", index), content.clone(), sent as u64)
+                    .await?;
+                load.sent(message.id, sent_at);
+            }
+            Some((receiver, event)) = events.next() => receive(&mut load, receiver, event),
+        }
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(args.settle);
+    while !load.complete() {
+        tokio::select! {
+            Some((receiver, event)) = events.next() => receive(&mut load, receiver, event),
+            _ = tokio::time::sleep_until(deadline) => break,
+        }
+    }
+
+    println!("Clients:     {}", clients.len());
+    println!("{}", load.summary(started.elapsed()));
+    Ok(())
+}
+
+// Starts the clients, the first creating the topic and the others joining it
+async fn start_clients(count: usize) -> Result<Vec<IrohClient>, String> {
+    let creator = IrohClient::new();
+    creator.initialize_network().await?;
+    let (_, ticket, _) = creator.create_topic("loadtest".to_string()).await?;
+    let mut clients = vec![creator];
+    while clients.len() < count {
+        let client = IrohClient::new();
+        client.initialize_network().await?;
+        client.join_topic(ticket.clone()).await?;
+        clients.push(client);
+        eprintln!("Started {} of {} client(s)", clients.len(), count);
+    }
+    Ok(clients)
+}
+
+// Waits until every client has a gossip neighbor, so the first messages
+// are not held back while the topic forms
+async fn wait_for_neighbors(
+    clients: &[IrohClient],
+    topic_hash: &str,
+    timeout: Duration,
+) -> Result<(), String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let waiting = clients
+            .iter()
+            .filter(|client| {
+                client
+                    .traffic_stats(topic_hash)
+                    .is_none_or(|stats| stats.peers == 0)
+            })
+            .count();
+        if waiting == 0 {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "{} of {} client(s) found no neighbor within {} s",
+                waiting,
+                clients.len(),
+                timeout.as_secs()
+            ));
+        }
+        tokio::time::sleep(NEIGHBOR_CHECK_INTERVAL).await;
+    }
+}
+
+fn receive(load: &mut LoadRun, receiver: NodeId, event: ClientEvent) {
+    if let ClientEvent::Message(message) = event {
+        load.received(receiver, &message.id, Instant::now().into_std());
+    }
+}
@@ -0,0 +1,37 @@
+//! Checks that this machine can take part in the chat network, for support
+//! tickets.
+//!
+//! Binds a UDP socket and an endpoint, reaches a relay, looks up our own node
+//! through discovery, finds out how peers outside the local network reach
+//! us, and sends a message round a throwaway topic between two nodes. The
+//! report is plain text to paste into a ticket, and the exit code is 1 when
+//! a check failed.
+use iroh_lab_core::diagnostics::{self, CheckStatus};
+use std::process::ExitCode;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    println!("iroh-lab {} connectivity check", env!("CARGO_PKG_VERSION"));
+    println!("This takes up to a minute and a half…\n");
+
+    let report = diagnostics::run_network().await;
+    for check in &report.checks {
+        let status = match check.status {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => "FAIL",
+        };
+        println!("[{}] {:<14} {}", status, check.name, check.detail);
+        if let Some(hint) = check.hint {
+            println!("       hint: {}", hint);
+        }
+    }
+
+    if report.is_healthy() {
+        println!("\nNo problems found.");
+        ExitCode::SUCCESS
+    } else {
+        println!("\nSome checks failed, see the hints above.");
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,263 @@
+//! Content-addressed blobs, such as avatar images, shared between nodes.
+//!
+//! Blobs are named by the SHA-256 hash of their content and cached on disk,
+//! e.g. below `~/.config/iroh-lab/blobs` on Linux. A node announcing a blob
+//! serves it over the [`BLOBS_ALPN`] protocol: the requester sends the hash,
+//! and the node answers with the content, or closes the stream if it does
+//! not have it. Fetched content is only kept if it matches its hash, so a
+//! peer can never substitute a blob.
+//!
+//! Storing and transferring blobs needs the `blobs` feature. Without it, only
+//! [`BlobHash`] is left, to name the blobs in payloads and the configuration.
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "blobs")]
+use {
+    crate::config::Config,
+    futures::future::BoxFuture,
+    iroh::endpoint::Connecting,
+    iroh::protocol::ProtocolHandler,
+    iroh::{Endpoint, NodeId},
+    std::io::ErrorKind,
+    std::path::{Path, PathBuf},
+    std::time::Duration,
+    tracing::{debug, warn},
+};
+
+/// ALPN of the protocol serving blobs.
+#[cfg(feature = "blobs")]
+pub const BLOBS_ALPN: &[u8] = b"iroh-lab/blobs/0";
+
+/// Largest blob stored or fetched. Avatars are small images.
+pub const MAX_BLOB_SIZE: usize = 256 * 1024;
+
+/// How long fetching a blob from a peer may take.
+#[cfg(feature = "blobs")]
+pub const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Directory below the configuration directory holding the blobs
+#[cfg(feature = "blobs")]
+const BLOBS_DIR: &str = "blobs";
+
+/// The SHA-256 hash naming a blob. Written as hex in human readable
+/// formats, and as bytes on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlobHash([u8; 32]);
+
+impl Serialize for BlobHash {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.to_string())
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for BlobHash {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            String::deserialize(deserializer)?
+                .parse()
+                .map_err(serde::de::Error::custom)
+        } else {
+            <[u8; 32]>::deserialize(deserializer).map(Self)
+        }
+    }
+}
+
+impl BlobHash {
+    /// The hash of `content`.
+    pub fn of(content: &[u8]) -> Self {
+        Self(Sha256::digest(content).into())
+    }
+}
+
+impl fmt::Display for BlobHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&data_encoding::HEXLOWER.encode(&self.0))
+    }
+}
+
+impl FromStr for BlobHash {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = data_encoding::HEXLOWER
+            .decode(s.as_bytes())
+            .map_err(|e| format!("Invalid blob hash: {}", e))?;
+        let bytes = bytes
+            .try_into()
+            .map_err(|_| "Blob hash should be 32 bytes".to_string())?;
+        Ok(Self(bytes))
+    }
+}
+
+/// The blobs cached on this device.
+#[cfg(feature = "blobs")]
+#[derive(Debug, Clone)]
+pub struct BlobStore {
+    dir: PathBuf,
+}
+
+#[cfg(feature = "blobs")]
+impl BlobStore {
+    /// Directory holding the blobs of the current user, if the platform has
+    /// a configuration directory.
+    pub fn default_dir() -> Option<PathBuf> {
+        Config::directory().map(|dir| dir.join(BLOBS_DIR))
+    }
+
+    /// A store keeping its blobs in `dir`, which is created when needed.
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    /// Stores `content`, returning its hash.
+    pub fn put(&self, content: &[u8]) -> Result<BlobHash, String> {
+        if content.len() > MAX_BLOB_SIZE {
+            return Err(format!(
+                "Blob of {} bytes exceeds the limit of {} bytes",
+                content.len(),
+                MAX_BLOB_SIZE
+            ));
+        }
+        let hash = BlobHash::of(content);
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create {}: {}", self.dir.display(), e))?;
+        // Write next to the blob first, so a crash never leaves half a blob
+        let path = self.path(&hash);
+        let partial = path.with_extension("partial");
+        std::fs::write(&partial, content)
+            .and_then(|_| std::fs::rename(&partial, &path))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(hash)
+    }
+
+    /// The content of the blob `hash`, if it is stored and intact.
+    pub fn get(&self, hash: &BlobHash) -> Result<Option<Vec<u8>>, String> {
+        let path = self.path(hash);
+        let content = match std::fs::read(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        if BlobHash::of(&content) != *hash {
+            warn!(%hash, "Removing corrupted blob");
+            let _ = std::fs::remove_file(&path);
+            return Ok(None);
+        }
+        Ok(Some(content))
+    }
+
+    /// Whether the blob `hash` is stored.
+    pub fn contains(&self, hash: &BlobHash) -> bool {
+        self.path(hash).is_file()
+    }
+
+    fn path(&self, hash: &BlobHash) -> PathBuf {
+        self.dir.join(hash.to_string())
+    }
+}
+
+/// Serves the blobs of a store to other nodes.
+#[cfg(feature = "blobs")]
+#[derive(Debug, Clone)]
+pub struct BlobsProtocol {
+    store: BlobStore,
+}
+
+#[cfg(feature = "blobs")]
+impl BlobsProtocol {
+    pub fn new(store: BlobStore) -> Self {
+        Self { store }
+    }
+}
+
+#[cfg(feature = "blobs")]
+impl ProtocolHandler for BlobsProtocol {
+    fn accept(&self, connecting: Connecting) -> BoxFuture<'static, anyhow::Result<()>> {
+        let store = self.store.clone();
+        Box::pin(async move {
+            let connection = connecting.await?;
+            let (mut send, mut recv) = connection.accept_bi().await?;
+            let request = recv.read_to_end(32).await?;
+            let hash = BlobHash(
+                request
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("Blob request should be a 32 byte hash"))?,
+            );
+            match store.get(&hash).map_err(anyhow::Error::msg)? {
+                Some(content) => {
+                    debug!(%hash, "Serving blob");
+                    send.write_all(&content).await?;
+                }
+                None => debug!(%hash, "Requested blob not found"),
+            }
+            send.finish()?;
+            // Let the requester read everything before the connection goes
+            connection.closed().await;
+            Ok(())
+        })
+    }
+}
+
+/// Fetches the blob `hash` from `node_id` into `store`, unless it is stored
+/// already, and returns its content.
+#[cfg(feature = "blobs")]
+pub async fn fetch(
+    endpoint: &Endpoint,
+    store: &BlobStore,
+    node_id: NodeId,
+    hash: BlobHash,
+) -> Result<Vec<u8>, String> {
+    if let Some(content) = store.get(&hash)? {
+        return Ok(content);
+    }
+
+    let request = async {
+        let connection = endpoint
+            .connect(node_id, BLOBS_ALPN)
+            .await
+            .map_err(|e| format!("Failed to connect to {}: {}", node_id.fmt_short(), e))?;
+        let (mut send, mut recv) = connection
+            .open_bi()
+            .await
+            .map_err(|e| format!("Failed to request blob: {}", e))?;
+        send.write_all(&hash.0)
+            .await
+            .map_err(|e| format!("Failed to request blob: {}", e))?;
+        send.finish()
+            .map_err(|e| format!("Failed to request blob: {}", e))?;
+        let content = recv
+            .read_to_end(MAX_BLOB_SIZE)
+            .await
+            .map_err(|e| format!("Failed to receive blob: {}", e))?;
+        connection.close(0u32.into(), b"done");
+        Ok::<_, String>(content)
+    };
+    let content = tokio::time::timeout(FETCH_TIMEOUT, request)
+        .await
+        .map_err(|_| format!("Fetching blob {} timed out", hash))??;
+
+    if content.is_empty() {
+        return Err(format!(
+            "{} does not have blob {}",
+            node_id.fmt_short(),
+            hash
+        ));
+    }
+    if BlobHash::of(&content) != hash {
+        return Err(format!(
+            "{} sent content not matching blob {}",
+            node_id.fmt_short(),
+            hash
+        ));
+    }
+    store.put(&content)?;
+    Ok(content)
+}
@@ -0,0 +1,161 @@
+//! Folders bridged to topics, a shared drive over the topic.
+//!
+//! A topic can be bridged to a local folder. Files dropped into the folder
+//! are posted to the topic as [attachments](crate::client::Attachment),
+//! sealed with the topic key and served as [blobs](crate::blobs), and
+//! attachments posted by other members are saved into the folder. The
+//! content hash of every file posted or saved is recorded next to the
+//! configuration, e.g. `~/.config/iroh-lab/bridges.json` on Linux, so files
+//! are only posted again once they change, also after a restart. Deleting a
+//! file is not mirrored, and a file posted by several members at once ends
+//! up with the content saved last.
+use crate::blobs::{BlobHash, MAX_BLOB_SIZE};
+use crate::config::Config;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Largest file posted, leaving room in the blob for the seal.
+pub const MAX_FILE_SIZE: usize = MAX_BLOB_SIZE - 1024;
+
+// Name of the file holding the hashes of the bridged files
+const BRIDGES_FILE: &str = "bridges.json";
+
+/// A new or changed file found in a bridged folder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DroppedFile {
+    pub name: String,
+    pub content: Vec<u8>,
+}
+
+/// The file name an attachment is saved as, if `name` is a plain file name.
+/// Names reaching into other folders and hidden files are refused, so a
+/// member can never write outside the bridged folder.
+pub fn safe_name(name: &str) -> Option<&str> {
+    let plain = !name.is_empty()
+        && !name.starts_with('.')
+        && !name.contains(['/', '\\', ':', '\0'])
+        && Path::new(name).file_name().and_then(|file| file.to_str()) == Some(name);
+    plain.then_some(name)
+}
+
+/// The files posted to or saved from every bridged topic.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bridges {
+    // Content hashes by file name, by topic hash
+    topics: BTreeMap<String, BTreeMap<String, BlobHash>>,
+}
+
+impl Bridges {
+    /// Location of the file of the current user, if the platform has a
+    /// configuration directory.
+    pub fn default_path() -> Option<PathBuf> {
+        Config::directory().map(|dir| dir.join(BRIDGES_FILE))
+    }
+
+    /// Loads the bridged files from `path`, or none if the file does not
+    /// exist.
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    /// Saves the bridged files to `path`, creating its directory if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to encode bridges: {}", e))?;
+        std::fs::write(path, contents)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// The files of `folder` that were not posted to or saved from the topic
+    /// `topic_hash` with their current content, by name. Hidden files,
+    /// folders and files larger than [`MAX_FILE_SIZE`] are skipped.
+    pub fn scan(&self, topic_hash: &str, folder: &Path) -> Result<Vec<DroppedFile>, String> {
+        let entries = std::fs::read_dir(folder)
+            .map_err(|e| format!("Failed to read {}: {}", folder.display(), e))?;
+        let known = self.topics.get(topic_hash);
+        let mut dropped = Vec::new();
+        for entry in entries.flatten() {
+            let Some(name) = entry
+                .file_name()
+                .to_str()
+                .and_then(safe_name)
+                .map(String::from)
+            else {
+                continue;
+            };
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if !metadata.is_file() || metadata.len() > MAX_FILE_SIZE as u64 {
+                continue;
+            }
+            // Files still being written are picked up on the next scan
+            let Ok(content) = std::fs::read(entry.path()) else {
+                continue;
+            };
+            if known.and_then(|files| files.get(&name)) != Some(&BlobHash::of(&content)) {
+                dropped.push(DroppedFile { name, content });
+            }
+        }
+        dropped.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(dropped)
+    }
+
+    /// Records that the file `name` of the topic `topic_hash` holds
+    /// `content`, so it is not posted again until it changes.
+    pub fn record(&mut self, topic_hash: &str, name: &str, content: &[u8]) {
+        self.topics
+            .entry(topic_hash.to_string())
+            .or_default()
+            .insert(name.to_string(), BlobHash::of(content));
+    }
+
+    /// Forgets the file `name` of the topic `topic_hash`, so it is posted
+    /// again on the next scan, e.g. as posting it failed.
+    pub fn forget(&mut self, topic_hash: &str, name: &str) {
+        if let Some(files) = self.topics.get_mut(topic_hash) {
+            files.remove(name);
+        }
+    }
+
+    /// Saves an attachment posted to the topic `topic_hash` into `folder`,
+    /// returning where it was saved. Refuses names that are not plain file
+    /// names.
+    pub fn save(
+        &mut self,
+        topic_hash: &str,
+        folder: &Path,
+        name: &str,
+        content: &[u8],
+    ) -> Result<PathBuf, String> {
+        let name = safe_name(name).ok_or_else(|| format!("Refusing to save \"{}\"", name))?;
+        std::fs::create_dir_all(folder)
+            .map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+        // Recorded first, so the file is not posted back while it is written
+        self.record(topic_hash, name, content);
+        // Written next to the file first, so a scan never reads half of it
+        let path = folder.join(name);
+        let partial = folder.join(format!(".{}.partial", name));
+        std::fs::write(&partial, content)
+            .and_then(|_| std::fs::rename(&partial, &path))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+
+    /// How many files of the topic `topic_hash` were posted or saved.
+    pub fn count(&self, topic_hash: &str) -> usize {
+        self.topics.get(topic_hash).map_or(0, BTreeMap::len)
+    }
+}
@@ -0,0 +1,324 @@
+//! Calendar events shared on a topic, and who is going.
+//!
+//! An event is announced by a chat message describing it, so it is readable
+//! by any member, followed by a [`CalendarEvent`] payload from the same node
+//! attaching the details to that message. Members answer with [`Rsvp`]
+//! payloads, and the latest answer of each member counts. Events and answers
+//! are stored next to the configuration, e.g.
+//! `~/.config/iroh-lab/calendar.json` on Linux. Each event can be exported
+//! as an iCalendar file to add it to a calendar, and the upcoming events of
+//! a topic as a feed calendar applications subscribe to.
+use crate::client::{CalendarEvent, ChatMessage, Rsvp};
+use crate::config::Config;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use iroh::NodeId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// How event times are typed.
+pub const TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+// Name of the file holding the events
+const CALENDAR_FILE: &str = "calendar.json";
+// Longest line of an iCalendar file, in bytes
+const ICS_LINE_LIMIT: usize = 75;
+
+/// A member's answer to an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RsvpResponse {
+    Accept,
+    Decline,
+}
+
+impl fmt::Display for RsvpResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Accept => "Going",
+            Self::Decline => "Not going",
+        })
+    }
+}
+
+/// Parses a time typed as [`TIME_FORMAT`], e.g. `2024-05-01 18:30`.
+pub fn parse_time(text: &str) -> Result<NaiveDateTime, String> {
+    NaiveDateTime::parse_from_str(text.trim(), TIME_FORMAT)
+        .map_err(|_| format!("\"{}\" is no time like 2024-05-01 18:30", text.trim()))
+}
+
+/// Checks that an event has a title and ends after it starts.
+pub fn validate(
+    title: &str,
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+) -> Result<(), String> {
+    if title.trim().is_empty() {
+        return Err("Give the event a title".to_string());
+    }
+    if ends_at <= starts_at {
+        return Err("The event must end after it starts".to_string());
+    }
+    Ok(())
+}
+
+/// The text of the chat message announcing an event.
+pub fn announcement(
+    title: &str,
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+    location: &str,
+) -> String {
+    let mut text = format!(
+        "📅 {}, {} to {} UTC",
+        title.trim(),
+        starts_at.format(TIME_FORMAT),
+        ends_at.format(TIME_FORMAT)
+    );
+    if !location.trim().is_empty() {
+        text.push_str(&format!(" at {}", location.trim()));
+    }
+    text
+}
+
+/// The iCalendar file adding `event` to a calendar, created at `now`.
+pub fn to_ics(event: &CalendarEvent, now: DateTime<Utc>) -> String {
+    let mut lines = calendar_start();
+    push_event(&mut lines, event, now);
+    calendar_end(lines)
+}
+
+/// The iCalendar file holding `events` as the calendar `name`, created at
+/// `now`. Calendar applications subscribing to it update the events they
+/// show whenever it is written again.
+pub fn to_ics_feed<'e>(
+    name: &str,
+    events: impl IntoIterator<Item = &'e CalendarEvent>,
+    now: DateTime<Utc>,
+) -> String {
+    let mut lines = calendar_start();
+    lines.push(format!("X-WR-CALNAME:{}", ics_text(name)));
+    for event in events {
+        push_event(&mut lines, event, now);
+    }
+    calendar_end(lines)
+}
+
+// The lines opening an iCalendar file
+fn calendar_start() -> Vec<String> {
+    vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//iroh-lab//Chat//EN".to_string(),
+    ]
+}
+
+// Adds the lines describing `event`, stamped `now`
+fn push_event(lines: &mut Vec<String>, event: &CalendarEvent, now: DateTime<Utc>) {
+    lines.extend([
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@iroh-lab", event.message_id),
+        format!("DTSTAMP:{}", ics_time(now)),
+        format!("DTSTART:{}", ics_time(event.starts_at)),
+        format!("DTEND:{}", ics_time(event.ends_at)),
+        format!("SUMMARY:{}", ics_text(&event.title)),
+    ]);
+    if !event.location.trim().is_empty() {
+        lines.push(format!("LOCATION:{}", ics_text(&event.location)));
+    }
+    lines.push("END:VEVENT".to_string());
+}
+
+// Closes the calendar of `lines`, folding them and ending each in CRLF
+fn calendar_end(mut lines: Vec<String>) -> String {
+    lines.push("END:VCALENDAR".to_string());
+    lines
+        .iter()
+        .map(|line| fold(line))
+        .collect::<Vec<_>>()
+        .join("\r\n")
+        + "\r\n"
+}
+
+// A time in the UTC form of iCalendar
+fn ics_time(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+// Escapes the characters iCalendar text values give a meaning
+fn ics_text(text: &str) -> String {
+    text.trim()
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\n', '\r'], "\\n")
+}
+
+// Breaks a line longer than iCalendar allows into continuation lines, which
+// start with a space, without splitting a character
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > ICS_LINE_LIMIT {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+// An event with the node that announced it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Scheduled {
+    event: CalendarEvent,
+    organizer: NodeId,
+}
+
+// The latest answer of a member, with its Lamport timestamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Answer {
+    response: RsvpResponse,
+    lamport: u64,
+}
+
+/// The events of every topic, and the answers of their members.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Calendar {
+    // Events by the id of the message announcing them, by topic hash. Any
+    // node may attach an event to a message, so all are kept until the
+    // sender of the message is known.
+    events: BTreeMap<String, BTreeMap<String, Vec<Scheduled>>>,
+    // Answers by node id as text, by message id, by topic hash. Kept even
+    // before the event itself arrives.
+    answers: BTreeMap<String, BTreeMap<String, BTreeMap<String, Answer>>>,
+}
+
+impl Calendar {
+    /// Location of the file of the current user, if the platform has a
+    /// configuration directory.
+    pub fn default_path() -> Option<PathBuf> {
+        Config::directory().map(|dir| dir.join(CALENDAR_FILE))
+    }
+
+    /// Loads the events from `path`, or none if the file does not exist.
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    /// Saves the events to `path`, creating its directory if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to encode calendar: {}", e))?;
+        std::fs::write(path, contents)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Adds an event announced by `organizer`, returning whether it is new.
+    /// Only the first event a node attaches to a message counts.
+    pub fn add(&mut self, event: CalendarEvent, organizer: NodeId) -> bool {
+        let events = self
+            .events
+            .entry(event.topic_hash.clone())
+            .or_default()
+            .entry(event.message_id.clone())
+            .or_default();
+        if events
+            .iter()
+            .any(|scheduled| scheduled.organizer == organizer)
+        {
+            return false;
+        }
+        events.push(Scheduled { event, organizer });
+        true
+    }
+
+    /// The event announced by `message`, if the node that sent the message
+    /// also sent the event.
+    pub fn event_of(&self, message: &ChatMessage) -> Option<&CalendarEvent> {
+        if message.is_deleted() {
+            return None;
+        }
+        self.events
+            .get(&message.topic_hash)?
+            .get(&message.id)?
+            .iter()
+            .find(|scheduled| Some(scheduled.organizer) == message.verified_sender)
+            .map(|scheduled| &scheduled.event)
+    }
+
+    /// The events announced by `messages` that did not end by `now`, the
+    /// earliest first.
+    pub fn upcoming<'m>(
+        &self,
+        messages: impl IntoIterator<Item = &'m ChatMessage>,
+        now: DateTime<Utc>,
+    ) -> Vec<&CalendarEvent> {
+        let mut events: Vec<_> = messages
+            .into_iter()
+            .filter_map(|message| self.event_of(message))
+            .filter(|event| event.ends_at > now)
+            .collect();
+        events.sort_by_key(|event| event.starts_at);
+        events
+    }
+
+    /// Records the answer of `sender`, returning whether it changed. Answers
+    /// older than the last one of the same member are ignored.
+    pub fn respond(&mut self, rsvp: &Rsvp, sender: NodeId) -> bool {
+        let answers = self
+            .answers
+            .entry(rsvp.topic_hash.clone())
+            .or_default()
+            .entry(rsvp.message_id.clone())
+            .or_default();
+        let answer = Answer {
+            response: rsvp.response,
+            lamport: rsvp.lamport,
+        };
+        match answers.get(&sender.to_string()) {
+            Some(current) if current.lamport >= answer.lamport => false,
+            _ => answers.insert(sender.to_string(), answer) != Some(answer),
+        }
+    }
+
+    /// The answer of `node_id` to the event of the message `message_id`.
+    pub fn response_of(
+        &self,
+        topic_hash: &str,
+        message_id: &str,
+        node_id: NodeId,
+    ) -> Option<RsvpResponse> {
+        self.answers
+            .get(topic_hash)?
+            .get(message_id)?
+            .get(&node_id.to_string())
+            .map(|answer| answer.response)
+    }
+
+    /// The members who answered the event of the message `message_id`, with
+    /// their answers.
+    pub fn responses(&self, topic_hash: &str, message_id: &str) -> Vec<(NodeId, RsvpResponse)> {
+        self.answers
+            .get(topic_hash)
+            .and_then(|messages| messages.get(message_id))
+            .into_iter()
+            .flatten()
+            .filter_map(|(node_id, answer)| Some((node_id.parse().ok()?, answer.response)))
+            .collect()
+    }
+}
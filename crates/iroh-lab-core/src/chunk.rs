@@ -0,0 +1,230 @@
+//! Splitting of large frames into fragments that fit into a gossip message.
+//!
+//! Gossip rejects messages above a fixed size, so long pastes would never
+//! reach other peers. Frames above [`MAX_FRAGMENT_DATA`] are split into
+//! numbered fragments sharing a random id, which the receiving side collects
+//! in a [`Reassembler`] until the frame is complete. Sets that stay incomplete
+//! for longer than the reassembly timeout are dropped.
+//!
+//! Fragments are not authenticated before the frame is complete, so anyone
+//! on the topic can start sets that never complete. A reassembler holds at
+//! most [`MAX_PENDING_FRAMES`] sets and [`MAX_BUFFERED_BYTES`] of fragments,
+//! dropping the oldest sets to make room for new ones.
+//!
+//! Fragments start with [`FRAGMENT_TAG`], which is never the first byte of a
+//! frame, so frames small enough to be sent whole are sent unchanged.
+use iroh_gossip::proto::topic::DEFAULT_MAX_MESSAGE_SIZE;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// First byte of every fragment.
+pub const FRAGMENT_TAG: u8 = 0xff;
+
+/// Largest part of a frame carried by a single fragment. The rest of the
+/// gossip message size is left for the fragment and gossip headers.
+pub const MAX_FRAGMENT_DATA: usize = DEFAULT_MAX_MESSAGE_SIZE - 1024;
+
+/// Most fragments a single frame may be split into.
+pub const MAX_FRAGMENTS: u16 = 256;
+
+/// Largest frame that can be sent, split into fragments.
+pub const MAX_FRAME_SIZE: usize = MAX_FRAGMENTS as usize * MAX_FRAGMENT_DATA;
+
+/// How long to wait for the missing fragments of a frame.
+pub const REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Most frames waiting for fragments at once.
+pub const MAX_PENDING_FRAMES: usize = 32;
+
+/// Most bytes of fragments held for frames waiting for the rest, room for a
+/// few frames of the largest size.
+pub const MAX_BUFFERED_BYTES: usize = 4 * MAX_FRAME_SIZE;
+
+// A part of a frame too large to be sent whole
+#[derive(Serialize, Deserialize)]
+struct Fragment {
+    tag: u8,
+    frame_id: [u8; 16],
+    index: u16,
+    count: u16,
+    data: Vec<u8>,
+}
+
+/// Splits an encoded frame into fragments small enough for gossip.
+///
+/// Frames that fit into a single gossip message are returned unchanged.
+pub fn split(frame: Vec<u8>) -> Result<Vec<Vec<u8>>, String> {
+    if frame.len() <= MAX_FRAGMENT_DATA {
+        return Ok(vec![frame]);
+    }
+
+    let count = frame.len().div_ceil(MAX_FRAGMENT_DATA);
+    if count > MAX_FRAGMENTS as usize {
+        return Err(format!(
+            "Message too large to send ({} bytes, at most {} bytes)",
+            frame.len(),
+            MAX_FRAME_SIZE
+        ));
+    }
+
+    let frame_id = rand::random();
+    frame
+        .chunks(MAX_FRAGMENT_DATA)
+        .enumerate()
+        .map(|(index, data)| {
+            let fragment = Fragment {
+                tag: FRAGMENT_TAG,
+                frame_id,
+                index: index as u16,
+                count: count as u16,
+                data: data.to_vec(),
+            };
+            postcard::to_stdvec(&fragment).map_err(|e| format!("Failed to encode fragment: {}", e))
+        })
+        .collect()
+}
+
+/// Collects fragments until the frames they belong to are complete.
+#[derive(Debug)]
+pub struct Reassembler {
+    timeout: Duration,
+    partial: HashMap<[u8; 16], PartialFrame>,
+    // Bytes of fragments held in `partial`
+    buffered: usize,
+}
+
+// The fragments of a frame received so far
+#[derive(Debug)]
+struct PartialFrame {
+    fragments: Vec<Option<Vec<u8>>>,
+    missing: usize,
+    first_seen: Instant,
+    // Bytes of the fragments received
+    buffered: usize,
+}
+
+impl Default for Reassembler {
+    fn default() -> Self {
+        Self::new(REASSEMBLY_TIMEOUT)
+    }
+}
+
+impl Reassembler {
+    /// Creates a reassembler dropping sets incomplete for longer than `timeout`.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            partial: HashMap::new(),
+            buffered: 0,
+        }
+    }
+
+    /// Accepts a gossip message received at `now`.
+    ///
+    /// Returns the complete frame once all of its fragments have arrived, and
+    /// `None` while fragments are still missing. Messages that are not
+    /// fragments are returned as they are. The oldest incomplete frames are
+    /// dropped when there are too many, or they take up too much memory.
+    pub fn accept(&mut self, bytes: &[u8], now: Instant) -> Result<Option<Vec<u8>>, String> {
+        if bytes.first() != Some(&FRAGMENT_TAG) {
+            return Ok(Some(bytes.to_vec()));
+        }
+
+        let fragment: Fragment =
+            postcard::from_bytes(bytes).map_err(|e| format!("Failed to decode fragment: {}", e))?;
+        if fragment.count == 0 || fragment.count > MAX_FRAGMENTS {
+            return Err(format!(
+                "Fragment claims an invalid count of {}",
+                fragment.count
+            ));
+        }
+        if fragment.index >= fragment.count {
+            return Err(format!(
+                "Fragment {} is out of range for {} fragments",
+                fragment.index, fragment.count
+            ));
+        }
+        if fragment.data.len() > MAX_FRAGMENT_DATA {
+            return Err(format!(
+                "Fragment carries {} bytes, at most {} fit",
+                fragment.data.len(),
+                MAX_FRAGMENT_DATA
+            ));
+        }
+
+        let frame_id = fragment.frame_id;
+        if !self.partial.contains_key(&frame_id) {
+            while self.partial.len() >= MAX_PENDING_FRAMES && self.evict_oldest(&frame_id) {}
+        }
+        let partial = self
+            .partial
+            .entry(frame_id)
+            .or_insert_with(|| PartialFrame {
+                fragments: vec![None; fragment.count as usize],
+                missing: fragment.count as usize,
+                first_seen: now,
+                buffered: 0,
+            });
+        if partial.fragments.len() != fragment.count as usize {
+            self.remove(&frame_id);
+            return Err("Fragments of a message disagree on their count".to_string());
+        }
+
+        // Gossip may deliver a fragment more than once
+        let slot = &mut partial.fragments[fragment.index as usize];
+        if slot.is_none() {
+            partial.buffered += fragment.data.len();
+            self.buffered += fragment.data.len();
+            *slot = Some(fragment.data);
+            partial.missing -= 1;
+        }
+        if partial.missing > 0 {
+            while self.buffered > MAX_BUFFERED_BYTES && self.evict_oldest(&frame_id) {}
+            return Ok(None);
+        }
+
+        let partial = self.remove(&frame_id).expect("complete frame is tracked");
+        Ok(Some(
+            partial.fragments.into_iter().flatten().flatten().collect(),
+        ))
+    }
+
+    /// Drops the sets still incomplete after the timeout, returning how many
+    /// frames were lost.
+    pub fn expire(&mut self, now: Instant) -> usize {
+        let before = self.partial.len();
+        self.partial
+            .retain(|_, partial| now.duration_since(partial.first_seen) < self.timeout);
+        self.buffered = self.partial.values().map(|partial| partial.buffered).sum();
+        before - self.partial.len()
+    }
+
+    /// Number of frames still waiting for fragments.
+    pub fn pending(&self) -> usize {
+        self.partial.len()
+    }
+
+    /// Bytes of fragments held for frames still waiting for the rest.
+    pub fn buffered(&self) -> usize {
+        self.buffered
+    }
+
+    fn remove(&mut self, frame_id: &[u8; 16]) -> Option<PartialFrame> {
+        let partial = self.partial.remove(frame_id)?;
+        self.buffered -= partial.buffered;
+        Some(partial)
+    }
+
+    // Drops the incomplete frame seen first, other than `keep`, returning
+    // whether there was one
+    fn evict_oldest(&mut self, keep: &[u8; 16]) -> bool {
+        let oldest = self
+            .partial
+            .iter()
+            .filter(|(frame_id, _)| *frame_id != keep)
+            .min_by_key(|(_, partial)| partial.first_seen)
+            .map(|(frame_id, _)| *frame_id);
+        oldest.and_then(|frame_id| self.remove(&frame_id)).is_some()
+    }
+}
@@ -0,0 +1,2627 @@
+//! Client for the iroh network.
+//!
+//! This is a client for the iroh network that allows you to send messages to the network and receive messages from the network.
+//!
+//! It also allows you to create topics and join topics
+//!
+//! It is designed to be used in a multi-process environment, where the main process is the client and the other processes are the nodes on the network.
+//!
+//! # Documentation
+//!
+//! See the Iroh documentation here. Note that it often out of sync with the actual code, so the examples are not always correct.
+//! - <https://www.iroh.computer/docs>
+//! - Specifically see the relevant "gossip" example: <https://www.iroh.computer/docs/examples/gossip-chat>
+//!
+//! The documentation for our specific version of `iroh` is here:
+//! <https://docs.rs/iroh/0.33.0/iroh/>
+//!
+//! The documentation for our specific version of `iroh-gossip` is here:
+//! https://docs.rs/iroh-gossip/0.33.0/iroh_gossip/
+//!
+use crate::access::Access;
+use crate::blobs::BlobHash;
+#[cfg(feature = "blobs")]
+use crate::blobs::{self, BlobStore, BlobsProtocol, BLOBS_ALPN};
+use crate::calendar::{self, RsvpResponse};
+use crate::chunk::{self, Reassembler};
+use crate::clock::LamportClock;
+use crate::config::{Binding, RelayMode};
+use crate::crypto::TopicKey;
+use crate::delivery::{Deliveries, DeliveryReport, DeliveryState};
+use crate::diagnostics::{Connectivity, NodeStatus};
+use crate::filter::Filters;
+use crate::invite::{self, Invite, InviteProtocol, Invites, INVITE_ALPN, INVITE_PREFIX};
+use crate::lan::LanDiscovery;
+use crate::liveness::{Liveness, TopicActivity, HEARTBEAT_INTERVAL};
+use crate::metrics::{Traffic, TrafficPath, TrafficStats};
+use crate::ping::{PingProtocol, Pinger, PING_ALPN, PING_INTERVAL};
+use crate::profile::UserProfile;
+use crate::reconnect::{ReconnectState, Reconnector};
+use crate::reputation::{Offense, Replays, Reputation};
+use crate::roster::{PresenceStatus, PRESENCE_INTERVAL};
+#[cfg(feature = "blobs")]
+use crate::snapshot;
+use crate::sync::{self, Outbox};
+use crate::tasks::TaskChange;
+use crate::ticket::Ticket;
+use crate::wire::{self, Payload};
+use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt};
+use iroh::endpoint::ConnectionType;
+use iroh::{protocol::Router, Endpoint, NodeAddr, NodeId, SecretKey};
+use iroh_gossip::net::{Event, Gossip, GossipEvent, GossipReceiver, GossipSender, GOSSIP_ALPN};
+use iroh_gossip::proto::TopicId;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex as StdMutex, OnceLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, oneshot, watch};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, instrument, trace, warn};
+use uuid::Uuid;
+
+// Message structure for chat
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub id: String,
+    pub author: String,
+    pub content: String,
+    pub timestamp: DateTime<Utc>,
+    pub topic_hash: String,
+    pub sequence: u64,
+    // Lamport timestamp ordering the message among those of all peers
+    #[serde(default)]
+    pub lamport: u64,
+    // Node whose signature on the message has been verified. Set locally on
+    // receipt, never sent over the wire.
+    #[serde(skip)]
+    pub verified_sender: Option<NodeId>,
+    // When the content was last replaced by an edit. Set locally when the
+    // edit is applied, never sent over the wire.
+    #[serde(skip)]
+    pub edited_at: Option<DateTime<Utc>>,
+    // When the message was deleted by its author. Set locally when the
+    // delete is applied, never sent over the wire.
+    #[serde(skip)]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+/// Content shown in place of a deleted message.
+pub const DELETED_CONTENT: &str = "Message deleted";
+
+/// Replaces the content of an earlier message.
+///
+/// Edits are only applied to messages verifiably sent by the same node as
+/// the edit itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageEdit {
+    pub target_id: String,
+    pub topic_hash: String,
+    pub new_content: String,
+    pub edited_at: DateTime<Utc>,
+}
+
+/// Deletes an earlier message.
+///
+/// Like edits, deletes are only applied to messages verifiably sent by the
+/// same node as the delete itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageDelete {
+    pub target_id: String,
+    pub topic_hash: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// Announces that the sending node is still subscribed to a topic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Heartbeat {
+    pub topic_hash: String,
+    pub sent_at: DateTime<Utc>,
+}
+
+/// Announces the user of the sending node, and whether they are around.
+///
+/// Only accepted from the node it names.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Presence {
+    pub topic_hash: String,
+    pub author: String,
+    pub node_id: NodeId,
+    pub status: PresenceStatus,
+}
+
+/// Announces the profile of the user of the sending node.
+///
+/// Only accepted from the node it names.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub topic_hash: String,
+    pub node_id: NodeId,
+    pub profile: UserProfile,
+}
+
+/// Announces the avatar image of the user of the sending node, which the
+/// node serves as a [blob](crate::blobs). `None` removes the image.
+///
+/// Only accepted from the node it names.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Avatar {
+    pub topic_hash: String,
+    pub node_id: NodeId,
+    pub image: Option<BlobHash>,
+}
+
+/// Offers the recent history of a topic to `recipient`, as a
+/// [snapshot](crate::snapshot) the sending node serves as a
+/// [blob](crate::blobs).
+///
+/// Only imported by the recipient, and only from the node it names.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub topic_hash: String,
+    pub node_id: NodeId,
+    pub recipient: NodeId,
+    pub blob: BlobHash,
+}
+
+/// Changes the [task](crate::tasks) made of a message of a topic.
+///
+/// Stamped with a Lamport timestamp, and only applied when the sending node
+/// is verified, as the signer breaks ties between updates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskUpdate {
+    pub topic_hash: String,
+    pub message_id: String,
+    pub change: TaskChange,
+    pub lamport: u64,
+}
+
+/// Attaches the details of a [calendar](crate::calendar) event to the chat
+/// message announcing it.
+///
+/// Only shown when sent by the same node as the message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub topic_hash: String,
+    pub message_id: String,
+    pub title: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub location: String,
+}
+
+/// Answers a calendar event.
+///
+/// Stamped with a Lamport timestamp, so the latest answer of the sending
+/// node counts, and only applied when the sending node is verified.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rsvp {
+    pub topic_hash: String,
+    pub message_id: String,
+    pub response: RsvpResponse,
+    pub lamport: u64,
+}
+
+/// Offers a file dropped into a [bridged](crate::bridge) folder, which the
+/// sending node serves as a [blob](crate::blobs) sealed with the topic key.
+///
+/// Only fetched from the node it names.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub topic_hash: String,
+    pub node_id: NodeId,
+    pub name: String,
+    pub size: u64,
+    pub blob: BlobHash,
+}
+
+/// Restricts who takes part in a topic to the listed nodes and its creator,
+/// see [`access`](crate::access). `None` opens the topic to everyone with the
+/// ticket again.
+///
+/// Stamped with a Lamport timestamp, so the latest list counts, and only
+/// accepted from the creator named by the ticket.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Allowlist {
+    pub topic_hash: String,
+    pub members: Option<BTreeSet<NodeId>>,
+    pub lamport: u64,
+}
+
+/// Acknowledges the receipt of a chat message to its sender.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ack {
+    pub topic_hash: String,
+    pub message_id: String,
+}
+
+/// Asks the other members of a topic to send again what they published since
+/// `since`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncRequest {
+    pub topic_hash: String,
+    pub since: DateTime<Utc>,
+}
+
+/// Something that happened on a topic, delivered to every
+/// [subscription](IrohClient::subscribe).
+#[derive(Debug, Clone)]
+pub enum ClientEvent {
+    /// A chat message was received or posted locally.
+    Message(ChatMessage),
+    /// A message was edited. `sender` is the node whose signature on the edit
+    /// has been verified.
+    Edit {
+        edit: MessageEdit,
+        sender: Option<NodeId>,
+    },
+    /// A message was deleted. `sender` is the node whose signature on the
+    /// delete has been verified.
+    Delete {
+        delete: MessageDelete,
+        sender: Option<NodeId>,
+    },
+    /// One of our messages was acknowledged, or given up on.
+    Delivery {
+        topic_hash: String,
+        message_id: String,
+        state: DeliveryState,
+    },
+    /// A member announced their presence.
+    Presence(Presence),
+    /// A member announced their profile.
+    Profile(Profile),
+    /// A member announced an avatar image, which is now in the blob store,
+    /// or removed theirs.
+    Avatar(Avatar),
+    /// A task was changed by the verified node `sender`.
+    Task { update: TaskUpdate, sender: NodeId },
+    /// A calendar event was announced by the verified node `sender`.
+    Calendar {
+        event: CalendarEvent,
+        sender: NodeId,
+    },
+    /// The verified node `sender` answered a calendar event.
+    Rsvp { rsvp: Rsvp, sender: NodeId },
+    /// A member offered a file, to be fetched with
+    /// [`IrohClient::fetch_attachment`].
+    Attachment(Attachment),
+    /// Whether other members are seen on the topic changed.
+    Activity {
+        topic_hash: String,
+        activity: TopicActivity,
+    },
+    /// How well our node is connected changed. Concerns every topic.
+    Connectivity(Connectivity),
+    /// The subscription to the topic failed, is being restored, or was
+    /// restored.
+    Reconnect {
+        topic_hash: String,
+        state: ReconnectState,
+    },
+    /// The node `node_id` became a gossip neighbor on the topic, which our
+    /// messages are handed to.
+    PeerJoined { topic_hash: String, node_id: NodeId },
+    /// The node `node_id` is no longer a gossip neighbor on the topic.
+    PeerLeft { topic_hash: String, node_id: NodeId },
+    /// Something the client did on its own failed, such as sending a queued
+    /// message or receiving one. Concerns every topic when `topic_hash` is
+    /// empty.
+    Error { topic_hash: String, error: String },
+}
+
+impl ClientEvent {
+    /// Hash of the topic the event happened on, empty for events
+    /// concerning every topic.
+    pub fn topic_hash(&self) -> &str {
+        match self {
+            ClientEvent::Message(message) => &message.topic_hash,
+            ClientEvent::Edit { edit, .. } => &edit.topic_hash,
+            ClientEvent::Delete { delete, .. } => &delete.topic_hash,
+            ClientEvent::Delivery { topic_hash, .. } => topic_hash,
+            ClientEvent::Presence(presence) => &presence.topic_hash,
+            ClientEvent::Profile(profile) => &profile.topic_hash,
+            ClientEvent::Avatar(avatar) => &avatar.topic_hash,
+            ClientEvent::Task { update, .. } => &update.topic_hash,
+            ClientEvent::Calendar { event, .. } => &event.topic_hash,
+            ClientEvent::Rsvp { rsvp, .. } => &rsvp.topic_hash,
+            ClientEvent::Attachment(attachment) => &attachment.topic_hash,
+            ClientEvent::Activity { topic_hash, .. } => topic_hash,
+            ClientEvent::Connectivity(_) => "",
+            ClientEvent::Reconnect { topic_hash, .. } => topic_hash,
+            ClientEvent::PeerJoined { topic_hash, .. } => topic_hash,
+            ClientEvent::PeerLeft { topic_hash, .. } => topic_hash,
+            ClientEvent::Error { topic_hash, .. } => topic_hash,
+        }
+    }
+}
+
+impl ChatMessage {
+    /// Key sorting messages into the same order on every peer: by Lamport
+    /// timestamp, with wall clock time and id breaking ties.
+    pub fn order_key(&self) -> (u64, DateTime<Utc>, &str) {
+        (self.lamport, self.timestamp, &self.id)
+    }
+
+    /// Applies an edit sent by `sender`, returning whether the message changed.
+    ///
+    /// Edits from another node than the one that sent the message, edits
+    /// older than the last one applied, and edits of deleted messages are
+    /// ignored.
+    pub fn apply_edit(&mut self, edit: &MessageEdit, sender: Option<NodeId>) -> bool {
+        if edit.target_id != self.id || !self.is_sent_by(sender) || self.is_deleted() {
+            return false;
+        }
+        if self
+            .edited_at
+            .is_some_and(|edited_at| edited_at >= edit.edited_at)
+        {
+            return false;
+        }
+        self.content = edit.new_content.clone();
+        self.edited_at = Some(edit.edited_at);
+        true
+    }
+
+    /// Applies a delete sent by `sender`, replacing the content with
+    /// [`DELETED_CONTENT`]. Returns whether the message changed.
+    ///
+    /// Deletes from another node than the one that sent the message are
+    /// ignored.
+    pub fn apply_delete(&mut self, delete: &MessageDelete, sender: Option<NodeId>) -> bool {
+        if delete.target_id != self.id || !self.is_sent_by(sender) || self.is_deleted() {
+            return false;
+        }
+        self.content = DELETED_CONTENT.to_string();
+        self.deleted_at = Some(delete.deleted_at);
+        true
+    }
+
+    /// Whether the message was deleted by its author.
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    // Whether `sender` is the verified sender of the message
+    fn is_sent_by(&self, sender: Option<NodeId>) -> bool {
+        sender.is_some() && sender == self.verified_sender
+    }
+}
+
+// How often a topic task checks whether members are still seen
+const LIVENESS_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+// How often a topic task looks for messages to send again
+const REDELIVERY_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+// Least time between attempts to rejoin a topic we seem to be cut off from
+const RESUBSCRIBE_INTERVAL: Duration = Duration::from_secs(60);
+
+// How often connectivity is checked besides when the relay or our addresses
+// change, to notice peers only reached through a relay
+const CONNECTIVITY_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+// How long leaving the topics may take when shutting down, before the
+// endpoint is closed regardless
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many events a subscription may fall behind before it misses the
+/// oldest of them.
+pub const EVENT_CAPACITY: usize = 1024;
+
+// Hands every event of the process to every subscription
+static EVENTS: LazyLock<broadcast::Sender<ClientEvent>> =
+    LazyLock::new(|| broadcast::channel(EVENT_CAPACITY).0);
+
+/// Events handed to one subscriber, from [`IrohClient::subscribe`].
+pub struct Subscription {
+    events: BoxStream<'static, ClientEvent>,
+    // Events missed by falling behind, shared with the stream counting them
+    dropped: Arc<AtomicU64>,
+}
+
+impl Subscription {
+    /// A subscription handing out `events`, for transports of other kinds.
+    pub fn new(events: impl Stream<Item = ClientEvent> + Send + 'static) -> Self {
+        Self {
+            events: events.boxed(),
+            dropped: Arc::default(),
+        }
+    }
+
+    /// A subscription to what is sent on the channel of `receiver` from now
+    /// on. Falling behind by the capacity of the channel misses the oldest
+    /// events, which are counted instead of holding up the sender.
+    pub fn from_broadcast(receiver: broadcast::Receiver<ClientEvent>) -> Self {
+        let dropped = Arc::new(AtomicU64::new(0));
+        let counted = dropped.clone();
+        let events = futures::stream::unfold(receiver, move |mut receiver| {
+            let counted = counted.clone();
+            async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(event) => return Some((event, receiver)),
+                        Err(broadcast::error::RecvError::Lagged(missed)) => {
+                            warn!(missed, "Subscriber fell behind, missing events");
+                            counted.fetch_add(missed, Ordering::Relaxed);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            }
+        });
+        Self {
+            events: events.boxed(),
+            dropped,
+        }
+    }
+
+    /// How many events were missed so far by falling behind.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl fmt::Debug for Subscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("dropped", &self.dropped())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Stream for Subscription {
+    type Item = ClientEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<ClientEvent>> {
+        self.events.poll_next_unpin(cx)
+    }
+}
+
+/// Where a client stands: its node, the topics it is on and the active one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Session {
+    /// The id of our node, once the network is initialized.
+    pub node_id: Option<String>,
+    /// Ticket of the active topic.
+    pub topic_ticket: Option<String>,
+    /// Hash of the active topic.
+    pub topic_hash: Option<String>,
+    /// Hashes of the topics created or joined, by name.
+    pub subscribed_topics: HashMap<String, String>,
+}
+
+/// A node chatting on gossip topics. Clones share everything but the relay
+/// mode and binding, so what one clone does every other one sees.
+#[derive(Clone, Debug)]
+pub struct IrohClient {
+    // Where the client stands, observed through watch channels
+    session: Arc<watch::Sender<Session>>,
+    // The node on the network, once initialized
+    node: Arc<OnceLock<Node>>,
+    // How the endpoint reaches other nodes, set before initializing the
+    // network
+    relay_mode: RelayMode,
+    // Where the endpoint listens, set before initializing the network
+    binding: Binding,
+    // Invites we handed out, redeemed by the router
+    invites: Arc<StdMutex<Invites>>,
+    // Round-trip latency to the gossip neighbors that answered the last ping
+    latencies: Arc<StdMutex<HashMap<NodeId, Duration>>>,
+    // The gossip topics we are subscribed to, keyed by topic hash. Shared
+    // between clones so any clone can publish to a topic joined by another.
+    topics: Arc<StdMutex<HashMap<String, TopicHandle>>>,
+    // Stamps outgoing messages, shared between clones
+    clock: LamportClock,
+    // Name and status of the user announced on every topic, once known
+    presence: Arc<StdMutex<Option<(String, PresenceStatus)>>>,
+    // Profile and avatar image of the user announced on every topic, once set
+    profile: SharedProfile,
+    // How the peers we receive gossip from behaved, across all topics
+    reputation: Arc<StdMutex<Reputation>>,
+    // What is filtered out of the messages of others, on every topic
+    filters: Arc<StdMutex<Filters>>,
+    // Tasks serving the whole node, stopped on shutdown
+    tasks: Arc<StdMutex<Vec<JoinHandle<()>>>>,
+    // Hands out the events of this client, shared between clones
+    events: Events,
+}
+
+// Hands the events of one client to its own subscriptions, and to those of
+// the whole process
+#[derive(Debug, Clone)]
+struct Events {
+    client: broadcast::Sender<ClientEvent>,
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        Self {
+            client: broadcast::channel(EVENT_CAPACITY).0,
+        }
+    }
+}
+
+impl Events {
+    fn emit(&self, event: ClientEvent) {
+        if self.client.receiver_count() > 0 {
+            let _ = self.client.send(event.clone());
+        }
+        IrohClient::broadcast_event(event);
+    }
+
+    fn emit_message(&self, message: ChatMessage) {
+        self.emit(ClientEvent::Message(message));
+    }
+}
+
+// The node of a client on the network
+#[derive(Debug)]
+struct Node {
+    endpoint: Endpoint,
+    gossip: Gossip,
+    router: Router,
+    // Blobs we serve and fetched
+    #[cfg(feature = "blobs")]
+    blobs: BlobStore,
+}
+
+// Profile and avatar image of the user, shared with the tasks serving topics
+type SharedProfile = Arc<StdMutex<Option<(UserProfile, Option<BlobHash>)>>>;
+
+// A gossip topic we are subscribed to
+#[derive(Clone, Debug)]
+struct TopicHandle {
+    sender: GossipSender,
+    key: TopicKey,
+    // The ticket the topic was created or joined with
+    ticket: String,
+    // Our own recent payloads, for answering sync requests
+    outbox: Arc<StdMutex<Outbox>>,
+    // Our chat messages nobody acknowledged yet
+    deliveries: Arc<StdMutex<Deliveries>>,
+    // Our current gossip neighbors, which our messages are handed to
+    neighbors: Arc<StdMutex<BTreeSet<NodeId>>>,
+    // Bytes and messages sent and received on the topic
+    traffic: Arc<StdMutex<Traffic>>,
+    // The creator of the topic, and whom it allows to take part
+    access: Arc<StdMutex<Access>>,
+    // Controls the task serving the topic
+    commands: mpsc::UnboundedSender<TopicCommand>,
+}
+
+// Requests handled by the task serving a topic
+#[derive(Debug)]
+enum TopicCommand {
+    // Replace the subscription and ask the other members for what we missed
+    Resync,
+    // Announce the presence of the user right away
+    AnnouncePresence,
+    // Announce the profile of the user right away
+    AnnounceProfile,
+    // Send what is queued, if anybody is connected, and stop serving the
+    // topic, answering once the subscription is dropped
+    Leave(oneshot::Sender<()>),
+}
+
+impl Default for IrohClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IrohClient {
+    pub fn new() -> Self {
+        trace!("Creating new IrohClient instance");
+        Self {
+            session: Arc::new(watch::Sender::new(Session::default())),
+            node: Arc::default(),
+            relay_mode: RelayMode::Default,
+            binding: Binding::default(),
+            invites: Arc::default(),
+            latencies: Arc::default(),
+            topics: Arc::new(StdMutex::new(HashMap::new())),
+            clock: LamportClock::new(),
+            presence: Arc::default(),
+            profile: Arc::default(),
+            reputation: Arc::default(),
+            filters: Arc::default(),
+            tasks: Arc::default(),
+            events: Events::default(),
+        }
+    }
+
+    /// Events from now on of the topic `topic_hash`, or of every topic
+    /// without one, and those concerning every topic. Every subscription
+    /// receives every event, and events of the clients of the whole process.
+    ///
+    /// A subscription falling more than [`EVENT_CAPACITY`] events behind
+    /// misses the oldest of them, and [counts](Subscription::dropped) them.
+    pub fn subscribe(topic_hash: Option<&str>) -> Subscription {
+        Self::concerning(EVENTS.subscribe(), topic_hash)
+    }
+
+    /// Events from now on of this client alone, and its clones, unlike
+    /// [`subscribe`](Self::subscribe), which hands out those of every client
+    /// of the process. Filtered by topic like it.
+    pub fn events(&self, topic_hash: Option<&str>) -> Subscription {
+        Self::concerning(self.events.client.subscribe(), topic_hash)
+    }
+
+    // A subscription to what `receiver` gets of the topic `topic_hash`, or of
+    // every topic without one
+    fn concerning(
+        receiver: broadcast::Receiver<ClientEvent>,
+        topic_hash: Option<&str>,
+    ) -> Subscription {
+        let topic_hash = topic_hash.map(str::to_string);
+        let Subscription { events, dropped } = Subscription::from_broadcast(receiver);
+        let events = events.filter(move |event| {
+            let concerned = match &topic_hash {
+                Some(topic_hash) => {
+                    event.topic_hash().is_empty() || event.topic_hash() == topic_hash
+                }
+                None => true,
+            };
+            futures::future::ready(concerned)
+        });
+        Subscription {
+            events: events.boxed(),
+            dropped,
+        }
+    }
+
+    // This function should be used to send messages, ensuring they go to all receivers
+    pub fn broadcast_message(message: ChatMessage) {
+        trace!(
+            message_id = %message.id,
+            author = %message.author,
+            "Broadcasting message to all receivers"
+        );
+        Self::broadcast_event(ClientEvent::Message(message));
+    }
+
+    // Delivers an event to every subscription
+    pub fn broadcast_event(event: ClientEvent) {
+        if EVENTS.send(event).is_err() {
+            trace!("No subscriptions to deliver the event to");
+        }
+    }
+
+    /// Sets whether the endpoint uses relay servers, connects directly, or
+    /// both. Takes effect when the network is initialized.
+    ///
+    /// On the [local network](RelayMode::Local) alone, other nodes are found
+    /// by their [announcements](crate::lan) instead of through the n0 DNS
+    /// servers. With [relays only](RelayMode::Forced), the endpoint is bound
+    /// to the loopback interface, so it cannot reach other machines, or be
+    /// reached by them, without a relay.
+    pub fn set_relay_mode(&mut self, relay_mode: RelayMode) {
+        self.relay_mode = relay_mode;
+    }
+
+    /// How the endpoint reaches other nodes, as set before initializing the
+    /// network.
+    pub fn relay_mode(&self) -> RelayMode {
+        self.relay_mode
+    }
+
+    /// Sets the port, IP versions and interfaces the endpoint listens on.
+    /// Takes effect when the network is initialized. With
+    /// [relays only](RelayMode::Forced), only the port applies.
+    pub fn set_binding(&mut self, binding: Binding) {
+        self.binding = binding;
+    }
+
+    /// Where the endpoint listens, as set before initializing the network.
+    pub fn binding(&self) -> Binding {
+        self.binding
+    }
+
+    #[instrument(skip(self), fields(node_id))]
+    pub async fn initialize_network(&self) -> Result<String, String> {
+        info!("Initializing network connection");
+        if self.node.get().is_some() {
+            return Err("The network is initialized already".to_string());
+        }
+
+        // Initialize the iroh endpoint
+        let builder = match self.relay_mode {
+            RelayMode::Local => Endpoint::builder().add_discovery(|secret_key| {
+                LanDiscovery::new(secret_key.clone())
+                    .inspect_err(|e| warn!("Local network discovery unavailable: {}", e))
+                    .ok()
+            }),
+            RelayMode::Default | RelayMode::Disabled | RelayMode::Forced => {
+                Endpoint::builder().discovery_n0()
+            }
+        };
+        let (addr_v4, addr_v6) = match self.relay_mode {
+            RelayMode::Forced => (
+                SocketAddrV4::new(Ipv4Addr::LOCALHOST, self.binding.port),
+                SocketAddrV6::new(Ipv6Addr::LOCALHOST, self.binding.port, 0, 0),
+            ),
+            _ => (self.binding.addr_v4(), self.binding.addr_v6()),
+        };
+        let endpoint = builder
+            .bind_addr_v4(addr_v4)
+            .bind_addr_v6(addr_v6)
+            .relay_mode(self.relay_mode.into())
+            .bind()
+            .await
+            .map_err(|e| format!("Failed to create iroh endpoint: {}", e))?;
+
+        // Start the gossip protocol and accept incoming gossip connections
+        let gossip = Gossip::builder()
+            .spawn(endpoint.clone())
+            .await
+            .map_err(|e| format!("Failed to start gossip: {}", e))?;
+        let router = Router::builder(endpoint.clone())
+            .accept(GOSSIP_ALPN, gossip.clone())
+            .accept(INVITE_ALPN, InviteProtocol::new(self.invites.clone()))
+            .accept(PING_ALPN, PingProtocol);
+        // Without a configuration directory, blobs last until the next reboot
+        #[cfg(feature = "blobs")]
+        let blobs = BlobStore::new(
+            &BlobStore::default_dir()
+                .unwrap_or_else(|| std::env::temp_dir().join("iroh-lab-blobs")),
+        );
+        #[cfg(feature = "blobs")]
+        let router = router.accept(BLOBS_ALPN, BlobsProtocol::new(blobs.clone()));
+        let router = router
+            .spawn()
+            .await
+            .map_err(|e| format!("Failed to start protocol router: {}", e))?;
+
+        self.tasks.lock().unwrap().extend([
+            tokio::spawn(watch_connectivity(
+                endpoint.clone(),
+                self.relay_mode,
+                self.topics.clone(),
+                self.events.clone(),
+            )),
+            tokio::spawn(measure_latencies(
+                endpoint.clone(),
+                self.topics.clone(),
+                self.latencies.clone(),
+            )),
+        ]);
+
+        // Get the node ID
+        let node_id = endpoint.node_id().to_string();
+
+        // Store endpoint and node_id
+        let node = Node {
+            endpoint,
+            gossip,
+            router,
+            #[cfg(feature = "blobs")]
+            blobs,
+        };
+        if self.node.set(node).is_err() {
+            return Err("The network is initialized already".to_string());
+        }
+        self.session
+            .send_modify(|session| session.node_id = Some(node_id.clone()));
+
+        info!(node_id = %node_id, "Network initialized with node ID");
+        Ok(node_id)
+    }
+
+    #[instrument(skip(self), fields(topic_name = %topic_name))]
+    pub async fn create_topic(
+        &self,
+        topic_name: String,
+    ) -> Result<(String, String, String), String> {
+        info!("Creating new topic: {}", topic_name);
+
+        let endpoint = self.endpoint()?.clone();
+
+        // Generate a random gossip topic and a ticket others can use to find us.
+        // The ticket also carries the random seed of the topic's encryption key.
+        let topic_id = TopicId::from_bytes(rand::random());
+        let own_addr = endpoint
+            .node_addr()
+            .await
+            .map_err(|e| format!("Failed to get own node address: {}", e))?;
+        let ticket = Ticket {
+            topic_name: topic_name.clone(),
+            topic_id,
+            nodes: vec![own_addr],
+            key_seed: rand::random(),
+            creator: Some(endpoint.node_id()),
+        };
+        let topic_key = ticket.topic_key();
+        let ticket = ticket.to_string();
+        let topic_hash = topic_id.to_string();
+
+        // We are the first peer on the topic, so there is no one to bootstrap from
+        self.subscribe_topic(
+            topic_id,
+            &topic_hash,
+            topic_key,
+            &ticket,
+            Access::new(Some(endpoint.node_id())),
+            Vec::new(),
+        )?;
+
+        // Store the topic, and make it the active one
+        self.enter(&topic_name, &topic_hash, &ticket);
+
+        // Announce the topic creation locally
+        self.events.emit_message(system_message(
+            &topic_hash,
+            format!("Topic '{}' was created", topic_name),
+            self.clock.tick(),
+            endpoint.node_id(),
+        ));
+
+        info!(
+            topic_hash = %topic_hash,
+            ticket = %ticket,
+            "Topic created successfully"
+        );
+
+        Ok((topic_name, ticket, topic_hash))
+    }
+
+    #[instrument(skip(self), fields(ticket = %ticket))]
+    pub async fn join_topic(&self, ticket: String) -> Result<(String, String), String> {
+        info!("Attempting to join topic with ticket: {}", ticket);
+
+        let endpoint = self.endpoint()?.clone();
+
+        // Invites only reveal the ticket once the inviter hands it over
+        let ticket = if ticket.trim().starts_with(INVITE_PREFIX) {
+            let invite = ticket.parse::<Invite>()?;
+            info!(inviter = %invite.inviter.fmt_short(), "Redeeming invite");
+            invite::redeem(&endpoint, &invite).await?
+        } else {
+            ticket
+        };
+
+        // Extract topic information from the ticket
+        let parsed = ticket.parse::<Ticket>()?;
+        let topic_key = parsed.topic_key();
+        let Ticket {
+            topic_name,
+            topic_id,
+            nodes,
+            creator,
+            ..
+        } = parsed;
+        let topic_hash = topic_id.to_string();
+
+        // Make the peers from the ticket dialable and bootstrap from them
+        let own_node_id = endpoint.node_id();
+        let mut bootstrap = Vec::new();
+        for node in nodes.into_iter().filter(|node| node.node_id != own_node_id) {
+            bootstrap.push(node.node_id);
+            endpoint
+                .add_node_addr(node)
+                .map_err(|e| format!("Failed to add peer address: {}", e))?;
+        }
+        self.subscribe_topic(
+            topic_id,
+            &topic_hash,
+            topic_key,
+            &ticket,
+            Access::new(creator),
+            bootstrap,
+        )?;
+
+        // Store the topic, and make it the active one
+        self.enter(&topic_name, &topic_hash, &ticket);
+
+        // Announce joining locally
+        self.events.emit_message(system_message(
+            &topic_hash,
+            "A new user joined the topic".to_string(),
+            self.clock.tick(),
+            endpoint.node_id(),
+        ));
+
+        info!(
+            topic_name = %topic_name,
+            topic_hash = %topic_hash,
+            "Successfully joined topic"
+        );
+
+        Ok((topic_name, topic_hash))
+    }
+
+    /// Sends a chat message to the active topic and returns the message as
+    /// it was sent, stamped with the next Lamport timestamp.
+    pub async fn send_message(
+        &self,
+        username: String,
+        message_content: String,
+        sequence: u64,
+    ) -> Result<ChatMessage, String> {
+        let topic_hash = self.active_topic()?;
+        self.send_message_to(&topic_hash, username, message_content, sequence)
+            .await
+    }
+
+    #[instrument(skip(self, message_content), fields(
+        username = %username,
+        topic_hash = %topic_hash,
+        sequence = %sequence
+    ))]
+    /// Sends a chat message to the topic `topic_hash`, active or not, and
+    /// returns the message as it was sent.
+    pub async fn send_message_to(
+        &self,
+        topic_hash: &str,
+        username: String,
+        message_content: String,
+        sequence: u64,
+    ) -> Result<ChatMessage, String> {
+        let topic_hash = &topic_hash.to_string();
+
+        info!(
+            content_length = message_content.len(),
+            "Sending message to network"
+        );
+
+        // Create the chat message
+        let message_id = Uuid::new_v4().to_string();
+        let chat_message = ChatMessage {
+            id: message_id.clone(),
+            author: username,
+            content: message_content,
+            timestamp: Utc::now(),
+            topic_hash: topic_hash.clone(),
+            sequence,
+            lamport: self.clock.tick(),
+            verified_sender: self.own_node_id(),
+            edited_at: None,
+            deleted_at: None,
+        };
+
+        // Gossip does not deliver our own messages back to us, the UI shows
+        // them directly
+        self.publish(topic_hash, &Payload::Chat(chat_message.clone()))
+            .await?;
+
+        info!(
+            message_id = %message_id,
+            "Message sent to P2P network"
+        );
+
+        Ok(chat_message)
+    }
+
+    /// Replaces the content of one of our messages on the active topic, and
+    /// returns the edit as it was sent.
+    #[instrument(skip(self, new_content), fields(topic_hash = ?self.session().topic_hash))]
+    pub async fn edit_message(
+        &self,
+        target_id: String,
+        new_content: String,
+    ) -> Result<MessageEdit, String> {
+        let topic_hash = &self.active_topic()?;
+
+        let edit = MessageEdit {
+            target_id,
+            topic_hash: topic_hash.clone(),
+            new_content,
+            edited_at: Utc::now(),
+        };
+        self.publish(topic_hash, &Payload::Edit(edit.clone()))
+            .await?;
+
+        info!(target_id = %edit.target_id, "Edit sent to P2P network");
+
+        Ok(edit)
+    }
+
+    /// Deletes one of our messages on the active topic, and returns the
+    /// delete as it was sent.
+    #[instrument(skip(self), fields(topic_hash = ?self.session().topic_hash))]
+    pub async fn delete_message(&self, target_id: String) -> Result<MessageDelete, String> {
+        let topic_hash = &self.active_topic()?;
+
+        let delete = MessageDelete {
+            target_id,
+            topic_hash: topic_hash.clone(),
+            deleted_at: Utc::now(),
+        };
+        self.publish(topic_hash, &Payload::Delete(delete.clone()))
+            .await?;
+
+        info!(target_id = %delete.target_id, "Delete sent to P2P network");
+
+        Ok(delete)
+    }
+
+    /// Changes the task made of the message `message_id` of the active
+    /// topic.
+    pub async fn update_task(
+        &self,
+        message_id: String,
+        change: TaskChange,
+    ) -> Result<TaskUpdate, String> {
+        let topic_hash = &self.active_topic()?;
+
+        let update = TaskUpdate {
+            topic_hash: topic_hash.clone(),
+            message_id,
+            change,
+            lamport: self.clock.tick(),
+        };
+        self.publish(topic_hash, &Payload::Task(update.clone()))
+            .await?;
+
+        info!(message_id = %update.message_id, "Task update sent to P2P network");
+
+        Ok(update)
+    }
+
+    /// Announces a calendar event on the active topic with a chat message,
+    /// and returns the message and the event as they were sent.
+    pub async fn share_event(
+        &self,
+        username: String,
+        sequence: u64,
+        title: String,
+        starts_at: DateTime<Utc>,
+        ends_at: DateTime<Utc>,
+        location: String,
+    ) -> Result<(ChatMessage, CalendarEvent), String> {
+        calendar::validate(&title, starts_at, ends_at)?;
+        let topic_hash = &self.active_topic()?;
+
+        let content = calendar::announcement(&title, starts_at, ends_at, &location);
+        let message = self.send_message(username, content, sequence).await?;
+        let event = CalendarEvent {
+            topic_hash: topic_hash.clone(),
+            message_id: message.id.clone(),
+            title: title.trim().to_string(),
+            starts_at,
+            ends_at,
+            location: location.trim().to_string(),
+        };
+        self.publish(topic_hash, &Payload::Event(event.clone()))
+            .await?;
+
+        info!(message_id = %event.message_id, "Event sent to P2P network");
+
+        Ok((message, event))
+    }
+
+    /// Answers the calendar event announced by the message `message_id` of
+    /// the active topic.
+    pub async fn respond_to_event(
+        &self,
+        message_id: String,
+        response: RsvpResponse,
+    ) -> Result<Rsvp, String> {
+        let topic_hash = &self.active_topic()?;
+
+        let rsvp = Rsvp {
+            topic_hash: topic_hash.clone(),
+            message_id,
+            response,
+            lamport: self.clock.tick(),
+        };
+        self.publish(topic_hash, &Payload::Rsvp(rsvp.clone()))
+            .await?;
+
+        info!(message_id = %rsvp.message_id, "Answer sent to P2P network");
+
+        Ok(rsvp)
+    }
+
+    /// Sends the latest of `messages` of the active topic to `recipient`,
+    /// who missed them, returning how many were sent.
+    #[cfg(feature = "blobs")]
+    pub async fn send_history(
+        &self,
+        recipient: NodeId,
+        messages: Vec<ChatMessage>,
+    ) -> Result<usize, String> {
+        let topic_hash = &self.active_topic()?;
+        let topic = self
+            .topics
+            .lock()
+            .unwrap()
+            .get(topic_hash)
+            .cloned()
+            .ok_or_else(|| "Not subscribed to the active topic".to_string())?;
+        let blobs = self
+            .blob_store()
+            .ok_or_else(|| "Network not initialized".to_string())?;
+
+        let (sealed, count) = snapshot::pack(&messages, &topic.key)?;
+        let snapshot = Snapshot {
+            topic_hash: topic_hash.clone(),
+            node_id: self.endpoint()?.node_id(),
+            recipient,
+            blob: blobs.put(&sealed)?,
+        };
+        // Not kept for sync requests, the recipient asks for it only once
+        broadcast_to(
+            &topic,
+            self.endpoint()?.secret_key(),
+            &Payload::Snapshot(snapshot),
+        )
+        .await?;
+
+        info!(recipient = %recipient.fmt_short(), count, "History sent");
+        Ok(count)
+    }
+
+    /// Offers the file `name` holding `content` on the topic `topic_hash`,
+    /// which we are subscribed to. The content is sealed with the topic key
+    /// and served as a blob to the members fetching it.
+    #[cfg(feature = "blobs")]
+    pub async fn post_attachment(
+        &self,
+        topic_hash: &str,
+        name: &str,
+        content: &[u8],
+    ) -> Result<Attachment, String> {
+        let key = self
+            .topics
+            .lock()
+            .unwrap()
+            .get(topic_hash)
+            .map(|topic| topic.key.clone())
+            .ok_or_else(|| "Not subscribed to this topic".to_string())?;
+        let blobs = self
+            .blob_store()
+            .ok_or_else(|| "Network not initialized".to_string())?;
+
+        let attachment = Attachment {
+            topic_hash: topic_hash.to_string(),
+            node_id: self.endpoint()?.node_id(),
+            name: name.to_string(),
+            size: content.len() as u64,
+            blob: blobs.put(&key.encrypt(content)?)?,
+        };
+        self.publish(topic_hash, &Payload::Attachment(attachment.clone()))
+            .await?;
+
+        info!(name = %attachment.name, size = attachment.size, "Attachment sent to P2P network");
+
+        Ok(attachment)
+    }
+
+    /// Fetches the file offered by `attachment` from the node offering it,
+    /// and opens it with the key of its topic.
+    #[cfg(feature = "blobs")]
+    pub async fn fetch_attachment(&self, attachment: &Attachment) -> Result<Vec<u8>, String> {
+        let key = self
+            .topics
+            .lock()
+            .unwrap()
+            .get(&attachment.topic_hash)
+            .map(|topic| topic.key.clone())
+            .ok_or_else(|| "Not subscribed to this topic".to_string())?;
+        let blobs = self
+            .blob_store()
+            .ok_or_else(|| "Network not initialized".to_string())?;
+
+        let sealed =
+            blobs::fetch(self.endpoint()?, blobs, attachment.node_id, attachment.blob).await?;
+        key.decrypt(&sealed)
+    }
+
+    // Publishes a payload to the other peers on a topic we are subscribed to
+    async fn publish(&self, topic_hash: &str, payload: &Payload) -> Result<(), String> {
+        let topic = self
+            .topics
+            .lock()
+            .unwrap()
+            .get(topic_hash)
+            .cloned()
+            .ok_or_else(|| "Not subscribed to the active topic".to_string())?;
+        publish_to(&topic, self.endpoint()?.secret_key(), payload).await
+    }
+
+    /// Makes `topic_hash`, which we are subscribed to, the active topic again,
+    /// and asks its members for what was said while it was not active.
+    pub fn switch_topic(&self, topic_hash: &str) -> Result<(), String> {
+        let ticket = self
+            .ticket(topic_hash)
+            .ok_or_else(|| "Not subscribed to this topic".to_string())?;
+        self.session.send_modify(|session| {
+            session.topic_ticket = Some(ticket);
+            session.topic_hash = Some(topic_hash.to_string());
+        });
+        self.resync()
+    }
+
+    /// Adds the address of a peer discovery could not find, e.g. one copied
+    /// from its diagnostics, and connects to it on every topic we are
+    /// subscribed to. Returns on how many topics the peer was tried.
+    pub async fn add_peer(&self, node_addr: NodeAddr) -> Result<usize, String> {
+        let endpoint = self.endpoint()?;
+        let node_id = node_addr.node_id;
+        if node_id == endpoint.node_id() {
+            return Err("This is the address of our own node".to_string());
+        }
+        endpoint
+            .add_node_addr(node_addr)
+            .map_err(|e| format!("Failed to add peer address: {}", e))?;
+
+        // The peer only becomes a neighbor on the topics it is subscribed to
+        let senders: Vec<GossipSender> = self
+            .topics
+            .lock()
+            .unwrap()
+            .values()
+            .map(|topic| topic.sender.clone())
+            .collect();
+        for sender in &senders {
+            sender
+                .join_peers(vec![node_id])
+                .await
+                .map_err(|e| format!("Failed to connect to the peer: {}", e))?;
+        }
+
+        info!(node_id = %node_id.fmt_short(), topics = senders.len(), "Peer added manually");
+        Ok(senders.len())
+    }
+
+    /// Replaces the subscription to the active topic, and asks the other
+    /// members to send again what was published since we last heard from
+    /// them.
+    pub fn resync(&self) -> Result<(), String> {
+        let topic_hash = &self.active_topic()?;
+        let topics = self.topics.lock().unwrap();
+        let topic = topics
+            .get(topic_hash)
+            .ok_or_else(|| "Not subscribed to the active topic".to_string())?;
+        topic
+            .commands
+            .send(TopicCommand::Resync)
+            .map_err(|_| "The topic is no longer served".to_string())
+    }
+
+    /// Filters the messages and edits of others arriving from now on with
+    /// `filters`, on every clone.
+    pub fn set_filters(&self, filters: Filters) {
+        *self.filters.lock().unwrap() = filters;
+    }
+
+    /// Sets the name and status of the user, announced on every topic we are
+    /// subscribed to from now on.
+    pub fn set_presence(&self, author: String, status: PresenceStatus) {
+        *self.presence.lock().unwrap() = Some((author, status));
+        for topic in self.topics.lock().unwrap().values() {
+            // Topics no longer served have nobody to announce to
+            let _ = topic.commands.send(TopicCommand::AnnouncePresence);
+        }
+    }
+
+    /// Sets the profile of the user and the blob of their avatar image,
+    /// announced on every topic we are subscribed to from now on.
+    pub fn set_profile(&self, profile: UserProfile, avatar_image: Option<BlobHash>) {
+        *self.profile.lock().unwrap() = Some((profile, avatar_image));
+        for topic in self.topics.lock().unwrap().values() {
+            // Topics no longer served have nobody to announce to
+            let _ = topic.commands.send(TopicCommand::AnnounceProfile);
+        }
+    }
+
+    /// Creates a single use invite to the current topic, which reveals its
+    /// ticket only to the node redeeming it.
+    pub fn create_invite(&self) -> Result<String, String> {
+        let topic_hash = self
+            .active_topic()
+            .map_err(|_| "Not in a topic".to_string())?;
+        self.create_invite_to(&topic_hash)
+    }
+
+    /// Creates a single use invite to the topic `topic_hash`, which reveals
+    /// its ticket only to the node redeeming it.
+    pub fn create_invite_to(&self, topic_hash: &str) -> Result<String, String> {
+        let ticket = self
+            .ticket(topic_hash)
+            .ok_or_else(|| "Not subscribed to this topic".to_string())?;
+        let inviter = self.endpoint()?.node_id();
+        let invite = self
+            .invites
+            .lock()
+            .unwrap()
+            .create(inviter, ticket, Instant::now());
+        Ok(invite.to_string())
+    }
+
+    /// The blobs we serve and fetched, once the network is initialized.
+    #[cfg(feature = "blobs")]
+    pub fn blob_store(&self) -> Option<&BlobStore> {
+        self.node.get().map(|node| &node.blobs)
+    }
+
+    /// Round-trip latency to `node_id`, if it is a gossip neighbor on any
+    /// topic and answered its last ping.
+    pub fn latency(&self, node_id: &NodeId) -> Option<Duration> {
+        self.latencies.lock().unwrap().get(node_id).copied()
+    }
+
+    /// The id of our own node, once the network is initialized.
+    pub fn own_node_id(&self) -> Option<NodeId> {
+        self.node.get().map(|node| node.endpoint.node_id())
+    }
+
+    /// How our message `message_id` on the topic `topic_hash` was delivered
+    /// so far, if it is one of the latest ones.
+    pub fn delivery_report(&self, topic_hash: &str, message_id: &str) -> Option<DeliveryReport> {
+        let topics = self.topics.lock().unwrap();
+        let report = topics
+            .get(topic_hash)?
+            .deliveries
+            .lock()
+            .unwrap()
+            .report(message_id);
+        report
+    }
+
+    /// A snapshot of our own node, once the network is initialized.
+    pub fn node_status(&self) -> Option<NodeStatus> {
+        let mut status = NodeStatus::of(&self.node.get()?.endpoint);
+        status.peer_scores = self.reputation.lock().unwrap().scores(Instant::now());
+        Some(status)
+    }
+
+    /// Name of a topic we are subscribed to, through this clone or any
+    /// other.
+    pub fn topic_name(&self, topic_hash: &str) -> Option<String> {
+        let topics = self.topics.lock().unwrap();
+        let ticket = topics.get(topic_hash)?.ticket.parse::<Ticket>().ok()?;
+        Some(ticket.topic_name)
+    }
+
+    /// Where the client stands now.
+    pub fn session(&self) -> Session {
+        self.session.borrow().clone()
+    }
+
+    /// Follows where the client stands, as any of its clones changes it.
+    pub fn watch_session(&self) -> watch::Receiver<Session> {
+        self.session.subscribe()
+    }
+
+    /// Ticket of a topic we are subscribed to, through this clone or any
+    /// other.
+    pub fn ticket(&self, topic_hash: &str) -> Option<String> {
+        let topics = self.topics.lock().unwrap();
+        Some(topics.get(topic_hash)?.ticket.clone())
+    }
+
+    /// The node that created a subscribed topic, if its ticket names it.
+    pub fn topic_creator(&self, topic_hash: &str) -> Option<NodeId> {
+        let topics = self.topics.lock().unwrap();
+        let creator = topics.get(topic_hash)?.access.lock().unwrap().creator();
+        creator
+    }
+
+    /// The nodes the creator of a subscribed topic allows to take part
+    /// besides itself, `None` while the topic is open.
+    pub fn allowlist(&self, topic_hash: &str) -> Option<BTreeSet<NodeId>> {
+        let topics = self.topics.lock().unwrap();
+        let access = topics.get(topic_hash)?.access.lock().unwrap();
+        access.members().cloned()
+    }
+
+    /// Restricts who takes part in the topic `topic_hash`, which we created,
+    /// to `members` and us, and tells the other members. `None` opens the
+    /// topic to everyone with the ticket again. The list is stamped later
+    /// than `after`, the timestamp of the list it replaces.
+    pub async fn set_allowlist(
+        &self,
+        topic_hash: &str,
+        members: Option<BTreeSet<NodeId>>,
+        after: u64,
+    ) -> Result<Allowlist, String> {
+        let access = self
+            .topics
+            .lock()
+            .unwrap()
+            .get(topic_hash)
+            .map(|topic| topic.access.clone())
+            .ok_or_else(|| "Not subscribed to this topic".to_string())?;
+        // The clock starts over when restarted, while the other members
+        // still hold the list published before
+        self.clock.observe(after);
+        let allowlist = Allowlist {
+            topic_hash: topic_hash.to_string(),
+            members,
+            lamport: self.clock.tick(),
+        };
+        access
+            .lock()
+            .unwrap()
+            .accept(allowlist.clone(), self.own_node_id())?;
+        info!(
+            topic_hash = %topic_hash,
+            members = ?allowlist.members.as_ref().map(BTreeSet::len),
+            "Changed who takes part in the topic"
+        );
+
+        self.publish(topic_hash, &Payload::Allowlist(allowlist.clone()))
+            .await?;
+        Ok(allowlist)
+    }
+
+    /// Hashes of the topics we are subscribed to, through this clone or any
+    /// other.
+    pub fn topic_hashes(&self) -> Vec<String> {
+        self.topics.lock().unwrap().keys().cloned().collect()
+    }
+
+    /// The traffic of a subscribed topic so far, with how its neighbors are
+    /// reached now.
+    pub fn traffic_stats(&self, topic_hash: &str) -> Option<TrafficStats> {
+        let endpoint = &self.node.get()?.endpoint;
+        let topics = self.topics.lock().unwrap();
+        let topic = topics.get(topic_hash)?;
+        let peers = topic.neighbors.lock().unwrap().len();
+        let stats = topic
+            .traffic
+            .lock()
+            .unwrap()
+            .stats(Instant::now(), peers, |node_id| {
+                match endpoint.conn_type(node_id).ok()?.get().ok()? {
+                    ConnectionType::Direct(_) | ConnectionType::Mixed(..) => {
+                        Some(TrafficPath::Direct)
+                    }
+                    ConnectionType::Relay(_) => Some(TrafficPath::Relayed),
+                    ConnectionType::None => None,
+                }
+            });
+        Some(stats)
+    }
+
+    /// Leaves every topic, sending what is queued to the members still
+    /// connected, stops the tasks of the node and closes the endpoint. Any
+    /// clone may shut the node down, for all of them.
+    pub async fn shutdown(&self) {
+        let leaving: Vec<_> = self
+            .topics
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(topic_hash, topic)| {
+                let (done, left) = oneshot::channel();
+                // Topics no longer served have left already
+                topic.commands.send(TopicCommand::Leave(done)).ok()?;
+                Some((topic_hash.clone(), left))
+            })
+            .collect();
+        info!(topics = leaving.len(), "Shutting down, leaving topics");
+        let left =
+            futures::future::join_all(leaving.into_iter().map(|(topic_hash, left)| async move {
+                if left.await.is_err() {
+                    warn!(topic_hash = %topic_hash, "Topic stopped without leaving");
+                }
+            }));
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, left).await.is_err() {
+            warn!("Leaving the topics timed out");
+        }
+
+        for (topic_hash, topic) in self.topics.lock().unwrap().drain() {
+            let unsent = topic.deliveries.lock().unwrap().queued();
+            if unsent > 0 {
+                warn!(topic_hash = %topic_hash, unsent, "Messages queued for nobody were dropped");
+            }
+        }
+        for task in self.tasks.lock().unwrap().drain(..) {
+            task.abort();
+        }
+        self.session.send_modify(|session| {
+            session.topic_ticket = None;
+            session.topic_hash = None;
+            session.subscribed_topics.clear();
+        });
+        // Closes the endpoint, too
+        if let Some(node) = self.node.get() {
+            if let Err(e) = node.router.shutdown().await {
+                warn!("Failed to shut down the node cleanly: {}", e);
+            }
+        }
+        info!("Node shut down");
+    }
+
+    fn endpoint(&self) -> Result<&Endpoint, String> {
+        self.node
+            .get()
+            .map(|node| &node.endpoint)
+            .ok_or_else(|| "Network not initialized".to_string())
+    }
+
+    // Hash of the active topic
+    fn active_topic(&self) -> Result<String, String> {
+        self.session
+            .borrow()
+            .topic_hash
+            .clone()
+            .ok_or_else(|| "No active topic hash".to_string())
+    }
+
+    // Records the topic `topic_name` we are subscribed to, and makes it the
+    // active topic
+    fn enter(&self, topic_name: &str, topic_hash: &str, ticket: &str) {
+        self.session.send_modify(|session| {
+            session.topic_ticket = Some(ticket.to_string());
+            session.topic_hash = Some(topic_hash.to_string());
+            session
+                .subscribed_topics
+                .insert(topic_name.to_string(), topic_hash.to_string());
+        });
+    }
+
+    // Subscribes to a gossip topic and spawns the task serving it
+    fn subscribe_topic(
+        &self,
+        topic_id: TopicId,
+        topic_hash: &str,
+        key: TopicKey,
+        ticket: &str,
+        access: Access,
+        bootstrap: Vec<NodeId>,
+    ) -> Result<(), String> {
+        let node = self
+            .node
+            .get()
+            .ok_or_else(|| "Network not initialized".to_string())?;
+        let gossip = &node.gossip;
+
+        let (sender, receiver) = gossip
+            .subscribe(topic_id, bootstrap)
+            .map_err(|e| format!("Failed to subscribe to topic: {}", e))?
+            .split();
+
+        let (commands, command_receiver) = mpsc::unbounded_channel();
+        let access = Arc::new(StdMutex::new(access));
+        self.topics.lock().unwrap().insert(
+            topic_hash.to_string(),
+            TopicHandle {
+                sender,
+                key: key.clone(),
+                ticket: ticket.to_string(),
+                outbox: Arc::default(),
+                deliveries: Arc::default(),
+                neighbors: Arc::default(),
+                traffic: Arc::default(),
+                access: access.clone(),
+                commands,
+            },
+        );
+
+        let task = TopicTask {
+            topic_id,
+            topic_hash: topic_hash.to_string(),
+            key,
+            clock: self.clock.clone(),
+            secret_key: node.endpoint.secret_key().clone(),
+            #[cfg(feature = "blobs")]
+            endpoint: node.endpoint.clone(),
+            #[cfg(feature = "blobs")]
+            blobs: node.blobs.clone(),
+            gossip: gossip.clone(),
+            topics: self.topics.clone(),
+            presence: self.presence.clone(),
+            profile: self.profile.clone(),
+            reputation: self.reputation.clone(),
+            filters: self.filters.clone(),
+            replays: StdMutex::default(),
+            access,
+            events: self.events.clone(),
+        };
+        tokio::spawn(task.run(receiver, command_receiver));
+
+        Ok(())
+    }
+}
+
+// Publishes a payload to the other peers on a topic, keeping it for
+// answering sync requests, and waiting for chat messages to be acknowledged.
+// Chat messages are queued instead while no neighbor is connected
+async fn publish_to(
+    topic: &TopicHandle,
+    secret_key: &SecretKey,
+    payload: &Payload,
+) -> Result<(), String> {
+    if let Payload::Chat(message) = payload {
+        if topic.neighbors.lock().unwrap().is_empty() {
+            debug!(message_id = %message.id, "No neighbors, queueing message");
+            topic
+                .deliveries
+                .lock()
+                .unwrap()
+                .queue(message.clone(), Instant::now());
+            return Ok(());
+        }
+    }
+    topic.outbox.lock().unwrap().push(Utc::now(), payload);
+    broadcast_to(topic, secret_key, payload).await?;
+    if let Payload::Chat(message) = payload {
+        let mut deliveries = topic.deliveries.lock().unwrap();
+        deliveries.track(message.clone(), Instant::now());
+        deliveries.sent_to(&message.id, topic.neighbors.lock().unwrap().iter().copied());
+    }
+    Ok(())
+}
+
+// Reports every change of how well the endpoint is connected, and
+// resubscribes every topic once it is back online after losing its
+// connection. Ends with the endpoint.
+async fn watch_connectivity(
+    endpoint: Endpoint,
+    relay_mode: RelayMode,
+    topics: Arc<StdMutex<HashMap<String, TopicHandle>>>,
+    events: Events,
+) {
+    let mut home_relay = endpoint.home_relay();
+    let mut direct_addresses = endpoint.direct_addresses();
+    let mut check = tokio::time::interval(CONNECTIVITY_CHECK_INTERVAL);
+    let mut connectivity = Connectivity::Offline;
+    let mut was_online = false;
+
+    loop {
+        let updated = tokio::select! {
+            updated = home_relay.updated() => updated.is_ok(),
+            updated = direct_addresses.updated() => updated.is_ok(),
+            _ = check.tick() => true,
+        };
+        if !updated {
+            break;
+        }
+
+        let current = NodeStatus::of(&endpoint).connectivity(relay_mode);
+        if current == connectivity {
+            continue;
+        }
+        info!(?current, "Connectivity changed");
+        if current.is_online() && !connectivity.is_online() && was_online {
+            let topics = topics.lock().unwrap();
+            info!(topics = topics.len(), "Back online, resubscribing topics");
+            for topic in topics.values() {
+                // Topics no longer served need no resubscribing
+                let _ = topic.commands.send(TopicCommand::Resync);
+            }
+        }
+        was_online |= current.is_online();
+        connectivity = current;
+        events.emit(ClientEvent::Connectivity(connectivity));
+    }
+}
+
+// Pings the gossip neighbors on every topic each PING_INTERVAL, keeping the
+// latencies of those that answered
+async fn measure_latencies(
+    endpoint: Endpoint,
+    topics: Arc<StdMutex<HashMap<String, TopicHandle>>>,
+    latencies: Arc<StdMutex<HashMap<NodeId, Duration>>>,
+) {
+    let mut pinger = Pinger::new(endpoint);
+    let mut interval = tokio::time::interval(PING_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let neighbors: BTreeSet<NodeId> = topics
+            .lock()
+            .unwrap()
+            .values()
+            .flat_map(|topic| topic.neighbors.lock().unwrap().clone())
+            .collect();
+        pinger.retain(|node_id| neighbors.contains(node_id));
+
+        let mut measured = HashMap::new();
+        for node_id in neighbors {
+            match pinger.ping(node_id).await {
+                Ok(latency) => {
+                    measured.insert(node_id, latency);
+                }
+                Err(e) => debug!(node_id = %node_id.fmt_short(), "Ping failed: {}", e),
+            }
+        }
+        *latencies.lock().unwrap() = measured;
+    }
+}
+
+// Sends a payload to the other peers on a topic
+async fn broadcast_to(
+    topic: &TopicHandle,
+    secret_key: &SecretKey,
+    payload: &Payload,
+) -> Result<(), String> {
+    let bytes = wire::encode(payload, secret_key, &topic.key)?;
+
+    // Large messages are split to fit into gossip messages
+    for fragment in chunk::split(bytes)? {
+        let len = fragment.len();
+        topic
+            .sender
+            .broadcast(fragment.into())
+            .await
+            .map_err(|e| format!("Failed to broadcast message: {}", e))?;
+        let neighbors = topic.neighbors.lock().unwrap().clone();
+        topic
+            .traffic
+            .lock()
+            .unwrap()
+            .sent(len, neighbors, Instant::now());
+    }
+
+    Ok(())
+}
+
+// The background task of a subscribed topic. It forwards everything received
+// on the topic to the subscriptions, acknowledges chat messages, sends
+// again our messages nobody acknowledged, sends our heartbeats, and resyncs
+// when asked to or when we seem to be cut off from the other members.
+struct TopicTask {
+    topic_id: TopicId,
+    topic_hash: String,
+    key: TopicKey,
+    clock: LamportClock,
+    secret_key: SecretKey,
+    gossip: Gossip,
+    topics: Arc<StdMutex<HashMap<String, TopicHandle>>>,
+    presence: Arc<StdMutex<Option<(String, PresenceStatus)>>>,
+    profile: SharedProfile,
+    #[cfg(feature = "blobs")]
+    endpoint: Endpoint,
+    #[cfg(feature = "blobs")]
+    blobs: BlobStore,
+    reputation: Arc<StdMutex<Reputation>>,
+    filters: Arc<StdMutex<Filters>>,
+    // Signers of the chat messages seen on this topic
+    replays: StdMutex<Replays>,
+    access: Arc<StdMutex<Access>>,
+    events: Events,
+}
+
+impl TopicTask {
+    async fn run(
+        self,
+        receiver: GossipReceiver,
+        mut commands: mpsc::UnboundedReceiver<TopicCommand>,
+    ) {
+        let topic_hash = self.topic_hash.as_str();
+        info!(topic_hash = %topic_hash, "Listening for gossip messages");
+
+        let mut reassembler = Reassembler::default();
+        let mut liveness = Liveness::default();
+        let mut activity = TopicActivity::default();
+        let mut last_resubscribe = Instant::now();
+        // The subscription, while it works, and the attempts to restore it
+        let mut receiver = Some(receiver);
+        let mut reconnector = Reconnector::default();
+        // When we last saw another member, and where a pending sync request
+        // should start once we are connected again
+        let mut last_contact = None;
+        let mut sync_from = None;
+        let mut expiry = tokio::time::interval(chunk::REASSEMBLY_TIMEOUT / 2);
+        let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+        let mut liveness_check = tokio::time::interval(LIVENESS_CHECK_INTERVAL);
+        let mut presence = tokio::time::interval(PRESENCE_INTERVAL);
+        let mut redelivery = tokio::time::interval(REDELIVERY_CHECK_INTERVAL);
+        // Whom to tell once we left the topic, when asked to leave
+        let mut left = None;
+
+        loop {
+            tokio::select! {
+                event = next_event(&mut receiver) => match event {
+                    None => {
+                        warn!("Gossip subscription ended");
+                        receiver = None;
+                        self.report_reconnect(reconnector.failed(Instant::now(), &mut rand::thread_rng()));
+                    }
+                    Some(Ok(Event::Gossip(GossipEvent::Received(message)))) => {
+                        if let Ok(topic) = self.topic() {
+                            topic.traffic.lock().unwrap().received(
+                                message.content.len(),
+                                message.delivered_from,
+                                Instant::now(),
+                            );
+                        }
+                        match reassembler.accept(&message.content, Instant::now()) {
+                            Ok(Some(frame)) => {
+                                let sender =
+                                    self.handle_payload(&frame, message.delivered_from).await;
+                                if let Some(sender) = sender {
+                                    liveness.record(sender, Instant::now());
+                                    last_contact = Some(Utc::now());
+                                }
+                            }
+                            Ok(None) => {
+                                trace!(pending = reassembler.pending(), "Waiting for fragments");
+                            }
+                            Err(e) => self.report(e),
+                        }
+                    }
+                    Some(Ok(Event::Gossip(GossipEvent::Joined(peers)))) => {
+                        debug!(peers = peers.len(), "Joined gossip swarm");
+                        self.set_neighbors(&receiver);
+                        for node_id in peers {
+                            self.events.emit(ClientEvent::PeerJoined { topic_hash: self.topic_hash.clone(), node_id });
+                        }
+                        self.flush_queue().await;
+                        self.announce_profile().await;
+                        self.announce_allowlist().await;
+                        self.send_sync_request(sync_from.take()).await;
+                    }
+                    Some(Ok(Event::Gossip(GossipEvent::NeighborUp(node_id)))) => {
+                        debug!(node_id = %node_id, "Neighbor up");
+                        self.set_neighbors(&receiver);
+                        self.events.emit(ClientEvent::PeerJoined { topic_hash: self.topic_hash.clone(), node_id });
+                        self.flush_queue().await;
+                        liveness.record(node_id, Instant::now());
+                        last_contact = Some(Utc::now());
+                        // Newcomers only learn profiles and the allowlist when
+                        // they are announced
+                        self.announce_profile().await;
+                        self.announce_allowlist().await;
+                        self.send_sync_request(sync_from.take()).await;
+                    }
+                    Some(Ok(Event::Gossip(GossipEvent::NeighborDown(node_id)))) => {
+                        debug!(node_id = %node_id, "Neighbor down");
+                        self.set_neighbors(&receiver);
+                        self.events.emit(ClientEvent::PeerLeft { topic_hash: self.topic_hash.clone(), node_id });
+                    }
+                    Some(Ok(Event::Lagged)) => {
+                        warn!("Gossip receiver lagged, some messages were dropped");
+                    }
+                    Some(Err(e)) => {
+                        warn!("Gossip subscription failed: {}", e);
+                        receiver = None;
+                        self.report_reconnect(reconnector.failed(Instant::now(), &mut rand::thread_rng()));
+                    }
+                },
+                _ = tokio::time::sleep_until(
+                    reconnector.retry_at().unwrap_or_else(Instant::now).into()
+                ), if reconnector.retry_at().is_some() => {
+                    reconnector.retrying();
+                    match self.resubscribe(liveness.known_members()) {
+                        Ok(new_receiver) => {
+                            receiver = Some(new_receiver);
+                            sync_from = Some(sync::sync_since(last_contact));
+                            if let Some(state) = reconnector.connected() {
+                                self.report_reconnect(state);
+                            }
+                        }
+                        Err(e) => {
+                            warn!("Failed to reconnect: {}", e);
+                            self.report_reconnect(reconnector.failed(Instant::now(), &mut rand::thread_rng()));
+                        }
+                    }
+                }
+                _ = expiry.tick() => {
+                    let expired = reassembler.expire(Instant::now());
+                    if expired > 0 {
+                        self.report(format!(
+                            "{} incomplete message(s) timed out waiting for their remaining parts",
+                            expired
+                        ));
+                    }
+                }
+                _ = heartbeat.tick() => {
+                    if let Err(e) = self.send_heartbeat().await {
+                        warn!("Failed to send heartbeat: {}", e);
+                    }
+                }
+                _ = presence.tick() => self.announce_presence().await,
+                _ = redelivery.tick() => self.redeliver().await,
+                _ = liveness_check.tick() => {
+                    let now = Instant::now();
+                    let current = liveness.activity(now);
+                    if current != activity {
+                        info!(?current, "Topic activity changed");
+                        activity = current;
+                        self.events.emit(ClientEvent::Activity {
+                            topic_hash: topic_hash.to_string(),
+                            activity,
+                        });
+                    }
+
+                    // Try to find our way back to the members we lost
+                    if activity == TopicActivity::Partitioned
+                        && now.duration_since(last_resubscribe) >= RESUBSCRIBE_INTERVAL
+                    {
+                        last_resubscribe = now;
+                        match self.resubscribe(liveness.known_members()) {
+                            Ok(new_receiver) => {
+                                receiver = Some(new_receiver);
+                                sync_from = Some(sync::sync_since(last_contact));
+                                if let Some(state) = reconnector.connected() {
+                                    self.report_reconnect(state);
+                                }
+                            }
+                            Err(e) => warn!("Failed to resubscribe: {}", e),
+                        }
+                    }
+                }
+                // Without the handle of the topic, nobody can ask for anything
+                // anymore
+                command = commands.recv() => match command {
+                    None => break,
+                    Some(TopicCommand::Resync) => {
+                        last_resubscribe = Instant::now();
+                        reconnector.retry_now();
+                        match self.resubscribe(liveness.known_members()) {
+                            Ok(new_receiver) => {
+                                receiver = Some(new_receiver);
+                                sync_from = Some(sync::sync_since(last_contact));
+                                if let Some(state) = reconnector.connected() {
+                                    self.report_reconnect(state);
+                                }
+                            }
+                            Err(e) => {
+                                self.report(e);
+                                if receiver.is_none() {
+                                    self.report_reconnect(
+                                        reconnector.failed(Instant::now(), &mut rand::thread_rng()),
+                                    );
+                                }
+                            }
+                        }
+                    }
+                    Some(TopicCommand::AnnouncePresence) => self.announce_presence().await,
+                    Some(TopicCommand::AnnounceProfile) => self.announce_profile().await,
+                    Some(TopicCommand::Leave(done)) => {
+                        self.flush_queue().await;
+                        left = Some(done);
+                        break;
+                    }
+                },
+            }
+        }
+
+        // Gossip leaves the topic once the subscription is dropped
+        drop(receiver);
+        info!(topic_hash = %topic_hash, "Stopped listening for gossip messages");
+        if let Some(done) = left {
+            let _ = done.send(());
+        }
+    }
+
+    // Delivers a received frame, forwarded by the neighbor `delivered_from`,
+    // to the subscriptions, returning the node that verifiably sent it
+    async fn handle_payload(&self, bytes: &[u8], delivered_from: NodeId) -> Option<NodeId> {
+        // Messages failing to authenticate with the topic key are never shown
+        let envelope = match wire::decode(bytes, &self.key) {
+            Ok(envelope) => envelope,
+            Err(e) => {
+                // Gossip forwards frames unchecked, so whoever delivered it
+                // need not have written it
+                warn!(delivered_from = %delivered_from.fmt_short(), "Dropping undecodable gossip message: {}", e);
+                return None;
+            }
+        };
+        let sender = envelope.sender;
+        {
+            let now = Instant::now();
+            let mut reputation = self.reputation.lock().unwrap();
+            if reputation.is_blocked(&sender, now) {
+                trace!(sender = %sender.fmt_short(), "Dropping message of blocked peer");
+                return None;
+            }
+            reputation.count_frame(sender, now);
+        }
+        if !self.access.lock().unwrap().admits(Some(sender)) {
+            warn!(
+                sender = %sender.fmt_short(),
+                "Dropping message of a node not on the allowlist"
+            );
+            return None;
+        }
+
+        match envelope.payload {
+            Payload::Chat(mut message) => {
+                if message.topic_hash != self.topic_hash {
+                    warn!(
+                        message_id = %message.id,
+                        "Dropping message addressed to another topic"
+                    );
+                    return None;
+                }
+                if self.replays.lock().unwrap().is_replay(&message.id, sender) {
+                    warn!(message_id = %message.id, "Dropping message replayed by another node");
+                    self.penalize(sender, Offense::Replay);
+                    return None;
+                }
+                if !self.clock.observe(message.lamport) {
+                    warn!(message_id = %message.id, "Dropping message with an implausible Lamport timestamp");
+                    return None;
+                }
+                message.verified_sender = Some(sender);
+                let ack = Ack {
+                    topic_hash: self.topic_hash.clone(),
+                    message_id: message.id.clone(),
+                };
+                let filtered = self
+                    .filters
+                    .lock()
+                    .unwrap()
+                    .apply(&self.topic_hash, &message.content);
+                match filtered {
+                    Some(content) => {
+                        message.content = content;
+                        self.events.emit_message(message);
+                    }
+                    None => debug!(message_id = %message.id, "Hiding filtered message"),
+                }
+                self.send_ack(ack).await;
+            }
+            Payload::Edit(mut edit) => {
+                if edit.topic_hash != self.topic_hash {
+                    warn!(
+                        target_id = %edit.target_id,
+                        "Dropping edit addressed to another topic"
+                    );
+                    return None;
+                }
+                let filtered = self
+                    .filters
+                    .lock()
+                    .unwrap()
+                    .apply(&self.topic_hash, &edit.new_content);
+                let Some(new_content) = filtered else {
+                    debug!(target_id = %edit.target_id, "Dropping filtered edit");
+                    return None;
+                };
+                edit.new_content = new_content;
+                self.events.emit(ClientEvent::Edit {
+                    edit,
+                    sender: Some(sender),
+                });
+            }
+            Payload::Delete(delete) => {
+                if delete.topic_hash != self.topic_hash {
+                    warn!(
+                        target_id = %delete.target_id,
+                        "Dropping delete addressed to another topic"
+                    );
+                    return None;
+                }
+                self.events.emit(ClientEvent::Delete {
+                    delete,
+                    sender: Some(sender),
+                });
+            }
+            Payload::Heartbeat(heartbeat) => {
+                if heartbeat.topic_hash != self.topic_hash {
+                    warn!("Dropping heartbeat addressed to another topic");
+                    return None;
+                }
+                trace!(sender = %sender.fmt_short(), "Received heartbeat");
+            }
+            Payload::Ack(ack) => {
+                if ack.topic_hash != self.topic_hash {
+                    warn!("Dropping acknowledgment addressed to another topic");
+                    return None;
+                }
+                self.acknowledge(ack, sender);
+            }
+            Payload::Presence(presence) => {
+                if presence.topic_hash != self.topic_hash {
+                    warn!("Dropping presence addressed to another topic");
+                    return None;
+                }
+                // Nobody may announce the presence of another node
+                if sender != presence.node_id {
+                    warn!(node_id = %presence.node_id, "Dropping presence sent by another node");
+                    self.penalize(sender, Offense::Impersonation);
+                    return None;
+                }
+                self.events.emit(ClientEvent::Presence(presence));
+            }
+            Payload::Profile(profile) => {
+                if profile.topic_hash != self.topic_hash {
+                    warn!("Dropping profile addressed to another topic");
+                    return None;
+                }
+                // Nobody may announce the profile of another node
+                if sender != profile.node_id {
+                    warn!(node_id = %profile.node_id, "Dropping profile sent by another node");
+                    self.penalize(sender, Offense::Impersonation);
+                    return None;
+                }
+                self.events.emit(ClientEvent::Profile(profile));
+            }
+            Payload::Avatar(avatar) => {
+                if avatar.topic_hash != self.topic_hash {
+                    warn!("Dropping avatar addressed to another topic");
+                    return None;
+                }
+                // Nobody may announce the avatar of another node
+                if sender != avatar.node_id {
+                    warn!(node_id = %avatar.node_id, "Dropping avatar sent by another node");
+                    self.penalize(sender, Offense::Impersonation);
+                    return None;
+                }
+                #[cfg(feature = "blobs")]
+                self.fetch_avatar(avatar);
+            }
+            Payload::Snapshot(snapshot) => {
+                if snapshot.topic_hash != self.topic_hash {
+                    warn!("Dropping snapshot addressed to another topic");
+                    return None;
+                }
+                // Nobody may offer the history on behalf of another node
+                if sender != snapshot.node_id {
+                    warn!(node_id = %snapshot.node_id, "Dropping snapshot sent by another node");
+                    self.penalize(sender, Offense::Impersonation);
+                    return None;
+                }
+                #[cfg(feature = "blobs")]
+                if snapshot.recipient == self.secret_key.public() {
+                    self.import_snapshot(snapshot);
+                }
+            }
+            Payload::Task(update) => {
+                if update.topic_hash != self.topic_hash {
+                    warn!("Dropping task update addressed to another topic");
+                    return None;
+                }
+                if !self.clock.observe(update.lamport) {
+                    warn!("Dropping task update with an implausible Lamport timestamp");
+                    return None;
+                }
+                // Ties between updates are broken by their signer
+                self.events.emit(ClientEvent::Task { update, sender });
+            }
+            Payload::Event(event) => {
+                if event.topic_hash != self.topic_hash {
+                    warn!("Dropping event addressed to another topic");
+                    return None;
+                }
+                // Only the node that sent the message may attach an event
+                self.events.emit(ClientEvent::Calendar { event, sender });
+            }
+            Payload::Rsvp(rsvp) => {
+                if rsvp.topic_hash != self.topic_hash {
+                    warn!("Dropping answer addressed to another topic");
+                    return None;
+                }
+                if !self.clock.observe(rsvp.lamport) {
+                    warn!("Dropping answer with an implausible Lamport timestamp");
+                    return None;
+                }
+                // Answers count per signer
+                self.events.emit(ClientEvent::Rsvp { rsvp, sender });
+            }
+            Payload::Attachment(attachment) => {
+                if attachment.topic_hash != self.topic_hash {
+                    warn!("Dropping attachment addressed to another topic");
+                    return None;
+                }
+                // Nobody may offer a file on behalf of another node
+                if sender != attachment.node_id {
+                    warn!(node_id = %attachment.node_id, "Dropping attachment sent by another node");
+                    self.penalize(sender, Offense::Impersonation);
+                    return None;
+                }
+                self.events.emit(ClientEvent::Attachment(attachment));
+            }
+            Payload::Allowlist(allowlist) => {
+                if allowlist.topic_hash != self.topic_hash {
+                    warn!("Dropping allowlist addressed to another topic");
+                    return None;
+                }
+                if !self.clock.observe(allowlist.lamport) {
+                    warn!("Dropping allowlist with an implausible Lamport timestamp");
+                    return None;
+                }
+                match self.access.lock().unwrap().accept(allowlist, Some(sender)) {
+                    Ok(true) => info!("The creator changed who takes part in the topic"),
+                    Ok(false) => trace!("Ignoring outdated allowlist"),
+                    Err(e) => {
+                        warn!("Dropping allowlist: {}", e);
+                        self.penalize(sender, Offense::Impersonation);
+                        return None;
+                    }
+                }
+            }
+            Payload::SyncRequest(request) => {
+                if request.topic_hash != self.topic_hash {
+                    warn!("Dropping sync request addressed to another topic");
+                    return None;
+                }
+                if let Err(e) = self.answer_sync_request(&request).await {
+                    warn!("Failed to answer sync request: {}", e);
+                }
+            }
+        }
+
+        Some(sender)
+    }
+
+    // Charges `node` for misbehaving on this topic
+    fn penalize(&self, node: NodeId, offense: Offense) {
+        debug!(node_id = %node.fmt_short(), ?offense, "Peer misbehaved");
+        self.reputation
+            .lock()
+            .unwrap()
+            .penalize(node, offense, Instant::now());
+    }
+
+    // Tells the user about what failed while sending on their behalf
+    fn fail(&self, error: String) {
+        warn!("{}", error);
+        self.events.emit(ClientEvent::Error {
+            topic_hash: self.topic_hash.clone(),
+            error,
+        });
+    }
+
+    // Tells the user about messages that could not be received
+    fn report(&self, error: String) {
+        self.fail(format!("A message could not be received: {}", error));
+    }
+
+    fn topic(&self) -> Result<TopicHandle, String> {
+        self.topics
+            .lock()
+            .unwrap()
+            .get(&self.topic_hash)
+            .cloned()
+            .ok_or_else(|| "Not subscribed to the topic".to_string())
+    }
+
+    async fn send_heartbeat(&self) -> Result<(), String> {
+        let topic = self.topic()?;
+        let heartbeat = Heartbeat {
+            topic_hash: self.topic_hash.clone(),
+            sent_at: Utc::now(),
+        };
+        publish_to(&topic, &self.secret_key, &Payload::Heartbeat(heartbeat)).await
+    }
+
+    // Tells the sender of a chat message that we received it
+    async fn send_ack(&self, ack: Ack) {
+        let result = match self.topic() {
+            Ok(topic) => broadcast_to(&topic, &self.secret_key, &Payload::Ack(ack)).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            warn!("Failed to acknowledge message: {}", e);
+        }
+    }
+
+    // Records that `member` received one of our messages, marking it as
+    // delivered the first time it is acknowledged
+    fn acknowledge(&self, ack: Ack, member: NodeId) {
+        let Ok(topic) = self.topic() else {
+            return;
+        };
+        if topic
+            .deliveries
+            .lock()
+            .unwrap()
+            .acknowledge_by(&ack.message_id, member, Instant::now())
+        {
+            debug!(message_id = %ack.message_id, "Message delivered");
+            self.events.emit(ClientEvent::Delivery {
+                topic_hash: ack.topic_hash,
+                message_id: ack.message_id,
+                state: DeliveryState::Delivered,
+            });
+        }
+    }
+
+    // Sends again our messages whose acknowledgment timed out, and gives up
+    // on those sent too often
+    async fn redeliver(&self) {
+        let Ok(topic) = self.topic() else {
+            return;
+        };
+        let due = topic.deliveries.lock().unwrap().due(Instant::now());
+
+        for message in due.resend {
+            debug!(message_id = %message.id, "Sending unacknowledged message again");
+            let message_id = message.id.clone();
+            if let Err(e) = broadcast_to(&topic, &self.secret_key, &Payload::Chat(message)).await {
+                self.fail(format!("Failed to send message again: {}", e));
+                continue;
+            }
+            let neighbors = topic.neighbors.lock().unwrap().clone();
+            topic
+                .deliveries
+                .lock()
+                .unwrap()
+                .sent_to(&message_id, neighbors);
+        }
+        for message_id in due.failed {
+            warn!(message_id = %message_id, "Message was never acknowledged");
+            self.events.emit(ClientEvent::Delivery {
+                topic_hash: self.topic_hash.clone(),
+                message_id,
+                state: DeliveryState::Failed,
+            });
+        }
+    }
+
+    // Sends the messages queued while no neighbor was connected
+    async fn flush_queue(&self) {
+        let Ok(topic) = self.topic() else {
+            return;
+        };
+        let queued = topic.deliveries.lock().unwrap().take_queued();
+        if !queued.is_empty() {
+            info!(messages = queued.len(), "Sending queued messages");
+        }
+        for message in queued {
+            let message_id = message.id.clone();
+            if let Err(e) = publish_to(&topic, &self.secret_key, &Payload::Chat(message)).await {
+                self.fail(format!("Failed to send queued message: {}", e));
+                continue;
+            }
+            self.events.emit(ClientEvent::Delivery {
+                topic_hash: self.topic_hash.clone(),
+                message_id,
+                state: DeliveryState::Pending,
+            });
+        }
+    }
+
+    // Tells the other members who we are and whether we are around
+    async fn announce_presence(&self) {
+        let Some((author, status)) = self.presence.lock().unwrap().clone() else {
+            return;
+        };
+        let presence = Presence {
+            topic_hash: self.topic_hash.clone(),
+            author,
+            node_id: self.secret_key.public(),
+            status,
+        };
+        let result = match self.topic() {
+            Ok(topic) => broadcast_to(&topic, &self.secret_key, &Payload::Presence(presence)).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            warn!("Failed to announce presence: {}", e);
+        }
+    }
+
+    // Tells the other members how the user wants to be shown
+    async fn announce_profile(&self) {
+        let Some((profile, image)) = self.profile.lock().unwrap().clone() else {
+            return;
+        };
+        let node_id = self.secret_key.public();
+        let profile = Payload::Profile(Profile {
+            topic_hash: self.topic_hash.clone(),
+            node_id,
+            profile,
+        });
+        let avatar = Payload::Avatar(Avatar {
+            topic_hash: self.topic_hash.clone(),
+            node_id,
+            image,
+        });
+        let result = match self.topic() {
+            Ok(topic) => match broadcast_to(&topic, &self.secret_key, &profile).await {
+                Ok(()) => broadcast_to(&topic, &self.secret_key, &avatar).await,
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            warn!("Failed to announce profile: {}", e);
+        }
+    }
+
+    // Tells the other members whom we allow to take part, when we created
+    // the topic and restricted it
+    async fn announce_allowlist(&self) {
+        let allowlist = {
+            let access = self.access.lock().unwrap();
+            if access.creator() != Some(self.secret_key.public()) {
+                return;
+            }
+            let Some(allowlist) = access.allowlist() else {
+                return;
+            };
+            allowlist.clone()
+        };
+        let result = match self.topic() {
+            Ok(topic) => {
+                broadcast_to(&topic, &self.secret_key, &Payload::Allowlist(allowlist)).await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            warn!("Failed to announce allowlist: {}", e);
+        }
+    }
+
+    // Fetches an announced avatar image from the node announcing it, and
+    // tells the application once it can be shown
+    #[cfg(feature = "blobs")]
+    fn fetch_avatar(&self, avatar: Avatar) {
+        let Some(image) = avatar.image else {
+            self.events.emit(ClientEvent::Avatar(avatar));
+            return;
+        };
+        let endpoint = self.endpoint.clone();
+        let blobs = self.blobs.clone();
+        let events = self.events.clone();
+        tokio::spawn(async move {
+            match blobs::fetch(&endpoint, &blobs, avatar.node_id, image).await {
+                Ok(_) => events.emit(ClientEvent::Avatar(avatar)),
+                Err(e) => warn!(node_id = %avatar.node_id, "Failed to fetch avatar: {}", e),
+            }
+        });
+    }
+
+    // Fetches a snapshot sent to us and delivers its messages
+    #[cfg(feature = "blobs")]
+    fn import_snapshot(&self, snapshot: Snapshot) {
+        let endpoint = self.endpoint.clone();
+        let blobs = self.blobs.clone();
+        let key = self.key.clone();
+        let clock = self.clock.clone();
+        let filters = self.filters.clone();
+        let own_node_id = self.secret_key.public();
+        let events = self.events.clone();
+        tokio::spawn(async move {
+            let imported = blobs::fetch(&endpoint, &blobs, snapshot.node_id, snapshot.blob)
+                .await
+                .and_then(|sealed| snapshot::unpack(&sealed, &key, &snapshot.topic_hash));
+            match imported {
+                Ok(messages) => {
+                    let count = messages.len();
+                    for mut message in messages {
+                        if !clock.observe(message.lamport) {
+                            warn!(message_id = %message.id, "Skipping message with an implausible Lamport timestamp");
+                            continue;
+                        }
+                        let filtered = filters
+                            .lock()
+                            .unwrap()
+                            .apply(&message.topic_hash, &message.content);
+                        if let Some(content) = filtered {
+                            message.content = content;
+                            events.emit_message(message);
+                        }
+                    }
+                    info!(node_id = %snapshot.node_id, count, "History imported");
+                    events.emit_message(system_message(
+                        &snapshot.topic_hash,
+                        format!(
+                            "Imported {} message(s) sent by node {}",
+                            count,
+                            snapshot.node_id.fmt_short()
+                        ),
+                        clock.tick(),
+                        own_node_id,
+                    ));
+                }
+                Err(e) => {
+                    warn!(node_id = %snapshot.node_id, "Failed to import history: {}", e);
+                    events.emit(ClientEvent::Error {
+                        topic_hash: snapshot.topic_hash,
+                        error: format!(
+                            "History sent by another member could not be imported: {}",
+                            e
+                        ),
+                    });
+                }
+            }
+        });
+    }
+
+    // Asks the other members for what they published since `since`, once we
+    // are connected to some of them again after a resync
+    async fn send_sync_request(&self, since: Option<DateTime<Utc>>) {
+        let Some(since) = since else {
+            return;
+        };
+        info!(%since, "Requesting messages missed while disconnected");
+        let request = SyncRequest {
+            topic_hash: self.topic_hash.clone(),
+            since,
+        };
+        let result = match self.topic() {
+            Ok(topic) => {
+                broadcast_to(&topic, &self.secret_key, &Payload::SyncRequest(request)).await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            warn!("Failed to send sync request: {}", e);
+        }
+    }
+
+    // Sends again everything we published since the requested time
+    async fn answer_sync_request(&self, request: &SyncRequest) -> Result<(), String> {
+        let topic = self.topic()?;
+        let payloads = topic.outbox.lock().unwrap().since(request.since);
+        debug!(count = payloads.len(), "Answering sync request");
+        for payload in payloads {
+            broadcast_to(&topic, &self.secret_key, &payload).await?;
+        }
+        Ok(())
+    }
+
+    // Remembers the gossip neighbors our messages are handed to
+    fn set_neighbors(&self, receiver: &Option<GossipReceiver>) {
+        if let (Ok(topic), Some(receiver)) = (self.topic(), receiver) {
+            *topic.neighbors.lock().unwrap() = receiver.neighbors().collect();
+        }
+    }
+
+    // Tells the application how reconnecting to the topic goes
+    fn report_reconnect(&self, state: ReconnectState) {
+        info!(?state, "Reconnecting to topic");
+        self.events.emit(ClientEvent::Reconnect {
+            topic_hash: self.topic_hash.clone(),
+            state,
+        });
+    }
+
+    // Subscribes to the topic again, bootstrapping from the members seen
+    // before, and replaces the sender used for publishing to it
+    fn resubscribe(&self, bootstrap: Vec<NodeId>) -> Result<GossipReceiver, String> {
+        info!(peers = bootstrap.len(), "Resubscribing to topic");
+        let (sender, receiver) = self
+            .gossip
+            .subscribe(self.topic_id, bootstrap)
+            .map_err(|e| format!("Failed to subscribe to topic: {}", e))?
+            .split();
+        if let Some(topic) = self.topics.lock().unwrap().get_mut(&self.topic_hash) {
+            topic.sender = sender;
+        }
+        Ok(receiver)
+    }
+}
+
+// The next event of the subscription, never coming without one
+async fn next_event(
+    receiver: &mut Option<GossipReceiver>,
+) -> Option<<GossipReceiver as futures::Stream>::Item> {
+    match receiver {
+        Some(receiver) => receiver.next().await,
+        None => std::future::pending().await,
+    }
+}
+
+// A message from the application itself, shown only to the local user
+fn system_message(topic_hash: &str, content: String, lamport: u64, node_id: NodeId) -> ChatMessage {
+    ChatMessage {
+        id: Uuid::new_v4().to_string(),
+        author: "System".to_string(),
+        content,
+        timestamp: Utc::now(),
+        topic_hash: topic_hash.to_string(),
+        sequence: 0,
+        lamport,
+        verified_sender: Some(node_id),
+        edited_at: None,
+        deleted_at: None,
+    }
+}
+
+// Hooks for simulating traffic from remote peers without a network, so the
+// update loop can be tested deterministically
+#[cfg(any(test, feature = "testing"))]
+impl IrohClient {
+    /// Delivers `message` to the subscriptions as if it was received from
+    /// a peer on its topic.
+    pub fn inject_incoming(&self, message: ChatMessage) {
+        self.inject_event(ClientEvent::Message(message));
+    }
+
+    /// Delivers `event` to the subscriptions as if it happened on the
+    /// network.
+    pub fn inject_event(&self, event: ClientEvent) {
+        if let ClientEvent::Message(message) = &event {
+            let _ = self.clock.observe(message.lamport);
+        }
+        self.events.emit(event);
+    }
+}
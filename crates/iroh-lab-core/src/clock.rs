@@ -0,0 +1,55 @@
+//! Lamport clock ordering chat messages across peers.
+//!
+//! Wall clocks of different peers drift, and per-sender sequence numbers say
+//! nothing about how messages of different senders relate. A Lamport clock
+//! guarantees that a message is always stamped later than every message its
+//! sender had seen when writing it, so replies never sort before the message
+//! they answer.
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Largest timestamp accepted from other peers. Honest peers never get
+/// anywhere near it, while it leaves room for any number of local events.
+pub const MAX_REMOTE_TIMESTAMP: u64 = 1 << 48;
+
+/// A Lamport clock shared between all clones of a client.
+#[derive(Clone, Debug, Default)]
+pub struct LamportClock {
+    time: Arc<AtomicU64>,
+}
+
+impl LamportClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advances the clock for a local event and returns its timestamp.
+    pub fn tick(&self) -> u64 {
+        // Saturates instead of wrapping around to the start
+        let previous = self
+            .time
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |time| {
+                Some(time.saturating_add(1))
+            })
+            .unwrap_or_else(|time| time);
+        previous.saturating_add(1)
+    }
+
+    /// Moves the clock past a timestamp received from another peer.
+    ///
+    /// Timestamps above [`MAX_REMOTE_TIMESTAMP`] are implausible and leave
+    /// the clock alone, returning `false`, so a single peer cannot push the
+    /// clock to its end.
+    pub fn observe(&self, remote: u64) -> bool {
+        if remote > MAX_REMOTE_TIMESTAMP {
+            return false;
+        }
+        self.time.fetch_max(remote, Ordering::SeqCst);
+        true
+    }
+
+    /// The timestamp of the latest event seen by this clock.
+    pub fn now(&self) -> u64 {
+        self.time.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,84 @@
+//! Slash commands typed into the composer.
+//!
+//! A message starting with `/` is a command handled on this node instead of
+//! being sent, e.g. `/nick alice`. Starting it with `//` sends it as a
+//! message beginning with a single `/`.
+
+/// A command typed into the composer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlashCommand {
+    /// Changes the user's name.
+    Nick(String),
+    /// Goes back to the main menu.
+    Leave,
+    /// Sends an action, e.g. `/me waves` as "*alice waves*".
+    Me(String),
+    /// Clears the messages shown in the chat room.
+    Clear,
+    /// Copies an invite to the topic.
+    Invite,
+    /// Hides the messages of a member.
+    Mute(String),
+    /// Shows the messages of a muted member again.
+    Unmute(String),
+    /// Sends a message starting with `/`, typed with a second `/`.
+    Say(String),
+}
+
+/// Every command, as typed, with what it does.
+pub const COMMANDS: [(&str, &str); 7] = [
+    ("/nick <name>", "Change your name"),
+    ("/leave", "Go back to the main menu"),
+    ("/me <action>", "Send an action, e.g. /me waves"),
+    ("/clear", "Clear the messages shown here"),
+    ("/invite", "Copy an invite to this topic"),
+    ("/mute <member>", "Hide the messages of a member"),
+    ("/unmute <member>", "Show the messages of a member again"),
+];
+
+/// The command typed as `input`, or None if it is a message. Unknown
+/// commands and missing arguments are errors.
+pub fn parse(input: &str) -> Option<Result<SlashCommand, String>> {
+    let command = input.trim_start().strip_prefix('/')?;
+    if command.starts_with('/') {
+        return Some(Ok(SlashCommand::Say(command.to_string())));
+    }
+    let (name, argument) = command
+        .split_once(char::is_whitespace)
+        .unwrap_or((command, ""));
+    let argument = argument.trim().to_string();
+    let needs = |what: &str| format!("/{} needs {}", name, what);
+    Some(match (name.to_lowercase().as_str(), argument.is_empty()) {
+        ("nick", false) => Ok(SlashCommand::Nick(argument)),
+        ("nick", true) => Err(needs("a name")),
+        ("leave", _) => Ok(SlashCommand::Leave),
+        ("me", false) => Ok(SlashCommand::Me(argument)),
+        ("me", true) => Err(needs("an action")),
+        ("clear", _) => Ok(SlashCommand::Clear),
+        ("invite", _) => Ok(SlashCommand::Invite),
+        ("mute", false) => Ok(SlashCommand::Mute(argument)),
+        ("unmute", false) => Ok(SlashCommand::Unmute(argument)),
+        ("mute" | "unmute", true) => Err(needs("the name of a member")),
+        _ => Err(format!("There is no command /{}", name)),
+    })
+}
+
+/// The commands starting like the one typed as `input`, to show while it is
+/// typed. Empty while typing a message.
+pub fn help(input: &str) -> Vec<(&'static str, &'static str)> {
+    let Some(typed) = input.trim_start().strip_prefix('/') else {
+        return Vec::new();
+    };
+    if typed.starts_with('/') {
+        return Vec::new();
+    }
+    let name = typed.split(char::is_whitespace).next().unwrap_or_default();
+    COMMANDS
+        .into_iter()
+        .filter(|(usage, _)| {
+            let command = usage[1..].split(' ').next().unwrap_or_default();
+            command.starts_with(&name.to_lowercase())
+                && (typed.len() == name.len() || command == name.to_lowercase())
+        })
+        .collect()
+}
@@ -0,0 +1,171 @@
+//! The text of the message composer.
+//!
+//! The composer is a single line input, so the line breaks of a message are
+//! shown as [`LINE_BREAK`] marks in it. Shift+Enter adds a line break, as
+//! does typing or pasting the mark, and deleting a mark joins the lines
+//! again.
+//!
+//! Like the prompt of a shell, the composer keeps an [`InputHistory`] of the
+//! messages sent on each topic, recalled with the Up and Down keys to send
+//! them again or change them first. Tab completes the name being typed
+//! with a [`NameCompletion`], cycling through the matching names.
+use std::collections::{HashMap, VecDeque};
+
+/// Messages kept in the history of each topic.
+pub const HISTORY_LIMIT: usize = 50;
+
+/// Stands for a line break of the message in the composer.
+pub const LINE_BREAK: char = '↵';
+
+/// How `message` is shown in the composer.
+pub fn to_input(message: &str) -> String {
+    message
+        .replace("\r\n", "\n")
+        .replace('\n', &LINE_BREAK.to_string())
+}
+
+/// The message typed into the composer as `input`, with pasted line
+/// breaks and marks turned into line breaks.
+pub fn from_input(input: &str) -> String {
+    input
+        .replace("\r\n", "\n")
+        .replace(['\r', LINE_BREAK], "\n")
+}
+
+/// The messages sent on each topic, the latest last, and which one is
+/// recalled into the composer.
+#[derive(Debug, Clone, Default)]
+pub struct InputHistory {
+    // Sent messages by topic hash, at most HISTORY_LIMIT each
+    topics: HashMap<String, VecDeque<String>>,
+    // How far back the recalled message is, 1 being the latest, with what
+    // was typed before recalling
+    recalled: Option<(usize, String)>,
+}
+
+impl InputHistory {
+    /// Adds a message sent on `topic_hash`, dropping the oldest one beyond
+    /// [`HISTORY_LIMIT`]. Blank messages and repeats of the latest one are
+    /// not kept.
+    pub fn push(&mut self, topic_hash: &str, message: &str) {
+        self.recalled = None;
+        if message.trim().is_empty() {
+            return;
+        }
+        let messages = self.topics.entry(topic_hash.to_string()).or_default();
+        if messages.back().map(String::as_str) == Some(message) {
+            return;
+        }
+        messages.push_back(message.to_string());
+        if messages.len() > HISTORY_LIMIT {
+            messages.pop_front();
+        }
+    }
+
+    /// The message sent before the one recalled, or the latest one if none
+    /// is, keeping `draft` to return to. None past the oldest message.
+    pub fn previous(&mut self, topic_hash: &str, draft: &str) -> Option<String> {
+        let messages = self.topics.get(topic_hash)?;
+        let (back, draft) = match self.recalled.take() {
+            Some((back, draft)) => (back + 1, draft),
+            None => (1, draft.to_string()),
+        };
+        let back = back.min(messages.len());
+        self.recalled = Some((back, draft));
+        messages.get(messages.len().checked_sub(back)?).cloned()
+    }
+
+    /// The message sent after the one recalled, or what was typed before
+    /// recalling past the latest one. None if no message is recalled.
+    pub fn next(&mut self, topic_hash: &str) -> Option<String> {
+        let (back, draft) = self.recalled.take()?;
+        if back <= 1 {
+            return Some(draft);
+        }
+        let messages = self.topics.get(topic_hash)?;
+        self.recalled = Some((back - 1, draft));
+        messages.get(messages.len().checked_sub(back - 1)?).cloned()
+    }
+
+    /// Stops recalling, e.g. as the recalled message is changed.
+    pub fn reset(&mut self) {
+        self.recalled = None;
+    }
+}
+
+/// Completes the name typed at the end of the composer, cycling through the
+/// matching names on repeated completions.
+#[derive(Debug, Clone, Default)]
+pub struct NameCompletion {
+    cycle: Option<Cycle>,
+}
+
+// The names matching what was typed, and which one was completed
+#[derive(Debug, Clone)]
+struct Cycle {
+    // The input before the completed name
+    before: String,
+    candidates: Vec<String>,
+    index: usize,
+    // The input the last completion left, telling a repeated completion
+    completed: String,
+}
+
+impl Cycle {
+    fn input(&self) -> String {
+        format!("{}{} ", self.before, self.candidates[self.index])
+    }
+}
+
+impl NameCompletion {
+    /// The input with the word it ends in completed to the first of `names`
+    /// starting with it, ignoring case, followed by a space. Completing the
+    /// input left by the last completion replaces the name with the next
+    /// match instead. None if no name matches.
+    pub fn complete(&mut self, input: &str, names: &[String]) -> Option<String> {
+        if let Some(cycle) = &mut self.cycle {
+            if cycle.completed == input {
+                cycle.index = (cycle.index + 1) % cycle.candidates.len();
+                cycle.completed = cycle.input();
+                return Some(cycle.completed.clone());
+            }
+        }
+        self.cycle = None;
+
+        let start = input
+            .char_indices()
+            .rfind(|(_, c)| c.is_whitespace())
+            .map_or(0, |(space, c)| space + c.len_utf8());
+        let (before, word) = input.split_at(start);
+        if word.is_empty() {
+            return None;
+        }
+        let word = word.to_lowercase();
+        let mut candidates: Vec<String> = names
+            .iter()
+            .filter(|name| !name.trim().is_empty() && name.to_lowercase().starts_with(&word))
+            .cloned()
+            .collect();
+        candidates.sort_by_key(|name| name.to_lowercase());
+        candidates.dedup();
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let mut cycle = Cycle {
+            before: before.to_string(),
+            candidates,
+            index: 0,
+            completed: String::new(),
+        };
+        cycle.completed = cycle.input();
+        let completed = cycle.completed.clone();
+        self.cycle = Some(cycle);
+        Some(completed)
+    }
+
+    /// Stops cycling, e.g. as the input is changed.
+    pub fn reset(&mut self) {
+        self.cycle = None;
+    }
+}
@@ -0,0 +1,655 @@
+//! Settings persisted between runs of the application.
+//!
+//! The configuration is stored as JSON in the configuration directory of the
+//! current user, e.g. `~/.config/iroh-lab/config.json` on Linux. Settings
+//! missing from the file take their default value, so files written by older
+//! releases keep loading.
+use crate::automation::ExportRule;
+use crate::blobs::BlobHash;
+use crate::client::ChatMessage;
+use crate::filter::ContentFilter;
+use crate::profile::UserProfile;
+use crate::wire;
+use iroh::NodeId;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// Directory below the user's configuration directory holding our files
+const APP_DIR: &str = "iroh-lab";
+
+// Name of the configuration file
+const CONFIG_FILE: &str = "config.json";
+
+/// User settings of the chat application.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Username entered last, offered on the welcome screen.
+    pub username: String,
+    /// Whether the user finished or skipped the guided tour.
+    pub tour_completed: bool,
+    /// Hashes of the topics whose messages are archived.
+    pub archived_topics: BTreeSet<String>,
+    /// Hashes of the topics on hold, whose archives are never pruned.
+    pub held_topics: BTreeSet<String>,
+    /// Profile announced on every topic.
+    pub profile: UserProfile,
+    /// Blob of the avatar image announced with the profile.
+    pub avatar_image: Option<BlobHash>,
+    /// Scheduled exports by topic hash.
+    pub export_rules: BTreeMap<String, ExportRule>,
+    /// Templates of the messages welcoming newcomers, by topic hash.
+    pub welcome_templates: BTreeMap<String, String>,
+    /// Files kept holding the upcoming calendar events of a topic, by topic
+    /// hash.
+    pub calendar_feeds: BTreeMap<String, PathBuf>,
+    /// Folders shared with a topic, by topic hash.
+    pub folder_bridges: BTreeMap<String, PathBuf>,
+    /// Members whose messages are hidden.
+    pub muted_members: BTreeSet<NodeId>,
+    /// Names whose messages are hidden, whichever node sends under them,
+    /// as given by [`muted_name`].
+    pub muted_authors: BTreeSet<String>,
+    /// Words and expressions filtered on every topic.
+    pub content_filter: ContentFilter,
+    /// Words and expressions filtered on a topic besides those filtered
+    /// everywhere, by topic hash.
+    pub topic_filters: BTreeMap<String, ContentFilter>,
+    /// Nodes allowed to take part in the private topics we created, besides
+    /// us, by topic hash.
+    pub allowlists: BTreeMap<String, BTreeSet<NodeId>>,
+    /// Lamport timestamps of the allowlists we published last, by topic
+    /// hash, so the lists published after a restart replace them.
+    pub allowlist_lamports: BTreeMap<String, u64>,
+    /// Hashes of the topics whose links open without asking.
+    pub trusted_topics: BTreeSet<String>,
+    /// Colors of the application.
+    pub theme: ThemePreference,
+    /// Which messages ask for attention while the window is in the
+    /// background.
+    pub notifications: Notifications,
+    /// Whether to reach peers through relay servers, or only on the local
+    /// network. Applies on the next start.
+    pub relay_mode: RelayMode,
+    /// Port, IP versions and interfaces the endpoint listens on. Applies on
+    /// the next start.
+    pub binding: Binding,
+    /// Most detailed log messages written.
+    pub log_level: LogLevel,
+    /// Key sending the message typed in the composer.
+    pub send_key: SendKey,
+    /// Whether sending a message longer than [`LARGE_MESSAGE_CHARS`] asks
+    /// for confirmation first.
+    pub large_messages: LargeMessages,
+    /// Whether message content is shown with its Markdown rendered.
+    pub message_format: MessageFormat,
+    /// Longest message that can be sent.
+    pub message_limit: MessageLimit,
+    /// Whether opening a link in a message asks for confirmation first.
+    pub link_confirmation: LinkConfirmation,
+    /// Emoji picked last in the composer, the latest first.
+    pub recent_emoji: Vec<String>,
+}
+
+/// Characters above which a message is large.
+pub const LARGE_MESSAGE_CHARS: usize = 2000;
+
+/// Most characters of a message that always fit into a frame, however many
+/// bytes each of them takes.
+pub const MAX_MESSAGE_CHARS: usize = wire::MAX_CONTENT_SIZE / 4;
+
+/// Which colors the application uses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemePreference {
+    Light,
+    Dark,
+    /// Light or dark, like the desktop.
+    #[default]
+    System,
+}
+
+impl ThemePreference {
+    /// Every preference, for choosing one.
+    pub const ALL: [Self; 3] = [Self::Light, Self::Dark, Self::System];
+}
+
+impl fmt::Display for ThemePreference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Light => "Light",
+            Self::Dark => "Dark",
+            Self::System => "Follow system",
+        })
+    }
+}
+
+impl Config {
+    /// Directory holding the files of the current user, if the platform has
+    /// a configuration directory.
+    pub fn directory() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(APP_DIR))
+    }
+
+    /// Location of the configuration file of the current user, if the
+    /// platform has a configuration directory.
+    pub fn default_path() -> Option<PathBuf> {
+        Self::directory().map(|dir| dir.join(CONFIG_FILE))
+    }
+
+    /// Loads the configuration of the current user, or the defaults if there
+    /// is none yet.
+    pub fn load() -> Result<Self, String> {
+        match Self::default_path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Saves the configuration for the current user.
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::default_path()
+            .ok_or_else(|| "No configuration directory on this platform".to_string())?;
+        self.save_to(&path)
+    }
+
+    /// Loads the configuration from `path`, or the defaults if the file does
+    /// not exist.
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    /// Whether `message` is hidden, as the node that signed it or the name
+    /// it was sent under is muted.
+    pub fn mutes(&self, message: &ChatMessage) -> bool {
+        message
+            .verified_sender
+            .is_some_and(|node_id| self.muted_members.contains(&node_id))
+            || self.mutes_author(&message.author)
+    }
+
+    /// Whether messages sent under `name` are hidden, whichever node sends
+    /// them.
+    pub fn mutes_author(&self, name: &str) -> bool {
+        let name = muted_name(name);
+        // Names saved by hand may not be normalized
+        self.muted_authors
+            .iter()
+            .any(|author| muted_name(author) == name)
+    }
+
+    /// Hides the messages sent under `name`, returning whether it was not
+    /// muted yet.
+    pub fn mute_author(&mut self, name: &str) -> bool {
+        let muted = self.mutes_author(name);
+        self.muted_authors.insert(muted_name(name));
+        !muted
+    }
+
+    /// Shows the messages sent under `name` again, returning whether it was
+    /// muted.
+    pub fn unmute_author(&mut self, name: &str) -> bool {
+        let name = muted_name(name);
+        let before = self.muted_authors.len();
+        self.muted_authors
+            .retain(|author| muted_name(author) != name);
+        self.muted_authors.len() < before
+    }
+
+    /// Allows `node_id` on the private topic `topic_hash`, returning its
+    /// allowlist, or `None` if the topic is open to everyone.
+    pub fn allow_node(&mut self, topic_hash: &str, node_id: NodeId) -> Option<BTreeSet<NodeId>> {
+        let members = self.allowlists.get_mut(topic_hash)?;
+        members.insert(node_id);
+        Some(members.clone())
+    }
+
+    /// Takes `node_id` off the allowlist of the private topic `topic_hash`,
+    /// returning what is left of it, or `None` if the topic is open to
+    /// everyone.
+    pub fn disallow_node(
+        &mut self,
+        topic_hash: &str,
+        node_id: &NodeId,
+    ) -> Option<BTreeSet<NodeId>> {
+        let members = self.allowlists.get_mut(topic_hash)?;
+        members.remove(node_id);
+        Some(members.clone())
+    }
+
+    /// Restricts the topic `topic_hash` to `members`.
+    pub fn make_private(&mut self, topic_hash: &str, members: BTreeSet<NodeId>) {
+        self.allowlists.insert(topic_hash.to_string(), members);
+    }
+
+    /// Opens the topic `topic_hash` to everyone, returning whether it was
+    /// private.
+    pub fn make_public(&mut self, topic_hash: &str) -> bool {
+        self.allowlists.remove(topic_hash).is_some()
+    }
+
+    /// Saves the configuration to `path`, creating its directory if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to encode configuration: {}", e))?;
+        std::fs::write(path, contents)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+/// `name` as muted names are kept and compared: trimmed and lowercase, in
+/// any script.
+pub fn muted_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Which messages of others ask for attention while the window is in the
+/// background.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Notifications {
+    All,
+    /// Messages mentioning the user's name.
+    #[default]
+    Mentions,
+    Off,
+}
+
+impl Notifications {
+    /// Every preference, for choosing one.
+    pub const ALL: [Self; 3] = [Self::All, Self::Mentions, Self::Off];
+
+    /// Whether a message with `content` asks for attention, for a user going
+    /// by any of `names`.
+    pub fn wants(self, content: &str, names: &[&str]) -> bool {
+        match self {
+            Self::All => true,
+            Self::Mentions => names.iter().any(|name| mentions(content, name)),
+            Self::Off => false,
+        }
+    }
+}
+
+// Whether `content` contains `name` as a whole word, ignoring case
+fn mentions(content: &str, name: &str) -> bool {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return false;
+    }
+    let content = content.to_lowercase();
+    content.match_indices(&name).any(|(start, _)| {
+        let before = content[..start].chars().next_back();
+        let after = content[start + name.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+impl fmt::Display for Notifications {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::All => "All messages",
+            Self::Mentions => "Mentions of my name",
+            Self::Off => "Off",
+        })
+    }
+}
+
+/// Whether to reach peers through relay servers, or only on the local
+/// network.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelayMode {
+    /// Use the public relays when no direct connection can be made.
+    #[default]
+    Default,
+    /// Only connect directly, e.g. on a local network.
+    Disabled,
+    /// Only connect directly, to the nodes found on the local network,
+    /// without any internet access.
+    Local,
+    /// Only connect through relays, e.g. on a locked-down network that
+    /// blocks direct connections.
+    Forced,
+}
+
+impl RelayMode {
+    /// Every mode, for choosing one.
+    pub const ALL: [Self; 4] = [Self::Default, Self::Disabled, Self::Local, Self::Forced];
+}
+
+impl From<RelayMode> for iroh::RelayMode {
+    fn from(mode: RelayMode) -> Self {
+        match mode {
+            RelayMode::Default | RelayMode::Forced => iroh::RelayMode::Default,
+            RelayMode::Disabled | RelayMode::Local => iroh::RelayMode::Disabled,
+        }
+    }
+}
+
+impl fmt::Display for RelayMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Default => "Use relays",
+            Self::Disabled => "Direct connections only",
+            Self::Local => "Local network only, without internet",
+            Self::Forced => "Relays only, without direct connections",
+        })
+    }
+}
+
+/// Where the endpoint listens for direct connections, so firewall rules
+/// can be written once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Binding {
+    /// UDP port listened on for both IP versions, or `0` for a random one.
+    /// A port already in use falls back to a random one.
+    pub port: u16,
+    /// IP versions other machines can connect over.
+    pub ip_version: IpVersion,
+    /// Address of the interface listened on for IPv4, instead of all of
+    /// them.
+    pub interface_v4: Option<Ipv4Addr>,
+    /// Address of the interface listened on for IPv6, instead of all of
+    /// them.
+    pub interface_v6: Option<Ipv6Addr>,
+}
+
+impl Binding {
+    /// Address the IPv4 socket of the endpoint is bound to, on the loopback
+    /// interface when only IPv6 is used.
+    pub fn addr_v4(&self) -> SocketAddrV4 {
+        let ip = match self.ip_version {
+            IpVersion::V6 => Ipv4Addr::LOCALHOST,
+            IpVersion::Both | IpVersion::V4 => self.interface_v4.unwrap_or(Ipv4Addr::UNSPECIFIED),
+        };
+        SocketAddrV4::new(ip, self.port)
+    }
+
+    /// Address the IPv6 socket of the endpoint is bound to, on the loopback
+    /// interface when only IPv4 is used.
+    pub fn addr_v6(&self) -> SocketAddrV6 {
+        let ip = match self.ip_version {
+            IpVersion::V4 => Ipv6Addr::LOCALHOST,
+            IpVersion::Both | IpVersion::V6 => self.interface_v6.unwrap_or(Ipv6Addr::UNSPECIFIED),
+        };
+        SocketAddrV6::new(ip, self.port, 0, 0)
+    }
+}
+
+/// IP versions other machines can connect over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IpVersion {
+    #[default]
+    Both,
+    V4,
+    V6,
+}
+
+impl IpVersion {
+    /// Every choice, for choosing one.
+    pub const ALL: [Self; 3] = [Self::Both, Self::V4, Self::V6];
+}
+
+impl fmt::Display for IpVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Both => "IPv4 and IPv6",
+            Self::V4 => "IPv4 only",
+            Self::V6 => "IPv6 only",
+        })
+    }
+}
+
+/// Most detailed log messages written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    #[default]
+    Trace,
+}
+
+impl LogLevel {
+    /// Every level, for choosing one.
+    pub const ALL: [Self; 5] = [
+        Self::Error,
+        Self::Warn,
+        Self::Info,
+        Self::Debug,
+        Self::Trace,
+    ];
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "error" => Ok(Self::Error),
+            "warn" => Ok(Self::Warn),
+            "info" => Ok(Self::Info),
+            "debug" => Ok(Self::Debug),
+            "trace" => Ok(Self::Trace),
+            _ => Err(format!(
+                "Unknown log level {}, expected error, warn, info, debug or trace",
+                s
+            )),
+        }
+    }
+}
+
+impl From<LogLevel> for tracing::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => tracing::Level::ERROR,
+            LogLevel::Warn => tracing::Level::WARN,
+            LogLevel::Info => tracing::Level::INFO,
+            LogLevel::Debug => tracing::Level::DEBUG,
+            LogLevel::Trace => tracing::Level::TRACE,
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "Errors",
+            Self::Warn => "Warnings",
+            Self::Info => "Info",
+            Self::Debug => "Debug",
+            Self::Trace => "Trace",
+        })
+    }
+}
+
+/// Key sending the message typed in the composer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SendKey {
+    #[default]
+    Enter,
+    /// Ctrl+Enter, with Enter breaking the line instead.
+    CtrlEnter,
+}
+
+impl SendKey {
+    /// Every key, for choosing one.
+    pub const ALL: [Self; 2] = [Self::Enter, Self::CtrlEnter];
+}
+
+impl fmt::Display for SendKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Enter => "Enter",
+            Self::CtrlEnter => "Ctrl+Enter",
+        })
+    }
+}
+
+/// Whether large messages are sent right away.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LargeMessages {
+    /// Ask before sending a large message.
+    #[default]
+    Confirm,
+    Send,
+}
+
+impl LargeMessages {
+    /// Every choice, for choosing one.
+    pub const ALL: [Self; 2] = [Self::Confirm, Self::Send];
+
+    /// Whether sending `content` asks for confirmation first.
+    pub fn confirms(self, content: &str) -> bool {
+        self == Self::Confirm && content.chars().count() > LARGE_MESSAGE_CHARS
+    }
+}
+
+impl fmt::Display for LargeMessages {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Confirm => "Ask before sending",
+            Self::Send => "Send right away",
+        })
+    }
+}
+
+/// Whether links in messages open right away.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkConfirmation {
+    /// Ask before opening any link.
+    Always,
+    /// Ask before opening links of topics not trusted in their settings.
+    #[default]
+    Untrusted,
+    Never,
+}
+
+impl LinkConfirmation {
+    /// Every choice, for choosing one.
+    pub const ALL: [Self; 3] = [Self::Always, Self::Untrusted, Self::Never];
+
+    /// Whether opening a link of a topic that is `trusted` or not asks for
+    /// confirmation first.
+    pub fn confirms(self, trusted: bool) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Untrusted => !trusted,
+            Self::Never => false,
+        }
+    }
+}
+
+impl fmt::Display for LinkConfirmation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Always => "Ask before opening",
+            Self::Untrusted => "Ask unless the topic is trusted",
+            Self::Never => "Open right away",
+        })
+    }
+}
+
+/// How message content is shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageFormat {
+    /// Bold, italics, code, lists and quotes rendered.
+    #[default]
+    Markdown,
+    /// Exactly as typed.
+    Raw,
+}
+
+impl MessageFormat {
+    /// Every format, for choosing one.
+    pub const ALL: [Self; 2] = [Self::Markdown, Self::Raw];
+}
+
+impl fmt::Display for MessageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Markdown => "Render Markdown",
+            Self::Raw => "Show raw text",
+        })
+    }
+}
+
+/// Longest message that can be sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageLimit {
+    /// 1,000 characters.
+    Short,
+    /// 4,000 characters.
+    Medium,
+    /// 20,000 characters.
+    #[default]
+    Long,
+    /// [`MAX_MESSAGE_CHARS`], as long as a frame allows.
+    Maximum,
+}
+
+impl MessageLimit {
+    /// Every limit, for choosing one.
+    pub const ALL: [Self; 4] = [Self::Short, Self::Medium, Self::Long, Self::Maximum];
+
+    /// Most characters of a message.
+    pub fn chars(self) -> usize {
+        match self {
+            Self::Short => 1_000,
+            Self::Medium => 4_000,
+            Self::Long => 20_000,
+            Self::Maximum => MAX_MESSAGE_CHARS,
+        }
+    }
+
+    /// How many more characters `content` may have, negative once it is
+    /// over the limit.
+    pub fn remaining(self, content: &str) -> i64 {
+        self.chars() as i64 - content.chars().count() as i64
+    }
+
+    /// Checks that `content` is within the limit.
+    pub fn check(self, content: &str) -> Result<(), String> {
+        match self.remaining(content) {
+            remaining if remaining < 0 => Err(format!(
+                "This message is {} characters over the limit of {}. Shorten it to send it",
+                -remaining,
+                self.chars()
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for MessageLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Maximum => write!(f, "As long as possible ({} characters)", self.chars()),
+            _ => write!(f, "{} characters", self.chars()),
+        }
+    }
+}
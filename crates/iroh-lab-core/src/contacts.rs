@@ -0,0 +1,98 @@
+//! The contacts book, kept between runs.
+//!
+//! A contact is a node the user wants to remember under a name, tagged with
+//! the topics it was met on. Contacts are stored as JSON next to the
+//! configuration, e.g. `~/.config/iroh-lab/contacts.json` on Linux.
+use crate::config::Config;
+use iroh::NodeId;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+// Name of the file holding the contacts
+const CONTACTS_FILE: &str = "contacts.json";
+
+/// A remembered node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contact {
+    pub name: String,
+    /// Names of the topics the contact was met on.
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+}
+
+/// The contacts of the user, by node.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contacts {
+    // Keyed by the node id as text, which JSON objects need
+    contacts: BTreeMap<String, Contact>,
+}
+
+impl Contacts {
+    /// Location of the contacts book of the current user, if the platform
+    /// has a configuration directory.
+    pub fn default_path() -> Option<PathBuf> {
+        Config::directory().map(|dir| dir.join(CONTACTS_FILE))
+    }
+
+    /// Loads the contacts from `path`, or none if the file does not exist.
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    /// Saves the contacts to `path`, creating its directory if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to encode contacts: {}", e))?;
+        std::fs::write(path, contents)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Saves the `members` of the topic `topic_name` as contacts, tagged
+    /// with the topic. Members already in the book keep their name and only
+    /// gain the tag. Returns how many contacts were added.
+    pub fn import<'a>(
+        &mut self,
+        members: impl IntoIterator<Item = (NodeId, &'a str)>,
+        topic_name: &str,
+    ) -> usize {
+        let mut added = 0;
+        for (node_id, name) in members {
+            let contact = self.contacts.entry(node_id.to_string()).or_insert_with(|| {
+                added += 1;
+                Contact {
+                    name: name.to_string(),
+                    tags: BTreeSet::new(),
+                }
+            });
+            contact.tags.insert(topic_name.to_string());
+        }
+        added
+    }
+
+    /// The contact on `node_id`, if any.
+    pub fn get(&self, node_id: &NodeId) -> Option<&Contact> {
+        self.contacts.get(&node_id.to_string())
+    }
+
+    /// Number of contacts.
+    pub fn len(&self) -> usize {
+        self.contacts.len()
+    }
+
+    /// Whether the book is empty.
+    pub fn is_empty(&self) -> bool {
+        self.contacts.is_empty()
+    }
+}
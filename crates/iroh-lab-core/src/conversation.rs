@@ -0,0 +1,114 @@
+//! The messages of a chat room, as every frontend shows them.
+//!
+//! Members send messages again to catch up those who missed them, and
+//! gossip delivers them in any order, so a [`Conversation`] drops the
+//! messages it already has and keeps the others in the order of their
+//! Lamport timestamps. It applies the edits and deletes of the messages,
+//! remembering deletes that overtook their message, and numbers the
+//! messages we send, which is all a frontend has to do besides showing them.
+use crate::client::{ChatMessage, ClientEvent, MessageDelete, MessageEdit};
+use crate::tombstone::Tombstones;
+use iroh::NodeId;
+use std::collections::HashSet;
+
+/// The messages of a chat room, oldest first.
+#[derive(Debug, Default)]
+pub struct Conversation {
+    messages: Vec<ChatMessage>,
+    // Ids of every message received, including those cleared from view
+    seen: HashSet<String>,
+    // Deletes received, for copies of their messages still to arrive
+    tombstones: Tombstones,
+    // Number of the last message we sent, across rooms
+    sequence: u64,
+}
+
+impl Conversation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The messages, in the order of their Lamport timestamps.
+    pub fn messages(&self) -> &[ChatMessage] {
+        &self.messages
+    }
+
+    /// The message with this id, if it is shown.
+    pub fn get(&self, id: &str) -> Option<&ChatMessage> {
+        self.messages.iter().find(|message| message.id == id)
+    }
+
+    /// The message with this id, for applying edits and deletes to it.
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut ChatMessage> {
+        self.messages.iter_mut().find(|message| message.id == id)
+    }
+
+    /// Whether a message with this id was received, even if it was cleared
+    /// since.
+    pub fn contains(&self, id: &str) -> bool {
+        self.seen.contains(id)
+    }
+
+    /// Adds `message` in order, unless it was received before, deleting it
+    /// if its delete came first. Returns whether it was new.
+    pub fn insert(&mut self, mut message: ChatMessage) -> bool {
+        if !self.seen.insert(message.id.clone()) {
+            return false;
+        }
+        self.tombstones.apply(&mut message);
+        let index = self
+            .messages
+            .partition_point(|shown| shown.order_key() <= message.order_key());
+        self.messages.insert(index, message);
+        true
+    }
+
+    /// Applies an edit sent by `sender` to the message it targets, if it is
+    /// shown. Returns whether the message changed.
+    pub fn edit(&mut self, edit: &MessageEdit, sender: Option<NodeId>) -> bool {
+        self.get_mut(&edit.target_id)
+            .is_some_and(|message| message.apply_edit(edit, sender))
+    }
+
+    /// Applies a delete sent by `sender` to the message it targets, and
+    /// remembers it for copies of the message still to arrive. Returns
+    /// whether a message shown changed.
+    pub fn delete(&mut self, delete: MessageDelete, sender: Option<NodeId>) -> bool {
+        let deleted = self
+            .get_mut(&delete.target_id)
+            .is_some_and(|message| message.apply_delete(&delete, sender));
+        self.tombstones.record(delete, sender);
+        deleted
+    }
+
+    /// Applies the messages, edits and deletes among the events of the
+    /// client, whatever their topic, and ignores the others. Returns whether
+    /// the messages changed.
+    pub fn apply(&mut self, event: ClientEvent) -> bool {
+        match event {
+            ClientEvent::Message(message) => self.insert(message),
+            ClientEvent::Edit { edit, sender } => self.edit(&edit, sender),
+            ClientEvent::Delete { delete, sender } => self.delete(delete, sender),
+            _ => false,
+        }
+    }
+
+    /// Clears the messages from view. Their ids are kept, so copies sent
+    /// again are not shown either.
+    pub fn clear(&mut self) {
+        self.messages.clear();
+    }
+
+    /// Forgets the messages, as when leaving the room, keeping the numbering
+    /// of the messages we send and the deletes received.
+    pub fn reset(&mut self) {
+        self.messages.clear();
+        self.seen.clear();
+    }
+
+    /// Number to send our next message with.
+    pub fn next_sequence(&mut self) -> u64 {
+        self.sequence += 1;
+        self.sequence
+    }
+}
@@ -0,0 +1,70 @@
+//! End-to-end encryption of topic messages.
+//!
+//! Every topic has a random secret seed that is generated when the topic is
+//! created and shared only inside its [`Ticket`](crate::ticket::Ticket). The
+//! symmetric [`TopicKey`] is derived from that seed with HKDF, so gossip peers
+//! relaying a topic without holding its ticket only ever see ciphertext.
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use hkdf::Hkdf;
+use iroh_gossip::proto::TopicId;
+use sha2::Sha256;
+
+// Context string binding derived keys to their purpose
+const KEY_INFO: &[u8] = b"iroh-lab topic message key v1";
+
+// Length of the random nonce prepended to every ciphertext
+const NONCE_LEN: usize = 24;
+
+/// Symmetric key used to encrypt and authenticate the messages of a topic.
+#[derive(Clone)]
+pub struct TopicKey {
+    cipher: XChaCha20Poly1305,
+}
+
+impl TopicKey {
+    /// Derives the key of a topic from the seed shared in its ticket.
+    pub fn derive(seed: &[u8; 32], topic_id: &TopicId) -> Self {
+        let hkdf = Hkdf::<Sha256>::new(Some(topic_id.as_bytes()), seed);
+        let mut key = [0u8; 32];
+        hkdf.expand(KEY_INFO, &mut key)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+
+        Self {
+            cipher: XChaCha20Poly1305::new(&key.into()),
+        }
+    }
+
+    /// Encrypts `plaintext`, returning the nonce followed by the ciphertext.
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| "Failed to encrypt message".to_string())?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Decrypts data produced by [`TopicKey::encrypt`].
+    ///
+    /// Fails if the data was not encrypted with this key or was modified.
+    pub fn decrypt(&self, sealed: &[u8]) -> Result<Vec<u8>, String> {
+        if sealed.len() < NONCE_LEN {
+            return Err("Encrypted message is too short".to_string());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+
+        self.cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "Failed to authenticate encrypted message".to_string())
+    }
+}
+
+impl std::fmt::Debug for TopicKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TopicKey(..)")
+    }
+}
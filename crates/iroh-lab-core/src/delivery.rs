@@ -0,0 +1,220 @@
+//! Delivery tracking of our own chat messages.
+//!
+//! Members receiving a chat message answer with an
+//! [`Ack`](crate::client::Ack). A message is delivered once any member
+//! acknowledged it. Until then it is sent again every [`ACK_TIMEOUT`], and
+//! given up on after [`MAX_ATTEMPTS`] sends.
+//!
+//! Messages sent while no neighbor is connected are queued instead, and sent
+//! in order once the topic has a neighbor again.
+//!
+//! For the last [`REPORT_LIMIT`] messages, a [`DeliveryReport`] tells which
+//! neighbors a message was handed to and which members acknowledged it when,
+//! to find out why messages do not arrive.
+use crate::client::ChatMessage;
+use iroh::NodeId;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// How long to wait for an acknowledgment before sending a message again.
+pub const ACK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often a message is sent, including the first time, before it fails.
+pub const MAX_ATTEMPTS: u32 = 4;
+
+/// How many of the latest messages keep their delivery report.
+pub const REPORT_LIMIT: usize = 256;
+
+/// Whether one of our messages reached another member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryState {
+    /// Waiting for a neighbor to be sent to.
+    Queued,
+    /// Sent, but not acknowledged yet.
+    Pending,
+    /// Acknowledged by at least one member.
+    Delivered,
+    /// Never acknowledged, even after sending it again.
+    Failed,
+}
+
+// A message waiting for its first acknowledgment
+#[derive(Debug)]
+struct Unacked {
+    message: ChatMessage,
+    last_sent: Instant,
+    attempts: u32,
+}
+
+/// How one of our messages was delivered so far.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryReport {
+    /// The neighbors the message was handed to, on any attempt.
+    pub neighbors: BTreeSet<NodeId>,
+    /// How often the message was sent, including the first time.
+    pub attempts: u32,
+    /// How long after it was first sent each member acknowledged it.
+    pub acks: BTreeMap<NodeId, Duration>,
+    pub state: DeliveryState,
+}
+
+impl DeliveryReport {
+    /// How often the message was sent again.
+    pub fn retransmissions(&self) -> u32 {
+        self.attempts.saturating_sub(1)
+    }
+
+    /// How long it took until the last member acknowledged the message.
+    pub fn propagation(&self) -> Option<Duration> {
+        self.acks.values().max().copied()
+    }
+}
+
+/// Our messages on a topic that nobody acknowledged yet, and the delivery
+/// reports of the latest ones.
+#[derive(Debug, Default)]
+pub struct Deliveries {
+    unacked: HashMap<String, Unacked>,
+    // Reports with when their message was first sent, by message id
+    reports: HashMap<String, (Instant, DeliveryReport)>,
+    // Ids of the reported messages, the oldest first
+    reported: VecDeque<String>,
+    // Messages waiting for a neighbor, the oldest first
+    queued: VecDeque<ChatMessage>,
+}
+
+/// What to do about unacknowledged messages, see [`Deliveries::due`].
+#[derive(Debug, Default)]
+pub struct DueDeliveries {
+    /// Messages to send again.
+    pub resend: Vec<ChatMessage>,
+    /// Ids of messages given up on.
+    pub failed: Vec<String>,
+}
+
+impl Deliveries {
+    /// Starts waiting for an acknowledgment of a message sent at `now`.
+    pub fn track(&mut self, message: ChatMessage, now: Instant) {
+        self.report_new(&message.id, now, 1, DeliveryState::Pending);
+        self.unacked.insert(
+            message.id.clone(),
+            Unacked {
+                message,
+                last_sent: now,
+                attempts: 1,
+            },
+        );
+    }
+
+    /// Keeps a message that could not be sent at `now`, as no neighbor was
+    /// connected, until [`take_queued`](Self::take_queued).
+    pub fn queue(&mut self, message: ChatMessage, now: Instant) {
+        self.report_new(&message.id, now, 0, DeliveryState::Queued);
+        self.queued.push_back(message);
+    }
+
+    /// The queued messages, the oldest first, which are no longer queued.
+    /// They are tracked again once they are sent.
+    pub fn take_queued(&mut self) -> Vec<ChatMessage> {
+        self.queued.drain(..).collect()
+    }
+
+    /// Number of messages waiting for a neighbor.
+    pub fn queued(&self) -> usize {
+        self.queued.len()
+    }
+
+    // Starts the report of a message, dropping the oldest one when full
+    fn report_new(&mut self, message_id: &str, now: Instant, attempts: u32, state: DeliveryState) {
+        if self.reports.contains_key(message_id) {
+            self.reported.retain(|id| id != message_id);
+        } else if self.reported.len() >= REPORT_LIMIT {
+            if let Some(oldest) = self.reported.pop_front() {
+                self.reports.remove(&oldest);
+            }
+        }
+        self.reported.push_back(message_id.to_string());
+        self.reports.insert(
+            message_id.to_string(),
+            (
+                now,
+                DeliveryReport {
+                    neighbors: BTreeSet::new(),
+                    attempts,
+                    acks: BTreeMap::new(),
+                    state,
+                },
+            ),
+        );
+    }
+
+    /// Records an acknowledgment, returning whether the message was still
+    /// waiting for one.
+    pub fn acknowledge(&mut self, message_id: &str) -> bool {
+        let delivered = self.unacked.remove(message_id).is_some();
+        if let Some((_, report)) = self.reports.get_mut(message_id) {
+            report.state = DeliveryState::Delivered;
+        }
+        delivered
+    }
+
+    /// Records an acknowledgment by `member` at `now`, see
+    /// [`acknowledge`](Self::acknowledge). Only the first acknowledgment of
+    /// each member is reported.
+    pub fn acknowledge_by(&mut self, message_id: &str, member: NodeId, now: Instant) -> bool {
+        if let Some((sent_at, report)) = self.reports.get_mut(message_id) {
+            report
+                .acks
+                .entry(member)
+                .or_insert_with(|| now.saturating_duration_since(*sent_at));
+        }
+        self.acknowledge(message_id)
+    }
+
+    /// Records that a message was handed to the gossip `neighbors`.
+    pub fn sent_to(&mut self, message_id: &str, neighbors: impl IntoIterator<Item = NodeId>) {
+        if let Some((_, report)) = self.reports.get_mut(message_id) {
+            report.neighbors.extend(neighbors);
+        }
+    }
+
+    /// The delivery report of one of the latest messages.
+    pub fn report(&self, message_id: &str) -> Option<DeliveryReport> {
+        self.reports
+            .get(message_id)
+            .map(|(_, report)| report.clone())
+    }
+
+    /// The messages whose acknowledgment timed out at `now`: those to send
+    /// again, which are counted as sent, and those that failed for good,
+    /// which are no longer tracked.
+    pub fn due(&mut self, now: Instant) -> DueDeliveries {
+        let mut due = DueDeliveries::default();
+        self.unacked.retain(|id, unacked| {
+            if now.saturating_duration_since(unacked.last_sent) < ACK_TIMEOUT {
+                return true;
+            }
+            let report = self.reports.get_mut(id).map(|(_, report)| report);
+            if unacked.attempts >= MAX_ATTEMPTS {
+                if let Some(report) = report {
+                    report.state = DeliveryState::Failed;
+                }
+                due.failed.push(id.clone());
+                return false;
+            }
+            unacked.attempts += 1;
+            unacked.last_sent = now;
+            if let Some(report) = report {
+                report.attempts = unacked.attempts;
+            }
+            due.resend.push(unacked.message.clone());
+            true
+        });
+        due
+    }
+
+    /// Number of messages waiting for an acknowledgment.
+    pub fn pending(&self) -> usize {
+        self.unacked.len()
+    }
+}
@@ -0,0 +1,113 @@
+//! Scripted conversations played into a local-only topic.
+//!
+//! Demos show a lively chat without a network or other people, for
+//! screenshots, presentations and trying out the interface. A script is a
+//! TOML file naming the topic and listing the messages to post, each with
+//! the number of seconds after the start of the demo it appears at:
+//!
+//! ```toml
+//! topic = "Launch planning"
+//!
+//! [[message]]
+//! at = 0
+//! author = "alice"
+//! content = "Morning! Shall we go over the launch?"
+//! ```
+//!
+//! Scripted messages are delivered to the subscriptions of the client like
+//! messages from the network, so the interface shows them the same way.
+use crate::client::{ChatMessage, IrohClient};
+use chrono::Utc;
+use serde::Deserialize;
+use std::path::Path;
+use std::time::Duration;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+/// A conversation to play.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Script {
+    /// Name of the topic shown during the demo.
+    pub topic: String,
+    /// Messages in the order they are posted.
+    #[serde(rename = "message", default)]
+    pub messages: Vec<ScriptedMessage>,
+}
+
+/// A message of a script.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScriptedMessage {
+    /// Seconds after the start of the demo the message is posted at.
+    pub at: f64,
+    pub author: String,
+    pub content: String,
+}
+
+impl Script {
+    /// Parses a script, ordering its messages by the time they are posted.
+    pub fn parse(script: &str) -> Result<Self, String> {
+        let mut script: Script =
+            toml::from_str(script).map_err(|e| format!("Failed to parse script: {}", e))?;
+        if let Some(message) = script
+            .messages
+            .iter()
+            .find(|message| !message.at.is_finite() || message.at < 0.0)
+        {
+            return Err(format!(
+                "Message from {} has an invalid time {}",
+                message.author, message.at
+            ));
+        }
+        script.messages.sort_by(|a, b| a.at.total_cmp(&b.at));
+        Ok(script)
+    }
+
+    /// Loads the script at `path`.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let script = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::parse(&script)
+    }
+
+    /// When the message at `index` is posted, with the demo running at
+    /// `speed` times real time.
+    pub fn offset(&self, index: usize, speed: f64) -> Duration {
+        Duration::from_secs_f64(self.messages[index].at / speed)
+    }
+}
+
+/// A fresh hash for a demo topic, which is never joined on the network.
+pub fn topic_hash() -> String {
+    format!("demo-{}", Uuid::new_v4())
+}
+
+/// A chat message posted on a demo topic.
+pub fn message(topic_hash: &str, author: &str, content: &str, lamport: u64) -> ChatMessage {
+    ChatMessage {
+        id: Uuid::new_v4().to_string(),
+        author: author.to_string(),
+        content: content.to_string(),
+        timestamp: Utc::now(),
+        topic_hash: topic_hash.to_string(),
+        sequence: lamport,
+        lamport,
+        verified_sender: None,
+        edited_at: None,
+        deleted_at: None,
+    }
+}
+
+/// Posts the messages of `script` to the topic `topic_hash` at `speed`
+/// times real time, finishing after the last message.
+pub async fn play(script: Script, topic_hash: String, speed: f64) {
+    let start = Instant::now();
+    for (index, scripted) in script.messages.iter().enumerate() {
+        tokio::time::sleep_until(start + script.offset(index, speed)).await;
+        IrohClient::broadcast_message(message(
+            &topic_hash,
+            &scripted.author,
+            &scripted.content,
+            index as u64 + 1,
+        ));
+    }
+}
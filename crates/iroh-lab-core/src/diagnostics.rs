@@ -0,0 +1,578 @@
+//! Checks of the environment the chat runs in.
+//!
+//! Each check tests one thing the chat depends on, such as reaching a relay
+//! or writing the configuration, and explains how to fix it when it fails.
+//! The checks know nothing about how they are presented: the `doctor`
+//! command prints them, the `test_iroh` binary prints those of the network
+//! alone, and the application can show them in its own way.
+//!
+//! A [`NodeStatus`] is a snapshot of the running node instead: its addresses,
+//! relay, and how it reaches its peers, for the diagnostics screen.
+use crate::client::{ClientEvent, IrohClient};
+use crate::config::{Config, RelayMode};
+use crate::delivery::DeliveryState;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use iroh::endpoint::{ConnectionType, DirectAddr, DirectAddrType};
+use iroh::{Endpoint, NodeAddr, NodeId, RelayUrl};
+use std::fmt;
+use std::net::{SocketAddr, UdpSocket};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// How long to wait for a relay before reporting it unreachable.
+pub const RELAY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long to wait for discovery to find our own node.
+pub const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// How long to wait for a public address of our own node.
+pub const NAT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long to wait for a message to go round a throwaway topic.
+pub const ROUND_TRIP_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Pause between lookups of our own node
+const DISCOVERY_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+// Clocks before this date are certainly wrong, as this release did not exist
+const EARLIEST_PLAUSIBLE_TIME: &str = "2025-01-01T00:00:00Z";
+
+// Name of the file written to check that the configuration can be saved
+const PROBE_FILE: &str = ".doctor-probe";
+
+/// Outcome of a check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    /// Something works worse than it should, but the chat still works.
+    Warn,
+    Fail,
+}
+
+/// The result of a single check.
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    /// What was found.
+    pub detail: String,
+    /// How to fix the problem, for checks that did not pass.
+    pub hint: Option<&'static str>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: &'static str) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            hint: Some(hint),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: &'static str) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            hint: Some(hint),
+        }
+    }
+}
+
+/// The results of all checks, in the order they ran.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    /// Whether no check failed.
+    pub fn is_healthy(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| check.status != CheckStatus::Fail)
+    }
+}
+
+/// Runs every check.
+pub async fn run() -> Report {
+    let mut checks = vec![check_udp_bind()];
+    checks.extend(check_network().await);
+    checks.push(check_clock(Utc::now()));
+    match Config::default_path() {
+        Some(path) => {
+            checks.push(check_storage(path.parent().unwrap_or(&path)));
+            checks.push(check_config(&path));
+        }
+        None => checks.push(Check::fail(
+            "Storage",
+            "This platform has no configuration directory",
+            "Set $HOME, or $XDG_CONFIG_HOME on Linux",
+        )),
+    }
+    Report { checks }
+}
+
+/// Runs the checks of the network alone: binding, the relay, discovery,
+/// NAT traversal and a gossip round trip.
+pub async fn run_network() -> Report {
+    let mut checks = vec![check_udp_bind()];
+    checks.extend(check_network().await);
+    checks.push(check_gossip_round_trip().await);
+    Report { checks }
+}
+
+/// Whether a UDP socket can be bound, which all connections need.
+pub fn check_udp_bind() -> Check {
+    match UdpSocket::bind("0.0.0.0:0").and_then(|socket| socket.local_addr()) {
+        Ok(addr) => Check::pass("UDP bind", format!("Bound {}", addr)),
+        Err(e) => Check::fail(
+            "UDP bind",
+            format!("Failed to bind a UDP socket: {}", e),
+            "Check that no firewall or sandbox blocks UDP for this application",
+        ),
+    }
+}
+
+// Binds an endpoint like the chat does, and checks the relay, discovery and
+// NAT traversal
+async fn check_network() -> Vec<Check> {
+    let endpoint = match Endpoint::builder().discovery_n0().bind().await {
+        Ok(endpoint) => endpoint,
+        Err(e) => {
+            return vec![Check::fail(
+                "Endpoint",
+                format!("Failed to bind an endpoint: {}", e),
+                "Check the network connection and firewall settings",
+            )]
+        }
+    };
+
+    let relay = check_relay(&endpoint).await;
+    let discovery = if relay.status == CheckStatus::Pass {
+        check_discovery(&endpoint).await
+    } else {
+        Check::warn(
+            "Discovery",
+            "Skipped, discovery is published through the relay",
+            "Fix the relay connection first",
+        )
+    };
+    let nat = check_nat_traversal(&endpoint).await;
+    endpoint.close().await;
+
+    vec![relay, discovery, nat]
+}
+
+/// Whether the endpoint connects to its home relay, which other nodes use to
+/// reach it when no direct connection is possible.
+pub async fn check_relay(endpoint: &Endpoint) -> Check {
+    let mut home_relay = endpoint.home_relay();
+    match tokio::time::timeout(RELAY_TIMEOUT, home_relay.initialized()).await {
+        Ok(Ok(url)) => Check::pass("Relay", format!("Connected to {}", url)),
+        Ok(Err(_)) => Check::fail(
+            "Relay",
+            "The endpoint shut down before reaching a relay",
+            "Run the check again",
+        ),
+        Err(_) => Check::fail(
+            "Relay",
+            format!("No relay reached within {}s", RELAY_TIMEOUT.as_secs()),
+            "Allow outgoing HTTPS connections to the relay servers",
+        ),
+    }
+}
+
+/// Whether discovery finds our own node, which is how the nodes in a ticket
+/// are found.
+pub async fn check_discovery(endpoint: &Endpoint) -> Check {
+    const HINT: &str = "Allow DNS lookups and HTTPS connections to the discovery service";
+    let Some(discovery) = endpoint.discovery() else {
+        return Check::fail("Discovery", "No discovery service configured", HINT);
+    };
+    // Our node is only published once it reached its relay, so early lookups
+    // may come back empty
+    let lookup = async {
+        loop {
+            let Some(mut results) = discovery.resolve(endpoint.clone(), endpoint.node_id()) else {
+                return Err("Discovery cannot look up nodes".to_string());
+            };
+            while let Some(result) = results.next().await {
+                match result {
+                    Ok(item) => return Ok(item.provenance()),
+                    Err(e) => tracing::debug!("Discovery lookup failed: {}", e),
+                }
+            }
+            tokio::time::sleep(DISCOVERY_RETRY_DELAY).await;
+        }
+    };
+    match tokio::time::timeout(DISCOVERY_TIMEOUT, lookup).await {
+        Ok(Ok(provenance)) => {
+            Check::pass("Discovery", format!("Found our node via {}", provenance))
+        }
+        Ok(Err(e)) => Check::fail("Discovery", e, HINT),
+        Err(_) => Check::warn(
+            "Discovery",
+            format!(
+                "Our node was not found within {}s",
+                DISCOVERY_TIMEOUT.as_secs()
+            ),
+            HINT,
+        ),
+    }
+}
+
+/// Whether the endpoint found a public address, which peers outside the local
+/// network connect to directly instead of through a relay.
+pub async fn check_nat_traversal(endpoint: &Endpoint) -> Check {
+    // The public addresses are only known once the STUN probes and port
+    // mapping finish, after the local ones
+    let mut updates = endpoint.direct_addresses().stream();
+    let mut addrs = Vec::new();
+    let public = async {
+        while let Some(update) = updates.next().await {
+            addrs = update.map(Vec::from_iter).unwrap_or_default();
+            if addrs.iter().any(|addr| addr.typ != DirectAddrType::Local) {
+                break;
+            }
+        }
+    };
+    let _ = tokio::time::timeout(NAT_TIMEOUT, public).await;
+    check_direct_addresses(&addrs)
+}
+
+/// How the direct addresses of a node reach peers outside the local
+/// network.
+pub fn check_direct_addresses(addrs: &[DirectAddr]) -> Check {
+    let local: Vec<_> = addrs
+        .iter()
+        .filter(|addr| addr.typ == DirectAddrType::Local)
+        .map(|addr| addr.addr)
+        .collect();
+    let find = |typ| {
+        addrs
+            .iter()
+            .find(|addr| addr.typ == typ)
+            .map(|addr| addr.addr)
+    };
+    if let Some(addr) = find(DirectAddrType::Portmapped) {
+        Check::pass("NAT traversal", format!("The router maps {} to us", addr))
+    } else if let Some(addr) = find(DirectAddrType::Stun4LocalPort) {
+        Check::pass(
+            "NAT traversal",
+            format!("Reachable at {}, forwarded to our fixed port", addr),
+        )
+    } else if let Some(addr) = find(DirectAddrType::Stun) {
+        if local.contains(&addr) {
+            Check::pass(
+                "NAT traversal",
+                format!("Not behind a NAT, reachable at {}", addr),
+            )
+        } else {
+            Check::pass(
+                "NAT traversal",
+                format!(
+                    "Behind a NAT with public address {}, holes are punched to peers",
+                    addr
+                ),
+            )
+        }
+    } else if local.is_empty() {
+        Check::fail(
+            "NAT traversal",
+            "The endpoint has no addresses",
+            "Check the network connection",
+        )
+    } else {
+        Check::warn(
+            "NAT traversal",
+            format!(
+                "No public address found within {}s, only local ones",
+                NAT_TIMEOUT.as_secs()
+            ),
+            "Allow outgoing UDP, or peers outside the local network are only reached through a relay",
+        )
+    }
+}
+
+/// Whether a message goes round a throwaway topic: a second node joins the
+/// topic of a first one, receives a message from it and acknowledges it.
+pub async fn check_gossip_round_trip() -> Check {
+    const NAME: &str = "Gossip";
+    const HINT: &str = "Fix the checks above, or allow UDP between nodes on this machine";
+    let mut events = IrohClient::subscribe(None);
+
+    let round_trip = async {
+        let creator = IrohClient::new();
+        creator.initialize_network().await?;
+        let (_, ticket, topic_hash) = creator.create_topic("diagnostics".to_string()).await?;
+        let joiner = IrohClient::new();
+        joiner.initialize_network().await?;
+        joiner.join_topic(ticket).await?;
+
+        let started = Instant::now();
+        let message = creator
+            .send_message("diagnostics".to_string(), "ping".to_string(), 1)
+            .await?;
+        let mut arrived = None;
+        while let Some(event) = events.next().await {
+            match event {
+                ClientEvent::Message(received) if received.id == message.id => {
+                    arrived.get_or_insert(started.elapsed());
+                }
+                ClientEvent::Delivery {
+                    topic_hash: delivered_hash,
+                    message_id,
+                    state,
+                } if delivered_hash == topic_hash && message_id == message.id => match state {
+                    DeliveryState::Delivered => {
+                        return Ok((arrived, started.elapsed()));
+                    }
+                    DeliveryState::Failed => {
+                        return Err("The message was never acknowledged".to_string());
+                    }
+                    DeliveryState::Queued | DeliveryState::Pending => {}
+                },
+                _ => {}
+            }
+        }
+        Err("The client stopped".to_string())
+    };
+    match tokio::time::timeout(ROUND_TRIP_TIMEOUT, round_trip).await {
+        Ok(Ok((Some(arrived), round_trip))) => Check::pass(
+            NAME,
+            format!(
+                "A message arrived in {} ms and was acknowledged in {} ms",
+                arrived.as_millis(),
+                round_trip.as_millis()
+            ),
+        ),
+        Ok(Ok((None, round_trip))) => Check::pass(
+            NAME,
+            format!(
+                "A message was acknowledged in {} ms",
+                round_trip.as_millis()
+            ),
+        ),
+        Ok(Err(e)) => Check::fail(NAME, e, HINT),
+        Err(_) => Check::fail(
+            NAME,
+            format!(
+                "No message went round a topic within {}s",
+                ROUND_TRIP_TIMEOUT.as_secs()
+            ),
+            HINT,
+        ),
+    }
+}
+
+/// Whether the system clock at `now` is plausible. Messages are ordered
+/// without it, but their times are shown as the sender's clock tells.
+pub fn check_clock(now: DateTime<Utc>) -> Check {
+    let earliest: DateTime<Utc> = EARLIEST_PLAUSIBLE_TIME
+        .parse()
+        .expect("Earliest plausible time should be valid");
+    if now < earliest {
+        Check::warn(
+            "Clock",
+            format!("The system clock says {}", now.format("%Y-%m-%d %H:%M UTC")),
+            "Set the system clock, or enable time synchronization",
+        )
+    } else {
+        Check::pass(
+            "Clock",
+            format!("It is {}", now.format("%Y-%m-%d %H:%M UTC")),
+        )
+    }
+}
+
+/// Whether files can be written to `dir`, where the settings are kept.
+pub fn check_storage(dir: &Path) -> Check {
+    let probe = dir.join(PROBE_FILE);
+    let result = std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&probe, b"ok"))
+        .and_then(|_| std::fs::remove_file(&probe));
+    match result {
+        Ok(()) => Check::pass("Storage", format!("{} is writable", dir.display())),
+        Err(e) => Check::fail(
+            "Storage",
+            format!("Cannot write to {}: {}", dir.display(), e),
+            "Check the permissions of the directory and the free disk space",
+        ),
+    }
+}
+
+/// Whether the configuration at `path` loads.
+pub fn check_config(path: &Path) -> Check {
+    match Config::load_from(path) {
+        Ok(_) => Check::pass("Configuration", format!("{} loads", path.display())),
+        Err(e) => Check::fail(
+            "Configuration",
+            e,
+            "Fix or delete the configuration file to start over with the defaults",
+        ),
+    }
+}
+
+/// How the node reaches its connected peers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NatTraversal {
+    /// No peer is connected.
+    NoPeers,
+    /// Every peer is reached directly.
+    Direct,
+    /// Some peers are reached directly, others only through a relay.
+    Partial,
+    /// Every peer is reached through a relay, as no hole was punched yet.
+    Relayed,
+}
+
+impl fmt::Display for NatTraversal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::NoPeers => "No peers connected",
+            Self::Direct => "Every peer is reached directly",
+            Self::Partial => "Some peers are only reached through a relay",
+            Self::Relayed => "Every peer is reached through a relay",
+        })
+    }
+}
+
+/// How well the node is connected to the network as a whole.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Other nodes can reach us directly, and through a relay when relays
+    /// are used.
+    Connected,
+    /// Other nodes can only reach us through a relay, or all connected
+    /// peers are reached through one.
+    RelayOnly,
+    /// The network is up, but the relay connection is being
+    /// reestablished.
+    Reconnecting,
+    /// Neither direct addresses nor a relay are available.
+    #[default]
+    Offline,
+}
+
+impl Connectivity {
+    /// Whether other nodes can reach us at all.
+    pub fn is_online(self) -> bool {
+        matches!(self, Self::Connected | Self::RelayOnly)
+    }
+}
+
+impl fmt::Display for Connectivity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Connected => "Connected",
+            Self::RelayOnly => "Relay-only",
+            Self::Reconnecting => "Reconnecting",
+            Self::Offline => "Offline",
+        })
+    }
+}
+
+/// A snapshot of our own node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeStatus {
+    pub node_id: NodeId,
+    /// Local addresses the endpoint's sockets are bound to, IPv4 first.
+    pub bound_sockets: Vec<SocketAddr>,
+    /// Addresses other nodes may reach us at without a relay.
+    pub direct_addresses: Vec<SocketAddr>,
+    /// The relay other nodes reach us through, once connected to it.
+    pub home_relay: Option<RelayUrl>,
+    /// How we reach each peer we know a path to.
+    pub connections: Vec<(NodeId, ConnectionType)>,
+    /// [Reputation](crate::reputation) of every peer heard from, lowest
+    /// first.
+    pub peer_scores: Vec<(NodeId, i32)>,
+}
+
+impl NodeStatus {
+    /// The current state of `endpoint`, without peer scores, which the
+    /// endpoint does not know.
+    pub fn of(endpoint: &Endpoint) -> Self {
+        let direct_addresses = endpoint
+            .direct_addresses()
+            .get()
+            .ok()
+            .flatten()
+            .map(|addrs| addrs.into_iter().map(|addr| addr.addr).collect())
+            .unwrap_or_default();
+        let connections = endpoint
+            .remote_info_iter()
+            .filter(|info| info.conn_type != ConnectionType::None)
+            .map(|info| (info.node_id, info.conn_type))
+            .collect();
+        let (bound_v4, bound_v6) = endpoint.bound_sockets();
+        Self {
+            node_id: endpoint.node_id(),
+            bound_sockets: std::iter::once(bound_v4).chain(bound_v6).collect(),
+            direct_addresses,
+            home_relay: endpoint.home_relay().get().ok().flatten(),
+            connections,
+            peer_scores: Vec::new(),
+        }
+    }
+
+    /// Number of peers we have a path to.
+    pub fn connected_peers(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// How the connected peers are reached.
+    pub fn nat_traversal(&self) -> NatTraversal {
+        let direct = self
+            .connections
+            .iter()
+            .filter(|(_, conn_type)| matches!(conn_type, ConnectionType::Direct(_)))
+            .count();
+        match direct {
+            _ if self.connections.is_empty() => NatTraversal::NoPeers,
+            0 => NatTraversal::Relayed,
+            direct if direct == self.connections.len() => NatTraversal::Direct,
+            _ => NatTraversal::Partial,
+        }
+    }
+
+    /// How well the node is connected, reaching other nodes as set by
+    /// `relay_mode`. Addresses on the loopback interface do not count, as
+    /// other machines cannot reach them.
+    pub fn connectivity(&self, relay_mode: RelayMode) -> Connectivity {
+        let direct = self
+            .direct_addresses
+            .iter()
+            .any(|addr| !addr.ip().is_loopback());
+        let relayed = self.home_relay.is_some();
+        let uses_relays = matches!(relay_mode, RelayMode::Default | RelayMode::Forced);
+        match (direct, relayed) {
+            (false, false) => Connectivity::Offline,
+            (true, false) if uses_relays => Connectivity::Reconnecting,
+            (false, true) => Connectivity::RelayOnly,
+            _ if self.nat_traversal() == NatTraversal::Relayed => Connectivity::RelayOnly,
+            _ => Connectivity::Connected,
+        }
+    }
+
+    /// The address other nodes can dial us at.
+    pub fn node_addr(&self) -> NodeAddr {
+        NodeAddr::from_parts(
+            self.node_id,
+            self.home_relay.clone(),
+            self.direct_addresses.iter().copied(),
+        )
+    }
+}
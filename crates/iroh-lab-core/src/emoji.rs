@@ -0,0 +1,154 @@
+//! The emoji offered by the picker of the message composer.
+//!
+//! A fixed selection of common emoji, each with a few words to find it by.
+//! The emoji picked last are kept in the configuration and offered first.
+
+/// Most emoji kept as recently used.
+pub const MAX_RECENT: usize = 16;
+
+/// Every emoji offered, with the words it is found by.
+pub const EMOJI: &[(&str, &str)] = &[
+    ("😀", "grinning smile happy"),
+    ("😃", "smiley smile happy"),
+    ("😄", "smile happy laugh"),
+    ("😁", "grin beaming smile"),
+    ("😆", "laughing squint"),
+    ("😅", "sweat smile relief"),
+    ("😂", "joy tears laugh lol"),
+    ("🤣", "rofl rolling laugh"),
+    ("🙂", "slight smile"),
+    ("🙃", "upside down silly"),
+    ("😉", "wink"),
+    ("😊", "blush smile happy"),
+    ("😇", "innocent halo angel"),
+    ("🥰", "love hearts smile"),
+    ("😍", "heart eyes love"),
+    ("🤩", "star struck excited"),
+    ("😘", "kiss blow"),
+    ("😋", "yum tasty delicious"),
+    ("😛", "tongue playful"),
+    ("😜", "wink tongue crazy"),
+    ("🤪", "zany crazy goofy"),
+    ("🤔", "thinking hmm"),
+    ("🤨", "raised eyebrow skeptical"),
+    ("😐", "neutral meh"),
+    ("😑", "expressionless"),
+    ("😶", "no mouth speechless"),
+    ("🙄", "eye roll"),
+    ("😏", "smirk"),
+    ("😬", "grimace awkward"),
+    ("😌", "relieved calm"),
+    ("😔", "pensive sad"),
+    ("😴", "sleeping tired zzz"),
+    ("😷", "mask sick"),
+    ("🤒", "thermometer sick fever"),
+    ("🤯", "mind blown exploding"),
+    ("🥳", "party celebrate"),
+    ("😎", "cool sunglasses"),
+    ("🤓", "nerd glasses"),
+    ("😕", "confused"),
+    ("😟", "worried"),
+    ("😮", "open mouth surprised wow"),
+    ("😲", "astonished shocked"),
+    ("😳", "flushed embarrassed"),
+    ("🥺", "pleading puppy eyes"),
+    ("😢", "cry sad tear"),
+    ("😭", "sob crying loud"),
+    ("😱", "scream fear"),
+    ("😤", "triumph huff"),
+    ("😠", "angry mad"),
+    ("😡", "rage pout angry"),
+    ("🤬", "cursing swearing"),
+    ("💀", "skull dead"),
+    ("💩", "poop"),
+    ("🤡", "clown"),
+    ("👻", "ghost boo"),
+    ("👽", "alien"),
+    ("🤖", "robot bot"),
+    ("👋", "wave hello hi bye"),
+    ("👌", "ok okay perfect"),
+    ("✌️", "victory peace"),
+    ("🤞", "fingers crossed luck"),
+    ("👍", "thumbs up yes like +1"),
+    ("👎", "thumbs down no dislike -1"),
+    ("👏", "clap applause"),
+    ("🙌", "raised hands hooray"),
+    ("🙏", "pray please thanks"),
+    ("🤝", "handshake deal"),
+    ("💪", "muscle strong flex"),
+    ("👀", "eyes look"),
+    ("🧠", "brain smart"),
+    ("❤️", "heart love red"),
+    ("🧡", "orange heart"),
+    ("💛", "yellow heart"),
+    ("💚", "green heart"),
+    ("💙", "blue heart"),
+    ("💜", "purple heart"),
+    ("🖤", "black heart"),
+    ("💔", "broken heart"),
+    ("💯", "hundred perfect score"),
+    ("✨", "sparkles shiny"),
+    ("🔥", "fire hot lit"),
+    ("⭐", "star"),
+    ("🌟", "glowing star"),
+    ("⚡", "lightning zap"),
+    ("🎉", "tada party celebrate"),
+    ("🎊", "confetti"),
+    ("🎁", "gift present"),
+    ("🎂", "birthday cake"),
+    ("🏆", "trophy win"),
+    ("🚀", "rocket launch ship"),
+    ("💡", "idea bulb"),
+    ("📌", "pin pushpin"),
+    ("📎", "paperclip attach"),
+    ("📅", "calendar date"),
+    ("⏰", "alarm clock time"),
+    ("🔒", "lock secure"),
+    ("🔑", "key"),
+    ("🐛", "bug"),
+    ("🔧", "wrench fix tool"),
+    ("⚙️", "gear settings"),
+    ("💻", "laptop computer"),
+    ("📱", "phone mobile"),
+    ("☕", "coffee tea hot"),
+    ("🍕", "pizza"),
+    ("🍺", "beer"),
+    ("🍻", "cheers beers"),
+    ("🦀", "crab rust"),
+    ("🐶", "dog puppy"),
+    ("🐱", "cat kitty"),
+    ("🌍", "earth globe world"),
+    ("☀️", "sun sunny"),
+    ("🌧️", "rain cloud"),
+    ("❄️", "snowflake cold"),
+    ("✅", "check done yes"),
+    ("❌", "cross no wrong"),
+    ("⚠️", "warning caution"),
+    ("❓", "question"),
+    ("❗", "exclamation"),
+    ("➕", "plus add"),
+];
+
+/// The emoji whose words start with every word of `query`, in the order
+/// offered. An empty query finds every emoji.
+pub fn search(query: &str) -> Vec<&'static str> {
+    let query = query.to_lowercase();
+    let terms: Vec<_> = query.split_whitespace().collect();
+    EMOJI
+        .iter()
+        .filter(|(_, words)| {
+            terms
+                .iter()
+                .all(|term| words.split(' ').any(|word| word.starts_with(term)))
+        })
+        .map(|(emoji, _)| *emoji)
+        .collect()
+}
+
+/// Moves `emoji` to the front of the `recent` ones, forgetting the oldest
+/// beyond [`MAX_RECENT`].
+pub fn remember(recent: &mut Vec<String>, emoji: &str) {
+    recent.retain(|known| known != emoji);
+    recent.insert(0, emoji.to_string());
+    recent.truncate(MAX_RECENT);
+}
@@ -0,0 +1,207 @@
+//! A chat client for programs, such as bots and scripts, that chat without a
+//! user interface.
+//!
+//! A [`ChatEngine`] runs a node under one name, joins and creates topics,
+//! and sends to any of them. What happens on its topics arrives on an
+//! [`Events`] stream:
+//!
+//! ```no_run
+//! use futures::StreamExt;
+//! use iroh_lab_core::config::Config;
+//! use iroh_lab_core::engine::ChatEngine;
+//! use iroh_lab_core::ClientEvent;
+//!
+//! # async fn echo(ticket: String) -> Result<(), String> {
+//! let engine = ChatEngine::start("echo", &Config::default()).await?;
+//! let mut events = engine.events()?;
+//! engine.join(ticket).await?;
+//! while let Some(event) = events.next().await {
+//!     if let ClientEvent::Message(message) = event {
+//!         engine.reply(&message, message.content.clone()).await?;
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Every engine in a process is a node of its own, and its stream only
+//! carries what happened to its node, on its own topics or node-wide like
+//! connectivity. Messages sent by an engine are not on its stream, while its
+//! notices, by the author "System", are.
+//!
+//! Engines chat over any [`ChatTransport`], so what is built on them can be
+//! tested over [`MemoryTransport`](crate::testing::MemoryTransport) instead
+//! of the network.
+use crate::client::{ChatMessage, ClientEvent, IrohClient, Subscription};
+use crate::config::Config;
+use crate::filter::Filters;
+use crate::roster::PresenceStatus;
+use crate::transport::ChatTransport;
+use futures::{Stream, StreamExt};
+use std::collections::HashSet;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+/// A node chatting under one name.
+#[derive(Debug)]
+pub struct ChatEngine<T = IrohClient> {
+    client: T,
+    username: String,
+    // Numbers the messages sent, across topics
+    sequence: AtomicU64,
+    // Hashes of the topics joined or created, shared with the event streams
+    topics: Arc<Mutex<HashSet<String>>>,
+}
+
+/// A topic the engine is on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Topic {
+    pub name: String,
+    pub hash: String,
+    /// Ticket others join the topic with.
+    pub ticket: String,
+}
+
+/// What happens on the topics of an engine, and to its node.
+///
+/// Events of topics joined after the stream was created are included.
+#[derive(Debug)]
+pub struct Events {
+    events: Subscription,
+    topics: Arc<Mutex<HashSet<String>>>,
+}
+
+impl ChatEngine {
+    /// Starts a node chatting as `username`, reaching others and filtering
+    /// what they write as set in `config`.
+    pub async fn start(username: impl Into<String>, config: &Config) -> Result<Self, String> {
+        // Checked before binding sockets, too
+        let username = username.into();
+        if username.trim().is_empty() {
+            return Err("The name to chat under is empty".to_string());
+        }
+        let mut client = IrohClient::new();
+        client.set_relay_mode(config.relay_mode);
+        client.set_binding(config.binding);
+        client.set_filters(Filters::from_config(config)?);
+        client.initialize_network().await?;
+        Self::with_transport(username, client)
+    }
+}
+
+impl<T: ChatTransport> ChatEngine<T> {
+    /// An engine chatting as `username` over `transport`, which is on the
+    /// network already.
+    pub fn with_transport(username: impl Into<String>, transport: T) -> Result<Self, String> {
+        let username = username.into();
+        if username.trim().is_empty() {
+            return Err("The name to chat under is empty".to_string());
+        }
+        transport.set_presence(username.clone(), PresenceStatus::Online);
+        Ok(Self {
+            client: transport,
+            username,
+            sequence: AtomicU64::new(0),
+            topics: Arc::default(),
+        })
+    }
+
+    /// Name the engine chats under.
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    /// The client running the node, for what the engine does not offer.
+    pub fn client(&self) -> &T {
+        &self.client
+    }
+
+    /// A new stream of events. Each stream receives every event, so several
+    /// tasks may each have their own.
+    pub fn events(&self) -> Result<Events, String> {
+        Ok(Events {
+            events: self.client.events(),
+            topics: self.topics.clone(),
+        })
+    }
+
+    /// Creates a topic named `name`.
+    pub async fn create(&self, name: impl Into<String>) -> Result<Topic, String> {
+        let topic = self.client.create(name.into()).await?;
+        self.topics.lock().unwrap().insert(topic.hash.clone());
+        Ok(topic)
+    }
+
+    /// Joins a topic with a ticket or invite.
+    pub async fn join(&self, ticket: impl Into<String>) -> Result<Topic, String> {
+        let topic = self.client.join(ticket.into()).await?;
+        self.topics.lock().unwrap().insert(topic.hash.clone());
+        Ok(topic)
+    }
+
+    /// Hashes of the topics joined or created.
+    pub fn topics(&self) -> Vec<String> {
+        self.topics.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Sends `content` to the topic `topic_hash`, returning the message as
+    /// it was sent.
+    pub async fn send(
+        &self,
+        topic_hash: &str,
+        content: impl Into<String>,
+    ) -> Result<ChatMessage, String> {
+        if !self.topics.lock().unwrap().contains(topic_hash) {
+            return Err("Not subscribed to this topic".to_string());
+        }
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        self.client
+            .send(topic_hash, self.username.clone(), content.into(), sequence)
+            .await
+    }
+
+    /// Leaves every topic and goes off the network, sending what is pending
+    /// first to the members still connected.
+    pub async fn shutdown(&self) {
+        self.topics.lock().unwrap().clear();
+        self.client.shutdown().await;
+    }
+
+    /// Sends `content` to the topic `message` was sent to.
+    pub async fn reply(
+        &self,
+        message: &ChatMessage,
+        content: impl Into<String>,
+    ) -> Result<ChatMessage, String> {
+        self.send(&message.topic_hash, content).await
+    }
+}
+
+impl Events {
+    // Whether the event belongs on the stream, node-wide ones having no
+    // topic
+    fn concerns(&self, event: &ClientEvent) -> bool {
+        let topic_hash = event.topic_hash();
+        topic_hash.is_empty() || self.topics.lock().unwrap().contains(topic_hash)
+    }
+}
+
+impl Stream for Events {
+    type Item = ClientEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<ClientEvent>> {
+        loop {
+            match self.events.poll_next_unpin(cx) {
+                Poll::Ready(Some(event)) => {
+                    if self.concerns(&event) {
+                        return Poll::Ready(Some(event));
+                    }
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Local filtering of what others write.
+//!
+//! A [`ContentFilter`] lists words, matched as whole words ignoring case,
+//! and regular expressions written between slashes, e.g. `/fo+bar/`, which
+//! may contain commas even where patterns are separated by them. What
+//! they match is masked with asterisks, or hides the whole message. The
+//! filter of the settings applies to every topic, and each topic may add one
+//! of its own.
+//!
+//! Clients filter the messages and edits of others as they arrive, before
+//! delivering them, so filtered content never reaches a user interface. A
+//! hidden message is acknowledged like any other, and an edit that would
+//! hide its message is dropped, leaving the content it replaced.
+use crate::config::Config;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// What becomes of a message matching a filter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterAction {
+    /// Replace each match with asterisks.
+    #[default]
+    Mask,
+    /// Leave the whole message out.
+    Hide,
+}
+
+impl FilterAction {
+    /// Every action, for choosing one.
+    pub const ALL: [Self; 2] = [Self::Mask, Self::Hide];
+}
+
+impl fmt::Display for FilterAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Mask => "Mask matches",
+            Self::Hide => "Hide messages",
+        })
+    }
+}
+
+/// Words and regular expressions to filter, as configured.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContentFilter {
+    /// Words, or regular expressions between slashes.
+    pub patterns: Vec<String>,
+    pub action: FilterAction,
+}
+
+impl ContentFilter {
+    /// A filter of the patterns in `text`, separated by commas outside of
+    /// regular expressions.
+    pub fn parse(text: &str, action: FilterAction) -> Result<Self, String> {
+        let filter = Self {
+            patterns: split_patterns(text)?,
+            action,
+        };
+        filter.compile()?;
+        Ok(filter)
+    }
+
+    /// Whether there is nothing to filter.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    // The patterns as regular expressions, failing on the first invalid one
+    fn compile(&self) -> Result<Compiled, String> {
+        let patterns = self
+            .patterns
+            .iter()
+            .map(|pattern| {
+                let expression = match pattern
+                    .strip_prefix('/')
+                    .and_then(|pattern| pattern.strip_suffix('/'))
+                {
+                    Some(expression) => expression.to_string(),
+                    None => whole_word(pattern),
+                };
+                Regex::new(&expression).map_err(|e| format!("Invalid pattern {}: {}", pattern, e))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Compiled {
+            patterns,
+            action: self.action,
+        })
+    }
+}
+
+/// The filters of every topic, as a client applies them.
+#[derive(Debug, Clone, Default)]
+pub struct Filters {
+    everywhere: Compiled,
+    // By topic hash
+    topics: HashMap<String, Compiled>,
+}
+
+impl Filters {
+    /// Applies `everywhere` to every topic, along with the filters of
+    /// `topics` by topic hash. Fails on invalid regular expressions.
+    pub fn new(
+        everywhere: &ContentFilter,
+        topics: &BTreeMap<String, ContentFilter>,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            everywhere: everywhere.compile()?,
+            topics: topics
+                .iter()
+                .map(|(topic_hash, filter)| Ok((topic_hash.clone(), filter.compile()?)))
+                .collect::<Result<_, String>>()?,
+        })
+    }
+
+    /// The filters of the settings in `config`.
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        Self::new(&config.content_filter, &config.topic_filters)
+    }
+
+    /// `content` as shown on the topic `topic_hash`, with its matches
+    /// masked, or `None` if it is hidden.
+    pub fn apply(&self, topic_hash: &str, content: &str) -> Option<String> {
+        let mut content = content.to_string();
+        for filter in std::iter::once(&self.everywhere).chain(self.topics.get(topic_hash)) {
+            content = filter.apply(&content)?;
+        }
+        Some(content)
+    }
+}
+
+// A filter ready to apply
+#[derive(Debug, Clone, Default)]
+struct Compiled {
+    patterns: Vec<Regex>,
+    action: FilterAction,
+}
+
+impl Compiled {
+    fn apply(&self, content: &str) -> Option<String> {
+        let mut content = content.to_string();
+        for pattern in &self.patterns {
+            // Patterns matching nothing at all, e.g. /a*/, mask nothing
+            if !pattern.find_iter(&content).any(|found| !found.is_empty()) {
+                continue;
+            }
+            if self.action == FilterAction::Hide {
+                return None;
+            }
+            content = pattern
+                .replace_all(&content, |found: &regex::Captures| {
+                    "*".repeat(found[0].chars().count())
+                })
+                .into_owned();
+        }
+        Some(content)
+    }
+}
+
+// The patterns of `text`, split at commas, except within regular
+// expressions, where a backslash escapes the character after it
+fn split_patterns(text: &str) -> Result<Vec<String>, String> {
+    let mut patterns = Vec::new();
+    let mut pattern = String::new();
+    let mut in_expression = false;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == ',' && !in_expression {
+            patterns.push(std::mem::take(&mut pattern));
+            continue;
+        }
+        if c == '/' && (in_expression || pattern.trim().is_empty()) {
+            in_expression = !in_expression;
+        }
+        pattern.push(c);
+        if c == '\\' && in_expression {
+            pattern.extend(chars.next());
+        }
+    }
+    if in_expression {
+        return Err(format!("Unclosed expression {}", pattern.trim()));
+    }
+    patterns.push(pattern);
+    Ok(patterns
+        .iter()
+        .map(|pattern| pattern.trim())
+        .filter(|pattern| !pattern.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+// An expression matching `word` as a whole word, ignoring case. Ends that are
+// not word characters, as in "c++", need no word boundary.
+fn whole_word(word: &str) -> String {
+    let boundary = |c: Option<char>| {
+        if c.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            r"\b"
+        } else {
+            ""
+        }
+    };
+    format!(
+        "(?i){}{}{}",
+        boundary(word.chars().next()),
+        regex::escape(word),
+        boundary(word.chars().last())
+    )
+}
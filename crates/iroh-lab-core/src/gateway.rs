@@ -0,0 +1,213 @@
+//! WebSocket gateway, letting web pages chat on the topics of a node.
+//!
+//! A page connects to `ws://<address>/<topic hash>?token=<token>` and
+//! exchanges JSON frames with the gateway, one per text message. It sends
+//! `{"type": "send", "content": ...}`, optionally with the `author` to send
+//! as, and receives:
+//!
+//! - `{"type": "topic", "topic_name": ..., "topic_hash": ...}` once
+//!   connected
+//! - `{"type": "message", ...}` with the fields of a [`ChatMessage`] for
+//!   every chat message of the topic, including those the page sent
+//! - `{"type": "error", "message": ...}` when a frame was not sent
+//!
+//! Browsers let any page connect to any address, so pages have to know the
+//! token the gateway was started with: anyone who has it can chat as the
+//! node.
+use crate::client::{ChatMessage, ClientEvent, IrohClient};
+use crate::config::MessageLimit;
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::debug;
+
+/// A frame sent by a page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum PageFrame {
+    /// Send a chat message, as `author` or the name of the gateway.
+    Send {
+        content: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        author: Option<String>,
+    },
+}
+
+/// A frame sent to a page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum GatewayFrame {
+    /// The topic the page is connected to.
+    Topic {
+        topic_name: String,
+        topic_hash: String,
+    },
+    /// A chat message of the topic.
+    Message(ChatMessage),
+    /// Why a frame of the page was not sent.
+    Error { message: String },
+}
+
+/// Serves the topics of a node to web pages.
+#[derive(Debug)]
+pub struct Gateway {
+    client: IrohClient,
+    token: String,
+    // Name messages are sent under when the page gives none
+    username: String,
+    message_limit: MessageLimit,
+    sequence: AtomicU64,
+}
+
+impl Gateway {
+    /// A gateway sending through `client` as `username`, with a new random
+    /// token.
+    pub fn new(client: IrohClient, username: String, message_limit: MessageLimit) -> Self {
+        Self {
+            client,
+            token: data_encoding::HEXLOWER.encode(&rand::random::<[u8; 16]>()),
+            username,
+            message_limit,
+            sequence: AtomicU64::new(0),
+        }
+    }
+
+    /// Token pages have to connect with.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Serves the pages connecting to `listener`, until accepting fails.
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) -> Result<(), String> {
+        loop {
+            let (stream, addr) = listener
+                .accept()
+                .await
+                .map_err(|e| format!("Failed to accept a connection: {}", e))?;
+            debug!(%addr, "Page connected to the gateway");
+            tokio::spawn(self.clone().connection(stream));
+        }
+    }
+
+    /// The topic a page asks for with the path and query of its request,
+    /// or the status refusing it.
+    pub fn route(&self, path: &str, query: Option<&str>) -> Result<String, StatusCode> {
+        let token = query
+            .into_iter()
+            .flat_map(|query| query.split('&'))
+            .find_map(|pair| pair.strip_prefix("token="));
+        if token != Some(self.token.as_str()) {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        let topic_hash = path.trim_start_matches('/');
+        match self.client.topic_name(topic_hash) {
+            Some(_) => Ok(topic_hash.to_string()),
+            None => Err(StatusCode::NOT_FOUND),
+        }
+    }
+
+    /// Sends what a page asked for on `topic_hash`, returning the error
+    /// frame to answer with if it was not sent.
+    pub async fn handle(&self, topic_hash: &str, frame: &str) -> Option<GatewayFrame> {
+        let error = |message: String| Some(GatewayFrame::Error { message });
+        let (content, author) = match serde_json::from_str(frame) {
+            Ok(PageFrame::Send { content, author }) => (content, author),
+            Err(e) => return error(format!("Invalid frame: {}", e)),
+        };
+        if content.trim().is_empty() {
+            return error("The message is empty".to_string());
+        }
+        if let Err(e) = self.message_limit.check(&content) {
+            return error(e);
+        }
+        let author = author
+            .map(|author| author.trim().to_string())
+            .filter(|author| !author.is_empty())
+            .unwrap_or_else(|| self.username.clone());
+
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        match self
+            .client
+            .send_message_to(topic_hash, author, content, sequence)
+            .await
+        {
+            Ok(message) => {
+                // Gossip does not deliver it back, so the pages and whatever
+                // else listens to the node are told here
+                IrohClient::broadcast_message(message);
+                None
+            }
+            Err(e) => error(e),
+        }
+    }
+
+    // Relays between a page and its topic until either side closes
+    async fn connection(self: Arc<Self>, stream: TcpStream) {
+        let mut route = Err(StatusCode::BAD_REQUEST);
+        // The refusal is as large as tungstenite makes it
+        #[allow(clippy::result_large_err)]
+        let callback = |request: &Request, response: Response| {
+            route = self.route(request.uri().path(), request.uri().query());
+            match &route {
+                Ok(_) => Ok(response),
+                Err(status) => {
+                    let mut refusal =
+                        ErrorResponse::new(status.canonical_reason().map(String::from));
+                    *refusal.status_mut() = *status;
+                    Err(refusal)
+                }
+            }
+        };
+        let socket = match tokio_tungstenite::accept_hdr_async(stream, callback).await {
+            Ok(socket) => socket,
+            Err(e) => {
+                debug!("Refused a page: {}", e);
+                return;
+            }
+        };
+        let Ok(topic_hash) = route else {
+            return;
+        };
+        let mut events = IrohClient::subscribe(Some(&topic_hash));
+
+        let (mut sink, mut frames) = socket.split();
+        let topic = GatewayFrame::Topic {
+            topic_name: self.client.topic_name(&topic_hash).unwrap_or_default(),
+            topic_hash: topic_hash.clone(),
+        };
+        if sink.send(encode(&topic)).await.is_err() {
+            return;
+        }
+        loop {
+            let reply = tokio::select! {
+                frame = frames.next() => match frame {
+                    Some(Ok(Message::Text(text))) => match self.handle(&topic_hash, &text).await {
+                        Some(reply) => reply,
+                        None => continue,
+                    },
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                    Some(Ok(_)) => continue,
+                },
+                event = events.next() => match event {
+                    Some(ClientEvent::Message(message)) if message.topic_hash == topic_hash => {
+                        GatewayFrame::Message(message)
+                    }
+                    Some(_) => continue,
+                    None => return,
+                },
+            };
+            if sink.send(encode(&reply)).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+fn encode(frame: &GatewayFrame) -> Message {
+    Message::Text(serde_json::to_string(frame).unwrap_or_default())
+}
@@ -0,0 +1,381 @@
+//! A client run by a task of its own, for user interfaces to share.
+//!
+//! A [`ClientHandle`] hands what it is asked to a task that carries it out
+//! on the client, one request at a time. Handles are cheap to clone, and
+//! never act on the client themselves: besides making requests, they only
+//! take snapshots of what the task changed, like the
+//! [`Session`](crate::client::Session), as soon as it is done.
+//!
+//! Requests are carried out in the order they are made, so a message sent
+//! after switching topics goes to the topic switched to. While 64 requests
+//! are waiting, asking for more waits for room.
+use crate::blobs::BlobHash;
+use crate::calendar::RsvpResponse;
+#[cfg(feature = "blobs")]
+use crate::client::Attachment;
+use crate::client::{
+    Allowlist, CalendarEvent, ChatMessage, IrohClient, MessageDelete, MessageEdit, Rsvp, Session,
+    TaskUpdate,
+};
+use crate::config::RelayMode;
+use crate::delivery::DeliveryReport;
+use crate::diagnostics::NodeStatus;
+use crate::filter::Filters;
+use crate::metrics::TrafficStats;
+use crate::profile::UserProfile;
+use crate::roster::PresenceStatus;
+use crate::tasks::TaskChange;
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use iroh::{NodeAddr, NodeId};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+// Requests waiting for the task, before making more waits for room
+const REQUEST_CAPACITY: usize = 64;
+
+// Carries out a request on the client, answering whoever made it
+type Request = Box<dyn for<'a> FnOnce(&'a IrohClient) -> BoxFuture<'a, ()> + Send>;
+
+/// Asks the task running a client to act on it.
+#[derive(Clone)]
+pub struct ClientHandle {
+    requests: mpsc::Sender<Request>,
+    // A clone of the client run by the task, only ever read from
+    client: IrohClient,
+}
+
+impl fmt::Debug for ClientHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientHandle")
+            .field("client", &self.client)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ClientHandle {
+    /// Moves `client` into a task of the current runtime, which runs it
+    /// until every handle is dropped.
+    pub fn spawn(client: IrohClient) -> Self {
+        let (requests, incoming) = mpsc::channel(REQUEST_CAPACITY);
+        tokio::spawn(serve(client.clone(), incoming));
+        Self { requests, client }
+    }
+
+    /// Starts the node, returning its id.
+    pub async fn initialize_network(&self) -> Result<String, String> {
+        self.request(move |client| Box::pin(client.initialize_network()))
+            .await?
+    }
+
+    /// Creates the topic `topic_name` and makes it the active topic,
+    /// returning its name, ticket and hash.
+    pub async fn create_topic(
+        &self,
+        topic_name: String,
+    ) -> Result<(String, String, String), String> {
+        self.request(move |client| Box::pin(client.create_topic(topic_name)))
+            .await?
+    }
+
+    /// Joins a topic with a ticket or invite and makes it the active topic,
+    /// returning its name and hash.
+    pub async fn join_topic(&self, ticket: String) -> Result<(String, String), String> {
+        self.request(move |client| Box::pin(client.join_topic(ticket)))
+            .await?
+    }
+
+    /// Makes `topic_hash`, which we are subscribed to, the active topic
+    /// again.
+    pub async fn switch_topic(&self, topic_hash: String) -> Result<(), String> {
+        self.request(move |client| {
+            let switched = client.switch_topic(&topic_hash);
+            Box::pin(async move { switched })
+        })
+        .await?
+    }
+
+    /// Sends a chat message to the active topic.
+    pub async fn send_message(
+        &self,
+        username: String,
+        message_content: String,
+        sequence: u64,
+    ) -> Result<ChatMessage, String> {
+        self.request(move |client| {
+            Box::pin(client.send_message(username, message_content, sequence))
+        })
+        .await?
+    }
+
+    /// Replaces the content of one of our messages on the active topic.
+    pub async fn edit_message(
+        &self,
+        target_id: String,
+        new_content: String,
+    ) -> Result<MessageEdit, String> {
+        self.request(move |client| Box::pin(client.edit_message(target_id, new_content)))
+            .await?
+    }
+
+    /// Deletes one of our messages on the active topic.
+    pub async fn delete_message(&self, target_id: String) -> Result<MessageDelete, String> {
+        self.request(move |client| Box::pin(client.delete_message(target_id)))
+            .await?
+    }
+
+    /// Changes a task of the active topic.
+    pub async fn update_task(
+        &self,
+        message_id: String,
+        change: TaskChange,
+    ) -> Result<TaskUpdate, String> {
+        self.request(move |client| Box::pin(client.update_task(message_id, change)))
+            .await?
+    }
+
+    /// Shares an event on the active topic.
+    pub async fn share_event(
+        &self,
+        username: String,
+        sequence: u64,
+        title: String,
+        starts_at: DateTime<Utc>,
+        ends_at: DateTime<Utc>,
+        location: String,
+    ) -> Result<(ChatMessage, CalendarEvent), String> {
+        self.request(move |client| {
+            Box::pin(client.share_event(username, sequence, title, starts_at, ends_at, location))
+        })
+        .await?
+    }
+
+    /// Answers an event shared on the active topic.
+    pub async fn respond_to_event(
+        &self,
+        message_id: String,
+        response: RsvpResponse,
+    ) -> Result<Rsvp, String> {
+        self.request(move |client| Box::pin(client.respond_to_event(message_id, response)))
+            .await?
+    }
+
+    /// Restricts who takes part in a topic we created to `members` and us.
+    /// `None` opens the topic again. The list is stamped later than `after`,
+    /// the timestamp of the list it replaces.
+    pub async fn set_allowlist(
+        &self,
+        topic_hash: String,
+        members: Option<BTreeSet<NodeId>>,
+        after: u64,
+    ) -> Result<Allowlist, String> {
+        self.request(move |client| {
+            Box::pin(async move { client.set_allowlist(&topic_hash, members, after).await })
+        })
+        .await?
+    }
+
+    /// Sends the messages of the active topic to a member that missed them.
+    #[cfg(feature = "blobs")]
+    pub async fn send_history(
+        &self,
+        recipient: NodeId,
+        messages: Vec<ChatMessage>,
+    ) -> Result<usize, String> {
+        self.request(move |client| Box::pin(client.send_history(recipient, messages)))
+            .await?
+    }
+
+    /// Shares a file on the topic `topic_hash`.
+    #[cfg(feature = "blobs")]
+    pub async fn post_attachment(
+        &self,
+        topic_hash: String,
+        name: String,
+        content: Vec<u8>,
+    ) -> Result<Attachment, String> {
+        self.request(move |client| {
+            Box::pin(async move { client.post_attachment(&topic_hash, &name, &content).await })
+        })
+        .await?
+    }
+
+    /// Downloads a file shared on a topic.
+    #[cfg(feature = "blobs")]
+    pub async fn fetch_attachment(&self, attachment: Attachment) -> Result<Vec<u8>, String> {
+        // The task starts downloads, which take long, without holding up the
+        // requests behind them
+        let download = self
+            .request(move |client| {
+                let client = client.clone();
+                let download =
+                    tokio::spawn(async move { client.fetch_attachment(&attachment).await });
+                Box::pin(std::future::ready(download))
+            })
+            .await?;
+        download
+            .await
+            .map_err(|e| format!("Download stopped: {}", e))?
+    }
+
+    /// Adds `content` to the blobs we serve, returning its hash.
+    #[cfg(feature = "blobs")]
+    pub async fn store_blob(&self, content: Vec<u8>) -> Result<BlobHash, String> {
+        self.request(move |client| {
+            let stored = client
+                .blob_store()
+                .ok_or_else(|| "Blobs need the network".to_string())
+                .and_then(|store| store.put(&content));
+            Box::pin(async move { stored })
+        })
+        .await?
+    }
+
+    /// Asks the peers of the active topic for the messages we missed.
+    pub async fn resync(&self) -> Result<(), String> {
+        self.request(move |client| {
+            let resync = client.resync();
+            Box::pin(async move { resync })
+        })
+        .await?
+    }
+
+    /// Creates a single use invite to the active topic.
+    pub async fn create_invite(&self) -> Result<String, String> {
+        self.request(move |client| {
+            let invite = client.create_invite();
+            Box::pin(async move { invite })
+        })
+        .await?
+    }
+
+    /// Announces our presence as `author` on every topic from now on.
+    pub async fn set_presence(&self, author: String, status: PresenceStatus) -> Result<(), String> {
+        self.request(move |client| {
+            client.set_presence(author, status);
+            Box::pin(async {})
+        })
+        .await
+    }
+
+    /// Announces our profile on every topic from now on.
+    pub async fn set_profile(
+        &self,
+        profile: UserProfile,
+        avatar_image: Option<BlobHash>,
+    ) -> Result<(), String> {
+        self.request(move |client| {
+            client.set_profile(profile, avatar_image);
+            Box::pin(async {})
+        })
+        .await
+    }
+
+    /// Filters what arrives from now on with `filters`.
+    pub async fn set_filters(&self, filters: Filters) -> Result<(), String> {
+        self.request(move |client| {
+            client.set_filters(filters);
+            Box::pin(async {})
+        })
+        .await
+    }
+
+    /// Adds the address of a peer and connects to it on every topic.
+    pub async fn add_peer(&self, node_addr: NodeAddr) -> Result<usize, String> {
+        self.request(move |client| Box::pin(client.add_peer(node_addr)))
+            .await?
+    }
+
+    /// Leaves every topic and goes off the network.
+    pub async fn shutdown(&self) {
+        // A client no longer running has nothing left to shut down
+        let _ = self
+            .request(move |client| Box::pin(client.shutdown()))
+            .await;
+    }
+
+    /// Our node id, once the network is initialized.
+    pub fn own_node_id(&self) -> Option<NodeId> {
+        self.client.own_node_id()
+    }
+
+    /// The relays the client uses.
+    pub fn relay_mode(&self) -> RelayMode {
+        self.client.relay_mode()
+    }
+
+    /// The topics we are in, and which of them is active.
+    pub fn session(&self) -> Session {
+        self.client.session()
+    }
+
+    /// How the node is connected, once the network is initialized.
+    pub fn node_status(&self) -> Option<NodeStatus> {
+        self.client.node_status()
+    }
+
+    /// The latest round trip time to `node_id`, if it was measured.
+    pub fn latency(&self, node_id: &NodeId) -> Option<Duration> {
+        self.client.latency(node_id)
+    }
+
+    /// What was sent and received on `topic_hash`.
+    pub fn traffic_stats(&self, topic_hash: &str) -> Option<TrafficStats> {
+        self.client.traffic_stats(topic_hash)
+    }
+
+    /// Who acknowledged one of our messages on `topic_hash`.
+    pub fn delivery_report(&self, topic_hash: &str, message_id: &str) -> Option<DeliveryReport> {
+        self.client.delivery_report(topic_hash, message_id)
+    }
+
+    /// The node that created `topic_hash`, if known.
+    pub fn topic_creator(&self, topic_hash: &str) -> Option<NodeId> {
+        self.client.topic_creator(topic_hash)
+    }
+
+    /// The members `topic_hash` is restricted to, if it is.
+    pub fn allowlist(&self, topic_hash: &str) -> Option<BTreeSet<NodeId>> {
+        self.client.allowlist(topic_hash)
+    }
+
+    /// The content of a blob we serve or fetched, if we have it.
+    #[cfg(feature = "blobs")]
+    pub fn blob(&self, hash: &BlobHash) -> Result<Option<Vec<u8>>, String> {
+        self.client
+            .blob_store()
+            .ok_or_else(|| "Blobs need the network".to_string())?
+            .get(hash)
+    }
+
+    // Has the task carry out `request` and waits for its outcome
+    async fn request<R: Send + 'static>(
+        &self,
+        request: impl for<'a> FnOnce(&'a IrohClient) -> BoxFuture<'a, R> + Send + 'static,
+    ) -> Result<R, String> {
+        let (reply, outcome) = oneshot::channel();
+        let request: Request = Box::new(move |client: &IrohClient| -> BoxFuture<'_, ()> {
+            Box::pin(async move {
+                let result = request(client).await;
+                // Nobody may be waiting anymore
+                let _ = reply.send(result);
+            })
+        });
+        self.requests
+            .send(request)
+            .await
+            .map_err(|_| "The client is no longer running".to_string())?;
+        outcome
+            .await
+            .map_err(|_| "The client stopped before answering".to_string())
+    }
+}
+
+// Carries out the requests of the handles until all of them are dropped
+async fn serve(client: IrohClient, mut requests: mpsc::Receiver<Request>) {
+    while let Some(request) = requests.recv().await {
+        request(&client).await;
+    }
+}
@@ -0,0 +1,77 @@
+//! When the members of a topic are active, by day of the week and hour of
+//! the day.
+//!
+//! Messages are counted at the hour they were sent in the time zone of the
+//! viewer, so the busiest cells tell when an announcement reaches the most
+//! members.
+use crate::automation::WEEKDAYS;
+use chrono::{DateTime, Datelike, TimeZone, Timelike, Utc, Weekday};
+
+/// Shades a cell of the heatmap can take, the first for no messages at all.
+pub const LEVELS: usize = 5;
+
+/// Messages counted per day of the week and hour of the day.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActivityHeatmap {
+    // Rows from Monday to Sunday, columns from midnight on
+    counts: [[u32; 24]; 7],
+}
+
+impl ActivityHeatmap {
+    /// Counts messages sent at `timestamps`, in the time zone `tz`.
+    pub fn of<Tz: TimeZone>(timestamps: impl IntoIterator<Item = DateTime<Utc>>, tz: &Tz) -> Self {
+        let mut heatmap = Self::default();
+        for at in timestamps {
+            heatmap.add(at, tz);
+        }
+        heatmap
+    }
+
+    /// Counts a message sent at `at`, in the time zone `tz`.
+    pub fn add<Tz: TimeZone>(&mut self, at: DateTime<Utc>, tz: &Tz) {
+        let local = at.with_timezone(tz);
+        let day = local.weekday().num_days_from_monday() as usize;
+        self.counts[day][local.hour() as usize] += 1;
+    }
+
+    /// Messages sent on `weekday` within `hour`, from 0 to 23.
+    pub fn count(&self, weekday: Weekday, hour: u32) -> u32 {
+        self.counts[weekday.num_days_from_monday() as usize]
+            .get(hour as usize)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Messages counted in all.
+    pub fn total(&self) -> u32 {
+        self.counts.iter().flatten().sum()
+    }
+
+    /// The day and hour with the most messages, the earliest in the week on
+    /// a tie, or none without messages.
+    pub fn busiest(&self) -> Option<(Weekday, u32)> {
+        let mut busiest = None;
+        let mut most = 0;
+        for (day, hours) in self.counts.iter().enumerate() {
+            for (hour, &count) in hours.iter().enumerate() {
+                if count > most {
+                    most = count;
+                    busiest = Some((day, hour));
+                }
+            }
+        }
+        busiest.map(|(day, hour)| (WEEKDAYS[day], hour as u32))
+    }
+
+    /// Shade of the cell of `weekday` and `hour`, from 0 for no messages to
+    /// `LEVELS - 1` for the busiest cell.
+    pub fn level(&self, weekday: Weekday, hour: u32) -> usize {
+        let count = self.count(weekday, hour);
+        let most = self.counts.iter().flatten().copied().max().unwrap_or(0);
+        if count == 0 || most == 0 {
+            return 0;
+        }
+        // Any message at all is visible
+        1 + (count as usize * (LEVELS - 2)) / most as usize
+    }
+}
@@ -0,0 +1,184 @@
+//! Invites revealing a topic only to whoever redeems them.
+//!
+//! A [`Ticket`](crate::ticket::Ticket) names its topic and carries its key,
+//! so anybody intercepting one learns about the room and can read it. An
+//! invite only names the inviting node and a random secret. The invitee's
+//! node connects to the inviter over the [`INVITE_ALPN`] protocol and sends
+//! the secret, and only then receives the real ticket. Invites work once and
+//! expire after [`INVITE_TTL`], so an intercepted invite is worthless once
+//! used. They are shared as text of the form `invite-<base32>`.
+use futures::future::BoxFuture;
+use iroh::endpoint::Connecting;
+use iroh::protocol::ProtocolHandler;
+use iroh::{Endpoint, NodeId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+/// ALPN of the protocol redeeming invites.
+pub const INVITE_ALPN: &[u8] = b"iroh-lab/invite/0";
+
+/// Prefix of the textual form of an invite.
+pub const INVITE_PREFIX: &str = "invite-";
+
+/// How long an invite can be redeemed.
+pub const INVITE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How long redeeming an invite may take.
+pub const REDEEM_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Largest ticket accepted from an inviter
+const MAX_TICKET_SIZE: usize = 16 * 1024;
+
+/// An invite to a topic, redeemed with the inviting node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Invite {
+    pub inviter: NodeId,
+    pub secret: [u8; 32],
+}
+
+impl fmt::Display for Invite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bytes = postcard::to_stdvec(self).expect("postcard::to_stdvec is infallible");
+        let mut text = data_encoding::BASE32_NOPAD.encode(&bytes);
+        text.make_ascii_lowercase();
+        write!(f, "{}{}", INVITE_PREFIX, text)
+    }
+}
+
+impl FromStr for Invite {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let encoded = s
+            .trim()
+            .strip_prefix(INVITE_PREFIX)
+            .ok_or_else(|| "Invalid invite format".to_string())?;
+        let bytes = data_encoding::BASE32_NOPAD
+            .decode(encoded.to_ascii_uppercase().as_bytes())
+            .map_err(|e| format!("Invalid invite encoding: {}", e))?;
+        postcard::from_bytes(&bytes).map_err(|e| format!("Invalid invite: {}", e))
+    }
+}
+
+// A ticket waiting for its invite to be redeemed
+#[derive(Debug)]
+struct Pending {
+    ticket: String,
+    expires: Instant,
+}
+
+/// The invites we handed out and that were not redeemed yet.
+#[derive(Debug, Default)]
+pub struct Invites {
+    pending: HashMap<[u8; 32], Pending>,
+}
+
+impl Invites {
+    /// Creates an invite from `inviter` to the topic of `ticket`, which can
+    /// be redeemed until [`INVITE_TTL`] after `now`.
+    pub fn create(&mut self, inviter: NodeId, ticket: String, now: Instant) -> Invite {
+        let secret = rand::random();
+        self.pending.insert(
+            secret,
+            Pending {
+                ticket,
+                expires: now + INVITE_TTL,
+            },
+        );
+        Invite { inviter, secret }
+    }
+
+    /// The ticket of the invite with `secret`, if it is still valid at
+    /// `now`. Every invite is only redeemed once.
+    pub fn redeem(&mut self, secret: &[u8; 32], now: Instant) -> Option<String> {
+        self.pending.retain(|_, pending| pending.expires > now);
+        self.pending.remove(secret).map(|pending| pending.ticket)
+    }
+
+    /// Number of invites that can still be redeemed at `now`.
+    pub fn pending(&self, now: Instant) -> usize {
+        self.pending
+            .values()
+            .filter(|pending| pending.expires > now)
+            .count()
+    }
+}
+
+/// Hands out the tickets of our invites to the nodes redeeming them.
+#[derive(Debug, Clone)]
+pub struct InviteProtocol {
+    invites: Arc<StdMutex<Invites>>,
+}
+
+impl InviteProtocol {
+    pub fn new(invites: Arc<StdMutex<Invites>>) -> Self {
+        Self { invites }
+    }
+}
+
+impl ProtocolHandler for InviteProtocol {
+    fn accept(&self, connecting: Connecting) -> BoxFuture<'static, anyhow::Result<()>> {
+        let invites = self.invites.clone();
+        Box::pin(async move {
+            let connection = connecting.await?;
+            let invitee = connection.remote_node_id()?;
+            let (mut send, mut recv) = connection.accept_bi().await?;
+            let secret: [u8; 32] = recv
+                .read_to_end(32)
+                .await?
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("Invite secret should be 32 bytes"))?;
+
+            let ticket = invites.lock().unwrap().redeem(&secret, Instant::now());
+            match ticket {
+                Some(ticket) => {
+                    info!(invitee = %invitee.fmt_short(), "Invite redeemed");
+                    send.write_all(ticket.as_bytes()).await?;
+                }
+                None => debug!(invitee = %invitee.fmt_short(), "Unknown or expired invite"),
+            }
+            send.finish()?;
+            // Let the invitee read the ticket before the connection goes
+            connection.closed().await;
+            Ok(())
+        })
+    }
+}
+
+/// Redeems `invite` with the inviting node, returning the ticket it reveals.
+pub async fn redeem(endpoint: &Endpoint, invite: &Invite) -> Result<String, String> {
+    let request = async {
+        let connection = endpoint
+            .connect(invite.inviter, INVITE_ALPN)
+            .await
+            .map_err(|e| format!("Failed to reach the inviter: {}", e))?;
+        let (mut send, mut recv) = connection
+            .open_bi()
+            .await
+            .map_err(|e| format!("Failed to redeem invite: {}", e))?;
+        send.write_all(&invite.secret)
+            .await
+            .map_err(|e| format!("Failed to redeem invite: {}", e))?;
+        send.finish()
+            .map_err(|e| format!("Failed to redeem invite: {}", e))?;
+        let ticket = recv
+            .read_to_end(MAX_TICKET_SIZE)
+            .await
+            .map_err(|e| format!("Failed to receive ticket: {}", e))?;
+        connection.close(0u32.into(), b"done");
+        Ok::<_, String>(ticket)
+    };
+    let ticket = tokio::time::timeout(REDEEM_TIMEOUT, request)
+        .await
+        .map_err(|_| "The inviter did not answer in time".to_string())??;
+
+    if ticket.is_empty() {
+        return Err("The invite was already used or has expired".to_string());
+    }
+    String::from_utf8(ticket).map_err(|_| "The inviter sent an invalid ticket".to_string())
+}
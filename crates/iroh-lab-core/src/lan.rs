@@ -0,0 +1,278 @@
+//! Finding the other nodes on the local network, without the internet.
+//!
+//! Nodes normally find each other through the DNS servers of n0 and reach
+//! each other through relays, neither of which is there at an event without
+//! internet access. In local network mode, every node instead announces its
+//! direct addresses to the multicast group [`LAN_DISCOVERY_GROUP`] every
+//! [`ANNOUNCE_INTERVAL`], and adds the addresses announced by the others to
+//! its endpoint. Announcements are signed by the node they announce, so
+//! nobody on the network can redirect another node's connections.
+use ed25519_dalek::Signature;
+use futures::stream::{self, BoxStream, StreamExt};
+use iroh::discovery::{Discovery, DiscoveryItem, NodeData, NodeInfo};
+use iroh::{Endpoint, NodeId, SecretKey};
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::collections::{BTreeSet, HashMap};
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::net::UdpSocket;
+use tokio::sync::{broadcast, Notify};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Multicast group and port the nodes on the local network announce
+/// themselves to.
+pub const LAN_DISCOVERY_GROUP: SocketAddrV4 =
+    SocketAddrV4::new(Ipv4Addr::new(239, 255, 73, 76), 45_213);
+
+/// How often a node announces its addresses.
+pub const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(5);
+
+// Names the source of the addresses in the endpoint's address book
+const PROVENANCE: &str = "iroh-lab.lan";
+
+// Largest announcement read, with room for dozens of addresses
+const MAX_ANNOUNCEMENT_SIZE: usize = 2048;
+
+// Discovered nodes buffered for slow subscribers
+const DISCOVERED_CAPACITY: usize = 64;
+
+/// The direct addresses of a node, as announced on the local network.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Announcement {
+    pub node_id: NodeId,
+    pub addrs: BTreeSet<SocketAddr>,
+}
+
+// An announcement as sent, with the signature of the node it announces
+#[derive(Serialize, Deserialize)]
+struct SignedAnnouncement {
+    announcement: Vec<u8>,
+    signature: Signature,
+}
+
+impl Announcement {
+    /// Encodes the announcement for sending, signed with `secret_key`.
+    pub fn sign(&self, secret_key: &SecretKey) -> Result<Vec<u8>, String> {
+        let announcement = postcard::to_stdvec(self)
+            .map_err(|e| format!("Failed to encode announcement: {}", e))?;
+        let signature = secret_key.sign(&announcement);
+        postcard::to_stdvec(&SignedAnnouncement {
+            announcement,
+            signature,
+        })
+        .map_err(|e| format!("Failed to encode announcement: {}", e))
+    }
+
+    /// Decodes an announcement received as `bytes`, which must be signed by
+    /// the node it announces.
+    pub fn verify(bytes: &[u8]) -> Result<Self, String> {
+        let signed: SignedAnnouncement =
+            postcard::from_bytes(bytes).map_err(|e| format!("Invalid announcement: {}", e))?;
+        let announcement: Self = postcard::from_bytes(&signed.announcement)
+            .map_err(|e| format!("Invalid announcement: {}", e))?;
+        announcement
+            .node_id
+            .verify(&signed.announcement, &signed.signature)
+            .map_err(|_| "Announcement not signed by the node it announces".to_string())?;
+        Ok(announcement)
+    }
+}
+
+/// Discovery of the nodes on the local network, added to an [`Endpoint`]
+/// in place of the n0 DNS servers. Announcing stops once it is dropped.
+#[derive(Debug)]
+pub struct LanDiscovery {
+    // Our direct addresses, as last published by the endpoint
+    addrs: Arc<StdMutex<BTreeSet<SocketAddr>>>,
+    // Wakes the announcing task when our addresses change
+    published: Arc<Notify>,
+    // The addresses last announced by every node heard of
+    nodes: Arc<StdMutex<HashMap<NodeId, BTreeSet<SocketAddr>>>>,
+    discovered: broadcast::Sender<DiscoveryItem>,
+    tasks: [JoinHandle<()>; 2],
+}
+
+impl LanDiscovery {
+    /// Starts announcing the node of `secret_key` and listening for the
+    /// other nodes. Must be called on a Tokio runtime.
+    pub fn new(secret_key: SecretKey) -> Result<Self, String> {
+        let socket =
+            Arc::new(bind().map_err(|e| format!("Failed to join the local network group: {}", e))?);
+        let addrs = Arc::new(StdMutex::new(BTreeSet::new()));
+        let published = Arc::new(Notify::new());
+        let nodes = Arc::new(StdMutex::new(HashMap::new()));
+        let (discovered, _) = broadcast::channel(DISCOVERED_CAPACITY);
+
+        let announcing = tokio::spawn(announce(
+            socket.clone(),
+            secret_key.clone(),
+            addrs.clone(),
+            published.clone(),
+        ));
+        let listening = tokio::spawn(listen(
+            socket,
+            secret_key.public(),
+            nodes.clone(),
+            discovered.clone(),
+        ));
+        info!(group = %LAN_DISCOVERY_GROUP, "Discovering nodes on the local network");
+
+        Ok(Self {
+            addrs,
+            published,
+            nodes,
+            discovered,
+            tasks: [announcing, listening],
+        })
+    }
+
+    // The nodes announced from now on
+    fn announced(&self) -> impl futures::Stream<Item = DiscoveryItem> {
+        stream::unfold(self.discovered.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(item) => return Some((item, receiver)),
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
+
+impl Drop for LanDiscovery {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+impl Discovery for LanDiscovery {
+    fn publish(&self, data: &NodeData) {
+        *self.addrs.lock().unwrap() = data.direct_addresses().clone();
+        self.published.notify_one();
+    }
+
+    fn resolve(
+        &self,
+        _endpoint: Endpoint,
+        node_id: NodeId,
+    ) -> Option<BoxStream<'static, anyhow::Result<DiscoveryItem>>> {
+        let known = self
+            .nodes
+            .lock()
+            .unwrap()
+            .get(&node_id)
+            .map(|addrs| discovery_item(node_id, addrs.clone()));
+        let later = self
+            .announced()
+            .filter(move |item| futures::future::ready(item.node_id() == node_id));
+        Some(stream::iter(known).chain(later).map(Ok).boxed())
+    }
+
+    fn subscribe(&self) -> Option<BoxStream<'static, DiscoveryItem>> {
+        Some(self.announced().boxed())
+    }
+}
+
+// Binds a socket to the port of the discovery group, shared with the other
+// nodes on this machine, and joins the group
+fn bind() -> std::io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, LAN_DISCOVERY_GROUP.port())).into())?;
+    socket.join_multicast_v4(LAN_DISCOVERY_GROUP.ip(), &Ipv4Addr::UNSPECIFIED)?;
+    // Nodes on the same machine hear each other too
+    socket.set_multicast_loop_v4(true)?;
+    UdpSocket::from_std(socket.into())
+}
+
+// Announces our addresses every interval, and right away when they change
+async fn announce(
+    socket: Arc<UdpSocket>,
+    secret_key: SecretKey,
+    addrs: Arc<StdMutex<BTreeSet<SocketAddr>>>,
+    published: Arc<Notify>,
+) {
+    let mut interval = tokio::time::interval(ANNOUNCE_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = published.notified() => {}
+        }
+        let announcement = Announcement {
+            node_id: secret_key.public(),
+            addrs: addrs.lock().unwrap().clone(),
+        };
+        if announcement.addrs.is_empty() {
+            continue;
+        }
+        let sent = match announcement.sign(&secret_key) {
+            Ok(bytes) => socket
+                .send_to(&bytes, LAN_DISCOVERY_GROUP)
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = sent {
+            warn!("Failed to announce on the local network: {}", e);
+        }
+    }
+}
+
+// Records the nodes announced by others, passing on new addresses
+async fn listen(
+    socket: Arc<UdpSocket>,
+    own_node_id: NodeId,
+    nodes: Arc<StdMutex<HashMap<NodeId, BTreeSet<SocketAddr>>>>,
+    discovered: broadcast::Sender<DiscoveryItem>,
+) {
+    let mut buffer = vec![0; MAX_ANNOUNCEMENT_SIZE];
+    loop {
+        let (len, from) = match socket.recv_from(&mut buffer).await {
+            Ok(received) => received,
+            Err(e) => {
+                warn!("Failed to listen on the local network: {}", e);
+                tokio::time::sleep(ANNOUNCE_INTERVAL).await;
+                continue;
+            }
+        };
+        let announcement = match Announcement::verify(&buffer[..len]) {
+            Ok(announcement) => announcement,
+            Err(e) => {
+                debug!(%from, "Dropping announcement: {}", e);
+                continue;
+            }
+        };
+        if announcement.node_id == own_node_id {
+            continue;
+        }
+        let previous = nodes
+            .lock()
+            .unwrap()
+            .insert(announcement.node_id, announcement.addrs.clone());
+        if previous.as_ref() != Some(&announcement.addrs) {
+            debug!(node_id = %announcement.node_id.fmt_short(), "Node found on the local network");
+            // Nobody may be subscribed yet
+            let _ = discovered.send(discovery_item(announcement.node_id, announcement.addrs));
+        }
+    }
+}
+
+// The addresses of a node, as added to the address book of the endpoint
+fn discovery_item(node_id: NodeId, addrs: BTreeSet<SocketAddr>) -> DiscoveryItem {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_micros() as u64)
+        .ok();
+    DiscoveryItem::new(
+        NodeInfo::from_parts(node_id, NodeData::new(None, addrs)),
+        PROVENANCE,
+        now,
+    )
+}
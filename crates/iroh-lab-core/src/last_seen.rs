@@ -0,0 +1,237 @@
+//! When each peer was last heard from, kept between runs.
+//!
+//! Every chat message with a verified sender and every presence
+//! announcement counts as a sign of life of the node that sent it. The
+//! times are stored as JSON next to the configuration, e.g.
+//! `~/.config/iroh-lab/last_seen.json` on Linux, so the member list can tell
+//! how long ago a member was around even right after starting the chat.
+//!
+//! Signs of life are also kept per topic, with the first time each member
+//! was seen there, so organizers can [export](LastSeen::export) who took
+//! part in a topic and when.
+use crate::config::Config;
+use chrono::{DateTime, Utc};
+use iroh::NodeId;
+use serde::{Deserialize, Serialize};
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+// Name of the file holding the last seen times
+const LAST_SEEN_FILE: &str = "last_seen.json";
+
+/// The most recent sign of life of a node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Seen {
+    /// Name the node's user had at the time.
+    pub author: String,
+    pub at: DateTime<Utc>,
+}
+
+/// When a member took part in a topic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Participation {
+    /// Name the node's user had when last seen.
+    pub author: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// File formats the participation in a topic is exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    /// Extension of files in the format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Csv => "CSV",
+            Self::Json => "JSON",
+        })
+    }
+}
+
+// A member in an export of the participation in a topic
+#[derive(Serialize)]
+struct ExportedParticipant<'a> {
+    node_id: &'a str,
+    author: &'a str,
+    first_seen: DateTime<Utc>,
+    last_seen: DateTime<Utc>,
+}
+
+/// When each node was last heard from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastSeen {
+    // Keyed by the node id as text, which JSON objects need
+    peers: BTreeMap<String, Seen>,
+    // Participation by topic hash, then node id as text. Missing in files
+    // of older releases
+    #[serde(default)]
+    topics: BTreeMap<String, BTreeMap<String, Participation>>,
+}
+
+impl LastSeen {
+    /// Location of the file of the current user, if the platform has a
+    /// configuration directory.
+    pub fn default_path() -> Option<PathBuf> {
+        Config::directory().map(|dir| dir.join(LAST_SEEN_FILE))
+    }
+
+    /// Loads the times from `path`, or none if the file does not exist.
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    /// Saves the times to `path`, creating its directory if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to encode last seen times: {}", e))?;
+        std::fs::write(path, contents)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Records that `author` on `node_id` was around `at`. Older times than
+    /// the one already known are ignored, as messages may arrive late.
+    pub fn record(&mut self, node_id: NodeId, author: &str, at: DateTime<Utc>) {
+        let seen = Seen {
+            author: author.to_string(),
+            at,
+        };
+        self.peers
+            .entry(node_id.to_string())
+            .and_modify(|known| {
+                if known.at <= at {
+                    *known = seen.clone();
+                }
+            })
+            .or_insert(seen);
+    }
+
+    /// Records that `author` on `node_id` was around `at` on the topic
+    /// `topic_hash`, which also counts as a sign of life in general.
+    pub fn record_in(
+        &mut self,
+        topic_hash: &str,
+        node_id: NodeId,
+        author: &str,
+        at: DateTime<Utc>,
+    ) {
+        self.record(node_id, author, at);
+        let members = self.topics.entry(topic_hash.to_string()).or_default();
+        match members.entry(node_id.to_string()) {
+            Entry::Vacant(entry) => {
+                entry.insert(Participation {
+                    author: author.to_string(),
+                    first_seen: at,
+                    last_seen: at,
+                });
+            }
+            Entry::Occupied(mut entry) => {
+                let known = entry.get_mut();
+                known.first_seen = known.first_seen.min(at);
+                if known.last_seen <= at {
+                    known.last_seen = at;
+                    known.author = author.to_string();
+                }
+            }
+        }
+    }
+
+    /// When `node_id` was last heard from.
+    pub fn get(&self, node_id: &NodeId) -> Option<&Seen> {
+        self.peers.get(&node_id.to_string())
+    }
+
+    /// The members seen on the topic `topic_hash` by node id, the earliest
+    /// first.
+    pub fn participants(&self, topic_hash: &str) -> Vec<(&str, &Participation)> {
+        let mut participants: Vec<_> = self
+            .topics
+            .get(topic_hash)
+            .into_iter()
+            .flatten()
+            .map(|(node_id, participation)| (node_id.as_str(), participation))
+            .collect();
+        participants.sort_by_key(|(node_id, participation)| (participation.first_seen, *node_id));
+        participants
+    }
+
+    /// The participation in the topic `topic_hash` as the contents of a
+    /// file in `format`, one member per row or object.
+    pub fn export(&self, topic_hash: &str, format: ExportFormat) -> Result<String, String> {
+        let participants = self.participants(topic_hash);
+        match format {
+            ExportFormat::Csv => {
+                let mut csv = "node_id,author,first_seen,last_seen\n".to_string();
+                for (node_id, participation) in participants {
+                    csv.push_str(&format!(
+                        "{},{},{},{}\n",
+                        node_id,
+                        csv_field(&participation.author),
+                        participation.first_seen.to_rfc3339(),
+                        participation.last_seen.to_rfc3339()
+                    ));
+                }
+                Ok(csv)
+            }
+            ExportFormat::Json => {
+                let exported: Vec<_> = participants
+                    .into_iter()
+                    .map(|(node_id, participation)| ExportedParticipant {
+                        node_id,
+                        author: &participation.author,
+                        first_seen: participation.first_seen,
+                        last_seen: participation.last_seen,
+                    })
+                    .collect();
+                serde_json::to_string_pretty(&exported)
+                    .map_err(|e| format!("Failed to encode participants: {}", e))
+            }
+        }
+    }
+}
+
+/// How long before `now` the time `at` was, e.g. "5m ago".
+pub fn ago(at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let seconds = (now - at).num_seconds();
+    match seconds {
+        ..=59 => "just now".to_string(),
+        60..=3_599 => format!("{}m ago", seconds / 60),
+        3_600..=86_399 => format!("{}h ago", seconds / 3_600),
+        _ => format!("{}d ago", seconds / 86_400),
+    }
+}
+
+// Quotes `value` for a CSV file if it holds a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
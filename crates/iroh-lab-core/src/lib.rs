@@ -0,0 +1,59 @@
+pub mod access;
+pub mod archive;
+pub mod audit;
+pub mod automation;
+pub mod blobs;
+#[cfg(feature = "bridges")]
+pub mod bridge;
+pub mod calendar;
+pub mod chunk;
+pub mod client;
+pub mod clock;
+pub mod commands;
+pub mod composer;
+pub mod config;
+pub mod contacts;
+pub mod conversation;
+pub mod crypto;
+pub mod delivery;
+pub mod demo;
+pub mod diagnostics;
+pub mod emoji;
+pub mod engine;
+pub mod filter;
+#[cfg(feature = "gateway")]
+pub mod gateway;
+pub mod handle;
+pub mod heatmap;
+pub mod invite;
+pub mod lan;
+pub mod last_seen;
+pub mod liveness;
+pub mod load;
+pub mod markdown;
+pub mod metrics;
+pub mod notes;
+pub mod ping;
+pub mod prelude;
+pub mod profile;
+pub mod reconnect;
+pub mod reputation;
+#[cfg(feature = "rest")]
+pub mod rest;
+pub mod room;
+pub mod roster;
+pub mod rpc;
+pub mod snapshot;
+pub mod sync;
+pub mod tasks;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod ticket;
+pub mod tombstone;
+pub mod transport;
+pub mod unread;
+pub mod welcome;
+pub mod wire;
+
+pub use client::{ChatMessage, ClientEvent, IrohClient};
+pub use engine::ChatEngine;
@@ -0,0 +1,76 @@
+//! Liveness of the members of a topic.
+//!
+//! Every node on a topic sends a [`Heartbeat`](crate::client::Heartbeat) at
+//! [`HEARTBEAT_INTERVAL`]. Together with chat messages and gossip neighbor
+//! events, these tell when each member was last seen, and so whether the
+//! topic is active, quiet, or whether we lost contact with everybody we
+//! talked to before.
+use iroh::NodeId;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How often each node announces that it is still subscribed to a topic.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Members not seen for this long are no longer counted as present. Long
+/// enough for a couple of heartbeats to get lost.
+pub const ACTIVE_WINDOW: Duration = Duration::from_secs(3 * HEARTBEAT_INTERVAL.as_secs());
+
+/// How the topic looks from our side.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TopicActivity {
+    /// Other members have been seen recently.
+    Active,
+    /// Nobody else has been seen on the topic yet.
+    #[default]
+    Quiet,
+    /// Members were seen before, but none of them recently. We are probably
+    /// cut off from the rest of the topic.
+    Partitioned,
+}
+
+/// Last-seen times of the members of a topic.
+#[derive(Debug, Default)]
+pub struct Liveness {
+    last_seen: HashMap<NodeId, Instant>,
+}
+
+impl Liveness {
+    /// Records that `node` was seen at `now`.
+    pub fn record(&mut self, node: NodeId, now: Instant) {
+        let last_seen = self.last_seen.entry(node).or_insert(now);
+        *last_seen = (*last_seen).max(now);
+    }
+
+    /// When `node` was last seen, if ever.
+    pub fn last_seen(&self, node: &NodeId) -> Option<Instant> {
+        self.last_seen.get(node).copied()
+    }
+
+    /// Members seen within the [`ACTIVE_WINDOW`] before `now`.
+    pub fn active_members(&self, now: Instant) -> Vec<NodeId> {
+        self.last_seen
+            .iter()
+            .filter(|(_, seen)| now.saturating_duration_since(**seen) < ACTIVE_WINDOW)
+            .map(|(node, _)| *node)
+            .collect()
+    }
+
+    /// Every member seen so far, most recently seen first.
+    pub fn known_members(&self) -> Vec<NodeId> {
+        let mut members: Vec<_> = self.last_seen.iter().collect();
+        members.sort_by(|a, b| b.1.cmp(a.1));
+        members.into_iter().map(|(node, _)| *node).collect()
+    }
+
+    /// The activity of the topic at `now`.
+    pub fn activity(&self, now: Instant) -> TopicActivity {
+        if self.last_seen.is_empty() {
+            TopicActivity::Quiet
+        } else if self.active_members(now).is_empty() {
+            TopicActivity::Partitioned
+        } else {
+            TopicActivity::Active
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Bookkeeping of the `loadtest` binary, which runs several clients in one
+//! process and has them chat on one topic.
+//!
+//! Gossip does not deliver the messages of a client back to it, so a message
+//! sent while `n` clients listen should arrive at the `n - 1` others. Each
+//! client counts a message once, and arrivals of a message it received
+//! already are duplicates; the arrivals missing by the end of the run are
+//! counted as lost. As the clients share one clock, the latency of an
+//! arrival is exact: the time since the message was sent.
+use iroh::NodeId;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// The messages of a load test, and when they arrived.
+#[derive(Debug)]
+pub struct LoadRun {
+    // Clients each message should reach, all but its sender
+    receivers: usize,
+    // When each message was sent, and the clients it arrived at since
+    messages: HashMap<String, (Instant, HashSet<NodeId>)>,
+    // How long every arrival took
+    latencies: Vec<Duration>,
+    // Arrivals at clients that had the message already, e.g. sent again
+    duplicates: usize,
+}
+
+/// What a load test measured.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadSummary {
+    /// Messages sent.
+    pub sent: usize,
+    /// Arrivals expected, every message at every client but its sender.
+    pub expected: usize,
+    /// Arrivals counted, once per message and client.
+    pub delivered: usize,
+    /// Arrivals at clients that had the message already.
+    pub duplicates: usize,
+    /// Latency of the arrivals, fastest first.
+    pub latencies: Vec<Duration>,
+    /// How long the run took, from the first message sent until the last
+    /// arrival or the end of waiting for it.
+    pub elapsed: Duration,
+}
+
+impl LoadRun {
+    /// A run among `clients` clients.
+    pub fn new(clients: usize) -> Self {
+        Self {
+            receivers: clients.saturating_sub(1),
+            messages: HashMap::new(),
+            latencies: Vec::new(),
+            duplicates: 0,
+        }
+    }
+
+    /// Records message `id` as sent at `now`.
+    pub fn sent(&mut self, id: impl Into<String>, now: Instant) {
+        self.messages.insert(id.into(), (now, HashSet::new()));
+    }
+
+    /// Records the arrival of message `id` at the client `receiver` at `now`,
+    /// returning whether it was sent in this run.
+    pub fn received(&mut self, receiver: NodeId, id: &str, now: Instant) -> bool {
+        let Some((sent_at, receivers)) = self.messages.get_mut(id) else {
+            return false;
+        };
+        if receivers.insert(receiver) {
+            self.latencies.push(now.saturating_duration_since(*sent_at));
+        } else {
+            self.duplicates += 1;
+        }
+        true
+    }
+
+    /// Whether every message reached every receiver.
+    pub fn complete(&self) -> bool {
+        self.messages
+            .values()
+            .all(|(_, receivers)| receivers.len() >= self.receivers)
+    }
+
+    /// What was measured, with the run having taken `elapsed`.
+    pub fn summary(&self, elapsed: Duration) -> LoadSummary {
+        let mut latencies = self.latencies.clone();
+        latencies.sort();
+        LoadSummary {
+            sent: self.messages.len(),
+            expected: self.messages.len() * self.receivers,
+            delivered: latencies.len(),
+            duplicates: self.duplicates,
+            latencies,
+            elapsed,
+        }
+    }
+}
+
+impl LoadSummary {
+    /// Arrivals that never happened.
+    pub fn lost(&self) -> usize {
+        self.expected.saturating_sub(self.delivered)
+    }
+
+    /// Share of the expected arrivals lost, from 0 to 1.
+    pub fn loss(&self) -> f64 {
+        if self.expected == 0 {
+            return 0.0;
+        }
+        self.lost() as f64 / self.expected as f64
+    }
+
+    /// The `percent` percentile of the latencies, if anything arrived.
+    pub fn percentile(&self, percent: f64) -> Option<Duration> {
+        let count = self.latencies.len();
+        if count == 0 {
+            return None;
+        }
+        // The rank of the arrival at the percentile, counting from 1
+        let rank = ((percent / 100.0 * count as f64).ceil() as usize).clamp(1, count);
+        Some(self.latencies[rank - 1])
+    }
+
+    /// Arrivals per second over the run.
+    pub fn throughput(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+        self.delivered as f64 / seconds
+    }
+}
+
+impl fmt::Display for LoadSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Sent:        {} message(s), {:.1} s until the last arrival",
+            self.sent,
+            self.elapsed.as_secs_f64()
+        )?;
+        writeln!(
+            f,
+            "Delivered:   {} of {} ({:.1} msg/s), {} duplicate(s)",
+            self.delivered,
+            self.expected,
+            self.throughput(),
+            self.duplicates
+        )?;
+        writeln!(
+            f,
+            "Lost:        {} ({:.2}%)",
+            self.lost(),
+            self.loss() * 100.0
+        )?;
+        let percentiles = [50.0, 95.0, 99.0, 100.0].map(|percent| self.percentile(percent));
+        match percentiles {
+            [Some(median), Some(p95), Some(p99), Some(max)] => write!(
+                f,
+                "Latency:     min {}, median {}, 95th {}, 99th {}, max {}",
+                millis(self.latencies[0]),
+                millis(median),
+                millis(p95),
+                millis(p99),
+                millis(max)
+            ),
+            _ => f.write_str("Latency:     nothing arrived"),
+        }
+    }
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
@@ -0,0 +1,274 @@
+//! The basic Markdown understood in message content.
+//!
+//! Each line of a message is a block of its own: a paragraph, an item of a
+//! bulleted (`- `, `* `, `+ `) or numbered (`1. `) list, or a quote
+//! (`> `). Within a block, `**bold**`, `*italics*` or `_italics_` and
+//! `` `inline code` `` are recognized, and a backslash escapes a marker.
+//! Markers without their closing counterpart stay as they were typed.
+//! Words like `#book-club` link to the topic of that [name](topic_slug),
+//! and web addresses starting with `http://` or `https://` are
+//! [links](web_link) too.
+//!
+//! Blocks never nest, and control and bidirectional formatting characters
+//! are dropped, so no content can reorder or break out of the message row
+//! showing it.
+
+/// How a span of text is emphasized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpanStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+}
+
+/// A run of text with a single style.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub text: String,
+    pub style: SpanStyle,
+}
+
+/// What a line of a message is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockKind {
+    Paragraph,
+    /// An item of a bulleted list.
+    Bullet,
+    /// An item of a numbered list, with its number.
+    Numbered(u32),
+    Quote,
+}
+
+/// A line of a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub kind: BlockKind,
+    pub spans: Vec<Span>,
+}
+
+// Characters a backslash turns into plain text
+const ESCAPABLE: &str = "\\`*_>-+.#";
+
+/// What a word of message content links to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Link {
+    /// A topic, by the [slug](topic_slug) of its name.
+    Topic(String),
+    /// A web address.
+    Web(String),
+}
+
+/// What `word` links to, if it is a [web address](web_link) or a
+/// [topic](topic_link).
+pub fn link(word: &str) -> Option<Link> {
+    web_link(word)
+        .map(|address| Link::Web(address.to_string()))
+        .or_else(|| topic_link(word).map(|name| Link::Topic(name.to_string())))
+}
+
+/// The topic `word` links to, as in `#book-club`, without the `#` and the
+/// punctuation ending a sentence. Links start with a letter, so numbers as
+/// in `#1` are no links.
+pub fn topic_link(word: &str) -> Option<&str> {
+    let name = word
+        .strip_prefix('#')?
+        .trim_end_matches(|c: char| c.is_ascii_punctuation() && c != '-' && c != '_');
+    let linked = name.starts_with(char::is_alphabetic)
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    linked.then_some(name)
+}
+
+/// The web address `word` is, without the brackets and punctuation around
+/// it, as in `(https://example.com).`. Only `http` and `https` addresses
+/// are links, so no link opens local files or other applications.
+pub fn web_link(word: &str) -> Option<&str> {
+    let address = word
+        .trim_start_matches(['(', '<', '[', '"', '\''])
+        .trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '>', ']', '"', '\'']);
+    let rest = address
+        .strip_prefix("https://")
+        .or_else(|| address.strip_prefix("http://"))?;
+    (!rest.is_empty() && !rest.starts_with('/')).then_some(address)
+}
+
+/// How the topic `name` is linked to, e.g. `book-club` for "Book Club".
+/// Links match topics whatever their case.
+pub fn topic_slug(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+/// Parses the lines of `content` into blocks, skipping empty lines.
+pub fn parse(content: &str) -> Vec<Block> {
+    content
+        .lines()
+        .map(sanitize)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (kind, rest) = block_kind(&line);
+            let mut spans = Vec::new();
+            inline(rest.trim(), SpanStyle::default(), &mut spans);
+            Block { kind, spans }
+        })
+        .collect()
+}
+
+// Drops control and bidirectional formatting characters, turning tabs into
+// spaces
+fn sanitize(line: &str) -> String {
+    line.chars()
+        .filter_map(|c| match c {
+            '\t' => Some(' '),
+            '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}' => None,
+            c if c.is_control() => None,
+            c => Some(c),
+        })
+        .collect()
+}
+
+// The kind of block `line` starts, and the rest of the line
+fn block_kind(line: &str) -> (BlockKind, &str) {
+    let trimmed = line.trim_start();
+    if let Some(rest) = trimmed.strip_prefix('>') {
+        return (BlockKind::Quote, rest);
+    }
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(rest) = trimmed.strip_prefix(bullet) {
+            return (BlockKind::Bullet, rest);
+        }
+    }
+    let digits = trimmed.len()
+        - trimmed
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .len();
+    if (1..=9).contains(&digits) {
+        if let Some(rest) = trimmed[digits..].strip_prefix(". ") {
+            if let Ok(number) = trimmed[..digits].parse() {
+                return (BlockKind::Numbered(number), rest);
+            }
+        }
+    }
+    (BlockKind::Paragraph, line)
+}
+
+// Appends the spans of `text`, which is emphasized as `style`, to `spans`
+fn inline(text: &str, style: SpanStyle, spans: &mut Vec<Span>) {
+    let mut literal = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '\\' {
+            if let Some(next) = rest[1..].chars().next().filter(|n| ESCAPABLE.contains(*n)) {
+                literal.push(next);
+                rest = &rest[1 + next.len_utf8()..];
+                continue;
+            }
+        }
+        let after_word = literal
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric);
+        if let Some((marker, inner_style)) = marker(rest, style, after_word) {
+            if let Some((inner, after)) = enclosed(rest, marker) {
+                push(spans, std::mem::take(&mut literal), style);
+                if inner_style.code {
+                    push(spans, inner.to_string(), inner_style);
+                } else {
+                    inline(inner, inner_style, spans);
+                }
+                rest = after;
+                continue;
+            }
+        }
+        literal.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    push(spans, literal, style);
+}
+
+// The marker `rest` starts with, and the style of the text it encloses.
+// Underscores within a word, as in snake_case, are no markers.
+fn marker(rest: &str, style: SpanStyle, after_word: bool) -> Option<(&'static str, SpanStyle)> {
+    if rest.starts_with('`') {
+        Some((
+            "`",
+            SpanStyle {
+                code: true,
+                ..style
+            },
+        ))
+    } else if rest.starts_with("**") {
+        Some((
+            "**",
+            SpanStyle {
+                bold: true,
+                ..style
+            },
+        ))
+    } else if rest.starts_with('*') {
+        Some((
+            "*",
+            SpanStyle {
+                italic: true,
+                ..style
+            },
+        ))
+    } else if rest.starts_with('_') && !after_word {
+        Some((
+            "_",
+            SpanStyle {
+                italic: true,
+                ..style
+            },
+        ))
+    } else {
+        None
+    }
+}
+
+// The text `rest` encloses between `marker` and its closing counterpart,
+// and what follows it. Emphasized text neither starts nor ends with a
+// space, and markers close on the end of a run of stars, so single ones
+// never close on half of a double one.
+fn enclosed<'t>(rest: &'t str, marker: &str) -> Option<(&'t str, &'t str)> {
+    let body = &rest[marker.len()..];
+    let code = marker == "`";
+    if !code && body.starts_with(char::is_whitespace) {
+        return None;
+    }
+    // Every occurrence, including overlapping ones as in `***`
+    let ends = body
+        .char_indices()
+        .filter(|(end, _)| body[*end..].starts_with(marker));
+    ends.map(|(end, _)| end).find_map(|end| {
+        let inner = &body[..end];
+        let after = &body[end + marker.len()..];
+        let closes = if inner.is_empty() {
+            false
+        } else if code {
+            true
+        } else if inner.ends_with(char::is_whitespace) {
+            false
+        } else if marker == "_" {
+            !inner.ends_with('_') && !after.starts_with(|c: char| c == '_' || c.is_alphanumeric())
+        } else {
+            // The closing marker is the last of a run of stars
+            !after.starts_with('*') && (marker.len() > 1 || !inner.ends_with('*'))
+        };
+        closes.then_some((inner, after))
+    })
+}
+
+// Appends `text` as `style`, joining it to the last span if it has the same
+fn push(spans: &mut Vec<Span>, text: String, style: SpanStyle) {
+    if text.is_empty() {
+        return;
+    }
+    match spans.last_mut() {
+        Some(last) if last.style == style => last.text.push_str(&text),
+        _ => spans.push(Span { text, style }),
+    }
+}
@@ -0,0 +1,217 @@
+//! Measurements of how the chat performs, kept while the application runs.
+//!
+//! The end-to-end latency of a message is the time from its sender stamping
+//! it until it is shown here. It covers gossip delivery and how long the
+//! message waited for the UI to pick it up, so comparing the histograms of
+//! two builds shows what a change to the message pipeline gained. Latencies
+//! rely on the clocks of sender and receiver agreeing, so they are only
+//! meaningful to a few milliseconds on synchronized machines.
+//!
+//! The [`Traffic`] of a topic counts the bytes of the gossip messages sent
+//! and received on it by neighbor, so how much went through relays can be
+//! told from how the neighbors are reached when the stats are taken.
+use iroh::NodeId;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Window over which messages per minute are counted.
+pub const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Upper bounds of the latency buckets, in milliseconds. Slower messages
+/// fall into a last, open bucket.
+pub const LATENCY_BUCKETS_MS: [u64; 10] = [10, 20, 50, 100, 200, 500, 1000, 2000, 5000, 10000];
+
+/// A histogram of message latencies.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    // Messages per bucket, the last one counting those above every bound
+    counts: [u64; LATENCY_BUCKETS_MS.len() + 1],
+}
+
+/// Where a percentile of the latencies lies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Percentile {
+    /// At most this long.
+    AtMost(Duration),
+    /// Longer than the largest bucket bound.
+    Above(Duration),
+}
+
+impl fmt::Display for Percentile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AtMost(bound) => write!(f, "≤ {} ms", bound.as_millis()),
+            Self::Above(bound) => write!(f, "> {} ms", bound.as_millis()),
+        }
+    }
+}
+
+impl LatencyHistogram {
+    /// Records a message that took `latency` to arrive.
+    pub fn record(&mut self, latency: Duration) {
+        let millis = latency.as_millis();
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| millis <= u128::from(*bound))
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.counts[bucket] += 1;
+    }
+
+    /// Number of messages recorded.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Messages recorded per bucket, in the order of [`LATENCY_BUCKETS_MS`]
+    /// followed by the open bucket.
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// The bucket holding the `percent` percentile, if anything was
+    /// recorded.
+    pub fn percentile(&self, percent: f64) -> Option<Percentile> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        // The rank of the message at the percentile, counting from 1
+        let rank = ((percent / 100.0 * count as f64).ceil() as u64).clamp(1, count);
+        let mut seen = 0;
+        for (bucket, bucket_count) in self.counts.iter().enumerate() {
+            seen += bucket_count;
+            if seen >= rank {
+                return Some(match LATENCY_BUCKETS_MS.get(bucket) {
+                    Some(bound) => Percentile::AtMost(Duration::from_millis(*bound)),
+                    None => Percentile::Above(Duration::from_millis(
+                        LATENCY_BUCKETS_MS[LATENCY_BUCKETS_MS.len() - 1],
+                    )),
+                });
+            }
+        }
+        None
+    }
+}
+
+impl fmt::Display for LatencyHistogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.percentile(50.0), self.percentile(95.0)) {
+            (Some(median), Some(p95)) => write!(
+                f,
+                "median {}, 95th percentile {} over {} message(s)",
+                median,
+                p95,
+                self.count()
+            ),
+            _ => f.write_str("no messages"),
+        }
+    }
+}
+
+/// The gossip traffic of a topic, counted while the application runs.
+#[derive(Debug, Clone, Default)]
+pub struct Traffic {
+    bytes_sent: u64,
+    bytes_received: u64,
+    // Bytes sent to and received from each neighbor
+    by_neighbor: HashMap<NodeId, u64>,
+    // When the gossip messages of the last RATE_WINDOW were sent or received
+    messages: VecDeque<Instant>,
+}
+
+/// How a neighbor is reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrafficPath {
+    Direct,
+    Relayed,
+}
+
+/// A snapshot of the [`Traffic`] of a topic.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrafficStats {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// Gossip messages sent and received within the last [`RATE_WINDOW`].
+    pub messages_per_minute: usize,
+    /// Current gossip neighbors on the topic.
+    pub peers: usize,
+    /// Bytes exchanged with the neighbors now reached directly.
+    pub direct_bytes: u64,
+    /// Bytes exchanged with the neighbors now reached through a relay.
+    pub relayed_bytes: u64,
+}
+
+impl Traffic {
+    /// Records a gossip message of `bytes` sent at `now` to `neighbors`.
+    pub fn sent(
+        &mut self,
+        bytes: usize,
+        neighbors: impl IntoIterator<Item = NodeId>,
+        now: Instant,
+    ) {
+        self.bytes_sent += bytes as u64;
+        for neighbor in neighbors {
+            *self.by_neighbor.entry(neighbor).or_default() += bytes as u64;
+        }
+        self.message(now);
+    }
+
+    /// Records a gossip message of `bytes` received at `now` from the
+    /// neighbor `from`.
+    pub fn received(&mut self, bytes: usize, from: NodeId, now: Instant) {
+        self.bytes_received += bytes as u64;
+        *self.by_neighbor.entry(from).or_default() += bytes as u64;
+        self.message(now);
+    }
+
+    // Counts a message towards the rate, forgetting those out of the window
+    fn message(&mut self, now: Instant) {
+        self.messages.push_back(now);
+        while self
+            .messages
+            .front()
+            .is_some_and(|at| now.saturating_duration_since(*at) > RATE_WINDOW)
+        {
+            self.messages.pop_front();
+        }
+    }
+
+    /// The traffic at `now` with `peers` neighbors, each reached as `path`
+    /// tells, if it is reached at all.
+    pub fn stats(
+        &self,
+        now: Instant,
+        peers: usize,
+        path: impl Fn(NodeId) -> Option<TrafficPath>,
+    ) -> TrafficStats {
+        let mut stats = TrafficStats {
+            bytes_sent: self.bytes_sent,
+            bytes_received: self.bytes_received,
+            messages_per_minute: self
+                .messages
+                .iter()
+                .filter(|at| now.saturating_duration_since(**at) <= RATE_WINDOW)
+                .count(),
+            peers,
+            ..TrafficStats::default()
+        };
+        for (neighbor, bytes) in &self.by_neighbor {
+            match path(*neighbor) {
+                Some(TrafficPath::Direct) => stats.direct_bytes += bytes,
+                Some(TrafficPath::Relayed) => stats.relayed_bytes += bytes,
+                None => {}
+            }
+        }
+        stats
+    }
+}
+
+impl TrafficStats {
+    /// Share of the traffic with reachable neighbors that goes through a
+    /// relay, from 0 to 1, if there was any.
+    pub fn relayed_share(&self) -> Option<f64> {
+        let total = self.direct_bytes + self.relayed_bytes;
+        (total > 0).then(|| self.relayed_bytes as f64 / total as f64)
+    }
+}
@@ -0,0 +1,98 @@
+//! Notes to self, a conversation that never leaves this device.
+//!
+//! Notes are shown like any other topic, but are never sent over the
+//! network. They are kept as one JSON encoded [`ChatMessage`] per line next
+//! to the configuration, e.g. `~/.config/iroh-lab/notes.jsonl` on Linux,
+//! which makes them a scratch space and a way to try the chat offline.
+use crate::client::ChatMessage;
+use crate::config::Config;
+use chrono::Utc;
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Topic hash of the notes, which is never joined on the network.
+pub const NOTES_TOPIC_HASH: &str = "notes-to-self";
+
+/// Name the notes are shown under.
+pub const NOTES_TOPIC_NAME: &str = "Notes to self";
+
+// Name of the file holding the notes
+const NOTES_FILE: &str = "notes.jsonl";
+
+/// The notes, open for adding more.
+#[derive(Debug)]
+pub struct Notes {
+    path: PathBuf,
+    // Lamport timestamp of the latest note
+    lamport: u64,
+}
+
+impl Notes {
+    /// Location of the notes of the current user, if the platform has a
+    /// configuration directory.
+    pub fn default_path() -> Option<PathBuf> {
+        Config::directory().map(|dir| dir.join(NOTES_FILE))
+    }
+
+    /// Opens the notes at `path`, returning them with the notes written so
+    /// far, oldest first.
+    pub fn open(path: &Path) -> Result<(Self, Vec<ChatMessage>), String> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        let notes = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str::<ChatMessage>(line)
+                    .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let lamport = notes.iter().map(|note| note.lamport).max().unwrap_or(0);
+        Ok((
+            Self {
+                path: path.to_path_buf(),
+                lamport,
+            },
+            notes,
+        ))
+    }
+
+    /// Writes a note by `author`, returning it for display.
+    pub fn add(&mut self, author: &str, content: &str) -> Result<ChatMessage, String> {
+        let note = ChatMessage {
+            id: Uuid::new_v4().to_string(),
+            author: author.to_string(),
+            content: content.to_string(),
+            timestamp: Utc::now(),
+            topic_hash: NOTES_TOPIC_HASH.to_string(),
+            sequence: self.lamport + 1,
+            lamport: self.lamport + 1,
+            verified_sender: None,
+            edited_at: None,
+            deleted_at: None,
+        };
+        let mut line =
+            serde_json::to_string(&note).map_err(|e| format!("Failed to encode note: {}", e))?;
+        line.push('\n');
+
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))?;
+
+        self.lamport += 1;
+        Ok(note)
+    }
+}
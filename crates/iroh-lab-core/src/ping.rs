@@ -0,0 +1,131 @@
+//! Round-trip latency to the gossip neighbors.
+//!
+//! Every [`PING_INTERVAL`], each neighbor is sent a random nonce on a new
+//! stream of a connection kept open over the [`PING_ALPN`] protocol, and
+//! echoes it back. The time until the echo arrives is the latency shown next
+//! to the member. Keeping the connection open leaves its handshake out of
+//! the measurement.
+use futures::future::BoxFuture;
+use iroh::endpoint::{Connecting, Connection};
+use iroh::protocol::ProtocolHandler;
+use iroh::{Endpoint, NodeId};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// ALPN of the protocol echoing pings.
+pub const PING_ALPN: &[u8] = b"iroh-lab/ping/0";
+
+/// How often every neighbor is pinged.
+pub const PING_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long to wait for the echo of a ping.
+pub const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Latencies above this are high.
+pub const HIGH_LATENCY: Duration = Duration::from_millis(300);
+
+// Size of the nonce of a ping
+const NONCE_SIZE: usize = 8;
+
+/// Whether `latency` is high enough to point out.
+pub fn is_high(latency: Duration) -> bool {
+    latency > HIGH_LATENCY
+}
+
+/// Echoes the pings of other nodes.
+#[derive(Debug, Clone, Default)]
+pub struct PingProtocol;
+
+impl ProtocolHandler for PingProtocol {
+    fn accept(&self, connecting: Connecting) -> BoxFuture<'static, anyhow::Result<()>> {
+        Box::pin(async move {
+            let connection = connecting.await?;
+            // Every ping comes on a stream of its own, until the pinger
+            // closes the connection
+            while let Ok((mut send, mut recv)) = connection.accept_bi().await {
+                let nonce = recv.read_to_end(NONCE_SIZE).await?;
+                send.write_all(&nonce).await?;
+                send.finish()?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Pings nodes over connections kept open between pings.
+#[derive(Debug)]
+pub struct Pinger {
+    endpoint: Endpoint,
+    connections: HashMap<NodeId, Connection>,
+}
+
+impl Pinger {
+    pub fn new(endpoint: Endpoint) -> Self {
+        Self {
+            endpoint,
+            connections: HashMap::new(),
+        }
+    }
+
+    /// Measures the round-trip latency to `node_id`.
+    pub async fn ping(&mut self, node_id: NodeId) -> Result<Duration, String> {
+        let connection = match self.connections.get(&node_id) {
+            Some(connection) if connection.close_reason().is_none() => connection.clone(),
+            _ => {
+                let connection =
+                    tokio::time::timeout(PING_TIMEOUT, self.endpoint.connect(node_id, PING_ALPN))
+                        .await
+                        .map_err(|_| "The node did not answer in time".to_string())?
+                        .map_err(|e| format!("Failed to reach the node: {}", e))?;
+                self.connections.insert(node_id, connection.clone());
+                connection
+            }
+        };
+
+        let result = tokio::time::timeout(PING_TIMEOUT, echo(&connection))
+            .await
+            .map_err(|_| "The node did not answer in time".to_string())
+            .and_then(|result| result);
+        if result.is_err() {
+            self.connections.remove(&node_id);
+        }
+        result
+    }
+
+    /// Closes the connections to the nodes not in `keep`.
+    pub fn retain(&mut self, keep: impl Fn(&NodeId) -> bool) {
+        self.connections.retain(|node_id, connection| {
+            let kept = keep(node_id);
+            if !kept {
+                debug!(node_id = %node_id.fmt_short(), "No longer pinging node");
+                connection.close(0u32.into(), b"done");
+            }
+            kept
+        });
+    }
+}
+
+// Sends a nonce on a new stream of `connection`, returning how long its echo
+// took to arrive
+async fn echo(connection: &Connection) -> Result<Duration, String> {
+    let nonce: [u8; NONCE_SIZE] = rand::random();
+    let start = Instant::now();
+    let (mut send, mut recv) = connection
+        .open_bi()
+        .await
+        .map_err(|e| format!("Failed to ping: {}", e))?;
+    send.write_all(&nonce)
+        .await
+        .map_err(|e| format!("Failed to ping: {}", e))?;
+    send.finish()
+        .map_err(|e| format!("Failed to ping: {}", e))?;
+    let echoed = recv
+        .read_to_end(NONCE_SIZE)
+        .await
+        .map_err(|e| format!("Failed to receive echo: {}", e))?;
+    if echoed != nonce {
+        return Err("The node echoed something else".to_string());
+    }
+    Ok(start.elapsed())
+}
@@ -0,0 +1,12 @@
+//! The types most programs built on the library need, for importing at once
+//! with `use iroh_lab_core::prelude::*`.
+//!
+//! What the prelude exports, and the signatures used in
+//! `tests/api/prelude.rs`, are the public API kept stable: changing them
+//! breaks the `api` test, and needs a new minor version while the crate is
+//! below 1.0, as semantic versioning asks of breaking changes.
+pub use crate::client::{ChatMessage, ClientEvent, IrohClient, Subscription};
+pub use crate::conversation::Conversation;
+pub use crate::engine::ChatEngine;
+pub use crate::roster::Roster;
+pub use crate::ticket::Ticket;
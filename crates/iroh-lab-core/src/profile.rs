@@ -0,0 +1,91 @@
+//! Profiles users show to the members of their topics.
+//!
+//! A user sets a display name, a status line and an avatar, which every node
+//! announces with a [`Profile`] payload when joining a topic, when a new
+//! neighbor shows up and when the profile changes. Messages are shown under
+//! the display name of the node that signed them, falling back to the name
+//! they carry.
+use crate::client::{ChatMessage, Profile};
+use iroh::NodeId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Longest display name accepted, in characters.
+pub const MAX_DISPLAY_NAME_LEN: usize = 40;
+
+/// Longest status line accepted, in characters.
+pub const MAX_STATUS_LINE_LEN: usize = 80;
+
+/// Longest avatar accepted, in characters. Enough for an emoji built from
+/// several code points, or initials.
+pub const MAX_AVATAR_LEN: usize = 8;
+
+/// What a user tells the members of their topics about themselves.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserProfile {
+    /// Name shown on messages instead of the username, if not empty.
+    pub display_name: String,
+    /// A short note such as "In meetings until 3pm".
+    pub status_line: String,
+    /// An emoji or initials shown in front of the name, if not empty.
+    pub avatar: String,
+}
+
+impl UserProfile {
+    /// Checks that every field fits its limit.
+    pub fn validate(&self) -> Result<(), String> {
+        let fields = [
+            ("Display name", &self.display_name, MAX_DISPLAY_NAME_LEN),
+            ("Status line", &self.status_line, MAX_STATUS_LINE_LEN),
+            ("Avatar", &self.avatar, MAX_AVATAR_LEN),
+        ];
+        for (name, value, limit) in fields {
+            if value.chars().count() > limit {
+                return Err(format!("{} is longer than {} characters", name, limit));
+            }
+        }
+        Ok(())
+    }
+
+    /// The name to show for a user called `username`.
+    pub fn name_or<'a>(&'a self, username: &'a str) -> &'a str {
+        match self.display_name.trim() {
+            "" => username,
+            name => name,
+        }
+    }
+}
+
+/// The latest profile announced by each node.
+#[derive(Debug, Default)]
+pub struct Profiles {
+    by_node: HashMap<NodeId, UserProfile>,
+}
+
+impl Profiles {
+    /// Records an announced profile, returning whether it was accepted.
+    /// Profiles exceeding the limits are ignored.
+    pub fn update(&mut self, profile: &Profile) -> bool {
+        if profile.profile.validate().is_err() {
+            return false;
+        }
+        self.by_node
+            .insert(profile.node_id, profile.profile.clone());
+        true
+    }
+
+    /// The profile announced by `node_id`.
+    pub fn get(&self, node_id: &NodeId) -> Option<&UserProfile> {
+        self.by_node.get(node_id)
+    }
+
+    /// The profile of the node that signed `message`. Unsigned messages have
+    /// none, as anybody could claim to be the author.
+    pub fn of_sender(&self, message: &ChatMessage) -> Option<&UserProfile> {
+        message
+            .verified_sender
+            .as_ref()
+            .and_then(|node_id| self.get(node_id))
+    }
+}
@@ -0,0 +1,87 @@
+//! Reconnecting to a topic after its gossip subscription failed.
+//!
+//! Every failed attempt doubles the delay before the next one, from
+//! [`INITIAL_DELAY`] up to [`MAX_DELAY`]. Each delay is drawn at random from
+//! its upper half, so the nodes that lost their connection together do not
+//! all retry at the same moment. After [`MAX_ATTEMPTS`], retrying stops until
+//! the user asks to retry now.
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+/// Delay before the first retry.
+pub const INITIAL_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest delay between two retries.
+pub const MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Retries before giving up.
+pub const MAX_ATTEMPTS: u32 = 8;
+
+/// Where reconnecting to a topic stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReconnectState {
+    /// The subscription works again.
+    Connected,
+    /// The subscription failed, and `attempt` is tried after `delay`.
+    Retrying { attempt: u32, delay: Duration },
+    /// Every attempt failed. Nothing is tried until asked to.
+    GaveUp,
+}
+
+/// Jittered exponential backoff between the attempts to reconnect.
+#[derive(Debug, Default)]
+pub struct Reconnector {
+    // Attempts made since the connection was lost
+    attempts: u32,
+    // When to make the next attempt, if one is due
+    retry_at: Option<Instant>,
+    // Whether the connection is lost at all
+    lost: bool,
+}
+
+impl Reconnector {
+    /// Records that the connection, or the last attempt to restore it,
+    /// failed at `now`, and schedules the next attempt unless all were made.
+    pub fn failed(&mut self, now: Instant, rng: &mut impl Rng) -> ReconnectState {
+        self.lost = true;
+        if self.attempts >= MAX_ATTEMPTS {
+            self.retry_at = None;
+            return ReconnectState::GaveUp;
+        }
+        self.attempts += 1;
+        let ceiling = INITIAL_DELAY
+            .saturating_mul(1 << (self.attempts - 1).min(16))
+            .min(MAX_DELAY);
+        let delay = rng.gen_range(ceiling / 2..=ceiling);
+        self.retry_at = Some(now + delay);
+        ReconnectState::Retrying {
+            attempt: self.attempts,
+            delay,
+        }
+    }
+
+    /// Records that the connection works, returning
+    /// [`Connected`](ReconnectState::Connected) if it was lost before.
+    pub fn connected(&mut self) -> Option<ReconnectState> {
+        let was_lost = self.lost;
+        *self = Self::default();
+        was_lost.then_some(ReconnectState::Connected)
+    }
+
+    /// When the next attempt is due, if one is.
+    pub fn retry_at(&self) -> Option<Instant> {
+        self.retry_at
+    }
+
+    /// Records that the attempt due is being made.
+    pub fn retrying(&mut self) {
+        self.retry_at = None;
+    }
+
+    /// Records that the user asked to retry now, so failing again starts
+    /// over with the shortest delay, even after giving up.
+    pub fn retry_now(&mut self) {
+        self.attempts = 0;
+        self.retry_at = None;
+    }
+}
@@ -0,0 +1,177 @@
+//! Local reputation of the peers we receive gossip from.
+//!
+//! Every peer starts with [`MAX_SCORE`] and loses points for each
+//! [`Offense`] in a frame it verifiably signed: presence or profiles
+//! announced for another node, allowlists of topics the peer did not create,
+//! chat messages replayed under another signer, and more frames than
+//! [`RATE_LIMIT`] per [`RATE_WINDOW`]. Frames that fail to decode are not
+//! charged to anyone, as gossip forwards them unchecked and the neighbor
+//! delivering one is rarely the node that wrote it. Scores recover by
+//! [`RECOVERY_PER_MINUTE`] up to the maximum. Frames signed by peers at or below [`BLOCK_THRESHOLD`] are
+//! dropped unseen until they recovered. Scores are only kept while the
+//! application runs and never shared with other nodes.
+use iroh::NodeId;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Score of a peer that never misbehaved.
+pub const MAX_SCORE: i32 = 100;
+
+/// Peers at or below this score are blocked.
+pub const BLOCK_THRESHOLD: i32 = 40;
+
+/// Points a peer regains per minute.
+pub const RECOVERY_PER_MINUTE: i32 = 1;
+
+/// Frames a peer may sign per [`RATE_WINDOW`]. Generous, as answering a
+/// sync request sends a burst of old messages.
+pub const RATE_LIMIT: u32 = 300;
+
+/// Window the [`RATE_LIMIT`] applies to.
+pub const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Chat message ids remembered to detect replays.
+pub const REPLAY_MEMORY: usize = 10_000;
+
+/// Misbehavior costing a peer reputation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Offense {
+    /// Signed a presence, profile or avatar of another node, or an
+    /// allowlist of a topic it did not create.
+    Impersonation,
+    /// Signed a chat message first signed by another node.
+    Replay,
+    /// Signed more than [`RATE_LIMIT`] frames in a [`RATE_WINDOW`].
+    RateLimit,
+}
+
+impl Offense {
+    /// Points the offense costs.
+    pub fn penalty(self) -> i32 {
+        match self {
+            Self::Impersonation => 25,
+            Self::Replay => 25,
+            Self::RateLimit => 20,
+        }
+    }
+}
+
+// What we know about a single peer
+#[derive(Debug, Clone)]
+struct Record {
+    // Score at `updated`, before recovery since
+    score: i32,
+    updated: Instant,
+    // Frames signed in the current rate window, and whether the limit was
+    // already exceeded in it
+    window_start: Instant,
+    frames: u32,
+    limited: bool,
+}
+
+impl Record {
+    fn new(now: Instant) -> Self {
+        Self {
+            score: MAX_SCORE,
+            updated: now,
+            window_start: now,
+            frames: 0,
+            limited: false,
+        }
+    }
+
+    fn score(&self, now: Instant) -> i32 {
+        let minutes = now.saturating_duration_since(self.updated).as_secs() / 60;
+        let recovered = i32::try_from(minutes)
+            .unwrap_or(i32::MAX)
+            .saturating_mul(RECOVERY_PER_MINUTE);
+        self.score.saturating_add(recovered).min(MAX_SCORE)
+    }
+}
+
+/// The reputation of every peer heard from.
+#[derive(Debug, Clone, Default)]
+pub struct Reputation {
+    peers: HashMap<NodeId, Record>,
+}
+
+impl Reputation {
+    /// Charges `node` for `offense` committed at `now`.
+    pub fn penalize(&mut self, node: NodeId, offense: Offense, now: Instant) {
+        let record = self.peers.entry(node).or_insert_with(|| Record::new(now));
+        record.score = record.score(now) - offense.penalty();
+        record.updated = now;
+    }
+
+    /// Counts a frame signed by `node` at `now` against the rate limit,
+    /// charging it once per window for exceeding it. Returns whether the
+    /// frame is within the limit.
+    pub fn count_frame(&mut self, node: NodeId, now: Instant) -> bool {
+        let record = self.peers.entry(node).or_insert_with(|| Record::new(now));
+        if now.saturating_duration_since(record.window_start) >= RATE_WINDOW {
+            record.window_start = now;
+            record.frames = 0;
+            record.limited = false;
+        }
+        record.frames += 1;
+        if record.frames <= RATE_LIMIT {
+            return true;
+        }
+        if !record.limited {
+            record.limited = true;
+            self.penalize(node, Offense::RateLimit, now);
+        }
+        false
+    }
+
+    /// Score of `node` at `now`.
+    pub fn score(&self, node: &NodeId, now: Instant) -> i32 {
+        self.peers
+            .get(node)
+            .map_or(MAX_SCORE, |record| record.score(now))
+    }
+
+    /// Whether frames signed by `node` are dropped at `now`.
+    pub fn is_blocked(&self, node: &NodeId, now: Instant) -> bool {
+        self.score(node, now) <= BLOCK_THRESHOLD
+    }
+
+    /// Score of every peer heard from at `now`, lowest first.
+    pub fn scores(&self, now: Instant) -> Vec<(NodeId, i32)> {
+        let mut scores: Vec<_> = self
+            .peers
+            .iter()
+            .map(|(node, record)| (*node, record.score(now)))
+            .collect();
+        scores.sort_by(|(a, a_score), (b, b_score)| a_score.cmp(b_score).then_with(|| a.cmp(b)));
+        scores
+    }
+}
+
+/// The signers of the latest chat messages of a topic, to tell replays from
+/// messages sent again by their author.
+#[derive(Debug, Default)]
+pub struct Replays {
+    signers: HashMap<String, NodeId>,
+    // Message ids in the order they were first seen, forgotten beyond
+    // REPLAY_MEMORY
+    order: VecDeque<String>,
+}
+
+impl Replays {
+    /// Records the chat message `id` signed by `signer`, returning whether
+    /// it was first signed by another node.
+    pub fn is_replay(&mut self, id: &str, signer: NodeId) -> bool {
+        if let Some(first) = self.signers.get(id) {
+            return *first != signer;
+        }
+        self.signers.insert(id.to_string(), signer);
+        self.order.push_back(id.to_string());
+        if self.order.len() > REPLAY_MEMORY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.signers.remove(&oldest);
+            }
+        }
+        false
+    }
+}
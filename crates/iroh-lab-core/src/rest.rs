@@ -0,0 +1,270 @@
+//! HTTP API, letting scripts such as home automation post into the topics of
+//! a node and read what was said there.
+//!
+//! Requests carry the token of the API as `Authorization: Bearer <token>`,
+//! and bodies are JSON:
+//!
+//! - `GET /topics` lists the topics of the node, as [`TopicInfo`]s
+//! - `GET /topics/<topic hash>/messages?limit=<n>` returns the latest
+//!   [`ChatMessage`]s of a topic, oldest first
+//! - `POST /topics/<topic hash>/messages` sends a [`PostMessage`], and
+//!   returns the message as it was sent
+//!
+//! Failed requests are answered with an [`ApiError`]. The API only knows the
+//! messages the node received or sent while it was running, up to
+//! [`HISTORY_CAPACITY`] per topic.
+use crate::client::{ChatMessage, ClientEvent, IrohClient};
+use crate::config::MessageLimit;
+use crate::rpc::TopicInfo;
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::ErrorKind;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+/// Number of messages kept per topic for reading the history.
+pub const HISTORY_CAPACITY: usize = 200;
+
+/// Number of messages returned when a history request gives no limit.
+pub const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+/// A message for the API to send.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostMessage {
+    pub content: String,
+    /// Name to send as, by default the name of the API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+}
+
+/// Why a request failed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiError {
+    pub error: String,
+}
+
+#[derive(Deserialize)]
+struct HistoryParams {
+    limit: Option<usize>,
+}
+
+// A failed request, answered with an ApiError
+struct Failure(StatusCode, String);
+
+/// Serves the topics of a node over HTTP.
+#[derive(Debug)]
+pub struct RestApi {
+    client: IrohClient,
+    token: String,
+    // Name messages are sent under when the request gives none
+    username: String,
+    message_limit: MessageLimit,
+    sequence: AtomicU64,
+    // The latest messages of each topic, oldest first
+    history: Mutex<HashMap<String, VecDeque<ChatMessage>>>,
+}
+
+impl RestApi {
+    /// An API sending through `client` as `username`, to requests carrying
+    /// `token`.
+    pub fn new(
+        client: IrohClient,
+        username: String,
+        message_limit: MessageLimit,
+        token: String,
+    ) -> Self {
+        Self {
+            client,
+            token,
+            username,
+            message_limit,
+            sequence: AtomicU64::new(0),
+            history: Mutex::default(),
+        }
+    }
+
+    /// Token requests have to carry.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Keeps a message for the history of its topic, dropping the oldest one
+    /// when the history is full.
+    pub fn record(&self, message: ChatMessage) {
+        let mut history = self.history.lock().unwrap();
+        let messages = history.entry(message.topic_hash.clone()).or_default();
+        if messages.iter().any(|kept| kept.id == message.id) {
+            return;
+        }
+        if messages.len() == HISTORY_CAPACITY {
+            messages.pop_front();
+        }
+        messages.push_back(message);
+    }
+
+    /// The routes of the API, for serving it along with others.
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new()
+            .route("/topics", get(list_topics))
+            .route(
+                "/topics/:topic_hash/messages",
+                get(history).post(post_message),
+            )
+            .route_layer(middleware::from_fn_with_state(self.clone(), authorize))
+            .with_state(self)
+    }
+
+    /// Records the messages of the node and serves the API on `listener`,
+    /// until serving fails.
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) -> Result<(), String> {
+        let mut events = IrohClient::subscribe(None);
+        let api = self.clone();
+        tokio::spawn(async move {
+            while let Some(event) = events.next().await {
+                if let ClientEvent::Message(message) = event {
+                    api.record(message);
+                }
+            }
+        });
+        axum::serve(listener, self.router())
+            .await
+            .map_err(|e| format!("Failed to serve the API: {}", e))
+    }
+
+    // Refuses requests for topics the node is not on
+    fn check_topic(&self, topic_hash: &str) -> Result<(), Failure> {
+        match self.client.topic_name(topic_hash) {
+            Some(_) => Ok(()),
+            None => Err(failure(
+                StatusCode::NOT_FOUND,
+                "Not subscribed to this topic",
+            )),
+        }
+    }
+}
+
+/// The token kept in `path`, creating one that only the user may read if
+/// there is none yet.
+pub fn load_token(path: &std::path::Path) -> Result<String, String> {
+    match std::fs::read_to_string(path) {
+        Ok(token) if !token.trim().is_empty() => return Ok(token.trim().to_string()),
+        Ok(_) => {}
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let token = data_encoding::HEXLOWER.encode(&rand::random::<[u8; 16]>());
+    std::fs::write(path, &token)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to restrict {}: {}", path.display(), e))?;
+    }
+    Ok(token)
+}
+
+// Refuses requests without the token
+async fn authorize(State(api): State<Arc<RestApi>>, request: Request, next: Next) -> Response {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if token != Some(api.token.as_str()) {
+        return failure(StatusCode::UNAUTHORIZED, "Missing or wrong token").into_response();
+    }
+    next.run(request).await
+}
+
+async fn list_topics(State(api): State<Arc<RestApi>>) -> Json<Vec<TopicInfo>> {
+    let mut topics: Vec<_> = api
+        .client
+        .topic_hashes()
+        .into_iter()
+        .filter_map(|topic_hash| {
+            Some(TopicInfo {
+                topic_name: api.client.topic_name(&topic_hash)?,
+                topic_hash,
+                ticket: None,
+            })
+        })
+        .collect();
+    topics.sort_by(|a, b| a.topic_name.cmp(&b.topic_name));
+    Json(topics)
+}
+
+async fn history(
+    State(api): State<Arc<RestApi>>,
+    Path(topic_hash): Path<String>,
+    params: Result<Query<HistoryParams>, QueryRejection>,
+) -> Result<Json<Vec<ChatMessage>>, Failure> {
+    api.check_topic(&topic_hash)?;
+    let Query(params) = params.map_err(|e| failure(StatusCode::BAD_REQUEST, e.body_text()))?;
+    let limit = params.limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+    let history = api.history.lock().unwrap();
+    let messages = history
+        .get(&topic_hash)
+        .map(|messages| {
+            let skipped = messages.len().saturating_sub(limit);
+            messages.iter().skip(skipped).cloned().collect()
+        })
+        .unwrap_or_default();
+    Ok(Json(messages))
+}
+
+async fn post_message(
+    State(api): State<Arc<RestApi>>,
+    Path(topic_hash): Path<String>,
+    body: Result<Json<PostMessage>, JsonRejection>,
+) -> Result<(StatusCode, Json<ChatMessage>), Failure> {
+    api.check_topic(&topic_hash)?;
+    let Json(post) = body.map_err(|e| failure(StatusCode::BAD_REQUEST, e.body_text()))?;
+    if post.content.trim().is_empty() {
+        return Err(failure(StatusCode::BAD_REQUEST, "The message is empty"));
+    }
+    api.message_limit
+        .check(&post.content)
+        .map_err(|e| failure(StatusCode::BAD_REQUEST, e))?;
+    let author = post
+        .author
+        .map(|author| author.trim().to_string())
+        .filter(|author| !author.is_empty())
+        .unwrap_or_else(|| api.username.clone());
+
+    let sequence = api.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+    let message = api
+        .client
+        .send_message_to(&topic_hash, author, post.content, sequence)
+        .await
+        .map_err(|e| failure(StatusCode::BAD_GATEWAY, e))?;
+    // Recorded right away, so the history read next includes it, while
+    // whatever else listens to the node is told through the channel
+    api.record(message.clone());
+    IrohClient::broadcast_message(message.clone());
+    Ok((StatusCode::CREATED, Json(message)))
+}
+
+impl IntoResponse for Failure {
+    fn into_response(self) -> Response {
+        let Failure(status, error) = self;
+        (status, Json(ApiError { error })).into_response()
+    }
+}
+
+fn failure(status: StatusCode, error: impl Into<String>) -> Failure {
+    Failure(status, error.into())
+}
@@ -0,0 +1,99 @@
+//! What a user interface keeps about the topic it shows.
+//!
+//! A [`Room`] is entered when a topic is opened and left with it. It tracks
+//! how the topic looks from our side, how restoring a lost subscription goes,
+//! and whether our own messages reached anybody, so user interfaces only
+//! have to render it.
+use crate::client::ChatMessage;
+use crate::delivery::DeliveryState;
+use crate::liveness::TopicActivity;
+use crate::reconnect::ReconnectState;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// The topic shown, from entering it until leaving it.
+#[derive(Debug, Clone)]
+pub struct Room {
+    name: String,
+    entered_at: DateTime<Utc>,
+    activity: TopicActivity,
+    // How restoring the subscription goes, while it is lost
+    reconnect: Option<ReconnectState>,
+    // Whether a resync was requested and nobody was seen since
+    resyncing: bool,
+    // By message id
+    deliveries: HashMap<String, DeliveryState>,
+}
+
+impl Room {
+    /// Enters the topic `name` now.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            entered_at: Utc::now(),
+            activity: TopicActivity::default(),
+            reconnect: None,
+            resyncing: false,
+            deliveries: HashMap::new(),
+        }
+    }
+
+    /// Name of the topic.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// When the room was entered.
+    pub fn entered_at(&self) -> DateTime<Utc> {
+        self.entered_at
+    }
+
+    /// Whether `message` was sent before the room was entered, and came from
+    /// history rather than live.
+    pub fn is_history(&self, message: &ChatMessage) -> bool {
+        message.timestamp < self.entered_at
+    }
+
+    /// How the topic looks from our side.
+    pub fn activity(&self) -> TopicActivity {
+        self.activity
+    }
+
+    /// Records how the topic looks now. Seeing members again ends a resync.
+    pub fn set_activity(&mut self, activity: TopicActivity) {
+        self.activity = activity;
+        if activity == TopicActivity::Active {
+            self.resyncing = false;
+        }
+    }
+
+    /// How restoring the subscription goes, or `None` while it works.
+    pub fn reconnect(&self) -> Option<ReconnectState> {
+        self.reconnect
+    }
+
+    /// Records how restoring the subscription goes.
+    pub fn set_reconnect(&mut self, state: ReconnectState) {
+        self.reconnect = (state != ReconnectState::Connected).then_some(state);
+    }
+
+    /// Whether a resync was requested and nobody was seen since.
+    pub fn is_resyncing(&self) -> bool {
+        self.resyncing
+    }
+
+    /// Records that a resync was requested.
+    pub fn start_resync(&mut self) {
+        self.resyncing = true;
+    }
+
+    /// Whether our message `message_id` reached anybody, if we sent it.
+    pub fn delivery(&self, message_id: &str) -> Option<DeliveryState> {
+        self.deliveries.get(message_id).copied()
+    }
+
+    /// Records whether our message `message_id` reached anybody.
+    pub fn set_delivery(&mut self, message_id: impl Into<String>, state: DeliveryState) {
+        self.deliveries.insert(message_id.into(), state);
+    }
+}
@@ -0,0 +1,109 @@
+//! Who is on a topic, from the presence announcements of its members.
+//!
+//! Every node announces the name and status of its user on each topic with
+//! a [`Presence`] payload at [`PRESENCE_INTERVAL`], and right away when the
+//! status changes. Members not heard from for [`PRESENCE_TIMEOUT`] are
+//! shown as offline.
+use crate::client::Presence;
+use iroh::NodeId;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, Instant};
+
+/// How often each node announces the presence of its user.
+pub const PRESENCE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Members not heard from for this long are offline. Long enough for a
+/// couple of announcements to get lost.
+pub const PRESENCE_TIMEOUT: Duration = Duration::from_secs(3 * PRESENCE_INTERVAL.as_secs());
+
+/// The status a user announces.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PresenceStatus {
+    /// The chat window has focus.
+    #[default]
+    Online,
+    /// The chat is running in the background.
+    Away,
+}
+
+/// How a member is shown in the member list, most present first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MemberState {
+    Online,
+    Away,
+    Offline,
+}
+
+/// A member of a topic, as last announced.
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub author: String,
+    pub node_id: NodeId,
+    pub status: PresenceStatus,
+    pub last_seen: Instant,
+}
+
+impl Member {
+    /// How the member is shown at `now`.
+    pub fn state(&self, now: Instant) -> MemberState {
+        if now.saturating_duration_since(self.last_seen) >= PRESENCE_TIMEOUT {
+            return MemberState::Offline;
+        }
+        match self.status {
+            PresenceStatus::Online => MemberState::Online,
+            PresenceStatus::Away => MemberState::Away,
+        }
+    }
+}
+
+/// The members of a topic, by node.
+#[derive(Debug, Default)]
+pub struct Roster {
+    members: HashMap<NodeId, Member>,
+}
+
+impl Roster {
+    /// Records a presence announcement received at `now`.
+    pub fn update(&mut self, presence: &Presence, now: Instant) {
+        self.members.insert(
+            presence.node_id,
+            Member {
+                author: presence.author.clone(),
+                node_id: presence.node_id,
+                status: presence.status,
+                last_seen: now,
+            },
+        );
+    }
+
+    /// Every member with how it is shown at `now`, the most present first
+    /// and by name within each state.
+    pub fn members(&self, now: Instant) -> Vec<(&Member, MemberState)> {
+        let mut members: Vec<_> = self
+            .members
+            .values()
+            .map(|member| (member, member.state(now)))
+            .collect();
+        members.sort_by(|(a, a_state), (b, b_state)| {
+            a_state
+                .cmp(b_state)
+                .then_with(|| a.author.cmp(&b.author))
+                .then_with(|| a.node_id.cmp(&b.node_id))
+        });
+        members
+    }
+
+    /// Nodes of every member, offline ones included.
+    pub fn node_ids(&self) -> BTreeSet<NodeId> {
+        self.members.keys().copied().collect()
+    }
+
+    /// Number of members not offline at `now`.
+    pub fn present(&self, now: Instant) -> usize {
+        self.members
+            .values()
+            .filter(|member| member.state(now) != MemberState::Offline)
+            .count()
+    }
+}
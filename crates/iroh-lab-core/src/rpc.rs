@@ -0,0 +1,260 @@
+//! JSON-RPC 2.0 protocol of the `chatd` daemon, which runs one node for
+//! several tools to share.
+//!
+//! Requests, responses and notifications are JSON objects, one per line. The
+//! methods are:
+//!
+//! - `join` with `{"ticket": ...}`, a ticket or invite, returns the
+//!   `topic_name` and `topic_hash` joined
+//! - `create` with `{"name": ...}` returns the `topic_name`, `topic_hash` and
+//!   `ticket` of the new topic
+//! - `send` with `{"topic_hash": ..., "content": ...}`, and optionally the
+//!   `username` to send as, returns the message sent
+//! - `subscribe`, optionally with a `topic_hash`, has the messages of that
+//!   topic, or of all of them, sent to the connection as `message`
+//!   notifications
+//! - `list-topics` returns the `topic_name` and `topic_hash` of every topic
+//!   of the node
+use crate::client::ChatMessage;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The request could not be parsed as JSON.
+pub const PARSE_ERROR: i64 = -32700;
+
+/// The request is not a JSON-RPC request.
+pub const INVALID_REQUEST: i64 = -32600;
+
+/// The method does not exist.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+
+/// The parameters do not fit the method.
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// The client failed to do what was asked.
+pub const CLIENT_ERROR: i64 = -32000;
+
+// Version of the protocol, in every message
+const VERSION: &str = "2.0";
+
+/// A request, or a notification if it has no id.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Request {
+    pub jsonrpc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// The answer to a request, with either a result or an error.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+/// Why a request failed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+/// Something the daemon tells a connection without being asked.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub jsonrpc: String,
+    pub method: String,
+    pub params: Value,
+}
+
+/// A method with its parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Call {
+    Join {
+        ticket: String,
+    },
+    Create {
+        name: String,
+    },
+    Send {
+        topic_hash: String,
+        content: String,
+        username: Option<String>,
+    },
+    Subscribe {
+        topic_hash: Option<String>,
+    },
+    ListTopics,
+}
+
+/// A topic of the node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopicInfo {
+    pub topic_name: String,
+    pub topic_hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticket: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct JoinParams {
+    ticket: String,
+}
+
+#[derive(Deserialize)]
+struct CreateParams {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct SendParams {
+    topic_hash: String,
+    content: String,
+    #[serde(default)]
+    username: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct SubscribeParams {
+    #[serde(default)]
+    topic_hash: Option<String>,
+}
+
+impl Request {
+    pub fn new(id: impl Into<Value>, method: &str, params: Value) -> Self {
+        Self {
+            jsonrpc: VERSION.to_string(),
+            id: Some(id.into()),
+            method: method.to_string(),
+            params,
+        }
+    }
+
+    /// The method called, or the error to answer with.
+    pub fn call(&self) -> Result<Call, RpcError> {
+        if self.jsonrpc != VERSION {
+            return Err(RpcError::new(
+                INVALID_REQUEST,
+                format!("Unsupported JSON-RPC version {}", self.jsonrpc),
+            ));
+        }
+        match self.method.as_str() {
+            "join" => self.params::<JoinParams>().map(|params| Call::Join {
+                ticket: params.ticket,
+            }),
+            "create" => self
+                .params::<CreateParams>()
+                .map(|params| Call::Create { name: params.name }),
+            "send" => self.params::<SendParams>().map(|params| Call::Send {
+                topic_hash: params.topic_hash,
+                content: params.content,
+                username: params.username,
+            }),
+            "subscribe" => {
+                let params = if self.params.is_null() {
+                    SubscribeParams::default()
+                } else {
+                    self.params()?
+                };
+                Ok(Call::Subscribe {
+                    topic_hash: params.topic_hash,
+                })
+            }
+            "list-topics" => Ok(Call::ListTopics),
+            method => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("There is no method {}", method),
+            )),
+        }
+    }
+
+    // The parameters, as the method expects them
+    fn params<T: for<'de> Deserialize<'de>>(&self) -> Result<T, RpcError> {
+        serde_json::from_value(self.params.clone()).map_err(|e| {
+            RpcError::new(
+                INVALID_PARAMS,
+                format!("Invalid parameters of {}: {}", self.method, e),
+            )
+        })
+    }
+}
+
+impl Response {
+    /// Answers request `id` with `result`.
+    pub fn result(id: Value, result: impl Serialize) -> Self {
+        match serde_json::to_value(result) {
+            Ok(result) => Self {
+                jsonrpc: VERSION.to_string(),
+                id,
+                result: Some(result),
+                error: None,
+            },
+            Err(e) => Self::error(
+                id,
+                RpcError::new(CLIENT_ERROR, format!("Failed to encode result: {}", e)),
+            ),
+        }
+    }
+
+    /// Answers request `id` with `error`.
+    pub fn error(id: Value, error: RpcError) -> Self {
+        Self {
+            jsonrpc: VERSION.to_string(),
+            id,
+            result: None,
+            error: Some(error),
+        }
+    }
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl Notification {
+    /// Tells a subscriber about a message of one of its topics.
+    pub fn message(message: &ChatMessage) -> Self {
+        Self {
+            jsonrpc: VERSION.to_string(),
+            method: "message".to_string(),
+            params: serde_json::to_value(message).unwrap_or_default(),
+        }
+    }
+}
+
+/// Parses a line received as a request, or returns the response to send
+/// back if it is none.
+pub fn parse(line: &str) -> Result<Request, Response> {
+    let value: Value = serde_json::from_str(line).map_err(|e| {
+        Response::error(
+            Value::Null,
+            RpcError::new(PARSE_ERROR, format!("Invalid JSON: {}", e)),
+        )
+    })?;
+    let id = value.get("id").cloned().unwrap_or_default();
+    serde_json::from_value(value).map_err(|e| {
+        Response::error(
+            id,
+            RpcError::new(INVALID_REQUEST, format!("Invalid request: {}", e)),
+        )
+    })
+}
+
+/// Encodes a response or notification as a line to send.
+pub fn encode(message: &impl Serialize) -> String {
+    let mut line = serde_json::to_string(message).unwrap_or_default();
+    line.push('\n');
+    line
+}
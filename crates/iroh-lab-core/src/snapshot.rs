@@ -0,0 +1,48 @@
+//! Snapshots of the recent history of a topic, sent to a single member.
+//!
+//! Sync requests only reach members who are connected when they are sent,
+//! so a newcomer may still miss what was said before joining. Any member can
+//! then send them a snapshot: the latest messages, encrypted with the topic
+//! key and stored as a [blob](crate::blobs). A
+//! [`Snapshot`](crate::client::Snapshot) payload tells the newcomer where to
+//! fetch it, and the newcomer imports the messages as unverified, as their
+//! original signatures are not part of the snapshot.
+use crate::blobs::MAX_BLOB_SIZE;
+use crate::client::ChatMessage;
+use crate::crypto::TopicKey;
+
+/// Most messages packed into a snapshot.
+pub const SNAPSHOT_MAX_MESSAGES: usize = 500;
+
+/// Packs the latest of `messages`, which are in display order, into an
+/// encrypted snapshot no larger than a blob may be. Returns the snapshot
+/// and how many messages it holds.
+pub fn pack(messages: &[ChatMessage], key: &TopicKey) -> Result<(Vec<u8>, usize), String> {
+    let mut first = messages.len().saturating_sub(SNAPSHOT_MAX_MESSAGES);
+    loop {
+        let plain = postcard::to_stdvec(&messages[first..])
+            .map_err(|e| format!("Failed to encode snapshot: {}", e))?;
+        let sealed = key.encrypt(&plain)?;
+        if sealed.len() <= MAX_BLOB_SIZE {
+            return Ok((sealed, messages.len() - first));
+        }
+        if first == messages.len() {
+            return Err("Snapshot does not fit into a blob".to_string());
+        }
+        // Drop the oldest messages in proportion to the excess
+        let excess = (sealed.len() - MAX_BLOB_SIZE) * (messages.len() - first) / sealed.len();
+        first += excess.max(1);
+    }
+}
+
+/// Unpacks a snapshot of the topic `topic_hash`, dropping messages of other
+/// topics.
+pub fn unpack(sealed: &[u8], key: &TopicKey, topic_hash: &str) -> Result<Vec<ChatMessage>, String> {
+    let plain = key.decrypt(sealed)?;
+    let messages: Vec<ChatMessage> =
+        postcard::from_bytes(&plain).map_err(|e| format!("Invalid snapshot: {}", e))?;
+    Ok(messages
+        .into_iter()
+        .filter(|message| message.topic_hash == topic_hash)
+        .collect())
+}
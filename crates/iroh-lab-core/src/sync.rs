@@ -0,0 +1,72 @@
+//! Anti-entropy between the members of a topic.
+//!
+//! Gossip only delivers a message to the members connected while it is
+//! spread, so a node cut off from the topic misses everything sent in the
+//! meantime. After resubscribing, it sends a
+//! [`SyncRequest`](crate::client::SyncRequest), and every member answers by
+//! sending again the payloads it published itself since then, which each
+//! node keeps in an [`Outbox`]. Receivers ignore messages they already have,
+//! so answering more than needed does no harm.
+use crate::wire::Payload;
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Number of our own payloads kept per topic for answering sync requests.
+pub const OUTBOX_CAPACITY: usize = 200;
+
+/// How far before the last contact with the topic a sync request reaches
+/// back, so clocks running slightly apart do not lose messages.
+pub const SYNC_MARGIN: Duration = Duration::from_secs(5 * 60);
+
+/// The most recent payloads we published on a topic.
+#[derive(Debug, Default)]
+pub struct Outbox {
+    sent: VecDeque<(DateTime<Utc>, Payload)>,
+}
+
+impl Outbox {
+    /// Keeps a payload sent at `sent_at`, dropping the oldest one when the
+    /// outbox is full. Only chat messages, edits, deletes, task updates,
+    /// events, answers and attachments are kept, as nothing else is worth
+    /// sending again.
+    pub fn push(&mut self, sent_at: DateTime<Utc>, payload: &Payload) {
+        if !matches!(
+            payload,
+            Payload::Chat(_)
+                | Payload::Edit(_)
+                | Payload::Delete(_)
+                | Payload::Task(_)
+                | Payload::Event(_)
+                | Payload::Rsvp(_)
+                | Payload::Attachment(_)
+        ) {
+            return;
+        }
+        if self.sent.len() == OUTBOX_CAPACITY {
+            self.sent.pop_front();
+        }
+        self.sent.push_back((sent_at, payload.clone()));
+    }
+
+    /// Payloads sent at or after `since`, oldest first.
+    pub fn since(&self, since: DateTime<Utc>) -> Vec<Payload> {
+        self.sent
+            .iter()
+            .filter(|(sent_at, _)| *sent_at >= since)
+            .map(|(_, payload)| payload.clone())
+            .collect()
+    }
+}
+
+/// Start of the period other members are asked to resend, for a node last
+/// in contact with the topic at `last_contact`. Nodes that never were in
+/// contact ask for everything.
+pub fn sync_since(last_contact: Option<DateTime<Utc>>) -> DateTime<Utc> {
+    match last_contact {
+        Some(last_contact) => {
+            last_contact - chrono::Duration::from_std(SYNC_MARGIN).unwrap_or_default()
+        }
+        None => DateTime::UNIX_EPOCH,
+    }
+}
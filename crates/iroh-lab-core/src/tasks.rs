@@ -0,0 +1,151 @@
+//! Action items: messages flagged as tasks of a topic.
+//!
+//! Any member can flag a message as an action item, tick it off, or assign it
+//! to a member. Each of these is a last-writer-wins register per message,
+//! stamped with the Lamport timestamp of the [`TaskUpdate`] that set it and
+//! the node that signed it, so every member ends up with the same tasks
+//! whatever order the updates arrive in. The registers are stored next to
+//! the configuration, e.g. `~/.config/iroh-lab/tasks.json` on Linux, so the
+//! tasks outlive the updates kept for sync requests.
+use crate::client::{ChatMessage, TaskUpdate};
+use crate::config::Config;
+use iroh::NodeId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+// Name of the file holding the tasks
+const TASKS_FILE: &str = "tasks.json";
+
+/// A change to the task made of a message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskChange {
+    /// Flags the message as an action item, or removes it from the tasks.
+    Flag(bool),
+    /// Marks the task done or undone.
+    Done(bool),
+    /// Assigns the task to a member, or to nobody.
+    Assign(Option<NodeId>),
+}
+
+// The stamp ordering the writes of a register: the later Lamport timestamp
+// wins, and the signer breaks ties
+type Stamp = (u64, NodeId);
+
+// A value with the stamp of the update that wrote it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct Register<T> {
+    value: T,
+    stamp: Option<Stamp>,
+}
+
+impl<T: PartialEq> Register<T> {
+    // Writes `value` unless a later one was written already, returning
+    // whether the value changed
+    fn write(&mut self, value: T, stamp: Stamp) -> bool {
+        if self.stamp.is_some_and(|current| current >= stamp) {
+            return false;
+        }
+        self.stamp = Some(stamp);
+        let changed = self.value != value;
+        self.value = value;
+        changed
+    }
+}
+
+// The registers of a single message
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct TaskState {
+    flagged: Register<bool>,
+    done: Register<bool>,
+    assignee: Register<Option<NodeId>>,
+}
+
+/// An action item of a topic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Task {
+    pub message_id: String,
+    pub done: bool,
+    pub assignee: Option<NodeId>,
+}
+
+/// The tasks of every topic, converged from the updates of every member.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskList {
+    // Registers by message id, by topic hash
+    topics: BTreeMap<String, BTreeMap<String, TaskState>>,
+}
+
+impl TaskList {
+    /// Location of the file of the current user, if the platform has a
+    /// configuration directory.
+    pub fn default_path() -> Option<PathBuf> {
+        Config::directory().map(|dir| dir.join(TASKS_FILE))
+    }
+
+    /// Loads the tasks from `path`, or none if the file does not exist.
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    /// Saves the tasks to `path`, creating its directory if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to encode tasks: {}", e))?;
+        std::fs::write(path, contents)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Applies an update signed by `sender`, returning whether a task
+    /// changed.
+    pub fn apply(&mut self, update: &TaskUpdate, sender: NodeId) -> bool {
+        let stamp = (update.lamport, sender);
+        let state = self
+            .topics
+            .entry(update.topic_hash.clone())
+            .or_default()
+            .entry(update.message_id.clone())
+            .or_default();
+        match &update.change {
+            TaskChange::Flag(flagged) => state.flagged.write(*flagged, stamp),
+            TaskChange::Done(done) => state.done.write(*done, stamp),
+            TaskChange::Assign(assignee) => state.assignee.write(*assignee, stamp),
+        }
+    }
+
+    /// The task made of the message `message_id` of the topic `topic_hash`,
+    /// if it is flagged.
+    pub fn get(&self, topic_hash: &str, message_id: &str) -> Option<Task> {
+        let state = self.topics.get(topic_hash)?.get(message_id)?;
+        state.flagged.value.then(|| Task {
+            message_id: message_id.to_string(),
+            done: state.done.value,
+            assignee: state.assignee.value,
+        })
+    }
+
+    /// The tasks made of `messages`, in their order, skipping deleted ones.
+    pub fn of<'m>(&self, messages: impl IntoIterator<Item = &'m ChatMessage>) -> Vec<Task> {
+        messages
+            .into_iter()
+            .filter(|message| !message.is_deleted())
+            .filter_map(|message| self.get(&message.topic_hash, &message.id))
+            .collect()
+    }
+
+    /// How many of the tasks made of `messages` are not done yet.
+    pub fn open<'m>(&self, messages: impl IntoIterator<Item = &'m ChatMessage>) -> usize {
+        self.of(messages).iter().filter(|task| !task.done).count()
+    }
+}
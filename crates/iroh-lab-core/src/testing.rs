@@ -0,0 +1,412 @@
+//! Fixtures for tests, built with the `testing` feature.
+//!
+//! [`ChatMessageBuilder`] and [`TopicFixture`] cut the boilerplate of
+//! building messages and frames without a network, and
+//! [`ClientPairFixture`] connects two clients over a topic, whose messages
+//! [`wait_for_message`] waits for.
+//! [`MemoryTransport`] chats without a network at all. Nothing here needs
+//! more than the dependencies of the application.
+use crate::client::{ChatMessage, ClientEvent, IrohClient, Presence, Subscription, EVENT_CAPACITY};
+use crate::clock::LamportClock;
+use crate::crypto::TopicKey;
+use crate::engine::Topic;
+use crate::roster::PresenceStatus;
+use crate::transport::ChatTransport;
+use crate::wire::{self, Payload};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use iroh::{NodeId, SecretKey};
+use iroh_gossip::proto::TopicId;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+// Prefix of the tickets of topics in memory
+const MEMORY_TICKET_PREFIX: &str = "memory:";
+
+/// Builds a chat message, with defaults for everything not set.
+#[derive(Debug, Clone)]
+pub struct ChatMessageBuilder {
+    message: ChatMessage,
+}
+
+impl ChatMessageBuilder {
+    /// An unsigned message `id` by "alice" saying "Hello", sent now as the
+    /// first message of the topic "test-topic".
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            message: ChatMessage {
+                id: id.into(),
+                author: "alice".to_string(),
+                content: "Hello".to_string(),
+                timestamp: Utc::now(),
+                topic_hash: "test-topic".to_string(),
+                sequence: 1,
+                lamport: 1,
+                verified_sender: None,
+                edited_at: None,
+                deleted_at: None,
+            },
+        }
+    }
+
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.message.author = author.into();
+        self
+    }
+
+    pub fn content(mut self, content: impl Into<String>) -> Self {
+        self.message.content = content.into();
+        self
+    }
+
+    pub fn topic(mut self, topic_hash: impl Into<String>) -> Self {
+        self.message.topic_hash = topic_hash.into();
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.message.timestamp = timestamp;
+        self
+    }
+
+    pub fn sequence(mut self, sequence: u64) -> Self {
+        self.message.sequence = sequence;
+        self
+    }
+
+    pub fn lamport(mut self, lamport: u64) -> Self {
+        self.message.lamport = lamport;
+        self
+    }
+
+    /// Marks the message as signed by `sender`.
+    pub fn sent_by(mut self, sender: NodeId) -> Self {
+        self.message.verified_sender = Some(sender);
+        self
+    }
+
+    pub fn edited_at(mut self, edited_at: DateTime<Utc>) -> Self {
+        self.message.edited_at = Some(edited_at);
+        self
+    }
+
+    pub fn deleted_at(mut self, deleted_at: DateTime<Utc>) -> Self {
+        self.message.deleted_at = Some(deleted_at);
+        self
+    }
+
+    pub fn build(self) -> ChatMessage {
+        self.message
+    }
+}
+
+/// The secret key of the test member `seed`, the same in every run.
+pub fn member_key(seed: u8) -> SecretKey {
+    SecretKey::from_bytes(&[seed; 32])
+}
+
+/// The node of the test member `seed`, the same in every run.
+pub fn member(seed: u8) -> NodeId {
+    member_key(seed).public()
+}
+
+/// A topic with a random id and key, for tests without a network.
+#[derive(Debug, Clone)]
+pub struct TopicFixture {
+    pub topic_hash: String,
+    pub topic_id: TopicId,
+    pub key: TopicKey,
+}
+
+impl TopicFixture {
+    pub fn new(topic_hash: impl Into<String>) -> Self {
+        let topic_id = TopicId::from_bytes(rand::random());
+        Self {
+            topic_hash: topic_hash.into(),
+            key: TopicKey::derive(&rand::random(), &topic_id),
+            topic_id,
+        }
+    }
+
+    /// A message `id` on the topic.
+    pub fn message(&self, id: impl Into<String>) -> ChatMessageBuilder {
+        ChatMessageBuilder::new(id).topic(self.topic_hash.clone())
+    }
+
+    /// The frame carrying `payload` on the topic, signed by `sender`.
+    pub fn encode(&self, payload: &Payload, sender: &SecretKey) -> Result<Vec<u8>, String> {
+        wire::encode(payload, sender, &self.key)
+    }
+}
+
+/// Two clients on the network, the joiner subscribed to a topic the
+/// creator created.
+pub struct ClientPairFixture {
+    pub creator: IrohClient,
+    pub joiner: IrohClient,
+    pub topic_name: String,
+    pub ticket: String,
+    /// Hash of the topic on the creator.
+    pub creator_hash: String,
+    /// Hash of the topic on the joiner.
+    pub joiner_hash: String,
+}
+
+impl ClientPairFixture {
+    /// Connects two clients over a new topic named `topic_name`.
+    pub async fn new(topic_name: &str) -> Result<Self, String> {
+        let creator = initialized_client().await?;
+        let (topic_name, ticket, creator_hash) =
+            creator.create_topic(topic_name.to_string()).await?;
+        let joiner = initialized_client().await?;
+        let (_, joiner_hash) = joiner.join_topic(ticket.clone()).await?;
+        Ok(Self {
+            creator,
+            joiner,
+            topic_name,
+            ticket,
+            creator_hash,
+            joiner_hash,
+        })
+    }
+}
+
+/// A client whose network is initialized.
+pub async fn initialized_client() -> Result<IrohClient, String> {
+    let client = IrohClient::new();
+    client.initialize_network().await?;
+    Ok(client)
+}
+
+/// The message `message_id` once it arrives on `events`, or `None` if it
+/// does not within `timeout`. Subscribe before sending the message, as
+/// subscriptions only receive what happens after they are made.
+pub async fn wait_for_message(
+    events: &mut Subscription,
+    message_id: &str,
+    timeout: Duration,
+) -> Option<ChatMessage> {
+    let arrival = async {
+        while let Some(event) = events.next().await {
+            match event {
+                ClientEvent::Message(message) if message.id == message_id => return Some(message),
+                _ => {}
+            }
+        }
+        None
+    };
+    tokio::time::timeout(timeout, arrival).await.ok().flatten()
+}
+
+/// Topics in memory, shared by the [`MemoryTransport`]s chatting on them.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryNetwork {
+    inner: Arc<Mutex<MemoryHub>>,
+    // Stamps the messages of every member, as gossip would bring their
+    // clocks in step
+    clock: LamportClock,
+}
+
+#[derive(Debug, Default)]
+struct MemoryHub {
+    // Name and members of each topic, by hash
+    topics: HashMap<String, (String, BTreeSet<NodeId>)>,
+    // Channels to the subscriptions of each node
+    listeners: HashMap<NodeId, Vec<broadcast::Sender<ClientEvent>>>,
+}
+
+impl MemoryHub {
+    // Hands `event` of the topic `topic_hash` to its members but `from`
+    fn deliver(&mut self, topic_hash: &str, from: NodeId, event: &ClientEvent) {
+        let Some((_, members)) = self.topics.get(topic_hash) else {
+            return;
+        };
+        for member in members.clone().into_iter().filter(|member| *member != from) {
+            self.deliver_to(member, event);
+        }
+    }
+
+    // Hands `event` to the subscriptions of `member`
+    fn deliver_to(&mut self, member: NodeId, event: &ClientEvent) {
+        if let Some(listeners) = self.listeners.get_mut(&member) {
+            listeners.retain(|listener| listener.send(event.clone()).is_ok());
+        }
+    }
+}
+
+impl MemoryNetwork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A transport on the network for the test member `seed`.
+    pub fn transport(&self, seed: u8) -> MemoryTransport {
+        MemoryTransport {
+            network: self.clone(),
+            node_id: member(seed),
+            presence: Arc::default(),
+        }
+    }
+}
+
+/// A [`ChatTransport`] delivering within the process, every message
+/// arriving once, right away, at the other members of its topic.
+///
+/// Tickets and invites are the same, and only work on the same
+/// [`MemoryNetwork`]. The notices of the real client are not sent.
+#[derive(Debug, Clone)]
+pub struct MemoryTransport {
+    network: MemoryNetwork,
+    node_id: NodeId,
+    presence: Arc<Mutex<Option<(String, PresenceStatus)>>>,
+}
+
+impl MemoryTransport {
+    // Announces our presence on `topic_hash`, if we have a name yet
+    fn announce(&self, hub: &mut MemoryHub, topic_hash: &str) {
+        if let Some((author, status)) = self.presence.lock().unwrap().clone() {
+            let presence = Presence {
+                topic_hash: topic_hash.to_string(),
+                author,
+                node_id: self.node_id,
+                status,
+            };
+            hub.deliver(topic_hash, self.node_id, &ClientEvent::Presence(presence));
+        }
+    }
+}
+
+impl ChatTransport for MemoryTransport {
+    fn own_node_id(&self) -> Option<NodeId> {
+        Some(self.node_id)
+    }
+
+    fn events(&self) -> Subscription {
+        // As bounded as the subscriptions of the network
+        let (sender, receiver) = broadcast::channel(EVENT_CAPACITY);
+        let mut hub = self.network.inner.lock().unwrap();
+        hub.listeners.entry(self.node_id).or_default().push(sender);
+        Subscription::from_broadcast(receiver)
+    }
+
+    async fn create(&self, name: String) -> Result<Topic, String> {
+        let hash = Uuid::new_v4().simple().to_string();
+        let mut hub = self.network.inner.lock().unwrap();
+        hub.topics
+            .insert(hash.clone(), (name.clone(), BTreeSet::from([self.node_id])));
+        Ok(Topic {
+            ticket: format!("{}{}", MEMORY_TICKET_PREFIX, hash),
+            name,
+            hash,
+        })
+    }
+
+    async fn join(&self, ticket: String) -> Result<Topic, String> {
+        let hash = ticket
+            .trim()
+            .strip_prefix(MEMORY_TICKET_PREFIX)
+            .ok_or_else(|| "Not a ticket of a topic in memory".to_string())?
+            .to_string();
+        let mut hub = self.network.inner.lock().unwrap();
+        let (name, members) = hub
+            .topics
+            .get_mut(&hash)
+            .ok_or_else(|| "No such topic in memory".to_string())?;
+        let neighbors: Vec<NodeId> = members.iter().copied().collect();
+        members.insert(self.node_id);
+        let name = name.clone();
+        // Every member becomes a neighbor of every other
+        let joined = |node_id| ClientEvent::PeerJoined {
+            topic_hash: hash.clone(),
+            node_id,
+        };
+        for node_id in neighbors {
+            hub.deliver_to(self.node_id, &joined(node_id));
+            hub.deliver_to(node_id, &joined(self.node_id));
+        }
+        self.announce(&mut hub, &hash);
+        Ok(Topic { name, hash, ticket })
+    }
+
+    async fn send(
+        &self,
+        topic_hash: &str,
+        author: String,
+        content: String,
+        sequence: u64,
+    ) -> Result<ChatMessage, String> {
+        let mut hub = self.network.inner.lock().unwrap();
+        if !hub
+            .topics
+            .get(topic_hash)
+            .is_some_and(|(_, members)| members.contains(&self.node_id))
+        {
+            return Err("Not subscribed to this topic".to_string());
+        }
+        let message = ChatMessage {
+            id: Uuid::new_v4().to_string(),
+            author,
+            content,
+            timestamp: Utc::now(),
+            topic_hash: topic_hash.to_string(),
+            sequence,
+            lamport: self.network.clock.tick(),
+            verified_sender: Some(self.node_id),
+            edited_at: None,
+            deleted_at: None,
+        };
+        hub.deliver(
+            topic_hash,
+            self.node_id,
+            &ClientEvent::Message(message.clone()),
+        );
+        Ok(message)
+    }
+
+    fn set_presence(&self, author: String, status: PresenceStatus) {
+        *self.presence.lock().unwrap() = Some((author, status));
+        let mut hub = self.network.inner.lock().unwrap();
+        let topics: Vec<String> = hub
+            .topics
+            .iter()
+            .filter(|(_, (_, members))| members.contains(&self.node_id))
+            .map(|(hash, _)| hash.clone())
+            .collect();
+        for topic_hash in topics {
+            self.announce(&mut hub, &topic_hash);
+        }
+    }
+
+    fn invite(&self, topic_hash: &str) -> Result<String, String> {
+        let hub = self.network.inner.lock().unwrap();
+        if !hub.topics.contains_key(topic_hash) {
+            return Err("Not subscribed to this topic".to_string());
+        }
+        Ok(format!("{}{}", MEMORY_TICKET_PREFIX, topic_hash))
+    }
+
+    async fn shutdown(&self) {
+        let mut hub = self.network.inner.lock().unwrap();
+        let left: Vec<String> = hub
+            .topics
+            .iter()
+            .filter(|(_, (_, members))| members.contains(&self.node_id))
+            .map(|(hash, _)| hash.clone())
+            .collect();
+        for topic_hash in left {
+            let event = ClientEvent::PeerLeft {
+                topic_hash: topic_hash.clone(),
+                node_id: self.node_id,
+            };
+            hub.deliver(&topic_hash, self.node_id, &event);
+            if let Some((_, members)) = hub.topics.get_mut(&topic_hash) {
+                members.remove(&self.node_id);
+            }
+        }
+        // Ends our subscriptions
+        hub.listeners.remove(&self.node_id);
+    }
+}
@@ -0,0 +1,114 @@
+//! Tickets for sharing a topic with other peers.
+//!
+//! A ticket contains everything needed to join a topic: the gossip topic id,
+//! the addresses of peers already subscribed to it, and the secret seed the
+//! topic's encryption key is derived from. Anyone holding a ticket can read
+//! the topic, so it should only be shared with the intended participants. It
+//! is shared as text of the form `ticket-<base32>`.
+//!
+//! Tickets also name the node that created the topic, which alone may
+//! restrict who takes part, see [`access`](crate::access). Tickets of earlier
+//! releases name no creator, and still parse.
+//!
+//! The address of a single node, for peering with it by hand, is shared the
+//! same way as text of the form `node-<base32>`.
+use crate::crypto::TopicKey;
+use iroh::{NodeAddr, NodeId};
+use iroh_gossip::proto::TopicId;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Prefix of the textual form of a ticket.
+pub const TICKET_PREFIX: &str = "ticket-";
+
+/// Prefix of the textual form of a node address.
+pub const NODE_ADDR_PREFIX: &str = "node-";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ticket {
+    pub topic_name: String,
+    pub topic_id: TopicId,
+    pub nodes: Vec<NodeAddr>,
+    pub key_seed: [u8; 32],
+    /// Node that created the topic, `None` in tickets of earlier releases.
+    pub creator: Option<NodeId>,
+}
+
+// A ticket of the releases before topics had a creator
+#[derive(Deserialize)]
+struct UncreditedTicket {
+    topic_name: String,
+    topic_id: TopicId,
+    nodes: Vec<NodeAddr>,
+    key_seed: [u8; 32],
+}
+
+impl Ticket {
+    /// The key encrypting the messages of the ticket's topic.
+    pub fn topic_key(&self) -> TopicKey {
+        TopicKey::derive(&self.key_seed, &self.topic_id)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_stdvec(self).expect("postcard::to_stdvec is infallible")
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        // Earlier tickets end before the creator, which fails to parse
+        postcard::from_bytes(bytes).or_else(|e| {
+            let ticket: UncreditedTicket =
+                postcard::from_bytes(bytes).map_err(|_| format!("Invalid ticket: {}", e))?;
+            Ok(Self {
+                topic_name: ticket.topic_name,
+                topic_id: ticket.topic_id,
+                nodes: ticket.nodes,
+                key_seed: ticket.key_seed,
+                creator: None,
+            })
+        })
+    }
+}
+
+impl fmt::Display for Ticket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut text = data_encoding::BASE32_NOPAD.encode(&self.to_bytes());
+        text.make_ascii_lowercase();
+        write!(f, "{}{}", TICKET_PREFIX, text)
+    }
+}
+
+impl FromStr for Ticket {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let encoded = s
+            .trim()
+            .strip_prefix(TICKET_PREFIX)
+            .ok_or_else(|| "Invalid ticket format".to_string())?;
+        let bytes = data_encoding::BASE32_NOPAD
+            .decode(encoded.to_ascii_uppercase().as_bytes())
+            .map_err(|e| format!("Invalid ticket encoding: {}", e))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// The textual form of `addr`.
+pub fn node_addr_to_string(addr: &NodeAddr) -> String {
+    let bytes = postcard::to_stdvec(addr).expect("postcard::to_stdvec is infallible");
+    let mut text = data_encoding::BASE32_NOPAD.encode(&bytes);
+    text.make_ascii_lowercase();
+    format!("{}{}", NODE_ADDR_PREFIX, text)
+}
+
+/// Parses the textual form of a node address.
+pub fn parse_node_addr(s: &str) -> Result<NodeAddr, String> {
+    let encoded = s
+        .trim()
+        .strip_prefix(NODE_ADDR_PREFIX)
+        .ok_or_else(|| "Invalid node address format".to_string())?;
+    let bytes = data_encoding::BASE32_NOPAD
+        .decode(encoded.to_ascii_uppercase().as_bytes())
+        .map_err(|e| format!("Invalid node address encoding: {}", e))?;
+    postcard::from_bytes(&bytes).map_err(|e| format!("Invalid node address: {}", e))
+}
@@ -0,0 +1,41 @@
+//! Records of deleted messages.
+//!
+//! Gossip gives no guarantee on the order messages arrive in, and peers
+//! rejoining a topic may relay messages long after they were sent. A
+//! [`Tombstones`] set remembers every verified [`MessageDelete`], so a copy
+//! of a deleted message arriving after the delete is deleted again instead
+//! of reappearing.
+use crate::client::{ChatMessage, MessageDelete};
+use iroh::NodeId;
+use std::collections::HashMap;
+
+/// Deletes received so far, by the id of the message they delete.
+#[derive(Debug, Default)]
+pub struct Tombstones {
+    deletes: HashMap<String, (MessageDelete, NodeId)>,
+}
+
+impl Tombstones {
+    /// Remembers a delete sent by `sender`. Deletes without a verified
+    /// sender could never be applied and are not kept.
+    pub fn record(&mut self, delete: MessageDelete, sender: Option<NodeId>) {
+        if let Some(sender) = sender {
+            self.deletes
+                .entry(delete.target_id.clone())
+                .or_insert((delete, sender));
+        }
+    }
+
+    /// Whether a delete for the message with `id` was received.
+    pub fn contains(&self, id: &str) -> bool {
+        self.deletes.contains_key(id)
+    }
+
+    /// Applies the delete recorded for `message`, if any, returning whether
+    /// the message was deleted.
+    pub fn apply(&self, message: &mut ChatMessage) -> bool {
+        self.deletes
+            .get(&message.id)
+            .is_some_and(|(delete, sender)| message.apply_delete(delete, Some(*sender)))
+    }
+}
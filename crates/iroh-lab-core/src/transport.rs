@@ -0,0 +1,98 @@
+//! What chatting needs of the network, so the logic above it can be tested
+//! without one.
+//!
+//! [`IrohClient`] is the transport of the applications. With the `testing`
+//! feature, [`MemoryTransport`](crate::testing::MemoryTransport) connects
+//! chats within the process instead, without binding sockets.
+//!
+//! The engine and the terminal client are generic over the transport. The
+//! iced application is not: it edits, deletes, shares files and events,
+//! restricts topics and shows diagnostics, none of which a fake could stand
+//! in for without the trait growing into the whole client. It talks to a
+//! [`ClientHandle`](crate::handle::ClientHandle) instead, and the logic it
+//! shares with the terminal client lives in core, where it is tested.
+use crate::client::{ChatMessage, IrohClient, Subscription};
+use crate::engine::Topic;
+use crate::roster::PresenceStatus;
+use iroh::NodeId;
+use std::future::Future;
+
+/// Creates and joins topics, and carries what is said on them.
+pub trait ChatTransport: Send + Sync {
+    /// Our node, once it is on the network.
+    fn own_node_id(&self) -> Option<NodeId>;
+
+    /// A new subscription to the events of every topic, and of the node.
+    /// Our own messages are not received.
+    fn events(&self) -> Subscription;
+
+    /// Creates a topic named `name`.
+    fn create(&self, name: String) -> impl Future<Output = Result<Topic, String>> + Send;
+
+    /// Joins a topic with a ticket or invite.
+    fn join(&self, ticket: String) -> impl Future<Output = Result<Topic, String>> + Send;
+
+    /// Sends `content` by `author` to the topic `topic_hash`, returning the
+    /// message as it was sent.
+    fn send(
+        &self,
+        topic_hash: &str,
+        author: String,
+        content: String,
+        sequence: u64,
+    ) -> impl Future<Output = Result<ChatMessage, String>> + Send;
+
+    /// Sets the name and status announced on every topic.
+    fn set_presence(&self, author: String, status: PresenceStatus);
+
+    /// An invite for others to join the topic `topic_hash` with.
+    fn invite(&self, topic_hash: &str) -> Result<String, String>;
+
+    /// Leaves every topic, sending what is pending, and goes off the
+    /// network.
+    fn shutdown(&self) -> impl Future<Output = ()> + Send;
+}
+
+impl ChatTransport for IrohClient {
+    fn own_node_id(&self) -> Option<NodeId> {
+        IrohClient::own_node_id(self)
+    }
+
+    fn events(&self) -> Subscription {
+        IrohClient::events(self, None)
+    }
+
+    async fn create(&self, name: String) -> Result<Topic, String> {
+        let (name, ticket, hash) = self.create_topic(name).await?;
+        Ok(Topic { name, hash, ticket })
+    }
+
+    async fn join(&self, ticket: String) -> Result<Topic, String> {
+        let (name, hash) = self.join_topic(ticket).await?;
+        let ticket = self.ticket(&hash).unwrap_or_default();
+        Ok(Topic { name, hash, ticket })
+    }
+
+    async fn send(
+        &self,
+        topic_hash: &str,
+        author: String,
+        content: String,
+        sequence: u64,
+    ) -> Result<ChatMessage, String> {
+        self.send_message_to(topic_hash, author, content, sequence)
+            .await
+    }
+
+    fn set_presence(&self, author: String, status: PresenceStatus) {
+        IrohClient::set_presence(self, author, status);
+    }
+
+    fn invite(&self, topic_hash: &str) -> Result<String, String> {
+        self.create_invite_to(topic_hash)
+    }
+
+    async fn shutdown(&self) {
+        IrohClient::shutdown(self).await;
+    }
+}
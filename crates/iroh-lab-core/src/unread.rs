@@ -0,0 +1,47 @@
+//! Counts of the messages that arrived on topics while their room was not
+//! shown.
+//!
+//! Topics keep running after their room is left, and their messages are
+//! counted until the room is viewed again. Each message is counted once,
+//! however often it arrives, e.g. again in answer to a sync request.
+use crate::client::ChatMessage;
+use iroh::NodeId;
+use std::collections::{BTreeMap, HashSet};
+
+/// Ids of the unread messages of every topic, by topic hash.
+#[derive(Debug, Clone, Default)]
+pub struct Unread {
+    topics: BTreeMap<String, HashSet<String>>,
+}
+
+impl Unread {
+    /// Counts `message` as unread, unless it is a system notice, was deleted
+    /// or was sent by ourselves as `own_node_id`.
+    pub fn count(&mut self, message: &ChatMessage, own_node_id: Option<NodeId>) {
+        if message.author == "System"
+            || message.is_deleted()
+            || (message.verified_sender.is_some() && message.verified_sender == own_node_id)
+        {
+            return;
+        }
+        self.topics
+            .entry(message.topic_hash.clone())
+            .or_default()
+            .insert(message.id.clone());
+    }
+
+    /// Marks every message of the topic `topic_hash` as read.
+    pub fn clear(&mut self, topic_hash: &str) {
+        self.topics.remove(topic_hash);
+    }
+
+    /// Number of unread messages of the topic `topic_hash`.
+    pub fn get(&self, topic_hash: &str) -> usize {
+        self.topics.get(topic_hash).map_or(0, HashSet::len)
+    }
+
+    /// Number of unread messages of all topics.
+    pub fn total(&self) -> usize {
+        self.topics.values().map(HashSet::len).sum()
+    }
+}
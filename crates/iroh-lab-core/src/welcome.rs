@@ -0,0 +1,86 @@
+//! Welcome messages posted when a newcomer shows up on a topic.
+//!
+//! Topics have no owner or shared settings, so the welcome message of a
+//! topic is a template saved on the node of whoever looks after the topic,
+//! and only that node posts it. A member is new the first time their
+//! presence is heard after the template was saved, so the members known by
+//! then are never welcomed. Which members were welcomed is stored next to
+//! the configuration, e.g. `~/.config/iroh-lab/welcomed.json` on Linux, so
+//! nobody is welcomed twice.
+use crate::config::Config;
+use iroh::NodeId;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Replaced by the name of the newcomer in a template.
+pub const NAME_PLACEHOLDER: &str = "{name}";
+
+// Name of the file holding the welcomed members
+const WELCOMED_FILE: &str = "welcomed.json";
+
+/// The welcome message of `template` for the newcomer `name`, who is
+/// mentioned in front if the template has no place for their name.
+pub fn render(template: &str, name: &str) -> String {
+    let template = template.trim();
+    if template.contains(NAME_PLACEHOLDER) {
+        template.replace(NAME_PLACEHOLDER, name)
+    } else {
+        format!("{}, {}", name, template)
+    }
+}
+
+/// The members welcomed on each topic.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Welcomed {
+    // Node ids as text by topic hash
+    topics: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Welcomed {
+    /// Location of the file of the current user, if the platform has a
+    /// configuration directory.
+    pub fn default_path() -> Option<PathBuf> {
+        Config::directory().map(|dir| dir.join(WELCOMED_FILE))
+    }
+
+    /// Loads the welcomed members from `path`, or none if the file does not
+    /// exist.
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    /// Saves the welcomed members to `path`, creating its directory if
+    /// needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to encode welcomed members: {}", e))?;
+        std::fs::write(path, contents)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Records `node_id` as welcomed on the topic `topic_hash`, returning
+    /// whether it was not before.
+    pub fn mark(&mut self, topic_hash: &str, node_id: NodeId) -> bool {
+        self.topics
+            .entry(topic_hash.to_string())
+            .or_default()
+            .insert(node_id.to_string())
+    }
+
+    /// Forgets who was welcomed on the topic `topic_hash`.
+    pub fn forget(&mut self, topic_hash: &str) {
+        self.topics.remove(topic_hash);
+    }
+}
@@ -0,0 +1,291 @@
+//! Wire format for messages exchanged over gossip.
+//!
+//! Every gossip payload is a single frame encoded with
+//! [postcard](https://docs.rs/postcard), which keeps messages well below the
+//! gossip message size limit. The [`Payload`] is encrypted with the
+//! [`TopicKey`] of the topic, and the ciphertext is signed with the secret key
+//! of the sending node, so receivers can tell which node really wrote a
+//! message no matter what author name it claims. Frames too large for a
+//! single gossip message are split by [`chunk`](crate::chunk).
+//!
+//! Payloads of at least [`COMPRESSION_THRESHOLD`] bytes are compressed with
+//! zstd before encryption, when that makes them smaller. The frame header
+//! flags compressed payloads, and the signature covers the flags as well.
+//!
+//! Older payloads are still accepted by [`decode`]:
+//! - Version 4 frames had no flags and were never compressed.
+//! - Version 3 frames carried chat messages without a Lamport timestamp.
+//!   Those messages are ordered as if sent before any other message.
+//!
+//! Older frames are refused, as nothing ties them to the topic key and
+//! anyone knowing the topic could send them: version 2 frames carried an
+//! unencrypted payload, version 1 frames had no signature, and earlier
+//! releases sent a plain JSON encoded [`ChatMessage`], starting with `{`.
+use crate::chunk;
+use crate::client::{
+    Ack, Allowlist, Attachment, Avatar, CalendarEvent, ChatMessage, Heartbeat, MessageDelete,
+    MessageEdit, Presence, Profile, Rsvp, Snapshot, SyncRequest, TaskUpdate,
+};
+use crate::crypto::TopicKey;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::Signature;
+use iroh::{NodeId, PublicKey, SecretKey};
+use serde::{Deserialize, Serialize};
+
+/// Version of the frame format written by this build.
+pub const WIRE_VERSION: u8 = 5;
+
+// Frame version without flags, whose payloads were never compressed
+const UNFLAGGED_VERSION: u8 = 4;
+
+// Frame version with chat messages lacking a Lamport timestamp
+const UNORDERED_VERSION: u8 = 3;
+
+// Frame version with signed, but unencrypted payloads, no longer accepted
+const PLAINTEXT_VERSION: u8 = 2;
+
+// Frame version without signatures, no longer accepted
+const UNSIGNED_VERSION: u8 = 1;
+
+// First byte of a legacy JSON payload, no longer accepted
+const JSON_OBJECT_START: u8 = b'{';
+
+// Frame flag set when the payload is compressed with zstd
+const FLAG_COMPRESSED: u8 = 0b0000_0001;
+
+/// Encoded payloads at least this large are compressed.
+pub const COMPRESSION_THRESHOLD: usize = 256;
+
+// Balances compression ratio against the time spent on every message
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Largest content of a chat message, in bytes, that fits into a frame with
+/// its other fields, signature and encryption.
+pub const MAX_CONTENT_SIZE: usize = chunk::MAX_FRAME_SIZE - FRAME_OVERHEAD;
+
+// Room in a frame for everything but the content of a chat message
+const FRAME_OVERHEAD: usize = 4 * 1024;
+
+// Largest payload accepted after decompression, so a small frame cannot
+// expand into an arbitrary amount of memory
+const MAX_DECOMPRESSED_SIZE: usize = 4 * 1024 * 1024;
+
+/// Whether [`encode_with`] may compress the payload.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// Compress payloads above [`COMPRESSION_THRESHOLD`] if that saves space.
+    #[default]
+    Auto,
+    /// Never compress the payload.
+    Never,
+}
+
+/// A message received over a gossip topic.
+#[derive(Debug, Clone)]
+pub struct Envelope {
+    /// Frame version the message was sent with.
+    pub version: u8,
+    pub payload: Payload,
+    /// Node whose signature over the payload was verified.
+    pub sender: NodeId,
+}
+
+/// The content carried by a frame.
+///
+/// New variants must only ever be appended, as postcard encodes the variant
+/// index on the wire.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Payload {
+    Chat(ChatMessage),
+    Edit(MessageEdit),
+    Heartbeat(Heartbeat),
+    Delete(MessageDelete),
+    SyncRequest(SyncRequest),
+    Presence(Presence),
+    Ack(Ack),
+    Profile(Profile),
+    Avatar(Avatar),
+    Snapshot(Snapshot),
+    Task(TaskUpdate),
+    Event(CalendarEvent),
+    Rsvp(Rsvp),
+    Attachment(Attachment),
+    Allowlist(Allowlist),
+}
+
+// The frame written to the wire. The payload is kept as encrypted bytes so
+// the signature covers exactly what was sent.
+#[derive(Serialize, Deserialize)]
+struct SignedFrame {
+    version: u8,
+    flags: u8,
+    sender: PublicKey,
+    signature: Signature,
+    payload: Vec<u8>,
+}
+
+// The frame written by versions 2 to 4, without flags
+#[derive(Deserialize)]
+struct UnflaggedFrame {
+    version: u8,
+    sender: PublicKey,
+    signature: Signature,
+    payload: Vec<u8>,
+}
+
+// The payload carried by frames up to version 3
+#[derive(Deserialize)]
+enum UnorderedPayload {
+    Chat(UnorderedChatMessage),
+}
+
+// A chat message as sent before messages carried a Lamport timestamp
+#[derive(Deserialize)]
+struct UnorderedChatMessage {
+    id: String,
+    author: String,
+    content: String,
+    timestamp: DateTime<Utc>,
+    topic_hash: String,
+    sequence: u64,
+}
+
+impl From<UnorderedPayload> for Payload {
+    fn from(payload: UnorderedPayload) -> Self {
+        match payload {
+            UnorderedPayload::Chat(message) => Payload::Chat(ChatMessage {
+                id: message.id,
+                author: message.author,
+                content: message.content,
+                timestamp: message.timestamp,
+                topic_hash: message.topic_hash,
+                sequence: message.sequence,
+                lamport: 0,
+                verified_sender: None,
+                edited_at: None,
+                deleted_at: None,
+            }),
+        }
+    }
+}
+
+/// Encodes, compresses, encrypts and signs a payload for sending over gossip.
+pub fn encode(
+    payload: &Payload,
+    secret_key: &SecretKey,
+    topic_key: &TopicKey,
+) -> Result<Vec<u8>, String> {
+    encode_with(payload, secret_key, topic_key, Compression::Auto)
+}
+
+/// Like [`encode`], with control over compression of the payload.
+pub fn encode_with(
+    payload: &Payload,
+    secret_key: &SecretKey,
+    topic_key: &TopicKey,
+    compression: Compression,
+) -> Result<Vec<u8>, String> {
+    let mut payload =
+        postcard::to_stdvec(payload).map_err(|e| format!("Failed to encode payload: {}", e))?;
+    let mut flags = 0;
+    if compression == Compression::Auto && payload.len() >= COMPRESSION_THRESHOLD {
+        let compressed = zstd::bulk::compress(&payload, COMPRESSION_LEVEL)
+            .map_err(|e| format!("Failed to compress payload: {}", e))?;
+        if compressed.len() < payload.len() {
+            payload = compressed;
+            flags |= FLAG_COMPRESSED;
+        }
+    }
+
+    let payload = topic_key.encrypt(&payload)?;
+    let frame = SignedFrame {
+        version: WIRE_VERSION,
+        flags,
+        sender: secret_key.public(),
+        signature: secret_key.sign(&signed_bytes(flags, &payload)),
+        payload,
+    };
+    postcard::to_stdvec(&frame).map_err(|e| format!("Failed to encode frame: {}", e))
+}
+
+/// Decodes a payload received over gossip on the topic with `topic_key`.
+///
+/// Payloads with an invalid signature, payloads that fail to authenticate
+/// with the topic key, and frames of versions that were not encrypted, are
+/// rejected.
+pub fn decode(bytes: &[u8], topic_key: &TopicKey) -> Result<Envelope, String> {
+    match bytes.first() {
+        None => Err("Empty payload".to_string()),
+        Some(&JSON_OBJECT_START) => Err("Refusing unencrypted legacy JSON message".to_string()),
+        Some(&(UNSIGNED_VERSION | PLAINTEXT_VERSION)) => Err(format!(
+            "Refusing unencrypted frame of version {}",
+            bytes[0]
+        )),
+        Some(&UNORDERED_VERSION) => decode_unflagged(bytes, topic_key, decode_unordered_payload),
+        Some(&UNFLAGGED_VERSION) => decode_unflagged(bytes, topic_key, decode_payload),
+        Some(&WIRE_VERSION) => decode_signed(bytes, topic_key),
+        Some(version) => Err(format!("Unsupported wire version {}", version)),
+    }
+}
+
+fn decode_signed(bytes: &[u8], topic_key: &TopicKey) -> Result<Envelope, String> {
+    let frame: SignedFrame =
+        postcard::from_bytes(bytes).map_err(|e| format!("Failed to decode frame: {}", e))?;
+    frame
+        .sender
+        .verify(&signed_bytes(frame.flags, &frame.payload), &frame.signature)
+        .map_err(|_| format!("Invalid signature from {}", frame.sender.fmt_short()))?;
+    if frame.flags & !FLAG_COMPRESSED != 0 {
+        return Err(format!("Unsupported frame flags {:#010b}", frame.flags));
+    }
+
+    let mut payload = topic_key.decrypt(&frame.payload)?;
+    if frame.flags & FLAG_COMPRESSED != 0 {
+        payload = zstd::bulk::decompress(&payload, MAX_DECOMPRESSED_SIZE)
+            .map_err(|e| format!("Failed to decompress payload: {}", e))?;
+    }
+
+    Ok(Envelope {
+        version: frame.version,
+        payload: decode_payload(&payload)?,
+        sender: frame.sender,
+    })
+}
+
+// The bytes covered by the signature of a frame
+fn signed_bytes(flags: u8, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(1 + payload.len());
+    bytes.push(flags);
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+fn decode_unflagged(
+    bytes: &[u8],
+    topic_key: &TopicKey,
+    decode_payload: fn(&[u8]) -> Result<Payload, String>,
+) -> Result<Envelope, String> {
+    let frame: UnflaggedFrame =
+        postcard::from_bytes(bytes).map_err(|e| format!("Failed to decode frame: {}", e))?;
+    frame
+        .sender
+        .verify(&frame.payload, &frame.signature)
+        .map_err(|_| format!("Invalid signature from {}", frame.sender.fmt_short()))?;
+    let payload = topic_key.decrypt(&frame.payload)?;
+
+    Ok(Envelope {
+        version: frame.version,
+        payload: decode_payload(&payload)?,
+        sender: frame.sender,
+    })
+}
+
+fn decode_payload(bytes: &[u8]) -> Result<Payload, String> {
+    postcard::from_bytes(bytes).map_err(|e| format!("Failed to decode payload: {}", e))
+}
+
+fn decode_unordered_payload(bytes: &[u8]) -> Result<Payload, String> {
+    postcard::from_bytes::<UnorderedPayload>(bytes)
+        .map(Payload::from)
+        .map_err(|e| format!("Failed to decode payload: {}", e))
+}
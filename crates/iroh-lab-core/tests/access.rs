@@ -0,0 +1,251 @@
+use futures::StreamExt;
+use iroh::NodeAddr;
+use iroh_gossip::proto::TopicId;
+use iroh_lab_core::access::Access;
+use iroh_lab_core::client::{Allowlist, ClientEvent, IrohClient};
+use iroh_lab_core::testing::{self, member, ClientPairFixture};
+use iroh_lab_core::ticket::{Ticket, TICKET_PREFIX};
+use std::collections::BTreeSet;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+// How long messages and allowlists may take to arrive
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+fn allowlist(members: Option<&[u8]>, lamport: u64) -> Allowlist {
+    Allowlist {
+        topic_hash: "test-topic".to_string(),
+        members: members.map(|seeds| seeds.iter().copied().map(member).collect()),
+        lamport,
+    }
+}
+
+/// # Test: Only Listed Nodes Take Part in Private Topics
+///
+/// ## Steps:
+/// 1. Check who is admitted to an open topic
+/// 2. Accept an allowlist of the creator naming one member
+/// 3. Offer allowlists signed by others, and an outdated one
+/// 4. Accept an allowlist opening the topic again
+///
+/// ## Assertions:
+/// - Open topics admit everyone, unsigned payloads included
+/// - Private topics admit the creator and the listed members only
+/// - Only the creator's allowlists are accepted, and only newer ones
+/// - Topics without a known creator cannot be made private
+#[test]
+fn test_only_listed_nodes_take_part_in_private_topics() {
+    let (creator, listed, outsider) = (member(1), member(2), member(3));
+    let mut access = Access::new(Some(creator));
+    assert!(
+        access.admits(Some(outsider)) && access.admits(None),
+        "Open topics should admit everyone"
+    );
+
+    assert_eq!(
+        access.accept(allowlist(Some(&[2]), 5), Some(creator)),
+        Ok(true),
+        "The allowlist of the creator should be accepted"
+    );
+    assert!(
+        access.admits(Some(creator)) && access.admits(Some(listed)),
+        "The creator and listed members should be admitted"
+    );
+    assert!(
+        !access.admits(Some(outsider)),
+        "Nodes not listed should not be admitted"
+    );
+    assert!(
+        !access.admits(None),
+        "Unsigned payloads should not be admitted"
+    );
+
+    assert!(
+        access
+            .accept(allowlist(Some(&[2, 3]), 6), Some(listed))
+            .is_err(),
+        "Allowlists of others should be refused"
+    );
+    assert_eq!(
+        access.accept(allowlist(None, 4), Some(creator)),
+        Ok(false),
+        "Outdated allowlists should be ignored"
+    );
+    assert!(
+        !access.admits(Some(outsider)),
+        "Refused and outdated allowlists should change nothing"
+    );
+
+    assert_eq!(
+        access.accept(allowlist(None, 7), Some(creator)),
+        Ok(true),
+        "Opening the topic should be accepted"
+    );
+    assert!(
+        access.admits(Some(outsider)) && access.members().is_none(),
+        "The topic should be open again"
+    );
+
+    let mut unknown = Access::new(None);
+    assert!(
+        unknown
+            .accept(allowlist(Some(&[2]), 1), Some(creator))
+            .is_err(),
+        "Topics without a known creator should stay open"
+    );
+}
+
+/// # Test: Tickets Name the Creator
+///
+/// ## Steps:
+/// 1. Create a topic and parse its ticket
+/// 2. Parse a ticket as written before tickets named a creator
+///
+/// ## Assertions:
+/// - New tickets name the node creating the topic
+/// - Earlier tickets still parse, naming no creator
+#[test]
+fn test_tickets_name_the_creator() {
+    let rt = Runtime::new().unwrap();
+
+    rt.block_on(async {
+        let client = testing::initialized_client()
+            .await
+            .expect("Failed to initialize network");
+        let (_, ticket, topic_hash) = client
+            .create_topic("credited".to_string())
+            .await
+            .expect("Failed to create topic");
+        let ticket: Ticket = ticket.parse().expect("Ticket should parse");
+        assert_eq!(
+            ticket.creator,
+            client.own_node_id(),
+            "The ticket should name the creator"
+        );
+        assert_eq!(
+            client.topic_creator(&topic_hash),
+            client.own_node_id(),
+            "The client should know who created the topic"
+        );
+        client.shutdown().await;
+    });
+
+    let earlier: (String, TopicId, Vec<NodeAddr>, [u8; 32]) = (
+        "uncredited".to_string(),
+        TopicId::from_bytes([3; 32]),
+        vec![NodeAddr::new(member(1))],
+        [2; 32],
+    );
+    let bytes = postcard::to_stdvec(&earlier).unwrap();
+    let text = format!(
+        "{}{}",
+        TICKET_PREFIX,
+        data_encoding::BASE32_NOPAD.encode(&bytes).to_lowercase()
+    );
+    let ticket: Ticket = text.parse().expect("Earlier tickets should parse");
+    assert_eq!(ticket.topic_name, "uncredited", "The name should be kept");
+    assert_eq!(
+        ticket.creator, None,
+        "Earlier tickets should name no creator"
+    );
+}
+
+/// # Test: Creators Drop Messages of Nodes Not Allowed
+///
+/// ## Steps:
+/// 1. Connect a creator and a member, and let an outsider join as well
+/// 2. Restrict the topic to the member, replacing a list published before a
+///    restart
+/// 3. Let the outsider, then the member, send a message
+///
+/// ## Assertions:
+/// - Only the creator may restrict the topic
+/// - The allowlist is stamped later than the list it replaces
+/// - The member learns the allowlist
+/// - The message of the member arrives, while the creator and the member
+///   drop the message of the outsider
+#[test]
+fn test_creators_drop_messages_of_nodes_not_allowed() {
+    let rt = Runtime::new().unwrap();
+
+    rt.block_on(async {
+        let pair = ClientPairFixture::new("private-topic")
+            .await
+            .expect("Failed to connect the clients");
+        let outsider = testing::initialized_client()
+            .await
+            .expect("Failed to initialize network");
+        outsider
+            .join_topic(pair.ticket.clone())
+            .await
+            .expect("Failed to join topic");
+        let member_id = pair.joiner.own_node_id().expect("Member should be online");
+        let members = BTreeSet::from([member_id]);
+
+        assert!(
+            pair.joiner
+                .set_allowlist(&pair.joiner_hash, Some(BTreeSet::new()), 0)
+                .await
+                .is_err(),
+            "Only the creator should restrict the topic"
+        );
+        let allowlist = pair
+            .creator
+            .set_allowlist(&pair.creator_hash, Some(members.clone()), 1000)
+            .await
+            .expect("The creator should restrict the topic");
+        assert!(
+            allowlist.lamport > 1000,
+            "The allowlist should be stamped later than the list it replaces"
+        );
+        assert_eq!(
+            pair.creator.allowlist(&pair.creator_hash),
+            Some(members.clone()),
+            "The creator should apply its allowlist"
+        );
+        let learned = tokio::time::timeout(TIMEOUT, async {
+            while pair.joiner.allowlist(&pair.joiner_hash).as_ref() != Some(&members) {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await;
+        assert!(learned.is_ok(), "The member should learn the allowlist");
+
+        let mut events = IrohClient::subscribe(Some(&pair.creator_hash));
+        let intruding = outsider
+            .send_message("mallory".to_string(), "Let me in".to_string(), 1)
+            .await
+            .expect("The outsider should still be able to send");
+        let allowed = pair
+            .joiner
+            .send_message("bob".to_string(), "Hello".to_string(), 1)
+            .await
+            .expect("Message should be sent");
+
+        // Whatever arrives before the message of the member is kept, as it
+        // may include the message of the outsider
+        let mut seen = Vec::new();
+        let arrival = tokio::time::timeout(TIMEOUT, async {
+            while let Some(event) = events.next().await {
+                if let ClientEvent::Message(message) = event {
+                    seen.push(message.id.clone());
+                    if message.id == allowed.id {
+                        break;
+                    }
+                }
+            }
+        })
+        .await;
+        assert!(arrival.is_ok(), "The message of the member should arrive");
+        let late =
+            testing::wait_for_message(&mut events, &intruding.id, Duration::from_secs(2)).await;
+        assert!(
+            !seen.contains(&intruding.id) && late.is_none(),
+            "The message of the outsider should be dropped"
+        );
+
+        outsider.shutdown().await;
+        pair.joiner.shutdown().await;
+        pair.creator.shutdown().await;
+    });
+}
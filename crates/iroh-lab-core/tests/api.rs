@@ -0,0 +1,11 @@
+/// # Test: Public API Stays Stable
+///
+/// ## Steps:
+/// 1. Build programs using the prelude the way downstream users do
+///
+/// ## Assertions:
+/// - They build, so the stable API has not changed by accident
+#[test]
+fn test_public_api_stays_stable() {
+    trybuild::TestCases::new().pass("tests/api/*.rs");
+}
@@ -0,0 +1,70 @@
+// Uses the prelude the way programs built on the library do. This file
+// failing to build means the stable API changed.
+use iroh_lab_core::prelude::*;
+use futures::{FutureExt, StreamExt};
+use std::str::FromStr;
+
+async fn chat(client: &mut IrohClient) -> Result<(), String> {
+    let _node_id: String = client.initialize_network().await?;
+    let (_topic_name, invite, _topic_hash): (String, String, String) =
+        client.create_topic("book-club".to_string()).await?;
+    let (_topic_name, topic_hash): (String, String) = client.join_topic(invite.clone()).await?;
+    client.switch_topic(&topic_hash)?;
+
+    let message: ChatMessage = client
+        .send_message("alice".to_string(), "Hello".to_string(), 1)
+        .await?;
+    let _: (&String, &String, &String, &String) = (
+        &message.id,
+        &message.author,
+        &message.content,
+        &message.topic_hash,
+    );
+    let _: (u64, u64) = (message.sequence, message.lamport);
+
+    let ticket: Ticket = Ticket::from_str(&invite)?;
+    let _: &String = &ticket.topic_name;
+    let _: String = ticket.to_string();
+    Ok(())
+}
+
+fn receive() -> Option<ChatMessage> {
+    let mut events: Subscription = IrohClient::subscribe(None);
+    let _: Subscription = IrohClient::subscribe(Some("topic-hash"));
+    match events.next().now_or_never()?? {
+        ClientEvent::Message(message) => Some(message),
+        event => {
+            let _: &str = event.topic_hash();
+            None
+        }
+    }
+}
+
+async fn bot(ticket: String) -> Result<(), String> {
+    let config = iroh_lab_core::config::Config::default();
+    let engine: ChatEngine = ChatEngine::start("bot", &config).await?;
+    let _events = engine.events()?;
+    let topic = engine.join(ticket).await?;
+    let _: (&String, &String, &String) = (&topic.name, &topic.hash, &topic.ticket);
+    let message: ChatMessage = engine.send(&topic.hash, "Hello").await?;
+    let _: ChatMessage = engine.reply(&message, "Hello again").await?;
+    let _: &str = engine.username();
+    Ok(())
+}
+
+fn view(message: ChatMessage) -> usize {
+    let mut conversation: Conversation = Conversation::new();
+    let _: bool = conversation.insert(message);
+    let _: u64 = conversation.next_sequence();
+    let roster: Roster = Roster::default();
+    let _: usize = roster.present(std::time::Instant::now());
+    conversation.messages().len()
+}
+
+fn main() {
+    let mut client: IrohClient = IrohClient::new();
+    let _ = chat(&mut client);
+    let _ = receive();
+    let _ = bot(String::new());
+    let _ = receive().map(view);
+}
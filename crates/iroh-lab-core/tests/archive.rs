@@ -0,0 +1,134 @@
+use iroh_lab_core::archive::{self, Archive, Problem};
+use iroh_lab_core::client::ChatMessage;
+use iroh_lab_core::testing::ChatMessageBuilder;
+use std::path::Path;
+
+fn chat(id: u64) -> ChatMessage {
+    ChatMessageBuilder::new(id.to_string())
+        .content(format!("Message {}", id))
+        .topic("archive-test-topic")
+        .sequence(id)
+        .lamport(id)
+        .build()
+}
+
+// Writes an archive of `count` messages to `path`
+fn write_archive(path: &Path, count: u64) {
+    let mut archive = Archive::open(path).expect("Archive should open");
+    for id in 0..count {
+        archive
+            .append(&chat(id))
+            .expect("Message should be archived");
+    }
+}
+
+// Replaces the lines of the archive at `path`
+fn edit_lines(path: &Path, edit: impl FnOnce(&mut Vec<String>)) {
+    let contents = std::fs::read_to_string(path).expect("Archive should be readable");
+    let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+    edit(&mut lines);
+    std::fs::write(path, lines.join("\n") + "\n").expect("Archive should be writable");
+}
+
+/// # Test: Archives Continue Their Chain
+///
+/// ## Steps:
+/// 1. Archive messages, reopen the archive and archive more
+/// 2. Verify the archive
+///
+/// ## Assertions:
+/// - A reopened archive continues after its last entry
+/// - Every entry continues the chain
+#[test]
+fn test_archives_continue_their_chain() {
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let path = Archive::path_in(dir.path(), "archive-test-topic");
+    write_archive(&path, 3);
+
+    let mut archive = Archive::open(&path).expect("Archive should reopen");
+    assert_eq!(
+        archive.len(),
+        3,
+        "Reopened archive should count its entries"
+    );
+    archive
+        .append(&chat(3))
+        .expect("Message should be archived");
+
+    let verification = archive::verify(&path).expect("Archive should be verified");
+    assert!(verification.is_intact(), "Archive should be intact");
+    assert_eq!(verification.intact, 4, "All entries should be verified");
+    assert_eq!(
+        Archive::list(dir.path()).expect("Archives should be listed"),
+        vec![path],
+        "Archive should be listed"
+    );
+}
+
+/// # Test: Changed Entries Are Detected
+///
+/// ## Steps:
+/// 1. Archive messages
+/// 2. Change the content of the second entry
+///
+/// ## Assertions:
+/// - Verification stops at the changed entry
+/// - The entries before it are reported intact
+#[test]
+fn test_changed_entries_are_detected() {
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let path = Archive::path_in(dir.path(), "archive-test-topic");
+    write_archive(&path, 3);
+    edit_lines(&path, |lines| {
+        lines[1] = lines[1].replace("Message 1", "Message one");
+    });
+
+    let verification = archive::verify(&path).expect("Archive should be verified");
+    let divergence = verification
+        .divergence
+        .expect("Changed archive should not be intact");
+    assert_eq!(divergence.line, 2, "Changed line should be reported");
+    assert_eq!(
+        divergence.problem,
+        Problem::Tampered,
+        "Changed entry should be reported as tampered"
+    );
+    assert_eq!(verification.intact, 1, "Earlier entries should be intact");
+}
+
+/// # Test: Missing and Reordered Entries Are Detected
+///
+/// ## Assertions:
+/// - A removed entry is reported as a hole at the entry after it
+/// - Swapped entries are reported where the chain breaks
+#[test]
+fn test_missing_and_reordered_entries_are_detected() {
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+
+    let removed = Archive::path_in(dir.path(), "removed");
+    write_archive(&removed, 3);
+    edit_lines(&removed, |lines| {
+        lines.remove(1);
+    });
+    let divergence = archive::verify(&removed)
+        .expect("Archive should be verified")
+        .divergence
+        .expect("Archive with a hole should not be intact");
+    assert_eq!(
+        (divergence.line, divergence.problem),
+        (2, Problem::Hole { expected: 1 }),
+        "Hole should be reported at the entry after it"
+    );
+
+    let swapped = Archive::path_in(dir.path(), "swapped");
+    write_archive(&swapped, 3);
+    edit_lines(&swapped, |lines| lines.swap(0, 1));
+    let divergence = archive::verify(&swapped)
+        .expect("Archive should be verified")
+        .divergence
+        .expect("Reordered archive should not be intact");
+    assert_eq!(
+        divergence.line, 1,
+        "Reordering should be reported at the first moved entry"
+    );
+}
@@ -0,0 +1,46 @@
+use chrono::Utc;
+use iroh_lab_core::audit::{self, AuditEntry, AuditEvent};
+
+/// # Test: Holds Are Recorded in the Audit Log
+///
+/// ## Steps:
+/// 1. Place a hold on a topic, skip a prune and release the hold
+/// 2. Place a hold on another topic
+/// 3. Read the entries of the first topic
+///
+/// ## Assertions:
+/// - The entries of the topic read back in order
+/// - Entries of other topics are left out
+#[test]
+fn test_holds_are_recorded_in_the_audit_log() {
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let path = dir.path().join("iroh-lab").join("audit.jsonl");
+    let entry = |topic_hash: &str, event| AuditEntry {
+        at: Utc::now(),
+        topic_hash: topic_hash.to_string(),
+        event,
+    };
+
+    let held = [
+        entry("held-topic", AuditEvent::HoldPlaced),
+        entry("held-topic", AuditEvent::PruneSkipped),
+        entry("held-topic", AuditEvent::HoldReleased),
+    ];
+    audit::append(&path, &held[0]).expect("Entry should be appended");
+    audit::append(&path, &entry("other-topic", AuditEvent::HoldPlaced))
+        .expect("Entry should be appended");
+    audit::append(&path, &held[1]).expect("Entry should be appended");
+    audit::append(&path, &held[2]).expect("Entry should be appended");
+
+    assert_eq!(
+        audit::entries(&path, "held-topic").expect("Audit log should be readable"),
+        held,
+        "Entries of the topic should read back in order"
+    );
+    assert!(
+        audit::entries(&dir.path().join("missing.jsonl"), "held-topic")
+            .expect("Missing audit log should be readable")
+            .is_empty(),
+        "Missing audit log should have no entries"
+    );
+}
@@ -0,0 +1,209 @@
+use chrono::{DateTime, Duration, TimeZone, Utc, Weekday};
+use iroh_lab_core::archive::{self, Archive};
+use iroh_lab_core::automation::{ExportRule, Run, RunHistory, MAX_RUNS_PER_TOPIC};
+use iroh_lab_core::client::ChatMessage;
+use iroh_lab_core::testing::ChatMessageBuilder;
+use std::path::Path;
+
+// Sunday, 9:00 UTC
+fn sunday_morning() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 10, 11, 9, 0, 0).unwrap()
+}
+
+fn weekly_rule(folder: &Path, prune: bool) -> ExportRule {
+    ExportRule {
+        topic_name: "Team chat".to_string(),
+        weekday: Weekday::Sun,
+        hour: 9,
+        days: 7,
+        folder: folder.to_path_buf(),
+        prune,
+        since: sunday_morning() - Duration::days(30),
+    }
+}
+
+fn chat(id: u64, timestamp: DateTime<Utc>) -> ChatMessage {
+    ChatMessageBuilder::new(id.to_string())
+        .content(format!("Message {}", id))
+        .topic("automation-test-topic")
+        .timestamp(timestamp)
+        .sequence(id)
+        .lamport(id)
+        .build()
+}
+
+/// # Test: Rules Run Once per Scheduled Time
+///
+/// ## Steps:
+/// 1. Check a weekly rule before, at and after its scheduled time
+/// 2. Check it again after it ran, and for a rule saved after the time
+///
+/// ## Assertions:
+/// - A rule is due once its scheduled time passed
+/// - A rule is not due again until the next scheduled time
+/// - Scheduled times before the rule was saved are not run
+#[test]
+fn test_rules_run_once_per_scheduled_time() {
+    let rule = weekly_rule(Path::new("exports"), false);
+    let last_week = sunday_morning() - Duration::days(7);
+
+    assert!(
+        !rule.is_due(Some(last_week), &(sunday_morning() - Duration::minutes(1))),
+        "Rule should not be due before its time"
+    );
+    assert!(
+        rule.is_due(Some(last_week), &sunday_morning()),
+        "Rule should be due at its time"
+    );
+    assert!(
+        rule.is_due(Some(last_week), &(sunday_morning() + Duration::days(2))),
+        "Missed rule should be due later in the week"
+    );
+    assert!(
+        !rule.is_due(
+            Some(sunday_morning()),
+            &(sunday_morning() + Duration::days(6))
+        ),
+        "Rule should not be due again within the week"
+    );
+    assert!(
+        rule.is_due(
+            Some(sunday_morning()),
+            &(sunday_morning() + Duration::days(7))
+        ),
+        "Rule should be due the next week"
+    );
+
+    let new_rule = ExportRule {
+        since: sunday_morning() + Duration::hours(1),
+        ..rule
+    };
+    assert!(
+        !new_rule.is_due(None, &(sunday_morning() + Duration::hours(2))),
+        "Rule should not run for times before it was saved"
+    );
+}
+
+/// # Test: Exports Write Markdown and Prune the Archive
+///
+/// ## Steps:
+/// 1. Archive messages from two weeks ago, last week and after the run
+/// 2. Run a weekly rule that prunes
+/// 3. Archive another message and run the rule with the topic on hold
+///
+/// ## Assertions:
+/// - Only last week's messages are exported, under a heading per day
+/// - Every exported and older message is pruned, later ones are kept
+/// - The pruned archive still verifies
+/// - A topic on hold is exported but not pruned
+/// - The run is recorded in the history
+#[test]
+fn test_exports_write_markdown_and_prune_the_archive() {
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let archive_dir = dir.path().join("archive");
+    let export_dir = dir.path().join("exports");
+    let path = Archive::path_in(&archive_dir, "automation-test-topic");
+
+    let mut archive = Archive::open(&path).expect("Archive should open");
+    archive
+        .append(&chat(1, sunday_morning() - Duration::days(10)))
+        .expect("Message should be archived");
+    archive
+        .append(&chat(2, sunday_morning() - Duration::days(3)))
+        .expect("Message should be archived");
+    archive
+        .append(&chat(3, sunday_morning() + Duration::minutes(1)))
+        .expect("Message should be archived");
+
+    let run = Run::execute(
+        &weekly_rule(&export_dir, true),
+        "automation-test-topic",
+        &archive_dir,
+        sunday_morning(),
+        false,
+    );
+    assert_eq!(run.error, None, "Export should succeed");
+    assert_eq!(
+        run.exported, 1,
+        "Only last week's message should be exported"
+    );
+    assert_eq!(
+        run.pruned, 2,
+        "Exported and older messages should be pruned"
+    );
+
+    let file = run.file.clone().expect("Export should be written");
+    assert_eq!(
+        file,
+        export_dir.join("Team-chat-2026-10-11.md"),
+        "Export should be named after the topic and date"
+    );
+    let markdown = std::fs::read_to_string(&file).expect("Export should be readable");
+    assert!(
+        markdown.starts_with("# Team chat\n"),
+        "Export should have a title"
+    );
+    assert!(
+        markdown.contains("## 2026-10-08\n") && markdown.contains("**alice** 09:00: Message 2"),
+        "Export should list the message under its day"
+    );
+    assert!(
+        !markdown.contains("Message 1") && !markdown.contains("Message 3"),
+        "Older and later messages should not be exported"
+    );
+
+    assert!(
+        archive::verify(&path)
+            .expect("Archive should be readable")
+            .is_intact(),
+        "Pruned archive should verify"
+    );
+    let kept = archive::read(&path).expect("Archive should be readable");
+    assert_eq!(
+        kept.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(),
+        ["3"],
+        "Later message should be kept"
+    );
+
+    // Another run of the same rule on hold keeps everything
+    archive::prune(&path, |_| true).expect("Archive should be readable");
+    let mut archive = Archive::open(&path).expect("Archive should reopen");
+    archive
+        .append(&chat(4, sunday_morning() - Duration::days(1)))
+        .expect("Message should be archived");
+    let held = Run::execute(
+        &weekly_rule(&export_dir, true),
+        "automation-test-topic",
+        &archive_dir,
+        sunday_morning(),
+        true,
+    );
+    assert_eq!(held.error, None, "Export on hold should succeed");
+    assert_eq!(held.exported, 1, "Topic on hold should still be exported");
+    assert!(
+        held.prune_held && held.pruned == 0,
+        "Topic on hold should not be pruned"
+    );
+    assert_eq!(
+        archive::read(&path)
+            .expect("Archive should be readable")
+            .len(),
+        2,
+        "Archive on hold should keep every message"
+    );
+
+    let mut history = RunHistory::default();
+    for _ in 0..=MAX_RUNS_PER_TOPIC {
+        history.record("automation-test-topic", run.clone());
+    }
+    assert_eq!(
+        history.runs("automation-test-topic").len(),
+        MAX_RUNS_PER_TOPIC,
+        "History should keep the latest runs"
+    );
+    assert_eq!(
+        history.last_run("automation-test-topic"),
+        Some(sunday_morning()),
+        "History should know the last run"
+    );
+}
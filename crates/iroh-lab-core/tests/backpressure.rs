@@ -0,0 +1,116 @@
+use futures::StreamExt;
+use iroh_lab_core::client::{IrohClient, Subscription, EVENT_CAPACITY};
+use iroh_lab_core::testing::{ChatMessageBuilder, MemoryNetwork};
+use iroh_lab_core::transport::ChatTransport;
+use iroh_lab_core::ClientEvent;
+use std::time::Duration;
+
+// Events sent beyond what a subscription holds
+const OVERFLOW: usize = 10;
+
+// How long the events sent may take to arrive
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+// Contents of the next `count` chat messages on `events`
+async fn receive(events: &mut Subscription, count: usize) -> Vec<String> {
+    let messages = events
+        .filter_map(|event| async move {
+            match event {
+                ClientEvent::Message(message) => Some(message.content),
+                _ => None,
+            }
+        })
+        .take(count)
+        .collect();
+    tokio::time::timeout(TIMEOUT, messages)
+        .await
+        .expect("Messages should arrive")
+}
+
+/// # Test: Subscriptions Drop the Oldest Events at Capacity
+///
+/// ## Steps:
+/// 1. Subscribe to the events of the process
+/// 2. Broadcast more messages than the subscription holds, without reading
+/// 3. Read what arrived
+///
+/// ## Assertions:
+/// - Nothing is counted as dropped before reading
+/// - The oldest messages are dropped and counted, the rest arrive in order
+#[tokio::test]
+async fn test_subscriptions_drop_the_oldest_events_at_capacity() {
+    let mut events = IrohClient::subscribe(None);
+    for i in 0..EVENT_CAPACITY + OVERFLOW {
+        IrohClient::broadcast_message(
+            ChatMessageBuilder::new(i.to_string())
+                .content(i.to_string())
+                .build(),
+        );
+    }
+    assert_eq!(
+        events.dropped(),
+        0,
+        "Nothing should be dropped before reading"
+    );
+
+    let received = receive(&mut events, EVENT_CAPACITY).await;
+
+    assert_eq!(
+        events.dropped(),
+        OVERFLOW as u64,
+        "The messages beyond capacity should be counted as dropped"
+    );
+    let expected: Vec<String> = (OVERFLOW..EVENT_CAPACITY + OVERFLOW)
+        .map(|i| i.to_string())
+        .collect();
+    assert_eq!(
+        received, expected,
+        "The newest messages should arrive in order"
+    );
+}
+
+/// # Test: Memory Transports Drop the Oldest Events at Capacity
+///
+/// ## Steps:
+/// 1. Put two members in memory on a topic, the creator subscribed
+/// 2. Send more messages than the subscription holds from the other member
+/// 3. Read what arrived
+///
+/// ## Assertions:
+/// - The oldest messages are dropped and counted, the rest arrive in order
+#[tokio::test]
+async fn test_memory_transports_drop_the_oldest_events_at_capacity() {
+    let network = MemoryNetwork::new();
+    let creator = network.transport(1);
+    let joiner = network.transport(2);
+    let topic = creator
+        .create("storm".to_string())
+        .await
+        .expect("Topic should be created");
+    joiner
+        .join(topic.ticket)
+        .await
+        .expect("Topic should be joined");
+    let mut events = creator.events();
+
+    for i in 0..EVENT_CAPACITY + OVERFLOW {
+        joiner
+            .send(&topic.hash, "bot".to_string(), i.to_string(), i as u64)
+            .await
+            .expect("Message should be sent");
+    }
+    let received = receive(&mut events, EVENT_CAPACITY).await;
+
+    assert_eq!(
+        events.dropped(),
+        OVERFLOW as u64,
+        "The messages beyond capacity should be counted as dropped"
+    );
+    let expected: Vec<String> = (OVERFLOW..EVENT_CAPACITY + OVERFLOW)
+        .map(|i| i.to_string())
+        .collect();
+    assert_eq!(
+        received, expected,
+        "The newest messages should arrive in order"
+    );
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "blobs")]
+
+use iroh::protocol::Router;
+use iroh::Endpoint;
+use iroh_lab_core::blobs::{self, BlobHash, BlobStore, BlobsProtocol, BLOBS_ALPN, MAX_BLOB_SIZE};
+
+/// # Test: Stored Blobs Are Checked Against Their Hash
+///
+/// ## Steps:
+/// 1. Store a blob and read it back
+/// 2. Corrupt it on disk
+///
+/// ## Assertions:
+/// - An intact blob reads back unchanged
+/// - A corrupted blob reads as missing
+/// - Blobs above the size limit are refused
+#[test]
+fn test_stored_blobs_are_checked_against_their_hash() {
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let store = BlobStore::new(dir.path());
+
+    let hash = store.put(b"avatar image").expect("Blob should be stored");
+    assert_eq!(
+        hash,
+        BlobHash::of(b"avatar image"),
+        "Blob should be named by its hash"
+    );
+    assert_eq!(
+        store.get(&hash).expect("Blob should be read"),
+        Some(b"avatar image".to_vec()),
+        "Stored blob should read back unchanged"
+    );
+
+    std::fs::write(dir.path().join(hash.to_string()), b"something else")
+        .expect("Blob should be overwritten");
+    assert_eq!(
+        store.get(&hash).expect("Blob should be read"),
+        None,
+        "Corrupted blob should read as missing"
+    );
+
+    assert!(
+        store.put(&vec![0; MAX_BLOB_SIZE + 1]).is_err(),
+        "Oversized blob should be refused"
+    );
+}
+
+/// # Test: Blobs Are Fetched From the Announcing Node
+///
+/// ## Steps:
+/// 1. Start a node serving a stored blob
+/// 2. Fetch it, and a blob it does not have, from a second node
+///
+/// ## Assertions:
+/// - The fetched blob matches and is cached by the second node
+/// - Fetching a blob the node does not have fails
+#[tokio::test]
+async fn test_blobs_are_fetched_from_the_announcing_node() {
+    let serving_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let serving_store = BlobStore::new(serving_dir.path());
+    let hash = serving_store
+        .put(b"avatar image")
+        .expect("Blob should be stored");
+
+    let serving = Endpoint::builder()
+        .bind()
+        .await
+        .expect("Serving endpoint should bind");
+    let router = Router::builder(serving.clone())
+        .accept(BLOBS_ALPN, BlobsProtocol::new(serving_store))
+        .spawn()
+        .await
+        .expect("Router should start");
+
+    let fetching = Endpoint::builder()
+        .bind()
+        .await
+        .expect("Fetching endpoint should bind");
+    fetching
+        .add_node_addr(
+            serving
+                .node_addr()
+                .await
+                .expect("Serving address should be known"),
+        )
+        .expect("Serving address should be added");
+    let fetching_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let fetching_store = BlobStore::new(fetching_dir.path());
+
+    let content = blobs::fetch(&fetching, &fetching_store, serving.node_id(), hash)
+        .await
+        .expect("Blob should be fetched");
+    assert_eq!(content, b"avatar image", "Fetched blob should match");
+    assert!(
+        fetching_store.contains(&hash),
+        "Fetched blob should be cached"
+    );
+
+    let missing = BlobHash::of(b"never stored");
+    assert!(
+        blobs::fetch(&fetching, &fetching_store, serving.node_id(), missing)
+            .await
+            .is_err(),
+        "Fetching a missing blob should fail"
+    );
+
+    router.shutdown().await.expect("Router should shut down");
+    fetching.close().await;
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "bridges")]
+
+use iroh_lab_core::bridge::{self, Bridges, MAX_FILE_SIZE};
+
+/// # Test: Dropped Files Are Posted Once Until They Change
+///
+/// ## Steps:
+/// 1. Drop files into a shared folder, including a hidden and a large one
+/// 2. Scan the folder, record the files found, and scan again
+/// 3. Change a file, and forget another
+///
+/// ## Assertions:
+/// - New files are found, hidden, large files and folders are skipped
+/// - Recorded files are not found again until they change
+/// - Forgotten files are found again
+/// - Files of other topics do not count
+#[test]
+fn test_dropped_files_are_posted_once_until_they_change() {
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let folder = dir.path();
+    std::fs::write(folder.join("notes.txt"), "first").unwrap();
+    std::fs::write(folder.join("plan.md"), "plan").unwrap();
+    std::fs::write(folder.join(".hidden"), "secret").unwrap();
+    std::fs::write(folder.join("large.bin"), vec![0; MAX_FILE_SIZE + 1]).unwrap();
+    std::fs::create_dir(folder.join("nested")).unwrap();
+    let mut bridges = Bridges::default();
+
+    let dropped = bridges.scan("topic", folder).expect("Folder should scan");
+    assert_eq!(
+        dropped
+            .iter()
+            .map(|file| file.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["notes.txt", "plan.md"],
+        "Only plain files within the size limit should be found"
+    );
+    for file in &dropped {
+        bridges.record("topic", &file.name, &file.content);
+    }
+    assert!(
+        bridges.scan("topic", folder).unwrap().is_empty(),
+        "Recorded files should not be found again"
+    );
+    assert_eq!(
+        bridges.scan("other", folder).unwrap().len(),
+        2,
+        "Files recorded for another topic should be found"
+    );
+
+    std::fs::write(folder.join("notes.txt"), "second").unwrap();
+    bridges.forget("topic", "plan.md");
+    let dropped = bridges.scan("topic", folder).unwrap();
+    assert_eq!(
+        dropped
+            .iter()
+            .map(|file| (file.name.as_str(), file.content.as_slice()))
+            .collect::<Vec<_>>(),
+        vec![("notes.txt", &b"second"[..]), ("plan.md", &b"plan"[..])],
+        "Changed and forgotten files should be found again"
+    );
+    assert_eq!(bridges.count("topic"), 1, "Forgotten file should not count");
+}
+
+/// # Test: Attachments Are Only Saved Inside the Shared Folder
+///
+/// ## Steps:
+/// 1. Save attachments with plain names and names reaching elsewhere
+/// 2. Scan the folder, then save and load the bridges
+///
+/// ## Assertions:
+/// - Plain names are saved into the folder and not posted back
+/// - Names with paths, hidden names and empty names are refused
+/// - The bridged files are kept between runs
+#[test]
+fn test_attachments_are_only_saved_inside_the_shared_folder() {
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let folder = dir.path().join("shared");
+    let mut bridges = Bridges::default();
+
+    let path = bridges
+        .save("topic", &folder, "report.txt", b"content")
+        .expect("Plain name should be saved");
+    assert_eq!(
+        path,
+        folder.join("report.txt"),
+        "File should be in the folder"
+    );
+    assert_eq!(
+        std::fs::read(&path).unwrap(),
+        b"content",
+        "File should hold the attachment"
+    );
+    assert!(
+        bridges.scan("topic", &folder).unwrap().is_empty(),
+        "Saved file should not be posted back"
+    );
+
+    for name in [
+        "../escape.txt",
+        "nested/file.txt",
+        "..",
+        ".bashrc",
+        "",
+        "C:evil",
+    ] {
+        assert!(
+            bridge::safe_name(name).is_none(),
+            "Name {:?} should be refused",
+            name
+        );
+        assert!(
+            bridges.save("topic", &folder, name, b"x").is_err(),
+            "Attachment named {:?} should not be saved",
+            name
+        );
+    }
+    assert!(
+        !dir.path().join("escape.txt").exists(),
+        "Nothing should be written outside the folder"
+    );
+
+    let state = dir.path().join("iroh-lab").join("bridges.json");
+    bridges.save_to(&state).expect("Bridges should save");
+    assert_eq!(
+        Bridges::load_from(&state).expect("Bridges should load"),
+        bridges,
+        "Loaded bridges should equal the saved ones"
+    );
+}
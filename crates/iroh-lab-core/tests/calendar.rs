@@ -0,0 +1,260 @@
+use chrono::{DateTime, Duration, Utc};
+use iroh::{NodeId, SecretKey};
+use iroh_lab_core::calendar::{self, Calendar, RsvpResponse};
+use iroh_lab_core::client::{CalendarEvent, ChatMessage, Rsvp};
+use iroh_lab_core::testing::ChatMessageBuilder;
+
+fn node(seed: u8) -> NodeId {
+    SecretKey::from_bytes(&[seed; 32]).public()
+}
+
+fn starts_at() -> DateTime<Utc> {
+    "2024-05-01T18:30:00Z".parse().unwrap()
+}
+
+fn event(title: &str, location: &str) -> CalendarEvent {
+    CalendarEvent {
+        topic_hash: "topic".to_string(),
+        message_id: "announcement".to_string(),
+        title: title.to_string(),
+        starts_at: starts_at(),
+        ends_at: starts_at() + Duration::hours(2),
+        location: location.to_string(),
+    }
+}
+
+fn announcement(sender: Option<NodeId>) -> ChatMessage {
+    let message = ChatMessageBuilder::new("announcement")
+        .content("📅 Meetup")
+        .topic("topic")
+        .sequence(0)
+        .lamport(0);
+    match sender {
+        Some(sender) => message.sent_by(sender).build(),
+        None => message.build(),
+    }
+}
+
+fn rsvp(response: RsvpResponse, lamport: u64) -> Rsvp {
+    Rsvp {
+        topic_hash: "topic".to_string(),
+        message_id: "announcement".to_string(),
+        response,
+        lamport,
+    }
+}
+
+/// # Test: Events Belong to the Node That Announced Them
+///
+/// ## Steps:
+/// 1. Attach an event to a message from another node, then from its sender
+/// 2. Look up the event of the message, signed and unsigned
+///
+/// ## Assertions:
+/// - Only the event of the message's sender is shown
+/// - Unsigned messages show no event
+/// - A node attaches only one event to a message
+#[test]
+fn test_events_belong_to_the_node_that_announced_them() {
+    let (alice, mallory) = (node(1), node(2));
+    let mut calendar = Calendar::default();
+
+    assert!(
+        calendar.add(event("Fake meetup", ""), mallory),
+        "Event of another node should be kept"
+    );
+    assert!(
+        calendar.event_of(&announcement(Some(alice))).is_none(),
+        "Event of another node should not be shown"
+    );
+    assert!(
+        calendar.add(event("Meetup", "Hall"), alice),
+        "Event of the sender should be added"
+    );
+    assert!(
+        !calendar.add(event("Changed meetup", "Hall"), alice),
+        "Second event of the sender should be ignored"
+    );
+    assert_eq!(
+        calendar
+            .event_of(&announcement(Some(alice)))
+            .map(|event| event.title.as_str()),
+        Some("Meetup"),
+        "Event of the sender should be shown"
+    );
+    assert!(
+        calendar.event_of(&announcement(None)).is_none(),
+        "Unsigned message should show no event"
+    );
+}
+
+/// # Test: The Latest Answer of Each Member Counts
+///
+/// ## Steps:
+/// 1. Answer an event before it arrives, then change the answer
+/// 2. Apply an answer older than the last one
+/// 3. Save and load the calendar
+///
+/// ## Assertions:
+/// - Answers are kept before the event arrives
+/// - Later answers replace earlier ones, older ones change nothing
+/// - Answers of different members are counted apart
+/// - The calendar is kept between runs
+#[test]
+fn test_the_latest_answer_of_each_member_counts() {
+    let (alice, bob) = (node(1), node(2));
+    let mut calendar = Calendar::default();
+
+    assert!(
+        calendar.respond(&rsvp(RsvpResponse::Accept, 1), bob),
+        "Answer should be recorded before the event"
+    );
+    calendar.add(event("Meetup", ""), alice);
+    assert!(
+        calendar.respond(&rsvp(RsvpResponse::Decline, 3), bob),
+        "Later answer should change the answer"
+    );
+    assert!(
+        !calendar.respond(&rsvp(RsvpResponse::Accept, 2), bob),
+        "Older answer should change nothing"
+    );
+    calendar.respond(&rsvp(RsvpResponse::Accept, 1), alice);
+    assert_eq!(
+        calendar.response_of("topic", "announcement", bob),
+        Some(RsvpResponse::Decline),
+        "Latest answer should count"
+    );
+    assert_eq!(
+        calendar.responses("topic", "announcement").len(),
+        2,
+        "Every member should be counted once"
+    );
+
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let path = dir.path().join("iroh-lab").join("calendar.json");
+    calendar.save_to(&path).expect("Calendar should save");
+    assert_eq!(
+        Calendar::load_from(&path).expect("Calendar should load"),
+        calendar,
+        "Loaded calendar should equal the saved one"
+    );
+}
+
+/// # Test: Events Export to iCalendar Files
+///
+/// ## Steps:
+/// 1. Export an event with special characters and a long title
+/// 2. Parse typed times, and validate event times
+///
+/// ## Assertions:
+/// - The file holds the event in UTC, with escaped text
+/// - No line exceeds 75 bytes, and lines end in CRLF
+/// - Typed times parse, and invalid ones are rejected
+/// - Events must end after they start and have a title
+#[test]
+fn test_events_export_to_icalendar_files() {
+    let long_title = format!("Planning; budget, roadmap {}", "and more ".repeat(10));
+    let ics = calendar::to_ics(&event(&long_title, "Room 1, 2nd floor"), starts_at());
+
+    assert!(
+        ics.starts_with("BEGIN:VCALENDAR\r\n"),
+        "File should be a calendar"
+    );
+    assert!(
+        ics.contains("DTSTART:20240501T183000Z\r\n"),
+        "Start should be in UTC"
+    );
+    assert!(
+        ics.contains("DTEND:20240501T203000Z\r\n"),
+        "End should be in UTC"
+    );
+    assert!(
+        ics.contains("SUMMARY:Planning\\; budget\\, roadmap"),
+        "Title should be escaped"
+    );
+    assert!(
+        ics.contains("LOCATION:Room 1\\, 2nd floor\r\n"),
+        "Location should be escaped"
+    );
+    assert!(
+        ics.split("\r\n").all(|line| line.len() <= 75),
+        "Lines should be folded"
+    );
+    assert!(
+        !ics.replace("\r\n", "").contains('\n'),
+        "Lines should end in CRLF"
+    );
+
+    assert!(
+        calendar::parse_time(" 2024-05-01 18:30 ").is_ok(),
+        "Typed time should parse"
+    );
+    assert!(
+        calendar::parse_time("tomorrow").is_err(),
+        "Invalid time should be rejected"
+    );
+    assert!(
+        calendar::validate("Meetup", starts_at(), starts_at()).is_err(),
+        "Event ending as it starts should be rejected"
+    );
+    assert!(
+        calendar::validate(" ", starts_at(), starts_at() + Duration::hours(1)).is_err(),
+        "Event without a title should be rejected"
+    );
+}
+
+/// # Test: Upcoming Events of a Topic Export as a Feed
+///
+/// ## Steps:
+/// 1. Announce a past event, two upcoming ones, and one from another node
+/// 2. Export the upcoming events as a feed
+///
+/// ## Assertions:
+/// - Only genuine events that did not end are upcoming, the earliest first
+/// - The feed names the calendar and holds every upcoming event
+#[test]
+fn test_upcoming_events_of_a_topic_export_as_a_feed() {
+    let (alice, mallory) = (node(1), node(2));
+    let mut calendar = Calendar::default();
+    let mut messages = Vec::new();
+    for (id, title, hours, organizer) in [
+        ("past", "Kickoff", -48, alice),
+        ("later", "Retro", 48, alice),
+        ("sooner", "Planning", 24, alice),
+        ("fake", "Fake meetup", 12, mallory),
+    ] {
+        let mut event = event(title, "");
+        event.message_id = id.to_string();
+        event.starts_at = starts_at() + Duration::hours(hours);
+        event.ends_at = event.starts_at + Duration::hours(1);
+        calendar.add(event, organizer);
+        let mut message = announcement(Some(alice));
+        message.id = id.to_string();
+        messages.push(message);
+    }
+
+    let upcoming = calendar.upcoming(&messages, starts_at());
+    assert_eq!(
+        upcoming
+            .iter()
+            .map(|event| event.title.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Planning", "Retro"],
+        "Upcoming events should be the genuine ones to come, the earliest first"
+    );
+
+    let feed = calendar::to_ics_feed("Book club, Tuesdays", upcoming, starts_at());
+    assert!(
+        feed.contains("X-WR-CALNAME:Book club\\, Tuesdays\r\n"),
+        "Feed should name the calendar"
+    );
+    assert_eq!(
+        feed.matches("BEGIN:VEVENT").count(),
+        2,
+        "Feed should hold every upcoming event"
+    );
+    assert!(
+        feed.ends_with("END:VCALENDAR\r\n"),
+        "Feed should be a complete calendar"
+    );
+}
@@ -0,0 +1,276 @@
+use iroh::SecretKey;
+use iroh_lab_core::chunk::{
+    self, Reassembler, MAX_BUFFERED_BYTES, MAX_FRAGMENTS, MAX_FRAGMENT_DATA, MAX_FRAME_SIZE,
+    MAX_PENDING_FRAMES,
+};
+use iroh_lab_core::config::MAX_MESSAGE_CHARS;
+use iroh_lab_core::testing::TopicFixture;
+use iroh_lab_core::wire::{self, Compression, Payload};
+use std::time::{Duration, Instant};
+
+fn large_frame(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+/// # Test: Small Frames Are Sent Whole
+///
+/// This test verifies that frames fitting into a gossip message are neither
+/// split nor changed, so peers without fragment support can still read them.
+///
+/// ## Assertions:
+/// - A small frame is returned as the only fragment, unchanged
+/// - The reassembler passes it through immediately
+#[test]
+fn test_small_frames_are_sent_whole() {
+    let frame = large_frame(100);
+
+    let fragments = chunk::split(frame.clone()).expect("Failed to split frame");
+    assert_eq!(fragments, vec![frame.clone()], "Frame should be unchanged");
+
+    let mut reassembler = Reassembler::default();
+    let reassembled = reassembler
+        .accept(&fragments[0], Instant::now())
+        .expect("Failed to accept frame");
+    assert_eq!(reassembled, Some(frame), "Frame should pass through");
+}
+
+/// # Test: Large Frames Round Trip Out Of Order
+///
+/// This test verifies that a frame larger than a gossip message is split into
+/// fragments that fit, and reassembled no matter the order they arrive in.
+///
+/// ## Steps:
+/// 1. Split a frame three times the fragment size
+/// 2. Feed the fragments to a reassembler in reverse order, one twice
+///
+/// ## Assertions:
+/// - The frame is split into several fragments, each below the gossip limit
+/// - Nothing is returned until the last fragment arrives
+/// - The reassembled frame matches the original
+#[test]
+fn test_large_frames_round_trip_out_of_order() {
+    let frame = large_frame(MAX_FRAGMENT_DATA * 3 + 17);
+
+    let fragments = chunk::split(frame.clone()).expect("Failed to split frame");
+    assert_eq!(fragments.len(), 4, "Frame should be split into 4 fragments");
+    for fragment in &fragments {
+        assert!(
+            fragment.len() < iroh_gossip::proto::topic::DEFAULT_MAX_MESSAGE_SIZE,
+            "Fragment should fit into a gossip message"
+        );
+    }
+
+    let mut reassembler = Reassembler::default();
+    let now = Instant::now();
+    for fragment in fragments[1..].iter().rev() {
+        assert_eq!(
+            reassembler.accept(fragment, now).expect("Failed to accept"),
+            None,
+            "Frame should not be complete yet"
+        );
+    }
+    assert_eq!(
+        reassembler
+            .accept(&fragments[3], now)
+            .expect("Failed to accept"),
+        None,
+        "Duplicate fragment should not complete the frame"
+    );
+
+    let reassembled = reassembler
+        .accept(&fragments[0], now)
+        .expect("Failed to accept fragment");
+    assert_eq!(reassembled, Some(frame), "Frame should be reassembled");
+    assert_eq!(reassembler.pending(), 0, "No frame should be pending");
+}
+
+/// # Test: Large Chat Messages Survive Chunking
+///
+/// This test verifies that a long chat message can be encoded, split,
+/// reassembled and decoded again.
+///
+/// ## Assertions:
+/// - The decoded message carries the full content of the original
+#[test]
+fn test_large_chat_messages_survive_chunking() {
+    let topic = TopicFixture::new("chunk-test-topic");
+    let message = topic
+        .message("large")
+        .content("A very long paste. ".repeat(1000))
+        .build();
+
+    let frame = topic
+        .encode(
+            &Payload::Chat(message.clone()),
+            &SecretKey::generate(rand::rngs::OsRng),
+        )
+        .expect("Failed to encode payload");
+    let mut reassembler = Reassembler::default();
+    let mut reassembled = None;
+    for fragment in chunk::split(frame).expect("Failed to split frame") {
+        reassembled = reassembler
+            .accept(&fragment, Instant::now())
+            .expect("Failed to accept fragment");
+    }
+
+    let bytes = reassembled.expect("Frame should be complete");
+    let envelope = wire::decode(&bytes, &topic.key).expect("Failed to decode frame");
+    let Payload::Chat(decoded) = envelope.payload else {
+        panic!("Expected a chat payload");
+    };
+    assert_eq!(decoded.content, message.content, "Content should be intact");
+}
+
+/// # Test: Incomplete Sets Expire
+///
+/// This test verifies that fragments of a frame that never completes are
+/// dropped after the reassembly timeout.
+///
+/// ## Assertions:
+/// - Nothing expires before the timeout
+/// - The incomplete frame is reported as expired after the timeout
+#[test]
+fn test_incomplete_sets_expire() {
+    let timeout = Duration::from_secs(5);
+    let fragments = chunk::split(large_frame(MAX_FRAGMENT_DATA * 2)).expect("Failed to split");
+
+    let mut reassembler = Reassembler::new(timeout);
+    let start = Instant::now();
+    reassembler
+        .accept(&fragments[0], start)
+        .expect("Failed to accept fragment");
+
+    assert_eq!(
+        reassembler.expire(start + Duration::from_secs(1)),
+        0,
+        "Nothing should expire before the timeout"
+    );
+    assert_eq!(
+        reassembler.expire(start + timeout),
+        1,
+        "Incomplete frame should expire"
+    );
+    assert_eq!(reassembler.pending(), 0, "No frame should be pending");
+}
+
+/// # Test: Incomplete Sets Are Bounded
+///
+/// This test verifies that peers starting sets that never complete cannot
+/// make the reassembler hold an unbounded number of frames or bytes.
+///
+/// ## Steps:
+/// 1. Start more frames than may be pending, one fragment each
+/// 2. Start a few frames of the largest size, leaving out their last
+///    fragment, until they take up more than may be buffered
+/// 3. Send the last fragments
+///
+/// ## Assertions:
+/// - At most the maximum number of frames are pending
+/// - At most the maximum number of bytes are buffered
+/// - The oldest frames are dropped, and can no longer complete
+/// - The newest frame still completes
+#[test]
+fn test_incomplete_sets_are_bounded() {
+    let mut reassembler = Reassembler::default();
+    let start = Instant::now();
+    for i in 0..=MAX_PENDING_FRAMES {
+        let fragments = chunk::split(large_frame(MAX_FRAGMENT_DATA * 2)).expect("Failed to split");
+        reassembler
+            .accept(&fragments[0], start + Duration::from_millis(i as u64))
+            .expect("Failed to accept fragment");
+    }
+    assert_eq!(
+        reassembler.pending(),
+        MAX_PENDING_FRAMES,
+        "At most the maximum number of frames should be pending"
+    );
+
+    let mut reassembler = Reassembler::default();
+    let frames = MAX_BUFFERED_BYTES / MAX_FRAME_SIZE + 1;
+    let mut last_fragments = Vec::new();
+    for i in 0..frames {
+        let mut fragments = chunk::split(large_frame(MAX_FRAME_SIZE)).expect("Failed to split");
+        last_fragments.push(fragments.pop().expect("Frame should have fragments"));
+        for fragment in &fragments {
+            reassembler
+                .accept(fragment, start + Duration::from_millis(i as u64))
+                .expect("Failed to accept fragment");
+        }
+    }
+    assert!(
+        reassembler.buffered() <= MAX_BUFFERED_BYTES,
+        "At most the maximum number of bytes should be buffered"
+    );
+    assert_eq!(
+        reassembler.pending(),
+        frames - 1,
+        "The oldest frame should be dropped"
+    );
+
+    let later = start + Duration::from_secs(1);
+    assert_eq!(
+        reassembler
+            .accept(&last_fragments[0], later)
+            .expect("Failed to accept fragment"),
+        None,
+        "The dropped frame should not complete"
+    );
+    assert_eq!(
+        reassembler
+            .accept(&last_fragments[frames - 1], later)
+            .expect("Failed to accept fragment")
+            .map(|frame| frame.len()),
+        Some(MAX_FRAME_SIZE),
+        "The newest frame should complete"
+    );
+}
+
+/// # Test: Oversized And Invalid Fragments Are Rejected
+///
+/// ## Assertions:
+/// - Frames needing more than the maximum number of fragments are not split
+/// - Garbage starting with the fragment tag is rejected by the reassembler
+#[test]
+fn test_oversized_and_invalid_fragments_are_rejected() {
+    let too_large = large_frame(MAX_FRAGMENT_DATA * MAX_FRAGMENTS as usize + 1);
+    assert!(
+        chunk::split(too_large).is_err(),
+        "Oversized frame should be rejected"
+    );
+
+    let mut reassembler = Reassembler::default();
+    assert!(
+        reassembler
+            .accept(&[chunk::FRAGMENT_TAG, 1, 2], Instant::now())
+            .is_err(),
+        "Invalid fragment should be rejected"
+    );
+}
+
+/// # Test: The Longest Message Always Fits Into a Frame
+///
+/// This test verifies that a message at the hard limit, made of characters
+/// taking four bytes each, can be sent uncompressed.
+///
+/// ## Assertions:
+/// - The encoded message splits into fragments
+#[test]
+fn test_the_longest_message_always_fits_into_a_frame() {
+    let topic = TopicFixture::new("chunk-test-topic");
+    let message = topic
+        .message("longest")
+        .content("😀".repeat(MAX_MESSAGE_CHARS))
+        .build();
+
+    let frame = wire::encode_with(
+        &Payload::Chat(message),
+        &SecretKey::generate(rand::rngs::OsRng),
+        &topic.key,
+        Compression::Never,
+    )
+    .expect("Failed to encode payload");
+    assert!(
+        chunk::split(frame).is_ok(),
+        "Longest message should fit into a frame"
+    );
+}
@@ -0,0 +1,449 @@
+use iroh_lab_core::client::IrohClient;
+use iroh_lab_core::config::{Binding, RelayMode};
+use iroh_lab_core::delivery::DeliveryState;
+use iroh_lab_core::testing::{self, ClientPairFixture};
+use iroh_lab_core::ticket::Ticket;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+/// # Test: Create Random Topic
+///
+/// This test verifies that a client can create a new randomly named topic using Iroh's
+/// decentralized document system.
+///
+/// ## Steps:
+/// 1. Initialize a new client with an Iroh node
+/// 2. Initialize the network
+/// 3. Create a topic with a specified name
+///
+/// ## Assertions:
+/// - The node ID should not be empty
+/// - The created topic name should match the requested name
+/// - The ticket and hash should not be empty
+/// - The ticket should be a valid Iroh document sharing ticket
+/// - The topic should be stored in the client's subscribed topics
+/// - The stored hash should match the returned hash
+#[test]
+fn test_create_random_topic() {
+    // Create a new runtime for async tests
+    let rt = Runtime::new().unwrap();
+
+    rt.block_on(async {
+        // Initialize a new client
+        let client = IrohClient::new();
+
+        // Initialize the network
+        let node_id = client
+            .initialize_network()
+            .await
+            .expect("Failed to initialize network");
+        assert!(!node_id.is_empty(), "Node ID should not be empty");
+
+        // Create a random topic
+        let topic_name = "test-topic".to_string();
+        let (created_name, ticket, hash) = client
+            .create_topic(topic_name.clone())
+            .await
+            .expect("Failed to create topic");
+
+        // Verify the topic was created successfully
+        assert_eq!(created_name, topic_name, "Topic name should match");
+        assert!(!ticket.is_empty(), "Ticket should not be empty");
+        assert!(!hash.is_empty(), "Topic hash should not be empty");
+        assert!(
+            ticket.starts_with("ticket-"),
+            "Ticket should start with 'ticket-'"
+        );
+
+        // Verify the topic is stored in the client's subscribed topics
+        assert!(
+            client.session().subscribed_topics.contains_key(&topic_name),
+            "Topic should be in subscribed topics"
+        );
+        assert_eq!(
+            client.session().subscribed_topics.get(&topic_name).unwrap(),
+            &hash,
+            "Topic hash should match"
+        );
+    });
+}
+
+/// # Test: Create Topic and Send Message
+///
+/// This test verifies that a client can create a topic and send a message to it
+/// using Iroh's peer-to-peer networking.
+///
+/// ## Steps:
+/// 1. Initialize a new client with an Iroh node
+/// 2. Create a topic
+/// 3. Send a message to the topic
+///
+/// ## Assertions:
+/// - The message should be successfully sent to the Iroh document
+/// - The message should be properly serialized and stored in the document
+/// - The message should be queued, as nobody else is on the topic
+#[test]
+fn test_create_topic_and_send_message() {
+    let rt = Runtime::new().unwrap();
+
+    rt.block_on(async {
+        // Initialize message channel - this is a simplified test that doesn't rely on
+        // the actual message receiving functionality, which is tested in the next test
+        let client = testing::initialized_client()
+            .await
+            .expect("Failed to initialize network");
+
+        // Create a topic
+        let topic_name = "message-test-topic".to_string();
+        let (_created_name, _ticket, _hash) = client
+            .create_topic(topic_name.clone())
+            .await
+            .expect("Failed to create topic");
+
+        // Send a message - we'll just verify it doesn't error
+        let username = "test-user".to_string();
+        let message_content = "Hello, world!".to_string();
+        let sequence = 1;
+
+        let result = client
+            .send_message(username.clone(), message_content.clone(), sequence)
+            .await;
+        assert!(result.is_ok(), "Message should be sent successfully");
+
+        // Nobody else is on the topic to send it to yet
+        let message = result.unwrap();
+        let report = client
+            .delivery_report(&message.topic_hash, &message.id)
+            .expect("Message should be reported");
+        assert_eq!(
+            report.state,
+            DeliveryState::Queued,
+            "Message should wait for a neighbor"
+        );
+    });
+}
+
+/// # Test: Two Clients Communication
+///
+/// This test verifies that two different client instances can communicate with each other
+/// using Iroh's peer-to-peer networking. It creates a scenario where one client creates a topic,
+/// another client joins it, and they exchange messages through Iroh documents.
+///
+/// ## Steps:
+/// 1. Initialize client A with an Iroh node
+/// 2. Create a topic with client A
+/// 3. Initialize client B with an Iroh node
+/// 4. Client B joins the topic created by client A
+/// 5. Client A sends a message to the shared document
+/// 6. Client B sends a message to the shared document
+///
+/// ## Assertions:
+/// - Both clients can successfully join the same topic
+/// - Messages from both clients can be sent successfully to the shared document
+/// - Client B receives the message of client A
+/// - The topic hashes are non-empty and valid Iroh document IDs
+#[test]
+fn test_two_clients_communication() {
+    let rt = Runtime::new().unwrap();
+
+    rt.block_on(async {
+        // Client A creates a topic, which client B joins
+        let pair = ClientPairFixture::new("two-clients-test-topic")
+            .await
+            .expect("Failed to connect the clients");
+        let (client_a, client_b) = (&pair.creator, &pair.joiner);
+
+        // Note: Due to the implementation of join_topic, hash_a and hash_b will be different
+        // hash_a is the full hash (topic_name-uuid), while hash_b is just the uuid part
+        // We'll verify they're both non-empty instead
+        assert!(
+            !pair.creator_hash.is_empty(),
+            "Topic hash A should not be empty"
+        );
+        assert!(
+            !pair.joiner_hash.is_empty(),
+            "Topic hash B should not be empty"
+        );
+
+        // Client A sends a message, which client B receives
+        let mut events_b = IrohClient::subscribe(Some(&pair.joiner_hash));
+        let username_a = "user-a".to_string();
+        let message_a = "Hello from client A".to_string();
+        let sequence_a = 1;
+
+        let result_a = client_a
+            .send_message(username_a.clone(), message_a.clone(), sequence_a)
+            .await;
+        assert!(
+            result_a.is_ok(),
+            "Message from client A should be sent successfully"
+        );
+        let received = testing::wait_for_message(
+            &mut events_b,
+            &result_a.unwrap().id,
+            Duration::from_secs(30),
+        )
+        .await;
+        assert!(
+            received.is_some_and(|message| message.content == message_a),
+            "Client B should receive the message from client A"
+        );
+
+        // Client B sends a message
+        let username_b = "user-b".to_string();
+        let message_b = "Hello from client B".to_string();
+        let sequence_b = 2;
+
+        let result_b = client_b
+            .send_message(username_b.clone(), message_b.clone(), sequence_b)
+            .await;
+        assert!(
+            result_b.is_ok(),
+            "Message from client B should be sent successfully"
+        );
+    });
+}
+
+/// # Test: Relay Only Mode Has No Direct Addresses
+///
+/// ## Steps:
+/// 1. Initialize a client forced to use relays
+///
+/// ## Assertions:
+/// - The mode set is reported
+/// - The only direct addresses are on the loopback interface, so other
+///   machines can only reach the node through a relay
+#[test]
+fn test_relay_only_mode_has_no_direct_addresses() {
+    let rt = Runtime::new().unwrap();
+
+    rt.block_on(async {
+        let mut client = IrohClient::new();
+        client.set_relay_mode(RelayMode::Forced);
+        client
+            .initialize_network()
+            .await
+            .expect("Failed to initialize network");
+
+        assert_eq!(
+            client.relay_mode(),
+            RelayMode::Forced,
+            "Mode should be reported"
+        );
+        let status = client.node_status().expect("Network should be initialized");
+        assert!(
+            status
+                .direct_addresses
+                .iter()
+                .all(|addr| addr.ip().is_loopback()),
+            "Direct addresses should all be on the loopback interface: {:?}",
+            status.direct_addresses
+        );
+    });
+}
+
+/// # Test: Endpoint Listens Where Configured
+///
+/// ## Steps:
+/// 1. Find a free port
+/// 2. Initialize the network listening on that port of the loopback
+///    interface
+///
+/// ## Assertions:
+/// - The endpoint's IPv4 socket is bound to the configured address
+#[test]
+fn test_endpoint_listens_where_configured() {
+    let rt = Runtime::new().unwrap();
+
+    rt.block_on(async {
+        let port = std::net::UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))
+            .and_then(|socket| socket.local_addr())
+            .expect("Failed to find a free port")
+            .port();
+        let mut client = IrohClient::new();
+        client.set_binding(Binding {
+            port,
+            interface_v4: Some(Ipv4Addr::LOCALHOST),
+            ..Binding::default()
+        });
+        client
+            .initialize_network()
+            .await
+            .expect("Failed to initialize network");
+
+        let status = client.node_status().expect("Network should be initialized");
+        assert_eq!(
+            status.bound_sockets.first(),
+            Some(&SocketAddr::from((Ipv4Addr::LOCALHOST, port))),
+            "IPv4 socket should be bound to the configured address"
+        );
+    });
+}
+
+/// # Test: Peers Can Be Added Manually
+///
+/// ## Steps:
+/// 1. Create a topic, and join it with a ticket naming no nodes, as if
+///    discovery had failed
+/// 2. Add the creator as a peer by its node address
+/// 3. Add our own node as a peer
+///
+/// ## Assertions:
+/// - The peer is tried on the topic joined
+/// - Our own node is refused
+#[test]
+fn test_peers_can_be_added_manually() {
+    let rt = Runtime::new().unwrap();
+
+    rt.block_on(async {
+        let creator = testing::initialized_client()
+            .await
+            .expect("Failed to initialize network");
+        let (_, ticket, _) = creator
+            .create_topic("manual-peer-topic".to_string())
+            .await
+            .expect("Failed to create topic");
+        let mut ticket: Ticket = ticket.parse().expect("Failed to parse ticket");
+        ticket.nodes.clear();
+
+        let joiner = testing::initialized_client()
+            .await
+            .expect("Failed to initialize network");
+        joiner
+            .join_topic(ticket.to_string())
+            .await
+            .expect("Failed to join topic");
+
+        let creator_addr = creator
+            .node_status()
+            .expect("Network should be initialized")
+            .node_addr();
+        let topics = joiner
+            .add_peer(creator_addr)
+            .await
+            .expect("Failed to add peer");
+        assert_eq!(topics, 1, "Peer should be tried on the topic joined");
+
+        let own_addr = joiner
+            .node_status()
+            .expect("Network should be initialized")
+            .node_addr();
+        assert!(
+            joiner.add_peer(own_addr).await.is_err(),
+            "Own node should be refused as a peer"
+        );
+    });
+}
+
+/// # Test: Shutdown Leaves Topics
+///
+/// ## Steps:
+/// 1. Create a topic and send a message nobody receives
+/// 2. Shut the client down from a clone
+///
+/// ## Assertions:
+/// - The client is on no topic anymore
+/// - Sending to the topic fails
+#[test]
+fn test_shutdown_leaves_topics() {
+    let rt = Runtime::new().unwrap();
+
+    rt.block_on(async {
+        let client = testing::initialized_client()
+            .await
+            .expect("Failed to initialize network");
+        client
+            .create_topic("shutdown-topic".to_string())
+            .await
+            .expect("Failed to create topic");
+        client
+            .send_message("test-user".to_string(), "Anybody?".to_string(), 1)
+            .await
+            .expect("Message should be queued");
+
+        client.clone().shutdown().await;
+
+        assert!(
+            client.topic_hashes().is_empty(),
+            "Client should be on no topic"
+        );
+        assert!(
+            client
+                .send_message("test-user".to_string(), "Hello?".to_string(), 2)
+                .await
+                .is_err(),
+            "Sending after shutdown should fail"
+        );
+    });
+}
+
+/// # Test: Clones Share the Session
+///
+/// ## Steps:
+/// 1. Watch the session of a client before initializing its network
+/// 2. Initialize the network and create two topics from clones
+/// 3. Switch back to the first topic from yet another clone
+///
+/// ## Assertions:
+/// - The watcher is told of the change
+/// - Every clone sees the node, both topics and the topic switched to
+/// - Initializing the network again fails
+#[test]
+fn test_clones_share_the_session() {
+    let rt = Runtime::new().unwrap();
+
+    rt.block_on(async {
+        let client = IrohClient::new();
+        let mut session = client.watch_session();
+
+        let node_id = client
+            .clone()
+            .initialize_network()
+            .await
+            .expect("Failed to initialize network");
+        let (_, _, first) = client
+            .clone()
+            .create_topic("first".to_string())
+            .await
+            .expect("Failed to create topic");
+        let (_, _, second) = client
+            .clone()
+            .create_topic("second".to_string())
+            .await
+            .expect("Failed to create topic");
+        client
+            .clone()
+            .switch_topic(&first)
+            .expect("Switching should succeed");
+
+        assert!(
+            session.has_changed().unwrap(),
+            "The watcher should be told of the change"
+        );
+        let seen = session.borrow_and_update().clone();
+        assert_eq!(seen, client.session(), "The watcher should see the session");
+        assert_eq!(seen.node_id, Some(node_id), "The node should be shared");
+        assert_eq!(
+            seen.topic_hash,
+            Some(first.clone()),
+            "The topic switched to should be active"
+        );
+        assert_eq!(
+            seen.topic_ticket,
+            client.ticket(&first),
+            "The ticket of the active topic should be shared"
+        );
+        assert_eq!(
+            seen.subscribed_topics.get("second"),
+            Some(&second),
+            "Both topics should be subscribed"
+        );
+        assert!(
+            client.initialize_network().await.is_err(),
+            "Initializing twice should fail"
+        );
+
+        client.shutdown().await;
+    });
+}
@@ -0,0 +1,107 @@
+use iroh_lab_core::clock::{LamportClock, MAX_REMOTE_TIMESTAMP};
+
+/// # Test: Local Events Advance the Clock
+///
+/// This test verifies that every local event gets a larger timestamp than the
+/// one before it.
+///
+/// ## Assertions:
+/// - A new clock starts at 0
+/// - Each tick returns the next timestamp
+#[test]
+fn test_local_events_advance_the_clock() {
+    let clock = LamportClock::new();
+    assert_eq!(clock.now(), 0, "A new clock should start at 0");
+
+    assert_eq!(clock.tick(), 1, "First tick should return 1");
+    assert_eq!(clock.tick(), 2, "Second tick should return 2");
+    assert_eq!(clock.now(), 2, "Clock should be at the last tick");
+}
+
+/// # Test: Observed Timestamps Order Later Events
+///
+/// This test verifies that a message sent after receiving another one is
+/// stamped later than it, even if the receiving clock was behind.
+///
+/// ## Steps:
+/// 1. Observe a timestamp ahead of the local clock
+/// 2. Observe a timestamp behind the local clock
+/// 3. Tick for a new local event
+///
+/// ## Assertions:
+/// - Observing a later timestamp moves the clock forward
+/// - Observing an earlier timestamp never moves the clock back
+/// - The next local event is stamped after the observed timestamp
+#[test]
+fn test_observed_timestamps_order_later_events() {
+    let clock = LamportClock::new();
+    clock.tick();
+
+    clock.observe(10);
+    assert_eq!(clock.now(), 10, "Clock should catch up with the peer");
+
+    clock.observe(3);
+    assert_eq!(clock.now(), 10, "Clock should never move back");
+
+    assert_eq!(clock.tick(), 11, "Next event should follow the peer's");
+}
+
+/// # Test: Clones Share the Clock
+///
+/// This test verifies that clones of a clock, like those held by clones of
+/// the client, advance together.
+///
+/// ## Assertions:
+/// - A tick on a clone is visible on the original
+#[test]
+fn test_clones_share_the_clock() {
+    let clock = LamportClock::new();
+    let clone = clock.clone();
+
+    clone.tick();
+    clone.observe(5);
+    assert_eq!(clock.now(), 5, "Original should see the clone's events");
+    assert_eq!(
+        clock.tick(),
+        6,
+        "Ticks should continue from the shared time"
+    );
+}
+
+/// # Test: Implausible Timestamps Are Ignored
+///
+/// This test verifies that a peer cannot push the clock towards its end, where
+/// the next local event would overflow it.
+///
+/// ## Steps:
+/// 1. Observe the largest plausible timestamp
+/// 2. Observe the largest possible timestamp
+/// 3. Tick for a new local event
+///
+/// ## Assertions:
+/// - The largest plausible timestamp is observed
+/// - The largest possible timestamp is refused and leaves the clock alone
+/// - The next local event follows the plausible timestamp
+#[test]
+fn test_implausible_timestamps_are_ignored() {
+    let clock = LamportClock::new();
+
+    assert!(
+        clock.observe(MAX_REMOTE_TIMESTAMP),
+        "Largest plausible timestamp should be observed"
+    );
+    assert!(
+        !clock.observe(u64::MAX),
+        "Largest possible timestamp should be refused"
+    );
+    assert_eq!(
+        clock.now(),
+        MAX_REMOTE_TIMESTAMP,
+        "Refused timestamp should leave the clock alone"
+    );
+    assert_eq!(
+        clock.tick(),
+        MAX_REMOTE_TIMESTAMP + 1,
+        "Next event should follow the plausible timestamp"
+    );
+}
@@ -0,0 +1,98 @@
+use iroh_lab_core::commands::{self, SlashCommand};
+
+/// # Test: Messages Starting With a Slash Are Commands
+///
+/// ## Steps:
+/// 1. Parse messages, commands with and without arguments, and unknown ones
+/// 2. Parse a message starting with two slashes
+///
+/// ## Assertions:
+/// - Messages are not commands
+/// - Commands are parsed with their arguments, ignoring the case of names
+/// - Missing arguments and unknown commands are errors
+/// - A second slash sends the message with a single one
+#[test]
+fn test_messages_starting_with_a_slash_are_commands() {
+    assert_eq!(
+        commands::parse("hello / world"),
+        None,
+        "Message should not be a command"
+    );
+    assert_eq!(
+        commands::parse("/nick  Alice Smith "),
+        Some(Ok(SlashCommand::Nick("Alice Smith".to_string()))),
+        "Name should be the rest of the command"
+    );
+    assert_eq!(
+        commands::parse("/ME waves"),
+        Some(Ok(SlashCommand::Me("waves".to_string()))),
+        "Command names should ignore case"
+    );
+    assert_eq!(
+        commands::parse("/leave"),
+        Some(Ok(SlashCommand::Leave)),
+        "Command without arguments should parse"
+    );
+    assert_eq!(
+        commands::parse("/mute bob"),
+        Some(Ok(SlashCommand::Mute("bob".to_string()))),
+        "Mute should name the member"
+    );
+    assert!(
+        matches!(commands::parse("/nick "), Some(Err(_))),
+        "Missing name should be an error"
+    );
+    assert!(
+        matches!(commands::parse("/dance"), Some(Err(_))),
+        "Unknown command should be an error"
+    );
+    assert_eq!(
+        commands::parse("//etc/hosts is the file"),
+        Some(Ok(SlashCommand::Say("/etc/hosts is the file".to_string()))),
+        "Second slash should send the message"
+    );
+}
+
+/// # Test: Typing a Command Lists the Matching Ones
+///
+/// ## Steps:
+/// 1. Type a slash, then the start of a command, then its argument
+/// 2. Type a message, and a message starting with two slashes
+///
+/// ## Assertions:
+/// - A slash alone lists every command
+/// - The start of a name lists the commands starting with it
+/// - Typing the argument keeps only the command typed
+/// - Messages list nothing
+#[test]
+fn test_typing_a_command_lists_the_matching_ones() {
+    assert_eq!(
+        commands::help("/").len(),
+        commands::COMMANDS.len(),
+        "Slash alone should list every command"
+    );
+    let usages = |input| {
+        commands::help(input)
+            .into_iter()
+            .map(|(usage, _)| usage)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        usages("/m"),
+        vec!["/me <action>", "/mute <member>"],
+        "Commands starting with the typed name should be listed"
+    );
+    assert_eq!(
+        usages("/me waves"),
+        vec!["/me <action>"],
+        "Only the typed command should be listed with its argument"
+    );
+    assert!(
+        commands::help("hello").is_empty(),
+        "Message should list nothing"
+    );
+    assert!(
+        commands::help("//path").is_empty(),
+        "Escaped slash should list nothing"
+    );
+}
@@ -0,0 +1,169 @@
+use iroh_lab_core::composer::{self, InputHistory, NameCompletion, HISTORY_LIMIT, LINE_BREAK};
+
+/// # Test: Line Breaks Show as Marks in the Composer
+///
+/// ## Steps:
+/// 1. Show a message of several lines in the composer
+/// 2. Read back the input, and input with pasted line breaks
+///
+/// ## Assertions:
+/// - Every line break shows as a mark, and reads back as a line break
+/// - Pasted line breaks of any platform become plain line breaks
+#[test]
+fn test_line_breaks_show_as_marks_in_the_composer() {
+    let message = "First line\nSecond line\n\nAfter a blank line";
+    let input = composer::to_input(message);
+    assert!(!input.contains('\n'), "Input should be a single line");
+    assert_eq!(
+        input.matches(LINE_BREAK).count(),
+        3,
+        "Every line break should show as a mark"
+    );
+    assert_eq!(
+        composer::from_input(&input),
+        message,
+        "Input should read back as the message"
+    );
+
+    assert_eq!(
+        composer::from_input("one\r\ntwo\rthree↵four"),
+        "one\ntwo\nthree\nfour",
+        "Pasted line breaks should become plain ones"
+    );
+}
+
+/// # Test: Sent Messages Are Recalled Like a Shell History
+///
+/// ## Steps:
+/// 1. Send messages on two topics, repeating the latest one
+/// 2. Go back through the history of one topic and forward again
+/// 3. Send more messages than the history keeps
+///
+/// ## Assertions:
+/// - Messages are recalled from the latest, and only those of the topic
+/// - Going forward past the latest message restores what was typed
+/// - Repeats and blank messages are kept once or not at all
+/// - Only the latest messages are kept
+#[test]
+fn test_sent_messages_are_recalled_like_a_shell_history() {
+    let mut history = InputHistory::default();
+    history.push("topic", "first");
+    history.push("other", "elsewhere");
+    history.push("topic", "second");
+    history.push("topic", "second");
+    history.push("topic", " ");
+
+    assert_eq!(
+        history.previous("topic", "draft").as_deref(),
+        Some("second"),
+        "Latest message should be recalled first"
+    );
+    assert_eq!(
+        history.previous("topic", "second").as_deref(),
+        Some("first"),
+        "Earlier message should be recalled next, without repeats"
+    );
+    assert_eq!(
+        history.previous("topic", "first").as_deref(),
+        Some("first"),
+        "Oldest message should stay recalled"
+    );
+    assert_eq!(
+        history.next("topic").as_deref(),
+        Some("second"),
+        "Later message should be recalled going forward"
+    );
+    assert_eq!(
+        history.next("topic").as_deref(),
+        Some("draft"),
+        "Going past the latest message should restore the draft"
+    );
+    assert_eq!(
+        history.next("topic"),
+        None,
+        "Nothing should be recalled past the draft"
+    );
+    assert_eq!(
+        history.previous("unknown", "draft"),
+        None,
+        "Topic without messages should recall nothing"
+    );
+
+    for i in 0..HISTORY_LIMIT + 5 {
+        history.push("topic", &format!("message {}", i));
+    }
+    let mut recalled = Vec::new();
+    let mut draft = String::new();
+    while let Some(message) = history.previous("topic", &draft) {
+        if message == draft {
+            break;
+        }
+        draft = message.clone();
+        recalled.push(message);
+    }
+    assert_eq!(
+        recalled.len(),
+        HISTORY_LIMIT,
+        "Only the latest messages should be kept"
+    );
+    assert_eq!(
+        recalled.last().map(String::as_str),
+        Some("message 5"),
+        "Oldest messages should be dropped"
+    );
+}
+
+/// # Test: Tab Completes Member Names
+///
+/// ## Steps:
+/// 1. Complete the start of a name shared by several members
+/// 2. Complete again, and again past the last match
+/// 3. Complete a word matching nobody, and an input ending in a space
+///
+/// ## Assertions:
+/// - The word is completed to the first match, ignoring case
+/// - Repeated completions cycle through the matches
+/// - Text before the word is kept
+/// - Nothing is completed without a matching word
+#[test]
+fn test_tab_completes_member_names() {
+    let names = ["bob", "Alice", "alfred", "Ålf", "alice"].map(String::from);
+    let mut completion = NameCompletion::default();
+
+    let first = completion
+        .complete("thanks al", &names)
+        .expect("Matching name should be completed");
+    assert_eq!(
+        first, "thanks alfred ",
+        "First match should be completed, followed by a space"
+    );
+    let second = completion.complete(&first, &names).unwrap();
+    assert_eq!(second, "thanks Alice ", "Next match should follow");
+    let third = completion.complete(&second, &names).unwrap();
+    assert_eq!(
+        third, "thanks alice ",
+        "Matches differing in case should both be offered"
+    );
+    assert_eq!(
+        completion.complete(&third, &names).as_deref(),
+        Some("thanks alfred "),
+        "Completions should cycle back to the first match"
+    );
+
+    completion.reset();
+    assert_eq!(
+        completion.complete("hi ål", &names).as_deref(),
+        Some("hi Ålf "),
+        "Names should match whatever their letters"
+    );
+    assert_eq!(
+        completion.complete("hi zed", &names),
+        None,
+        "Word matching nobody should not be completed"
+    );
+    assert_eq!(
+        completion.complete("hi ", &names),
+        None,
+        "Input ending in a space should not be completed"
+    );
+}
@@ -0,0 +1,444 @@
+use chrono::{TimeZone, Utc, Weekday};
+use iroh::SecretKey;
+use iroh_lab_core::automation::ExportRule;
+use iroh_lab_core::blobs::BlobHash;
+use iroh_lab_core::config::{
+    Binding, Config, IpVersion, LargeMessages, LinkConfirmation, LogLevel, MessageFormat,
+    MessageLimit, Notifications, RelayMode, SendKey, ThemePreference, LARGE_MESSAGE_CHARS,
+    MAX_MESSAGE_CHARS,
+};
+use iroh_lab_core::filter::{ContentFilter, FilterAction};
+use iroh_lab_core::profile::UserProfile;
+use iroh_lab_core::testing::{member, ChatMessageBuilder};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+
+/// # Test: Missing Configuration Uses Defaults
+///
+/// This test verifies that the first run, without a configuration file, starts
+/// with the default settings.
+///
+/// ## Assertions:
+/// - Loading a missing file returns the default configuration
+/// - The guided tour has not been completed by default
+/// - The theme follows the system by default
+#[test]
+fn test_missing_configuration_uses_defaults() {
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+
+    let config =
+        Config::load_from(&dir.path().join("config.json")).expect("Failed to load configuration");
+    assert_eq!(config, Config::default(), "Should load the defaults");
+    assert!(
+        !config.tour_completed,
+        "Tour should not be completed by default"
+    );
+    assert_eq!(
+        config.theme,
+        ThemePreference::System,
+        "Theme should follow the system by default"
+    );
+}
+
+/// # Test: Configuration Round Trip
+///
+/// This test verifies that saved settings are loaded again on the next run.
+///
+/// ## Steps:
+/// 1. Save a configuration to a directory that does not exist yet
+/// 2. Load it from the same path
+///
+/// ## Assertions:
+/// - The loaded configuration matches the saved one
+#[test]
+fn test_configuration_round_trip() {
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let path = dir.path().join("iroh-lab").join("config.json");
+    let config = Config {
+        username: "alice".to_string(),
+        tour_completed: true,
+        archived_topics: ["archived-topic".to_string()].into(),
+        held_topics: ["archived-topic".to_string()].into(),
+        profile: UserProfile {
+            display_name: "Alice".to_string(),
+            status_line: "Testing".to_string(),
+            avatar: "🦀".to_string(),
+        },
+        avatar_image: Some(BlobHash::of(b"avatar image")),
+        export_rules: [(
+            "archived-topic".to_string(),
+            ExportRule {
+                topic_name: "Archived".to_string(),
+                weekday: Weekday::Sun,
+                hour: 9,
+                days: 7,
+                folder: "exports".into(),
+                prune: true,
+                since: Utc.with_ymd_and_hms(2026, 10, 1, 12, 0, 0).unwrap(),
+            },
+        )]
+        .into(),
+        welcome_templates: [("archived-topic".to_string(), "Welcome, {name}!".to_string())].into(),
+        calendar_feeds: [("archived-topic".to_string(), "exports/Archived.ics".into())].into(),
+        folder_bridges: [("archived-topic".to_string(), "shared".into())].into(),
+        muted_members: [SecretKey::from_bytes(&[5; 32]).public()].into(),
+        muted_authors: ["mallory".to_string()].into(),
+        content_filter: ContentFilter {
+            patterns: vec!["darn".to_string(), "/sp[a@]m/".to_string()],
+            action: FilterAction::Mask,
+        },
+        topic_filters: [(
+            "archived-topic".to_string(),
+            ContentFilter {
+                patterns: vec!["heck".to_string()],
+                action: FilterAction::Hide,
+            },
+        )]
+        .into(),
+        allowlists: [(
+            "archived-topic".to_string(),
+            [SecretKey::from_bytes(&[6; 32]).public()].into(),
+        )]
+        .into(),
+        allowlist_lamports: [("archived-topic".to_string(), 42)].into(),
+        trusted_topics: ["archived-topic".to_string()].into(),
+        theme: ThemePreference::Dark,
+        notifications: Notifications::All,
+        relay_mode: RelayMode::Disabled,
+        binding: Binding {
+            port: 11_204,
+            ip_version: IpVersion::V4,
+            interface_v4: Some(Ipv4Addr::new(192, 168, 1, 20)),
+            interface_v6: None,
+        },
+        log_level: LogLevel::Info,
+        send_key: SendKey::CtrlEnter,
+        large_messages: LargeMessages::Send,
+        message_format: MessageFormat::Raw,
+        message_limit: MessageLimit::Medium,
+        link_confirmation: LinkConfirmation::Always,
+        recent_emoji: vec!["🦀".to_string()],
+    };
+
+    config.save_to(&path).expect("Failed to save configuration");
+    let loaded = Config::load_from(&path).expect("Failed to load configuration");
+    assert_eq!(loaded, config, "Loaded configuration should match");
+}
+
+/// # Test: Partial Configuration Files
+///
+/// This test verifies that files written by other releases load, taking the
+/// default for missing settings and ignoring unknown ones, while broken files
+/// are reported.
+///
+/// ## Assertions:
+/// - A file with an unknown setting only loads the known ones
+/// - An empty object loads the defaults
+/// - A file that is not JSON is rejected
+#[test]
+fn test_partial_configuration_files() {
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let path = dir.path().join("config.json");
+
+    std::fs::write(&path, r#"{"tour_completed": true, "unknown": 1}"#).unwrap();
+    let config = Config::load_from(&path).expect("Failed to load configuration");
+    assert!(config.tour_completed, "Known setting should be loaded");
+
+    std::fs::write(&path, "{}").unwrap();
+    let config = Config::load_from(&path).expect("Failed to load configuration");
+    assert_eq!(config, Config::default(), "Should load the defaults");
+
+    std::fs::write(&path, "not json").unwrap();
+    assert!(
+        Config::load_from(&path).is_err(),
+        "Broken file should be rejected"
+    );
+}
+
+/// # Test: Mentions Match Whole Names
+///
+/// ## Assertions:
+/// - A name mentioned in any case asks for attention
+/// - A name inside another word does not
+/// - Every message asks for attention with all notifications on, none with
+///   them off
+#[test]
+fn test_mentions_match_whole_names() {
+    let names = ["al", "Alice Liddell"];
+    assert!(
+        Notifications::Mentions.wants("Thanks, AL!", &names),
+        "Mentioned username should ask for attention"
+    );
+    assert!(
+        Notifications::Mentions.wants("ask alice liddell", &names),
+        "Mentioned display name should ask for attention"
+    );
+    assert!(
+        !Notifications::Mentions.wants("always late", &names),
+        "Name inside a word should not ask for attention"
+    );
+    assert!(
+        Notifications::All.wants("always late", &names),
+        "Every message should ask for attention"
+    );
+    assert!(
+        !Notifications::Off.wants("Thanks, al!", &names),
+        "No message should ask for attention"
+    );
+}
+
+/// # Test: Muted Senders
+///
+/// ## Assertions:
+/// - Messages signed by a muted member are hidden, whatever name they use
+/// - Messages sent under a muted name are hidden, signed or not, in any case
+/// - Messages of others are shown
+#[test]
+fn test_muted_senders() {
+    let config = Config {
+        muted_members: [member(1)].into(),
+        muted_authors: ["Mallory".to_string()].into(),
+        ..Config::default()
+    };
+    assert!(
+        config.mutes(&ChatMessageBuilder::new("m1").sent_by(member(1)).build()),
+        "Messages of a muted member should be hidden"
+    );
+    assert!(
+        config.mutes(&ChatMessageBuilder::new("m2").author("mallory").build()),
+        "Unsigned messages under a muted name should be hidden"
+    );
+    assert!(
+        config.mutes(
+            &ChatMessageBuilder::new("m3")
+                .author("MALLORY")
+                .sent_by(member(2))
+                .build()
+        ),
+        "Signed messages under a muted name should be hidden"
+    );
+    assert!(
+        !config.mutes(&ChatMessageBuilder::new("m4").sent_by(member(2)).build()),
+        "Messages of others should be shown"
+    );
+}
+
+/// # Test: Muted Names Match in Any Script
+///
+/// ## Steps:
+/// 1. Mute a name with uppercase letters beyond ASCII and spaces around it
+/// 2. Unmute it under another case
+///
+/// ## Assertions:
+/// - Messages under the name are hidden in any case
+/// - Muting the name again changes nothing
+/// - Unmuting it under another case shows its messages again
+#[test]
+fn test_muted_names_match_in_any_script() {
+    let mut config = Config::default();
+    assert!(
+        config.mute_author(" ÉLODIE "),
+        "Name should not be muted yet"
+    );
+    assert!(
+        config.mutes(&ChatMessageBuilder::new("m1").author("élodie").build()),
+        "Messages under the name should be hidden in any case"
+    );
+    assert!(
+        !config.mute_author("Élodie"),
+        "Muting the name again should change nothing"
+    );
+    assert!(
+        config.unmute_author("élodie"),
+        "Name should be unmuted under another case"
+    );
+    assert!(
+        !config.mutes(&ChatMessageBuilder::new("m2").author("ÉLODIE").build()),
+        "Messages under the unmuted name should be shown"
+    );
+}
+
+/// # Test: Large Messages Ask for Confirmation
+///
+/// ## Assertions:
+/// - Messages up to the limit are sent right away
+/// - Longer messages ask for confirmation, unless the user turned it off
+/// - The limit counts characters, not bytes
+#[test]
+fn test_large_messages_ask_for_confirmation() {
+    let limit = "x".repeat(LARGE_MESSAGE_CHARS);
+    let large = "x".repeat(LARGE_MESSAGE_CHARS + 1);
+    assert!(
+        !LargeMessages::Confirm.confirms(&limit),
+        "Message at the limit should be sent right away"
+    );
+    assert!(
+        LargeMessages::Confirm.confirms(&large),
+        "Large message should ask for confirmation"
+    );
+    assert!(
+        !LargeMessages::Send.confirms(&large),
+        "Large message should be sent right away when confirmation is off"
+    );
+    assert!(
+        !LargeMessages::Confirm.confirms(&"é".repeat(LARGE_MESSAGE_CHARS)),
+        "Limit should count characters"
+    );
+}
+
+/// # Test: Messages Over the Limit Are Refused
+///
+/// ## Steps:
+/// 1. Check messages at and over the chosen limit
+/// 2. Check a message of characters taking several bytes
+///
+/// ## Assertions:
+/// - Messages at the limit are accepted, longer ones refused
+/// - The characters left go negative once over the limit
+/// - The limit counts characters, not bytes
+/// - No limit exceeds the longest message a frame holds
+#[test]
+fn test_messages_over_the_limit_are_refused() {
+    let limit = MessageLimit::Short;
+    assert!(
+        limit.check(&"x".repeat(limit.chars())).is_ok(),
+        "Message at the limit should be accepted"
+    );
+    assert!(
+        limit.check(&"x".repeat(limit.chars() + 1)).is_err(),
+        "Message over the limit should be refused"
+    );
+    assert_eq!(
+        limit.remaining(&"x".repeat(limit.chars() + 5)),
+        -5,
+        "Characters left should be negative over the limit"
+    );
+    assert_eq!(
+        limit.remaining(&"é".repeat(10)),
+        limit.chars() as i64 - 10,
+        "Limit should count characters"
+    );
+    assert!(
+        MessageLimit::ALL
+            .iter()
+            .all(|limit| limit.chars() <= MAX_MESSAGE_CHARS),
+        "No limit should exceed what a frame holds"
+    );
+}
+
+/// # Test: Links Ask for Confirmation Unless Trusted
+///
+/// ## Assertions:
+/// - Links of untrusted topics ask by default, those of trusted ones do not
+/// - Asking always or never ignores whether the topic is trusted
+#[test]
+fn test_links_ask_for_confirmation_unless_trusted() {
+    assert!(
+        LinkConfirmation::default().confirms(false),
+        "Link of an untrusted topic should ask by default"
+    );
+    assert!(
+        !LinkConfirmation::default().confirms(true),
+        "Link of a trusted topic should open by default"
+    );
+    assert!(
+        LinkConfirmation::Always.confirms(true),
+        "Link should ask when always asking"
+    );
+    assert!(
+        !LinkConfirmation::Never.confirms(false),
+        "Link should open when never asking"
+    );
+}
+
+/// # Test: Binding Addresses
+///
+/// ## Assertions:
+/// - By default, a random port is listened on all interfaces of both IP
+///   versions
+/// - A fixed port and interface are bound as configured
+/// - An IP version not used is bound to the loopback interface, out of
+///   reach of other machines
+#[test]
+fn test_binding_addresses() {
+    let binding = Binding::default();
+    assert_eq!(
+        binding.addr_v4(),
+        SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0),
+        "IPv4 should listen on all interfaces by default"
+    );
+    assert_eq!(
+        binding.addr_v6(),
+        SocketAddrV6::new(Ipv6Addr::UNSPECIFIED, 0, 0, 0),
+        "IPv6 should listen on all interfaces by default"
+    );
+
+    let binding = Binding {
+        port: 11_204,
+        ip_version: IpVersion::V4,
+        interface_v4: Some(Ipv4Addr::new(192, 168, 1, 20)),
+        interface_v6: Some(Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 1)),
+    };
+    assert_eq!(
+        binding.addr_v4(),
+        SocketAddrV4::new(Ipv4Addr::new(192, 168, 1, 20), 11_204),
+        "IPv4 should listen on the configured interface and port"
+    );
+    assert_eq!(
+        binding.addr_v6(),
+        SocketAddrV6::new(Ipv6Addr::LOCALHOST, 11_204, 0, 0),
+        "IPv6 should only listen on the loopback interface"
+    );
+
+    let binding = Binding {
+        ip_version: IpVersion::V6,
+        ..Binding::default()
+    };
+    assert_eq!(
+        binding.addr_v4(),
+        SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0),
+        "IPv4 should only listen on the loopback interface"
+    );
+}
+
+/// # Test: Allowlists Change Only While Topics Are Private
+///
+/// ## Steps:
+/// 1. Allow a node on a topic open to everyone
+/// 2. Make the topic private, then allow and disallow nodes
+/// 3. Make it public again
+///
+/// ## Assertions:
+/// - Topics open to everyone have no allowlist to change
+/// - Private topics return their allowlist as changed
+/// - Making a topic public reports whether it was private
+#[test]
+fn test_allowlists_change_only_while_topics_are_private() {
+    let mut config = Config::default();
+    assert_eq!(
+        config.allow_node("topic", member(1)),
+        None,
+        "Open topics should have no allowlist to change"
+    );
+
+    config.make_private("topic", [member(1)].into());
+    assert_eq!(
+        config.allow_node("topic", member(2)),
+        Some([member(1), member(2)].into()),
+        "Allowed nodes should join the allowlist"
+    );
+    assert_eq!(
+        config.disallow_node("topic", &member(1)),
+        Some([member(2)].into()),
+        "Disallowed nodes should leave the allowlist"
+    );
+
+    assert!(
+        config.make_public("topic"),
+        "The topic should have been private"
+    );
+    assert!(
+        !config.make_public("topic"),
+        "The topic should be public already"
+    );
+    assert!(config.allowlists.is_empty(), "No allowlist should be left");
+}
@@ -0,0 +1,53 @@
+use iroh::SecretKey;
+use iroh_lab_core::contacts::Contacts;
+
+/// # Test: Members Are Imported Once and Tagged With Their Topic
+///
+/// ## Steps:
+/// 1. Import the members of a topic
+/// 2. Import members of a second topic, one of them already a contact
+/// 3. Save the contacts book and load it again
+///
+/// ## Assertions:
+/// - Only new members are added, and existing contacts keep their name
+/// - Every contact is tagged with the topics it was imported from
+/// - The contacts book survives saving
+#[test]
+fn test_members_are_imported_once_and_tagged_with_their_topic() {
+    let alice = SecretKey::generate(rand::rngs::OsRng).public();
+    let bob = SecretKey::generate(rand::rngs::OsRng).public();
+    let mut contacts = Contacts::default();
+
+    let added = contacts.import([(alice, "alice")], "rust");
+    assert_eq!(added, 1, "New member should be added");
+
+    let added = contacts.import([(alice, "alicia"), (bob, "bob")], "iroh");
+    assert_eq!(added, 1, "Existing contact should not be added again");
+    assert_eq!(contacts.len(), 2, "Book should hold both members");
+
+    let alice_contact = contacts.get(&alice).expect("Alice should be a contact");
+    assert_eq!(
+        alice_contact.name, "alice",
+        "Existing contact should keep its name"
+    );
+    assert_eq!(
+        alice_contact.tags.iter().collect::<Vec<_>>(),
+        ["iroh", "rust"],
+        "Contact should be tagged with both topics"
+    );
+
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let path = dir.path().join("iroh-lab").join("contacts.json");
+    assert!(
+        Contacts::load_from(&path)
+            .expect("Missing file should load")
+            .is_empty(),
+        "Missing file should load no contacts"
+    );
+    contacts.save_to(&path).expect("Contacts should be saved");
+    assert_eq!(
+        Contacts::load_from(&path).expect("Contacts should load"),
+        contacts,
+        "Contacts should survive saving"
+    );
+}
@@ -0,0 +1,176 @@
+use chrono::Utc;
+use iroh_lab_core::client::{ChatMessage, ClientEvent, MessageDelete, DELETED_CONTENT};
+use iroh_lab_core::conversation::Conversation;
+use iroh_lab_core::testing::{member, ChatMessageBuilder};
+
+fn message(id: &str, lamport: u64) -> ChatMessage {
+    ChatMessageBuilder::new(id)
+        .content("Are we meeting today?")
+        .topic("conversation-test-topic")
+        .lamport(lamport)
+        .build()
+}
+
+fn ids(conversation: &Conversation) -> Vec<&str> {
+    conversation
+        .messages()
+        .iter()
+        .map(|message| message.id.as_str())
+        .collect()
+}
+
+/// # Test: Messages Are Shown Once, in Lamport Order
+///
+/// ## Steps:
+/// 1. Insert messages out of order, and one of them again
+///
+/// ## Assertions:
+/// - The messages are in the order of their Lamport timestamps
+/// - The copy is not inserted
+#[test]
+fn test_messages_are_shown_once_in_lamport_order() {
+    let mut conversation = Conversation::new();
+
+    assert!(
+        conversation.insert(message("c", 3)),
+        "New message should be inserted"
+    );
+    assert!(
+        conversation.insert(message("a", 1)),
+        "Earlier message should be inserted"
+    );
+    assert!(
+        conversation.insert(message("b", 2)),
+        "Late message should be inserted"
+    );
+    assert!(
+        !conversation.insert(message("a", 1)),
+        "Copy should not be inserted"
+    );
+
+    assert_eq!(
+        ids(&conversation),
+        vec!["a", "b", "c"],
+        "Messages should be in Lamport order"
+    );
+    assert_eq!(
+        conversation.get("b").map(|message| message.lamport),
+        Some(2),
+        "Message should be found by id"
+    );
+}
+
+/// # Test: Cleared Messages Stay Hidden Until Reset
+///
+/// ## Steps:
+/// 1. Insert a message and clear the conversation
+/// 2. Insert it again, then reset the conversation and insert it once more
+///
+/// ## Assertions:
+/// - The copy of a cleared message is not shown
+/// - After a reset, the message is shown again
+#[test]
+fn test_cleared_messages_stay_hidden_until_reset() {
+    let mut conversation = Conversation::new();
+    conversation.insert(message("a", 1));
+
+    conversation.clear();
+    assert!(
+        conversation.messages().is_empty(),
+        "Cleared conversation should show nothing"
+    );
+    assert!(
+        !conversation.insert(message("a", 1)),
+        "Copy of a cleared message should not be shown"
+    );
+    assert!(
+        conversation.contains("a"),
+        "Cleared message should be known"
+    );
+
+    conversation.reset();
+    assert!(
+        conversation.insert(message("a", 1)),
+        "Message should be shown again after a reset"
+    );
+}
+
+/// # Test: Sent Messages Are Numbered Across Resets
+///
+/// ## Steps:
+/// 1. Number two messages, reset the conversation and number another
+///
+/// ## Assertions:
+/// - The numbers start at 1 and keep increasing
+#[test]
+fn test_sent_messages_are_numbered_across_resets() {
+    let mut conversation = Conversation::new();
+
+    assert_eq!(conversation.next_sequence(), 1, "First message should be 1");
+    assert_eq!(
+        conversation.next_sequence(),
+        2,
+        "Second message should be 2"
+    );
+    conversation.reset();
+    assert_eq!(
+        conversation.next_sequence(),
+        3,
+        "Numbering should go on after a reset"
+    );
+}
+
+/// # Test: Deletes Arriving Before Their Message Are Applied
+///
+/// ## Steps:
+/// 1. Apply the delete of a message not received yet
+/// 2. Apply the message itself
+/// 3. Apply a delete of another message by a node that did not send it
+///
+/// ## Assertions:
+/// - The early delete changes nothing shown
+/// - The message is shown deleted once it arrives
+/// - The delete from another node is ignored
+#[test]
+fn test_deletes_arriving_before_their_message_are_applied() {
+    let mut conversation = Conversation::new();
+    let sender = member(1);
+    let delete = |target_id: &str| MessageDelete {
+        target_id: target_id.to_string(),
+        topic_hash: "conversation-test-topic".to_string(),
+        deleted_at: Utc::now(),
+    };
+
+    assert!(
+        !conversation.apply(ClientEvent::Delete {
+            delete: delete("a"),
+            sender: Some(sender),
+        }),
+        "Delete of a message not received should change nothing"
+    );
+    assert!(
+        conversation.apply(ClientEvent::Message(
+            ChatMessageBuilder::new("a").sent_by(sender).build()
+        )),
+        "Deleted message should still be inserted"
+    );
+    let deleted = conversation.get("a").expect("Message should be shown");
+    assert!(deleted.is_deleted(), "Message should be deleted on arrival");
+    assert_eq!(
+        deleted.content, DELETED_CONTENT,
+        "Deleted message should not show its content"
+    );
+
+    conversation.insert(ChatMessageBuilder::new("b").sent_by(sender).build());
+    assert!(
+        !conversation.apply(ClientEvent::Delete {
+            delete: delete("b"),
+            sender: Some(member(2)),
+        }),
+        "Delete from another node should be ignored"
+    );
+    assert!(
+        !conversation.get("b").is_some_and(ChatMessage::is_deleted),
+        "Message should not be deleted by another node"
+    );
+}
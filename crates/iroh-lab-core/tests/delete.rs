@@ -0,0 +1,148 @@
+use chrono::Utc;
+use iroh::SecretKey;
+use iroh_gossip::proto::TopicId;
+use iroh_lab_core::client::{ChatMessage, MessageDelete, MessageEdit, DELETED_CONTENT};
+use iroh_lab_core::crypto::TopicKey;
+use iroh_lab_core::testing::ChatMessageBuilder;
+use iroh_lab_core::tombstone::Tombstones;
+use iroh_lab_core::wire::{self, Payload};
+
+fn node_id() -> iroh::NodeId {
+    SecretKey::generate(rand::rngs::OsRng).public()
+}
+
+fn message_from(sender: iroh::NodeId) -> ChatMessage {
+    ChatMessageBuilder::new("deleted-message")
+        .content("Oops, wrong room")
+        .topic("delete-test-topic")
+        .sent_by(sender)
+        .build()
+}
+
+fn delete_of(message: &ChatMessage) -> MessageDelete {
+    MessageDelete {
+        target_id: message.id.clone(),
+        topic_hash: message.topic_hash.clone(),
+        deleted_at: Utc::now(),
+    }
+}
+
+/// # Test: Deletes Round Trip Over The Wire
+///
+/// ## Assertions:
+/// - The decoded payload is a delete of the original message
+/// - The sender is the signing node
+#[test]
+fn test_deletes_round_trip_over_the_wire() {
+    let key = SecretKey::generate(rand::rngs::OsRng);
+    let topic_key = TopicKey::derive(&rand::random(), &TopicId::from_bytes(rand::random()));
+    let delete = delete_of(&message_from(key.public()));
+
+    let bytes = wire::encode(&Payload::Delete(delete.clone()), &key, &topic_key)
+        .expect("Failed to encode delete");
+    let envelope = wire::decode(&bytes, &topic_key).expect("Failed to decode delete");
+    assert_eq!(
+        envelope.sender,
+        key.public(),
+        "Sender should be the signing node"
+    );
+
+    let Payload::Delete(decoded) = envelope.payload else {
+        panic!("Expected a delete payload");
+    };
+    assert_eq!(decoded.target_id, delete.target_id, "Target should match");
+    assert_eq!(
+        decoded.deleted_at, delete.deleted_at,
+        "Delete time should match"
+    );
+}
+
+/// # Test: Authors Can Delete Their Messages
+///
+/// This test verifies that only the sender of a message can delete it, and
+/// that a deleted message cannot be edited back.
+///
+/// ## Assertions:
+/// - A delete from another node is ignored
+/// - A delete signed by the sender replaces the content with a placeholder
+/// - Edits of the deleted message are ignored
+#[test]
+fn test_authors_can_delete_their_messages() {
+    let sender = node_id();
+    let mut message = message_from(sender);
+    let delete = delete_of(&message);
+
+    assert!(
+        !message.apply_delete(&delete, Some(node_id())),
+        "Delete from another node should be ignored"
+    );
+    assert!(!message.is_deleted(), "Message should not be deleted");
+
+    assert!(
+        message.apply_delete(&delete, Some(sender)),
+        "Delete should apply"
+    );
+    assert!(message.is_deleted(), "Message should be deleted");
+    assert_eq!(
+        message.content, DELETED_CONTENT,
+        "Content should be replaced by the placeholder"
+    );
+
+    let edit = MessageEdit {
+        target_id: message.id.clone(),
+        topic_hash: message.topic_hash.clone(),
+        new_content: "Back again".to_string(),
+        edited_at: Utc::now(),
+    };
+    assert!(
+        !message.apply_edit(&edit, Some(sender)),
+        "Edit of a deleted message should be ignored"
+    );
+    assert_eq!(
+        message.content, DELETED_CONTENT,
+        "Deleted message should keep the placeholder"
+    );
+}
+
+/// # Test: Tombstones Delete Late Copies
+///
+/// This test verifies that a message arriving after its delete does not
+/// come back.
+///
+/// ## Steps:
+/// 1. Record deletes from the sender and from an unverified peer
+/// 2. Apply the tombstones to a copy of the message arriving late
+///
+/// ## Assertions:
+/// - Deletes without a verified sender are not kept
+/// - The late copy is deleted
+#[test]
+fn test_tombstones_delete_late_copies() {
+    let sender = node_id();
+    let late_copy = message_from(sender);
+    let mut tombstones = Tombstones::default();
+
+    let mut unverified = delete_of(&late_copy);
+    unverified.target_id = "other-message".to_string();
+    tombstones.record(unverified, None);
+    assert!(
+        !tombstones.contains("other-message"),
+        "Unverified delete should not be kept"
+    );
+
+    tombstones.record(delete_of(&late_copy), Some(sender));
+    assert!(
+        tombstones.contains(&late_copy.id),
+        "Verified delete should be kept"
+    );
+
+    let mut late_copy = late_copy;
+    assert!(
+        tombstones.apply(&mut late_copy),
+        "Late copy should be deleted"
+    );
+    assert_eq!(
+        late_copy.content, DELETED_CONTENT,
+        "Late copy should only show the placeholder"
+    );
+}
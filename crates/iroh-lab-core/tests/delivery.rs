@@ -0,0 +1,194 @@
+use iroh_lab_core::client::ChatMessage;
+use iroh_lab_core::delivery::{Deliveries, DeliveryState, ACK_TIMEOUT, MAX_ATTEMPTS, REPORT_LIMIT};
+use iroh_lab_core::testing::{member, ChatMessageBuilder};
+use std::time::{Duration, Instant};
+
+fn message(id: &str) -> ChatMessage {
+    ChatMessageBuilder::new(id)
+        .content("Did you get this?")
+        .topic("delivery-test-topic")
+        .build()
+}
+
+/// # Test: Acknowledged Messages Are Delivered
+///
+/// ## Assertions:
+/// - The first acknowledgment of a message delivers it
+/// - Further acknowledgments and unknown ids are ignored
+/// - Delivered messages are never sent again
+#[test]
+fn test_acknowledged_messages_are_delivered() {
+    let start = Instant::now();
+    let mut deliveries = Deliveries::default();
+    deliveries.track(message("a"), start);
+
+    assert!(deliveries.acknowledge("a"), "First ack should deliver");
+    assert!(!deliveries.acknowledge("a"), "Second ack should be ignored");
+    assert!(
+        !deliveries.acknowledge("unknown"),
+        "Ack of an unknown message should be ignored"
+    );
+
+    let due = deliveries.due(start + ACK_TIMEOUT);
+    assert!(
+        due.resend.is_empty() && due.failed.is_empty(),
+        "Delivered message should not be due"
+    );
+}
+
+/// # Test: Unacknowledged Messages Are Resent, Then Fail
+///
+/// ## Steps:
+/// 1. Track a message nobody acknowledges
+/// 2. Check for due messages before and after each timeout
+///
+/// ## Assertions:
+/// - Nothing is due before the timeout
+/// - The message is sent again after each timeout, up to the attempt limit
+/// - The message fails after the last attempt and is no longer tracked
+#[test]
+fn test_unacknowledged_messages_are_resent_then_fail() {
+    let mut now = Instant::now();
+    let mut deliveries = Deliveries::default();
+    deliveries.track(message("a"), now);
+
+    let early = deliveries.due(now + ACK_TIMEOUT - Duration::from_millis(1));
+    assert!(early.resend.is_empty(), "Nothing should be due yet");
+
+    for attempt in 2..=MAX_ATTEMPTS {
+        now += ACK_TIMEOUT;
+        let due = deliveries.due(now);
+        assert_eq!(
+            due.resend.len(),
+            1,
+            "Attempt {} should send the message again",
+            attempt
+        );
+        assert!(due.failed.is_empty(), "Message should not fail yet");
+    }
+
+    now += ACK_TIMEOUT;
+    let due = deliveries.due(now);
+    assert!(due.resend.is_empty(), "Message should not be sent again");
+    assert_eq!(due.failed, vec!["a"], "Message should fail");
+    assert_eq!(deliveries.pending(), 0, "Failed message should be dropped");
+}
+
+/// # Test: Delivery Reports Tell Who Received a Message When
+///
+/// ## Steps:
+/// 1. Hand a message to two neighbors, send it again to a third
+/// 2. Have two members acknowledge it, one of them twice
+/// 3. Track more messages than reports are kept for
+///
+/// ## Assertions:
+/// - Every neighbor the message was handed to is reported
+/// - Sends and retransmissions are counted
+/// - Each member's first acknowledgment is reported, the last one as the
+///   propagation time
+/// - Only the latest messages keep their report
+#[test]
+fn test_delivery_reports_tell_who_received_a_message_when() {
+    let start = Instant::now();
+    let mut deliveries = Deliveries::default();
+    deliveries.track(message("a"), start);
+    deliveries.sent_to("a", [member(1), member(2)]);
+    deliveries.due(start + ACK_TIMEOUT);
+    deliveries.sent_to("a", [member(2), member(3)]);
+
+    let later = start + ACK_TIMEOUT + Duration::from_millis(300);
+    assert!(
+        deliveries.acknowledge_by("a", member(4), later),
+        "First acknowledgment should deliver"
+    );
+    deliveries.acknowledge_by("a", member(5), later + Duration::from_secs(1));
+    deliveries.acknowledge_by("a", member(4), later + Duration::from_secs(2));
+
+    let report = deliveries.report("a").expect("Report should be kept");
+    assert_eq!(
+        report.neighbors,
+        [member(1), member(2), member(3)].into(),
+        "Every neighbor should be reported"
+    );
+    assert_eq!(
+        (report.attempts, report.retransmissions()),
+        (2, 1),
+        "Sends should be counted"
+    );
+    assert_eq!(
+        report.state,
+        DeliveryState::Delivered,
+        "Message should be delivered"
+    );
+    assert_eq!(
+        report.acks.get(&member(4)),
+        Some(&(later - start)),
+        "First acknowledgment of a member should count"
+    );
+    assert_eq!(
+        report.propagation(),
+        Some(later + Duration::from_secs(1) - start),
+        "Last member to acknowledge should set the propagation time"
+    );
+
+    for i in 0..REPORT_LIMIT {
+        deliveries.track(message(&i.to_string()), start);
+    }
+    assert!(
+        deliveries.report("a").is_none(),
+        "Oldest report should be dropped"
+    );
+    assert!(
+        deliveries.report("0").is_some(),
+        "Latest reports should be kept"
+    );
+}
+
+/// # Test: Messages Without Neighbors Are Queued
+///
+/// ## Steps:
+/// 1. Queue two messages while no neighbor is connected
+/// 2. Take them once a neighbor is, and send them
+///
+/// ## Assertions:
+/// - Queued messages are reported as queued, and never due
+/// - They are taken in the order they were sent, once
+/// - Sent again, they are pending like any other message
+#[test]
+fn test_messages_without_neighbors_are_queued() {
+    let start = Instant::now();
+    let mut deliveries = Deliveries::default();
+    deliveries.queue(message("a"), start);
+    deliveries.queue(message("b"), start);
+
+    assert_eq!(deliveries.queued(), 2, "Both messages should be queued");
+    assert_eq!(
+        deliveries.report("a").map(|report| report.state),
+        Some(DeliveryState::Queued),
+        "Queued message should be reported as queued"
+    );
+    let due = deliveries.due(start + ACK_TIMEOUT * MAX_ATTEMPTS);
+    assert!(
+        due.resend.is_empty() && due.failed.is_empty(),
+        "Queued message should not be due"
+    );
+
+    let queued = deliveries.take_queued();
+    let ids: Vec<_> = queued.iter().map(|message| message.id.as_str()).collect();
+    assert_eq!(ids, ["a", "b"], "Messages should be taken in order");
+    assert!(
+        deliveries.take_queued().is_empty(),
+        "Messages should be taken once"
+    );
+
+    let now = start + Duration::from_secs(60);
+    for message in queued {
+        deliveries.track(message, now);
+    }
+    assert_eq!(
+        deliveries.report("b").map(|report| report.state),
+        Some(DeliveryState::Pending),
+        "Sent message should be pending"
+    );
+    assert_eq!(deliveries.pending(), 2, "Both messages should be pending");
+}
@@ -0,0 +1,92 @@
+use iroh_lab_core::demo::Script;
+use std::path::Path;
+use std::time::Duration;
+
+/// # Test: Scripts Are Played in Time Order
+///
+/// ## Steps:
+/// 1. Parse a script listing its messages out of order
+///
+/// ## Assertions:
+/// - The topic name is read
+/// - Messages are ordered by the time they are posted
+/// - Offsets shrink with the playback speed
+#[test]
+fn test_scripts_are_played_in_time_order() {
+    let script = Script::parse(
+        r#"
+        topic = "Launch planning"
+
+        [[message]]
+        at = 4
+        author = "bob"
+        content = "Second"
+
+        [[message]]
+        at = 1.5
+        author = "alice"
+        content = "First"
+        "#,
+    )
+    .expect("Script should parse");
+
+    assert_eq!(script.topic, "Launch planning", "Topic should be read");
+    let contents: Vec<&str> = script
+        .messages
+        .iter()
+        .map(|message| message.content.as_str())
+        .collect();
+    assert_eq!(
+        contents,
+        vec!["First", "Second"],
+        "Messages should be ordered by time"
+    );
+    assert_eq!(
+        script.offset(1, 2.0),
+        Duration::from_secs(2),
+        "Offsets should be divided by the speed"
+    );
+}
+
+/// # Test: Scripts With Invalid Times Are Rejected
+///
+/// ## Assertions:
+/// - A message posted before the start of the demo is an error
+#[test]
+fn test_scripts_with_invalid_times_are_rejected() {
+    let result = Script::parse(
+        r#"
+        topic = "Time travel"
+
+        [[message]]
+        at = -1
+        author = "alice"
+        content = "Too early"
+        "#,
+    );
+
+    assert!(
+        result.is_err(),
+        "Script with a negative time should be rejected"
+    );
+}
+
+/// # Test: Bundled Demo Scripts Load
+///
+/// ## Assertions:
+/// - Every script in `demos/` parses and posts at least one message
+#[test]
+fn test_bundled_demo_scripts_load() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../demos");
+    let entries = std::fs::read_dir(&dir).expect("Demo directory should exist");
+    for entry in entries {
+        let path = entry.expect("Demo directory should be readable").path();
+        let script =
+            Script::load(&path).unwrap_or_else(|e| panic!("{} should load: {}", path.display(), e));
+        assert!(
+            !script.messages.is_empty(),
+            "{} should post messages",
+            path.display()
+        );
+    }
+}
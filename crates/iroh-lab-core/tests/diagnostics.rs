@@ -0,0 +1,319 @@
+use iroh::endpoint::{ConnectionType, DirectAddr, DirectAddrType};
+use iroh::SecretKey;
+use iroh_lab_core::config::RelayMode;
+use iroh_lab_core::diagnostics::{
+    self, Check, CheckStatus, Connectivity, NatTraversal, NodeStatus, Report,
+};
+use iroh_lab_core::ticket;
+
+/// # Test: Storage Checks
+///
+/// This test verifies the checks of the directory holding the settings.
+///
+/// ## Steps:
+/// 1. Check a writable directory that does not exist yet
+/// 2. Check a configuration file that is not valid JSON
+///
+/// ## Assertions:
+/// - A writable directory passes and is left without the probe file
+/// - A broken configuration fails with a hint
+#[test]
+fn test_storage_checks() {
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let settings = dir.path().join("iroh-lab");
+
+    let storage = diagnostics::check_storage(&settings);
+    assert_eq!(
+        storage.status,
+        CheckStatus::Pass,
+        "Writable directory should pass"
+    );
+    assert_eq!(
+        std::fs::read_dir(&settings).unwrap().count(),
+        0,
+        "Probe file should be removed"
+    );
+
+    let config = settings.join("config.json");
+    std::fs::write(&config, "{ not json").unwrap();
+    let check = diagnostics::check_config(&config);
+    assert_eq!(
+        check.status,
+        CheckStatus::Fail,
+        "Broken configuration should fail"
+    );
+    assert!(check.hint.is_some(), "Failed check should have a hint");
+}
+
+/// # Test: Clock Check
+///
+/// ## Assertions:
+/// - A clock set before this release existed is reported
+/// - The current time passes
+#[test]
+fn test_clock_check() {
+    let stale = "2001-01-01T00:00:00Z".parse().unwrap();
+    assert_eq!(
+        diagnostics::check_clock(stale).status,
+        CheckStatus::Warn,
+        "Stale clock should be reported"
+    );
+    assert_eq!(
+        diagnostics::check_clock(chrono::Utc::now()).status,
+        CheckStatus::Pass,
+        "Current time should pass"
+    );
+}
+
+/// # Test: Reports Are Healthy Without Failures
+///
+/// ## Assertions:
+/// - Warnings leave a report healthy
+/// - A single failure makes it unhealthy
+#[test]
+fn test_reports_are_healthy_without_failures() {
+    let check = |status| Check {
+        name: "Test",
+        status,
+        detail: String::new(),
+        hint: None,
+    };
+
+    let mut report = Report {
+        checks: vec![check(CheckStatus::Pass), check(CheckStatus::Warn)],
+    };
+    assert!(
+        report.is_healthy(),
+        "Warnings should leave the report healthy"
+    );
+
+    report.checks.push(check(CheckStatus::Fail));
+    assert!(
+        !report.is_healthy(),
+        "Failure should make the report unhealthy"
+    );
+}
+
+/// # Test: Node Status Summarizes How Peers Are Reached
+///
+/// ## Steps:
+/// 1. Describe a node without peers, then add a relayed and a direct peer
+/// 2. Share its node address as text and parse it back
+///
+/// ## Assertions:
+/// - The NAT traversal status follows the connection types of the peers
+/// - The node address survives its textual form
+#[test]
+fn test_node_status_summarizes_how_peers_are_reached() {
+    let peer = || SecretKey::generate(rand::rngs::OsRng).public();
+    let relay: iroh::RelayUrl = "https://relay.example.com".parse().unwrap();
+    let direct = "192.0.2.1:4433".parse().unwrap();
+
+    let mut status = NodeStatus {
+        node_id: peer(),
+        bound_sockets: vec!["0.0.0.0:4433".parse().unwrap()],
+        direct_addresses: vec![direct],
+        home_relay: Some(relay.clone()),
+        connections: Vec::new(),
+        peer_scores: Vec::new(),
+    };
+    assert_eq!(
+        status.nat_traversal(),
+        NatTraversal::NoPeers,
+        "Node without peers should report none"
+    );
+
+    status
+        .connections
+        .push((peer(), ConnectionType::Relay(relay.clone())));
+    assert_eq!(
+        status.nat_traversal(),
+        NatTraversal::Relayed,
+        "Relayed peer should be reported"
+    );
+
+    status
+        .connections
+        .push((peer(), ConnectionType::Direct(direct)));
+    assert_eq!(status.connected_peers(), 2, "Both peers should be counted");
+    assert_eq!(
+        status.nat_traversal(),
+        NatTraversal::Partial,
+        "Mix of direct and relayed peers should be partial"
+    );
+
+    status.connections.remove(0);
+    assert_eq!(
+        status.nat_traversal(),
+        NatTraversal::Direct,
+        "Only direct peers should be reported as direct"
+    );
+
+    let addr = status.node_addr();
+    let text = ticket::node_addr_to_string(&addr);
+    assert!(
+        text.starts_with(ticket::NODE_ADDR_PREFIX),
+        "Node address should be prefixed"
+    );
+    assert_eq!(
+        ticket::parse_node_addr(&text),
+        Ok(addr),
+        "Node address should survive its textual form"
+    );
+    assert!(
+        ticket::parse_node_addr("ticket-abc").is_err(),
+        "Other text should not parse as a node address"
+    );
+}
+
+/// # Test: Connectivity Follows the Relay and Addresses
+///
+/// ## Steps:
+/// 1. Describe a node with a relay and a direct address
+/// 2. Reach its only peer through the relay
+/// 3. Lose the relay, then the direct address
+/// 4. Bind to the loopback interface only, with a relay
+///
+/// ## Assertions:
+/// - A node reachable directly and through its relay is connected
+/// - A node whose peers are all relayed is relay-only
+/// - Losing the relay is reconnecting when relays are used, and connected
+///   when they are not
+/// - A node with neither is offline
+/// - Loopback addresses do not make a node reachable directly
+#[test]
+fn test_connectivity_follows_the_relay_and_addresses() {
+    let peer = || SecretKey::generate(rand::rngs::OsRng).public();
+    let relay: iroh::RelayUrl = "https://relay.example.com".parse().unwrap();
+
+    let mut status = NodeStatus {
+        node_id: peer(),
+        bound_sockets: vec!["0.0.0.0:4433".parse().unwrap()],
+        direct_addresses: vec!["192.0.2.1:4433".parse().unwrap()],
+        home_relay: Some(relay.clone()),
+        connections: Vec::new(),
+        peer_scores: Vec::new(),
+    };
+    assert_eq!(
+        status.connectivity(RelayMode::Default),
+        Connectivity::Connected,
+        "Node with a relay and a direct address should be connected"
+    );
+
+    status
+        .connections
+        .push((peer(), ConnectionType::Relay(relay.clone())));
+    assert_eq!(
+        status.connectivity(RelayMode::Default),
+        Connectivity::RelayOnly,
+        "Node reaching its peers through the relay should be relay-only"
+    );
+
+    status.connections.clear();
+    status.home_relay = None;
+    assert_eq!(
+        status.connectivity(RelayMode::Default),
+        Connectivity::Reconnecting,
+        "Node that lost its relay should be reconnecting"
+    );
+    assert_eq!(
+        status.connectivity(RelayMode::Disabled),
+        Connectivity::Connected,
+        "Node without relays should not need one"
+    );
+
+    status.direct_addresses.clear();
+    assert_eq!(
+        status.connectivity(RelayMode::Default),
+        Connectivity::Offline,
+        "Node without relay and addresses should be offline"
+    );
+    assert!(
+        !Connectivity::Offline.is_online(),
+        "Offline should not be online"
+    );
+
+    status.direct_addresses = vec!["127.0.0.1:4433".parse().unwrap()];
+    status.home_relay = Some(relay);
+    assert_eq!(
+        status.connectivity(RelayMode::Forced),
+        Connectivity::RelayOnly,
+        "Node on the loopback interface should be relay-only"
+    );
+}
+
+/// # Test: NAT Traversal Follows the Direct Addresses
+///
+/// ## Steps:
+/// 1. Check no addresses, local ones only, a public address found via STUN
+///    behind a NAT and on a local interface, and a port mapping
+///
+/// ## Assertions:
+/// - Without addresses the check fails, with local ones only it warns
+/// - Public addresses pass, telling whether the node is behind a NAT
+/// - A port mapping is preferred
+#[test]
+fn test_nat_traversal_follows_the_direct_addresses() {
+    let addr = |addr: &str, typ| DirectAddr {
+        addr: addr.parse().unwrap(),
+        typ,
+    };
+    let local = addr("192.168.1.10:4433", DirectAddrType::Local);
+
+    assert_eq!(
+        diagnostics::check_direct_addresses(&[]).status,
+        CheckStatus::Fail,
+        "Node without addresses should fail"
+    );
+    let check = diagnostics::check_direct_addresses(std::slice::from_ref(&local));
+    assert_eq!(
+        check.status,
+        CheckStatus::Warn,
+        "Node with local addresses only should warn"
+    );
+    assert!(check.hint.is_some(), "Warning should come with a hint");
+
+    let stun = addr("203.0.113.7:61000", DirectAddrType::Stun);
+    let check = diagnostics::check_direct_addresses(&[local.clone(), stun.clone()]);
+    assert_eq!(
+        check.status,
+        CheckStatus::Pass,
+        "Public address should pass"
+    );
+    assert!(
+        check.detail.contains("Behind a NAT"),
+        "Public address unlike the local ones should be behind a NAT"
+    );
+
+    let public_local = addr("203.0.113.7:61000", DirectAddrType::Local);
+    let check = diagnostics::check_direct_addresses(&[public_local, stun.clone()]);
+    assert!(
+        check.detail.contains("Not behind a NAT"),
+        "Public address of a local interface should not be behind a NAT"
+    );
+
+    let mapped = addr("203.0.113.7:5000", DirectAddrType::Portmapped);
+    let check = diagnostics::check_direct_addresses(&[local, stun, mapped]);
+    assert!(
+        check.detail.contains("203.0.113.7:5000"),
+        "Port mapping should be reported"
+    );
+}
+
+/// # Test: Gossip Round Trip
+///
+/// ## Steps:
+/// 1. Send a message round a throwaway topic between two local nodes
+///
+/// ## Assertions:
+/// - The message arrives and is acknowledged
+#[tokio::test]
+async fn test_gossip_round_trip() {
+    let check = diagnostics::check_gossip_round_trip().await;
+    assert_eq!(
+        check.status,
+        CheckStatus::Pass,
+        "Message should go round the topic: {}",
+        check.detail
+    );
+}
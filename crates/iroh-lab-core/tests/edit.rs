@@ -0,0 +1,139 @@
+use chrono::{Duration, Utc};
+use iroh::SecretKey;
+use iroh_gossip::proto::TopicId;
+use iroh_lab_core::client::{ChatMessage, MessageEdit};
+use iroh_lab_core::crypto::TopicKey;
+use iroh_lab_core::testing::ChatMessageBuilder;
+use iroh_lab_core::wire::{self, Payload};
+
+fn node_id() -> iroh::NodeId {
+    SecretKey::generate(rand::rngs::OsRng).public()
+}
+
+fn message_from(sender: iroh::NodeId) -> ChatMessage {
+    ChatMessageBuilder::new("edited-message")
+        .content("Helo")
+        .topic("edit-test-topic")
+        .sent_by(sender)
+        .build()
+}
+
+fn edit_of(message: &ChatMessage, new_content: &str) -> MessageEdit {
+    MessageEdit {
+        target_id: message.id.clone(),
+        topic_hash: message.topic_hash.clone(),
+        new_content: new_content.to_string(),
+        edited_at: Utc::now(),
+    }
+}
+
+/// # Test: Edits Round Trip Over The Wire
+///
+/// This test verifies that an edit can be encoded and decoded like a chat
+/// message, and that the decoder reports who signed it.
+///
+/// ## Assertions:
+/// - The decoded payload is an edit with the original fields
+/// - The sender is the signing node
+#[test]
+fn test_edits_round_trip_over_the_wire() {
+    let key = SecretKey::generate(rand::rngs::OsRng);
+    let topic_key = TopicKey::derive(&rand::random(), &TopicId::from_bytes(rand::random()));
+    let edit = edit_of(&message_from(key.public()), "Hello");
+
+    let bytes = wire::encode(&Payload::Edit(edit.clone()), &key, &topic_key)
+        .expect("Failed to encode edit");
+    let envelope = wire::decode(&bytes, &topic_key).expect("Failed to decode edit");
+    assert_eq!(
+        envelope.sender,
+        key.public(),
+        "Sender should be the signing node"
+    );
+
+    let Payload::Edit(decoded) = envelope.payload else {
+        panic!("Expected an edit payload");
+    };
+    assert_eq!(decoded.target_id, edit.target_id, "Target should match");
+    assert_eq!(
+        decoded.new_content, edit.new_content,
+        "Content should match"
+    );
+    assert_eq!(decoded.edited_at, edit.edited_at, "Edit time should match");
+}
+
+/// # Test: Authors Can Edit Their Messages
+///
+/// ## Assertions:
+/// - An edit signed by the sender of the message replaces its content
+/// - The message is marked as edited
+#[test]
+fn test_authors_can_edit_their_messages() {
+    let sender = node_id();
+    let mut message = message_from(sender);
+    let edit = edit_of(&message, "Hello");
+
+    assert!(message.apply_edit(&edit, Some(sender)), "Edit should apply");
+    assert_eq!(message.content, "Hello", "Content should be replaced");
+    assert_eq!(
+        message.edited_at,
+        Some(edit.edited_at),
+        "Message should be marked as edited"
+    );
+}
+
+/// # Test: Edits From Others Are Ignored
+///
+/// This test verifies that nobody but the node that sent a message can
+/// change it.
+///
+/// ## Assertions:
+/// - An edit signed by another node is ignored
+/// - An unsigned edit is ignored
+/// - An edit of an unsigned message is ignored
+#[test]
+fn test_edits_from_others_are_ignored() {
+    let mut message = message_from(node_id());
+    let edit = edit_of(&message, "Hijacked");
+
+    assert!(
+        !message.apply_edit(&edit, Some(node_id())),
+        "Edit from another node should be ignored"
+    );
+    assert!(
+        !message.apply_edit(&edit, None),
+        "Unsigned edit should be ignored"
+    );
+
+    message.verified_sender = None;
+    assert!(
+        !message.apply_edit(&edit, None),
+        "Edit of an unsigned message should be ignored"
+    );
+    assert_eq!(message.content, "Helo", "Content should be unchanged");
+}
+
+/// # Test: Latest Edit Wins
+///
+/// This test verifies that edits arriving out of order leave the message with
+/// the content of the latest edit.
+///
+/// ## Assertions:
+/// - An edit older than the one applied is ignored
+#[test]
+fn test_latest_edit_wins() {
+    let sender = node_id();
+    let mut message = message_from(sender);
+    let older = edit_of(&message, "Hello");
+    let mut newer = edit_of(&message, "Hello, world");
+    newer.edited_at = older.edited_at + Duration::seconds(1);
+
+    assert!(
+        message.apply_edit(&newer, Some(sender)),
+        "Edit should apply"
+    );
+    assert!(
+        !message.apply_edit(&older, Some(sender)),
+        "Older edit should be ignored"
+    );
+    assert_eq!(message.content, "Hello, world", "Latest edit should win");
+}
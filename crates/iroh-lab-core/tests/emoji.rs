@@ -0,0 +1,61 @@
+use iroh_lab_core::emoji::{self, EMOJI, MAX_RECENT};
+
+/// # Test: Emoji Are Found by the Start of Their Words
+///
+/// ## Assertions:
+/// - An empty query finds every emoji
+/// - Every word of the query has to match the start of a word, in any case
+/// - Unknown words find nothing
+#[test]
+fn test_emoji_are_found_by_the_start_of_their_words() {
+    assert_eq!(
+        emoji::search("  ").len(),
+        EMOJI.len(),
+        "Empty query should find every emoji"
+    );
+    assert_eq!(
+        emoji::search("Thumbs UP"),
+        vec!["👍"],
+        "Every word should match, in any case"
+    );
+    assert!(
+        emoji::search("cra").contains(&"🦀"),
+        "Start of a word should match"
+    );
+    assert!(
+        !emoji::search("rab").contains(&"🦀"),
+        "Middle of a word should not match"
+    );
+    assert!(
+        emoji::search("xylophone").is_empty(),
+        "Unknown word should find nothing"
+    );
+}
+
+/// # Test: Recent Emoji Keep the Latest First
+///
+/// ## Assertions:
+/// - Picking an emoji again moves it to the front without repeating it
+/// - Only the latest emoji are kept
+#[test]
+fn test_recent_emoji_keep_the_latest_first() {
+    let mut recent = Vec::new();
+    emoji::remember(&mut recent, "👍");
+    emoji::remember(&mut recent, "🎉");
+    emoji::remember(&mut recent, "👍");
+    assert_eq!(
+        recent,
+        vec!["👍".to_string(), "🎉".to_string()],
+        "Picked emoji should move to the front"
+    );
+
+    for (emoji, _) in EMOJI.iter().take(MAX_RECENT + 5) {
+        emoji::remember(&mut recent, emoji);
+    }
+    assert_eq!(recent.len(), MAX_RECENT, "Only the latest should be kept");
+    assert_eq!(
+        recent.first().map(String::as_str),
+        Some(EMOJI[MAX_RECENT + 4].0),
+        "Latest should come first"
+    );
+}
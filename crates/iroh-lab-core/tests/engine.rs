@@ -0,0 +1,261 @@
+use futures::StreamExt;
+use iroh_lab_core::config::Config;
+use iroh_lab_core::engine::{ChatEngine, Events};
+use iroh_lab_core::testing::MemoryNetwork;
+use iroh_lab_core::transport::ChatTransport;
+use iroh_lab_core::{ChatMessage, ClientEvent};
+use std::time::Duration;
+
+// How long a message may take to arrive
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+// The next chat message on the stream, skipping the notices of the node
+async fn next_message(events: &mut Events) -> Option<ChatMessage> {
+    let message = async {
+        while let Some(event) = events.next().await {
+            match event {
+                ClientEvent::Message(message) if message.author != "System" => {
+                    return Some(message)
+                }
+                _ => {}
+            }
+        }
+        None
+    };
+    tokio::time::timeout(TIMEOUT, message).await.ok().flatten()
+}
+
+/// # Test: Engines Chat on a Topic
+///
+/// ## Steps:
+/// 1. Start two engines, one creating a topic and the other joining it
+/// 2. Send a message from the creator, and reply from the joiner
+///
+/// ## Assertions:
+/// - Both engines are on the topic
+/// - Each receives the message of the other, under its name, and not its
+///   own
+/// - Sending to a topic the engine is not on fails
+#[tokio::test]
+async fn test_engines_chat_on_a_topic() {
+    let config = Config::default();
+    let creator = ChatEngine::start("alice", &config)
+        .await
+        .expect("Creator should start");
+    let joiner = ChatEngine::start("bot", &config)
+        .await
+        .expect("Joiner should start");
+    let mut creator_events = creator.events().expect("Creator should have events");
+    let mut joiner_events = joiner.events().expect("Joiner should have events");
+
+    let topic = creator
+        .create("book-club")
+        .await
+        .expect("Topic should be created");
+    let joined = joiner
+        .join(topic.ticket.clone())
+        .await
+        .expect("Topic should be joined");
+    assert_eq!(joined.hash, topic.hash, "Joiner should be on the topic");
+    assert_eq!(joined.name, "book-club", "Topic should keep its name");
+    assert_eq!(
+        creator.topics(),
+        std::slice::from_ref(&topic.hash),
+        "Creator should be on the topic"
+    );
+
+    let sent = creator
+        .send(&topic.hash, "!time")
+        .await
+        .expect("Message should be sent");
+    let received = next_message(&mut joiner_events)
+        .await
+        .expect("Joiner should receive the message");
+    assert_eq!(
+        received.id, sent.id,
+        "Joiner should receive the message sent"
+    );
+    assert_eq!(received.author, "alice", "Message should be by the creator");
+
+    let reply = joiner
+        .reply(&received, "It is noon")
+        .await
+        .expect("Reply should be sent");
+    let received = next_message(&mut creator_events)
+        .await
+        .expect("Creator should receive the reply");
+    assert_eq!(
+        received.id, reply.id,
+        "Creator should receive the reply, not its message"
+    );
+    assert_eq!(received.author, "bot", "Reply should be by the joiner");
+
+    assert!(
+        creator.send("unknown", "Hello").await.is_err(),
+        "Sending to another topic should fail"
+    );
+}
+
+/// # Test: Engines Need a Name
+///
+/// ## Steps:
+/// 1. Start an engine with a blank name
+///
+/// ## Assertions:
+/// - It does not start
+#[tokio::test]
+async fn test_engines_need_a_name() {
+    assert!(
+        ChatEngine::start("  ", &Config::default()).await.is_err(),
+        "Engine without a name should not start"
+    );
+}
+
+/// # Test: Engines Chat Over Memory
+///
+/// ## Steps:
+/// 1. Start two engines on transports in memory, one creating a topic and
+///    the other joining it
+/// 2. Send a message from the joiner
+///
+/// ## Assertions:
+/// - The creator learns of the joiner as a neighbor, and from their presence
+/// - The creator receives the message, and the joiner does not
+#[tokio::test]
+async fn test_engines_chat_over_memory() {
+    let network = MemoryNetwork::new();
+    let creator =
+        ChatEngine::with_transport("alice", network.transport(1)).expect("Creator should start");
+    let joiner =
+        ChatEngine::with_transport("bot", network.transport(2)).expect("Joiner should start");
+    let mut creator_events = creator.events().expect("Creator should have events");
+    let mut joiner_events = joiner.events().expect("Joiner should have events");
+
+    let topic = creator
+        .create("book-club")
+        .await
+        .expect("Topic should be created");
+    joiner
+        .join(topic.ticket.clone())
+        .await
+        .expect("Topic should be joined");
+    let Some(ClientEvent::PeerJoined { node_id, .. }) = creator_events.next().await else {
+        panic!("Creator should learn of the joiner as a neighbor");
+    };
+    assert_eq!(
+        Some(node_id),
+        joiner.client().own_node_id(),
+        "Joiner should become a neighbor"
+    );
+    let Some(ClientEvent::Presence(presence)) = creator_events.next().await else {
+        panic!("Creator should learn of the joiner");
+    };
+    assert_eq!(presence.author, "bot", "Joiner should announce their name");
+
+    let sent = joiner
+        .send(&topic.hash, "!time")
+        .await
+        .expect("Message should be sent");
+    let received = next_message(&mut creator_events)
+        .await
+        .expect("Creator should receive the message");
+    assert_eq!(received.id, sent.id, "Creator should receive the message");
+    assert_eq!(
+        received.verified_sender,
+        joiner.client().own_node_id(),
+        "Message should be from the joiner"
+    );
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), next_message(&mut joiner_events))
+            .await
+            .is_err(),
+        "Joiner should not receive its own message"
+    );
+}
+
+/// # Test: Engines Shut Down
+///
+/// ## Steps:
+/// 1. Put two engines in memory on a topic
+/// 2. Shut the creator down
+///
+/// ## Assertions:
+/// - The creator is on no topic, and its events end
+/// - Its messages no longer reach the creator
+#[tokio::test]
+async fn test_engines_shut_down() {
+    let network = MemoryNetwork::new();
+    let creator =
+        ChatEngine::with_transport("alice", network.transport(1)).expect("Creator should start");
+    let joiner =
+        ChatEngine::with_transport("bot", network.transport(2)).expect("Joiner should start");
+    let mut creator_events = creator.events().expect("Creator should have events");
+    let topic = creator
+        .create("book-club")
+        .await
+        .expect("Topic should be created");
+    joiner
+        .join(topic.ticket.clone())
+        .await
+        .expect("Topic should be joined");
+
+    creator.shutdown().await;
+
+    assert!(creator.topics().is_empty(), "Creator should be on no topic");
+    joiner
+        .send(&topic.hash, "Still there?")
+        .await
+        .expect("Joiner should still send");
+    assert!(
+        next_message(&mut creator_events).await.is_none(),
+        "Creator should receive nothing after shutting down"
+    );
+}
+
+/// # Test: Engines See Their Neighbors
+///
+/// ## Steps:
+/// 1. Put two engines in memory on a topic
+/// 2. Shut the joiner down
+///
+/// ## Assertions:
+/// - The creator sees the joiner join, and leave
+#[tokio::test]
+async fn test_engines_see_their_neighbors() {
+    let network = MemoryNetwork::new();
+    let creator =
+        ChatEngine::with_transport("alice", network.transport(1)).expect("Creator should start");
+    let joiner =
+        ChatEngine::with_transport("bot", network.transport(2)).expect("Joiner should start");
+    let joiner_id = joiner.client().own_node_id();
+    let mut events = creator.events().expect("Creator should have events");
+    let topic = creator
+        .create("book-club")
+        .await
+        .expect("Topic should be created");
+
+    joiner
+        .join(topic.ticket.clone())
+        .await
+        .expect("Topic should be joined");
+    joiner.shutdown().await;
+
+    let mut neighbors = Vec::new();
+    while neighbors.len() < 2 {
+        match tokio::time::timeout(TIMEOUT, events.next()).await {
+            Ok(Some(ClientEvent::PeerJoined { node_id, .. })) => {
+                neighbors.push(("joined", Some(node_id)))
+            }
+            Ok(Some(ClientEvent::PeerLeft { node_id, .. })) => {
+                neighbors.push(("left", Some(node_id)))
+            }
+            Ok(Some(_)) => {}
+            _ => break,
+        }
+    }
+    assert_eq!(
+        neighbors,
+        vec![("joined", joiner_id), ("left", joiner_id)],
+        "Creator should see the joiner join and leave"
+    );
+}
@@ -0,0 +1,200 @@
+use futures::StreamExt;
+use iroh_lab_core::client::{ClientEvent, IrohClient};
+use iroh_lab_core::filter::{ContentFilter, FilterAction, Filters};
+use iroh_lab_core::testing::ClientPairFixture;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+// How long messages and edits may take to arrive
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+fn filter(text: &str, action: FilterAction) -> ContentFilter {
+    ContentFilter::parse(text, action).expect("Filter should parse")
+}
+
+/// # Test: Filters Mask or Hide What They Match
+///
+/// ## Steps:
+/// 1. Mask words and an expression on every topic
+/// 2. Hide an expression on one topic
+/// 3. Apply the filters to messages of either topic
+///
+/// ## Assertions:
+/// - Words are masked as whole words in any case, expressions as written
+/// - Messages matching a hiding filter are hidden on its topic only
+/// - Expressions matching nothing at all mask nothing
+/// - Invalid expressions are refused
+#[test]
+fn test_filters_mask_or_hide_what_they_match() {
+    let everywhere = filter("darn, c++, /b[a4]d+/, /x*/", FilterAction::Mask);
+    assert_eq!(
+        everywhere.patterns,
+        ["darn", "c++", "/b[a4]d+/", "/x*/"],
+        "Patterns should be split at commas"
+    );
+    let topics = BTreeMap::from([(
+        "moderated".to_string(),
+        filter("/sp[a@]m/", FilterAction::Hide),
+    )]);
+    let filters = Filters::new(&everywhere, &topics).expect("Filters should compile");
+
+    assert_eq!(
+        filters
+            .apply("open", "Darn, darning is b4dd in C++")
+            .as_deref(),
+        Some("****, darning is **** in ***"),
+        "Words and expressions should be masked"
+    );
+    assert_eq!(
+        filters.apply("open", "Buy spam").as_deref(),
+        Some("Buy spam"),
+        "Hiding filters of other topics should not apply"
+    );
+    assert_eq!(
+        filters.apply("moderated", "Buy sp@m"),
+        None,
+        "Matching messages should be hidden on the topic"
+    );
+    assert_eq!(
+        filters.apply("moderated", "darn").as_deref(),
+        Some("****"),
+        "Filters of every topic should apply as well"
+    );
+    assert!(
+        ContentFilter::parse("/(unclosed/", FilterAction::Mask).is_err(),
+        "Invalid expressions should be refused"
+    );
+}
+
+/// # Test: Commas Within Expressions Do Not Split Them
+///
+/// ## Assertions:
+/// - A comma within an expression belongs to it
+/// - An escaped slash does not end an expression
+/// - Expressions with commas match as written
+/// - Unclosed expressions are refused
+#[test]
+fn test_commas_within_expressions_do_not_split_them() {
+    let filter = filter(r"darn, /a{1,3}h/, /x\/y,z/, and/or", FilterAction::Mask);
+    assert_eq!(
+        filter.patterns,
+        ["darn", "/a{1,3}h/", r"/x\/y,z/", "and/or"],
+        "Patterns should be split at commas outside of expressions"
+    );
+
+    let filters = Filters::new(&filter, &BTreeMap::new()).expect("Filters should compile");
+    assert_eq!(
+        filters.apply("open", "aah, aaaah").as_deref(),
+        Some("***, a****"),
+        "Expressions with commas should match as written"
+    );
+    assert!(
+        ContentFilter::parse("darn, /a{1,3", FilterAction::Mask).is_err(),
+        "Unclosed expressions should be refused"
+    );
+}
+
+/// # Test: Clients Filter What Arrives
+///
+/// ## Steps:
+/// 1. Connect two clients, the joiner masking a word everywhere and hiding
+///    an expression on the topic
+/// 2. Let the creator send a message to mask, one to hide and a clean one
+/// 3. Once it arrives, let the creator edit the clean message to content to
+///    hide, then to content to mask
+///
+/// ## Assertions:
+/// - The message to mask arrives masked
+/// - The message to hide does not arrive
+/// - The edit to hide is dropped, and the edit to mask arrives masked
+#[test]
+fn test_clients_filter_what_arrives() {
+    let rt = Runtime::new().unwrap();
+
+    rt.block_on(async {
+        let pair = ClientPairFixture::new("filtered-topic")
+            .await
+            .expect("Failed to connect the clients");
+        let topics = BTreeMap::from([(
+            pair.joiner_hash.clone(),
+            filter("/sp[a@]m/", FilterAction::Hide),
+        )]);
+        let filters = Filters::new(&filter("heck", FilterAction::Mask), &topics)
+            .expect("Filters should compile");
+        pair.joiner.set_filters(filters);
+
+        let mut events = IrohClient::subscribe(Some(&pair.joiner_hash));
+        let masked = pair
+            .creator
+            .send_message("alice".to_string(), "What the heck".to_string(), 1)
+            .await
+            .expect("Message should be sent");
+        let hidden = pair
+            .creator
+            .send_message("alice".to_string(), "Cheap spam".to_string(), 2)
+            .await
+            .expect("Message should be sent");
+        let clean = pair
+            .creator
+            .send_message("alice".to_string(), "All good".to_string(), 3)
+            .await
+            .expect("Message should be sent");
+
+        // Whatever arrives before the clean message is kept, as it may
+        // include the message to hide
+        let mut contents = Vec::new();
+        let arrival = tokio::time::timeout(TIMEOUT, async {
+            while let Some(event) = events.next().await {
+                if let ClientEvent::Message(message) = event {
+                    contents.push((message.id.clone(), message.content));
+                    if message.id == clean.id {
+                        break;
+                    }
+                }
+            }
+        })
+        .await;
+        assert!(arrival.is_ok(), "The clean message should arrive");
+
+        // Edits are only sent while connected, unlike messages
+        pair.creator
+            .edit_message(clean.id.clone(), "More spam".to_string())
+            .await
+            .expect("Edit should be sent");
+        pair.creator
+            .edit_message(clean.id.clone(), "Heck yes".to_string())
+            .await
+            .expect("Edit should be sent");
+        let mut edits = Vec::new();
+        let arrival = tokio::time::timeout(TIMEOUT, async {
+            while let Some(event) = events.next().await {
+                if let ClientEvent::Edit { edit, .. } = event {
+                    edits.push(edit.new_content);
+                    if edits.last().is_some_and(|content| content.ends_with("yes")) {
+                        break;
+                    }
+                }
+            }
+        })
+        .await;
+        assert!(arrival.is_ok(), "The edit to mask should arrive");
+
+        assert!(
+            contents.contains(&(masked.id.clone(), "What the ****".to_string())),
+            "The message to mask should arrive masked"
+        );
+        assert!(
+            !contents.iter().any(|(id, _)| *id == hidden.id),
+            "The message to hide should not arrive"
+        );
+        assert_eq!(
+            edits,
+            ["**** yes"],
+            "Only the edit to mask should arrive, masked"
+        );
+
+        pair.joiner.shutdown().await;
+        pair.creator.shutdown().await;
+    });
+}
@@ -0,0 +1,172 @@
+#![cfg(feature = "gateway")]
+
+use futures::{SinkExt, StreamExt};
+use iroh_lab_core::config::MessageLimit;
+use iroh_lab_core::gateway::{Gateway, GatewayFrame};
+use iroh_lab_core::testing;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::{Error, Message};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+// How long a frame may take to arrive
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+type Page = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+// The next frame the gateway sent to the page
+async fn next_frame(page: &mut Page) -> GatewayFrame {
+    loop {
+        let frame = tokio::time::timeout(TIMEOUT, page.next())
+            .await
+            .expect("Frame should arrive")
+            .expect("Page should stay connected")
+            .expect("Frame should be received");
+        if let Message::Text(text) = frame {
+            return serde_json::from_str(&text).expect("Frame should parse");
+        }
+    }
+}
+
+/// # Test: Pages Need the Token and a Topic
+///
+/// ## Steps:
+/// 1. Start a gateway for a client on a topic
+/// 2. Route requests without the token, with another, for an unknown topic
+///    and for the topic
+///
+/// ## Assertions:
+/// - Requests without the right token are unauthorized
+/// - Unknown topics are not found
+/// - The topic is served with the token, among other parameters
+#[tokio::test]
+async fn test_pages_need_the_token_and_a_topic() {
+    let client = testing::initialized_client()
+        .await
+        .expect("Client should start");
+    let (_, _, topic_hash) = client
+        .create_topic("book-club".to_string())
+        .await
+        .expect("Topic should be created");
+    let gateway = Gateway::new(client, "host".to_string(), MessageLimit::default());
+    let path = format!("/{}", topic_hash);
+    let token = format!("token={}", gateway.token());
+
+    assert_eq!(
+        gateway.route(&path, None),
+        Err(StatusCode::UNAUTHORIZED),
+        "Request without token should be unauthorized"
+    );
+    assert_eq!(
+        gateway.route(&path, Some("token=guess")),
+        Err(StatusCode::UNAUTHORIZED),
+        "Request with another token should be unauthorized"
+    );
+    assert_eq!(
+        gateway.route("/unknown", Some(&token)),
+        Err(StatusCode::NOT_FOUND),
+        "Unknown topic should not be found"
+    );
+    assert_eq!(
+        gateway.route(&path, Some(&format!("theme=dark&{}", token))),
+        Ok(topic_hash),
+        "Topic should be served with the token"
+    );
+}
+
+/// # Test: Pages Chat Through the Gateway
+///
+/// ## Steps:
+/// 1. Serve a topic through a gateway
+/// 2. Connect a page with a wrong token, then with the right one
+/// 3. Send a message, an empty one and an invalid frame from the page
+///
+/// ## Assertions:
+/// - The page with the wrong token is refused
+/// - The page is told its topic, and receives the message it sent under
+///   its author
+/// - Empty messages and invalid frames are answered with errors
+#[tokio::test]
+async fn test_pages_chat_through_the_gateway() {
+    let client = testing::initialized_client()
+        .await
+        .expect("Client should start");
+    let (_, _, topic_hash) = client
+        .create_topic("book-club".to_string())
+        .await
+        .expect("Topic should be created");
+    let gateway = Arc::new(Gateway::new(
+        client,
+        "host".to_string(),
+        MessageLimit::default(),
+    ));
+    let token = gateway.token().to_string();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(gateway.serve(listener));
+
+    let refused = tokio_tungstenite::connect_async(format!("ws://{}/{}", addr, topic_hash)).await;
+    assert!(
+        matches!(refused, Err(Error::Http(response)) if response.status() == StatusCode::UNAUTHORIZED),
+        "Page without the token should be refused"
+    );
+
+    let (mut page, _) =
+        tokio_tungstenite::connect_async(format!("ws://{}/{}?token={}", addr, topic_hash, token))
+            .await
+            .expect("Page should connect");
+    match next_frame(&mut page).await {
+        GatewayFrame::Topic {
+            topic_name,
+            topic_hash: hash,
+        } => {
+            assert_eq!(
+                topic_name, "book-club",
+                "Page should be told the topic name"
+            );
+            assert_eq!(hash, topic_hash, "Page should be told the topic hash");
+        }
+        frame => panic!("Page should be told its topic first, not {:?}", frame),
+    }
+
+    page.send(Message::Text(
+        r#"{"type":"send","content":"Hello from the web","author":"carol"}"#.to_string(),
+    ))
+    .await
+    .unwrap();
+    page.send(Message::Text(
+        r#"{"type":"send","content":"  "}"#.to_string(),
+    ))
+    .await
+    .unwrap();
+    page.send(Message::Text(r#"{"type":"leave"}"#.to_string()))
+        .await
+        .unwrap();
+
+    let mut message = None;
+    let mut errors = 0;
+    while message.is_none() || errors < 2 {
+        match next_frame(&mut page).await {
+            GatewayFrame::Message(received) if received.author != "System" => {
+                message = Some(received)
+            }
+            GatewayFrame::Error { .. } => errors += 1,
+            _ => {}
+        }
+    }
+    let message = message.unwrap();
+    assert_eq!(
+        message.content, "Hello from the web",
+        "Page should receive the message it sent"
+    );
+    assert_eq!(
+        message.author, "carol",
+        "Message should be under its author"
+    );
+    assert_eq!(
+        message.topic_hash, topic_hash,
+        "Message should be on the topic"
+    );
+}
@@ -0,0 +1,350 @@
+use chrono::{DateTime, Utc};
+use iroh::{NodeId, SecretKey};
+use iroh_gossip::proto::TopicId;
+use iroh_lab_core::blobs::BlobHash;
+use iroh_lab_core::calendar::RsvpResponse;
+use iroh_lab_core::client::{
+    Ack, Allowlist, Attachment, Avatar, CalendarEvent, ChatMessage, Heartbeat, MessageDelete,
+    MessageEdit, Presence, Profile, Rsvp, Snapshot, SyncRequest, TaskUpdate,
+};
+use iroh_lab_core::crypto::TopicKey;
+use iroh_lab_core::profile::UserProfile;
+use iroh_lab_core::roster::PresenceStatus;
+use iroh_lab_core::tasks::TaskChange;
+use iroh_lab_core::testing::ChatMessageBuilder;
+use iroh_lab_core::wire::{self, Compression, Envelope, Payload, WIRE_VERSION};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+// Fixtures are frames as written by each wire version, stored in
+// tests/fixtures/wire. Fixtures of earlier versions must never be changed:
+// they are what peers running those releases still send.
+
+fn fixture_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/wire")
+}
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    std::fs::read(fixture_dir().join(name))
+        .unwrap_or_else(|e| panic!("Failed to read fixture {}: {}", name, e))
+}
+
+fn decode_fixture(name: &str) -> Envelope {
+    wire::decode(&read_fixture(name), &topic_key())
+        .unwrap_or_else(|e| panic!("Failed to decode fixture {}: {}", name, e))
+}
+
+fn secret_key() -> SecretKey {
+    SecretKey::from_bytes(&[1; 32])
+}
+
+fn topic_key() -> TopicKey {
+    TopicKey::derive(&[2; 32], &TopicId::from_bytes([3; 32]))
+}
+
+fn fixture_time() -> DateTime<Utc> {
+    "2025-01-01T12:00:00Z".parse().unwrap()
+}
+
+fn fixture_message(lamport: u64) -> ChatMessage {
+    ChatMessageBuilder::new("golden-message")
+        .content("Hello from the past")
+        .topic("golden-topic")
+        .timestamp(fixture_time())
+        .sequence(3)
+        .lamport(lamport)
+        .build()
+}
+
+// The payloads written as current version fixtures, by file name
+fn current_fixtures() -> Vec<(&'static str, Payload, Compression)> {
+    let mut long_message = fixture_message(7);
+    long_message.content = "Hello from the past! ".repeat(40);
+
+    vec![
+        (
+            "v5-chat.bin",
+            Payload::Chat(fixture_message(7)),
+            Compression::Never,
+        ),
+        (
+            "v5-chat-compressed.bin",
+            Payload::Chat(long_message),
+            Compression::Auto,
+        ),
+        (
+            "v5-edit.bin",
+            Payload::Edit(MessageEdit {
+                target_id: "golden-message".to_string(),
+                topic_hash: "golden-topic".to_string(),
+                new_content: "Hello again".to_string(),
+                edited_at: fixture_time(),
+            }),
+            Compression::Never,
+        ),
+        (
+            "v5-heartbeat.bin",
+            Payload::Heartbeat(Heartbeat {
+                topic_hash: "golden-topic".to_string(),
+                sent_at: fixture_time(),
+            }),
+            Compression::Never,
+        ),
+        (
+            "v5-delete.bin",
+            Payload::Delete(MessageDelete {
+                target_id: "golden-message".to_string(),
+                topic_hash: "golden-topic".to_string(),
+                deleted_at: fixture_time(),
+            }),
+            Compression::Never,
+        ),
+        (
+            "v5-sync-request.bin",
+            Payload::SyncRequest(SyncRequest {
+                topic_hash: "golden-topic".to_string(),
+                since: fixture_time(),
+            }),
+            Compression::Never,
+        ),
+        (
+            "v5-presence.bin",
+            Payload::Presence(Presence {
+                topic_hash: "golden-topic".to_string(),
+                author: "alice".to_string(),
+                node_id: secret_key().public(),
+                status: PresenceStatus::Away,
+            }),
+            Compression::Never,
+        ),
+        (
+            "v5-ack.bin",
+            Payload::Ack(Ack {
+                topic_hash: "golden-topic".to_string(),
+                message_id: "golden-message".to_string(),
+            }),
+            Compression::Never,
+        ),
+        (
+            "v5-profile.bin",
+            Payload::Profile(Profile {
+                topic_hash: "golden-topic".to_string(),
+                node_id: secret_key().public(),
+                profile: UserProfile {
+                    display_name: "Alice".to_string(),
+                    status_line: "Reviewing fixtures".to_string(),
+                    avatar: "🦀".to_string(),
+                },
+            }),
+            Compression::Never,
+        ),
+        (
+            "v5-avatar.bin",
+            Payload::Avatar(Avatar {
+                topic_hash: "golden-topic".to_string(),
+                node_id: secret_key().public(),
+                image: Some(BlobHash::of(b"golden avatar")),
+            }),
+            Compression::Never,
+        ),
+        (
+            "v5-snapshot.bin",
+            Payload::Snapshot(Snapshot {
+                topic_hash: "golden-topic".to_string(),
+                node_id: secret_key().public(),
+                recipient: SecretKey::from_bytes(&[4; 32]).public(),
+                blob: BlobHash::of(b"golden snapshot"),
+            }),
+            Compression::Never,
+        ),
+        (
+            "v5-task.bin",
+            Payload::Task(TaskUpdate {
+                topic_hash: "golden-topic".to_string(),
+                message_id: "golden-message".to_string(),
+                change: TaskChange::Assign(Some(secret_key().public())),
+                lamport: 7,
+            }),
+            Compression::Never,
+        ),
+        (
+            "v5-event.bin",
+            Payload::Event(CalendarEvent {
+                topic_hash: "golden-topic".to_string(),
+                message_id: "golden-message".to_string(),
+                title: "Golden meetup".to_string(),
+                starts_at: fixture_time(),
+                ends_at: fixture_time() + chrono::Duration::hours(2),
+                location: "Golden hall".to_string(),
+            }),
+            Compression::Never,
+        ),
+        (
+            "v5-rsvp.bin",
+            Payload::Rsvp(Rsvp {
+                topic_hash: "golden-topic".to_string(),
+                message_id: "golden-message".to_string(),
+                response: RsvpResponse::Accept,
+                lamport: 8,
+            }),
+            Compression::Never,
+        ),
+        (
+            "v5-attachment.bin",
+            Payload::Attachment(Attachment {
+                topic_hash: "golden-topic".to_string(),
+                node_id: secret_key().public(),
+                name: "golden.txt".to_string(),
+                size: 6,
+                blob: BlobHash::of(b"golden attachment"),
+            }),
+            Compression::Never,
+        ),
+        (
+            "v5-allowlist.bin",
+            Payload::Allowlist(Allowlist {
+                topic_hash: "golden-topic".to_string(),
+                members: Some(BTreeSet::from([secret_key().public()])),
+                lamport: 9,
+            }),
+            Compression::Never,
+        ),
+    ]
+}
+
+fn assert_fixture_message(envelope: &Envelope, lamport: u64) {
+    let Payload::Chat(decoded) = &envelope.payload else {
+        panic!("Expected a chat payload");
+    };
+    let expected = fixture_message(lamport);
+    assert_eq!(decoded.id, expected.id, "Message id should match");
+    assert_eq!(decoded.author, expected.author, "Author should match");
+    assert_eq!(
+        decoded.timestamp, expected.timestamp,
+        "Timestamp should match"
+    );
+    assert_eq!(
+        decoded.topic_hash, expected.topic_hash,
+        "Topic should match"
+    );
+    assert_eq!(decoded.sequence, expected.sequence, "Sequence should match");
+    assert_eq!(decoded.lamport, lamport, "Lamport timestamp should match");
+}
+
+fn signer() -> NodeId {
+    secret_key().public()
+}
+
+/// # Test: Every Wire Version Has a Fixture
+///
+/// This test makes sure a fixture is added whenever the wire version is
+/// bumped.
+///
+/// ## Assertions:
+/// - A fixture exists for each version up to the current one
+#[test]
+fn test_every_wire_version_has_a_fixture() {
+    let names: Vec<String> = std::fs::read_dir(fixture_dir())
+        .expect("Failed to read fixture directory")
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+
+    for version in 0..=WIRE_VERSION {
+        let prefix = format!("v{}-", version);
+        assert!(
+            names.iter().any(|name| name.starts_with(&prefix)),
+            "Wire version {} should have a fixture",
+            version
+        );
+    }
+}
+
+/// # Test: Historical Fixtures Still Decode
+///
+/// This test verifies that frames written by every earlier encrypted wire
+/// version are still understood.
+///
+/// ## Assertions:
+/// - Each fixture decodes with its own version
+/// - Each fixture names the signing node as sender
+/// - Chat messages before version 4 decode with Lamport timestamp 0
+#[test]
+fn test_historical_fixtures_still_decode() {
+    let cases = [("v3-unordered.bin", 3, 0), ("v4-unflagged.bin", 4, 7)];
+
+    for (name, version, lamport) in cases {
+        let envelope = decode_fixture(name);
+        assert_eq!(envelope.version, version, "{} version should match", name);
+        assert_eq!(envelope.sender, signer(), "{} sender should match", name);
+        assert_fixture_message(&envelope, lamport);
+    }
+}
+
+/// # Test: Unencrypted Fixtures Are Refused
+///
+/// This test verifies that frames written by wire versions predating
+/// encryption are refused, as the topic key does not protect them.
+///
+/// ## Assertions:
+/// - The legacy JSON, unsigned and plaintext fixtures are rejected
+#[test]
+fn test_unencrypted_fixtures_are_refused() {
+    for name in ["v0-legacy-json.bin", "v1-unsigned.bin", "v2-plaintext.bin"] {
+        assert!(
+            wire::decode(&read_fixture(name), &topic_key()).is_err(),
+            "{} should be rejected",
+            name
+        );
+    }
+}
+
+/// # Test: Current Fixtures Still Decode
+///
+/// This test verifies that every payload kind written by the current wire
+/// version decodes to what was encoded.
+///
+/// ## Assertions:
+/// - Each fixture decodes with the current version and the signing node
+/// - Each fixture carries the payload it was generated from
+#[test]
+fn test_current_fixtures_still_decode() {
+    for (name, payload, _) in current_fixtures() {
+        let envelope = decode_fixture(name);
+        assert_eq!(
+            envelope.version, WIRE_VERSION,
+            "{} version should match",
+            name
+        );
+        assert_eq!(envelope.sender, signer(), "{} sender should match", name);
+
+        // Payloads have no equality, compare their encodings instead
+        assert_eq!(
+            postcard::to_stdvec(&envelope.payload).unwrap(),
+            postcard::to_stdvec(&payload).unwrap(),
+            "{} payload should match",
+            name
+        );
+    }
+}
+
+/// # Test: Generate Current Fixtures
+///
+/// Writes the missing fixtures of the current wire version. Run it after
+/// bumping the wire version or appending a payload variant:
+///
+/// `cargo test --test golden -- --ignored generate_current_fixtures`
+///
+/// Never rename or regenerate fixtures of earlier versions.
+#[test]
+#[ignore]
+fn generate_current_fixtures() {
+    for (name, payload, compression) in current_fixtures() {
+        // Encryption is randomized, existing fixtures would change needlessly
+        if fixture_dir().join(name).exists() {
+            continue;
+        }
+        let bytes = wire::encode_with(&payload, &secret_key(), &topic_key(), compression)
+            .expect("Failed to encode fixture");
+        std::fs::write(fixture_dir().join(name), bytes).expect("Failed to write fixture");
+    }
+}
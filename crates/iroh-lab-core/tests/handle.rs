@@ -0,0 +1,78 @@
+use iroh_lab_core::client::IrohClient;
+use iroh_lab_core::handle::ClientHandle;
+
+/// # Test: Handles Share the Client
+///
+/// ## Steps:
+/// 1. Run a client behind a handle, and initialize its network through a
+///    clone of the handle
+/// 2. Create two topics through the handle, and switch back to the first
+///    through the clone
+/// 3. Send a message through the handle
+///
+/// ## Assertions:
+/// - Both handles see the node, both topics and the active topic
+/// - The message goes to the topic switched to
+/// - Requests fail once the client is shut down
+#[tokio::test]
+async fn test_handles_share_the_client() {
+    let handle = ClientHandle::spawn(IrohClient::new());
+    let other = handle.clone();
+
+    let node_id = other
+        .initialize_network()
+        .await
+        .expect("Network should initialize");
+    let (_, _, first) = handle
+        .create_topic("first".to_string())
+        .await
+        .expect("First topic should be created");
+    let (_, _, second) = handle
+        .create_topic("second".to_string())
+        .await
+        .expect("Second topic should be created");
+    assert_eq!(
+        other.session().node_id,
+        Some(node_id),
+        "Both handles should see the node"
+    );
+    assert_eq!(
+        other.session().topic_hash.as_ref(),
+        Some(&second),
+        "The topic created last should be active for both handles"
+    );
+
+    other
+        .switch_topic(first.clone())
+        .await
+        .expect("Switching should succeed");
+    let message = handle
+        .send_message("alice".to_string(), "Hello".to_string(), 1)
+        .await
+        .expect("Message should be sent");
+
+    assert_eq!(
+        message.topic_hash, first,
+        "The message should go to the topic switched to"
+    );
+    let session = handle.session();
+    assert_eq!(
+        session.subscribed_topics.get("first"),
+        Some(&first),
+        "The first topic should be subscribed"
+    );
+    assert_eq!(
+        session.subscribed_topics.get("second"),
+        Some(&second),
+        "The second topic should be subscribed"
+    );
+
+    other.shutdown().await;
+    assert!(
+        handle
+            .send_message("alice".to_string(), "Anybody?".to_string(), 2)
+            .await
+            .is_err(),
+        "Sending after shutdown should fail"
+    );
+}
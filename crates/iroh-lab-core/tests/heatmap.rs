@@ -0,0 +1,59 @@
+use chrono::{FixedOffset, TimeZone, Utc, Weekday};
+use iroh_lab_core::heatmap::{ActivityHeatmap, LEVELS};
+
+/// # Test: Messages Are Counted by Local Day and Hour
+///
+/// ## Steps:
+/// 1. Count messages sent late on a Sunday in UTC, viewed two hours east
+/// 2. Count more messages on a Tuesday afternoon
+///
+/// ## Assertions:
+/// - Messages are counted at their local day and hour
+/// - The busiest hour is the one with the most messages
+/// - The busiest cell takes the darkest shade, empty cells none
+#[test]
+fn test_messages_are_counted_by_local_day_and_hour() {
+    let east = FixedOffset::east_opt(2 * 3600).unwrap();
+    // Sunday 23:30 UTC is Monday 01:30 two hours east
+    let sunday_night = Utc.with_ymd_and_hms(2026, 10, 11, 23, 30, 0).unwrap();
+    let tuesday = Utc.with_ymd_and_hms(2026, 10, 13, 12, 15, 0).unwrap();
+    let heatmap = ActivityHeatmap::of([sunday_night, tuesday, tuesday, tuesday, tuesday], &east);
+
+    assert_eq!(
+        heatmap.count(Weekday::Mon, 1),
+        1,
+        "Message should count at its local day and hour"
+    );
+    assert_eq!(
+        heatmap.count(Weekday::Sun, 23),
+        0,
+        "Message should not count at its UTC day and hour"
+    );
+    assert_eq!(heatmap.total(), 5, "Every message should be counted");
+    assert_eq!(
+        heatmap.busiest(),
+        Some((Weekday::Tue, 14)),
+        "Busiest hour should have the most messages"
+    );
+
+    assert_eq!(
+        heatmap.level(Weekday::Tue, 14),
+        LEVELS - 1,
+        "Busiest cell should take the darkest shade"
+    );
+    assert_eq!(
+        heatmap.level(Weekday::Mon, 1),
+        1,
+        "Quiet cell should take the lightest shade"
+    );
+    assert_eq!(
+        heatmap.level(Weekday::Wed, 9),
+        0,
+        "Empty cell should not be shaded"
+    );
+    assert_eq!(
+        ActivityHeatmap::default().busiest(),
+        None,
+        "Empty heatmap should have no busiest hour"
+    );
+}
@@ -0,0 +1,131 @@
+use chrono::Utc;
+use futures::FutureExt;
+use futures::StreamExt;
+use iroh_lab_core::client::{ChatMessage, ClientEvent, IrohClient, MessageDelete, Subscription};
+use iroh_lab_core::testing::ChatMessageBuilder;
+
+fn remote_message(topic_hash: &str, lamport: u64) -> ChatMessage {
+    ChatMessageBuilder::new(format!("{}-{}", topic_hash, lamport))
+        .author("remote")
+        .content("Hello from afar")
+        .topic(topic_hash)
+        .lamport(lamport)
+        .build()
+}
+
+// Events received so far. Tests share the subscriptions, so each test
+// subscribes to its own topic.
+fn drain(events: &mut Subscription) -> Vec<ClientEvent> {
+    std::iter::from_fn(|| events.next().now_or_never().flatten()).collect()
+}
+
+/// # Test: Injected Events Reach Every Receiver
+///
+/// This test verifies that injected traffic is delivered like traffic from
+/// the network, without waiting.
+///
+/// ## Steps:
+/// 1. Subscribe to a topic like the UI does
+/// 2. Inject a message and a delete
+///
+/// ## Assertions:
+/// - The subscription gets both events right away, in order
+#[test]
+fn test_injected_events_reach_every_receiver() {
+    let topic_hash = "inject-events-topic";
+    let mut events = IrohClient::subscribe(Some(topic_hash));
+    let client = IrohClient::new();
+
+    client.inject_incoming(remote_message(topic_hash, 1));
+    client.inject_event(ClientEvent::Delete {
+        delete: MessageDelete {
+            target_id: format!("{}-1", topic_hash),
+            topic_hash: topic_hash.to_string(),
+            deleted_at: Utc::now(),
+        },
+        sender: None,
+    });
+
+    let events = drain(&mut events);
+    assert_eq!(events.len(), 2, "Both events should be delivered");
+    assert!(
+        matches!(&events[0], ClientEvent::Message(message) if message.lamport == 1),
+        "Message should be delivered first"
+    );
+    assert!(
+        matches!(&events[1], ClientEvent::Delete { .. }),
+        "Delete should be delivered second"
+    );
+}
+
+/// # Test: Client Events Reach Only Its Own Receivers
+///
+/// ## Steps:
+/// 1. Subscribe to the events of two clients, and to those of the process
+/// 2. Inject a message into the first client
+///
+/// ## Assertions:
+/// - The events of the first client and of the process get the message
+/// - The events of the second client get nothing
+#[test]
+fn test_client_events_reach_only_its_own_receivers() {
+    let topic_hash = "inject-client-topic";
+    let mut everywhere = IrohClient::subscribe(Some(topic_hash));
+    let receiving = IrohClient::new();
+    let other = IrohClient::new();
+    let mut received = receiving.events(Some(topic_hash));
+    let mut missed = other.events(Some(topic_hash));
+
+    receiving.inject_incoming(remote_message(topic_hash, 1));
+
+    assert_eq!(
+        drain(&mut received).len(),
+        1,
+        "The receiving client should get the message"
+    );
+    assert_eq!(
+        drain(&mut everywhere).len(),
+        1,
+        "The process should get the message"
+    );
+    assert!(
+        drain(&mut missed).is_empty(),
+        "The other client should get nothing"
+    );
+}
+
+/// # Test: Injected Messages Advance the Clock
+///
+/// This test verifies that a message sent after an injected one is ordered
+/// after it, as it would be after a message from the network.
+///
+/// ## Steps:
+/// 1. Create a topic
+/// 2. Inject a message with a Lamport timestamp far ahead
+/// 3. Send a message
+///
+/// ## Assertions:
+/// - The sent message is stamped after the injected one
+#[tokio::test]
+async fn test_injected_messages_advance_the_clock() {
+    let client = IrohClient::new();
+    client
+        .initialize_network()
+        .await
+        .expect("Failed to initialize network");
+    let (_, _, topic_hash) = client
+        .create_topic("inject-clock-topic".to_string())
+        .await
+        .expect("Failed to create topic");
+
+    client.inject_incoming(remote_message(&topic_hash, 41));
+    let sent = client
+        .send_message("local".to_string(), "Hi".to_string(), 1)
+        .await
+        .expect("Failed to send message");
+
+    assert!(
+        sent.lamport > 41,
+        "Sent message should be ordered after the injected one"
+    );
+}
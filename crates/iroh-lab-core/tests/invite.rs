@@ -0,0 +1,111 @@
+use iroh::protocol::Router;
+use iroh::{Endpoint, SecretKey};
+use iroh_lab_core::invite::{self, Invite, InviteProtocol, Invites, INVITE_ALPN, INVITE_TTL};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// # Test: Invites Are Redeemed Once Before They Expire
+///
+/// ## Steps:
+/// 1. Create invites and format and parse one
+/// 2. Redeem them repeatedly and after they expired
+///
+/// ## Assertions:
+/// - An invite survives its textual form
+/// - An invite reveals its ticket only once
+/// - An expired invite reveals nothing
+#[test]
+fn test_invites_are_redeemed_once_before_they_expire() {
+    let inviter = SecretKey::generate(rand::rngs::OsRng).public();
+    let now = Instant::now();
+    let mut invites = Invites::default();
+
+    let invite = invites.create(inviter, "ticket-secret".to_string(), now);
+    let text = invite.to_string();
+    assert!(
+        text.starts_with("invite-"),
+        "Invite should have its own prefix"
+    );
+    assert_eq!(
+        text.parse::<Invite>().expect("Invite should parse"),
+        invite,
+        "Invite should survive its textual form"
+    );
+    assert!(
+        "ticket-secret".parse::<Invite>().is_err(),
+        "Ticket should not parse as an invite"
+    );
+
+    assert_eq!(
+        invites.redeem(&invite.secret, now),
+        Some("ticket-secret".to_string()),
+        "Invite should reveal its ticket"
+    );
+    assert_eq!(
+        invites.redeem(&invite.secret, now),
+        None,
+        "Invite should only be redeemed once"
+    );
+
+    let expiring = invites.create(inviter, "ticket-secret".to_string(), now);
+    let later = now + INVITE_TTL + Duration::from_secs(1);
+    assert_eq!(invites.pending(later), 0, "Invite should expire");
+    assert_eq!(
+        invites.redeem(&expiring.secret, later),
+        None,
+        "Expired invite should reveal nothing"
+    );
+}
+
+/// # Test: Invites Are Redeemed With the Inviting Node
+///
+/// ## Steps:
+/// 1. Start a node handing out an invite
+/// 2. Redeem it twice from a second node
+///
+/// ## Assertions:
+/// - The first redemption receives the ticket
+/// - The second redemption fails
+#[tokio::test]
+async fn test_invites_are_redeemed_with_the_inviting_node() {
+    let inviting = Endpoint::builder()
+        .bind()
+        .await
+        .expect("Inviting endpoint should bind");
+    let invites = Arc::new(Mutex::new(Invites::default()));
+    let invite = invites.lock().unwrap().create(
+        inviting.node_id(),
+        "ticket-secret".to_string(),
+        Instant::now(),
+    );
+    let router = Router::builder(inviting.clone())
+        .accept(INVITE_ALPN, InviteProtocol::new(invites))
+        .spawn()
+        .await
+        .expect("Router should start");
+
+    let invitee = Endpoint::builder()
+        .bind()
+        .await
+        .expect("Invitee endpoint should bind");
+    invitee
+        .add_node_addr(
+            inviting
+                .node_addr()
+                .await
+                .expect("Inviting address should be known"),
+        )
+        .expect("Inviting address should be added");
+
+    let ticket = invite::redeem(&invitee, &invite)
+        .await
+        .expect("Invite should be redeemed");
+    assert_eq!(ticket, "ticket-secret", "Invite should reveal the ticket");
+    assert!(
+        invite::redeem(&invitee, &invite).await.is_err(),
+        "Redeeming an invite twice should fail"
+    );
+
+    router.shutdown().await.expect("Router should shut down");
+    invitee.close().await;
+}
@@ -0,0 +1,78 @@
+use iroh::SecretKey;
+use iroh_lab_core::config::RelayMode;
+use iroh_lab_core::lan::Announcement;
+use std::collections::BTreeSet;
+
+fn announcement(secret_key: &SecretKey) -> Announcement {
+    Announcement {
+        node_id: secret_key.public(),
+        addrs: BTreeSet::from([
+            "192.168.1.20:51234".parse().unwrap(),
+            "[fe80::1]:51234".parse().unwrap(),
+        ]),
+    }
+}
+
+/// # Test: Announcements Are Signed by the Node They Announce
+///
+/// ## Steps:
+/// 1. Sign an announcement of a node with its key, and verify it
+/// 2. Tamper with the signed announcement
+/// 3. Sign an announcement of a node with the key of another node
+///
+/// ## Assertions:
+/// - The announcement signed by its node is read back unchanged
+/// - Tampered announcements and announcements signed by another node are
+///   refused
+#[test]
+fn test_announcements_are_signed_by_the_node_they_announce() {
+    let secret_key = SecretKey::generate(rand::rngs::OsRng);
+    let announced = announcement(&secret_key);
+
+    let bytes = announced.sign(&secret_key).expect("Failed to sign");
+    assert_eq!(
+        Announcement::verify(&bytes),
+        Ok(announced.clone()),
+        "Announcement should verify unchanged"
+    );
+
+    let mut tampered = bytes.clone();
+    let last = tampered.len() - 1;
+    tampered[last] ^= 1;
+    assert!(
+        Announcement::verify(&tampered).is_err(),
+        "Tampered announcement should be refused"
+    );
+    assert!(
+        Announcement::verify(&bytes[..bytes.len() / 2]).is_err(),
+        "Truncated announcement should be refused"
+    );
+
+    let impostor = SecretKey::generate(rand::rngs::OsRng);
+    let forged = announced.sign(&impostor).expect("Failed to sign");
+    assert!(
+        Announcement::verify(&forged).is_err(),
+        "Announcement signed by another node should be refused"
+    );
+}
+
+/// # Test: Local Network Mode Turns Relays Off
+///
+/// ## Steps:
+/// 1. Convert every relay mode to the mode of the endpoint
+///
+/// ## Assertions:
+/// - Only the default mode uses relays
+#[test]
+fn test_local_network_mode_turns_relays_off() {
+    assert_eq!(
+        iroh::RelayMode::from(RelayMode::Default),
+        iroh::RelayMode::Default,
+        "Default mode should use relays"
+    );
+    assert_eq!(
+        iroh::RelayMode::from(RelayMode::Local),
+        iroh::RelayMode::Disabled,
+        "Local network mode should not use relays"
+    );
+}
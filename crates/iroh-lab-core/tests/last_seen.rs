@@ -0,0 +1,157 @@
+use chrono::{Duration, Utc};
+use iroh::SecretKey;
+use iroh_lab_core::last_seen::{self, ExportFormat, LastSeen};
+
+/// # Test: Only the Latest Sign of Life Is Kept
+///
+/// ## Steps:
+/// 1. Record a node, then an earlier time of the same node under a new name
+/// 2. Record a later time under the new name
+///
+/// ## Assertions:
+/// - Late arrivals do not move the time back
+/// - The name of the latest sign of life is kept
+#[test]
+fn test_only_the_latest_sign_of_life_is_kept() {
+    let node_id = SecretKey::generate(rand::rngs::OsRng).public();
+    let now = Utc::now();
+    let mut last_seen = LastSeen::default();
+
+    last_seen.record(node_id, "alice", now);
+    last_seen.record(node_id, "alicia", now - Duration::minutes(5));
+    let seen = last_seen.get(&node_id).expect("Node should have been seen");
+    assert_eq!(
+        (seen.author.as_str(), seen.at),
+        ("alice", now),
+        "Earlier times should be ignored"
+    );
+
+    last_seen.record(node_id, "alicia", now + Duration::minutes(1));
+    let seen = last_seen.get(&node_id).expect("Node should have been seen");
+    assert_eq!(seen.author, "alicia", "Latest name should be kept");
+}
+
+/// # Test: Last Seen Times Are Kept Between Runs
+///
+/// ## Assertions:
+/// - Saved times load again
+/// - A missing file loads no times
+#[test]
+fn test_last_seen_times_are_kept_between_runs() {
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let path = dir.path().join("iroh-lab").join("last_seen.json");
+    assert_eq!(
+        LastSeen::load_from(&path).expect("Missing file should load"),
+        LastSeen::default(),
+        "Missing file should load no times"
+    );
+
+    let node_id = SecretKey::generate(rand::rngs::OsRng).public();
+    let mut last_seen = LastSeen::default();
+    last_seen.record(node_id, "alice", Utc::now());
+    last_seen.save_to(&path).expect("Times should be saved");
+
+    assert_eq!(
+        LastSeen::load_from(&path).expect("Times should load"),
+        last_seen,
+        "Loaded times should match the saved ones"
+    );
+}
+
+/// # Test: Last Seen Times Read Naturally
+///
+/// ## Assertions:
+/// - Times are rounded down to the largest whole unit
+#[test]
+fn test_last_seen_times_read_naturally() {
+    let now = Utc::now();
+    let cases = [
+        (Duration::seconds(20), "just now"),
+        (Duration::seconds(5 * 60 + 30), "5m ago"),
+        (Duration::hours(3), "3h ago"),
+        (Duration::days(2) + Duration::hours(23), "2d ago"),
+    ];
+    for (before, expected) in cases {
+        assert_eq!(
+            last_seen::ago(now - before, now),
+            expected,
+            "{:?} before should read {}",
+            before,
+            expected
+        );
+    }
+}
+
+/// # Test: Participation Is Kept and Exported Per Topic
+///
+/// ## Steps:
+/// 1. Record two members on a topic, one of them also on another topic
+/// 2. Record a late arrival from before the first sign of life
+/// 3. Export the topic as CSV and JSON
+///
+/// ## Assertions:
+/// - Each topic only lists the members seen on it, the earliest first
+/// - Late arrivals move the first time back, but not the last
+/// - Names with commas are quoted in CSV
+/// - JSON lists the same members
+#[test]
+fn test_participation_is_kept_and_exported_per_topic() {
+    let alice = SecretKey::generate(rand::rngs::OsRng).public();
+    let bob = SecretKey::generate(rand::rngs::OsRng).public();
+    let now = Utc::now();
+    let mut last_seen = LastSeen::default();
+
+    last_seen.record_in("topic", alice, "alice", now);
+    last_seen.record_in("topic", bob, "Bob, the builder", now - Duration::hours(1));
+    last_seen.record_in("other", alice, "alice", now);
+    last_seen.record_in("topic", alice, "al", now - Duration::hours(2));
+
+    let participants = last_seen.participants("topic");
+    let node_ids: Vec<_> = participants.iter().map(|(node_id, _)| *node_id).collect();
+    let (alice_id, bob_id) = (alice.to_string(), bob.to_string());
+    assert_eq!(
+        node_ids,
+        vec![alice_id.as_str(), bob_id.as_str()],
+        "Members should be listed by when they were first seen"
+    );
+    let (_, participation) = participants[0];
+    assert_eq!(
+        (
+            participation.first_seen,
+            participation.last_seen,
+            participation.author.as_str()
+        ),
+        (now - Duration::hours(2), now, "alice"),
+        "Late arrival should only move the first time back"
+    );
+    assert_eq!(
+        last_seen.participants("other").len(),
+        1,
+        "Other topic should only list its own members"
+    );
+
+    let csv = last_seen
+        .export("topic", ExportFormat::Csv)
+        .expect("CSV should export");
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(
+        lines.first(),
+        Some(&"node_id,author,first_seen,last_seen"),
+        "CSV should start with a header"
+    );
+    assert!(
+        lines[2].starts_with(&format!("{},\"Bob, the builder\",", bob)),
+        "Name with a comma should be quoted"
+    );
+
+    let json = last_seen
+        .export("topic", ExportFormat::Json)
+        .expect("JSON should export");
+    let exported: Vec<serde_json::Value> =
+        serde_json::from_str(&json).expect("Export should be JSON");
+    assert_eq!(exported.len(), 2, "JSON should list every member");
+    assert_eq!(
+        exported[1]["author"], "Bob, the builder",
+        "JSON should hold the names"
+    );
+}
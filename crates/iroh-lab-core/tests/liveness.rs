@@ -0,0 +1,131 @@
+use chrono::Utc;
+use iroh::SecretKey;
+use iroh_gossip::proto::TopicId;
+use iroh_lab_core::client::Heartbeat;
+use iroh_lab_core::crypto::TopicKey;
+use iroh_lab_core::liveness::{Liveness, TopicActivity, ACTIVE_WINDOW};
+use iroh_lab_core::wire::{self, Payload};
+use std::time::{Duration, Instant};
+
+fn node(seed: u8) -> iroh::NodeId {
+    SecretKey::from_bytes(&[seed; 32]).public()
+}
+
+/// # Test: Activity Follows the Members Seen
+///
+/// This test verifies how the activity of a topic changes as members are
+/// seen and then go silent.
+///
+/// ## Steps:
+/// 1. Check a topic where nobody was seen
+/// 2. Record a member
+/// 3. Let the active window pass without hearing from it again
+///
+/// ## Assertions:
+/// - A topic without members is quiet
+/// - A topic with a recently seen member is active
+/// - A topic whose members all went silent is partitioned
+#[test]
+fn test_activity_follows_the_members_seen() {
+    let mut liveness = Liveness::default();
+    let start = Instant::now();
+    assert_eq!(
+        liveness.activity(start),
+        TopicActivity::Quiet,
+        "Topic without members should be quiet"
+    );
+
+    liveness.record(node(1), start);
+    assert_eq!(
+        liveness.activity(start + Duration::from_secs(1)),
+        TopicActivity::Active,
+        "Topic with a recently seen member should be active"
+    );
+
+    assert_eq!(
+        liveness.activity(start + ACTIVE_WINDOW),
+        TopicActivity::Partitioned,
+        "Topic whose members went silent should be partitioned"
+    );
+}
+
+/// # Test: Members Are Remembered by Their Latest Sighting
+///
+/// This test verifies the last-seen times kept for each member, which decide
+/// whom to reconnect to first.
+///
+/// ## Assertions:
+/// - An earlier sighting recorded late does not move the last-seen time back
+/// - Only members seen within the active window are active
+/// - Known members are listed most recently seen first
+#[test]
+fn test_members_are_remembered_by_their_latest_sighting() {
+    let mut liveness = Liveness::default();
+    let start = Instant::now();
+    let later = start + ACTIVE_WINDOW;
+
+    liveness.record(node(1), start);
+    liveness.record(node(2), later);
+    liveness.record(node(2), start);
+    assert_eq!(
+        liveness.last_seen(&node(2)),
+        Some(later),
+        "Last-seen time should never move back"
+    );
+    assert_eq!(
+        liveness.last_seen(&node(3)),
+        None,
+        "Unknown member should never have been seen"
+    );
+
+    assert_eq!(
+        liveness.active_members(later),
+        vec![node(2)],
+        "Only the recently seen member should be active"
+    );
+    assert_eq!(
+        liveness.known_members(),
+        vec![node(2), node(1)],
+        "Known members should be listed most recent first"
+    );
+}
+
+/// # Test: Heartbeats Round Trip Over the Wire
+///
+/// This test verifies that heartbeats are signed like any other payload, so
+/// they tell which node is still subscribed.
+///
+/// ## Assertions:
+/// - The decoded payload is the heartbeat that was sent
+/// - The sender of the heartbeat is verified
+#[test]
+fn test_heartbeats_round_trip_over_the_wire() {
+    let secret_key = SecretKey::from_bytes(&[7; 32]);
+    let topic_key = TopicKey::derive(&rand::random(), &TopicId::from_bytes(rand::random()));
+    let heartbeat = Heartbeat {
+        topic_hash: "topic".to_string(),
+        sent_at: Utc::now(),
+    };
+
+    let bytes = wire::encode(
+        &Payload::Heartbeat(heartbeat.clone()),
+        &secret_key,
+        &topic_key,
+    )
+    .expect("Heartbeat should encode");
+    let envelope = wire::decode(&bytes, &topic_key).expect("Heartbeat should decode");
+
+    let Payload::Heartbeat(decoded) = envelope.payload else {
+        panic!("Payload should be a heartbeat");
+    };
+    assert_eq!(
+        decoded.topic_hash, heartbeat.topic_hash,
+        "Topic should match"
+    );
+    assert_eq!(decoded.sent_at, heartbeat.sent_at, "Send time should match");
+    assert_eq!(
+        envelope.sender,
+        secret_key.public(),
+        "Sender should be verified"
+    );
+}
@@ -0,0 +1,112 @@
+use iroh_lab_core::load::LoadRun;
+use iroh_lab_core::testing::member;
+use std::time::{Duration, Instant};
+
+/// # Test: Arrivals Are Counted Against the Receivers
+///
+/// ## Steps:
+/// 1. Send two messages among three clients
+/// 2. Have the first arrive at one client twice and at another, and the
+///    second at one client
+/// 3. Receive a message not sent in the run
+///
+/// ## Assertions:
+/// - The run is complete only once every receiver has every message
+/// - Arrivals at a client that had the message are duplicates, not
+///   deliveries, even while another client misses it
+/// - The missing arrival is lost
+/// - Unknown messages are not counted
+#[test]
+fn test_arrivals_are_counted_against_the_receivers() {
+    let start = Instant::now();
+    let mut load = LoadRun::new(3);
+    load.sent("a", start);
+    load.sent("b", start);
+    assert!(!load.complete(), "Run should wait for the arrivals");
+
+    for (receiver, millis) in [(1, 10), (1, 30)] {
+        assert!(
+            load.received(member(receiver), "a", start + Duration::from_millis(millis)),
+            "Message of the run should be counted"
+        );
+    }
+    assert!(!load.complete(), "Run should wait for the other receiver");
+    load.received(member(2), "a", start + Duration::from_millis(50));
+    load.received(member(1), "b", start + Duration::from_millis(20));
+    assert!(
+        !load.received(member(1), "c", start),
+        "Message of another run should not be counted"
+    );
+    assert!(!load.complete(), "Run should miss an arrival");
+
+    let summary = load.summary(Duration::from_secs(2));
+    assert_eq!(summary.sent, 2, "Both messages should be sent");
+    assert_eq!(summary.expected, 4, "Each should reach two clients");
+    assert_eq!(summary.delivered, 3, "Three arrivals should be delivered");
+    assert_eq!(
+        summary.duplicates, 1,
+        "Second arrival at a client should be a duplicate"
+    );
+    assert_eq!(summary.lost(), 1, "Missing arrival should be lost");
+    assert_eq!(summary.loss(), 0.25, "A quarter should be lost");
+    assert_eq!(summary.throughput(), 1.5, "Throughput should be per second");
+
+    load.received(member(2), "b", start + Duration::from_millis(40));
+    assert!(load.complete(), "Run should be complete");
+}
+
+/// # Test: Latency Percentiles Are Exact
+///
+/// ## Steps:
+/// 1. Have a message arrive at ten clients, 1 to 10 ms after it was sent,
+///    in random order
+///
+/// ## Assertions:
+/// - Percentiles are the arrivals at their rank
+/// - An empty run has no percentiles, and reports nothing arrived
+#[test]
+fn test_latency_percentiles_are_exact() {
+    let start = Instant::now();
+    let mut load = LoadRun::new(11);
+    load.sent("a", start);
+    for (receiver, millis) in [7, 3, 10, 1, 5, 9, 2, 8, 4, 6].into_iter().enumerate() {
+        load.received(
+            member(receiver as u8),
+            "a",
+            start + Duration::from_millis(millis),
+        );
+    }
+
+    let summary = load.summary(Duration::from_secs(1));
+    assert_eq!(
+        summary.percentile(50.0),
+        Some(Duration::from_millis(5)),
+        "Median should be the fifth arrival"
+    );
+    assert_eq!(
+        summary.percentile(95.0),
+        Some(Duration::from_millis(10)),
+        "95th percentile should be the slowest of ten"
+    );
+    assert_eq!(
+        summary.percentile(0.0),
+        Some(Duration::from_millis(1)),
+        "Lowest percentile should be the fastest"
+    );
+    assert!(
+        summary.to_string().contains("median 5.0 ms"),
+        "Summary should report the median"
+    );
+
+    let empty = LoadRun::new(3).summary(Duration::ZERO);
+    assert_eq!(
+        empty.percentile(50.0),
+        None,
+        "Empty run should have no median"
+    );
+    assert_eq!(empty.loss(), 0.0, "Empty run should lose nothing");
+    assert!(
+        empty.to_string().contains("nothing arrived"),
+        "Empty run should report nothing arrived"
+    );
+}
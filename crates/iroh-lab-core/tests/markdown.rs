@@ -0,0 +1,185 @@
+use iroh_lab_core::markdown::{self, Block, BlockKind, Link, Span, SpanStyle};
+
+fn plain(text: &str) -> Span {
+    Span {
+        text: text.to_string(),
+        style: SpanStyle::default(),
+    }
+}
+
+fn styled(text: &str, bold: bool, italic: bool, code: bool) -> Span {
+    Span {
+        text: text.to_string(),
+        style: SpanStyle { bold, italic, code },
+    }
+}
+
+/// # Test: Lines Become Paragraphs, List Items and Quotes
+///
+/// ## Assertions:
+/// - Each non-empty line is a block of its own
+/// - Bullets, numbers and quote markers are recognized and removed
+/// - Markers without a space after them are plain text
+#[test]
+fn test_lines_become_paragraphs_list_items_and_quotes() {
+    let blocks =
+        markdown::parse("Agenda:\n\n- first\n2. second\n> quoted\n-5 degrees\n*not a list*");
+    let kinds: Vec<_> = blocks.iter().map(|block| block.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            BlockKind::Paragraph,
+            BlockKind::Bullet,
+            BlockKind::Numbered(2),
+            BlockKind::Quote,
+            BlockKind::Paragraph,
+            BlockKind::Paragraph,
+        ],
+        "Each line should be a block of its kind"
+    );
+    assert_eq!(
+        blocks[1].spans,
+        vec![plain("first")],
+        "Bullet should be removed from the item"
+    );
+    assert_eq!(
+        blocks[3].spans,
+        vec![plain("quoted")],
+        "Quote marker should be removed"
+    );
+    assert_eq!(
+        blocks[4].spans,
+        vec![plain("-5 degrees")],
+        "Dash without a space should stay"
+    );
+}
+
+/// # Test: Emphasis and Code Are Recognized Within Lines
+///
+/// ## Assertions:
+/// - Bold, italics and code are recognized, also nested
+/// - Code keeps markers inside it as typed
+/// - Unclosed markers, escaped markers and underscores within words stay
+///   plain text
+#[test]
+fn test_emphasis_and_code_are_recognized_within_lines() {
+    let spans = |content: &str| -> Vec<Span> {
+        let blocks: Vec<Block> = markdown::parse(content);
+        blocks.into_iter().flat_map(|block| block.spans).collect()
+    };
+
+    assert_eq!(
+        spans("a **bold *and italic*** `x*y` _too_"),
+        vec![
+            plain("a "),
+            styled("bold ", true, false, false),
+            styled("and italic", true, true, false),
+            plain(" "),
+            styled("x*y", false, false, true),
+            plain(" "),
+            styled("too", false, true, false),
+        ],
+        "Nested emphasis and code should be recognized"
+    );
+    assert_eq!(
+        spans("2 * 3 * 4, **open and snake_case_name"),
+        vec![plain("2 * 3 * 4, **open and snake_case_name")],
+        "Unclosed markers and underscores within words should stay"
+    );
+    assert_eq!(
+        spans(r"\*not italic\*"),
+        vec![plain("*not italic*")],
+        "Escaped markers should stay"
+    );
+}
+
+/// # Test: Content Cannot Reorder the Layout
+///
+/// ## Assertions:
+/// - Bidirectional overrides and control characters are dropped
+/// - Tabs become spaces
+#[test]
+fn test_content_cannot_reorder_the_layout() {
+    let blocks = markdown::parse("evil\u{202e}txt.exe\u{7}\tdone");
+    assert_eq!(
+        blocks[0].spans,
+        vec![plain("eviltxt.exe done")],
+        "Formatting characters should be dropped"
+    );
+}
+
+/// # Test: Words Starting With a Hash Link to Topics
+///
+/// ## Assertions:
+/// - `#name` links to the topic, without punctuation ending the sentence
+/// - Numbers and lone hashes are no links
+/// - Topic names are linked to in lowercase, with dashes for spaces
+#[test]
+fn test_words_starting_with_a_hash_link_to_topics() {
+    assert_eq!(
+        markdown::topic_link("#book-club"),
+        Some("book-club"),
+        "Word should link to the topic"
+    );
+    assert_eq!(
+        markdown::topic_link("#general."),
+        Some("general"),
+        "Punctuation ending the sentence should not be part of the link"
+    );
+    for word in ["#1", "#", "plain", "#a/b", "##"] {
+        assert_eq!(
+            markdown::topic_link(word),
+            None,
+            "Word {:?} should not link to a topic",
+            word
+        );
+    }
+    assert_eq!(
+        markdown::topic_slug(" Book  Club "),
+        "book-club",
+        "Topic name should be linked to in lowercase, with dashes"
+    );
+}
+
+/// # Test: Web Addresses Are Links
+///
+/// ## Assertions:
+/// - `http` and `https` addresses are links, without surrounding punctuation
+/// - Other schemes and incomplete addresses are no links
+/// - Words link to web addresses or topics
+#[test]
+fn test_web_addresses_are_links() {
+    assert_eq!(
+        markdown::web_link("https://example.com/a?b=c"),
+        Some("https://example.com/a?b=c"),
+        "Address should be a link"
+    );
+    assert_eq!(
+        markdown::web_link("(http://example.com)."),
+        Some("http://example.com"),
+        "Brackets and punctuation should not be part of the link"
+    );
+    for word in [
+        "file:///etc/passwd",
+        "javascript:alert(1)",
+        "https://",
+        "example.com",
+    ] {
+        assert_eq!(
+            markdown::web_link(word),
+            None,
+            "Word {:?} should not be a link",
+            word
+        );
+    }
+    assert_eq!(
+        markdown::link("https://example.com"),
+        Some(Link::Web("https://example.com".to_string())),
+        "Address should link to the web"
+    );
+    assert_eq!(
+        markdown::link("#general"),
+        Some(Link::Topic("general".to_string())),
+        "Hash should link to a topic"
+    );
+}
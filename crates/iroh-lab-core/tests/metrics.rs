@@ -0,0 +1,118 @@
+use iroh_lab_core::metrics::{
+    LatencyHistogram, Percentile, Traffic, TrafficPath, LATENCY_BUCKETS_MS, RATE_WINDOW,
+};
+use iroh_lab_core::testing::member;
+use std::time::{Duration, Instant};
+
+/// # Test: Latency Percentiles Come From Their Buckets
+///
+/// ## Steps:
+/// 1. Record nine fast messages and one very slow one
+///
+/// ## Assertions:
+/// - An empty histogram has no percentiles
+/// - The median lies in the bucket of the fast messages
+/// - The slowest message lies above every bucket bound
+#[test]
+fn test_latency_percentiles_come_from_their_buckets() {
+    let mut histogram = LatencyHistogram::default();
+    assert_eq!(
+        histogram.percentile(50.0),
+        None,
+        "Empty histogram should have no median"
+    );
+
+    for _ in 0..9 {
+        histogram.record(Duration::from_millis(30));
+    }
+    histogram.record(Duration::from_secs(60));
+
+    assert_eq!(histogram.count(), 10, "Every message should be counted");
+    assert_eq!(
+        histogram.counts().len(),
+        LATENCY_BUCKETS_MS.len() + 1,
+        "Histogram should have an open bucket"
+    );
+    assert_eq!(
+        histogram.percentile(50.0),
+        Some(Percentile::AtMost(Duration::from_millis(50))),
+        "Median should be in the bucket of the fast messages"
+    );
+    assert_eq!(
+        histogram.percentile(90.0),
+        Some(Percentile::AtMost(Duration::from_millis(50))),
+        "90th percentile should still be a fast message"
+    );
+    assert_eq!(
+        histogram.percentile(100.0),
+        Some(Percentile::Above(Duration::from_secs(10))),
+        "Slowest message should be above every bound"
+    );
+}
+
+/// # Test: Traffic Is Split by How Neighbors Are Reached
+///
+/// ## Steps:
+/// 1. Send a message to a direct and a relayed neighbor
+/// 2. Receive messages from both, and from a neighbor no longer reached
+/// 3. Take the stats right away, then after the rate window
+///
+/// ## Assertions:
+/// - Bytes sent and received are counted once per message
+/// - Bytes are attributed to the path each neighbor is reached on, and
+///   those of unreachable neighbors to neither
+/// - Messages only count towards the rate within the window
+#[test]
+fn test_traffic_is_split_by_how_neighbors_are_reached() {
+    let (direct, relayed, gone) = (member(1), member(2), member(3));
+    let path = |node_id| {
+        if node_id == direct {
+            Some(TrafficPath::Direct)
+        } else if node_id == relayed {
+            Some(TrafficPath::Relayed)
+        } else {
+            None
+        }
+    };
+    let start = Instant::now();
+    let mut traffic = Traffic::default();
+    assert_eq!(
+        traffic.stats(start, 0, path).relayed_share(),
+        None,
+        "No traffic should have no share"
+    );
+
+    traffic.sent(100, [direct, relayed], start);
+    traffic.received(300, direct, start);
+    traffic.received(100, relayed, start);
+    traffic.received(50, gone, start);
+
+    let stats = traffic.stats(start, 2, path);
+    assert_eq!(stats.bytes_sent, 100, "Sent bytes should be counted once");
+    assert_eq!(
+        stats.bytes_received, 450,
+        "Received bytes should be counted"
+    );
+    assert_eq!(stats.peers, 2, "Peers should be reported");
+    assert_eq!(
+        (stats.direct_bytes, stats.relayed_bytes),
+        (400, 200),
+        "Bytes should follow the path of each neighbor"
+    );
+    assert_eq!(
+        stats.relayed_share(),
+        Some(200.0 / 600.0),
+        "Share should leave out unreachable neighbors"
+    );
+    assert_eq!(
+        stats.messages_per_minute, 4,
+        "Every message should count towards the rate"
+    );
+
+    let later = traffic.stats(start + RATE_WINDOW + Duration::from_secs(1), 2, path);
+    assert_eq!(
+        later.messages_per_minute, 0,
+        "Old messages should not count towards the rate"
+    );
+    assert_eq!(later.bytes_received, 450, "Totals should be kept");
+}
@@ -0,0 +1,48 @@
+use iroh_lab_core::notes::{Notes, NOTES_TOPIC_HASH};
+
+/// # Test: Notes Are Kept Between Runs
+///
+/// ## Steps:
+/// 1. Open notes that do not exist yet and write two notes
+/// 2. Open them again and write another note
+///
+/// ## Assertions:
+/// - New notes start out empty
+/// - Written notes load again in order, on the notes topic
+/// - Notes written after reopening follow the earlier ones
+#[test]
+fn test_notes_are_kept_between_runs() {
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let path = dir.path().join("iroh-lab").join("notes.jsonl");
+
+    let (mut notes, written) = Notes::open(&path).expect("New notes should open");
+    assert!(written.is_empty(), "New notes should be empty");
+    notes
+        .add("alice", "Buy milk")
+        .expect("Note should be written");
+    notes
+        .add("alice", "Call Bob")
+        .expect("Note should be written");
+
+    let (mut notes, written) = Notes::open(&path).expect("Notes should reopen");
+    let contents: Vec<&str> = written.iter().map(|note| note.content.as_str()).collect();
+    assert_eq!(
+        contents,
+        vec!["Buy milk", "Call Bob"],
+        "Notes should load in order"
+    );
+    assert!(
+        written
+            .iter()
+            .all(|note| note.topic_hash == NOTES_TOPIC_HASH),
+        "Notes should be on the notes topic"
+    );
+
+    let later = notes
+        .add("alice", "Water plants")
+        .expect("Note should be written");
+    assert!(
+        later.order_key() > written[1].order_key(),
+        "Later notes should follow the earlier ones"
+    );
+}
@@ -0,0 +1,76 @@
+use iroh::protocol::Router;
+use iroh::Endpoint;
+use iroh_lab_core::ping::{self, PingProtocol, Pinger, HIGH_LATENCY, PING_ALPN, PING_TIMEOUT};
+use std::time::Duration;
+
+/// # Test: Neighbors Echo Pings
+///
+/// ## Steps:
+/// 1. Start a node echoing pings
+/// 2. Ping it twice from a second node
+/// 3. Stop pinging it, and ping it again
+///
+/// ## Assertions:
+/// - Every ping is answered within the timeout
+/// - Pinging again after no longer pinging reconnects
+/// - Only latencies above the threshold are high
+#[tokio::test]
+async fn test_neighbors_echo_pings() {
+    let echoing = Endpoint::builder()
+        .bind()
+        .await
+        .expect("Echoing endpoint should bind");
+    let router = Router::builder(echoing.clone())
+        .accept(PING_ALPN, PingProtocol)
+        .spawn()
+        .await
+        .expect("Router should start");
+
+    let pinging = Endpoint::builder()
+        .bind()
+        .await
+        .expect("Pinging endpoint should bind");
+    pinging
+        .add_node_addr(
+            echoing
+                .node_addr()
+                .await
+                .expect("Echoing address should be known"),
+        )
+        .expect("Echoing address should be added");
+    let mut pinger = Pinger::new(pinging.clone());
+
+    for _ in 0..2 {
+        let latency = pinger
+            .ping(echoing.node_id())
+            .await
+            .expect("Ping should be echoed");
+        assert!(
+            latency < PING_TIMEOUT,
+            "Latency {:?} should be within the timeout",
+            latency
+        );
+    }
+
+    pinger.retain(|_| false);
+    assert!(
+        pinger.ping(echoing.node_id()).await.is_ok(),
+        "Pinging again should reconnect"
+    );
+
+    assert!(
+        !ping::is_high(Duration::from_millis(40)),
+        "Short latency should not be high"
+    );
+    assert!(
+        !ping::is_high(HIGH_LATENCY),
+        "Threshold itself should not be high"
+    );
+    assert!(
+        ping::is_high(HIGH_LATENCY + Duration::from_millis(1)),
+        "Latency above the threshold should be high"
+    );
+
+    router.shutdown().await.expect("Router should shut down");
+    pinging.close().await;
+}
@@ -0,0 +1,97 @@
+use iroh::SecretKey;
+use iroh_lab_core::client::{ChatMessage, Profile};
+use iroh_lab_core::profile::{Profiles, UserProfile, MAX_AVATAR_LEN};
+use iroh_lab_core::testing::ChatMessageBuilder;
+
+fn message_from(sender: Option<iroh::NodeId>) -> ChatMessage {
+    let message = ChatMessageBuilder::new("profile-test-message").topic("profile-test-topic");
+    match sender {
+        Some(sender) => message.sent_by(sender).build(),
+        None => message.build(),
+    }
+}
+
+/// # Test: Messages Are Shown Under the Sender's Profile
+///
+/// ## Steps:
+/// 1. Record a profile announced by a node
+/// 2. Look up the profile of a signed and of an unsigned message
+///
+/// ## Assertions:
+/// - Signed messages use the display name of their sender
+/// - Unsigned messages have no profile, whatever name they carry
+/// - A blank display name falls back to the username
+#[test]
+fn test_messages_are_shown_under_the_senders_profile() {
+    let node_id = SecretKey::generate(rand::rngs::OsRng).public();
+    let mut profiles = Profiles::default();
+    let accepted = profiles.update(&Profile {
+        topic_hash: "profile-test-topic".to_string(),
+        node_id,
+        profile: UserProfile {
+            display_name: "Alice Liddell".to_string(),
+            status_line: "Down the rabbit hole".to_string(),
+            avatar: "🐇".to_string(),
+        },
+    });
+    assert!(accepted, "Valid profile should be accepted");
+
+    let signed = message_from(Some(node_id));
+    let profile = profiles
+        .of_sender(&signed)
+        .expect("Signed message should have a profile");
+    assert_eq!(
+        profile.name_or(&signed.author),
+        "Alice Liddell",
+        "Display name should replace the username"
+    );
+    assert!(
+        profiles.of_sender(&message_from(None)).is_none(),
+        "Unsigned message should have no profile"
+    );
+    assert_eq!(
+        UserProfile::default().name_or("alice"),
+        "alice",
+        "Blank display name should fall back to the username"
+    );
+}
+
+/// # Test: Oversized Profiles Are Ignored
+///
+/// ## Assertions:
+/// - A profile with a too long avatar fails validation
+/// - Announcing it does not replace the known profile
+#[test]
+fn test_oversized_profiles_are_ignored() {
+    let node_id = SecretKey::generate(rand::rngs::OsRng).public();
+    let mut profiles = Profiles::default();
+    let profile = UserProfile {
+        display_name: "Alice".to_string(),
+        ..Default::default()
+    };
+    profiles.update(&Profile {
+        topic_hash: "profile-test-topic".to_string(),
+        node_id,
+        profile: profile.clone(),
+    });
+
+    let oversized = UserProfile {
+        avatar: "x".repeat(MAX_AVATAR_LEN + 1),
+        ..Default::default()
+    };
+    assert!(
+        oversized.validate().is_err(),
+        "Oversized avatar should fail validation"
+    );
+    let accepted = profiles.update(&Profile {
+        topic_hash: "profile-test-topic".to_string(),
+        node_id,
+        profile: oversized,
+    });
+    assert!(!accepted, "Oversized profile should be rejected");
+    assert_eq!(
+        profiles.get(&node_id),
+        Some(&profile),
+        "Known profile should be kept"
+    );
+}
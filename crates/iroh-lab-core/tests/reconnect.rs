@@ -0,0 +1,107 @@
+use iroh_lab_core::reconnect::{
+    ReconnectState, Reconnector, INITIAL_DELAY, MAX_ATTEMPTS, MAX_DELAY,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::time::Instant;
+
+/// # Test: Retries Back Off Exponentially
+///
+/// ## Steps:
+/// 1. Fail the connection, then every attempt to restore it
+///
+/// ## Assertions:
+/// - Each delay lies in the upper half of its doubled ceiling, capped at
+///   the longest delay
+/// - The next attempt is due after the delay
+/// - Retrying stops after the last attempt
+#[test]
+fn test_retries_back_off_exponentially() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut reconnector = Reconnector::default();
+    let now = Instant::now();
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let ceiling = (INITIAL_DELAY * 2u32.pow(attempt - 1)).min(MAX_DELAY);
+        match reconnector.failed(now, &mut rng) {
+            ReconnectState::Retrying {
+                attempt: retried,
+                delay,
+            } => {
+                assert_eq!(retried, attempt, "Attempts should be counted");
+                assert!(
+                    delay >= ceiling / 2 && delay <= ceiling,
+                    "Delay {:?} of attempt {} should be within {:?}",
+                    delay,
+                    attempt,
+                    ceiling
+                );
+                assert_eq!(
+                    reconnector.retry_at(),
+                    Some(now + delay),
+                    "Attempt should be due after the delay"
+                );
+            }
+            state => panic!("Attempt {} should be retried: {:?}", attempt, state),
+        }
+        reconnector.retrying();
+        assert_eq!(
+            reconnector.retry_at(),
+            None,
+            "Attempt made should no longer be due"
+        );
+    }
+
+    assert_eq!(
+        reconnector.failed(now, &mut rng),
+        ReconnectState::GaveUp,
+        "Retrying should stop after the last attempt"
+    );
+    assert_eq!(reconnector.retry_at(), None, "Nothing should be due");
+}
+
+/// # Test: Retrying Now Starts Over
+///
+/// ## Steps:
+/// 1. Fail until giving up
+/// 2. Ask to retry now, and fail again
+/// 3. Restore the connection
+///
+/// ## Assertions:
+/// - Failing after retrying now starts over with the first attempt
+/// - Restoring a lost connection reports it once
+/// - A connection that was never lost reports nothing
+#[test]
+fn test_retrying_now_starts_over() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut reconnector = Reconnector::default();
+    let now = Instant::now();
+    assert_eq!(
+        reconnector.connected(),
+        None,
+        "Working connection should report nothing"
+    );
+
+    for _ in 0..=MAX_ATTEMPTS {
+        reconnector.failed(now, &mut rng);
+    }
+    reconnector.retry_now();
+    assert!(
+        matches!(
+            reconnector.failed(now, &mut rng),
+            ReconnectState::Retrying { attempt: 1, delay } if delay <= INITIAL_DELAY
+        ),
+        "Failing after retrying now should start over"
+    );
+
+    assert_eq!(
+        reconnector.connected(),
+        Some(ReconnectState::Connected),
+        "Restored connection should be reported"
+    );
+    assert_eq!(
+        reconnector.connected(),
+        None,
+        "Restored connection should be reported once"
+    );
+}
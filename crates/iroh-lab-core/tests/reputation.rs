@@ -0,0 +1,132 @@
+use iroh::SecretKey;
+use iroh_lab_core::reputation::{
+    Offense, Replays, Reputation, MAX_SCORE, RATE_LIMIT, RATE_WINDOW, RECOVERY_PER_MINUTE,
+};
+use std::time::{Duration, Instant};
+
+/// # Test: Misbehaving Peers Are Blocked Until They Recover
+///
+/// ## Steps:
+/// 1. Charge a peer for offenses until it is blocked
+/// 2. Let time pass
+///
+/// ## Assertions:
+/// - Unknown peers have the maximum score
+/// - Offenses lower the score until the peer is blocked
+/// - Scores recover over time, but never above the maximum
+/// - The lowest scores are listed first
+#[test]
+fn test_misbehaving_peers_are_blocked_until_they_recover() {
+    let honest = SecretKey::generate(rand::rngs::OsRng).public();
+    let rogue = SecretKey::generate(rand::rngs::OsRng).public();
+    let now = Instant::now();
+    let mut reputation = Reputation::default();
+    assert_eq!(
+        reputation.score(&rogue, now),
+        MAX_SCORE,
+        "Unknown peer should have the maximum score"
+    );
+
+    reputation.count_frame(honest, now);
+    reputation.penalize(rogue, Offense::Impersonation, now);
+    reputation.penalize(rogue, Offense::Replay, now);
+    assert_eq!(
+        reputation.score(&rogue, now),
+        MAX_SCORE - 50,
+        "Offenses should lower the score"
+    );
+    assert!(
+        !reputation.is_blocked(&rogue, now),
+        "Peer above the threshold should not be blocked"
+    );
+    reputation.penalize(rogue, Offense::RateLimit, now);
+    assert!(
+        reputation.is_blocked(&rogue, now),
+        "Peer at the threshold should be blocked"
+    );
+    assert_eq!(
+        reputation.scores(now).first().map(|(node, _)| *node),
+        Some(rogue),
+        "Lowest score should be listed first"
+    );
+
+    let later = now + Duration::from_secs(20 * 60);
+    assert_eq!(
+        reputation.score(&rogue, later),
+        MAX_SCORE - 70 + 20 * RECOVERY_PER_MINUTE,
+        "Score should recover over time"
+    );
+    assert!(
+        !reputation.is_blocked(&rogue, later),
+        "Recovered peer should no longer be blocked"
+    );
+    let much_later = now + Duration::from_secs(24 * 60 * 60);
+    assert_eq!(
+        reputation.score(&rogue, much_later),
+        MAX_SCORE,
+        "Score should not recover above the maximum"
+    );
+}
+
+/// # Test: Flooding Peers Are Charged Once Per Window
+///
+/// ## Steps:
+/// 1. Count frames of a peer up to and beyond the rate limit
+/// 2. Count a frame in the next window
+///
+/// ## Assertions:
+/// - Frames within the limit are allowed
+/// - Exceeding the limit is charged once per window
+#[test]
+fn test_flooding_peers_are_charged_once_per_window() {
+    let peer = SecretKey::generate(rand::rngs::OsRng).public();
+    let now = Instant::now();
+    let mut reputation = Reputation::default();
+
+    for _ in 0..RATE_LIMIT {
+        assert!(
+            reputation.count_frame(peer, now),
+            "Frames within the limit should be allowed"
+        );
+    }
+    assert!(
+        !reputation.count_frame(peer, now),
+        "Frame above the limit should not be allowed"
+    );
+    reputation.count_frame(peer, now);
+    assert_eq!(
+        reputation.score(&peer, now),
+        MAX_SCORE - Offense::RateLimit.penalty(),
+        "Flood should be charged once per window"
+    );
+
+    assert!(
+        reputation.count_frame(peer, now + RATE_WINDOW),
+        "Frames should be allowed again in the next window"
+    );
+}
+
+/// # Test: Messages Signed by Another Node Are Replays
+///
+/// ## Assertions:
+/// - A message sent again by its signer is not a replay
+/// - The same message signed by another node is
+#[test]
+fn test_messages_signed_by_another_node_are_replays() {
+    let author = SecretKey::generate(rand::rngs::OsRng).public();
+    let other = SecretKey::generate(rand::rngs::OsRng).public();
+    let mut replays = Replays::default();
+
+    assert!(
+        !replays.is_replay("message-1", author),
+        "First copy should not be a replay"
+    );
+    assert!(
+        !replays.is_replay("message-1", author),
+        "Copy sent again by its signer should not be a replay"
+    );
+    assert!(
+        replays.is_replay("message-1", other),
+        "Copy signed by another node should be a replay"
+    );
+}
@@ -0,0 +1,214 @@
+#![cfg(feature = "rest")]
+
+use axum::body::Body;
+use axum::http::{header, Method, Request, StatusCode};
+use axum::Router;
+use iroh_lab_core::client::ChatMessage;
+use iroh_lab_core::config::MessageLimit;
+use iroh_lab_core::rest::{self, ApiError, RestApi};
+use iroh_lab_core::rpc::TopicInfo;
+use iroh_lab_core::testing;
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const TOKEN: &str = "secret";
+
+// Sends a request to the API, returning the status and the body
+async fn request<T: DeserializeOwned>(
+    router: &Router,
+    method: Method,
+    uri: &str,
+    token: Option<&str>,
+    body: Option<&str>,
+) -> (StatusCode, T) {
+    let mut builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json");
+    if let Some(token) = token {
+        builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let request = builder
+        .body(Body::from(body.unwrap_or_default().to_string()))
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body = serde_json::from_slice(&bytes).expect("Body should be JSON");
+    (status, body)
+}
+
+// An API on a new topic, with its hash
+async fn api_on_topic() -> (Router, String) {
+    let client = testing::initialized_client()
+        .await
+        .expect("Client should start");
+    let (_, _, topic_hash) = client
+        .create_topic("kitchen".to_string())
+        .await
+        .expect("Topic should be created");
+    let api = RestApi::new(
+        client,
+        "home".to_string(),
+        MessageLimit::default(),
+        TOKEN.to_string(),
+    );
+    (Arc::new(api).router(), topic_hash)
+}
+
+/// # Test: Scripts Need the Token
+///
+/// ## Steps:
+/// 1. Serve a topic through the API
+/// 2. List the topics without a token, with another one, and with the token
+///
+/// ## Assertions:
+/// - Requests without the right token are unauthorized
+/// - The topic is listed with the token
+#[tokio::test]
+async fn test_scripts_need_the_token() {
+    let (router, topic_hash) = api_on_topic().await;
+
+    let (status, _) = request::<ApiError>(&router, Method::GET, "/topics", None, None).await;
+    assert_eq!(
+        status,
+        StatusCode::UNAUTHORIZED,
+        "Request without token should be unauthorized"
+    );
+    let (status, _) =
+        request::<ApiError>(&router, Method::GET, "/topics", Some("guess"), None).await;
+    assert_eq!(
+        status,
+        StatusCode::UNAUTHORIZED,
+        "Request with another token should be unauthorized"
+    );
+
+    let (status, topics) =
+        request::<Vec<TopicInfo>>(&router, Method::GET, "/topics", Some(TOKEN), None).await;
+    assert_eq!(status, StatusCode::OK, "Request with token should succeed");
+    assert_eq!(
+        topics,
+        vec![TopicInfo {
+            topic_name: "kitchen".to_string(),
+            topic_hash,
+            ticket: None,
+        }],
+        "Topic should be listed"
+    );
+}
+
+/// # Test: Scripts Post and Read Messages
+///
+/// ## Steps:
+/// 1. Serve a topic through the API
+/// 2. Post a message as an author, and one under the name of the API
+/// 3. Read the history, whole and limited
+/// 4. Post an empty message, and a message to an unknown topic
+///
+/// ## Assertions:
+/// - Posted messages are sent under their author, or the name of the API
+/// - The history holds them, oldest first, and the limit keeps the latest
+/// - Empty messages are bad requests and unknown topics are not found
+#[tokio::test]
+async fn test_scripts_post_and_read_messages() {
+    let (router, topic_hash) = api_on_topic().await;
+    let messages = format!("/topics/{}/messages", topic_hash);
+
+    let (status, sent) = request::<ChatMessage>(
+        &router,
+        Method::POST,
+        &messages,
+        Some(TOKEN),
+        Some(r#"{"content":"Washing machine is done","author":"laundry"}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "Message should be sent");
+    assert_eq!(sent.author, "laundry", "Message should be under its author");
+    let (_, unnamed) = request::<ChatMessage>(
+        &router,
+        Method::POST,
+        &messages,
+        Some(TOKEN),
+        Some(r#"{"content":"Front door opened"}"#),
+    )
+    .await;
+    assert_eq!(
+        unnamed.author, "home",
+        "Message without author should be under the name of the API"
+    );
+
+    let (status, history) =
+        request::<Vec<ChatMessage>>(&router, Method::GET, &messages, Some(TOKEN), None).await;
+    assert_eq!(status, StatusCode::OK, "History should be read");
+    let ids: Vec<_> = history.iter().map(|message| message.id.clone()).collect();
+    assert_eq!(
+        ids,
+        vec![sent.id, unnamed.id.clone()],
+        "History should hold the messages, oldest first"
+    );
+    let (_, latest) = request::<Vec<ChatMessage>>(
+        &router,
+        Method::GET,
+        &format!("{}?limit=1", messages),
+        Some(TOKEN),
+        None,
+    )
+    .await;
+    assert_eq!(latest.len(), 1, "Limit should bound the history");
+    assert_eq!(latest[0].id, unnamed.id, "Limit should keep the latest");
+
+    let (status, _) = request::<ApiError>(
+        &router,
+        Method::POST,
+        &messages,
+        Some(TOKEN),
+        Some(r#"{"content":"  "}"#),
+    )
+    .await;
+    assert_eq!(
+        status,
+        StatusCode::BAD_REQUEST,
+        "Empty message should be a bad request"
+    );
+    let (status, error) = request::<ApiError>(
+        &router,
+        Method::POST,
+        "/topics/unknown/messages",
+        Some(TOKEN),
+        Some(r#"{"content":"Hello"}"#),
+    )
+    .await;
+    assert_eq!(
+        status,
+        StatusCode::NOT_FOUND,
+        "Unknown topic should not be found"
+    );
+    assert!(!error.error.is_empty(), "Error should say why");
+}
+
+/// # Test: Tokens Are Kept
+///
+/// ## Steps:
+/// 1. Load the token from a file that does not exist
+/// 2. Load it again
+///
+/// ## Assertions:
+/// - A token is created and written to the file
+/// - The same token is loaded again
+#[test]
+fn test_tokens_are_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config").join("api.token");
+
+    let token = rest::load_token(&path).expect("Token should be created");
+    assert!(!token.is_empty(), "Token should not be empty");
+    assert!(path.exists(), "Token should be written");
+    assert_eq!(
+        rest::load_token(&path),
+        Ok(token),
+        "Same token should be loaded again"
+    );
+}
@@ -0,0 +1,67 @@
+use chrono::Duration;
+use iroh_lab_core::delivery::DeliveryState;
+use iroh_lab_core::liveness::TopicActivity;
+use iroh_lab_core::reconnect::ReconnectState;
+use iroh_lab_core::room::Room;
+use iroh_lab_core::testing::ChatMessageBuilder;
+
+/// # Test: Rooms Track the Topic Shown
+///
+/// ## Steps:
+/// 1. Enter a room, and compare messages sent before and after
+/// 2. Request a resync, then see members again
+/// 3. Lose the subscription, then restore it
+/// 4. Record the delivery of a message
+///
+/// ## Assertions:
+/// - Messages sent before entering come from history
+/// - Seeing members again ends the resync
+/// - Reconnecting is only tracked while the subscription is lost
+/// - Deliveries are kept by message id
+#[test]
+fn test_rooms_track_the_topic_shown() {
+    let mut room = Room::new("general");
+    assert_eq!(room.name(), "general", "The room should have its name");
+    let before = ChatMessageBuilder::new("before")
+        .timestamp(room.entered_at() - Duration::minutes(1))
+        .build();
+    let after = ChatMessageBuilder::new("after")
+        .timestamp(room.entered_at() + Duration::minutes(1))
+        .build();
+    assert!(room.is_history(&before), "Older messages should be history");
+    assert!(!room.is_history(&after), "Newer messages should be live");
+
+    room.set_activity(TopicActivity::Partitioned);
+    room.start_resync();
+    assert!(room.is_resyncing(), "The resync should be tracked");
+    room.set_activity(TopicActivity::Active);
+    assert!(
+        !room.is_resyncing(),
+        "Seeing members again should end the resync"
+    );
+
+    room.set_reconnect(ReconnectState::GaveUp);
+    assert_eq!(
+        room.reconnect(),
+        Some(ReconnectState::GaveUp),
+        "A lost subscription should be tracked"
+    );
+    room.set_reconnect(ReconnectState::Connected);
+    assert_eq!(
+        room.reconnect(),
+        None,
+        "A restored subscription should not be tracked"
+    );
+
+    room.set_delivery("after", DeliveryState::Delivered);
+    assert_eq!(
+        room.delivery("after"),
+        Some(DeliveryState::Delivered),
+        "The delivery should be kept"
+    );
+    assert_eq!(
+        room.delivery("before"),
+        None,
+        "Messages of others should have no delivery"
+    );
+}
@@ -0,0 +1,88 @@
+use iroh::SecretKey;
+use iroh_lab_core::client::Presence;
+use iroh_lab_core::roster::{MemberState, PresenceStatus, Roster, PRESENCE_TIMEOUT};
+use std::time::{Duration, Instant};
+
+fn presence(seed: u8, author: &str, status: PresenceStatus) -> Presence {
+    Presence {
+        topic_hash: "roster-test-topic".to_string(),
+        author: author.to_string(),
+        node_id: SecretKey::from_bytes(&[seed; 32]).public(),
+        status,
+    }
+}
+
+/// # Test: Members Follow Their Announced Status
+///
+/// ## Steps:
+/// 1. Record an online and an away member
+/// 2. Let the presence timeout pass for the online member only
+///
+/// ## Assertions:
+/// - Members are shown with the status they announced
+/// - Members not heard from for the timeout are offline
+/// - Offline members are no longer counted as present
+#[test]
+fn test_members_follow_their_announced_status() {
+    let mut roster = Roster::default();
+    let start = Instant::now();
+    roster.update(&presence(1, "alice", PresenceStatus::Online), start);
+    roster.update(&presence(2, "bob", PresenceStatus::Away), start);
+
+    let states: Vec<_> = roster
+        .members(start)
+        .into_iter()
+        .map(|(member, state)| (member.author.clone(), state))
+        .collect();
+    assert_eq!(
+        states,
+        vec![
+            ("alice".to_string(), MemberState::Online),
+            ("bob".to_string(), MemberState::Away),
+        ],
+        "Members should be shown as announced"
+    );
+
+    let later = start + PRESENCE_TIMEOUT;
+    roster.update(&presence(2, "bob", PresenceStatus::Away), later);
+    let alice = roster
+        .members(later)
+        .into_iter()
+        .find(|(member, _)| member.author == "alice")
+        .map(|(_, state)| state);
+    assert_eq!(
+        alice,
+        Some(MemberState::Offline),
+        "Silent member should be offline"
+    );
+    assert_eq!(roster.present(later), 1, "Only bob should be present");
+}
+
+/// # Test: Members Are Listed by Presence, Then Name
+///
+/// ## Assertions:
+/// - Online members come before away members, each sorted by name
+/// - A new announcement from a node replaces its earlier one
+#[test]
+fn test_members_are_listed_by_presence_then_name() {
+    let mut roster = Roster::default();
+    let now = Instant::now();
+    roster.update(&presence(1, "zoe", PresenceStatus::Online), now);
+    roster.update(&presence(2, "adam", PresenceStatus::Away), now);
+    roster.update(&presence(3, "mia", PresenceStatus::Online), now);
+    roster.update(
+        &presence(3, "mia", PresenceStatus::Away),
+        now + Duration::from_secs(1),
+    );
+
+    let authors: Vec<_> = roster
+        .members(now + Duration::from_secs(1))
+        .into_iter()
+        .map(|(member, _)| member.author.clone())
+        .collect();
+    assert_eq!(
+        authors,
+        vec!["zoe", "adam", "mia"],
+        "Members should be sorted by presence, then name"
+    );
+}
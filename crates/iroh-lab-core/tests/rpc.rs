@@ -0,0 +1,156 @@
+use iroh_lab_core::rpc::{
+    self, Call, Request, Response, RpcError, INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND,
+    PARSE_ERROR,
+};
+use serde_json::{json, Value};
+
+/// # Test: Requests Are Parsed Into Calls
+///
+/// ## Steps:
+/// 1. Parse a request of every method
+/// 2. Parse a notification, i.e. a request without an id
+///
+/// ## Assertions:
+/// - Every method is called with its parameters
+/// - Optional parameters may be left out
+/// - Notifications have no id to answer
+#[test]
+fn test_requests_are_parsed_into_calls() {
+    let cases = [
+        (
+            r#"{"jsonrpc":"2.0","id":1,"method":"join","params":{"ticket":"ticket-abc"}}"#,
+            Call::Join {
+                ticket: "ticket-abc".to_string(),
+            },
+        ),
+        (
+            r#"{"jsonrpc":"2.0","id":2,"method":"create","params":{"name":"book-club"}}"#,
+            Call::Create {
+                name: "book-club".to_string(),
+            },
+        ),
+        (
+            r#"{"jsonrpc":"2.0","id":3,"method":"send","params":{"topic_hash":"abc","content":"hi"}}"#,
+            Call::Send {
+                topic_hash: "abc".to_string(),
+                content: "hi".to_string(),
+                username: None,
+            },
+        ),
+        (
+            r#"{"jsonrpc":"2.0","id":4,"method":"subscribe"}"#,
+            Call::Subscribe { topic_hash: None },
+        ),
+        (
+            r#"{"jsonrpc":"2.0","id":5,"method":"subscribe","params":{"topic_hash":"abc"}}"#,
+            Call::Subscribe {
+                topic_hash: Some("abc".to_string()),
+            },
+        ),
+        (
+            r#"{"jsonrpc":"2.0","id":"six","method":"list-topics"}"#,
+            Call::ListTopics,
+        ),
+    ];
+    for (line, expected) in cases {
+        let request = rpc::parse(line).expect("Request should parse");
+        assert!(request.id.is_some(), "Request {} should have an id", line);
+        assert_eq!(
+            request.call(),
+            Ok(expected),
+            "Request {} should call its method",
+            line
+        );
+    }
+
+    let notification = rpc::parse(r#"{"jsonrpc":"2.0","method":"list-topics"}"#)
+        .expect("Notification should parse");
+    assert_eq!(notification.id, None, "Notification should have no id");
+}
+
+/// # Test: Bad Requests Are Answered With Errors
+///
+/// ## Steps:
+/// 1. Parse lines that are not JSON, not requests, of another version, of
+///    an unknown method and with missing parameters
+///
+/// ## Assertions:
+/// - Each is answered with the matching JSON-RPC error code
+/// - The error answers the request id when there is one
+#[test]
+fn test_bad_requests_are_answered_with_errors() {
+    let response = rpc::parse("{not json").expect_err("Invalid JSON should be refused");
+    assert_eq!(
+        response.error.map(|e| e.code),
+        Some(PARSE_ERROR),
+        "Invalid JSON should be a parse error"
+    );
+    assert_eq!(response.id, Value::Null, "Parse error should have no id");
+
+    let response =
+        rpc::parse(r#"{"jsonrpc":"2.0","id":7}"#).expect_err("Missing method should be refused");
+    assert_eq!(
+        response.error.map(|e| e.code),
+        Some(INVALID_REQUEST),
+        "Missing method should be an invalid request"
+    );
+    assert_eq!(response.id, json!(7), "Error should answer the request");
+
+    let code = |line: &str| {
+        rpc::parse(line)
+            .expect("Request should parse")
+            .call()
+            .map_err(|e| e.code)
+    };
+    assert_eq!(
+        code(r#"{"jsonrpc":"1.0","id":1,"method":"list-topics"}"#),
+        Err(INVALID_REQUEST),
+        "Other versions should be refused"
+    );
+    assert_eq!(
+        code(r#"{"jsonrpc":"2.0","id":1,"method":"leave"}"#),
+        Err(METHOD_NOT_FOUND),
+        "Unknown method should be refused"
+    );
+    assert_eq!(
+        code(r#"{"jsonrpc":"2.0","id":1,"method":"send","params":{"content":"hi"}}"#),
+        Err(INVALID_PARAMS),
+        "Missing parameters should be refused"
+    );
+}
+
+/// # Test: Responses Are Encoded as Lines
+///
+/// ## Steps:
+/// 1. Encode a result and an error, and parse them back
+/// 2. Encode a request
+///
+/// ## Assertions:
+/// - Each message is a single line
+/// - Results and errors round-trip, with only one of them set
+/// - Encoded requests parse into the same request
+#[test]
+fn test_responses_are_encoded_as_lines() {
+    let result = Response::result(json!(1), vec!["a", "b"]);
+    let line = rpc::encode(&result);
+    assert!(
+        line.ends_with('\n') && line.matches('\n').count() == 1,
+        "Response should be a single line"
+    );
+    assert!(!line.contains("error"), "Result should have no error");
+    let parsed: Response = serde_json::from_str(&line).expect("Response should parse");
+    assert_eq!(parsed, result, "Result should round-trip");
+
+    let error = Response::error(json!("x"), RpcError::new(-32000, "No active topic"));
+    let line = rpc::encode(&error);
+    assert!(!line.contains("result"), "Error should have no result");
+    let parsed: Response = serde_json::from_str(&line).expect("Response should parse");
+    assert_eq!(parsed, error, "Error should round-trip");
+
+    let request = Request::new(3, "join", json!({ "ticket": "ticket-abc" }));
+    assert_eq!(
+        rpc::parse(&rpc::encode(&request)),
+        Ok(request),
+        "Request should round-trip"
+    );
+}
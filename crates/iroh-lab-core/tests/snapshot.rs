@@ -0,0 +1,78 @@
+use iroh_gossip::proto::TopicId;
+use iroh_lab_core::blobs::MAX_BLOB_SIZE;
+use iroh_lab_core::client::ChatMessage;
+use iroh_lab_core::crypto::TopicKey;
+use iroh_lab_core::snapshot::{self, SNAPSHOT_MAX_MESSAGES};
+use iroh_lab_core::testing::ChatMessageBuilder;
+
+fn message(index: usize, topic_hash: &str, content: String) -> ChatMessage {
+    ChatMessageBuilder::new(format!("message-{}", index))
+        .content(content)
+        .topic(topic_hash)
+        .sequence(index as u64)
+        .lamport(index as u64)
+        .build()
+}
+
+/// # Test: Snapshots Hold the Latest Messages That Fit
+///
+/// ## Steps:
+/// 1. Pack more messages than a snapshot holds, and unpack them
+/// 2. Pack messages too large to fit into a blob together
+/// 3. Unpack a snapshot with another topic's key
+///
+/// ## Assertions:
+/// - Only the latest messages are packed, and unpack unchanged
+/// - Snapshots never exceed the blob size limit
+/// - Messages of other topics are dropped
+/// - Snapshots only open with their topic's key
+#[test]
+fn test_snapshots_hold_the_latest_messages_that_fit() {
+    let key = TopicKey::derive(&[1; 32], &TopicId::from_bytes([2; 32]));
+    let mut messages: Vec<_> = (0..SNAPSHOT_MAX_MESSAGES + 10)
+        .map(|index| message(index, "topic", format!("Message {}", index)))
+        .collect();
+    messages.push(message(0, "other-topic", "Elsewhere".to_string()));
+
+    let (sealed, count) = snapshot::pack(&messages, &key).expect("Snapshot should pack");
+    assert_eq!(
+        count, SNAPSHOT_MAX_MESSAGES,
+        "Snapshot should hold the most messages allowed"
+    );
+    let unpacked = snapshot::unpack(&sealed, &key, "topic").expect("Snapshot should unpack");
+    assert_eq!(
+        unpacked.len(),
+        SNAPSHOT_MAX_MESSAGES - 1,
+        "Messages of other topics should be dropped"
+    );
+    assert_eq!(
+        unpacked.last().map(|m| m.content.as_str()),
+        Some(format!("Message {}", SNAPSHOT_MAX_MESSAGES + 9).as_str()),
+        "Latest message should be packed"
+    );
+
+    let large: Vec<_> = (0..10)
+        .map(|index| message(index, "topic", "x".repeat(MAX_BLOB_SIZE / 4)))
+        .collect();
+    let (sealed, count) = snapshot::pack(&large, &key).expect("Snapshot should pack");
+    assert!(
+        sealed.len() <= MAX_BLOB_SIZE,
+        "Snapshot should fit into a blob"
+    );
+    assert!(
+        (1..10).contains(&count),
+        "Only some large messages should fit"
+    );
+    let unpacked = snapshot::unpack(&sealed, &key, "topic").expect("Snapshot should unpack");
+    assert_eq!(
+        unpacked.last().map(|m| m.id.as_str()),
+        Some("message-9"),
+        "Latest large message should be packed"
+    );
+
+    let other_key = TopicKey::derive(&[3; 32], &TopicId::from_bytes([2; 32]));
+    assert!(
+        snapshot::unpack(&sealed, &other_key, "topic").is_err(),
+        "Snapshot should not open with another key"
+    );
+}
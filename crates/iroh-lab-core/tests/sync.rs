@@ -0,0 +1,184 @@
+use chrono::{DateTime, Duration, Utc};
+use iroh::SecretKey;
+use iroh_lab_core::client::{ChatMessage, Heartbeat, SyncRequest};
+use iroh_lab_core::sync::{self, Outbox, OUTBOX_CAPACITY, SYNC_MARGIN};
+use iroh_lab_core::testing::{ChatMessageBuilder, TopicFixture};
+use iroh_lab_core::wire::{self, Payload};
+use rand::seq::SliceRandom;
+
+fn chat(id: usize) -> Payload {
+    Payload::Chat(
+        ChatMessageBuilder::new(id.to_string())
+            .content(format!("Message {}", id))
+            .topic("sync-test-topic")
+            .sequence(id as u64)
+            .lamport(id as u64)
+            .build(),
+    )
+}
+
+fn chat_ids(payloads: &[Payload]) -> Vec<String> {
+    payloads
+        .iter()
+        .map(|payload| match payload {
+            Payload::Chat(message) => message.id.clone(),
+            other => panic!("Expected a chat payload, got {:?}", other),
+        })
+        .collect()
+}
+
+/// # Test: Outbox Resends Payloads Since the Requested Time
+///
+/// ## Steps:
+/// 1. Push chat messages sent a minute apart, and a heartbeat
+/// 2. Ask for the payloads sent since the second message
+///
+/// ## Assertions:
+/// - Only payloads sent at or after the requested time are returned
+/// - Payloads are returned oldest first
+/// - Heartbeats are never kept
+#[test]
+fn test_outbox_resends_payloads_since_the_requested_time() {
+    let start = Utc::now();
+    let mut outbox = Outbox::default();
+    for id in 0..3 {
+        outbox.push(start + Duration::minutes(id as i64), &chat(id));
+    }
+    outbox.push(
+        start + Duration::minutes(3),
+        &Payload::Heartbeat(Heartbeat {
+            topic_hash: "sync-test-topic".to_string(),
+            sent_at: start,
+        }),
+    );
+
+    let resent = outbox.since(start + Duration::minutes(1));
+    assert_eq!(
+        chat_ids(&resent),
+        vec!["1", "2"],
+        "Messages since the requested time should be resent in order"
+    );
+}
+
+/// # Test: Outbox Keeps Only the Latest Payloads
+///
+/// ## Assertions:
+/// - The oldest payloads are dropped once the outbox is full
+#[test]
+fn test_outbox_keeps_only_the_latest_payloads() {
+    let start = Utc::now();
+    let mut outbox = Outbox::default();
+    for id in 0..OUTBOX_CAPACITY + 2 {
+        outbox.push(start, &chat(id));
+    }
+
+    let resent = chat_ids(&outbox.since(start));
+    assert_eq!(
+        resent.len(),
+        OUTBOX_CAPACITY,
+        "Outbox should hold at most its capacity"
+    );
+    assert_eq!(resent[0], "2", "Oldest messages should be dropped first");
+}
+
+/// # Test: Sync Requests Reach Back Before the Last Contact
+///
+/// This test verifies the period other members are asked to resend, and
+/// that the request survives the wire.
+///
+/// ## Assertions:
+/// - Requests reach back the sync margin before the last contact
+/// - Nodes never in contact ask for everything
+/// - A sync request round trips over the wire
+#[test]
+fn test_sync_requests_reach_back_before_the_last_contact() {
+    let last_contact = Utc::now();
+    assert_eq!(
+        sync::sync_since(Some(last_contact)),
+        last_contact - Duration::from_std(SYNC_MARGIN).unwrap(),
+        "Request should reach back the sync margin"
+    );
+    assert_eq!(
+        sync::sync_since(None),
+        DateTime::UNIX_EPOCH,
+        "Request without earlier contact should ask for everything"
+    );
+
+    let topic = TopicFixture::new("sync-test-topic");
+    let request = SyncRequest {
+        topic_hash: topic.topic_hash.clone(),
+        since: sync::sync_since(Some(last_contact)),
+    };
+    let bytes = topic
+        .encode(
+            &Payload::SyncRequest(request.clone()),
+            &SecretKey::generate(rand::rngs::OsRng),
+        )
+        .expect("Failed to encode sync request");
+    let Payload::SyncRequest(decoded) = wire::decode(&bytes, &topic.key)
+        .expect("Failed to decode sync request")
+        .payload
+    else {
+        panic!("Expected a sync request payload");
+    };
+    assert_eq!(decoded.since, request.since, "Start time should match");
+}
+
+// Inserts a message into the history the way the chat room does: dropping
+// duplicates, and keeping the history sorted by the order key
+fn insert(history: &mut Vec<ChatMessage>, message: ChatMessage) {
+    if history.iter().any(|existing| existing.id == message.id) {
+        return;
+    }
+    let index = history.partition_point(|existing| existing.order_key() <= message.order_key());
+    history.insert(index, message);
+}
+
+/// # Test: Every Delivery Order Converges to the Same History
+///
+/// ## Steps:
+/// 1. Build messages from several members, with clashing Lamport timestamps
+///    and wall clock times
+/// 2. Deliver them many times in random order, some of them twice
+///
+/// ## Assertions:
+/// - Every delivery ends with the same history, sorted by the order key
+/// - Duplicates are dropped
+#[test]
+fn test_every_delivery_order_converges_to_the_same_history() {
+    let topic = TopicFixture::new("sync-test-topic");
+    let start = Utc::now();
+    let messages: Vec<ChatMessage> = (0..30)
+        .map(|i| {
+            topic
+                .message(format!("message-{:02}", i))
+                .author(["alice", "bob", "carol"][i % 3])
+                .lamport(i as u64 / 4)
+                .timestamp(start + Duration::seconds(i as i64 % 3))
+                .build()
+        })
+        .collect();
+    let mut expected = messages.clone();
+    expected.sort_by(|a, b| a.order_key().cmp(&b.order_key()));
+    let expected: Vec<&str> = expected.iter().map(|m| m.id.as_str()).collect();
+
+    let mut rng = rand::thread_rng();
+    for _ in 0..100 {
+        let mut delivery: Vec<ChatMessage> = messages
+            .iter()
+            .chain(messages.choose_multiple(&mut rng, 10))
+            .cloned()
+            .collect();
+        delivery.shuffle(&mut rng);
+
+        let mut history = Vec::new();
+        for message in delivery {
+            insert(&mut history, message);
+        }
+        let ids: Vec<&str> = history.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(
+            ids, expected,
+            "History should not depend on the delivery order"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use iroh::{NodeId, SecretKey};
+use iroh_lab_core::client::{ChatMessage, TaskUpdate};
+use iroh_lab_core::tasks::{TaskChange, TaskList};
+use iroh_lab_core::testing::ChatMessageBuilder;
+
+fn node(seed: u8) -> NodeId {
+    SecretKey::from_bytes(&[seed; 32]).public()
+}
+
+fn update(message_id: &str, change: TaskChange, lamport: u64) -> TaskUpdate {
+    TaskUpdate {
+        topic_hash: "topic".to_string(),
+        message_id: message_id.to_string(),
+        change,
+        lamport,
+    }
+}
+
+fn message(id: &str) -> ChatMessage {
+    ChatMessageBuilder::new(id)
+        .content(format!("Decision {}", id))
+        .topic("topic")
+        .sequence(0)
+        .lamport(0)
+        .build()
+}
+
+/// # Test: Tasks Converge Whatever Order Updates Arrive In
+///
+/// ## Steps:
+/// 1. Apply the same updates from two members to two task lists, in
+///    opposite orders, including concurrent ones with equal timestamps
+/// 2. Apply an update older than the one that set a field
+///
+/// ## Assertions:
+/// - Both lists end up with the same tasks
+/// - The later update of a field wins, and the signer breaks ties
+/// - Older updates change nothing
+/// - Only flagged messages are tasks
+#[test]
+fn test_tasks_converge_whatever_order_updates_arrive_in() {
+    let (alice, bob) = (node(1), node(2));
+    let updates = vec![
+        (update("m1", TaskChange::Flag(true), 1), alice),
+        (update("m1", TaskChange::Assign(Some(bob)), 2), alice),
+        (update("m1", TaskChange::Assign(Some(alice)), 2), bob),
+        (update("m1", TaskChange::Done(true), 3), bob),
+        (update("m2", TaskChange::Done(true), 4), alice),
+    ];
+
+    let mut forward = TaskList::default();
+    for (update, sender) in &updates {
+        forward.apply(update, *sender);
+    }
+    let mut backward = TaskList::default();
+    for (update, sender) in updates.iter().rev() {
+        backward.apply(update, *sender);
+    }
+    assert_eq!(forward, backward, "Task lists should converge");
+
+    let task = forward
+        .get("topic", "m1")
+        .expect("Flagged message should be a task");
+    assert!(task.done, "Task should be done");
+    // Alice assigned Bob and Bob assigned Alice, the larger node id wins
+    let assigned_by_winner = if alice > bob { bob } else { alice };
+    assert_eq!(
+        task.assignee,
+        Some(assigned_by_winner),
+        "Signer should break the tie between assignments"
+    );
+    assert!(
+        forward.get("topic", "m2").is_none(),
+        "Unflagged message should not be a task"
+    );
+
+    assert!(
+        !forward.apply(&update("m1", TaskChange::Done(false), 2), bob),
+        "Older update should change nothing"
+    );
+    assert!(
+        forward.apply(&update("m1", TaskChange::Done(false), 5), bob),
+        "Later update should change the task"
+    );
+    assert_eq!(
+        forward.open(&[message("m1"), message("m2")]),
+        1,
+        "Undone task should be open"
+    );
+}
+
+/// # Test: Tasks Are Kept Between Runs
+///
+/// ## Steps:
+/// 1. Flag and assign a message, then save and load the tasks
+/// 2. Load tasks from a missing file
+///
+/// ## Assertions:
+/// - Loaded tasks equal the saved ones
+/// - A missing file holds no tasks
+#[test]
+fn test_tasks_are_kept_between_runs() {
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let path = dir.path().join("iroh-lab").join("tasks.json");
+    let mut tasks = TaskList::default();
+    tasks.apply(&update("m1", TaskChange::Flag(true), 1), node(1));
+    tasks.apply(&update("m1", TaskChange::Assign(Some(node(2))), 2), node(1));
+
+    tasks.save_to(&path).expect("Tasks should save");
+    let loaded = TaskList::load_from(&path).expect("Tasks should load");
+    assert_eq!(loaded, tasks, "Loaded tasks should equal the saved ones");
+
+    let missing =
+        TaskList::load_from(&dir.path().join("missing.json")).expect("Missing file should load");
+    assert!(
+        missing.get("topic", "m1").is_none(),
+        "Missing file should hold no tasks"
+    );
+}
@@ -0,0 +1,58 @@
+use iroh::SecretKey;
+use iroh_lab_core::client::ChatMessage;
+use iroh_lab_core::testing::ChatMessageBuilder;
+use iroh_lab_core::unread::Unread;
+
+fn message(id: &str, topic_hash: &str, author: &str, sender: Option<iroh::NodeId>) -> ChatMessage {
+    let message = ChatMessageBuilder::new(id)
+        .author(author)
+        .topic(topic_hash)
+        .sequence(0)
+        .lamport(0);
+    match sender {
+        Some(sender) => message.sent_by(sender).build(),
+        None => message.build(),
+    }
+}
+
+/// # Test: Unread Messages Are Counted Per Topic Until Viewed
+///
+/// ## Steps:
+/// 1. Count messages of two topics, some of them twice
+/// 2. Count our own messages and system notices
+/// 3. View one of the topics
+///
+/// ## Assertions:
+/// - Each message is counted once, for its own topic
+/// - Our own messages and system notices are not counted
+/// - Viewing a topic only clears its own count
+#[test]
+fn test_unread_messages_are_counted_per_topic_until_viewed() {
+    let own = SecretKey::generate(rand::rngs::OsRng).public();
+    let other = SecretKey::generate(rand::rngs::OsRng).public();
+    let mut unread = Unread::default();
+
+    unread.count(&message("a-1", "a", "bob", Some(other)), Some(own));
+    unread.count(&message("a-1", "a", "bob", Some(other)), Some(own));
+    unread.count(&message("a-2", "a", "carol", None), Some(own));
+    unread.count(&message("b-1", "b", "bob", Some(other)), Some(own));
+    assert_eq!(unread.get("a"), 2, "Each message should be counted once");
+    assert_eq!(unread.get("b"), 1, "Messages should count for their topic");
+
+    unread.count(&message("a-3", "a", "alice", Some(own)), Some(own));
+    unread.count(&message("a-4", "a", "System", None), Some(own));
+    assert_eq!(
+        unread.get("a"),
+        2,
+        "Own messages and system notices should not be counted"
+    );
+    assert_eq!(unread.total(), 3, "Total should cover every topic");
+
+    unread.clear("a");
+    assert_eq!(
+        unread.get("a"),
+        0,
+        "Viewed topic should have no unread messages"
+    );
+    assert_eq!(unread.total(), 1, "Other topics should keep their count");
+}
@@ -0,0 +1,66 @@
+use iroh::SecretKey;
+use iroh_lab_core::welcome::{self, Welcomed};
+
+/// # Test: Welcome Messages Mention the Newcomer
+///
+/// ## Assertions:
+/// - The name replaces every placeholder
+/// - Templates without a placeholder start with the name
+#[test]
+fn test_welcome_messages_mention_the_newcomer() {
+    assert_eq!(
+        welcome::render("Welcome, {name}! Glad you are here, {name}.", "bob"),
+        "Welcome, bob! Glad you are here, bob.",
+        "Name should replace the placeholders"
+    );
+    assert_eq!(
+        welcome::render("  please read the rules ", "bob"),
+        "bob, please read the rules",
+        "Name should be put in front"
+    );
+}
+
+/// # Test: Members Are Welcomed Once Per Topic
+///
+/// ## Steps:
+/// 1. Mark a member as welcomed on a topic, twice
+/// 2. Save and load the welcomed members
+/// 3. Forget the welcomed members of the topic
+///
+/// ## Assertions:
+/// - Only the first mark is new
+/// - The same member is new on another topic
+/// - Welcomed members are kept between runs
+/// - Forgotten members are new again
+#[test]
+fn test_members_are_welcomed_once_per_topic() {
+    let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let path = dir.path().join("iroh-lab").join("welcomed.json");
+    let bob = SecretKey::generate(rand::rngs::OsRng).public();
+    let mut welcomed = Welcomed::default();
+
+    assert!(welcomed.mark("topic", bob), "First mark should be new");
+    assert!(
+        !welcomed.mark("topic", bob),
+        "Member should only be welcomed once"
+    );
+    assert!(
+        welcomed.mark("other", bob),
+        "Member should be new on another topic"
+    );
+
+    welcomed
+        .save_to(&path)
+        .expect("Welcomed members should be saved");
+    let mut loaded = Welcomed::load_from(&path).expect("Welcomed members should load");
+    assert_eq!(
+        loaded, welcomed,
+        "Loaded members should match the saved ones"
+    );
+
+    loaded.forget("topic");
+    assert!(
+        loaded.mark("topic", bob),
+        "Forgotten member should be new again"
+    );
+}
@@ -0,0 +1,487 @@
+use chrono::{DateTime, Utc};
+use iroh::SecretKey;
+use iroh_gossip::proto::TopicId;
+use iroh_lab_core::client::ChatMessage;
+use iroh_lab_core::crypto::TopicKey;
+use iroh_lab_core::testing::ChatMessageBuilder;
+use iroh_lab_core::wire::{self, Compression, Payload, WIRE_VERSION};
+use serde::Serialize;
+
+fn sample_message() -> ChatMessage {
+    ChatMessageBuilder::new("3f0c8a52-5d4e-4a57-9a53-1f9e4c1d2b7a")
+        .content("Hello, world!")
+        .topic("wire-test-topic")
+        .sequence(42)
+        .lamport(7)
+        .build()
+}
+
+fn secret_key() -> SecretKey {
+    SecretKey::generate(rand::rngs::OsRng)
+}
+
+fn topic_key() -> TopicKey {
+    TopicKey::derive(&rand::random(), &TopicId::from_bytes(rand::random()))
+}
+
+fn assert_same_message(actual: &ChatMessage, expected: &ChatMessage) {
+    assert_eq!(
+        actual.id, expected.id,
+        "Message id should survive the round trip"
+    );
+    assert_eq!(
+        actual.author, expected.author,
+        "Author should survive the round trip"
+    );
+    assert_eq!(
+        actual.content, expected.content,
+        "Content should survive the round trip"
+    );
+    assert_eq!(
+        actual.timestamp, expected.timestamp,
+        "Timestamp should survive the round trip"
+    );
+    assert_eq!(
+        actual.topic_hash, expected.topic_hash,
+        "Topic hash should survive the round trip"
+    );
+    assert_eq!(
+        actual.sequence, expected.sequence,
+        "Sequence should survive the round trip"
+    );
+    assert_eq!(
+        actual.lamport, expected.lamport,
+        "Lamport timestamp should survive the round trip"
+    );
+}
+
+// A chat message as sent before messages carried a Lamport timestamp
+#[derive(Serialize)]
+struct UnorderedChatMessage {
+    id: String,
+    author: String,
+    content: String,
+    timestamp: DateTime<Utc>,
+    topic_hash: String,
+    sequence: u64,
+}
+
+// The payload carried by frames up to version 3
+#[derive(Serialize)]
+enum UnorderedPayload {
+    Chat(UnorderedChatMessage),
+}
+
+fn unordered_payload(message: &ChatMessage) -> UnorderedPayload {
+    UnorderedPayload::Chat(UnorderedChatMessage {
+        id: message.id.clone(),
+        author: message.author.clone(),
+        content: message.content.clone(),
+        timestamp: message.timestamp,
+        topic_hash: message.topic_hash.clone(),
+        sequence: message.sequence,
+    })
+}
+
+/// # Test: Postcard Round Trip
+///
+/// This test verifies that a chat message can be encoded and decoded again
+/// without losing information, and that the decoder reports who signed it.
+///
+/// ## Steps:
+/// 1. Encode, encrypt and sign a chat message
+/// 2. Decode the encoded bytes with the same topic key
+///
+/// ## Assertions:
+/// - The encoded frame starts with the current wire version
+/// - The decoded envelope carries the current wire version
+/// - The decoded envelope names the signing node as sender
+/// - The decoded chat message matches the original
+#[test]
+fn test_postcard_round_trip() {
+    let message = sample_message();
+    let key = secret_key();
+    let topic_key = topic_key();
+
+    let bytes = wire::encode(&Payload::Chat(message.clone()), &key, &topic_key)
+        .expect("Failed to encode payload");
+    assert_eq!(
+        bytes[0], WIRE_VERSION,
+        "Frame should start with the version"
+    );
+
+    let envelope = wire::decode(&bytes, &topic_key).expect("Failed to decode frame");
+    assert_eq!(envelope.version, WIRE_VERSION, "Version should match");
+    assert_eq!(
+        envelope.sender,
+        key.public(),
+        "Sender should be the signing node"
+    );
+
+    let Payload::Chat(decoded) = envelope.payload else {
+        panic!("Expected a chat payload");
+    };
+    assert_same_message(&decoded, &message);
+}
+
+/// # Test: Tampered Messages Are Rejected
+///
+/// This test verifies that a message modified after signing fails
+/// verification instead of being attributed to the signing node.
+///
+/// ## Steps:
+/// 1. Encode and sign a chat message
+/// 2. Flip a byte near the end of the frame, inside the signed payload
+/// 3. Decode the modified bytes
+///
+/// ## Assertions:
+/// - The modified frame is rejected
+#[test]
+fn test_tampered_messages_are_rejected() {
+    let topic_key = topic_key();
+    let mut bytes = wire::encode(&Payload::Chat(sample_message()), &secret_key(), &topic_key)
+        .expect("Failed to encode payload");
+
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0x01;
+
+    assert!(
+        wire::decode(&bytes, &topic_key).is_err(),
+        "Tampered frame should be rejected"
+    );
+}
+
+/// # Test: Unencrypted Frames Are Refused
+///
+/// This test verifies that frames of versions predating encryption are
+/// refused, as nothing ties them to the topic key and anyone knowing the
+/// topic could send them.
+///
+/// ## Steps:
+/// 1. Encode a chat message in the unsigned version 1 frame layout
+/// 2. Sign the same chat message in the plaintext version 2 frame layout
+/// 3. Decode both with the wire decoder
+///
+/// ## Assertions:
+/// - Both frames are rejected
+#[test]
+fn test_unencrypted_frames_are_refused() {
+    #[derive(Serialize)]
+    struct UnsignedFrame {
+        version: u8,
+        payload: UnorderedPayload,
+    }
+
+    #[derive(Serialize)]
+    struct SignedFrame {
+        version: u8,
+        sender: iroh::PublicKey,
+        signature: ed25519_dalek::Signature,
+        payload: Vec<u8>,
+    }
+
+    let message = sample_message();
+    let key = secret_key();
+    let topic_key = topic_key();
+
+    let unsigned = postcard::to_stdvec(&UnsignedFrame {
+        version: 1,
+        payload: unordered_payload(&message),
+    })
+    .expect("Failed to encode version 1 frame");
+    assert!(
+        wire::decode(&unsigned, &topic_key).is_err(),
+        "Unsigned version 1 frame should be rejected"
+    );
+
+    let payload =
+        postcard::to_stdvec(&unordered_payload(&message)).expect("Failed to encode payload");
+    let plaintext = postcard::to_stdvec(&SignedFrame {
+        version: 2,
+        sender: key.public(),
+        signature: key.sign(&payload),
+        payload,
+    })
+    .expect("Failed to encode version 2 frame");
+    assert!(
+        wire::decode(&plaintext, &topic_key).is_err(),
+        "Unencrypted version 2 frame should be rejected"
+    );
+}
+
+/// # Test: Postcard Is More Compact Than JSON
+///
+/// This test verifies that the postcard encoding of a payload is smaller than
+/// the JSON encoding previously used on the wire.
+///
+/// ## Steps:
+/// 1. Encode a chat payload with postcard
+/// 2. Encode the same chat message as JSON
+///
+/// ## Assertions:
+/// - The postcard encoding is smaller than the JSON encoding
+#[test]
+fn test_postcard_is_more_compact_than_json() {
+    let message = sample_message();
+
+    let postcard_bytes =
+        postcard::to_stdvec(&Payload::Chat(message.clone())).expect("Failed to encode payload");
+    let json_bytes = serde_json::to_vec(&message).expect("Failed to encode JSON");
+
+    assert!(
+        postcard_bytes.len() < json_bytes.len(),
+        "Postcard ({} bytes) should be smaller than JSON ({} bytes)",
+        postcard_bytes.len(),
+        json_bytes.len()
+    );
+}
+
+/// # Test: Legacy JSON Is Refused
+///
+/// This test verifies that JSON encoded chat messages, as sent by releases
+/// predating signatures and encryption, are refused.
+///
+/// ## Steps:
+/// 1. Encode a chat message as JSON, like older releases did
+/// 2. Decode it with the wire decoder
+///
+/// ## Assertions:
+/// - The JSON payload is rejected
+#[test]
+fn test_legacy_json_is_refused() {
+    let json_bytes = serde_json::to_vec(&sample_message()).expect("Failed to encode JSON");
+
+    assert!(
+        wire::decode(&json_bytes, &topic_key()).is_err(),
+        "Legacy JSON should be rejected"
+    );
+}
+
+/// # Test: Invalid Payloads Are Rejected
+///
+/// This test verifies that payloads which are not a known frame version are
+/// rejected instead of being misinterpreted.
+///
+/// ## Assertions:
+/// - An empty payload is rejected
+/// - A payload with an unknown version is rejected
+/// - A truncated frame is rejected
+#[test]
+fn test_invalid_payloads_are_rejected() {
+    let topic_key = topic_key();
+    assert!(
+        wire::decode(&[], &topic_key).is_err(),
+        "Empty payload should be rejected"
+    );
+    assert!(
+        wire::decode(&[WIRE_VERSION + 1, 0, 0], &topic_key).is_err(),
+        "Unknown version should be rejected"
+    );
+
+    let bytes = wire::encode(&Payload::Chat(sample_message()), &secret_key(), &topic_key)
+        .expect("Failed to encode payload");
+    assert!(
+        wire::decode(&bytes[..bytes.len() / 2], &topic_key).is_err(),
+        "Truncated frame should be rejected"
+    );
+}
+
+/// # Test: Payloads Are Encrypted
+///
+/// This test verifies that the message content is not readable on the wire,
+/// and that only holders of the topic key can decode it.
+///
+/// ## Steps:
+/// 1. Encode a chat message with one topic key
+/// 2. Decode it with a different topic key
+///
+/// ## Assertions:
+/// - The encoded frame does not contain the message content in cleartext
+/// - Decoding with another topic key fails
+#[test]
+fn test_payloads_are_encrypted() {
+    let message = sample_message();
+    let bytes = wire::encode(&Payload::Chat(message.clone()), &secret_key(), &topic_key())
+        .expect("Failed to encode payload");
+
+    assert!(
+        !bytes
+            .windows(message.content.len())
+            .any(|window| window == message.content.as_bytes()),
+        "Content should not appear in cleartext"
+    );
+    assert!(
+        wire::decode(&bytes, &topic_key()).is_err(),
+        "Decoding with another topic key should fail"
+    );
+}
+
+/// # Test: Version 3 Frames Without Lamport Timestamp
+///
+/// This test verifies that encrypted frames from peers that do not stamp their
+/// messages with a Lamport timestamp yet are still readable.
+///
+/// ## Steps:
+/// 1. Encode, encrypt and sign a chat message in the version 3 frame layout
+/// 2. Decode it with the wire decoder
+///
+/// ## Assertions:
+/// - The frame decodes with version 3 and the signing node as sender
+/// - The decoded chat message matches the original, with Lamport timestamp 0
+#[test]
+fn test_version_3_frames_without_lamport_timestamp() {
+    #[derive(Serialize)]
+    struct SignedFrame {
+        version: u8,
+        sender: iroh::PublicKey,
+        signature: ed25519_dalek::Signature,
+        payload: Vec<u8>,
+    }
+
+    let mut message = sample_message();
+    let key = secret_key();
+    let topic_key = topic_key();
+
+    let payload =
+        postcard::to_stdvec(&unordered_payload(&message)).expect("Failed to encode payload");
+    let payload = topic_key
+        .encrypt(&payload)
+        .expect("Failed to encrypt payload");
+    let bytes = postcard::to_stdvec(&SignedFrame {
+        version: 3,
+        sender: key.public(),
+        signature: key.sign(&payload),
+        payload,
+    })
+    .expect("Failed to encode version 3 frame");
+    message.lamport = 0;
+
+    let envelope = wire::decode(&bytes, &topic_key).expect("Failed to decode version 3 frame");
+    assert_eq!(envelope.version, 3, "Version should be 3");
+    assert_eq!(
+        envelope.sender,
+        key.public(),
+        "Sender should be the signing node"
+    );
+
+    let Payload::Chat(decoded) = envelope.payload else {
+        panic!("Expected a chat payload");
+    };
+    assert_same_message(&decoded, &message);
+}
+
+/// # Test: Large Payloads Are Compressed
+///
+/// This test verifies that large, repetitive payloads are compressed on the
+/// wire, and decode back to the original message.
+///
+/// ## Steps:
+/// 1. Encode a long chat message with and without compression
+/// 2. Decode the compressed frame
+///
+/// ## Assertions:
+/// - The compressed frame is smaller than the uncompressed one
+/// - The decoded chat message matches the original
+#[test]
+fn test_large_payloads_are_compressed() {
+    let mut message = sample_message();
+    message.content = "All work and no play makes Jack a dull boy. ".repeat(50);
+    let key = secret_key();
+    let topic_key = topic_key();
+    let payload = Payload::Chat(message.clone());
+
+    let compressed = wire::encode_with(&payload, &key, &topic_key, Compression::Auto)
+        .expect("Failed to encode payload");
+    let uncompressed = wire::encode_with(&payload, &key, &topic_key, Compression::Never)
+        .expect("Failed to encode payload");
+    assert!(
+        compressed.len() < uncompressed.len(),
+        "Compressed frame ({} bytes) should be smaller than uncompressed ({} bytes)",
+        compressed.len(),
+        uncompressed.len()
+    );
+
+    let envelope = wire::decode(&compressed, &topic_key).expect("Failed to decode frame");
+    let Payload::Chat(decoded) = envelope.payload else {
+        panic!("Expected a chat payload");
+    };
+    assert_same_message(&decoded, &message);
+}
+
+/// # Test: Frame Flags Are Signed
+///
+/// This test verifies that the compression flag cannot be changed without
+/// invalidating the signature of the frame.
+///
+/// ## Steps:
+/// 1. Encode a short chat message, which is not compressed
+/// 2. Set the compression flag, the second byte of the frame
+///
+/// ## Assertions:
+/// - The modified frame is rejected
+#[test]
+fn test_frame_flags_are_signed() {
+    let topic_key = topic_key();
+    let mut bytes = wire::encode(&Payload::Chat(sample_message()), &secret_key(), &topic_key)
+        .expect("Failed to encode payload");
+    assert_eq!(bytes[1], 0, "Short payload should not be compressed");
+
+    bytes[1] = 1;
+    assert!(
+        wire::decode(&bytes, &topic_key).is_err(),
+        "Frame with modified flags should be rejected"
+    );
+}
+
+/// # Test: Version 4 Frames Without Flags
+///
+/// This test verifies that frames from peers that do not compress their
+/// messages yet are still readable.
+///
+/// ## Steps:
+/// 1. Encode, encrypt and sign a chat message in the version 4 frame layout
+/// 2. Decode it with the wire decoder
+///
+/// ## Assertions:
+/// - The frame decodes with version 4 and the signing node as sender
+/// - The decoded chat message matches the original
+#[test]
+fn test_version_4_frames_without_flags() {
+    #[derive(Serialize)]
+    struct UnflaggedFrame {
+        version: u8,
+        sender: iroh::PublicKey,
+        signature: ed25519_dalek::Signature,
+        payload: Vec<u8>,
+    }
+
+    let message = sample_message();
+    let key = secret_key();
+    let topic_key = topic_key();
+
+    let payload =
+        postcard::to_stdvec(&Payload::Chat(message.clone())).expect("Failed to encode payload");
+    let payload = topic_key
+        .encrypt(&payload)
+        .expect("Failed to encrypt payload");
+    let bytes = postcard::to_stdvec(&UnflaggedFrame {
+        version: 4,
+        sender: key.public(),
+        signature: key.sign(&payload),
+        payload,
+    })
+    .expect("Failed to encode version 4 frame");
+
+    let envelope = wire::decode(&bytes, &topic_key).expect("Failed to decode version 4 frame");
+    assert_eq!(envelope.version, 4, "Version should be 4");
+    assert_eq!(
+        envelope.sender,
+        key.public(),
+        "Sender should be the signing node"
+    );
+
+    let Payload::Chat(decoded) = envelope.payload else {
+        panic!("Expected a chat payload");
+    };
+    assert_same_message(&decoded, &message);
+}
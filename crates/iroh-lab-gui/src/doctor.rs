@@ -0,0 +1,69 @@
+//! The `iroh-lab doctor` command, printing the environment checks.
+
+use iroh_lab_core::diagnostics::{self, CheckStatus, Report};
+use std::io::IsTerminal;
+
+// ANSI escape sequences coloring the report
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const RED: &str = "\x1b[31m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Runs the checks, prints the report and returns the exit code of the
+/// command: 0 when no check failed, 1 otherwise.
+pub fn run() -> i32 {
+    let runtime = match tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to create Tokio runtime: {}", e);
+            return 1;
+        }
+    };
+
+    println!("Checking the environment, this takes up to a minute…\n");
+    let report = runtime.block_on(diagnostics::run());
+
+    // Only color the report for terminals that asked for it
+    let color = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    print!("{}", format_report(&report, color));
+
+    if report.is_healthy() {
+        0
+    } else {
+        1
+    }
+}
+
+fn format_report(report: &Report, color: bool) -> String {
+    let paint = |code: &str, text: &str| {
+        if color {
+            format!("{}{}{}", code, text, RESET)
+        } else {
+            text.to_string()
+        }
+    };
+
+    let mut out = String::new();
+    for check in &report.checks {
+        let status = match check.status {
+            CheckStatus::Pass => paint(GREEN, "PASS"),
+            CheckStatus::Warn => paint(YELLOW, "WARN"),
+            CheckStatus::Fail => paint(RED, "FAIL"),
+        };
+        out += &format!("[{}] {:<14} {}\n", status, check.name, check.detail);
+        if let Some(hint) = check.hint {
+            out += &format!("       {}\n", paint(DIM, &format!("hint: {}", hint)));
+        }
+    }
+
+    out += if report.is_healthy() {
+        "\nNo problems found.\n"
+    } else {
+        "\nSome checks failed, see the hints above.\n"
+    };
+    out
+}
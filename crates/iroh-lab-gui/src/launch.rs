@@ -0,0 +1,67 @@
+//! Command line options of the application, for going straight to a topic.
+//!
+//! `iroh-lab --username alice --join <ticket>` joins a topic, and
+//! `iroh-lab --username alice --create <topic>` creates one, without going
+//! through the welcome screen and main menu. Without `--username`, the name
+//! saved in the settings is used, and the welcome screen only shows when
+//! there is none.
+use clap::Parser;
+use iroh_lab_core::config::LogLevel;
+use std::path::PathBuf;
+
+/// Options given on the command line.
+#[derive(Debug, Clone, Default, PartialEq, Parser)]
+#[command(
+    name = "iroh-lab",
+    about = "Peer-to-peer chat over iroh",
+    after_help = "Other commands: doctor, verify [<topic hash or archive file>], demo <script.toml> [--speed <factor>]"
+)]
+pub struct Args {
+    /// Name to chat under, skipping the welcome screen.
+    #[arg(long)]
+    pub username: Option<String>,
+    /// Ticket or invite of a topic to join right away.
+    #[arg(long, value_name = "TICKET", conflicts_with = "create")]
+    pub join: Option<String>,
+    /// Name of a topic to create right away.
+    #[arg(long, value_name = "TOPIC")]
+    pub create: Option<String>,
+    /// Configuration file to use instead of the one of the current user.
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// Most detailed log messages written this time: error, warn, info,
+    /// debug or trace.
+    #[arg(long, value_name = "LEVEL")]
+    pub log_level: Option<LogLevel>,
+}
+
+/// What to do once the network is up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Launch {
+    /// Join the topic of a ticket or invite.
+    Join(String),
+    /// Create a topic of that name.
+    Create(String),
+}
+
+impl Args {
+    /// The name given, unless it is blank.
+    pub fn username(&self) -> Option<String> {
+        self.username
+            .as_deref()
+            .map(str::trim)
+            .filter(|username| !username.is_empty())
+            .map(str::to_string)
+    }
+
+    /// The topic to join or create, if one was given.
+    pub fn launch(&self) -> Option<Launch> {
+        let join = self.join.as_deref().map(str::trim);
+        let create = self.create.as_deref().map(str::trim);
+        match (join, create) {
+            (Some(ticket), _) if !ticket.is_empty() => Some(Launch::Join(ticket.to_string())),
+            (_, Some(topic)) if !topic.is_empty() => Some(Launch::Create(topic.to_string())),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,4 @@
+//! The parts of the desktop application worth testing on their own. The
+//! application itself is the `iroh-lab` binary.
+pub mod launch;
+pub mod paste;
@@ -0,0 +1,5497 @@
+use chrono::{DateTime, Local, Utc, Weekday};
+use clap::Parser;
+use futures::StreamExt;
+use iced::{
+    alignment, clipboard, event, executor, keyboard, subscription, time,
+    widget::{self, checkbox, column, container, image, row, scrollable, text, text_input, Space},
+    window, Alignment, Application, Command, Element, Event, Length, Settings, Subscription, Theme,
+};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tracing::{info, warn, Level};
+use tracing_subscriber::{fmt::Formatter, reload, EnvFilter, FmtSubscriber};
+
+// Import our client module
+use iroh::NodeId;
+use iroh_lab_core::archive::Archive;
+use iroh_lab_core::audit::{self, AuditEntry, AuditEvent};
+use iroh_lab_core::automation::{self, ExportRule, Run, RunHistory, WEEKDAYS};
+use iroh_lab_core::blobs::{BlobHash, MAX_BLOB_SIZE};
+use iroh_lab_core::bridge::{self, Bridges};
+use iroh_lab_core::calendar::{self, Calendar, RsvpResponse};
+use iroh_lab_core::client::{
+    Allowlist, Attachment, Avatar, CalendarEvent, ChatMessage, ClientEvent, IrohClient,
+    MessageDelete, MessageEdit, Presence, Profile, Rsvp, TaskUpdate,
+};
+use iroh_lab_core::commands::{self, SlashCommand};
+use iroh_lab_core::composer::{self, InputHistory, NameCompletion};
+use iroh_lab_core::config::{
+    muted_name, Config, IpVersion, LargeMessages, LinkConfirmation, LogLevel, MessageFormat,
+    MessageLimit, Notifications, RelayMode, SendKey, ThemePreference,
+};
+use iroh_lab_core::contacts::Contacts;
+use iroh_lab_core::conversation::Conversation;
+use iroh_lab_core::delivery::DeliveryState;
+use iroh_lab_core::demo::{self, Script};
+use iroh_lab_core::diagnostics::{Connectivity, NodeStatus};
+use iroh_lab_core::emoji;
+use iroh_lab_core::filter::{ContentFilter, FilterAction, Filters};
+use iroh_lab_core::handle::ClientHandle;
+use iroh_lab_core::heatmap::ActivityHeatmap;
+use iroh_lab_core::last_seen::{ExportFormat, LastSeen};
+use iroh_lab_core::liveness::TopicActivity;
+use iroh_lab_core::markdown::{self, Link};
+use iroh_lab_core::metrics::{LatencyHistogram, TrafficStats};
+use iroh_lab_core::notes::{Notes, NOTES_TOPIC_HASH, NOTES_TOPIC_NAME};
+use iroh_lab_core::profile::{Profiles, UserProfile};
+use iroh_lab_core::reconnect::{self, ReconnectState};
+use iroh_lab_core::reputation::BLOCK_THRESHOLD;
+use iroh_lab_core::room::Room;
+use iroh_lab_core::roster::{Member, PresenceStatus, Roster};
+use iroh_lab_core::tasks::{TaskChange, TaskList};
+use iroh_lab_core::ticket::{self, Ticket};
+use iroh_lab_core::unread::Unread;
+use iroh_lab_core::welcome::{self, Welcomed};
+use iroh_lab_gui::launch::{Args, Launch};
+use iroh_lab_gui::paste::{self, PastedImage};
+
+#[cfg(feature = "cli")]
+mod doctor;
+mod ui;
+#[cfg(feature = "cli")]
+mod verify;
+use ui::components::{
+    activity_heatmap, activity_label, banner, button_row, connectivity_chip, day_separator,
+    delivery_details, event_card, heading, hidden_messages, key_bindings, labeled_input,
+    labeled_pick_list, member_list, message_row, primary_button, screen_container,
+    secondary_button, task_list, titled_card, traffic_panel, unread_badge, Assignee,
+};
+use ui::focus;
+use ui::layout::LayoutMode;
+use ui::shortcuts::{self, Shortcut};
+use ui::timeline;
+use ui::tour::{Tour, TourStep};
+use ui::virtual_list;
+use ui::widgets::{coach_mark, context_menu, emoji_picker, topic_switcher, MenuItem};
+
+fn main() -> iced::Result {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // `iroh-lab doctor` checks the environment instead of starting the chat
+    #[cfg(feature = "cli")]
+    if args.first().map(String::as_str) == Some("doctor") {
+        std::process::exit(doctor::run());
+    }
+
+    // `iroh-lab verify` checks the archives for tampering
+    #[cfg(feature = "cli")]
+    if args.first().map(String::as_str) == Some("verify") {
+        std::process::exit(verify::run(&args[1..]));
+    }
+
+    // `iroh-lab demo <script>` plays a conversation instead of joining one
+    let demo = if args.first().map(String::as_str) == Some("demo") {
+        match parse_demo_args(&args[1..]) {
+            Ok(demo) => Some(demo),
+            Err(e) => {
+                eprintln!(
+                    "{}\n\nUsage: iroh-lab demo <script.toml> [--speed <factor>]",
+                    e
+                );
+                std::process::exit(2);
+            }
+        }
+    } else {
+        None
+    };
+
+    // Otherwise the options may go straight to a topic
+    let options = if demo.is_none() {
+        Args::parse()
+    } else {
+        Args::default()
+    };
+
+    // The settings choose the log level, so they are loaded before logging
+    let config = match &options.config {
+        Some(path) => Config::load_from(path),
+        None => Config::load(),
+    };
+
+    // Initialize tracing for stdout
+    let log_level = options
+        .log_level
+        .or_else(|| config.as_ref().ok().map(|config| config.log_level));
+    let builder = FmtSubscriber::builder()
+        .with_env_filter(log_filter(log_level.unwrap_or_default()))
+        .with_target(true)
+        .with_line_number(true)
+        .with_filter_reloading();
+    let _ = LOG_FILTER.set(builder.reload_handle());
+    let subscriber = builder.finish();
+
+    tracing::subscriber::set_global_default(subscriber).expect("Failed to set tracing subscriber");
+
+    info!("Starting Iroh Chat application");
+    let config = config.unwrap_or_else(|e| {
+        warn!("Using default settings: {}", e);
+        Config::default()
+    });
+
+    // Create a Tokio runtime for our application
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to create Tokio runtime");
+
+    // Enter the runtime context
+    let _guard = runtime.enter();
+
+    IrohChat::run(Settings {
+        // The node is shut down before the window closes
+        exit_on_close_request: false,
+        ..Settings::with_flags(Flags {
+            config,
+            config_path: options.config.clone(),
+            username: options.username(),
+            launch: options.launch(),
+            demo,
+        })
+    })
+}
+
+// Filter of the log messages, replaced when the user picks another level
+static LOG_FILTER: OnceLock<reload::Handle<EnvFilter, Formatter>> = OnceLock::new();
+
+// Log messages up to `level`, keeping noisy dependencies quiet
+fn log_filter(level: LogLevel) -> EnvFilter {
+    EnvFilter::from_default_env()
+        .add_directive(Level::from(level).into())
+        .add_directive("iced=warn".parse().unwrap())
+        .add_directive("tokio=info".parse().unwrap())
+        .add_directive("async_std=info".parse().unwrap())
+}
+
+// Settings loaded before the application starts, where they are saved if
+// not in the default location, the options going straight to a topic, and
+// the demo to play
+struct Flags {
+    config: Config,
+    config_path: Option<PathBuf>,
+    username: Option<String>,
+    launch: Option<Launch>,
+    demo: Option<Demo>,
+}
+
+// How often the last seen times are saved while peers are active
+const LAST_SEEN_SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+// How often the scheduled exports are checked
+const EXPORT_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+// How often the shared folders are checked for new files
+const BRIDGE_SCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+// Distance from the bottom of the message list, in logical pixels, within
+// which it counts as showing the latest message
+const LATEST_MARGIN: f32 = 20.0;
+
+// Height of the preview of an image pasted into the composer
+const PASTE_PREVIEW_HEIGHT: f32 = 80.0;
+
+// How often the diagnostics screen is refreshed
+const DIAGNOSTICS_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+// Schedule offered for topics without an export rule
+const DEFAULT_EXPORT_HOUR: u32 = 9;
+const DEFAULT_EXPORT_DAYS: u32 = 7;
+
+// Name of the user in demos, who has not picked one
+const DEMO_USERNAME: &str = "You";
+
+// A topic kept in this window, never joined on the network
+enum LocalTopic {
+    // A scripted conversation playing, on a topic of this hash
+    Demo(String),
+    // The notes to self, saved with every message
+    Notes(Notes),
+}
+
+// A scripted conversation to play, and how fast
+struct Demo {
+    script: Script,
+    speed: f64,
+}
+
+fn parse_demo_args(args: &[String]) -> Result<Demo, String> {
+    let mut script = None;
+    let mut speed = 1.0;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--speed" {
+            speed = args
+                .next()
+                .and_then(|speed| speed.parse::<f64>().ok())
+                .filter(|speed| speed.is_finite() && *speed > 0.0)
+                .ok_or_else(|| "--speed needs a positive number".to_string())?;
+        } else if script.is_none() {
+            script = Some(Script::load(Path::new(arg))?);
+        } else {
+            return Err(format!("Unexpected argument {}", arg));
+        }
+    }
+
+    Ok(Demo {
+        script: script.ok_or_else(|| "No script given".to_string())?,
+        speed,
+    })
+}
+
+// The export rule entered on the topic settings screen
+fn parse_export_rule(
+    topic_name: String,
+    weekday: Weekday,
+    hour: &str,
+    days: &str,
+    folder: &str,
+    prune: bool,
+) -> Result<ExportRule, String> {
+    let hour = hour
+        .trim()
+        .parse::<u32>()
+        .ok()
+        .filter(|hour| *hour < 24)
+        .ok_or_else(|| "The hour should be between 0 and 23".to_string())?;
+    let days = days
+        .trim()
+        .parse::<u32>()
+        .ok()
+        .filter(|days| *days > 0)
+        .ok_or_else(|| "The number of days should be at least 1".to_string())?;
+    if folder.trim().is_empty() {
+        return Err("Choose a folder to export to".to_string());
+    }
+    Ok(ExportRule {
+        topic_name,
+        weekday,
+        hour,
+        days,
+        folder: folder.trim().into(),
+        prune,
+        since: Utc::now(),
+    })
+}
+
+// Whether the desktop is set to dark colors
+fn system_is_dark() -> bool {
+    dark_light::detect() == dark_light::Mode::Dark
+}
+
+// Opens a web address from a message in the browser, without waiting for it
+fn open_link(address: &str) {
+    info!(address, "Opening link");
+    if let Err(e) = open::that_detached(address) {
+        warn!("Failed to open {}: {}", address, e);
+    }
+}
+
+// Reads an image to use as avatar, no larger than a blob may be
+fn read_avatar_image(path: &Path) -> Result<Vec<u8>, String> {
+    let image =
+        std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if image.is_empty() || image.len() > MAX_BLOB_SIZE {
+        return Err(format!(
+            "Avatar images must be at most {} KiB",
+            MAX_BLOB_SIZE / 1024
+        ));
+    }
+    Ok(image)
+}
+
+// The scrollable holding the messages of the chat room
+fn message_list() -> scrollable::Id {
+    scrollable::Id::new("messages")
+}
+
+// Whether `message` holds `query`, lowercase, in its text or author name
+fn matches_search(message: &ChatMessage, query: &str) -> bool {
+    !message.is_deleted()
+        && (message.content.to_lowercase().contains(query)
+            || message.author.to_lowercase().contains(query))
+}
+
+// A line of the run history of a topic
+fn describe_run(run: &Run) -> String {
+    let at = run.at.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+    match (&run.error, &run.file) {
+        (Some(error), _) => format!("{}: failed, {}", at, error),
+        (None, Some(file)) if run.prune_held => format!(
+            "{}: exported {} message(s) to {}, not pruned while on hold",
+            at,
+            run.exported,
+            file.display()
+        ),
+        (None, Some(file)) if run.pruned > 0 => format!(
+            "{}: exported {} message(s) to {}, pruned {}",
+            at,
+            run.exported,
+            file.display(),
+            run.pruned
+        ),
+        (None, Some(file)) => format!(
+            "{}: exported {} message(s) to {}",
+            at,
+            run.exported,
+            file.display()
+        ),
+        (None, None) => format!("{}: nothing exported", at),
+    }
+}
+
+// Application state
+struct IrohChat {
+    // UI state
+    input_state: InputState,
+
+    // Chat state
+    // The topic shown, while one is
+    room: Option<Room>,
+    // How well our node is connected, on every topic
+    connectivity: Connectivity,
+    // Members of the current topic, and the status we announce on it
+    roster: Roster,
+    // Messages of our topics that arrived while their room was not shown
+    unread: Unread,
+    // When the messages of a topic were sent, computed once its statistics
+    // are shown and kept up to date while its messages are
+    heatmaps: HashMap<String, ActivityHeatmap>,
+    // Profiles announced by the members of our topics, and our own
+    profiles: Profiles,
+    // Avatar images of members, loaded from the blob store
+    avatars: HashMap<NodeId, image::Handle>,
+    presence: PresenceStatus,
+    // Messages of the current topic, and the numbering of those we send
+    conversation: Conversation,
+    // How long messages of others took to show up on the current topic
+    latency: LatencyHistogram,
+
+    // Hash-chained record of the current topic's messages, when enabled
+    archive: Option<Archive>,
+    // Latest runs of the scheduled exports
+    export_runs: RunHistory,
+
+    // Client state. Unlike the engine and the terminal client, the view
+    // needs far more of the client than a ChatTransport offers
+    client: ClientHandle,
+    // Events missed by falling behind the client, as of the latest one
+    events_dropped: u64,
+
+    // Id of our own message being edited in the composer, if any
+    editing: Option<String>,
+
+    // Currently open context menu, if any
+    context_menu: Option<ContextTarget>,
+
+    // Layout matching the current window size
+    layout: LayoutMode,
+    // Height of the window, bounding how much of the message list is visible
+    window_height: f32,
+    // How far the message list is scrolled down, in logical pixels
+    message_scroll: f32,
+    // Whether the message list shows the latest message, and how many
+    // messages arrived since it was scrolled away from it
+    at_latest: bool,
+    unseen: usize,
+    // Whether sending the large message in the composer waits for the user
+    // to confirm it
+    confirming_send: bool,
+    // Modifier keys held down, telling Enter from Shift+Enter in the composer
+    modifiers: keyboard::Modifiers,
+    // Messages sent on each topic, recalled into the composer
+    input_history: InputHistory,
+    // Completes the names of members typed into the composer
+    name_completion: NameCompletion,
+    // Why what was typed into the composer could not be sent, or the command
+    // could not run
+    composer_error: Option<String>,
+    // What is searched in the emoji picker, while it is open
+    emoji_search: Option<String>,
+    // The event being shared, while its form is open
+    event_form: Option<EventForm>,
+    // Address of the link waiting for the user to confirm opening it
+    confirming_link: Option<String>,
+    // Id of our message whose delivery details are shown
+    delivery_details: Option<String>,
+    // The image pasted into the composer with its preview, until it is sent
+    // or discarded
+    pasted_image: Option<(PastedImage, image::Handle)>,
+    // What is typed into the topic switcher, while it is open
+    switcher: Option<String>,
+    // What the messages of the chat room are searched for, while searching
+    message_search: Option<String>,
+    // Whether the messages of muted members are shown in the chat room,
+    // until another topic is entered
+    show_muted: bool,
+
+    // Topic name and ticket of a valid ticket found in the clipboard
+    clipboard_ticket: Option<(String, String)>,
+
+    // Persisted user settings, and the file they are saved to if not the
+    // default one
+    config: Config,
+    config_path: Option<PathBuf>,
+    // Topic to join or create once the network is up and we have a name
+    launch: Option<Launch>,
+    // Whether the desktop uses dark colors, checked when the window gets focus
+    system_dark: bool,
+
+    // When each peer was last heard from, and when that was last saved
+    last_seen: LastSeen,
+    last_seen_saved: Instant,
+    // Newcomers already welcomed on the topics with a welcome message
+    welcomed: Welcomed,
+    // Messages flagged as action items, and whether the room shows them
+    // instead of the messages
+    tasks: TaskList,
+    show_tasks: bool,
+    // Network statistics of the topic, while they are shown
+    traffic: Option<TrafficStats>,
+    // Calendar events shared on the topics, and the file the last one
+    // exported was saved to, by the id of the message announcing it
+    calendar: Calendar,
+    event_exported: Option<(String, PathBuf)>,
+    // Files posted from or saved to the folders shared with topics
+    bridges: Bridges,
+    // Nodes the user saved as contacts
+    contacts: Contacts,
+
+    // Guided tour, until the user finished or skipped it
+    tour: Option<Tour>,
+
+    // Set while on a topic that never touches the network
+    local: Option<LocalTopic>,
+
+    // Error message
+    error: Option<String>,
+}
+
+// What is typed into the form sharing a calendar event
+#[derive(Debug, Clone, Default)]
+struct EventForm {
+    title: String,
+    // Local times as typed, in the format of `calendar::TIME_FORMAT`
+    starts: String,
+    ends: String,
+    location: String,
+}
+
+// A field of the event form
+#[derive(Debug, Clone, Copy)]
+enum EventField {
+    Title,
+    Starts,
+    Ends,
+    Location,
+}
+
+// Elements of the UI that offer a context menu
+#[derive(Debug, Clone, PartialEq)]
+enum ContextTarget {
+    // A chat message, identified by its id
+    ChatMessage(String),
+    // The ticket shown after creating a topic
+    Ticket,
+    // A member in the member list
+    Member(NodeId),
+}
+
+// Input state for different screens
+#[derive(Clone)]
+enum InputState {
+    Welcome {
+        username: String,
+    },
+    MainMenu {
+        username: String,
+    },
+    CreateTopic {
+        username: String,
+        topic_name: String,
+    },
+    JoinTopic {
+        username: String,
+        ticket: String,
+    },
+    TopicCreated {
+        username: String,
+        topic_name: String,
+        ticket: String,
+    },
+    ChatRoom {
+        username: String,
+        message: String,
+    },
+    EditProfile {
+        username: String,
+        profile: UserProfile,
+        // Path of a new avatar image, and the image kept otherwise
+        image_path: String,
+        avatar_image: Option<BlobHash>,
+    },
+    Settings {
+        username: String,
+        // Patterns filtered on every topic, applied when leaving
+        filtered: String,
+    },
+    Diagnostics {
+        username: String,
+        // Refreshed while the screen is shown, none until the network is up
+        status: Option<NodeStatus>,
+        // The node address typed while adding a peer manually
+        peer_addr: Option<String>,
+    },
+    TopicSettings {
+        username: String,
+        // Unsent message of the chat room, restored when going back
+        draft: String,
+        weekday: Weekday,
+        hour: String,
+        days: String,
+        folder: String,
+        prune: bool,
+        // Where the members were exported to last
+        members_exported: Option<PathBuf>,
+        welcome: String,
+        bridge_folder: String,
+        // The node id or member name typed to allow into a private topic
+        allowed_node: String,
+        // Patterns filtered on the topic besides those filtered everywhere
+        filtered: String,
+        filter_action: FilterAction,
+    },
+}
+
+// Messages for the Iced application
+#[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
+enum Message {
+    // Input events
+    UsernameChanged(String),
+    TopicNameChanged(String),
+    TicketChanged(String),
+    MessageChanged(String),
+    MessagePasted(String),
+    MessagesScrolled(scrollable::Viewport),
+    JumpToLatest,
+
+    // Button events
+    SubmitUsername,
+    CreateTopicSelected,
+    EditProfileSelected,
+    DisplayNameChanged(String),
+    StatusLineChanged(String),
+    AvatarChanged(String),
+    AvatarImagePathChanged(String),
+    RemoveAvatarImage,
+    SaveProfile,
+    AvatarImageStored(Result<BlobHash, String>),
+    NotesSelected,
+    SettingsSelected,
+    ThemeChanged(ThemePreference),
+    NotificationsChanged(Notifications),
+    RelayModeChanged(RelayMode),
+    IpVersionChanged(IpVersion),
+    LogLevelChanged(LogLevel),
+    SendKeyChanged(SendKey),
+    LargeMessagesChanged(LargeMessages),
+    MessageFormatChanged(MessageFormat),
+    MessageLimitChanged(MessageLimit),
+    LinkConfirmationChanged(LinkConfirmation),
+    ContentFilterChanged(String),
+    FilterActionChanged(FilterAction),
+    LeaveSettings,
+    DiagnosticsSelected,
+    RefreshDiagnostics,
+    AddPeerSelected,
+    PeerAddrChanged(String),
+    SubmitAddPeer,
+    CancelAddPeer,
+    PeerAdded(Result<usize, String>),
+    JoinTopicSelected,
+    BackToMenu,
+    SubmitCreateTopic,
+    SubmitJoinTopic,
+    EnterChatRoom,
+    SendMessage,
+    ComposerSubmitted,
+    RecallPrevious,
+    RecallNext,
+    ModifiersChanged(keyboard::Modifiers),
+    CancelSend,
+    ToggleEmojiPicker,
+    EmojiSearchChanged(String),
+    EmojiPicked(String),
+    StartEdit(String),
+    CancelEdit,
+    DeleteMessage(String),
+
+    // Clipboard
+    CopyTicket,
+    CopyInvite,
+    InviteCreated(Result<String, String>),
+    CopyNodeAddr,
+    CopyToClipboard(String),
+    ClipboardRead(Option<String>),
+    JoinFromClipboard,
+
+    // Context menus
+    OpenContextMenu(ContextTarget),
+    CloseContextMenu,
+
+    // Network events
+    NetworkInitialized(Result<String, String>),
+    TopicCreated(Result<(String, String, String), String>),
+    TopicJoined(Result<(String, String), String>),
+    MessageReceived(ChatMessage),
+    MessageSent(Result<ChatMessage, String>),
+    EditReceived(MessageEdit, Option<NodeId>),
+    EditSent(Result<MessageEdit, String>),
+    DeleteReceived(MessageDelete, Option<NodeId>),
+    DeleteSent(Result<MessageDelete, String>),
+    UpdateTask(String, TaskChange),
+    TaskReceived(TaskUpdate, NodeId),
+    TaskSent(Result<TaskUpdate, String>),
+    ToggleTasks,
+    ToggleTraffic,
+    ToggleEventForm,
+    Shortcut(Shortcut),
+    SwitcherQueryChanged(String),
+    SwitchToFirstMatch,
+    SwitchTopic(String),
+    OpenLink(Link),
+    ConfirmOpenLink,
+    CancelOpenLink,
+    ShowDeliveryDetails(String),
+    CloseDeliveryDetails,
+    ShowMutedMessages,
+    // Mutes or unmutes the sender of a message, by node and name
+    ToggleMute(Option<NodeId>, String),
+    MessageSearchChanged(String),
+    CloseMessageSearch,
+    EventFieldChanged(EventField, String),
+    ShareEvent,
+    EventShared(Result<(ChatMessage, CalendarEvent), String>),
+    EventReceived(CalendarEvent, NodeId),
+    RespondToEvent(String, RsvpResponse),
+    RsvpReceived(Rsvp, NodeId),
+    RsvpSent(Result<Rsvp, String>),
+    ExportEvent(String),
+    ActivityChanged(TopicActivity),
+    ConnectivityChanged(Connectivity),
+    ReconnectChanged(ReconnectState),
+    ClientError(String),
+    PresenceReceived(Presence),
+    ProfileReceived(Profile),
+    AvatarReceived(Avatar),
+    DeliveryChanged(String, DeliveryState),
+    Resync,
+    Resynced(Result<(), String>),
+    ClientUpdated(Result<(), String>),
+    ToggleArchive,
+    SaveMembersAsContacts,
+    SendHistory(NodeId),
+    HistorySent(Result<usize, String>),
+
+    // Topic settings
+    TopicSettingsSelected,
+    ExportWeekdayChanged(Weekday),
+    ExportHourChanged(String),
+    ExportDaysChanged(String),
+    ExportFolderChanged(String),
+    ExportPruneToggled(bool),
+    SaveExportRule,
+    RemoveExportRule,
+    RunExportNow,
+    ExportMembers(ExportFormat),
+    ExportCalendar,
+    StopCalendarFeed,
+    WelcomeTemplateChanged(String),
+    SaveWelcome,
+    ToggleHold,
+    ToggleTrustedLinks,
+    AllowedNodeChanged(String),
+    AllowNode,
+    DisallowNode(NodeId),
+    ToggleAllowlist,
+    AllowlistPublished(Result<Allowlist, String>),
+    TopicFilterChanged(String),
+    TopicFilterActionChanged(FilterAction),
+    SaveTopicFilter,
+    LeaveTopicSettings,
+    RunScheduledExports,
+    BridgeFolderChanged(String),
+    ToggleBridge,
+    ScanBridges,
+    AttachmentPosted(String, String, Result<Attachment, String>),
+    AttachmentReceived(Attachment),
+    AttachmentFetched(Attachment, Result<Vec<u8>, String>),
+    SendPastedImage,
+    DiscardPastedImage,
+    PastedImageSent(Result<Attachment, String>),
+
+    // Window events
+    WindowResized(u32, u32),
+    WindowFocused,
+    WindowUnfocused,
+    CloseRequested,
+    ShutDown,
+
+    // Keyboard navigation
+    CompleteName,
+    FocusPrevious,
+
+    // Guided tour
+    TourNext(TourStep),
+    TourSkip,
+
+    // Events of the client, with how many were dropped so far
+    Incoming(ClientEvent, u64),
+    // Refreshing the traffic panel
+    Tick,
+}
+
+impl Application for IrohChat {
+    type Executor = executor::Default;
+    type Message = Message;
+    type Theme = Theme;
+    type Flags = Flags;
+
+    fn new(
+        Flags {
+            mut config,
+            config_path,
+            username,
+            launch,
+            demo,
+        }: Flags,
+    ) -> (Self, Command<Message>) {
+        // A name given on the command line replaces the one saved
+        let named = username.is_some();
+        let remember = username.filter(|username| *username != config.username);
+        if let Some(username) = &remember {
+            config.username = username.clone();
+        }
+
+        let last_seen = LastSeen::default_path()
+            .map(|path| LastSeen::load_from(&path))
+            .unwrap_or_else(|| Ok(LastSeen::default()))
+            .unwrap_or_else(|e| {
+                warn!("Forgetting when peers were last seen: {}", e);
+                LastSeen::default()
+            });
+
+        let welcomed = Welcomed::default_path()
+            .map(|path| Welcomed::load_from(&path))
+            .unwrap_or_else(|| Ok(Welcomed::default()))
+            .unwrap_or_else(|e| {
+                warn!("Forgetting who was welcomed: {}", e);
+                Welcomed::default()
+            });
+
+        let tasks = TaskList::default_path()
+            .map(|path| TaskList::load_from(&path))
+            .unwrap_or_else(|| Ok(TaskList::default()))
+            .unwrap_or_else(|e| {
+                warn!("Starting without action items: {}", e);
+                TaskList::default()
+            });
+
+        let calendar = Calendar::default_path()
+            .map(|path| Calendar::load_from(&path))
+            .unwrap_or_else(|| Ok(Calendar::default()))
+            .unwrap_or_else(|e| {
+                warn!("Starting without calendar events: {}", e);
+                Calendar::default()
+            });
+
+        let bridges = Bridges::default_path()
+            .map(|path| Bridges::load_from(&path))
+            .unwrap_or_else(|| Ok(Bridges::default()))
+            .unwrap_or_else(|e| {
+                warn!("Starting without shared files: {}", e);
+                Bridges::default()
+            });
+
+        let contacts = Contacts::default_path()
+            .map(|path| Contacts::load_from(&path))
+            .unwrap_or_else(|| Ok(Contacts::default()))
+            .unwrap_or_else(|e| {
+                warn!("Starting with an empty contacts book: {}", e);
+                Contacts::default()
+            });
+
+        let export_runs = RunHistory::default_path()
+            .map(|path| RunHistory::load_from(&path))
+            .unwrap_or_else(|| Ok(RunHistory::default()))
+            .unwrap_or_else(|e| {
+                warn!("Forgetting the export runs: {}", e);
+                RunHistory::default()
+            });
+
+        // Initialized below, unless a demo runs without the network
+        let mut client = IrohClient::new();
+        client.set_relay_mode(config.relay_mode);
+        client.set_binding(config.binding);
+        match Filters::from_config(&config) {
+            Ok(filters) => client.set_filters(filters),
+            Err(e) => warn!("Filtering nothing: {}", e),
+        }
+
+        let mut app = Self {
+            input_state: InputState::Welcome {
+                username: config.username.clone(),
+            },
+            room: None,
+            connectivity: Connectivity::default(),
+            roster: Roster::default(),
+            unread: Unread::default(),
+            heatmaps: HashMap::new(),
+            profiles: Profiles::default(),
+            avatars: HashMap::new(),
+            presence: PresenceStatus::default(),
+            conversation: Conversation::new(),
+            latency: LatencyHistogram::default(),
+            archive: None,
+            export_runs,
+            client: ClientHandle::spawn(client),
+            events_dropped: 0,
+            editing: None,
+            context_menu: None,
+            layout: LayoutMode::default(),
+            window_height: window::Settings::default().size.1 as f32,
+            message_scroll: 0.0,
+            at_latest: true,
+            unseen: 0,
+            confirming_send: false,
+            modifiers: keyboard::Modifiers::default(),
+            input_history: InputHistory::default(),
+            name_completion: NameCompletion::default(),
+            composer_error: None,
+            emoji_search: None,
+            pasted_image: None,
+            delivery_details: None,
+            confirming_link: None,
+            event_form: None,
+            switcher: None,
+            message_search: None,
+            show_muted: false,
+            clipboard_ticket: None,
+            tour: (!config.tour_completed).then(Tour::new),
+            config,
+            config_path,
+            launch,
+            system_dark: system_is_dark(),
+            last_seen,
+            last_seen_saved: Instant::now(),
+            welcomed,
+            tasks,
+            show_tasks: false,
+            traffic: None,
+            calendar,
+            event_exported: None,
+            bridges,
+            contacts,
+            local: None,
+            error: None,
+        };
+
+        // Demos run on a local-only topic, without the network
+        if let Some(Demo { script, speed }) = demo {
+            let topic_hash = demo::topic_hash();
+            app.input_state = InputState::ChatRoom {
+                username: DEMO_USERNAME.to_string(),
+                message: String::new(),
+            };
+            app.room = Some(Room::new(script.topic.clone()));
+            app.tour = None;
+            app.local = Some(LocalTopic::Demo(topic_hash.clone()));
+
+            // Pick up the last message right away once the script is done
+            let play = Command::perform(demo::play(script, topic_hash, speed), |()| Message::Tick);
+            return (
+                app,
+                Command::batch(vec![play, text_input::focus(focus::composer())]),
+            );
+        }
+
+        // A name given, or a topic to go to under the name saved, skips the
+        // welcome screen
+        if remember.is_some() {
+            app.save_config();
+        }
+        if (named || app.launch.is_some()) && !app.config.username.trim().is_empty() {
+            app.input_state = InputState::MainMenu {
+                username: app.config.username.clone(),
+            };
+        }
+
+        // Initialize network
+        let client = app.client.clone();
+        let command = Command::perform(
+            async move { client.initialize_network().await },
+            Message::NetworkInitialized,
+        );
+
+        (
+            app,
+            Command::batch(vec![command, text_input::focus(focus::username())]),
+        )
+    }
+
+    fn title(&self) -> String {
+        let title = match &self.room {
+            Some(room) => format!("Chat - {}", room.name()),
+            None => "Chat Application".to_string(),
+        };
+        match self.unread.total() {
+            0 => title,
+            unread => format!("({}) {}", unread, title),
+        }
+    }
+
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::UsernameChanged(username) => {
+                match &mut self.input_state {
+                    InputState::Welcome { username: u } => *u = username,
+                    InputState::MainMenu { username: u } => *u = username,
+                    InputState::CreateTopic { username: u, .. } => *u = username,
+                    InputState::JoinTopic { username: u, .. } => *u = username,
+                    InputState::TopicCreated { username: u, .. } => *u = username,
+                    InputState::ChatRoom { username: u, .. } => *u = username,
+                    InputState::EditProfile { username: u, .. } => *u = username,
+                    InputState::Settings { username: u, .. } => *u = username,
+                    InputState::Diagnostics { username: u, .. } => *u = username,
+                    InputState::TopicSettings { username: u, .. } => *u = username,
+                }
+                Command::none()
+            }
+
+            Message::TopicNameChanged(topic_name) => {
+                if let InputState::CreateTopic { topic_name: t, .. } = &mut self.input_state {
+                    *t = topic_name;
+                }
+                Command::none()
+            }
+
+            Message::TicketChanged(ticket) => {
+                if let InputState::JoinTopic { ticket: t, .. } = &mut self.input_state {
+                    *t = ticket;
+                }
+                Command::none()
+            }
+
+            Message::MessageChanged(message) => {
+                if let InputState::ChatRoom { message: m, .. } = &mut self.input_state {
+                    *m = composer::from_input(&message);
+                }
+                // A changed message is confirmed anew
+                self.confirming_send = false;
+                self.input_history.reset();
+                self.name_completion.reset();
+                self.composer_error = None;
+                Command::none()
+            }
+
+            // Pasting text changes the message, pasting an image offers to
+            // send it
+            Message::MessagePasted(message) => {
+                let unchanged = matches!(
+                    &self.input_state,
+                    InputState::ChatRoom { message: m, .. } if composer::to_input(m) == message
+                );
+                if !unchanged || self.local.is_some() {
+                    return self.update(Message::MessageChanged(message));
+                }
+                match paste::read_clipboard(Utc::now()) {
+                    Ok(Some(pasted)) => {
+                        let preview = image::Handle::from_memory(pasted.png.clone());
+                        self.pasted_image = Some((pasted, preview));
+                    }
+                    Ok(None) => {}
+                    Err(e) => self.composer_error = Some(e),
+                }
+                Command::none()
+            }
+
+            recall @ (Message::RecallPrevious | Message::RecallNext) => {
+                if self.editing.is_some() || !self.composer_has_focus() {
+                    return Command::none();
+                }
+                let (Some(topic_hash), InputState::ChatRoom { message, .. }) =
+                    (self.topic_hash(), &mut self.input_state)
+                else {
+                    return Command::none();
+                };
+                let recalled = if matches!(recall, Message::RecallPrevious) {
+                    self.input_history.previous(&topic_hash, message)
+                } else {
+                    self.input_history.next(&topic_hash)
+                };
+                match recalled {
+                    Some(recalled) => {
+                        *message = recalled;
+                        self.confirming_send = false;
+                        self.name_completion.reset();
+                        text_input::move_cursor_to_end(focus::composer())
+                    }
+                    None => Command::none(),
+                }
+            }
+
+            // Tab moves the focus unless it completes a name
+            Message::CompleteName => {
+                if !self.composer_has_focus() {
+                    return widget::focus_next();
+                }
+                let names = self.member_names();
+                let InputState::ChatRoom { message, .. } = &mut self.input_state else {
+                    return widget::focus_next();
+                };
+                match self.name_completion.complete(message, &names) {
+                    Some(completed) => {
+                        *message = completed;
+                        self.confirming_send = false;
+                        text_input::move_cursor_to_end(focus::composer())
+                    }
+                    None => widget::focus_next(),
+                }
+            }
+
+            Message::SubmitUsername => {
+                if let InputState::Welcome { username } = &self.input_state {
+                    if !username.trim().is_empty() {
+                        self.input_state = InputState::MainMenu {
+                            username: username.clone(),
+                        };
+                        self.remember_username();
+                        return Command::batch(vec![
+                            clipboard::read(Message::ClipboardRead),
+                            self.start_launch(),
+                        ]);
+                    }
+                }
+                Command::none()
+            }
+
+            Message::EditProfileSelected => {
+                if let InputState::MainMenu { username } = &self.input_state {
+                    self.input_state = InputState::EditProfile {
+                        username: username.clone(),
+                        profile: self.config.profile.clone(),
+                        image_path: String::new(),
+                        avatar_image: self.config.avatar_image,
+                    };
+                    return text_input::focus(focus::display_name());
+                }
+                Command::none()
+            }
+
+            Message::SettingsSelected => {
+                if let InputState::MainMenu { username } = &self.input_state {
+                    self.input_state = InputState::Settings {
+                        username: username.clone(),
+                        filtered: self.config.content_filter.patterns.join(", "),
+                    };
+                    return text_input::focus(focus::settings_username());
+                }
+                Command::none()
+            }
+
+            Message::ThemeChanged(theme) => {
+                self.config.theme = theme;
+                self.save_config();
+                Command::none()
+            }
+
+            Message::NotificationsChanged(notifications) => {
+                self.config.notifications = notifications;
+                self.save_config();
+                Command::none()
+            }
+
+            Message::RelayModeChanged(relay_mode) => {
+                self.config.relay_mode = relay_mode;
+                self.save_config();
+                Command::none()
+            }
+
+            Message::IpVersionChanged(ip_version) => {
+                self.config.binding.ip_version = ip_version;
+                self.save_config();
+                Command::none()
+            }
+
+            Message::LogLevelChanged(log_level) => {
+                self.config.log_level = log_level;
+                if let Some(filter) = LOG_FILTER.get() {
+                    if let Err(e) = filter.reload(log_filter(log_level)) {
+                        warn!("Failed to change the log level: {}", e);
+                    }
+                }
+                self.save_config();
+                Command::none()
+            }
+
+            Message::SendKeyChanged(send_key) => {
+                self.config.send_key = send_key;
+                self.save_config();
+                Command::none()
+            }
+
+            Message::LargeMessagesChanged(large_messages) => {
+                self.config.large_messages = large_messages;
+                self.save_config();
+                Command::none()
+            }
+
+            Message::MessageFormatChanged(message_format) => {
+                self.config.message_format = message_format;
+                self.save_config();
+                Command::none()
+            }
+
+            Message::MessageLimitChanged(message_limit) => {
+                self.config.message_limit = message_limit;
+                self.save_config();
+                Command::none()
+            }
+
+            Message::LinkConfirmationChanged(link_confirmation) => {
+                self.config.link_confirmation = link_confirmation;
+                self.save_config();
+                Command::none()
+            }
+
+            Message::ContentFilterChanged(patterns) => {
+                if let InputState::Settings { filtered, .. } = &mut self.input_state {
+                    *filtered = patterns;
+                }
+                Command::none()
+            }
+
+            Message::FilterActionChanged(action) => {
+                self.config.content_filter.action = action;
+                match self.apply_filters() {
+                    Ok(command) => command,
+                    Err(e) => {
+                        self.error = Some(e);
+                        Command::none()
+                    }
+                }
+            }
+
+            Message::LeaveSettings => {
+                if let InputState::Settings { username, filtered } = &self.input_state {
+                    if username.trim().is_empty() {
+                        self.error = Some("The username cannot be empty".to_string());
+                        return Command::none();
+                    }
+                    let username = username.clone();
+                    match ContentFilter::parse(filtered, self.config.content_filter.action) {
+                        Ok(filter) => self.config.content_filter = filter,
+                        Err(e) => {
+                            self.error = Some(e);
+                            return Command::none();
+                        }
+                    }
+                    let filtered = match self.apply_filters() {
+                        Ok(command) => command,
+                        Err(e) => {
+                            self.error = Some(e);
+                            return Command::none();
+                        }
+                    };
+                    self.input_state = InputState::MainMenu { username };
+                    self.remember_username();
+                    return Command::batch(vec![filtered, self.announce_presence()]);
+                }
+                Command::none()
+            }
+
+            Message::DiagnosticsSelected => {
+                if let InputState::MainMenu { username } = &self.input_state {
+                    self.input_state = InputState::Diagnostics {
+                        username: username.clone(),
+                        status: self.client.node_status(),
+                        peer_addr: None,
+                    };
+                }
+                Command::none()
+            }
+
+            Message::RefreshDiagnostics => {
+                if let InputState::Diagnostics { status, .. } = &mut self.input_state {
+                    *status = self.client.node_status();
+                }
+                Command::none()
+            }
+
+            Message::AddPeerSelected => {
+                if let InputState::Diagnostics { peer_addr, .. } = &mut self.input_state {
+                    *peer_addr = Some(String::new());
+                    return text_input::focus(focus::peer_addr());
+                }
+                Command::none()
+            }
+
+            Message::PeerAddrChanged(text) => {
+                if let InputState::Diagnostics {
+                    peer_addr: Some(peer_addr),
+                    ..
+                } = &mut self.input_state
+                {
+                    *peer_addr = text;
+                }
+                Command::none()
+            }
+
+            Message::CancelAddPeer => {
+                if let InputState::Diagnostics { peer_addr, .. } = &mut self.input_state {
+                    *peer_addr = None;
+                }
+                Command::none()
+            }
+
+            Message::SubmitAddPeer => {
+                let InputState::Diagnostics {
+                    peer_addr: Some(peer_addr),
+                    ..
+                } = &self.input_state
+                else {
+                    return Command::none();
+                };
+                match ticket::parse_node_addr(peer_addr.trim()) {
+                    Ok(node_addr) => {
+                        let client = self.client.clone();
+                        Command::perform(
+                            async move { client.add_peer(node_addr).await },
+                            Message::PeerAdded,
+                        )
+                    }
+                    Err(e) => {
+                        self.error = Some(e);
+                        Command::none()
+                    }
+                }
+            }
+
+            Message::PeerAdded(result) => {
+                match result {
+                    Ok(topics) => {
+                        info!(topics, "Added a peer manually");
+                        self.error = None;
+                        if let InputState::Diagnostics {
+                            status, peer_addr, ..
+                        } = &mut self.input_state
+                        {
+                            *peer_addr = None;
+                            *status = self.client.node_status();
+                        }
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+                Command::none()
+            }
+
+            Message::DisplayNameChanged(display_name) => {
+                if let InputState::EditProfile { profile, .. } = &mut self.input_state {
+                    profile.display_name = display_name;
+                }
+                Command::none()
+            }
+
+            Message::StatusLineChanged(status_line) => {
+                if let InputState::EditProfile { profile, .. } = &mut self.input_state {
+                    profile.status_line = status_line;
+                }
+                Command::none()
+            }
+
+            Message::AvatarChanged(avatar) => {
+                if let InputState::EditProfile { profile, .. } = &mut self.input_state {
+                    profile.avatar = avatar;
+                }
+                Command::none()
+            }
+
+            Message::AvatarImagePathChanged(path) => {
+                if let InputState::EditProfile { image_path, .. } = &mut self.input_state {
+                    *image_path = path;
+                }
+                Command::none()
+            }
+
+            Message::RemoveAvatarImage => {
+                if let InputState::EditProfile {
+                    image_path,
+                    avatar_image,
+                    ..
+                } = &mut self.input_state
+                {
+                    image_path.clear();
+                    *avatar_image = None;
+                }
+                Command::none()
+            }
+
+            Message::SaveProfile => {
+                if let InputState::EditProfile {
+                    profile,
+                    image_path,
+                    avatar_image,
+                    ..
+                } = &self.input_state
+                {
+                    if let Err(e) = profile.validate() {
+                        self.error = Some(e);
+                        return Command::none();
+                    }
+                    if image_path.trim().is_empty() {
+                        return self.save_profile(*avatar_image);
+                    }
+                    let image = match read_avatar_image(Path::new(image_path.trim())) {
+                        Ok(image) => image,
+                        Err(e) => {
+                            self.error = Some(e);
+                            return Command::none();
+                        }
+                    };
+                    // Served to others from the blob store
+                    let client = self.client.clone();
+                    return Command::perform(
+                        async move { client.store_blob(image).await },
+                        Message::AvatarImageStored,
+                    );
+                }
+                Command::none()
+            }
+
+            Message::AvatarImageStored(result) => match result {
+                Ok(hash) => self.save_profile(Some(hash)),
+                Err(e) => {
+                    self.error = Some(e);
+                    Command::none()
+                }
+            },
+
+            Message::CreateTopicSelected => {
+                if let InputState::MainMenu { username } = &self.input_state {
+                    self.input_state = InputState::CreateTopic {
+                        username: username.clone(),
+                        topic_name: String::new(),
+                    };
+                    return text_input::focus(focus::topic_name());
+                }
+                Command::none()
+            }
+
+            Message::NotesSelected => {
+                let Some(username) = self.get_username() else {
+                    return Command::none();
+                };
+                let Some(path) = Notes::default_path() else {
+                    self.error = Some("No configuration directory to keep notes in".to_string());
+                    return Command::none();
+                };
+                match Notes::open(&path) {
+                    Ok((notes, written)) => {
+                        self.input_state = InputState::ChatRoom {
+                            username,
+                            message: String::new(),
+                        };
+                        self.room = Some(Room::new(NOTES_TOPIC_NAME));
+                        self.local = Some(LocalTopic::Notes(notes));
+                        for note in written {
+                            self.insert_message(note);
+                        }
+                        return Command::batch(vec![
+                            self.jump_to_latest(),
+                            text_input::focus(focus::composer()),
+                        ]);
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+                Command::none()
+            }
+
+            Message::JoinTopicSelected => {
+                if let InputState::MainMenu { username } = &self.input_state {
+                    self.input_state = InputState::JoinTopic {
+                        username: username.clone(),
+                        ticket: String::new(),
+                    };
+                    // The ticket may have been copied since the menu was shown
+                    return Command::batch([
+                        text_input::focus(focus::ticket()),
+                        clipboard::read(Message::ClipboardRead),
+                    ]);
+                }
+                Command::none()
+            }
+
+            Message::BackToMenu => {
+                self.save_last_seen();
+                if self.latency.count() > 0 {
+                    info!(latency = %self.latency, "Left topic");
+                    self.latency = LatencyHistogram::default();
+                }
+                if let Some(username) = self.get_username() {
+                    self.input_state = InputState::MainMenu { username };
+                    self.room = None;
+                    self.roster = Roster::default();
+                    self.archive = None;
+                    self.local = None;
+                    self.conversation.reset();
+                    // Computed anew from the messages shown on returning
+                    self.heatmaps.clear();
+                    self.message_scroll = 0.0;
+                    self.at_latest = true;
+                    self.unseen = 0;
+                    self.confirming_send = false;
+                    self.emoji_search = None;
+                    self.input_history.reset();
+                    self.show_tasks = false;
+                    self.traffic = None;
+                    self.event_form = None;
+                    self.pasted_image = None;
+                    self.delivery_details = None;
+                    self.confirming_link = None;
+                    self.event_exported = None;
+                    self.message_search = None;
+                    self.context_menu = None;
+                    return clipboard::read(Message::ClipboardRead);
+                }
+                Command::none()
+            }
+
+            Message::SubmitCreateTopic => {
+                if let InputState::CreateTopic { topic_name, .. } = &self.input_state.clone() {
+                    if !topic_name.trim().is_empty() {
+                        let topic_name = topic_name.clone();
+                        let client = self.client.clone();
+
+                        return Command::perform(
+                            async move { client.create_topic(topic_name).await },
+                            |result| match result {
+                                Ok((topic_name, ticket, hash)) => {
+                                    Message::TopicCreated(Ok((topic_name, ticket, hash)))
+                                }
+                                Err(e) => Message::TopicCreated(Err(e)),
+                            },
+                        );
+                    }
+                }
+                Command::none()
+            }
+
+            Message::SubmitJoinTopic => {
+                if let InputState::JoinTopic { ticket, .. } = &self.input_state.clone() {
+                    if !ticket.trim().is_empty() {
+                        let ticket = ticket.clone();
+                        let client = self.client.clone();
+
+                        return Command::perform(
+                            async move { client.join_topic(ticket).await },
+                            |result| match result {
+                                Ok((topic_name, hash)) => {
+                                    Message::TopicJoined(Ok((topic_name, hash)))
+                                }
+                                Err(e) => Message::TopicJoined(Err(e)),
+                            },
+                        );
+                    }
+                }
+                Command::none()
+            }
+
+            Message::CopyTicket => {
+                self.context_menu = None;
+                if let InputState::TopicCreated { ticket, .. } = &self.input_state {
+                    return Command::batch(vec![clipboard::write(ticket.clone())]);
+                }
+                Command::none()
+            }
+
+            Message::CopyNodeAddr => {
+                if let InputState::Diagnostics {
+                    status: Some(status),
+                    ..
+                } = &self.input_state
+                {
+                    return clipboard::write(ticket::node_addr_to_string(&status.node_addr()));
+                }
+                Command::none()
+            }
+
+            Message::CopyInvite => {
+                self.context_menu = None;
+                self.create_invite()
+            }
+
+            Message::InviteCreated(result) => {
+                match result {
+                    Ok(invite) => return clipboard::write(invite),
+                    Err(e) => self.error = Some(e),
+                }
+                Command::none()
+            }
+
+            Message::CopyToClipboard(contents) => {
+                self.context_menu = None;
+                clipboard::write(contents)
+            }
+
+            Message::ClipboardRead(contents) => {
+                // Only suggest joining when the clipboard holds a valid ticket
+                self.clipboard_ticket = contents.and_then(|contents| {
+                    let ticket = contents.trim();
+                    ticket
+                        .parse::<Ticket>()
+                        .ok()
+                        .map(|parsed| (parsed.topic_name, ticket.to_string()))
+                });
+                // Fill in the join screen, unless something was typed already
+                if let (InputState::JoinTopic { ticket, .. }, Some((_, found))) =
+                    (&mut self.input_state, &self.clipboard_ticket)
+                {
+                    if ticket.trim().is_empty() {
+                        *ticket = found.clone();
+                    }
+                }
+                Command::none()
+            }
+
+            Message::JoinFromClipboard => {
+                if let (InputState::MainMenu { username }, Some((_, ticket))) =
+                    (&self.input_state, self.clipboard_ticket.take())
+                {
+                    self.input_state = InputState::JoinTopic {
+                        username: username.clone(),
+                        ticket,
+                    };
+                    return self.update(Message::SubmitJoinTopic);
+                }
+                Command::none()
+            }
+
+            Message::OpenContextMenu(target) => {
+                // Right-clicking the element with the open menu closes it again
+                if self.context_menu.as_ref() == Some(&target) {
+                    self.context_menu = None;
+                } else {
+                    self.context_menu = Some(target);
+                }
+                Command::none()
+            }
+
+            Message::CloseContextMenu => {
+                self.context_menu = None;
+                Command::none()
+            }
+
+            Message::EnterChatRoom => {
+                if let InputState::TopicCreated {
+                    username,
+                    topic_name,
+                    ..
+                } = &self.input_state.clone()
+                {
+                    self.input_state = InputState::ChatRoom {
+                        username: username.clone(),
+                        message: String::new(),
+                    };
+                    if self.room.is_none() {
+                        self.room = Some(Room::new(topic_name.clone()));
+                    }
+                    self.context_menu = None;
+                    self.mark_read();
+                    return Command::batch(vec![
+                        self.announce_presence(),
+                        text_input::focus(focus::composer()),
+                    ]);
+                }
+                Command::none()
+            }
+
+            Message::StartEdit(id) => {
+                self.context_menu = None;
+                let content = self
+                    .conversation
+                    .get(&id)
+                    .map(|message| message.content.clone());
+                if let (Some(content), InputState::ChatRoom { message, .. }) =
+                    (content, &mut self.input_state)
+                {
+                    *message = content;
+                    self.editing = Some(id);
+                    return text_input::focus(focus::composer());
+                }
+                Command::none()
+            }
+
+            Message::CancelEdit => {
+                if self.editing.take().is_some() {
+                    if let InputState::ChatRoom { message, .. } = &mut self.input_state {
+                        message.clear();
+                    }
+                }
+                text_input::focus(focus::composer())
+            }
+
+            Message::SendHistory(recipient) => {
+                self.context_menu = None;
+                // Only what the members actually said, as it is shown now
+                let messages: Vec<_> = self
+                    .conversation
+                    .messages()
+                    .iter()
+                    .filter(|message| message.author != "System" && !message.is_deleted())
+                    .cloned()
+                    .collect();
+                let client = self.client.clone();
+                Command::perform(
+                    async move { client.send_history(recipient, messages).await },
+                    Message::HistorySent,
+                )
+            }
+
+            Message::HistorySent(result) => {
+                match result {
+                    Ok(count) => info!(count, "Sent the history to a member"),
+                    Err(e) => self.error = Some(e),
+                }
+                Command::none()
+            }
+
+            Message::DeleteMessage(id) => {
+                self.context_menu = None;
+                let client = self.client.clone();
+                Command::perform(
+                    async move { client.delete_message(id).await },
+                    Message::DeleteSent,
+                )
+            }
+
+            Message::ComposerSubmitted => {
+                // Ctrl+Enter always sends, and Shift+Enter never does
+                let sends = self.modifiers.control()
+                    || (!self.modifiers.shift() && self.config.send_key == SendKey::Enter);
+                if sends {
+                    return self.update(Message::SendMessage);
+                }
+                // The cursor position is not known, so the line is broken
+                // at the end
+                if let InputState::ChatRoom { message, .. } = &mut self.input_state {
+                    message.push('\n');
+                }
+                self.confirming_send = false;
+                text_input::move_cursor_to_end(focus::composer())
+            }
+
+            Message::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+                Command::none()
+            }
+
+            Message::ToggleEmojiPicker => {
+                if self.emoji_search.take().is_some() {
+                    return text_input::focus(focus::composer());
+                }
+                self.emoji_search = Some(String::new());
+                text_input::focus(focus::emoji_search())
+            }
+
+            Message::EmojiSearchChanged(query) => {
+                if let Some(search) = &mut self.emoji_search {
+                    *search = query;
+                }
+                Command::none()
+            }
+
+            Message::EmojiPicked(picked) => {
+                self.emoji_search = None;
+                // The position of the cursor is not known, so the emoji goes
+                // at the end, where the cursor is moved to
+                if let InputState::ChatRoom { message, .. } = &mut self.input_state {
+                    message.push_str(&picked);
+                }
+                emoji::remember(&mut self.config.recent_emoji, &picked);
+                self.save_config();
+                Command::batch(vec![
+                    text_input::focus(focus::composer()),
+                    text_input::move_cursor_to_end(focus::composer()),
+                ])
+            }
+
+            Message::CancelSend => {
+                self.confirming_send = false;
+                text_input::focus(focus::composer())
+            }
+
+            Message::SendMessage => {
+                if let InputState::ChatRoom { username, message } = &self.input_state.clone() {
+                    // Messages starting with a slash are commands, unless
+                    // they replace what was already sent
+                    let parsed = match &self.editing {
+                        Some(_) => None,
+                        None => commands::parse(message),
+                    };
+                    let message = &match parsed {
+                        None => message.clone(),
+                        Some(Ok(SlashCommand::Me(action))) => format!("*{} {}*", username, action),
+                        Some(Ok(SlashCommand::Say(text))) => text,
+                        Some(Ok(command)) => return self.run_command(command),
+                        Some(Err(e)) => {
+                            self.composer_error = Some(e);
+                            return Command::none();
+                        }
+                    };
+                    if let Err(e) = self.config.message_limit.check(message) {
+                        self.composer_error = Some(e);
+                        self.confirming_send = false;
+                        return Command::none();
+                    }
+                    // Large messages are only sent once confirmed, edits
+                    // replace what was already sent
+                    if self.editing.is_none()
+                        && !self.confirming_send
+                        && self.config.large_messages.confirms(message)
+                    {
+                        self.confirming_send = true;
+                        return Command::none();
+                    }
+                    self.confirming_send = false;
+
+                    if let Some(target_id) = self.editing.take() {
+                        if let InputState::ChatRoom { message: m, .. } = &mut self.input_state {
+                            *m = String::new();
+                        }
+                        if message.trim().is_empty() {
+                            return Command::none();
+                        }
+
+                        let new_content = message.clone();
+                        let client = self.client.clone();
+                        let edit = Command::perform(
+                            async move { client.edit_message(target_id, new_content).await },
+                            Message::EditSent,
+                        );
+                        return Command::batch(vec![edit, text_input::focus(focus::composer())]);
+                    }
+
+                    if !message.trim().is_empty()
+                        && self.room.is_some()
+                        && self.topic_hash().is_some()
+                    {
+                        let username = username.clone();
+                        let message_content = message.clone();
+                        let sequence = self.conversation.next_sequence();
+                        let client = self.client.clone();
+
+                        // Clear the message input
+                        if let InputState::ChatRoom { message: m, .. } = &mut self.input_state {
+                            *m = String::new();
+                        }
+                        if let Some(topic_hash) = self.topic_hash() {
+                            self.input_history.push(&topic_hash, &message_content);
+                        }
+
+                        // Local topics never leave this window
+                        match &mut self.local {
+                            Some(LocalTopic::Demo(_)) => {
+                                let lamport =
+                                    self.conversation.messages().last().map_or(0, |m| m.lamport)
+                                        + 1;
+                                let topic_hash = self.topic_hash().unwrap_or_default();
+                                self.insert_message(demo::message(
+                                    &topic_hash,
+                                    &username,
+                                    &message_content,
+                                    lamport,
+                                ));
+                                return Command::batch(vec![
+                                    self.jump_to_latest(),
+                                    text_input::focus(focus::composer()),
+                                ]);
+                            }
+                            Some(LocalTopic::Notes(notes)) => {
+                                match notes.add(&username, &message_content) {
+                                    Ok(note) => {
+                                        self.insert_message(note);
+                                    }
+                                    Err(e) => self.error = Some(e),
+                                }
+                                return Command::batch(vec![
+                                    self.jump_to_latest(),
+                                    text_input::focus(focus::composer()),
+                                ]);
+                            }
+                            None => {}
+                        }
+
+                        // The client stamps the message, it is shown once sent
+                        let send = Command::perform(
+                            async move {
+                                client
+                                    .send_message(username, message_content, sequence)
+                                    .await
+                            },
+                            Message::MessageSent,
+                        );
+
+                        // Keep typing without clicking the composer again
+                        return Command::batch(vec![send, text_input::focus(focus::composer())]);
+                    }
+                }
+                Command::none()
+            }
+
+            Message::NetworkInitialized(result) => {
+                match result {
+                    Ok(_) => {
+                        // Nothing to tell the others before a profile is set
+                        if self.config.profile != UserProfile::default()
+                            || self.config.avatar_image.is_some()
+                        {
+                            return Command::batch(vec![
+                                self.publish_profile(),
+                                self.start_launch(),
+                            ]);
+                        }
+                        return self.start_launch();
+                    }
+                    Err(error) => {
+                        self.error = Some(error);
+                    }
+                }
+                Command::none()
+            }
+
+            Message::TopicCreated(result) => {
+                match result {
+                    Ok((topic, ticket, _)) => {
+                        self.room = Some(Room::new(topic.clone()));
+                        self.open_archive();
+
+                        if let Some(username) = self.get_username() {
+                            self.input_state = InputState::TopicCreated {
+                                username,
+                                topic_name: topic,
+                                ticket,
+                            };
+                        }
+                    }
+                    Err(error) => {
+                        self.error = Some(error);
+                    }
+                }
+                Command::none()
+            }
+
+            Message::TopicJoined(result) => {
+                match result {
+                    Ok((topic, topic_hash)) => {
+                        self.room = Some(Room::new(topic.clone()));
+                        self.show_muted = false;
+                        self.open_archive();
+
+                        if let Some(username) = self.get_username() {
+                            self.input_state = InputState::ChatRoom {
+                                username,
+                                message: String::new(),
+                            };
+                            self.mark_read();
+                            return Command::batch(vec![
+                                self.announce_presence(),
+                                text_input::focus(focus::composer()),
+                                self.restore_allowlist(&topic_hash),
+                            ]);
+                        }
+                    }
+                    Err(error) => {
+                        self.error = Some(error);
+                    }
+                }
+                Command::none()
+            }
+
+            Message::MessageReceived(message) => {
+                // Messages resent after a partition may be older than their
+                // arrival, and sender clocks may run ahead
+                if let Some(node_id) = message.verified_sender {
+                    let at = message.timestamp.min(Utc::now());
+                    self.record_seen(&message.topic_hash, node_id, &message.author, at);
+                }
+                self.record_latency(&message);
+                let notify = self.notify(&message);
+                if !matches!(self.input_state, InputState::ChatRoom { .. })
+                    && !self.config.mutes(&message)
+                {
+                    self.unread.count(&message, self.client.own_node_id());
+                }
+                if !self.insert_message(message) {
+                    return notify;
+                }
+                Command::batch(vec![notify, self.follow_new_message()])
+            }
+
+            Message::EditReceived(edit, sender) => {
+                self.apply_edit(&edit, sender);
+                Command::none()
+            }
+
+            Message::DeleteReceived(delete, sender) => {
+                self.apply_delete(delete, sender);
+                Command::none()
+            }
+
+            Message::DeleteSent(result) => {
+                match result {
+                    Ok(delete) => {
+                        // Gossip does not echo our own deletes, apply it here
+                        self.apply_delete(delete, self.client.own_node_id());
+                    }
+                    Err(error) => {
+                        warn!("Failed to delete message: {}", error);
+                        self.error = Some(error);
+                    }
+                }
+                Command::none()
+            }
+
+            Message::UpdateTask(message_id, change) => {
+                self.context_menu = None;
+                let client = self.client.clone();
+                Command::perform(
+                    async move { client.update_task(message_id, change).await },
+                    Message::TaskSent,
+                )
+            }
+
+            Message::TaskReceived(update, sender) => {
+                self.apply_task(&update, sender);
+                Command::none()
+            }
+
+            Message::TaskSent(result) => {
+                match result {
+                    Ok(update) => {
+                        // Gossip does not echo our own updates, apply it here
+                        if let Some(own_node_id) = self.client.own_node_id() {
+                            self.apply_task(&update, own_node_id);
+                        }
+                    }
+                    Err(error) => self.error = Some(error),
+                }
+                Command::none()
+            }
+
+            Message::ToggleTasks => {
+                self.show_tasks = !self.show_tasks;
+                Command::none()
+            }
+
+            Message::ToggleTraffic => {
+                self.traffic = match self.traffic {
+                    Some(_) => None,
+                    None => self.current_traffic(),
+                };
+                Command::none()
+            }
+
+            Message::Shortcut(shortcut) => match shortcut {
+                Shortcut::Back => self.back(),
+                Shortcut::SwitchTopic => {
+                    if self.switcher.take().is_some() || self.get_username().is_none() {
+                        return Command::none();
+                    }
+                    self.switcher = Some(String::new());
+                    text_input::focus(focus::topic_switcher())
+                }
+                Shortcut::Search => {
+                    if !matches!(self.input_state, InputState::ChatRoom { .. }) {
+                        return Command::none();
+                    }
+                    self.message_search.get_or_insert_with(String::new);
+                    text_input::focus(focus::message_search())
+                }
+                Shortcut::Copy => match self.context_menu.take() {
+                    Some(ContextTarget::ChatMessage(id)) => self
+                        .conversation
+                        .get(&id)
+                        .map_or_else(Command::none, |message| {
+                            clipboard::write(message.content.clone())
+                        }),
+                    // Other menus copy nothing
+                    target => {
+                        self.context_menu = target;
+                        Command::none()
+                    }
+                },
+            },
+
+            Message::SwitcherQueryChanged(query) => {
+                if let Some(switcher) = &mut self.switcher {
+                    *switcher = query;
+                }
+                Command::none()
+            }
+
+            Message::SwitchToFirstMatch => match self.switcher_topics().first() {
+                Some((topic_name, _)) => self.update(Message::SwitchTopic(topic_name.clone())),
+                None => Command::none(),
+            },
+
+            Message::SwitchTopic(topic_name) => {
+                self.switcher = None;
+                let Some(topic_hash) = self
+                    .client
+                    .session()
+                    .subscribed_topics
+                    .get(&topic_name)
+                    .cloned()
+                else {
+                    return Command::none();
+                };
+                if self
+                    .room
+                    .as_ref()
+                    .is_some_and(|room| room.name() == topic_name)
+                    && matches!(self.input_state, InputState::ChatRoom { .. })
+                {
+                    return text_input::focus(focus::composer());
+                }
+                // Leave whatever is shown, as the room of another topic would
+                let back = self.update(Message::BackToMenu);
+                let client = self.client.clone();
+                let enter = Command::perform(
+                    async move {
+                        client.switch_topic(topic_hash.clone()).await?;
+                        Ok((topic_name, topic_hash))
+                    },
+                    Message::TopicJoined,
+                );
+                Command::batch(vec![back, enter])
+            }
+
+            Message::ShowMutedMessages => {
+                self.show_muted = true;
+                Command::none()
+            }
+
+            Message::ToggleMute(node_id, author) => {
+                self.context_menu = None;
+                let muted = node_id
+                    .is_some_and(|node_id| self.config.muted_members.contains(&node_id))
+                    || self.config.mutes_author(&author);
+                if muted {
+                    if let Some(node_id) = node_id {
+                        self.config.muted_members.remove(&node_id);
+                    }
+                    self.config.unmute_author(&author);
+                } else {
+                    match node_id {
+                        Some(node_id) => self.config.muted_members.insert(node_id),
+                        None => self.config.mute_author(&author),
+                    };
+                }
+                self.save_config();
+                Command::none()
+            }
+
+            Message::ShowDeliveryDetails(message_id) => {
+                self.context_menu = None;
+                self.delivery_details = Some(message_id);
+                Command::none()
+            }
+
+            Message::CloseDeliveryDetails => {
+                self.delivery_details = None;
+                Command::none()
+            }
+
+            Message::OpenLink(Link::Web(address)) => {
+                let trusted = self
+                    .topic_hash()
+                    .is_some_and(|topic_hash| self.config.trusted_topics.contains(&topic_hash));
+                if self.config.link_confirmation.confirms(trusted) {
+                    self.confirming_link = Some(address);
+                } else {
+                    open_link(&address);
+                }
+                Command::none()
+            }
+
+            Message::ConfirmOpenLink => {
+                if let Some(address) = self.confirming_link.take() {
+                    open_link(&address);
+                }
+                Command::none()
+            }
+
+            Message::CancelOpenLink => {
+                self.confirming_link = None;
+                Command::none()
+            }
+
+            Message::OpenLink(Link::Topic(link)) => {
+                let linked = self
+                    .client
+                    .session()
+                    .subscribed_topics
+                    .keys()
+                    .find(|name| markdown::topic_slug(name) == link.to_lowercase())
+                    .cloned();
+                match linked {
+                    Some(topic_name) => self.update(Message::SwitchTopic(topic_name)),
+                    // Nothing knows the tickets of other topics, so joining
+                    // asks for one, suggesting a ticket on the clipboard
+                    None => {
+                        let back = self.update(Message::BackToMenu);
+                        Command::batch([back, self.update(Message::JoinTopicSelected)])
+                    }
+                }
+            }
+
+            Message::MessageSearchChanged(query) => {
+                if let Some(search) = &mut self.message_search {
+                    *search = query;
+                }
+                Command::none()
+            }
+
+            Message::CloseMessageSearch => {
+                self.message_search = None;
+                text_input::focus(focus::composer())
+            }
+
+            Message::ToggleEventForm => {
+                if self.event_form.take().is_some() {
+                    return text_input::focus(focus::composer());
+                }
+                self.event_form = Some(EventForm::default());
+                text_input::focus(focus::event_title())
+            }
+
+            Message::EventFieldChanged(field, value) => {
+                if let Some(form) = &mut self.event_form {
+                    match field {
+                        EventField::Title => form.title = value,
+                        EventField::Starts => form.starts = value,
+                        EventField::Ends => form.ends = value,
+                        EventField::Location => form.location = value,
+                    }
+                }
+                Command::none()
+            }
+
+            Message::ShareEvent => {
+                let (Some(form), Some(username)) = (&self.event_form, self.get_username()) else {
+                    return Command::none();
+                };
+                // Times are typed in the local time zone
+                let times = [&form.starts, &form.ends].map(|typed| {
+                    calendar::parse_time(typed).and_then(|time| {
+                        time.and_local_timezone(Local)
+                            .earliest()
+                            .map(|time| time.with_timezone(&Utc))
+                            .ok_or_else(|| format!("{} does not exist here", typed.trim()))
+                    })
+                });
+                let (starts_at, ends_at) = match times {
+                    [Ok(starts_at), Ok(ends_at)] => (starts_at, ends_at),
+                    [Err(e), _] | [_, Err(e)] => {
+                        self.error = Some(e);
+                        return Command::none();
+                    }
+                };
+                if let Err(e) = calendar::validate(&form.title, starts_at, ends_at) {
+                    self.error = Some(e);
+                    return Command::none();
+                }
+                let (title, location) = (form.title.clone(), form.location.clone());
+                let sequence = self.conversation.next_sequence();
+                self.event_form = None;
+                let client = self.client.clone();
+                Command::perform(
+                    async move {
+                        client
+                            .share_event(username, sequence, title, starts_at, ends_at, location)
+                            .await
+                    },
+                    Message::EventShared,
+                )
+            }
+
+            Message::EventShared(result) => match result {
+                Ok((message, event)) => {
+                    // Gossip does not echo our own events, add them here
+                    if let Some(own_node_id) = self.client.own_node_id() {
+                        self.add_event(event, own_node_id);
+                    }
+                    self.update(Message::MessageSent(Ok(message)))
+                }
+                Err(error) => {
+                    self.error = Some(error);
+                    Command::none()
+                }
+            },
+
+            Message::EventReceived(event, sender) => {
+                self.add_event(event, sender);
+                Command::none()
+            }
+
+            Message::RespondToEvent(message_id, response) => {
+                let client = self.client.clone();
+                Command::perform(
+                    async move { client.respond_to_event(message_id, response).await },
+                    Message::RsvpSent,
+                )
+            }
+
+            Message::RsvpReceived(rsvp, sender) => {
+                self.respond(&rsvp, sender);
+                Command::none()
+            }
+
+            Message::RsvpSent(result) => {
+                match result {
+                    Ok(rsvp) => {
+                        // Gossip does not echo our own answers, apply it here
+                        if let Some(own_node_id) = self.client.own_node_id() {
+                            self.respond(&rsvp, own_node_id);
+                        }
+                    }
+                    Err(error) => self.error = Some(error),
+                }
+                Command::none()
+            }
+
+            Message::ExportEvent(message_id) => {
+                let Some(event) = self
+                    .conversation
+                    .get(&message_id)
+                    .and_then(|message| self.calendar.event_of(message))
+                else {
+                    return Command::none();
+                };
+                let Some(dir) = dirs::download_dir().or_else(dirs::home_dir) else {
+                    self.error = Some("No folder to save the event to".to_string());
+                    return Command::none();
+                };
+                let path = dir.join(format!("{}.ics", automation::safe_file_name(&event.title)));
+                match std::fs::write(&path, calendar::to_ics(event, Utc::now())) {
+                    Ok(()) => self.event_exported = Some((message_id, path)),
+                    Err(e) => {
+                        self.error = Some(format!("Failed to write {}: {}", path.display(), e))
+                    }
+                }
+                Command::none()
+            }
+
+            Message::ActivityChanged(activity) => {
+                if let Some(room) = &mut self.room {
+                    room.set_activity(activity);
+                }
+                Command::none()
+            }
+
+            Message::ConnectivityChanged(connectivity) => {
+                self.connectivity = connectivity;
+                Command::none()
+            }
+
+            Message::ReconnectChanged(state) => {
+                if let Some(room) = &mut self.room {
+                    room.set_reconnect(state);
+                }
+                Command::none()
+            }
+
+            Message::ClientError(error) => {
+                self.error = Some(error);
+                Command::none()
+            }
+
+            Message::DeliveryChanged(message_id, state) => {
+                if let Some(room) = &mut self.room {
+                    room.set_delivery(message_id, state);
+                }
+                Command::none()
+            }
+
+            Message::ProfileReceived(profile) => {
+                self.profiles.update(&profile);
+                Command::none()
+            }
+
+            Message::AvatarReceived(avatar) => {
+                self.show_avatar(avatar.node_id, avatar.image);
+                Command::none()
+            }
+
+            Message::PresenceReceived(presence) => {
+                self.roster.update(&presence, Instant::now());
+                self.record_seen(
+                    &presence.topic_hash,
+                    presence.node_id,
+                    &presence.author,
+                    Utc::now(),
+                );
+                self.welcome(&presence)
+            }
+
+            Message::SaveMembersAsContacts => {
+                let Some(topic_name) = self.room.as_ref().map(|room| room.name().to_string())
+                else {
+                    return Command::none();
+                };
+                let own_node_id = self.client.own_node_id();
+                let members = self.roster.members(Instant::now());
+                let added = self.contacts.import(
+                    members
+                        .iter()
+                        .filter(|(member, _)| Some(member.node_id) != own_node_id)
+                        .map(|(member, _)| {
+                            let name = self
+                                .profiles
+                                .get(&member.node_id)
+                                .map_or(member.author.as_str(), |profile| {
+                                    profile.name_or(&member.author)
+                                });
+                            (member.node_id, name)
+                        }),
+                    &topic_name,
+                );
+                info!(added, topic = %topic_name, "Saved members as contacts");
+                if let Some(path) = Contacts::default_path() {
+                    if let Err(e) = self.contacts.save_to(&path) {
+                        self.error = Some(e);
+                    }
+                }
+                Command::none()
+            }
+
+            Message::ToggleArchive => {
+                let Some(topic_hash) = self.topic_hash() else {
+                    return Command::none();
+                };
+                if self.archive.take().is_none() {
+                    self.config.archived_topics.insert(topic_hash);
+                    self.open_archive();
+                } else {
+                    self.config.archived_topics.remove(&topic_hash);
+                }
+                self.save_config();
+                Command::none()
+            }
+
+            Message::TopicSettingsSelected => {
+                let Some(topic_hash) = self.topic_hash() else {
+                    return Command::none();
+                };
+                if let InputState::ChatRoom { username, message } = &self.input_state {
+                    if !self.heatmaps.contains_key(&topic_hash) {
+                        let heatmap = ActivityHeatmap::of(
+                            self.conversation
+                                .messages()
+                                .iter()
+                                .filter(|message| message.author != "System")
+                                .map(|message| message.timestamp),
+                            &Local,
+                        );
+                        self.heatmaps.insert(topic_hash.clone(), heatmap);
+                    }
+                    let rule = self.config.export_rules.get(&topic_hash);
+                    let topic_filter = self
+                        .config
+                        .topic_filters
+                        .get(&topic_hash)
+                        .cloned()
+                        .unwrap_or_default();
+                    self.input_state = InputState::TopicSettings {
+                        username: username.clone(),
+                        draft: message.clone(),
+                        weekday: rule.map_or(Weekday::Sun, |rule| rule.weekday),
+                        hour: rule
+                            .map_or(DEFAULT_EXPORT_HOUR, |rule| rule.hour)
+                            .to_string(),
+                        days: rule
+                            .map_or(DEFAULT_EXPORT_DAYS, |rule| rule.days)
+                            .to_string(),
+                        folder: rule
+                            .map(|rule| rule.folder.clone())
+                            .or_else(|| dirs::document_dir().map(|dir| dir.join("iroh-lab")))
+                            .map(|folder| folder.display().to_string())
+                            .unwrap_or_default(),
+                        prune: rule.is_some_and(|rule| rule.prune),
+                        members_exported: None,
+                        bridge_folder: self
+                            .config
+                            .folder_bridges
+                            .get(&topic_hash)
+                            .map(|folder| folder.display().to_string())
+                            .unwrap_or_default(),
+                        welcome: self
+                            .config
+                            .welcome_templates
+                            .get(&topic_hash)
+                            .cloned()
+                            .unwrap_or_default(),
+                        allowed_node: String::new(),
+                        filtered: topic_filter.patterns.join(", "),
+                        filter_action: topic_filter.action,
+                    };
+                    return text_input::focus(focus::export_hour());
+                }
+                Command::none()
+            }
+
+            Message::ExportWeekdayChanged(day) => {
+                if let InputState::TopicSettings { weekday, .. } = &mut self.input_state {
+                    *weekday = day;
+                }
+                Command::none()
+            }
+
+            Message::ExportHourChanged(value) => {
+                if let InputState::TopicSettings { hour, .. } = &mut self.input_state {
+                    *hour = value;
+                }
+                Command::none()
+            }
+
+            Message::ExportDaysChanged(value) => {
+                if let InputState::TopicSettings { days, .. } = &mut self.input_state {
+                    *days = value;
+                }
+                Command::none()
+            }
+
+            Message::ExportFolderChanged(value) => {
+                if let InputState::TopicSettings { folder, .. } = &mut self.input_state {
+                    *folder = value;
+                }
+                Command::none()
+            }
+
+            Message::ExportPruneToggled(value) => {
+                if let InputState::TopicSettings { prune, .. } = &mut self.input_state {
+                    *prune = value;
+                }
+                Command::none()
+            }
+
+            Message::SaveExportRule => {
+                let (Some(topic_hash), Some(topic_name)) = (
+                    self.topic_hash(),
+                    self.room.as_ref().map(|room| room.name().to_string()),
+                ) else {
+                    return Command::none();
+                };
+                let InputState::TopicSettings {
+                    weekday,
+                    hour,
+                    days,
+                    folder,
+                    prune,
+                    ..
+                } = &self.input_state
+                else {
+                    return Command::none();
+                };
+                let rule = match parse_export_rule(topic_name, *weekday, hour, days, folder, *prune)
+                {
+                    Ok(rule) => rule,
+                    Err(e) => {
+                        self.error = Some(e);
+                        return Command::none();
+                    }
+                };
+                self.config.export_rules.insert(topic_hash.clone(), rule);
+                // Exports read the archive, so it has to be kept
+                if self.config.archived_topics.insert(topic_hash) {
+                    self.open_archive();
+                }
+                self.save_config();
+                Command::none()
+            }
+
+            Message::RemoveExportRule => {
+                if let Some(topic_hash) = self.topic_hash() {
+                    self.config.export_rules.remove(&topic_hash);
+                    self.save_config();
+                }
+                Command::none()
+            }
+
+            Message::RunExportNow => {
+                let Some(topic_hash) = self.topic_hash() else {
+                    return Command::none();
+                };
+                let Some(rule) = self.config.export_rules.get(&topic_hash).cloned() else {
+                    self.error = Some("Save the export rule first".to_string());
+                    return Command::none();
+                };
+                if let Some(error) = self.run_export(&topic_hash, &rule) {
+                    self.error = Some(error);
+                }
+                Command::none()
+            }
+
+            Message::ExportMembers(format) => {
+                let (Some(topic_hash), Some(topic_name)) = (
+                    self.topic_hash(),
+                    self.room.as_ref().map(|room| room.name().to_string()),
+                ) else {
+                    return Command::none();
+                };
+                let InputState::TopicSettings {
+                    folder,
+                    members_exported,
+                    ..
+                } = &mut self.input_state
+                else {
+                    return Command::none();
+                };
+                if folder.trim().is_empty() {
+                    self.error = Some("Choose a folder to export to".to_string());
+                    return Command::none();
+                }
+                let path = PathBuf::from(folder.trim()).join(format!(
+                    "{}-members-{}.{}",
+                    automation::safe_file_name(&topic_name),
+                    Local::now().format("%Y-%m-%d"),
+                    format.extension()
+                ));
+                let written = self
+                    .last_seen
+                    .export(&topic_hash, format)
+                    .and_then(|contents| {
+                        std::fs::create_dir_all(folder.trim())
+                            .and_then(|_| std::fs::write(&path, contents))
+                            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+                    });
+                match written {
+                    Ok(()) => *members_exported = Some(path),
+                    Err(e) => self.error = Some(e),
+                }
+                Command::none()
+            }
+
+            Message::ExportCalendar => {
+                let (Some(topic_hash), Some(topic_name)) = (
+                    self.topic_hash(),
+                    self.room.as_ref().map(|room| room.name().to_string()),
+                ) else {
+                    return Command::none();
+                };
+                let InputState::TopicSettings { folder, .. } = &self.input_state else {
+                    return Command::none();
+                };
+                if folder.trim().is_empty() {
+                    self.error = Some("Choose a folder to export to".to_string());
+                    return Command::none();
+                }
+                let path = PathBuf::from(folder.trim())
+                    .join(format!("{}.ics", automation::safe_file_name(&topic_name)));
+                self.config.calendar_feeds.insert(topic_hash, path);
+                self.save_config();
+                self.write_calendar_feed();
+                Command::none()
+            }
+
+            Message::StopCalendarFeed => {
+                if let Some(topic_hash) = self.topic_hash() {
+                    self.config.calendar_feeds.remove(&topic_hash);
+                    self.save_config();
+                }
+                Command::none()
+            }
+
+            Message::WelcomeTemplateChanged(template) => {
+                if let InputState::TopicSettings { welcome, .. } = &mut self.input_state {
+                    *welcome = template;
+                }
+                Command::none()
+            }
+
+            Message::SaveWelcome => {
+                let Some(topic_hash) = self.topic_hash() else {
+                    return Command::none();
+                };
+                let InputState::TopicSettings { welcome, .. } = &self.input_state else {
+                    return Command::none();
+                };
+                if welcome.trim().is_empty() {
+                    self.config.welcome_templates.remove(&topic_hash);
+                    self.welcomed.forget(&topic_hash);
+                } else {
+                    self.config
+                        .welcome_templates
+                        .insert(topic_hash.clone(), welcome.trim().to_string());
+                    // Only members showing up from now on are new
+                    let known: Vec<NodeId> = self
+                        .roster
+                        .members(Instant::now())
+                        .into_iter()
+                        .map(|(member, _)| member.node_id)
+                        .chain(
+                            self.last_seen
+                                .participants(&topic_hash)
+                                .into_iter()
+                                .filter_map(|(node_id, _)| node_id.parse().ok()),
+                        )
+                        .collect();
+                    for node_id in known {
+                        self.welcomed.mark(&topic_hash, node_id);
+                    }
+                }
+                self.save_config();
+                self.save_welcomed();
+                Command::none()
+            }
+
+            Message::ToggleTrustedLinks => {
+                let Some(topic_hash) = self.topic_hash() else {
+                    return Command::none();
+                };
+                if !self.config.trusted_topics.remove(&topic_hash) {
+                    self.config.trusted_topics.insert(topic_hash);
+                }
+                self.save_config();
+                Command::none()
+            }
+
+            Message::AllowedNodeChanged(node) => {
+                if let InputState::TopicSettings { allowed_node, .. } = &mut self.input_state {
+                    *allowed_node = node;
+                }
+                Command::none()
+            }
+
+            Message::AllowNode => {
+                let InputState::TopicSettings { allowed_node, .. } = &self.input_state else {
+                    return Command::none();
+                };
+                // Members present are allowed by name, anyone else by node id
+                let node_id = match self.member_named(allowed_node) {
+                    Ok(node_id) => node_id,
+                    Err(e) => match allowed_node.trim().parse::<NodeId>() {
+                        Ok(node_id) => node_id,
+                        Err(_) => {
+                            self.error = Some(e);
+                            return Command::none();
+                        }
+                    },
+                };
+                if let InputState::TopicSettings { allowed_node, .. } = &mut self.input_state {
+                    allowed_node.clear();
+                }
+                let Some(topic_hash) = self.topic_hash() else {
+                    return Command::none();
+                };
+                let members = self.config.allow_node(&topic_hash, node_id);
+                self.change_allowlist(topic_hash, members)
+            }
+
+            Message::DisallowNode(node_id) => {
+                let Some(topic_hash) = self.topic_hash() else {
+                    return Command::none();
+                };
+                let members = self.config.disallow_node(&topic_hash, &node_id);
+                self.change_allowlist(topic_hash, members)
+            }
+
+            Message::ToggleAllowlist => {
+                let Some(topic_hash) = self.topic_hash() else {
+                    return Command::none();
+                };
+                if self.config.make_public(&topic_hash) {
+                    self.save_config();
+                    return self.publish_allowlist(topic_hash, None);
+                }
+                // Whoever is here already keeps taking part
+                let mut present = self.roster.node_ids();
+                if let Some(own_node_id) = self.client.own_node_id() {
+                    present.remove(&own_node_id);
+                }
+                self.config.make_private(&topic_hash, present.clone());
+                self.change_allowlist(topic_hash, Some(present))
+            }
+
+            Message::AllowlistPublished(result) => {
+                match result {
+                    Ok(allowlist) => {
+                        info!(topic_hash = %allowlist.topic_hash, "Published the allowlist");
+                        self.config
+                            .allowlist_lamports
+                            .insert(allowlist.topic_hash, allowlist.lamport);
+                        self.save_config();
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+                Command::none()
+            }
+
+            Message::TopicFilterChanged(patterns) => {
+                if let InputState::TopicSettings { filtered, .. } = &mut self.input_state {
+                    *filtered = patterns;
+                }
+                Command::none()
+            }
+
+            Message::TopicFilterActionChanged(action) => {
+                if let InputState::TopicSettings { filter_action, .. } = &mut self.input_state {
+                    *filter_action = action;
+                }
+                Command::none()
+            }
+
+            Message::SaveTopicFilter => {
+                let Some(topic_hash) = self.topic_hash() else {
+                    return Command::none();
+                };
+                let InputState::TopicSettings {
+                    filtered,
+                    filter_action,
+                    ..
+                } = &self.input_state
+                else {
+                    return Command::none();
+                };
+                let filter = match ContentFilter::parse(filtered, *filter_action) {
+                    Ok(filter) => filter,
+                    Err(e) => {
+                        self.error = Some(e);
+                        return Command::none();
+                    }
+                };
+                info!(topic_hash = %topic_hash, patterns = filter.patterns.len(), "Changed the filter of the topic");
+                if filter.is_empty() {
+                    self.config.topic_filters.remove(&topic_hash);
+                } else {
+                    self.config.topic_filters.insert(topic_hash, filter);
+                }
+                match self.apply_filters() {
+                    Ok(command) => {
+                        self.error = None;
+                        command
+                    }
+                    Err(e) => {
+                        self.error = Some(e);
+                        Command::none()
+                    }
+                }
+            }
+
+            Message::ToggleHold => {
+                let Some(topic_hash) = self.topic_hash() else {
+                    return Command::none();
+                };
+                let event = if self.config.held_topics.remove(&topic_hash) {
+                    AuditEvent::HoldReleased
+                } else {
+                    self.config.held_topics.insert(topic_hash.clone());
+                    AuditEvent::HoldPlaced
+                };
+                info!(topic_hash = %topic_hash, ?event, "Changed legal hold");
+                self.save_config();
+                self.audit(&topic_hash, event);
+                Command::none()
+            }
+
+            Message::LeaveTopicSettings => {
+                if let InputState::TopicSettings {
+                    username, draft, ..
+                } = &self.input_state
+                {
+                    self.input_state = InputState::ChatRoom {
+                        username: username.clone(),
+                        message: draft.clone(),
+                    };
+                    self.mark_read();
+                    return Command::batch(vec![
+                        self.jump_to_latest(),
+                        text_input::focus(focus::composer()),
+                    ]);
+                }
+                Command::none()
+            }
+
+            Message::RunScheduledExports => {
+                let now = Local::now();
+                let due: Vec<_> = self
+                    .config
+                    .export_rules
+                    .iter()
+                    .filter(|(topic_hash, rule)| {
+                        rule.is_due(self.export_runs.last_run(topic_hash), &now)
+                    })
+                    .map(|(topic_hash, rule)| (topic_hash.clone(), rule.clone()))
+                    .collect();
+                for (topic_hash, rule) in due {
+                    info!(topic_hash = %topic_hash, "Running scheduled export");
+                    self.run_export(&topic_hash, &rule);
+                }
+                Command::none()
+            }
+
+            Message::BridgeFolderChanged(folder) => {
+                if let InputState::TopicSettings { bridge_folder, .. } = &mut self.input_state {
+                    *bridge_folder = folder;
+                }
+                Command::none()
+            }
+
+            Message::ToggleBridge => {
+                let Some(topic_hash) = self.topic_hash() else {
+                    return Command::none();
+                };
+                if self.config.folder_bridges.remove(&topic_hash).is_none() {
+                    let InputState::TopicSettings { bridge_folder, .. } = &self.input_state else {
+                        return Command::none();
+                    };
+                    if bridge_folder.trim().is_empty() {
+                        self.error = Some("Choose a folder to share".to_string());
+                        return Command::none();
+                    }
+                    let folder = PathBuf::from(bridge_folder.trim());
+                    if let Err(e) = std::fs::create_dir_all(&folder) {
+                        self.error = Some(format!("Failed to create {}: {}", folder.display(), e));
+                        return Command::none();
+                    }
+                    self.config.folder_bridges.insert(topic_hash, folder);
+                }
+                self.save_config();
+                Command::none()
+            }
+
+            Message::ScanBridges => {
+                // Only topics we are subscribed to can be posted to
+                let bridged: Vec<_> = self
+                    .config
+                    .folder_bridges
+                    .iter()
+                    .filter(|(topic_hash, _)| {
+                        self.client
+                            .session()
+                            .subscribed_topics
+                            .values()
+                            .any(|hash| hash == *topic_hash)
+                    })
+                    .map(|(topic_hash, folder)| (topic_hash.clone(), folder.clone()))
+                    .collect();
+                let mut commands = Vec::new();
+                for (topic_hash, folder) in bridged {
+                    let dropped = match self.bridges.scan(&topic_hash, &folder) {
+                        Ok(dropped) => dropped,
+                        Err(e) => {
+                            self.error = Some(e);
+                            continue;
+                        }
+                    };
+                    for file in dropped {
+                        // Recorded now, so the file is not posted again
+                        // while it is being posted
+                        self.bridges.record(&topic_hash, &file.name, &file.content);
+                        let client = self.client.clone();
+                        let topic_hash = topic_hash.clone();
+                        commands.push(Command::perform(
+                            async move {
+                                let posted = client
+                                    .post_attachment(
+                                        topic_hash.clone(),
+                                        file.name.clone(),
+                                        file.content,
+                                    )
+                                    .await;
+                                (topic_hash, file.name, posted)
+                            },
+                            |(topic_hash, name, posted)| {
+                                Message::AttachmentPosted(topic_hash, name, posted)
+                            },
+                        ));
+                    }
+                }
+                if !commands.is_empty() {
+                    self.save_bridges();
+                }
+                Command::batch(commands)
+            }
+
+            Message::AttachmentPosted(topic_hash, name, result) => {
+                match result {
+                    Ok(attachment) => {
+                        info!(name = %attachment.name, "Shared file posted");
+                    }
+                    Err(error) => {
+                        // Posted again on the next scan
+                        self.bridges.forget(&topic_hash, &name);
+                        self.save_bridges();
+                        self.error = Some(error);
+                    }
+                }
+                Command::none()
+            }
+
+            Message::AttachmentReceived(attachment) => {
+                if !self
+                    .config
+                    .folder_bridges
+                    .contains_key(&attachment.topic_hash)
+                    || Some(attachment.node_id) == self.client.own_node_id()
+                    || bridge::safe_name(&attachment.name).is_none()
+                    || attachment.size > bridge::MAX_FILE_SIZE as u64
+                {
+                    return Command::none();
+                }
+                let client = self.client.clone();
+                Command::perform(
+                    async move {
+                        let content = client.fetch_attachment(attachment.clone()).await;
+                        (attachment, content)
+                    },
+                    |(attachment, content)| Message::AttachmentFetched(attachment, content),
+                )
+            }
+
+            Message::AttachmentFetched(attachment, result) => {
+                let Some(folder) = self.config.folder_bridges.get(&attachment.topic_hash) else {
+                    return Command::none();
+                };
+                let saved = result.and_then(|content| {
+                    self.bridges
+                        .save(&attachment.topic_hash, folder, &attachment.name, &content)
+                });
+                match saved {
+                    Ok(path) => {
+                        info!(path = %path.display(), "Shared file saved");
+                        self.save_bridges();
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+                Command::none()
+            }
+
+            Message::SendPastedImage => {
+                let (Some(topic_hash), Some((pasted, _))) =
+                    (self.topic_hash(), self.pasted_image.take())
+                else {
+                    return Command::none();
+                };
+                let client = self.client.clone();
+                Command::perform(
+                    async move {
+                        client
+                            .post_attachment(topic_hash, pasted.name, pasted.png)
+                            .await
+                    },
+                    Message::PastedImageSent,
+                )
+            }
+
+            Message::DiscardPastedImage => {
+                self.pasted_image = None;
+                text_input::focus(focus::composer())
+            }
+
+            Message::PastedImageSent(result) => {
+                match result {
+                    Ok(attachment) => {
+                        info!(name = %attachment.name, "Pasted image sent");
+                    }
+                    Err(e) => self.composer_error = Some(e),
+                }
+                Command::none()
+            }
+
+            Message::Resync => {
+                let client = self.client.clone();
+                Command::perform(async move { client.resync().await }, Message::Resynced)
+            }
+
+            Message::Resynced(result) => {
+                match result {
+                    Ok(()) => {
+                        if let Some(room) = &mut self.room {
+                            room.start_resync();
+                        }
+                    }
+                    Err(error) => self.error = Some(error),
+                }
+                Command::none()
+            }
+
+            Message::ClientUpdated(result) => {
+                if let Err(error) = result {
+                    self.error = Some(error);
+                }
+                Command::none()
+            }
+
+            Message::EditSent(result) => {
+                match result {
+                    Ok(edit) => {
+                        // Gossip does not echo our own edits, apply it here
+                        self.apply_edit(&edit, self.client.own_node_id());
+                    }
+                    Err(error) => {
+                        warn!("Failed to edit message: {}", error);
+                        self.error = Some(error);
+                    }
+                }
+                Command::none()
+            }
+
+            Message::MessageSent(result) => {
+                match result {
+                    Ok(message) => {
+                        // Gossip does not echo our own messages, add it here.
+                        // Messages sent while nobody is connected are queued
+                        let state = self
+                            .client
+                            .delivery_report(&message.topic_hash, &message.id)
+                            .map_or(DeliveryState::Pending, |report| report.state);
+                        if let Some(room) = &mut self.room {
+                            room.set_delivery(message.id.clone(), state);
+                        }
+                        self.insert_message(message);
+                        return self.jump_to_latest();
+                    }
+                    Err(error) => {
+                        println!("Error sending message: {}", error);
+                        self.error = Some(error);
+                    }
+                }
+                Command::none()
+            }
+
+            Message::WindowResized(width, height) => {
+                self.layout = LayoutMode::for_width(width);
+                self.window_height = height as f32;
+                Command::none()
+            }
+
+            Message::MessagesScrolled(viewport) => {
+                self.message_scroll = viewport.absolute_offset().y;
+                self.at_latest = viewport.absolute_offset_reversed().y <= LATEST_MARGIN;
+                if self.at_latest {
+                    self.unseen = 0;
+                }
+                Command::none()
+            }
+
+            Message::JumpToLatest => self.jump_to_latest(),
+
+            Message::WindowFocused => {
+                self.presence = PresenceStatus::Online;
+                // Follow the desktop switching between light and dark
+                if self.config.theme == ThemePreference::System {
+                    self.system_dark = system_is_dark();
+                }
+                let announced = self.announce_presence();
+
+                // Look for a ticket copied while the app was in the background
+                if let InputState::MainMenu { .. } = self.input_state {
+                    return Command::batch(vec![
+                        announced,
+                        clipboard::read(Message::ClipboardRead),
+                    ]);
+                }
+                announced
+            }
+
+            Message::WindowUnfocused => {
+                self.presence = PresenceStatus::Away;
+                self.announce_presence()
+            }
+
+            Message::CloseRequested => {
+                // Only saved now and then while running
+                self.save_last_seen();
+                let client = self.client.clone();
+                Command::perform(async move { client.shutdown().await }, |_| {
+                    Message::ShutDown
+                })
+            }
+
+            Message::ShutDown => window::close(),
+
+            Message::TourNext(step) => {
+                if let Some(tour) = self.tour.as_mut() {
+                    tour.dismiss(step);
+                    if tour.is_finished() {
+                        self.complete_tour();
+                    }
+                }
+                Command::none()
+            }
+
+            Message::TourSkip => {
+                self.complete_tour();
+                Command::none()
+            }
+
+            Message::FocusPrevious => widget::focus_previous(),
+
+            Message::Tick => {
+                if self.traffic.is_some() {
+                    self.traffic = self.current_traffic();
+                }
+                Command::none()
+            }
+
+            Message::Incoming(event, dropped) => {
+                self.events_dropped = dropped;
+                // Process messages only for the room we are in, and
+                // count the messages of the others until viewed.
+                // Events without a topic concern every room
+                if !event.topic_hash().is_empty()
+                    && (self.room.is_none()
+                        || self.topic_hash().as_deref() != Some(event.topic_hash()))
+                {
+                    let kept = match &event {
+                        ClientEvent::Message(message) => {
+                            self.unread.count(message, self.client.own_node_id());
+                            None
+                        }
+                        // Kept for when the room is shown
+                        ClientEvent::Task { update, sender } => {
+                            Some(Message::TaskReceived(update.clone(), *sender))
+                        }
+                        ClientEvent::Calendar { event, sender } => {
+                            Some(Message::EventReceived(event.clone(), *sender))
+                        }
+                        ClientEvent::Rsvp { rsvp, sender } => {
+                            Some(Message::RsvpReceived(rsvp.clone(), *sender))
+                        }
+                        // Shared folders follow every topic
+                        ClientEvent::Attachment(attachment) => {
+                            Some(Message::AttachmentReceived(attachment.clone()))
+                        }
+                        // Failures matter wherever they happened
+                        ClientEvent::Error { error, .. } => {
+                            Some(Message::ClientError(error.clone()))
+                        }
+                        _ => None,
+                    };
+                    return match kept {
+                        Some(message) => self.update(message),
+                        None => Command::none(),
+                    };
+                }
+                let message = match event {
+                    ClientEvent::Message(message) => Message::MessageReceived(message),
+                    ClientEvent::Edit { edit, sender } => Message::EditReceived(edit, sender),
+                    ClientEvent::Delete { delete, sender } => {
+                        Message::DeleteReceived(delete, sender)
+                    }
+                    ClientEvent::Delivery {
+                        message_id, state, ..
+                    } => Message::DeliveryChanged(message_id, state),
+                    ClientEvent::Presence(presence) => Message::PresenceReceived(presence),
+                    ClientEvent::Profile(profile) => Message::ProfileReceived(profile),
+                    ClientEvent::Avatar(avatar) => Message::AvatarReceived(avatar),
+                    ClientEvent::Task { update, sender } => Message::TaskReceived(update, sender),
+                    ClientEvent::Calendar { event, sender } => {
+                        Message::EventReceived(event, sender)
+                    }
+                    ClientEvent::Rsvp { rsvp, sender } => Message::RsvpReceived(rsvp, sender),
+                    ClientEvent::Attachment(attachment) => Message::AttachmentReceived(attachment),
+                    ClientEvent::Activity { activity, .. } => Message::ActivityChanged(activity),
+                    ClientEvent::Connectivity(connectivity) => {
+                        Message::ConnectivityChanged(connectivity)
+                    }
+                    ClientEvent::Reconnect { state, .. } => Message::ReconnectChanged(state),
+                    ClientEvent::Error { error, .. } => Message::ClientError(error),
+                    // The traffic panel counts the neighbors on every tick
+                    ClientEvent::PeerJoined { .. } | ClientEvent::PeerLeft { .. } => {
+                        return Command::none()
+                    }
+                };
+                self.update(message)
+            }
+        }
+    }
+
+    fn theme(&self) -> Theme {
+        let dark = match self.config.theme {
+            ThemePreference::Light => false,
+            ThemePreference::Dark => true,
+            ThemePreference::System => self.system_dark,
+        };
+        if dark {
+            Theme::Dark
+        } else {
+            Theme::Light
+        }
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let screen = match &self.input_state {
+            InputState::Welcome { username } => screen_container(titled_card(
+                "Welcome to Chat",
+                vec![
+                    labeled_input(
+                        focus::username(),
+                        "Username",
+                        "Enter your username",
+                        username,
+                        Message::UsernameChanged,
+                    ),
+                    primary_button("Continue", Message::SubmitUsername).into(),
+                ],
+                400,
+                self.layout,
+            )),
+
+            InputState::MainMenu { username } => {
+                let mut choices: Vec<Element<_>> = vec![
+                    primary_button("Create a new topic", Message::CreateTopicSelected)
+                        .width(Length::Fill)
+                        .into(),
+                    primary_button("Join an existing topic", Message::JoinTopicSelected)
+                        .width(Length::Fill)
+                        .into(),
+                    secondary_button("Notes to self", Message::NotesSelected)
+                        .width(Length::Fill)
+                        .into(),
+                    secondary_button("Edit profile", Message::EditProfileSelected)
+                        .width(Length::Fill)
+                        .into(),
+                    secondary_button("Settings", Message::SettingsSelected)
+                        .width(Length::Fill)
+                        .into(),
+                    secondary_button("Diagnostics", Message::DiagnosticsSelected)
+                        .width(Length::Fill)
+                        .into(),
+                ];
+
+                // Tell which of the topics left have unread messages
+                let mut topics: Vec<_> = self
+                    .client
+                    .session()
+                    .subscribed_topics
+                    .into_iter()
+                    .collect();
+                topics.sort();
+                for (topic_name, topic_hash) in topics {
+                    let unread = self.unread.get(&topic_hash);
+                    if unread > 0 {
+                        choices.push(unread_badge(&topic_name, unread));
+                    }
+                }
+
+                // Offer the ticket found in the clipboard first
+                if let Some((topic_name, _)) = &self.clipboard_ticket {
+                    choices.insert(
+                        0,
+                        primary_button(
+                            &format!("Join '{}' from clipboard", topic_name),
+                            Message::JoinFromClipboard,
+                        )
+                        .width(Length::Fill)
+                        .into(),
+                    );
+                }
+
+                screen_container(titled_card(
+                    format!("Hello, {}! What would you like to do?", username),
+                    choices,
+                    400,
+                    self.layout,
+                ))
+            }
+
+            InputState::EditProfile {
+                username: _,
+                profile,
+                image_path,
+                avatar_image,
+            } => {
+                let mut fields = vec![
+                    labeled_input(
+                        focus::display_name(),
+                        "Display name",
+                        "Shown on your messages instead of your username",
+                        &profile.display_name,
+                        Message::DisplayNameChanged,
+                    ),
+                    labeled_input(
+                        focus::status_line(),
+                        "Status",
+                        "What are you up to?",
+                        &profile.status_line,
+                        Message::StatusLineChanged,
+                    ),
+                    labeled_input(
+                        focus::avatar(),
+                        "Avatar",
+                        "An emoji or your initials",
+                        &profile.avatar,
+                        Message::AvatarChanged,
+                    ),
+                    labeled_input(
+                        focus::avatar_image(),
+                        "Avatar image",
+                        "Path to an image of at most 256 KiB",
+                        image_path,
+                        Message::AvatarImagePathChanged,
+                    ),
+                ];
+                if avatar_image.is_some() {
+                    fields.push(
+                        row![
+                            text("An image is set").size(14).width(Length::Fill),
+                            secondary_button("Remove image", Message::RemoveAvatarImage).padding(5),
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center)
+                        .into(),
+                    );
+                }
+                fields.push(button_row(vec![
+                    secondary_button("Back", Message::BackToMenu),
+                    primary_button("Save", Message::SaveProfile),
+                ]));
+
+                screen_container(titled_card("Your Profile", fields, 400, self.layout))
+            }
+
+            InputState::Settings { username, filtered } => screen_container(titled_card(
+                "Settings",
+                vec![
+                    labeled_input(
+                        focus::settings_username(),
+                        "Username",
+                        "Enter your username",
+                        username,
+                        Message::UsernameChanged,
+                    ),
+                    labeled_pick_list(
+                        "Theme",
+                        &ThemePreference::ALL,
+                        self.config.theme,
+                        Message::ThemeChanged,
+                    ),
+                    labeled_pick_list(
+                        "Notify in the background about",
+                        &Notifications::ALL,
+                        self.config.notifications,
+                        Message::NotificationsChanged,
+                    ),
+                    labeled_pick_list(
+                        "Network (applies on the next start)",
+                        &RelayMode::ALL,
+                        self.config.relay_mode,
+                        Message::RelayModeChanged,
+                    ),
+                    labeled_pick_list(
+                        "Connect over (applies on the next start)",
+                        &IpVersion::ALL,
+                        self.config.binding.ip_version,
+                        Message::IpVersionChanged,
+                    ),
+                    labeled_pick_list(
+                        "Log level",
+                        &LogLevel::ALL,
+                        self.config.log_level,
+                        Message::LogLevelChanged,
+                    ),
+                    labeled_pick_list(
+                        "Send messages with",
+                        &SendKey::ALL,
+                        self.config.send_key,
+                        Message::SendKeyChanged,
+                    ),
+                    labeled_pick_list(
+                        "Large messages",
+                        &LargeMessages::ALL,
+                        self.config.large_messages,
+                        Message::LargeMessagesChanged,
+                    ),
+                    labeled_pick_list(
+                        "Message text",
+                        &MessageFormat::ALL,
+                        self.config.message_format,
+                        Message::MessageFormatChanged,
+                    ),
+                    labeled_pick_list(
+                        "Longest message sent",
+                        &MessageLimit::ALL,
+                        self.config.message_limit,
+                        Message::MessageLimitChanged,
+                    ),
+                    labeled_pick_list(
+                        "Links in messages",
+                        &LinkConfirmation::ALL,
+                        self.config.link_confirmation,
+                        Message::LinkConfirmationChanged,
+                    ),
+                    labeled_input(
+                        focus::content_filter(),
+                        "Filtered on every topic (applies when going back)",
+                        "Words, or /expressions/, separated by commas",
+                        filtered,
+                        Message::ContentFilterChanged,
+                    ),
+                    labeled_pick_list(
+                        "Filtered content",
+                        &FilterAction::ALL,
+                        self.config.content_filter.action,
+                        Message::FilterActionChanged,
+                    ),
+                    key_bindings("Keyboard shortcuts", &shortcuts::BINDINGS),
+                    button_row(vec![secondary_button("Back", Message::LeaveSettings)]),
+                ],
+                400,
+                self.layout,
+            )),
+
+            InputState::Diagnostics {
+                status, peer_addr, ..
+            } => {
+                let mut fields: Vec<Element<_>> = Vec::new();
+                match status {
+                    Some(status) => {
+                        let addresses = if status.direct_addresses.is_empty() {
+                            "None found yet".to_string()
+                        } else {
+                            status
+                                .direct_addresses
+                                .iter()
+                                .map(ToString::to_string)
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        };
+                        let listening = status
+                            .bound_sockets
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        let peer_scores = if status.peer_scores.is_empty() {
+                            "No peers heard from yet".to_string()
+                        } else {
+                            status
+                                .peer_scores
+                                .iter()
+                                .map(|(node_id, score)| {
+                                    let blocked = if *score <= BLOCK_THRESHOLD {
+                                        ", blocked"
+                                    } else {
+                                        ""
+                                    };
+                                    format!("{}: {}{}", node_id.fmt_short(), score, blocked)
+                                })
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        };
+                        let relay = status
+                            .home_relay
+                            .as_ref()
+                            .map_or("Not connected".to_string(), ToString::to_string);
+                        for (label, value) in [
+                            ("Node id", status.node_id.to_string()),
+                            ("Listening on", listening),
+                            ("Direct addresses", addresses),
+                            ("Relay", relay),
+                            ("Network mode", self.client.relay_mode().to_string()),
+                            (
+                                "Connectivity",
+                                status.connectivity(self.client.relay_mode()).to_string(),
+                            ),
+                            ("NAT traversal", status.nat_traversal().to_string()),
+                            ("Connected peers", status.connected_peers().to_string()),
+                            ("Peer reputation", peer_scores),
+                            // Missed by the window falling behind the network
+                            ("Events dropped", self.events_dropped.to_string()),
+                        ] {
+                            fields.push(
+                                column![text(label).size(14), text(value).size(16)]
+                                    .spacing(5)
+                                    .into(),
+                            );
+                        }
+                        match peer_addr {
+                            // When discovery fails, the address of a peer is
+                            // copied from its diagnostics and typed in here
+                            Some(peer_addr) => {
+                                fields.push(labeled_input(
+                                    focus::peer_addr(),
+                                    "Node address of the peer",
+                                    "node-…",
+                                    peer_addr,
+                                    Message::PeerAddrChanged,
+                                ));
+                                fields.push(button_row(vec![
+                                    secondary_button("Cancel", Message::CancelAddPeer),
+                                    primary_button("Add peer", Message::SubmitAddPeer),
+                                ]));
+                            }
+                            None => fields.push(button_row(vec![
+                                secondary_button("Back", Message::BackToMenu),
+                                secondary_button("Add peer manually", Message::AddPeerSelected),
+                                primary_button("Copy node address", Message::CopyNodeAddr),
+                            ])),
+                        }
+                    }
+                    None => {
+                        fields.push(text("The network is still starting").size(16).into());
+                        fields.push(button_row(vec![secondary_button(
+                            "Back",
+                            Message::BackToMenu,
+                        )]));
+                    }
+                }
+                screen_container(titled_card("Diagnostics", fields, 500, self.layout))
+            }
+
+            InputState::TopicSettings {
+                weekday,
+                hour,
+                days,
+                folder,
+                prune,
+                members_exported,
+                welcome,
+                bridge_folder,
+                allowed_node,
+                filtered,
+                filter_action,
+                ..
+            } => {
+                let topic_hash = &self.topic_hash().unwrap_or_default();
+                let has_rule = self.config.export_rules.contains_key(topic_hash);
+
+                let mut fields = vec![
+                    text("Scheduled export").size(18).into(),
+                    labeled_pick_list("Day", &WEEKDAYS, *weekday, Message::ExportWeekdayChanged),
+                    labeled_input(
+                        focus::export_hour(),
+                        "Hour",
+                        "0 to 23, in local time",
+                        hour,
+                        Message::ExportHourChanged,
+                    ),
+                    labeled_input(
+                        focus::export_days(),
+                        "Days exported",
+                        "How many days of messages to export",
+                        days,
+                        Message::ExportDaysChanged,
+                    ),
+                    labeled_input(
+                        focus::export_folder(),
+                        "Folder",
+                        "Where to write the Markdown files",
+                        folder,
+                        Message::ExportFolderChanged,
+                    ),
+                    checkbox(
+                        "Then prune the exported messages from the archive",
+                        *prune,
+                        Message::ExportPruneToggled,
+                    )
+                    .into(),
+                ];
+                let mut buttons = vec![secondary_button("Back", Message::LeaveTopicSettings)];
+                if has_rule {
+                    buttons.push(secondary_button("Remove", Message::RemoveExportRule));
+                    buttons.push(secondary_button("Run now", Message::RunExportNow));
+                }
+                buttons.push(primary_button("Save", Message::SaveExportRule));
+                fields.push(button_row(buttons));
+
+                fields.push(text("Welcome message").size(18).into());
+                fields.push(labeled_input(
+                    focus::welcome_template(),
+                    "Posted from this node when a newcomer shows up",
+                    "e.g. Welcome, {name}! Please read the pinned rules",
+                    welcome,
+                    Message::WelcomeTemplateChanged,
+                ));
+                fields.push(button_row(vec![primary_button(
+                    if welcome.trim().is_empty() {
+                        "Save (no welcome)"
+                    } else {
+                        "Save welcome"
+                    },
+                    Message::SaveWelcome,
+                )]));
+
+                fields.push(text("Members").size(18).into());
+                fields.push(
+                    text(match members_exported {
+                        Some(path) => format!("Exported to {}", path.display()),
+                        None => "Export when each member was first and last seen here, \
+                            to the folder above"
+                            .to_string(),
+                    })
+                    .size(14)
+                    .into(),
+                );
+                fields.push(button_row(vec![
+                    secondary_button("Export CSV", Message::ExportMembers(ExportFormat::Csv)),
+                    secondary_button("Export JSON", Message::ExportMembers(ExportFormat::Json)),
+                ]));
+
+                fields.push(text("Calendar").size(18).into());
+                let mut calendar_buttons = vec![secondary_button(
+                    "Export upcoming events",
+                    Message::ExportCalendar,
+                )];
+                fields.push(
+                    text(match self.config.calendar_feeds.get(topic_hash) {
+                        Some(path) => {
+                            calendar_buttons
+                                .push(secondary_button("Stop updating", Message::StopCalendarFeed));
+                            format!(
+                                "Kept up to date at {} whenever an event is shared here. \
+                                Subscribe to the file from a calendar application",
+                                path.display()
+                            )
+                        }
+                        None => "Export the upcoming events as a calendar, to the folder \
+                            above, and keep it up to date"
+                            .to_string(),
+                    })
+                    .size(14)
+                    .into(),
+                );
+                fields.push(button_row(calendar_buttons));
+
+                fields.push(text("Shared folder").size(18).into());
+                let shared = self.config.folder_bridges.get(topic_hash);
+                fields.push(labeled_input(
+                    focus::bridge_folder(),
+                    "Files dropped here are posted, and files posted by others saved here",
+                    "Folder to share with the topic",
+                    bridge_folder,
+                    Message::BridgeFolderChanged,
+                ));
+                fields.push(
+                    text(match shared {
+                        Some(folder) => format!(
+                            "Sharing {}: {} file(s) posted or saved. Files over {} KB, \
+                            hidden files and deletions are not shared",
+                            folder.display(),
+                            self.bridges.count(topic_hash),
+                            bridge::MAX_FILE_SIZE / 1024
+                        ),
+                        None => "Members sharing a folder with the topic keep the same files"
+                            .to_string(),
+                    })
+                    .size(14)
+                    .into(),
+                );
+                fields.push(button_row(vec![if shared.is_some() {
+                    secondary_button("Stop sharing", Message::ToggleBridge)
+                } else {
+                    primary_button("Share folder", Message::ToggleBridge)
+                }]));
+
+                fields.push(text("Filter").size(18).into());
+                fields.push(labeled_input(
+                    focus::topic_filter(),
+                    "Filtered here besides what is filtered everywhere",
+                    "Words, or /expressions/, separated by commas",
+                    filtered,
+                    Message::TopicFilterChanged,
+                ));
+                fields.push(labeled_pick_list(
+                    "Filtered content",
+                    &FilterAction::ALL,
+                    *filter_action,
+                    Message::TopicFilterActionChanged,
+                ));
+                fields.push(button_row(vec![primary_button(
+                    "Save filter",
+                    Message::SaveTopicFilter,
+                )]));
+
+                fields.push(text("Links").size(18).into());
+                let links = if self.config.trusted_topics.contains(topic_hash) {
+                    row![
+                        text("Trusted: links open without asking, unless asking always")
+                            .size(14)
+                            .width(Length::Fill),
+                        secondary_button("Stop trusting", Message::ToggleTrustedLinks).padding(5),
+                    ]
+                } else {
+                    row![
+                        text("Ask before opening the links posted here")
+                            .size(14)
+                            .width(Length::Fill),
+                        secondary_button("Trust links", Message::ToggleTrustedLinks).padding(5),
+                    ]
+                };
+                fields.push(
+                    links
+                        .spacing(10)
+                        .width(Length::Fill)
+                        .align_items(Alignment::Center)
+                        .into(),
+                );
+
+                fields.push(text("Access").size(18).into());
+                let own_node_id = self.client.own_node_id();
+                let created =
+                    own_node_id.is_some() && self.client.topic_creator(topic_hash) == own_node_id;
+                match (created, self.config.allowlists.get(topic_hash)) {
+                    (true, Some(members)) => {
+                        fields.push(
+                            text("Private: only you and the nodes below take part")
+                                .size(14)
+                                .into(),
+                        );
+                        for node_id in members {
+                            fields.push(
+                                row![
+                                    text(self.member_name(*node_id))
+                                        .size(14)
+                                        .width(Length::Fill),
+                                    secondary_button("Remove", Message::DisallowNode(*node_id))
+                                        .padding(5),
+                                ]
+                                .spacing(10)
+                                .width(Length::Fill)
+                                .align_items(Alignment::Center)
+                                .into(),
+                            );
+                        }
+                        fields.push(labeled_input(
+                            focus::allowed_node(),
+                            "Allow a node",
+                            "Name of a member here, or node id",
+                            allowed_node,
+                            Message::AllowedNodeChanged,
+                        ));
+                        fields.push(button_row(vec![
+                            secondary_button("Open to everyone", Message::ToggleAllowlist),
+                            primary_button("Allow", Message::AllowNode),
+                        ]));
+                    }
+                    (true, None) => fields.push(
+                        row![
+                            text("Open: anyone with the ticket takes part")
+                                .size(14)
+                                .width(Length::Fill),
+                            secondary_button("Make private", Message::ToggleAllowlist).padding(5),
+                        ]
+                        .spacing(10)
+                        .width(Length::Fill)
+                        .align_items(Alignment::Center)
+                        .into(),
+                    ),
+                    (false, _) => fields.push(
+                        text(if self.client.allowlist(topic_hash).is_some() {
+                            "Private: the creator decides who takes part"
+                        } else {
+                            "Open: anyone with the ticket takes part"
+                        })
+                        .size(14)
+                        .into(),
+                    ),
+                }
+
+                fields.push(text("Legal hold").size(18).into());
+                let hold = if self.config.held_topics.contains(topic_hash) {
+                    row![
+                        text("On hold: the archive is never pruned")
+                            .size(14)
+                            .width(Length::Fill),
+                        secondary_button("Release hold", Message::ToggleHold).padding(5),
+                    ]
+                } else {
+                    row![
+                        text("Keep the archive from being pruned")
+                            .size(14)
+                            .width(Length::Fill),
+                        secondary_button("Place on hold", Message::ToggleHold).padding(5),
+                    ]
+                };
+                fields.push(
+                    hold.spacing(10)
+                        .width(Length::Fill)
+                        .align_items(Alignment::Center)
+                        .into(),
+                );
+
+                // Newest runs first
+                fields.push(text("Run history").size(18).into());
+                let runs = self.export_runs.runs(topic_hash);
+                if runs.is_empty() {
+                    fields.push(text("No runs yet").size(14).into());
+                }
+                for run in runs.iter().rev() {
+                    fields.push(text(describe_run(run)).size(14).into());
+                }
+
+                fields.push(text("Statistics").size(18).into());
+                fields.push(
+                    text(format!("Message latency: {}", self.latency))
+                        .size(14)
+                        .into(),
+                );
+                if let Some(heatmap) = self.heatmaps.get(topic_hash) {
+                    let busiest = match heatmap.busiest() {
+                        Some((weekday, hour)) => format!(
+                            "Busiest hour: {} {:02}:00–{:02}:00, local time",
+                            weekday,
+                            hour,
+                            hour + 1
+                        ),
+                        None => "No messages to tell the busiest hour yet".to_string(),
+                    };
+                    fields.push(text(busiest).size(14).into());
+                    fields.push(activity_heatmap(heatmap));
+                }
+
+                screen_container(titled_card("Topic Settings", fields, 500, self.layout))
+            }
+
+            InputState::CreateTopic {
+                username: _,
+                topic_name,
+            } => screen_container(titled_card(
+                "Create a New Topic",
+                vec![
+                    labeled_input(
+                        focus::topic_name(),
+                        "Topic name",
+                        "Enter topic name",
+                        topic_name,
+                        Message::TopicNameChanged,
+                    ),
+                    button_row(vec![
+                        secondary_button("Back", Message::BackToMenu),
+                        primary_button("Create", Message::SubmitCreateTopic),
+                    ]),
+                ],
+                400,
+                self.layout,
+            )),
+
+            InputState::JoinTopic {
+                username: _,
+                ticket,
+            } => {
+                let mut fields = Vec::new();
+                // Joining the ticket pasted from the clipboard takes one click
+                if let Some((topic_name, _)) = self
+                    .clipboard_ticket
+                    .as_ref()
+                    .filter(|(_, found)| found == ticket)
+                {
+                    fields.push(banner(
+                        &format!(
+                            "Pasted the ticket of '{}' from the clipboard. Join?",
+                            topic_name
+                        ),
+                        primary_button("Join", Message::SubmitJoinTopic),
+                    ));
+                }
+                fields.push(labeled_input(
+                    focus::ticket(),
+                    "Ticket",
+                    "Paste the ticket or invite",
+                    ticket,
+                    Message::TicketChanged,
+                ));
+                fields.push(button_row(vec![
+                    secondary_button("Back", Message::BackToMenu),
+                    primary_button("Join", Message::SubmitJoinTopic),
+                ]));
+                screen_container(titled_card(
+                    "Join an Existing Topic",
+                    fields,
+                    400,
+                    self.layout,
+                ))
+            }
+
+            InputState::TopicCreated {
+                username: _,
+                topic_name,
+                ticket,
+            } => {
+                let ticket_text = text("Share this ticket with others to let them join:")
+                    .size(16)
+                    .width(Length::Fill)
+                    .horizontal_alignment(alignment::Horizontal::Center);
+
+                let ticket_field = context_menu(
+                    text(ticket)
+                        .size(18)
+                        .width(Length::Fill)
+                        .horizontal_alignment(alignment::Horizontal::Center),
+                    vec![
+                        MenuItem::new("Copy ticket", Message::CopyTicket),
+                        MenuItem::new("Copy private invite", Message::CopyInvite),
+                    ],
+                    self.context_menu == Some(ContextTarget::Ticket),
+                    Message::OpenContextMenu(ContextTarget::Ticket),
+                    Message::CloseContextMenu,
+                );
+
+                let ticket_row = row![
+                    ticket_field,
+                    secondary_button("Copy", Message::CopyTicket).padding(5),
+                ]
+                .spacing(10)
+                .width(Length::Fill)
+                .align_items(Alignment::Center);
+
+                // Invites keep the topic secret from anybody intercepting them
+                let invite_row = row![
+                    text("Or share a single use invite revealing the topic only once redeemed:")
+                        .size(14)
+                        .width(Length::Fill),
+                    secondary_button("Copy invite", Message::CopyInvite).padding(5),
+                ]
+                .spacing(10)
+                .width(Length::Fill)
+                .align_items(Alignment::Center);
+
+                screen_container(titled_card(
+                    format!("Topic '{}' Created Successfully!", topic_name),
+                    vec![
+                        ticket_text.into(),
+                        self.tour_mark(TourStep::CopyTicket, ticket_row),
+                        invite_row.into(),
+                        button_row(vec![
+                            secondary_button("Back to Menu", Message::BackToMenu),
+                            primary_button("Enter Chat Room", Message::EnterChatRoom),
+                        ]),
+                    ],
+                    600,
+                    self.layout,
+                ))
+            }
+
+            InputState::ChatRoom {
+                username: _,
+                message,
+            } => {
+                let mut title = column![
+                    heading(format!(
+                        "Topic: {}",
+                        self.room.as_ref().map_or("Unknown", Room::name)
+                    )),
+                    activity_label(
+                        self.room
+                            .as_ref()
+                            .map_or(TopicActivity::default(), Room::activity)
+                    ),
+                ]
+                .spacing(2)
+                .width(Length::Fill);
+
+                // Compact windows show how many members are around instead of
+                // the member list
+                if self.layout.is_compact() {
+                    title = title.push(
+                        text(format!(
+                            "{} member(s) present",
+                            self.roster.present(Instant::now())
+                        ))
+                        .size(12)
+                        .width(Length::Fill)
+                        .horizontal_alignment(alignment::Horizontal::Center),
+                    );
+                }
+
+                // Create the message list, rendering only the rows around the
+                // viewport
+                let search = self
+                    .message_search
+                    .as_deref()
+                    .map(|query| query.trim().to_lowercase())
+                    .filter(|query| !query.is_empty());
+                let rows = timeline::rows(
+                    self.conversation.messages().iter().filter(|message| {
+                        search
+                            .as_ref()
+                            .is_none_or(|query| matches_search(message, query))
+                    }),
+                    |message| !self.show_muted && self.config.mutes(message),
+                );
+                let visible = virtual_list::visible_range(
+                    rows.len(),
+                    self.message_scroll,
+                    self.window_height,
+                );
+                let above = virtual_list::spacer_height(visible.start);
+                let below = virtual_list::spacer_height(rows.len() - visible.end);
+                let messages = rows[visible].iter().fold(
+                    column![Space::with_height(Length::Fixed(above))]
+                        .spacing(10)
+                        .width(Length::Fill),
+                    |column, row| {
+                        let (msg, grouped) = match *row {
+                            timeline::Row::Day(day) => return column.push(day_separator(day)),
+                            timeline::Row::Hidden(count) => {
+                                return column
+                                    .push(hidden_messages(count, Message::ShowMutedMessages))
+                            }
+                            timeline::Row::Message { message, grouped } => (message, grouped),
+                        };
+                        let target = ContextTarget::ChatMessage(msg.id.clone());
+
+                        let mut items = vec![
+                            MenuItem::new(
+                                "Copy message",
+                                Message::CopyToClipboard(msg.content.clone()),
+                            ),
+                            MenuItem::new(
+                                "Copy author",
+                                Message::CopyToClipboard(msg.author.clone()),
+                            ),
+                        ];
+                        if self.local.is_none() && !msg.is_deleted() && msg.author != "System" {
+                            let flagged = self.tasks.get(&msg.topic_hash, &msg.id).is_some();
+                            items.push(MenuItem::new(
+                                if flagged {
+                                    "Remove from tasks"
+                                } else {
+                                    "Mark as action item"
+                                },
+                                Message::UpdateTask(msg.id.clone(), TaskChange::Flag(!flagged)),
+                            ));
+                        }
+                        if self.local.is_none()
+                            && msg.author != "System"
+                            && !self.is_own_message(msg)
+                        {
+                            items.push(MenuItem::new(
+                                if self.config.mutes(msg) {
+                                    "Unmute author"
+                                } else {
+                                    "Mute author"
+                                },
+                                Message::ToggleMute(msg.verified_sender, msg.author.clone()),
+                            ));
+                        }
+                        if self.is_own_message(msg) && !msg.is_deleted() {
+                            items.push(MenuItem::new(
+                                "Edit message",
+                                Message::StartEdit(msg.id.clone()),
+                            ));
+                            items.push(MenuItem::new(
+                                "Delete message",
+                                Message::DeleteMessage(msg.id.clone()),
+                            ));
+                            if self.local.is_none() {
+                                items.push(MenuItem::new(
+                                    "Delivery details",
+                                    Message::ShowDeliveryDetails(msg.id.clone()),
+                                ));
+                            }
+                        }
+
+                        let row = match self.calendar.event_of(msg) {
+                            Some(event) => event_card(
+                                msg,
+                                event,
+                                &self
+                                    .calendar
+                                    .responses(&msg.topic_hash, &msg.id)
+                                    .into_iter()
+                                    .map(|(node_id, response)| {
+                                        (self.member_name(node_id), response)
+                                    })
+                                    .collect::<Vec<_>>(),
+                                self.client.own_node_id().and_then(|node_id| {
+                                    self.calendar.response_of(&msg.topic_hash, &msg.id, node_id)
+                                }),
+                                self.event_exported
+                                    .as_ref()
+                                    .filter(|(id, _)| *id == msg.id)
+                                    .map(|(_, path)| path.as_path()),
+                                Message::RespondToEvent,
+                                Message::ExportEvent(msg.id.clone()),
+                            ),
+                            None => message_row(
+                                msg,
+                                self.profiles.of_sender(msg),
+                                msg.verified_sender
+                                    .and_then(|node_id| self.avatars.get(&node_id)),
+                                self.room.as_ref().and_then(|room| room.delivery(&msg.id)),
+                                grouped,
+                                self.config.message_format == MessageFormat::Raw,
+                                Message::OpenLink,
+                            ),
+                        };
+                        column.push(context_menu(
+                            row,
+                            items,
+                            self.context_menu.as_ref() == Some(&target),
+                            Message::OpenContextMenu(target),
+                            Message::CloseContextMenu,
+                        ))
+                    },
+                );
+
+                let messages_scrollable =
+                    scrollable(messages.push(Space::with_height(Length::Fixed(below))))
+                        .id(message_list())
+                        .on_scroll(Message::MessagesScrolled)
+                        .height(Length::Fill)
+                        .width(Length::Fill);
+
+                let composer_input = text_input("Type a message", &composer::to_input(message))
+                    .id(focus::composer())
+                    .on_input(Message::MessageChanged)
+                    .on_paste(Message::MessagePasted)
+                    .on_submit(Message::ComposerSubmitted)
+                    .padding(10)
+                    .width(Length::Fill);
+                let mut input_row = row![
+                    composer_input,
+                    secondary_button("😊", Message::ToggleEmojiPicker),
+                ]
+                .spacing(10)
+                .width(Length::Fill);
+                // Local topics have nobody to invite
+                if self.local.is_none() {
+                    input_row = input_row.push(secondary_button("📅", Message::ToggleEventForm));
+                }
+                let remaining = self.config.message_limit.remaining(message);
+                let input_row = input_row
+                    .push(
+                        text(if remaining < 0 {
+                            format!("{} over", -remaining)
+                        } else {
+                            format!("{} left", remaining)
+                        })
+                        .size(14),
+                    )
+                    .push(primary_button("Send", Message::SendMessage))
+                    .align_items(Alignment::Center);
+
+                let leave_button = secondary_button("Leave", Message::BackToMenu).padding(5);
+                let archive_button = if self.archive.is_some() {
+                    secondary_button("Stop archiving", Message::ToggleArchive)
+                } else {
+                    secondary_button("Archive", Message::ToggleArchive)
+                }
+                .padding(5);
+                // Local topics are never sent anywhere to archive
+                let actions = if self.local.is_some() {
+                    row![leave_button]
+                } else {
+                    let invite_button = secondary_button("Invite", Message::CopyInvite).padding(5);
+                    let tasks_label = if self.show_tasks {
+                        "Messages".to_string()
+                    } else {
+                        format!("Tasks ({})", self.tasks.open(self.conversation.messages()))
+                    };
+                    let tasks_button =
+                        secondary_button(&tasks_label, Message::ToggleTasks).padding(5);
+                    let settings_button =
+                        secondary_button("Settings", Message::TopicSettingsSelected).padding(5);
+                    let stats_label = if self.traffic.is_some() {
+                        "Hide stats"
+                    } else {
+                        "Stats"
+                    };
+                    let stats_button =
+                        secondary_button(stats_label, Message::ToggleTraffic).padding(5);
+                    row![
+                        connectivity_chip(self.connectivity),
+                        invite_button,
+                        tasks_button,
+                        stats_button,
+                        settings_button,
+                        archive_button,
+                        leave_button
+                    ]
+                }
+                .spacing(10);
+
+                // Narrow windows stack the title above the leave button
+                let header: Element<_> = if self.layout.is_compact() {
+                    column![title, actions]
+                        .spacing(10)
+                        .width(Length::Fill)
+                        .align_items(Alignment::Center)
+                        .into()
+                } else {
+                    row![title, actions].spacing(10).width(Length::Fill).into()
+                };
+
+                let input_row = self.tour_mark(TourStep::Composer, input_row);
+                let input_row: Element<_> = match &self.emoji_search {
+                    Some(query) => column![
+                        emoji_picker(
+                            focus::emoji_search(),
+                            query,
+                            &self.config.recent_emoji,
+                            &emoji::search(query),
+                            Message::EmojiSearchChanged,
+                            Message::EmojiPicked,
+                        ),
+                        input_row,
+                    ]
+                    .spacing(5)
+                    .into(),
+                    None => input_row,
+                };
+                let input_row: Element<_> = match &self.event_form {
+                    Some(form) => column![
+                        row![
+                            labeled_input(
+                                focus::event_title(),
+                                "Event",
+                                "What is happening",
+                                &form.title,
+                                |value| Message::EventFieldChanged(EventField::Title, value),
+                            ),
+                            labeled_input(
+                                focus::event_location(),
+                                "Location",
+                                "Where, optional",
+                                &form.location,
+                                |value| Message::EventFieldChanged(EventField::Location, value),
+                            ),
+                        ]
+                        .spacing(10),
+                        row![
+                            labeled_input(
+                                focus::event_starts(),
+                                "Starts",
+                                "2024-05-01 18:30",
+                                &form.starts,
+                                |value| Message::EventFieldChanged(EventField::Starts, value),
+                            ),
+                            labeled_input(
+                                focus::event_ends(),
+                                "Ends",
+                                "2024-05-01 20:00",
+                                &form.ends,
+                                |value| Message::EventFieldChanged(EventField::Ends, value),
+                            ),
+                        ]
+                        .spacing(10),
+                        row![
+                            Space::with_width(Length::Fill),
+                            secondary_button("Cancel", Message::ToggleEventForm).padding(5),
+                            primary_button("Share event", Message::ShareEvent).padding(5),
+                        ]
+                        .spacing(10),
+                        input_row,
+                    ]
+                    .spacing(5)
+                    .into(),
+                    None => input_row,
+                };
+
+                // Tell the user that sending replaces an earlier message
+                let composer: Element<_> = if let Some((pasted, preview)) = &self.pasted_image {
+                    column![
+                        row![
+                            image(preview.clone()).height(Length::Fixed(PASTE_PREVIEW_HEIGHT)),
+                            text(format!(
+                                "Send {} ({} × {}, {} KB)?",
+                                pasted.name,
+                                pasted.width,
+                                pasted.height,
+                                pasted.png.len().div_ceil(1024)
+                            ))
+                            .size(14)
+                            .width(Length::Fill),
+                            secondary_button("Discard", Message::DiscardPastedImage).padding(5),
+                            primary_button("Send image", Message::SendPastedImage).padding(5),
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                        input_row,
+                    ]
+                    .spacing(5)
+                    .into()
+                } else if self.editing.is_some() {
+                    column![
+                        row![
+                            text("Editing message").size(14).width(Length::Fill),
+                            secondary_button("Cancel", Message::CancelEdit).padding(5),
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                        input_row,
+                    ]
+                    .spacing(5)
+                    .into()
+                } else if self.confirming_send {
+                    column![
+                        row![
+                            text(format!(
+                                "This message is {} characters long. Send it anyway?",
+                                message.chars().count()
+                            ))
+                            .size(14)
+                            .width(Length::Fill),
+                            secondary_button("Cancel", Message::CancelSend).padding(5),
+                            primary_button("Send anyway", Message::SendMessage).padding(5),
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                        input_row,
+                    ]
+                    .spacing(5)
+                    .into()
+                } else if let Some(help) = self.command_help(message) {
+                    column![help, input_row].spacing(5).into()
+                } else {
+                    input_row
+                };
+
+                let mut content = column![header];
+                if let Some(address) = &self.confirming_link {
+                    content = content.push(
+                        row![
+                            text(format!(
+                                "Open {} in your browser? Links can lead anywhere",
+                                address
+                            ))
+                            .size(14)
+                            .width(Length::Fill),
+                            secondary_button("Cancel", Message::CancelOpenLink).padding(5),
+                            primary_button("Open", Message::ConfirmOpenLink).padding(5),
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                    );
+                }
+                if let Some(message_id) = &self.delivery_details {
+                    content = content.push(self.delivery_details(message_id));
+                }
+                let room = self.room.as_ref();
+                if let Some(state) = room.and_then(Room::reconnect) {
+                    let notice = match state {
+                        ReconnectState::Retrying { attempt, delay } => format!(
+                            "Lost the connection to this room, retrying (attempt {} of {}, \
+                             waiting {} s)",
+                            attempt,
+                            reconnect::MAX_ATTEMPTS,
+                            delay.as_secs().max(1)
+                        ),
+                        _ => "Could not reconnect to this room".to_string(),
+                    };
+                    content = content.push(banner(
+                        &notice,
+                        primary_button("Retry now", Message::Resync),
+                    ));
+                } else if room.is_some_and(|room| room.activity() == TopicActivity::Partitioned) {
+                    let resync = if room.is_some_and(Room::is_resyncing) {
+                        secondary_button("Resyncing…", Message::Resync)
+                    } else {
+                        primary_button("Resync now", Message::Resync)
+                    };
+                    content =
+                        content.push(banner("You may be disconnected from this room", resync));
+                }
+
+                if let Some(query) = &self.message_search {
+                    let found = rows
+                        .iter()
+                        .filter(|row| matches!(row, timeline::Row::Message { .. }))
+                        .count();
+                    content = content.push(
+                        row![
+                            text_input("Search messages", query)
+                                .id(focus::message_search())
+                                .on_input(Message::MessageSearchChanged)
+                                .padding(6)
+                                .width(Length::Fill),
+                            text(format!("{} found", found)).size(14),
+                            secondary_button("Close", Message::CloseMessageSearch).padding(5),
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                    );
+                }
+
+                if let Some(stats) = &self.traffic {
+                    content = content.push(traffic_panel(stats));
+                }
+
+                let mut content = if self.show_tasks {
+                    content.push(task_list(
+                        self.tasks
+                            .of(self.conversation.messages())
+                            .into_iter()
+                            .filter_map(|task| {
+                                let message = self.conversation.get(&task.message_id)?;
+                                Some((task, message))
+                            })
+                            .collect(),
+                        self.assignees(),
+                        |id, done| Message::UpdateTask(id, TaskChange::Done(done)),
+                        |id, node_id| Message::UpdateTask(id, TaskChange::Assign(node_id)),
+                        |id| Message::UpdateTask(id, TaskChange::Flag(false)),
+                    ))
+                } else {
+                    content.push(messages_scrollable)
+                };
+                if self.unseen > 0 {
+                    content = content.push(
+                        container(
+                            primary_button(
+                                &format!("{} new message(s) ↓", self.unseen),
+                                Message::JumpToLatest,
+                            )
+                            .padding(5),
+                        )
+                        .width(Length::Fill)
+                        .center_x(),
+                    );
+                }
+                let content = content
+                    .push(composer)
+                    .spacing(self.layout.spacing())
+                    .width(Length::Fill)
+                    .height(Length::Fill);
+
+                // Narrow windows leave no room for the member list
+                let members: Vec<_> = self
+                    .roster
+                    .members(Instant::now())
+                    .into_iter()
+                    .map(|(member, state)| (member, state, self.client.latency(&member.node_id)))
+                    .collect();
+                let content: Element<_> = if self.layout.is_compact() {
+                    content.into()
+                } else {
+                    row![
+                        content,
+                        member_list(
+                            &members,
+                            &self.profiles,
+                            &self.last_seen,
+                            Utc::now(),
+                            Some(Message::SaveMembersAsContacts)
+                                .filter(|_| self.local.is_none() && !members.is_empty()),
+                            match self.context_menu {
+                                Some(ContextTarget::Member(node_id)) => Some(node_id),
+                                _ => None,
+                            },
+                            |member| self.member_menu(member),
+                            |node_id| Message::OpenContextMenu(ContextTarget::Member(node_id)),
+                            Message::CloseContextMenu,
+                        )
+                    ]
+                    .spacing(self.layout.spacing())
+                    .into()
+                };
+
+                container(content)
+                    .padding(self.layout.padding())
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .into()
+            }
+        };
+
+        // The topic switcher opens above whatever screen is shown
+        match &self.switcher {
+            Some(query) => column![
+                container(topic_switcher(
+                    focus::topic_switcher(),
+                    query,
+                    &self.switcher_topics(),
+                    Message::SwitcherQueryChanged,
+                    Message::SwitchToFirstMatch,
+                    Message::SwitchTopic,
+                ))
+                .padding(self.layout.padding()),
+                screen,
+            ]
+            .into(),
+            None => screen,
+        }
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        // Follow the window size and keyboard navigation
+        let events = subscription::events_with(|event, status| match (event, status) {
+            (Event::Window(window::Event::Resized { width, height }), _) => {
+                Some(Message::WindowResized(width, height))
+            }
+            (Event::Window(window::Event::Focused), _) => Some(Message::WindowFocused),
+            (Event::Window(window::Event::Unfocused), _) => Some(Message::WindowUnfocused),
+            (Event::Window(window::Event::CloseRequested), _) => Some(Message::CloseRequested),
+            // Tells what submitting the composer means
+            (Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)), _) => {
+                Some(Message::ModifiersChanged(modifiers))
+            }
+            (
+                Event::Keyboard(keyboard::Event::KeyPressed {
+                    key_code: keyboard::KeyCode::Tab,
+                    modifiers,
+                }),
+                event::Status::Ignored,
+            ) => Some(if modifiers.shift() {
+                Message::FocusPrevious
+            } else {
+                Message::CompleteName
+            }),
+            // Inputs ignore these keys, so they reach us whatever has focus
+            (
+                Event::Keyboard(keyboard::Event::KeyPressed {
+                    key_code: key_code @ (keyboard::KeyCode::Up | keyboard::KeyCode::Down),
+                    modifiers,
+                }),
+                event::Status::Ignored,
+            ) if modifiers.is_empty() => Some(if key_code == keyboard::KeyCode::Up {
+                Message::RecallPrevious
+            } else {
+                Message::RecallNext
+            }),
+            // Sent even while an input has focus and captures the key
+            (
+                Event::Keyboard(keyboard::Event::KeyPressed {
+                    key_code,
+                    modifiers,
+                }),
+                _,
+            ) => shortcuts::shortcut(key_code, modifiers).map(Message::Shortcut),
+            _ => None,
+        });
+
+        // Check the scheduled exports whatever screen is shown
+        let exports = if self.config.export_rules.is_empty() {
+            Subscription::none()
+        } else {
+            time::every(EXPORT_CHECK_INTERVAL).map(|_| Message::RunScheduledExports)
+        };
+        let bridges = if self.config.folder_bridges.is_empty() {
+            Subscription::none()
+        } else {
+            time::every(BRIDGE_SCAN_INTERVAL).map(|_| Message::ScanBridges)
+        };
+        let exports = Subscription::batch(vec![exports, bridges]);
+
+        // Read the events of the client as they come, whatever screen is
+        // shown, so none is left behind
+        let incoming = subscription::unfold(
+            "incoming",
+            None,
+            |incoming: Option<iroh_lab_core::client::Subscription>| async move {
+                let mut incoming = incoming.unwrap_or_else(|| IrohClient::subscribe(None));
+                match incoming.next().await {
+                    Some(event) => {
+                        let dropped = incoming.dropped();
+                        (Message::Incoming(event, dropped), Some(incoming))
+                    }
+                    // The events of the process outlive the window
+                    None => futures::future::pending().await,
+                }
+            },
+        );
+
+        // Follow the node while its diagnostics are shown
+        if let InputState::Diagnostics { .. } = self.input_state {
+            return Subscription::batch(vec![
+                events,
+                incoming,
+                exports,
+                time::every(DIAGNOSTICS_REFRESH_INTERVAL).map(|_| Message::RefreshDiagnostics),
+            ]);
+        }
+
+        // Refresh the traffic panel while it is shown
+        let traffic = if self.traffic.is_some() {
+            time::every(std::time::Duration::from_millis(200)).map(|_| Message::Tick)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch(vec![events, incoming, exports, traffic])
+    }
+}
+
+impl IrohChat {
+    // Network statistics of the topic shown
+    fn current_traffic(&self) -> Option<TrafficStats> {
+        let topic_hash = self.topic_hash()?;
+        self.client.traffic_stats(&topic_hash)
+    }
+
+    // Shows the guided tour's explanation of `step` around `content`, as long
+    // as the user has not seen it yet
+    fn tour_mark<'a>(
+        &self,
+        step: TourStep,
+        content: impl Into<Element<'a, Message>>,
+    ) -> Element<'a, Message> {
+        match &self.tour {
+            Some(tour) if tour.shows(step) => coach_mark(
+                content,
+                step.hint(),
+                Message::TourNext(step),
+                Message::TourSkip,
+            ),
+            _ => content.into(),
+        }
+    }
+
+    // Who tasks of the current topic can be assigned to: nobody, ourselves
+    // and the members heard of
+    fn assignees(&self) -> Vec<Assignee> {
+        let mut assignees = vec![Assignee {
+            node_id: None,
+            name: "Unassigned".to_string(),
+        }];
+        let own_node_id = self.client.own_node_id();
+        if let (Some(node_id), Some(username)) = (own_node_id, self.get_username()) {
+            assignees.push(Assignee {
+                node_id: Some(node_id),
+                name: format!("{} (you)", username),
+            });
+        }
+        for (member, _) in self.roster.members(Instant::now()) {
+            if Some(member.node_id) == own_node_id {
+                continue;
+            }
+            let name = self
+                .profiles
+                .get(&member.node_id)
+                .map_or(member.author.as_str(), |profile| {
+                    profile.name_or(&member.author)
+                });
+            assignees.push(Assignee {
+                node_id: Some(member.node_id),
+                name: name.to_string(),
+            });
+        }
+        assignees
+    }
+
+    // Whether the message was sent by us, rather than by a peer or the app
+    fn is_own_message(&self, message: &ChatMessage) -> bool {
+        message.author != "System"
+            && message.verified_sender.is_some()
+            && message.verified_sender == self.client.own_node_id()
+    }
+
+    // Applies an edit to the message it targets, if we have it
+    fn apply_edit(&mut self, edit: &MessageEdit, sender: Option<NodeId>) {
+        if self.conversation.get(&edit.target_id).is_some() && !self.conversation.edit(edit, sender)
+        {
+            warn!(target_id = %edit.target_id, "Ignoring edit of message");
+        }
+    }
+
+    // Announces our profile on every topic, and shows it on our own messages,
+    // as gossip does not echo our own announcements
+    fn publish_profile(&mut self) -> Command<Message> {
+        let Some(node_id) = self.client.own_node_id() else {
+            return Command::none();
+        };
+        self.profiles.update(&Profile {
+            topic_hash: self.topic_hash().unwrap_or_default(),
+            node_id,
+            profile: self.config.profile.clone(),
+        });
+        self.show_avatar(node_id, self.config.avatar_image);
+
+        let client = self.client.clone();
+        let (profile, avatar_image) = (self.config.profile.clone(), self.config.avatar_image);
+        Command::perform(
+            async move { client.set_profile(profile, avatar_image).await },
+            Message::ClientUpdated,
+        )
+    }
+
+    // Saves the profile being edited with `avatar_image`, and publishes it
+    fn save_profile(&mut self, avatar_image: Option<BlobHash>) -> Command<Message> {
+        let InputState::EditProfile {
+            username, profile, ..
+        } = &self.input_state
+        else {
+            return Command::none();
+        };
+        self.config.profile = profile.clone();
+        self.config.avatar_image = avatar_image;
+        self.input_state = InputState::MainMenu {
+            username: username.clone(),
+        };
+        self.save_config();
+        self.publish_profile()
+    }
+
+    // Shows the avatar image of `node_id`, which is in the blob store
+    fn show_avatar(&mut self, node_id: NodeId, image: Option<BlobHash>) {
+        let content = image.and_then(|hash| {
+            self.client.blob(&hash).unwrap_or_else(|e| {
+                warn!("Failed to load avatar: {}", e);
+                None
+            })
+        });
+        match content {
+            Some(content) => {
+                self.avatars
+                    .insert(node_id, image::Handle::from_memory(content));
+            }
+            None => {
+                self.avatars.remove(&node_id);
+            }
+        }
+    }
+
+    // Announces our presence on the current topic, and lists ourselves among
+    // its members, as gossip does not echo our own announcements
+    fn announce_presence(&mut self) -> Command<Message> {
+        let (Some(author), Some(node_id)) = (self.get_username(), self.client.own_node_id()) else {
+            return Command::none();
+        };
+
+        if let Some(topic_hash) = self.topic_hash() {
+            if let InputState::ChatRoom { .. } = self.input_state {
+                self.roster.update(
+                    &Presence {
+                        topic_hash,
+                        author: author.clone(),
+                        node_id,
+                        status: self.presence,
+                    },
+                    Instant::now(),
+                );
+            }
+        }
+
+        let client = self.client.clone();
+        let status = self.presence;
+        Command::perform(
+            async move { client.set_presence(author, status).await },
+            Message::ClientUpdated,
+        )
+    }
+
+    // Has the client create an invite to the current topic, to copy
+    fn create_invite(&self) -> Command<Message> {
+        let client = self.client.clone();
+        Command::perform(
+            async move { client.create_invite().await },
+            Message::InviteCreated,
+        )
+    }
+
+    // Deletes the message a delete targets, and remembers the delete for
+    // copies of the message still to arrive
+    fn apply_delete(&mut self, delete: MessageDelete, sender: Option<NodeId>) {
+        // Stop editing a message that no longer exists
+        if self.editing.as_ref() == Some(&delete.target_id) {
+            self.editing = None;
+            if let InputState::ChatRoom { message, .. } = &mut self.input_state {
+                message.clear();
+            }
+        }
+
+        let shown = self.conversation.get(&delete.target_id).is_some();
+        let target_id = delete.target_id.clone();
+        if !self.conversation.delete(delete, sender) && shown {
+            warn!(target_id = %target_id, "Ignoring delete of message");
+        }
+    }
+
+    // Opens the archive of the current topic, if the user enabled it
+    fn open_archive(&mut self) {
+        self.archive = None;
+        let Some(topic_hash) = self.topic_hash() else {
+            return;
+        };
+        if !self.config.archived_topics.contains(&topic_hash) {
+            return;
+        }
+        let Some(dir) = Archive::default_dir() else {
+            self.error = Some("No configuration directory to archive to".to_string());
+            return;
+        };
+        match Archive::open(&Archive::path_in(&dir, &topic_hash)) {
+            Ok(archive) => self.archive = Some(archive),
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    // Runs an export rule of the topic `topic_hash` and records the run,
+    // returning why it failed, if it did
+    fn run_export(&mut self, topic_hash: &str, rule: &ExportRule) -> Option<String> {
+        let Some(dir) = Archive::default_dir() else {
+            return Some("No configuration directory holding archives".to_string());
+        };
+        let held = self.config.held_topics.contains(topic_hash);
+        let run = Run::execute(rule, topic_hash, &dir, Utc::now(), held);
+        let error = run.error.clone();
+        if let Some(error) = &error {
+            warn!(topic_hash = %topic_hash, "Export failed: {}", error);
+        }
+        if run.prune_held {
+            self.audit(topic_hash, AuditEvent::PruneSkipped);
+        }
+        // Continue the new chain of a pruned archive
+        let pruned = run.pruned > 0;
+        self.export_runs.record(topic_hash, run);
+        if pruned && self.topic_hash().as_deref() == Some(topic_hash) {
+            self.open_archive();
+        }
+        if let Some(path) = RunHistory::default_path() {
+            if let Err(e) = self.export_runs.save_to(&path) {
+                warn!("Failed to save the export runs: {}", e);
+            }
+        }
+        error
+    }
+
+    // Appends an event about the topic `topic_hash` to the audit log
+    fn audit(&mut self, topic_hash: &str, event: AuditEvent) {
+        let Some(path) = audit::default_path() else {
+            self.error = Some("No configuration directory for the audit log".to_string());
+            return;
+        };
+        let entry = AuditEntry {
+            at: Utc::now(),
+            topic_hash: topic_hash.to_string(),
+            event,
+        };
+        if let Err(e) = audit::append(&path, &entry) {
+            self.error = Some(e);
+        }
+    }
+
+    // Asks for attention when a message of somebody else arrives while the
+    // window is in the background, as far as the user wants to know
+    fn notify(&self, message: &ChatMessage) -> Command<Message> {
+        if self.presence != PresenceStatus::Away
+            || self.local.is_some()
+            || message.verified_sender == self.client.own_node_id()
+            || self.config.mutes(message)
+            || self
+                .room
+                .as_ref()
+                .is_none_or(|room| room.is_history(message))
+        {
+            return Command::none();
+        }
+        let username = self.get_username().unwrap_or_default();
+        let names = [username.as_str(), self.config.profile.display_name.as_str()];
+        if !self.config.notifications.wants(&message.content, &names) {
+            return Command::none();
+        }
+        window::request_user_attention(Some(window::UserAttention::Informational))
+    }
+
+    // Offers the current username on the welcome screen of the next start
+    fn remember_username(&mut self) {
+        if let Some(username) = self.get_username() {
+            if self.config.username != username {
+                self.config.username = username;
+                self.save_config();
+            }
+        }
+    }
+
+    // Joins or creates the topic given on the command line, once the network
+    // is up and we have a name
+    fn start_launch(&mut self) -> Command<Message> {
+        let InputState::MainMenu { username } = &self.input_state else {
+            return Command::none();
+        };
+        if self.client.own_node_id().is_none() {
+            return Command::none();
+        }
+        let username = username.clone();
+        match self.launch.take() {
+            Some(Launch::Join(ticket)) => {
+                self.input_state = InputState::JoinTopic { username, ticket };
+                self.update(Message::SubmitJoinTopic)
+            }
+            Some(Launch::Create(topic_name)) => {
+                self.input_state = InputState::CreateTopic {
+                    username,
+                    topic_name,
+                };
+                self.update(Message::SubmitCreateTopic)
+            }
+            None => Command::none(),
+        }
+    }
+
+    fn save_config(&mut self) {
+        let saved = match &self.config_path {
+            Some(path) => self.config.save_to(path),
+            None => self.config.save(),
+        };
+        if let Err(e) = saved {
+            self.error = Some(e);
+        }
+    }
+
+    // Records how long a message of somebody else took to arrive
+    fn record_latency(&mut self, message: &ChatMessage) {
+        if self.local.is_some()
+            || message.verified_sender == self.client.own_node_id()
+            || self
+                .room
+                .as_ref()
+                .is_none_or(|room| room.is_history(message))
+        {
+            return;
+        }
+        // Senders with clocks running ahead look faster than light
+        let latency = (Utc::now() - message.timestamp)
+            .to_std()
+            .unwrap_or_default();
+        self.latency.record(latency);
+    }
+
+    // Posts the welcome message of the topic of `presence` if it is the
+    // first of a newcomer
+    fn welcome(&mut self, presence: &Presence) -> Command<Message> {
+        let Some(template) = self.config.welcome_templates.get(&presence.topic_hash) else {
+            return Command::none();
+        };
+        let content = welcome::render(template, &presence.author);
+        if self.local.is_some()
+            || Some(presence.node_id) == self.client.own_node_id()
+            || self.topic_hash().as_ref() != Some(&presence.topic_hash)
+            || !self.welcomed.mark(&presence.topic_hash, presence.node_id)
+        {
+            return Command::none();
+        }
+        let Some(username) = self.get_username() else {
+            return Command::none();
+        };
+        self.save_welcomed();
+        info!(node_id = %presence.node_id, "Welcoming a newcomer");
+
+        let sequence = self.conversation.next_sequence();
+        let client = self.client.clone();
+        Command::perform(
+            async move { client.send_message(username, content, sequence).await },
+            Message::MessageSent,
+        )
+    }
+
+    // Closes what is open on the screen, or leaves it for the one it was
+    // opened from
+    fn back(&mut self) -> Command<Message> {
+        if self.context_menu.take().is_some() || self.switcher.take().is_some() {
+            return Command::none();
+        }
+        match &self.input_state {
+            InputState::ChatRoom { .. } => {
+                if self.confirming_link.is_some() {
+                    self.update(Message::CancelOpenLink)
+                } else if self.delivery_details.is_some() {
+                    self.update(Message::CloseDeliveryDetails)
+                } else if self.pasted_image.is_some() {
+                    self.update(Message::DiscardPastedImage)
+                } else if self.emoji_search.is_some() {
+                    self.update(Message::ToggleEmojiPicker)
+                } else if self.event_form.is_some() {
+                    self.update(Message::ToggleEventForm)
+                } else if self.message_search.is_some() {
+                    self.update(Message::CloseMessageSearch)
+                } else if self.editing.is_some() {
+                    self.update(Message::CancelEdit)
+                } else if self.confirming_send {
+                    self.update(Message::CancelSend)
+                } else if self.show_tasks {
+                    self.update(Message::ToggleTasks)
+                } else {
+                    self.update(Message::BackToMenu)
+                }
+            }
+            InputState::Diagnostics {
+                peer_addr: Some(_), ..
+            } => self.update(Message::CancelAddPeer),
+            InputState::CreateTopic { .. }
+            | InputState::JoinTopic { .. }
+            | InputState::EditProfile { .. }
+            | InputState::Diagnostics { .. } => self.update(Message::BackToMenu),
+            InputState::Settings { .. } => self.update(Message::LeaveSettings),
+            InputState::TopicSettings { .. } => self.update(Message::LeaveTopicSettings),
+            InputState::Welcome { .. }
+            | InputState::MainMenu { .. }
+            | InputState::TopicCreated { .. } => Command::none(),
+        }
+    }
+
+    // The topics the switcher offers for what is typed into it, by name with
+    // their unread messages
+    fn switcher_topics(&self) -> Vec<(String, usize)> {
+        let query = self.switcher.as_deref().unwrap_or_default().to_lowercase();
+        let mut topics: Vec<_> = self
+            .client
+            .session()
+            .subscribed_topics
+            .iter()
+            .filter(|(topic_name, _)| topic_name.to_lowercase().contains(query.trim()))
+            .map(|(topic_name, topic_hash)| (topic_name.clone(), self.unread.get(topic_hash)))
+            .collect();
+        topics.sort();
+        topics
+    }
+
+    // Adds a calendar event announced by `organizer`, saving the calendar
+    // if it is new
+    fn add_event(&mut self, event: CalendarEvent, organizer: NodeId) {
+        let current = self.topic_hash().as_ref() == Some(&event.topic_hash);
+        if self.calendar.add(event, organizer) {
+            self.save_calendar();
+            if current {
+                self.write_calendar_feed();
+            }
+        }
+    }
+
+    // Writes the upcoming events of the open topic to its calendar feed, if
+    // it has one. Events are only trusted with the messages announcing them,
+    // so those of the messages shown are written.
+    fn write_calendar_feed(&mut self) {
+        let (Some(topic_hash), Some(topic_name)) = (
+            self.topic_hash(),
+            self.room.as_ref().map(|room| room.name().to_string()),
+        ) else {
+            return;
+        };
+        let Some(path) = self.config.calendar_feeds.get(&topic_hash) else {
+            return;
+        };
+        let now = Utc::now();
+        let feed = calendar::to_ics_feed(
+            &topic_name,
+            self.calendar.upcoming(self.conversation.messages(), now),
+            now,
+        );
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, feed));
+        if let Err(e) = written {
+            self.error = Some(format!("Failed to write {}: {}", path.display(), e));
+        }
+    }
+
+    // Records an answer to a calendar event, saving the calendar if it
+    // changed
+    fn respond(&mut self, rsvp: &Rsvp, sender: NodeId) {
+        if self.calendar.respond(rsvp, sender) {
+            self.save_calendar();
+        }
+    }
+
+    fn save_bridges(&mut self) {
+        if let Some(path) = Bridges::default_path() {
+            if let Err(e) = self.bridges.save_to(&path) {
+                self.error = Some(e);
+            }
+        }
+    }
+
+    fn save_calendar(&mut self) {
+        if let Some(path) = Calendar::default_path() {
+            if let Err(e) = self.calendar.save_to(&path) {
+                self.error = Some(e);
+            }
+        }
+    }
+
+    // Runs a command typed into the composer, clearing it unless the command
+    // failed
+    fn run_command(&mut self, command: SlashCommand) -> Command<Message> {
+        let result = match command {
+            SlashCommand::Nick(name) => {
+                if let InputState::ChatRoom { username, .. } = &mut self.input_state {
+                    *username = name;
+                }
+                self.remember_username();
+                Ok(self.announce_presence())
+            }
+            SlashCommand::Leave => return self.update(Message::BackToMenu),
+            SlashCommand::Clear => {
+                // Their ids are kept, so they are not shown again
+                self.conversation.clear();
+                self.unseen = 0;
+                Ok(Command::none())
+            }
+            SlashCommand::Invite => Ok(self.create_invite()),
+            SlashCommand::Mute(name) => self.set_muted(&name, true),
+            SlashCommand::Unmute(name) => self.set_muted(&name, false),
+            // Sent as messages
+            SlashCommand::Me(_) | SlashCommand::Say(_) => Ok(Command::none()),
+        };
+        match result {
+            Ok(command) => {
+                self.composer_error = None;
+                if let InputState::ChatRoom { message, .. } = &mut self.input_state {
+                    message.clear();
+                }
+                command
+            }
+            Err(e) => {
+                self.composer_error = Some(e);
+                Command::none()
+            }
+        }
+    }
+
+    // Hides or shows again the messages of the member going by `name`, or
+    // of whoever sends under that name if no member here does
+    fn set_muted(&mut self, name: &str, muted: bool) -> Result<Command<Message>, String> {
+        if muted_name(name) == "system" {
+            return Err("System notices cannot be muted".to_string());
+        }
+        match (self.member_named(name), muted) {
+            (Ok(node_id), true) => {
+                self.config.muted_members.insert(node_id);
+            }
+            (Err(_), true) => {
+                self.config.mute_author(name);
+            }
+            (node_id, false) => {
+                let unmuted =
+                    node_id.is_ok_and(|node_id| self.config.muted_members.remove(&node_id));
+                if !self.config.unmute_author(name) && !unmuted {
+                    return Err(format!("{} is not muted", name.trim()));
+                }
+            }
+        }
+        self.save_config();
+        Ok(Command::none())
+    }
+
+    // Has the client filter what arrives from now on as configured, saving
+    // the configuration unless a pattern is invalid
+    fn apply_filters(&mut self) -> Result<Command<Message>, String> {
+        let filters = Filters::from_config(&self.config)?;
+        self.save_config();
+        let client = self.client.clone();
+        Ok(Command::perform(
+            async move { client.set_filters(filters).await },
+            Message::ClientUpdated,
+        ))
+    }
+
+    // Saves and publishes the changed allowlist of a topic, unless it is
+    // open to everyone
+    fn change_allowlist(
+        &mut self,
+        topic_hash: String,
+        members: Option<BTreeSet<NodeId>>,
+    ) -> Command<Message> {
+        let Some(members) = members else {
+            return Command::none();
+        };
+        self.save_config();
+        self.publish_allowlist(topic_hash, Some(members))
+    }
+
+    // Publishes the saved allowlist of a topic we created, unless the
+    // client already applies it, as it forgets it when restarted
+    fn restore_allowlist(&self, topic_hash: &str) -> Command<Message> {
+        let Some(members) = self.config.allowlists.get(topic_hash) else {
+            return Command::none();
+        };
+        if self.client.topic_creator(topic_hash) != self.client.own_node_id()
+            || self.client.allowlist(topic_hash).as_ref() == Some(members)
+        {
+            return Command::none();
+        }
+        self.publish_allowlist(topic_hash.to_string(), Some(members.clone()))
+    }
+
+    // Has the client restrict `topic_hash` to `members`, or open it,
+    // replacing the list we published last
+    fn publish_allowlist(
+        &self,
+        topic_hash: String,
+        members: Option<BTreeSet<NodeId>>,
+    ) -> Command<Message> {
+        let after = self
+            .config
+            .allowlist_lamports
+            .get(&topic_hash)
+            .copied()
+            .unwrap_or_default();
+        let client = self.client.clone();
+        Command::perform(
+            async move { client.set_allowlist(topic_hash, members, after).await },
+            Message::AllowlistPublished,
+        )
+    }
+
+    // What the context menu of a member in the member list offers
+    fn member_menu(&self, member: &Member) -> Vec<MenuItem<Message>> {
+        let name = self.member_name(member.node_id);
+        let mut items = vec![
+            MenuItem::new("Copy name", Message::CopyToClipboard(name.clone())),
+            MenuItem::new(
+                "Copy node id",
+                Message::CopyToClipboard(member.node_id.to_string()),
+            ),
+        ];
+        if self.local.is_some() || Some(member.node_id) == self.client.own_node_id() {
+            return items;
+        }
+        items.push(MenuItem::new(
+            "Send history",
+            Message::SendHistory(member.node_id),
+        ));
+        let muted =
+            self.config.muted_members.contains(&member.node_id) || self.config.mutes_author(&name);
+        items.push(MenuItem::new(
+            if muted {
+                "Unmute member"
+            } else {
+                "Mute member"
+            },
+            Message::ToggleMute(Some(member.node_id), name),
+        ));
+        items
+    }
+
+    // The member of the topic going by `name`, ignoring case
+    fn member_named(&self, name: &str) -> Result<NodeId, String> {
+        self.roster
+            .members(Instant::now())
+            .into_iter()
+            .map(|(member, _)| member.node_id)
+            .find(|node_id| muted_name(&self.member_name(*node_id)) == muted_name(name))
+            .ok_or_else(|| format!("Nobody here goes by {}", name.trim()))
+    }
+
+    // The commands matching the one being typed into the composer, with why
+    // it failed if it did
+    fn command_help(&self, message: &str) -> Option<Element<'_, Message>> {
+        let matching = commands::help(message);
+        if matching.is_empty() && self.composer_error.is_none() {
+            return None;
+        }
+        let mut help = column![].spacing(2);
+        if let Some(error) = &self.composer_error {
+            help = help.push(text(error).size(14));
+        }
+        for (usage, description) in matching {
+            help = help.push(
+                row![
+                    text(usage).size(14).width(Length::FillPortion(1)),
+                    text(description).size(14).width(Length::FillPortion(2)),
+                ]
+                .spacing(10),
+            );
+        }
+        Some(help.into())
+    }
+
+    // Whether the composer is the only input of the chat room that may have
+    // the focus, as iced does not tell which one has it
+    fn composer_has_focus(&self) -> bool {
+        matches!(self.input_state, InputState::ChatRoom { .. })
+            && self.emoji_search.is_none()
+            && self.event_form.is_none()
+            && self.message_search.is_none()
+            && self.switcher.is_none()
+    }
+
+    // How our message `message_id` on the current topic was delivered,
+    // naming the members who did not confirm it yet
+    fn delivery_details(&self, message_id: &str) -> Element<'_, Message> {
+        let report = self
+            .topic_hash()
+            .as_ref()
+            .and_then(|topic_hash| self.client.delivery_report(topic_hash, message_id));
+        let waiting: Vec<_> = match &report {
+            Some(report) => self
+                .roster
+                .members(Instant::now())
+                .into_iter()
+                .map(|(member, _)| member.node_id)
+                .filter(|node_id| {
+                    Some(*node_id) != self.client.own_node_id()
+                        && !report.acks.contains_key(node_id)
+                })
+                .map(|node_id| self.member_name(node_id))
+                .collect(),
+            None => Vec::new(),
+        };
+        delivery_details(
+            report.as_ref(),
+            |node_id| self.member_name(node_id),
+            &waiting,
+            Message::CloseDeliveryDetails,
+        )
+    }
+
+    // The names of the other members of the topic, to complete in the
+    // composer
+    fn member_names(&self) -> Vec<String> {
+        self.roster
+            .members(Instant::now())
+            .into_iter()
+            .filter(|(member, _)| Some(member.node_id) != self.client.own_node_id())
+            .map(|(member, _)| self.member_name(member.node_id))
+            .collect()
+    }
+
+    // The name of `node_id` as known from its profile or presence
+    fn member_name(&self, node_id: NodeId) -> String {
+        if Some(node_id) == self.client.own_node_id() {
+            if let Some(username) = self.get_username() {
+                return username;
+            }
+        }
+        let author = self
+            .roster
+            .members(Instant::now())
+            .into_iter()
+            .find(|(member, _)| member.node_id == node_id)
+            .map(|(member, _)| member.author.clone());
+        match (self.profiles.get(&node_id), author) {
+            (Some(profile), Some(author)) => profile.name_or(&author).to_string(),
+            (Some(profile), None) => profile.name_or(&node_id.fmt_short()).to_string(),
+            (None, Some(author)) => author,
+            (None, None) => node_id.fmt_short(),
+        }
+    }
+
+    // Applies a task update signed by `sender`, saving the tasks if it
+    // changed one
+    fn apply_task(&mut self, update: &TaskUpdate, sender: NodeId) {
+        if !self.tasks.apply(update, sender) {
+            return;
+        }
+        if let Some(path) = TaskList::default_path() {
+            if let Err(e) = self.tasks.save_to(&path) {
+                self.error = Some(e);
+            }
+        }
+    }
+
+    fn save_welcomed(&mut self) {
+        if let Some(path) = Welcomed::default_path() {
+            if let Err(e) = self.welcomed.save_to(&path) {
+                self.error = Some(e);
+            }
+        }
+    }
+
+    // Records a sign of life of a peer, saving the times now and then
+    fn record_seen(&mut self, topic_hash: &str, node_id: NodeId, author: &str, at: DateTime<Utc>) {
+        self.last_seen.record_in(topic_hash, node_id, author, at);
+        if self.last_seen_saved.elapsed() >= LAST_SEEN_SAVE_INTERVAL {
+            self.save_last_seen();
+        }
+    }
+
+    fn save_last_seen(&mut self) {
+        self.last_seen_saved = Instant::now();
+        if let Some(path) = LastSeen::default_path() {
+            if let Err(e) = self.last_seen.save_to(&path) {
+                warn!("Failed to save when peers were last seen: {}", e);
+            }
+        }
+    }
+
+    // Ends the guided tour for good
+    fn complete_tour(&mut self) {
+        self.tour = None;
+        self.config.tour_completed = true;
+        self.save_config();
+    }
+
+    // Adds a message to the chat view, keeping the view in Lamport order.
+    // Returns whether it was new.
+    fn insert_message(&mut self, message: ChatMessage) -> bool {
+        let id = message.id.clone();
+        if !self.conversation.insert(message) {
+            return false;
+        }
+        // Archived as shown, deleted if the delete overtook it
+        let Some(message) = self.conversation.get(&id) else {
+            return true;
+        };
+        if let Some(archive) = self.archive.as_mut() {
+            if let Err(e) = archive.append(message) {
+                self.error = Some(e);
+            }
+        }
+        if message.author != "System" {
+            if let Some(heatmap) = self.heatmaps.get_mut(&message.topic_hash) {
+                heatmap.add(message.timestamp, &Local);
+            }
+        }
+        true
+    }
+
+    // Marks the messages of the current topic as read, as its room is shown
+    fn mark_read(&mut self) {
+        if let Some(topic_hash) = self.topic_hash() {
+            self.unread.clear(&topic_hash);
+        }
+    }
+
+    // Scrolls the message list to the latest message
+    fn jump_to_latest(&mut self) -> Command<Message> {
+        self.at_latest = true;
+        self.unseen = 0;
+        // Render the end of the list until a scroll event tells where it is
+        self.message_scroll = f32::MAX;
+        scrollable::snap_to(message_list(), scrollable::RelativeOffset::END)
+    }
+
+    // Keeps showing the latest message if the list showed it before a new
+    // one arrived, and counts the new one as unseen otherwise
+    fn follow_new_message(&mut self) -> Command<Message> {
+        if !matches!(self.input_state, InputState::ChatRoom { .. }) {
+            return Command::none();
+        }
+        if self.at_latest {
+            self.jump_to_latest()
+        } else {
+            self.unseen += 1;
+            Command::none()
+        }
+    }
+
+    // Hash of the topic shown, local ones included
+    fn topic_hash(&self) -> Option<String> {
+        match &self.local {
+            Some(LocalTopic::Demo(topic_hash)) => Some(topic_hash.clone()),
+            Some(LocalTopic::Notes(_)) => Some(NOTES_TOPIC_HASH.to_string()),
+            None => self.client.session().topic_hash,
+        }
+    }
+
+    fn get_username(&self) -> Option<String> {
+        match &self.input_state {
+            InputState::Welcome { username } => Some(username.clone()),
+            InputState::MainMenu { username } => Some(username.clone()),
+            InputState::CreateTopic { username, .. } => Some(username.clone()),
+            InputState::JoinTopic { username, .. } => Some(username.clone()),
+            InputState::TopicCreated { username, .. } => Some(username.clone()),
+            InputState::ChatRoom { username, .. } => Some(username.clone()),
+            InputState::EditProfile { username, .. } => Some(username.clone()),
+            InputState::Settings { username, .. } => Some(username.clone()),
+            InputState::Diagnostics { username, .. } => Some(username.clone()),
+            InputState::TopicSettings { username, .. } => Some(username.clone()),
+        }
+    }
+}
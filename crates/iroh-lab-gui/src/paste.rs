@@ -0,0 +1,100 @@
+//! Images pasted into the composer.
+//!
+//! Pasting while the clipboard holds an image instead of text encodes the
+//! image as a PNG file, which is posted to the topic as an
+//! [attachment](iroh_lab_core::client::Attachment) once the user confirms it.
+//! Images too large for a [blob](iroh_lab_core::blobs) are scaled down until they
+//! fit.
+use chrono::{DateTime, Utc};
+use iroh_lab_core::bridge::MAX_FILE_SIZE;
+
+/// An image pasted into the composer, encoded as PNG.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PastedImage {
+    /// File name the image is posted as.
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub png: Vec<u8>,
+}
+
+/// Reads the image on the clipboard, or None if it holds something else.
+pub fn read_clipboard(now: DateTime<Utc>) -> Result<Option<PastedImage>, String> {
+    let mut clipboard =
+        arboard::Clipboard::new().map_err(|e| format!("Failed to open the clipboard: {}", e))?;
+    match clipboard.get_image() {
+        Ok(image) => {
+            from_rgba(image.width as u32, image.height as u32, &image.bytes, now).map(Some)
+        }
+        Err(arboard::Error::ContentNotAvailable) => Ok(None),
+        Err(e) => Err(format!("Failed to read the clipboard: {}", e)),
+    }
+}
+
+/// Encodes an image of `width` by `height` pixels, given as RGBA bytes, as a
+/// PNG file pasted at `now`. The image is halved until the file fits into
+/// [`MAX_FILE_SIZE`].
+pub fn from_rgba(
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+    now: DateTime<Utc>,
+) -> Result<PastedImage, String> {
+    if width == 0 || height == 0 || rgba.len() != width as usize * height as usize * 4 {
+        return Err("The pasted image is empty or damaged".to_string());
+    }
+    let (mut width, mut height, mut rgba) = (width, height, rgba.to_vec());
+    loop {
+        let png = encode_png(width, height, &rgba)?;
+        if png.len() <= MAX_FILE_SIZE {
+            return Ok(PastedImage {
+                name: format!("pasted-{}.png", now.format("%Y%m%d-%H%M%S")),
+                width,
+                height,
+                png,
+            });
+        }
+        if width == 1 && height == 1 {
+            return Err("The pasted image is too large to send".to_string());
+        }
+        (width, height, rgba) = halve(width, height, &rgba);
+    }
+}
+
+// Encodes RGBA pixels as a PNG file
+fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>, String> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(rgba))
+        .map_err(|e| format!("Failed to encode the pasted image: {}", e))?;
+    Ok(png)
+}
+
+// Scales RGBA pixels down to half their size, averaging each square of up to
+// four pixels
+fn halve(width: u32, height: u32, rgba: &[u8]) -> (u32, u32, Vec<u8>) {
+    let (half_width, half_height) = (width.div_ceil(2), height.div_ceil(2));
+    let mut halved = Vec::with_capacity(half_width as usize * half_height as usize * 4);
+    for y in 0..half_height {
+        for x in 0..half_width {
+            let pixels: Vec<usize> = [(0, 0), (1, 0), (0, 1), (1, 1)]
+                .into_iter()
+                .map(|(dx, dy)| (x * 2 + dx, y * 2 + dy))
+                .filter(|&(px, py)| px < width && py < height)
+                .map(|(px, py)| (py as usize * width as usize + px as usize) * 4)
+                .collect();
+            for channel in 0..4 {
+                let sum: usize = pixels
+                    .iter()
+                    .map(|pixel| rgba[pixel + channel] as usize)
+                    .sum();
+                halved.push((sum / pixels.len()) as u8);
+            }
+        }
+    }
+    (half_width, half_height, halved)
+}
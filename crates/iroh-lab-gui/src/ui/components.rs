@@ -0,0 +1,967 @@
+//! Layout components shared by the screens of the chat application.
+//!
+//! Screens are assembled from these building blocks so that spacing, sizes and
+//! button styles stay consistent between them.
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use iced::widget::{
+    button, checkbox, column, container, horizontal_rule, image, pick_list, row, scrollable, text,
+    text_input, tooltip, Button, Text,
+};
+use iced::{alignment, font, theme, Alignment, Color, Element, Font, Length};
+use iroh::NodeId;
+use iroh_lab_core::automation::WEEKDAYS;
+use iroh_lab_core::calendar::RsvpResponse;
+use iroh_lab_core::client::{CalendarEvent, ChatMessage};
+use iroh_lab_core::delivery::{DeliveryReport, DeliveryState};
+use iroh_lab_core::diagnostics::Connectivity;
+use iroh_lab_core::heatmap::{ActivityHeatmap, LEVELS};
+use iroh_lab_core::last_seen::{self, LastSeen};
+use iroh_lab_core::liveness::TopicActivity;
+use iroh_lab_core::markdown::{self, BlockKind, Link, Span};
+use iroh_lab_core::metrics::TrafficStats;
+use iroh_lab_core::ping;
+use iroh_lab_core::profile::{Profiles, UserProfile};
+use iroh_lab_core::roster::{Member, MemberState};
+use iroh_lab_core::tasks::Task;
+use std::path::Path;
+use std::time::Duration;
+
+use super::flow::Flow;
+use super::layout::LayoutMode;
+use super::widgets::{context_menu, MenuItem};
+
+// Text size of screen headings
+const HEADING_SIZE: u16 = 24;
+// Text size of the labels above inputs
+const LABEL_SIZE: u16 = 14;
+// Padding inside buttons and inputs
+const CONTROL_PADDING: u16 = 10;
+// Characters shading the cells of the activity heatmap, from no messages on
+const HEATMAP_SHADES: [char; LEVELS] = ['·', '░', '▒', '▓', '█'];
+// Colors of the message signature badges
+const VERIFIED_COLOR: Color = Color::from_rgb(0.2, 0.6, 0.3);
+const UNVERIFIED_COLOR: Color = Color::from_rgb(0.6, 0.6, 0.6);
+// Color of the marker on edited messages
+const EDITED_COLOR: Color = Color::from_rgb(0.6, 0.6, 0.6);
+// Colors of the topic activity states
+const ACTIVE_COLOR: Color = Color::from_rgb(0.2, 0.6, 0.3);
+const QUIET_COLOR: Color = Color::from_rgb(0.6, 0.6, 0.6);
+const PARTITIONED_COLOR: Color = Color::from_rgb(0.8, 0.4, 0.1);
+// Colors of the connectivity states
+const CONNECTED_COLOR: Color = Color::from_rgb(0.2, 0.6, 0.3);
+const RELAY_ONLY_COLOR: Color = Color::from_rgb(0.8, 0.6, 0.1);
+const RECONNECTING_COLOR: Color = Color::from_rgb(0.8, 0.4, 0.1);
+const DISCONNECTED_COLOR: Color = Color::from_rgb(0.8, 0.2, 0.2);
+// Colors of the member states
+const ONLINE_COLOR: Color = Color::from_rgb(0.2, 0.6, 0.3);
+const AWAY_COLOR: Color = Color::from_rgb(0.8, 0.6, 0.1);
+const OFFLINE_COLOR: Color = Color::from_rgb(0.6, 0.6, 0.6);
+// Colors of the latency of members, normal and high
+const LATENCY_COLOR: Color = Color::from_rgb(0.6, 0.6, 0.6);
+const HIGH_LATENCY_COLOR: Color = Color::from_rgb(0.8, 0.4, 0.1);
+// Color of the marker on messages that never reached anybody
+const FAILED_COLOR: Color = Color::from_rgb(0.8, 0.2, 0.2);
+// Color of italic text, which the fonts cannot slant
+const EMPHASIS_COLOR: Color = Color::from_rgb(0.35, 0.45, 0.75);
+// Color of the bar in front of quotes
+const QUOTE_COLOR: Color = Color::from_rgb(0.6, 0.6, 0.6);
+// Space between the words of rendered message content
+const WORD_GAP: f32 = 4.0;
+// Side length of the avatar images in front of message authors
+const AVATAR_IMAGE_SIZE: f32 = 20.0;
+// Width of the member list next to the chat
+const MEMBER_LIST_WIDTH: f32 = 180.0;
+// Width of the assignee pick list of each task
+const ASSIGNEE_WIDTH: f32 = 160.0;
+// Characters of a message shown in the task list
+const TASK_EXCERPT_CHARS: usize = 120;
+
+/// Fills the window and centers `content` in it.
+pub fn screen_container<'a, Message: 'a>(
+    content: impl Into<Element<'a, Message>>,
+) -> Element<'a, Message> {
+    container(content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .into()
+}
+
+/// A centered screen heading.
+pub fn heading<'a>(title: impl ToString) -> Text<'a> {
+    text(title)
+        .size(HEADING_SIZE)
+        .width(Length::Fill)
+        .horizontal_alignment(alignment::Horizontal::Center)
+}
+
+/// A column of `children` below a heading, at most `max_width` wide.
+pub fn titled_card<'a, Message: 'a>(
+    title: impl ToString,
+    children: Vec<Element<'a, Message>>,
+    max_width: u16,
+    layout: LayoutMode,
+) -> Element<'a, Message> {
+    children
+        .into_iter()
+        .fold(column![heading(title)], |card, child| card.push(child))
+        .spacing(layout.spacing())
+        .padding(layout.padding())
+        .width(Length::Fill)
+        .max_width(max_width)
+        .align_items(Alignment::Center)
+        .into()
+}
+
+/// A text input with a label above it, focusable through `id`.
+pub fn labeled_input<'a, Message: Clone + 'a>(
+    id: text_input::Id,
+    label: &str,
+    placeholder: &str,
+    value: &str,
+    on_input: impl Fn(String) -> Message + 'a,
+) -> Element<'a, Message> {
+    column![
+        text(label).size(LABEL_SIZE),
+        text_input(placeholder, value)
+            .id(id)
+            .on_input(on_input)
+            .padding(CONTROL_PADDING),
+    ]
+    .spacing(5)
+    .width(Length::Fill)
+    .into()
+}
+
+/// A choice between `options` with a label above it.
+pub fn labeled_pick_list<'a, T, Message: Clone + 'a>(
+    label: &str,
+    options: &'a [T],
+    selected: T,
+    on_selected: impl Fn(T) -> Message + 'a,
+) -> Element<'a, Message>
+where
+    T: ToString + Eq + Clone + 'static,
+{
+    column![
+        text(label).size(LABEL_SIZE),
+        pick_list(options, Some(selected), on_selected)
+            .padding(CONTROL_PADDING)
+            .width(Length::Fill),
+    ]
+    .spacing(5)
+    .width(Length::Fill)
+    .into()
+}
+
+/// A labeled list of key bindings, each a key with what it does.
+pub fn key_bindings<'a, Message: 'a>(
+    label: &str,
+    bindings: &[(&'a str, &'a str)],
+) -> Element<'a, Message> {
+    bindings
+        .iter()
+        .fold(
+            column![text(label).size(LABEL_SIZE)],
+            |list, (key, action)| {
+                list.push(
+                    row![
+                        text(*key).size(LABEL_SIZE).width(Length::FillPortion(1)),
+                        text(*action).size(LABEL_SIZE).width(Length::FillPortion(2)),
+                    ]
+                    .spacing(10),
+                )
+            },
+        )
+        .spacing(5)
+        .width(Length::Fill)
+        .into()
+}
+
+/// A button for the main action of a screen.
+pub fn primary_button<'a, Message: Clone + 'a>(
+    label: &str,
+    on_press: Message,
+) -> Button<'a, Message> {
+    button(text(label))
+        .on_press(on_press)
+        .style(theme::Button::Primary)
+        .padding(CONTROL_PADDING)
+}
+
+/// A button for secondary actions such as navigating back.
+pub fn secondary_button<'a, Message: Clone + 'a>(
+    label: &str,
+    on_press: Message,
+) -> Button<'a, Message> {
+    button(text(label))
+        .on_press(on_press)
+        .style(theme::Button::Secondary)
+        .padding(CONTROL_PADDING)
+}
+
+/// A row of buttons filling the available width.
+pub fn button_row<'a, Message: Clone + 'a>(
+    buttons: Vec<Button<'a, Message>>,
+) -> Element<'a, Message> {
+    buttons
+        .into_iter()
+        .fold(row![], |buttons, button| buttons.push(button))
+        .spacing(10)
+        .width(Length::Fill)
+        .into()
+}
+
+/// A centered line telling whether other members are seen on the topic.
+pub fn activity_label<'a>(activity: TopicActivity) -> Text<'a> {
+    let (label, color) = match activity {
+        TopicActivity::Active => ("● Active", ACTIVE_COLOR),
+        TopicActivity::Quiet => ("○ Quiet — nobody else seen yet", QUIET_COLOR),
+        TopicActivity::Partitioned => {
+            ("○ No peers seen recently, reconnecting…", PARTITIONED_COLOR)
+        }
+    };
+
+    text(label)
+        .size(12)
+        .style(color)
+        .width(Length::Fill)
+        .horizontal_alignment(alignment::Horizontal::Center)
+}
+
+/// A chip telling how well the node is connected.
+pub fn connectivity_chip<'a, Message: 'a>(connectivity: Connectivity) -> Element<'a, Message> {
+    let (symbol, color) = match connectivity {
+        Connectivity::Connected => ("●", CONNECTED_COLOR),
+        Connectivity::RelayOnly => ("◐", RELAY_ONLY_COLOR),
+        Connectivity::Reconnecting => ("◌", RECONNECTING_COLOR),
+        Connectivity::Offline => ("○", DISCONNECTED_COLOR),
+    };
+    container(
+        text(format!("{} {}", symbol, connectivity))
+            .size(12)
+            .style(color),
+    )
+    .style(theme::Container::Box)
+    .padding([2, 8])
+    .into()
+}
+
+/// The network statistics of a topic: bytes sent and received, gossip
+/// messages per minute, connected peers and how much of the traffic goes
+/// through relays.
+pub fn traffic_panel<'a, Message: 'a>(stats: &TrafficStats) -> Element<'a, Message> {
+    let share = match stats.relayed_share() {
+        Some(share) => format!(
+            "{:.0}% through relays, {:.0}% direct",
+            share * 100.0,
+            (1.0 - share) * 100.0
+        ),
+        None => "No traffic with connected peers yet".to_string(),
+    };
+    container(
+        column![
+            text("Network statistics").size(16),
+            text(format!(
+                "Sent {}, received {}",
+                byte_size(stats.bytes_sent),
+                byte_size(stats.bytes_received)
+            ))
+            .size(14),
+            text(format!(
+                "{} gossip message(s) in the last minute",
+                stats.messages_per_minute
+            ))
+            .size(14),
+            text(format!("{} connected peer(s)", stats.peers)).size(14),
+            text(share).size(14),
+        ]
+        .spacing(4),
+    )
+    .style(theme::Container::Box)
+    .padding(CONTROL_PADDING)
+    .width(Length::Fill)
+    .into()
+}
+
+// A number of bytes in the largest unit keeping it above 1
+fn byte_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+/// A line naming a topic that was left with `unread` messages arriving
+/// since, counted in a badge.
+pub fn unread_badge<'a, Message: 'a>(topic_name: &str, unread: usize) -> Element<'a, Message> {
+    row![
+        text(format!("'{}'", topic_name)).width(Length::Fill),
+        container(text(unread).size(12))
+            .style(theme::Container::Box)
+            .padding([2, 8]),
+    ]
+    .spacing(10)
+    .align_items(Alignment::Center)
+    .into()
+}
+
+/// Messages by day of the week and hour of the day, shaded from none to the
+/// busiest hour.
+pub fn activity_heatmap<'a, Message: 'a>(heatmap: &ActivityHeatmap) -> Element<'a, Message> {
+    let mut rows = column![text(format!("    {:<6}{:<6}{:<6}{}", 0, 6, 12, 18))
+        .size(12)
+        .font(Font::MONOSPACE)]
+    .spacing(2);
+    for weekday in WEEKDAYS {
+        let cells: String = (0..24)
+            .map(|hour| HEATMAP_SHADES[heatmap.level(weekday, hour)])
+            .collect();
+        rows = rows.push(
+            text(format!("{:<4}{}", weekday, cells))
+                .size(12)
+                .font(Font::MONOSPACE),
+        );
+    }
+    rows.into()
+}
+
+/// A full-width notice with a button offering a way out of the situation.
+pub fn banner<'a, Message: Clone + 'a>(
+    notice: &str,
+    action: Button<'a, Message>,
+) -> Element<'a, Message> {
+    container(
+        row![text(notice).width(Length::Fill), action.padding(5)]
+            .spacing(10)
+            .align_items(Alignment::Center),
+    )
+    .style(theme::Container::Box)
+    .padding(CONTROL_PADDING)
+    .width(Length::Fill)
+    .into()
+}
+
+/// The members of a topic, each marked as online, away or offline and with
+/// when they were `last_seen` before `now`. Offline members show it below
+/// their name, the others in their tooltip. Members are named as their
+/// `profiles` tell, and the tooltip shows their status line. Members with a
+/// round-trip latency measured show it, highlighted when high. Below the
+/// members is a button saving them all as contacts, if `on_save_contacts`
+/// is given. Right-clicking a member emits `on_open_menu` with its node, and
+/// the member whose menu is `open_menu` shows the `menu` items for it.
+#[allow(clippy::too_many_arguments)]
+pub fn member_list<'a, Message: Clone + 'a>(
+    members: &[(&Member, MemberState, Option<Duration>)],
+    profiles: &Profiles,
+    last_seen: &LastSeen,
+    now: DateTime<Utc>,
+    on_save_contacts: Option<Message>,
+    open_menu: Option<NodeId>,
+    menu: impl Fn(&Member) -> Vec<MenuItem<Message>>,
+    on_open_menu: impl Fn(NodeId) -> Message,
+    on_dismiss_menu: Message,
+) -> Element<'a, Message> {
+    let list = members.iter().fold(
+        column![text("Members").size(LABEL_SIZE)].spacing(8),
+        |list, (member, state, latency)| {
+            let (dot, color, label) = match state {
+                MemberState::Online => ("●", ONLINE_COLOR, "Online"),
+                MemberState::Away => ("◐", AWAY_COLOR, "Away"),
+                MemberState::Offline => ("○", OFFLINE_COLOR, "Offline"),
+            };
+            let seen = last_seen
+                .get(&member.node_id)
+                .map(|seen| format!("last seen {}", last_seen::ago(seen.at, now)));
+
+            let profile = profiles.get(&member.node_id);
+            let author = profile.map_or(member.author.as_str(), |profile| {
+                profile.name_or(&member.author)
+            });
+
+            let mut name = column![text(author.to_string()).size(14)];
+            let mut hint = format!("{}, node {}", label, member.node_id.fmt_short());
+            if let Some(status_line) = profile
+                .map(|profile| profile.status_line.trim())
+                .filter(|status_line| !status_line.is_empty())
+            {
+                hint = format!("{}\n{}", status_line, hint);
+            }
+            if let Some(seen) = seen {
+                if *state == MemberState::Offline {
+                    name = name.push(text(&seen).size(11).style(OFFLINE_COLOR));
+                }
+                hint = format!("{}\n{}", hint, seen);
+            }
+            if let Some(latency) = latency {
+                let color = if ping::is_high(*latency) {
+                    HIGH_LATENCY_COLOR
+                } else {
+                    LATENCY_COLOR
+                };
+                let round_trip = format!("{} ms", latency.as_millis());
+                hint = format!("{}\nround trip {}", hint, round_trip);
+                name = name.push(text(round_trip).size(11).style(color));
+            }
+
+            list.push(context_menu(
+                tooltip(
+                    row![text(dot).size(12).style(color), name]
+                        .spacing(6)
+                        .align_items(Alignment::Center),
+                    hint,
+                    tooltip::Position::Left,
+                )
+                .style(theme::Container::Box),
+                menu(member),
+                open_menu == Some(member.node_id),
+                on_open_menu(member.node_id),
+                on_dismiss_menu.clone(),
+            ))
+        },
+    );
+
+    let list = match on_save_contacts {
+        Some(on_press) => list.push(
+            secondary_button("Save all as contacts", on_press)
+                .padding(5)
+                .width(Length::Fill),
+        ),
+        None => list,
+    };
+
+    container(scrollable(list))
+        .style(theme::Container::Box)
+        .padding(CONTROL_PADDING)
+        .width(Length::Fixed(MEMBER_LIST_WIDTH))
+        .height(Length::Fill)
+        .into()
+}
+
+/// Somebody a task can be assigned to, as offered by [`task_list`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assignee {
+    /// The assigned node, or none for nobody.
+    pub node_id: Option<NodeId>,
+    pub name: String,
+}
+
+impl std::fmt::Display for Assignee {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// The action items of a topic, each with the message it was made of, a
+/// checkbox ticking it off and who it is assigned to.
+pub fn task_list<'a, Message: Clone + 'a>(
+    tasks: Vec<(Task, &ChatMessage)>,
+    assignees: Vec<Assignee>,
+    on_done: fn(String, bool) -> Message,
+    on_assign: fn(String, Option<NodeId>) -> Message,
+    on_remove: fn(String) -> Message,
+) -> Element<'a, Message> {
+    if tasks.is_empty() {
+        return container(
+            text("No action items yet. Mark a message as one from its menu.").size(14),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .into();
+    }
+
+    let list = tasks
+        .into_iter()
+        .fold(column![].spacing(10), |list, (task, message)| {
+            let excerpt: String = message
+                .content
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .chars()
+                .take(TASK_EXCERPT_CHARS)
+                .collect();
+            let excerpt = if excerpt.len() < message.content.len() {
+                format!("{}…", excerpt)
+            } else {
+                excerpt
+            };
+            let mut excerpt = text(excerpt).width(Length::Fill);
+            if task.done {
+                excerpt = excerpt.style(EDITED_COLOR);
+            }
+
+            // Members who left are still shown as assigned
+            let selected = assignees
+                .iter()
+                .find(|assignee| assignee.node_id == task.assignee)
+                .cloned()
+                .unwrap_or_else(|| Assignee {
+                    node_id: task.assignee,
+                    name: task
+                        .assignee
+                        .map(|node_id| node_id.fmt_short())
+                        .unwrap_or_default(),
+                });
+
+            let id = task.message_id.clone();
+            let done = checkbox("", task.done, move |done| on_done(id.clone(), done));
+            let id = task.message_id.clone();
+            let assignee = pick_list(assignees.clone(), Some(selected), move |assignee| {
+                on_assign(id.clone(), assignee.node_id)
+            })
+            .width(Length::Fixed(ASSIGNEE_WIDTH));
+
+            list.push(
+                row![
+                    done,
+                    column![
+                        excerpt,
+                        text(format!(
+                            "{}, {}",
+                            message.author,
+                            message.timestamp.format("%Y-%m-%d %H:%M")
+                        ))
+                        .size(12)
+                        .style(EDITED_COLOR),
+                    ]
+                    .spacing(2)
+                    .width(Length::Fill),
+                    assignee,
+                    secondary_button("Remove", on_remove(task.message_id)).padding(5),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            )
+        });
+
+    scrollable(list).height(Length::Fill).into()
+}
+
+/// The calendar event announced by `message` as a card, with who answered
+/// it, buttons answering it and one exporting it to a calendar file.
+pub fn event_card<'a, Message: Clone + 'a>(
+    message: &ChatMessage,
+    event: &CalendarEvent,
+    responses: &[(String, RsvpResponse)],
+    own_response: Option<RsvpResponse>,
+    exported: Option<&Path>,
+    on_respond: fn(String, RsvpResponse) -> Message,
+    on_export: Message,
+) -> Element<'a, Message> {
+    let starts_at = event.starts_at.with_timezone(&Local);
+    let ends_at = event.ends_at.with_timezone(&Local);
+    let ends = if ends_at.date_naive() == starts_at.date_naive() {
+        ends_at.format("%H:%M")
+    } else {
+        ends_at.format("%a %d %b %Y %H:%M")
+    };
+    let bold = Font {
+        weight: font::Weight::Bold,
+        ..Font::DEFAULT
+    };
+
+    let mut card = column![
+        text(format!("📅 {}", event.title)).size(18).font(bold),
+        text(format!(
+            "{} to {}",
+            starts_at.format("%a %d %b %Y %H:%M"),
+            ends
+        ))
+        .size(14),
+    ]
+    .spacing(5)
+    .width(Length::Fill);
+    if !event.location.is_empty() {
+        card = card.push(text(format!("📍 {}", event.location)).size(14));
+    }
+    card = card.push(
+        text(format!(
+            "Shared by {} at {}",
+            message.author,
+            message.timestamp.format("%H:%M")
+        ))
+        .size(12)
+        .style(EDITED_COLOR),
+    );
+
+    for response in [RsvpResponse::Accept, RsvpResponse::Decline] {
+        let names: Vec<_> = responses
+            .iter()
+            .filter(|(_, answer)| *answer == response)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if !names.is_empty() {
+            card = card.push(
+                text(format!(
+                    "{} ({}): {}",
+                    response,
+                    names.len(),
+                    names.join(", ")
+                ))
+                .size(12),
+            );
+        }
+    }
+
+    let answer_button = |response: RsvpResponse| {
+        let label = response.to_string();
+        let on_press = on_respond(message.id.clone(), response);
+        if own_response == Some(response) {
+            primary_button(&label, on_press).padding(5)
+        } else {
+            secondary_button(&label, on_press).padding(5)
+        }
+    };
+    card = card.push(
+        row![
+            answer_button(RsvpResponse::Accept),
+            answer_button(RsvpResponse::Decline),
+            secondary_button("Add to calendar", on_export).padding(5),
+        ]
+        .spacing(10),
+    );
+    if let Some(path) = exported {
+        card = card.push(
+            text(format!("Saved to {}", path.display()))
+                .size(12)
+                .style(EDITED_COLOR),
+        );
+    }
+
+    container(card)
+        .style(theme::Container::Box)
+        .padding(CONTROL_PADDING)
+        .width(Length::Fill)
+        .into()
+}
+
+/// Divider in the message list before the first message of `day`.
+pub fn day_separator<'a, Message: 'a>(day: NaiveDate) -> Element<'a, Message> {
+    row![
+        horizontal_rule(1),
+        text(day.format("%A, %-d %B %Y"))
+            .size(12)
+            .style(EDITED_COLOR),
+        horizontal_rule(1),
+    ]
+    .spacing(10)
+    .align_items(Alignment::Center)
+    .into()
+}
+
+/// Row in the message list standing for `count` hidden messages, which sends
+/// `on_show` when pressed to show them.
+pub fn hidden_messages<'a, Message: Clone + 'a>(
+    count: usize,
+    on_show: Message,
+) -> Element<'a, Message> {
+    button(
+        text(if count == 1 {
+            "1 hidden message".to_string()
+        } else {
+            format!("{} hidden messages", count)
+        })
+        .size(12)
+        .style(EDITED_COLOR),
+    )
+    .style(theme::Button::Text)
+    .padding(0)
+    .on_press(on_show)
+    .into()
+}
+
+/// A single chat message in the message list.
+///
+/// The author is preceded by a badge telling whether the message was signed by
+/// the node it claims to come from, and shown with the name and avatar of the
+/// sender's `profile` if it has one, and their `avatar_image`. Edited messages are marked as such, and
+/// deleted messages are greyed out. Our own messages tell whether they were
+/// `delivered` to anybody. Messages `grouped` with the one above leave out
+/// the author and avatar. The content is shown as typed if `raw`, and with
+/// its Markdown rendered otherwise, where pressing a link to a topic or web
+/// address sends `on_link` with what it links to.
+pub fn message_row<'a, Message: Clone + 'a>(
+    message: &ChatMessage,
+    profile: Option<&UserProfile>,
+    avatar_image: Option<&image::Handle>,
+    delivery: Option<DeliveryState>,
+    grouped: bool,
+    raw: bool,
+    on_link: impl Fn(Link) -> Message + Copy + 'a,
+) -> Element<'a, Message> {
+    let author = match profile {
+        Some(profile) if !profile.avatar.trim().is_empty() => format!(
+            "{} {}",
+            profile.avatar.trim(),
+            profile.name_or(&message.author)
+        ),
+        Some(profile) => profile.name_or(&message.author).to_string(),
+        None => message.author.clone(),
+    };
+    let timestamp = message.timestamp.format("%H:%M:%S").to_string();
+
+    // Deleted messages only keep their placeholder, greyed out
+    let content = if message.is_deleted() {
+        text(&message.content)
+            .style(EDITED_COLOR)
+            .width(Length::Fill)
+            .into()
+    } else if raw {
+        text(&message.content).width(Length::Fill).into()
+    } else {
+        message_content(&message.content, on_link)
+    };
+    let message_text: Element<_> = if grouped {
+        content
+    } else {
+        row![text(format!("{}:", author)), content]
+            .spacing(WORD_GAP)
+            .width(Length::Fill)
+            .into()
+    };
+
+    let mut row = row![verification_badge(message)]
+        .spacing(10)
+        .width(Length::Fill)
+        .align_items(Alignment::Center);
+    if let Some(avatar_image) = avatar_image.filter(|_| !grouped) {
+        row = row.push(
+            image(avatar_image.clone())
+                .width(Length::Fixed(AVATAR_IMAGE_SIZE))
+                .height(Length::Fixed(AVATAR_IMAGE_SIZE)),
+        );
+    }
+    let mut row = row.push(message_text);
+    if message.edited_at.is_some() && !message.is_deleted() {
+        row = row.push(text("(edited)").size(12).style(EDITED_COLOR));
+    }
+    if let Some(delivery) = delivery {
+        row = row.push(delivery_marker(delivery));
+    }
+
+    row.push(text(timestamp).size(12)).into()
+}
+
+// The blocks of the Markdown in `content`, one below the other
+fn message_content<'a, Message: Clone + 'a>(
+    content: &str,
+    on_link: impl Fn(Link) -> Message + Copy + 'a,
+) -> Element<'a, Message> {
+    markdown::parse(content)
+        .into_iter()
+        .fold(column![].spacing(2), |blocks, block| {
+            let spans = spans_flow(&block.spans, on_link);
+            let marker = match block.kind {
+                BlockKind::Paragraph => None,
+                BlockKind::Bullet => Some(text("•")),
+                BlockKind::Numbered(number) => Some(text(format!("{}.", number))),
+                BlockKind::Quote => Some(text("▎").style(QUOTE_COLOR)),
+            };
+            blocks.push(match marker {
+                Some(marker) => Element::from(row![marker, spans].spacing(WORD_GAP)),
+                None => spans,
+            })
+        })
+        .width(Length::Fill)
+        .into()
+}
+
+// The words of `spans` in their style, wrapping at the available width.
+// Links to topics and web addresses outside of code are buttons.
+fn spans_flow<'a, Message: Clone + 'a>(
+    spans: &[Span],
+    on_link: impl Fn(Link) -> Message + Copy + 'a,
+) -> Element<'a, Message> {
+    let mut flow = Flow::new().line_spacing(2.0);
+    // Spans run into each other unless there is a space in between
+    let mut space_before = false;
+    for span in spans {
+        let font = Font {
+            weight: if span.style.bold {
+                font::Weight::Bold
+            } else {
+                font::Weight::Normal
+            },
+            ..if span.style.code {
+                Font::MONOSPACE
+            } else {
+                Font::DEFAULT
+            }
+        };
+        space_before |= span.text.starts_with(char::is_whitespace);
+        for word in span.text.split_whitespace() {
+            let on_press = markdown::link(word)
+                .filter(|_| !span.style.code)
+                .map(on_link);
+            let mut word = text(word).font(font);
+            if span.style.italic || on_press.is_some() {
+                word = word.style(EMPHASIS_COLOR);
+            }
+            let word: Element<_> = match on_press {
+                Some(on_press) => button(word)
+                    .style(theme::Button::Text)
+                    .padding(0)
+                    .on_press(on_press)
+                    .into(),
+                None => word.into(),
+            };
+            flow = flow.push(word, if space_before { WORD_GAP } else { 0.0 });
+            space_before = true;
+        }
+        space_before = span.text.ends_with(char::is_whitespace);
+    }
+    flow.into()
+}
+
+// Marker telling whether one of our messages reached anybody
+fn delivery_marker<'a, Message: 'a>(delivery: DeliveryState) -> Element<'a, Message> {
+    let (marker, color, hint) = match delivery {
+        DeliveryState::Queued => (
+            "◷",
+            EDITED_COLOR,
+            "Pending: sent once another member is connected",
+        ),
+        DeliveryState::Pending => ("…", EDITED_COLOR, "Sending, nobody confirmed it yet"),
+        DeliveryState::Delivered => ("✓", VERIFIED_COLOR, "Received by another member"),
+        DeliveryState::Failed => (
+            "!",
+            FAILED_COLOR,
+            "Not delivered: nobody confirmed it, even after sending it again",
+        ),
+    };
+
+    tooltip(
+        text(marker).size(12).style(color),
+        hint,
+        tooltip::Position::Top,
+    )
+    .style(theme::Container::Box)
+    .into()
+}
+
+/// How one of our messages was delivered, from its `report` if one is
+/// still kept: the neighbors it was handed to, how often it was sent, and
+/// which members acknowledged it how long after it was sent. Members are
+/// named by `name_of`, and the members `waiting` did not acknowledge it yet.
+pub fn delivery_details<'a, Message: Clone + 'a>(
+    report: Option<&DeliveryReport>,
+    name_of: impl Fn(NodeId) -> String,
+    waiting: &[String],
+    on_close: Message,
+) -> Element<'a, Message> {
+    let (state, lines) = match report {
+        Some(report) => {
+            let state = match report.state {
+                DeliveryState::Queued => "Pending, sent once another member is connected",
+                DeliveryState::Pending => "Sending, nobody confirmed it yet",
+                DeliveryState::Delivered => "Delivered",
+                DeliveryState::Failed => "Not delivered",
+            };
+            let neighbors = match report.neighbors.len() {
+                0 => "Handed to no neighbor, nobody was connected".to_string(),
+                count => format!(
+                    "Handed to {} neighbor(s): {}",
+                    count,
+                    names(report.neighbors.iter().map(|node_id| name_of(*node_id)))
+                ),
+            };
+            let mut lines = vec![
+                neighbors,
+                format!(
+                    "Sent {} time(s), {} of them again as nobody confirmed it",
+                    report.attempts,
+                    report.retransmissions()
+                ),
+            ];
+            if !report.acks.is_empty() {
+                lines.push(format!(
+                    "Received by {}",
+                    names(report.acks.iter().map(|(node_id, after)| {
+                        format!("{} after {}", name_of(*node_id), elapsed(*after))
+                    }))
+                ));
+            }
+            if !waiting.is_empty() {
+                lines.push(format!(
+                    "Not confirmed by {}",
+                    names(waiting.iter().cloned())
+                ));
+            }
+            if let Some(propagation) = report.propagation() {
+                lines.push(format!(
+                    "Reached everybody above within {}",
+                    elapsed(propagation)
+                ));
+            }
+            (state, lines)
+        }
+        None => (
+            "Unknown",
+            vec![
+                "Details are only kept for the latest messages sent since the app started"
+                    .to_string(),
+            ],
+        ),
+    };
+
+    let details = lines.into_iter().fold(
+        column![row![
+            text(format!("Delivery: {}", state))
+                .size(LABEL_SIZE)
+                .width(Length::Fill),
+            secondary_button("Close", on_close).padding(5),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center)],
+        |details, line| details.push(text(line).size(LABEL_SIZE)),
+    );
+    container(details.spacing(5))
+        .style(theme::Container::Box)
+        .padding(CONTROL_PADDING)
+        .width(Length::Fill)
+        .into()
+}
+
+// Names joined into a list
+fn names(names: impl Iterator<Item = String>) -> String {
+    names.collect::<Vec<_>>().join(", ")
+}
+
+// A short duration in milliseconds, or seconds once it is longer
+fn elapsed(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        format!("{} ms", duration.as_millis())
+    } else {
+        format!("{:.1} s", duration.as_secs_f64())
+    }
+}
+
+// Marker shown in front of the author of a message
+fn verification_badge<'a, Message: 'a>(message: &ChatMessage) -> Element<'a, Message> {
+    let (badge, color, hint) = match &message.verified_sender {
+        Some(node_id) => (
+            "✓",
+            VERIFIED_COLOR,
+            format!("Signed by node {}", node_id.fmt_short()),
+        ),
+        None => (
+            "?",
+            UNVERIFIED_COLOR,
+            "Unverified: the sender did not sign this message".to_string(),
+        ),
+    };
+
+    tooltip(
+        text(badge).size(14).style(color),
+        hint,
+        tooltip::Position::Top,
+    )
+    .style(theme::Container::Box)
+    .into()
+}
@@ -0,0 +1,129 @@
+//! A widget laying out its children like the words of a paragraph.
+//!
+//! Children are placed left to right and continue on the next line once the
+//! available width is used up, so a paragraph made of differently styled
+//! pieces of text wraps as a whole instead of overflowing its row.
+
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::renderer;
+use iced::advanced::widget::{Tree, Widget};
+use iced::{mouse, Element, Length, Point, Rectangle, Size};
+
+/// Children laid out in lines, wrapping at the available width.
+pub struct Flow<'a, Message, Renderer> {
+    children: Vec<Element<'a, Message, Renderer>>,
+    // Space before each child, unless it starts a line
+    gaps: Vec<f32>,
+    line_spacing: f32,
+}
+
+impl<'a, Message, Renderer> Flow<'a, Message, Renderer> {
+    pub fn new() -> Self {
+        Self {
+            children: Vec::new(),
+            gaps: Vec::new(),
+            line_spacing: 0.0,
+        }
+    }
+
+    /// Adds `child` after the last one, leaving `gap` pixels in between if
+    /// both end up on the same line.
+    pub fn push(mut self, child: impl Into<Element<'a, Message, Renderer>>, gap: f32) -> Self {
+        self.children.push(child.into());
+        self.gaps.push(gap);
+        self
+    }
+
+    /// Sets the vertical space between lines.
+    pub fn line_spacing(mut self, line_spacing: f32) -> Self {
+        self.line_spacing = line_spacing;
+        self
+    }
+}
+
+impl<'a, Message, Renderer> Default for Flow<'a, Message, Renderer> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, Message, Renderer> Widget<Message, Renderer> for Flow<'a, Message, Renderer>
+where
+    Renderer: renderer::Renderer,
+{
+    fn children(&self) -> Vec<Tree> {
+        self.children.iter().map(Tree::new).collect()
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(&self.children);
+    }
+
+    fn width(&self) -> Length {
+        Length::Fill
+    }
+
+    fn height(&self) -> Length {
+        Length::Shrink
+    }
+
+    fn layout(&self, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        let limits = limits.width(Length::Fill).height(Length::Shrink);
+        let max_width = limits.max().width;
+        let child_limits = layout::Limits::new(Size::ZERO, Size::new(max_width, f32::INFINITY));
+
+        let mut nodes = Vec::with_capacity(self.children.len());
+        let (mut x, mut y, mut line_height, mut width) = (0.0_f32, 0.0_f32, 0.0_f32, 0.0_f32);
+        for (child, gap) in self.children.iter().zip(&self.gaps) {
+            let mut node = child.as_widget().layout(renderer, &child_limits);
+            let size = node.size();
+            if x > 0.0 && x + gap + size.width > max_width {
+                // Start a new line
+                y += line_height + self.line_spacing;
+                x = 0.0;
+                line_height = 0.0;
+            } else if x > 0.0 {
+                x += gap;
+            }
+            node.move_to(Point::new(x, y));
+            x += size.width;
+            line_height = line_height.max(size.height);
+            width = width.max(x);
+            nodes.push(node);
+        }
+
+        layout::Node::with_children(limits.resolve(Size::new(width, y + line_height)), nodes)
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Renderer::Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        for ((child, state), layout) in self
+            .children
+            .iter()
+            .zip(&tree.children)
+            .zip(layout.children())
+        {
+            child
+                .as_widget()
+                .draw(state, renderer, theme, style, layout, cursor, viewport);
+        }
+    }
+}
+
+impl<'a, Message, Renderer> From<Flow<'a, Message, Renderer>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+    Renderer: renderer::Renderer + 'a,
+{
+    fn from(flow: Flow<'a, Message, Renderer>) -> Self {
+        Element::new(flow)
+    }
+}
@@ -0,0 +1,129 @@
+//! Identifiers of the text inputs that can receive keyboard focus.
+//!
+//! Screens focus their main input when they are entered, so typing works
+//! without clicking it first.
+
+use iced::widget::text_input::Id;
+
+/// The username input on the welcome screen.
+pub fn username() -> Id {
+    Id::new("username")
+}
+
+/// The topic name input on the create topic screen.
+pub fn topic_name() -> Id {
+    Id::new("topic-name")
+}
+
+/// The ticket input on the join topic screen.
+pub fn ticket() -> Id {
+    Id::new("ticket")
+}
+
+/// The display name input on the profile screen.
+pub fn display_name() -> Id {
+    Id::new("display-name")
+}
+
+/// The status line input on the profile screen.
+pub fn status_line() -> Id {
+    Id::new("status-line")
+}
+
+/// The avatar input on the profile screen.
+pub fn avatar() -> Id {
+    Id::new("avatar")
+}
+
+/// The avatar image path input on the profile screen.
+pub fn avatar_image() -> Id {
+    Id::new("avatar-image")
+}
+
+/// The username input on the settings screen.
+pub fn settings_username() -> Id {
+    Id::new("settings-username")
+}
+
+/// The input of the words filtered on every topic, on the settings screen.
+pub fn content_filter() -> Id {
+    Id::new("content-filter")
+}
+
+/// The input of the words filtered on a topic, on the topic settings screen.
+pub fn topic_filter() -> Id {
+    Id::new("topic-filter")
+}
+
+/// The hour input of the scheduled export on the topic settings screen.
+pub fn export_hour() -> Id {
+    Id::new("export-hour")
+}
+
+/// The days input of the scheduled export on the topic settings screen.
+pub fn export_days() -> Id {
+    Id::new("export-days")
+}
+
+/// The folder input of the scheduled export on the topic settings screen.
+pub fn export_folder() -> Id {
+    Id::new("export-folder")
+}
+
+/// The input of the folder shared with the topic on the topic settings
+/// screen.
+pub fn bridge_folder() -> Id {
+    Id::new("bridge-folder")
+}
+
+/// The welcome message input on the topic settings screen.
+pub fn welcome_template() -> Id {
+    Id::new("welcome-template")
+}
+
+/// The input of the node to allow on the topic settings screen.
+pub fn allowed_node() -> Id {
+    Id::new("allowed-node")
+}
+
+/// The search input of the emoji picker in the chat room.
+pub fn emoji_search() -> Id {
+    Id::new("emoji-search")
+}
+
+/// The message composer in the chat room.
+pub fn composer() -> Id {
+    Id::new("composer")
+}
+
+/// The inputs of the event form in the chat room.
+pub fn event_title() -> Id {
+    Id::new("event-title")
+}
+
+pub fn event_starts() -> Id {
+    Id::new("event-starts")
+}
+
+pub fn event_ends() -> Id {
+    Id::new("event-ends")
+}
+
+pub fn event_location() -> Id {
+    Id::new("event-location")
+}
+
+/// The input of the topic switcher.
+pub fn topic_switcher() -> Id {
+    Id::new("topic-switcher")
+}
+
+/// The search input of the messages in the chat room.
+pub fn message_search() -> Id {
+    Id::new("message-search")
+}
+
+/// The node address input of the diagnostics screen, adding a peer manually.
+pub fn peer_addr() -> Id {
+    Id::new("peer-addr")
+}
@@ -0,0 +1,44 @@
+//! Breakpoints adapting the layout to the size of the window.
+
+// Windows narrower than this use the compact layout
+const COMPACT_BREAKPOINT: u32 = 600;
+
+/// How much room the window leaves for the interface.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LayoutMode {
+    #[default]
+    Regular,
+    Compact,
+}
+
+impl LayoutMode {
+    /// The layout to use for a window `width` logical pixels wide.
+    pub fn for_width(width: u32) -> Self {
+        if width < COMPACT_BREAKPOINT {
+            Self::Compact
+        } else {
+            Self::Regular
+        }
+    }
+
+    /// Padding around the content of a screen.
+    pub fn padding(self) -> u16 {
+        match self {
+            Self::Regular => 20,
+            Self::Compact => 8,
+        }
+    }
+
+    /// Spacing between the main elements of a screen.
+    pub fn spacing(self) -> u16 {
+        match self {
+            Self::Regular => 20,
+            Self::Compact => 10,
+        }
+    }
+
+    /// Whether elements placed side by side should be stacked instead.
+    pub fn is_compact(self) -> bool {
+        self == Self::Compact
+    }
+}
@@ -0,0 +1,11 @@
+//! User interface building blocks for the chat application.
+
+pub mod components;
+pub mod flow;
+pub mod focus;
+pub mod layout;
+pub mod shortcuts;
+pub mod timeline;
+pub mod tour;
+pub mod virtual_list;
+pub mod widgets;
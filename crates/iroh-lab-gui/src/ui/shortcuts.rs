@@ -0,0 +1,49 @@
+//! Keyboard shortcuts working on every screen.
+//!
+//! Shortcuts are caught by the application subscription, so they work
+//! whichever widget has focus. Enter and Tab keep their meaning in inputs
+//! and are listed along with them.
+
+use iced::keyboard::{KeyCode, Modifiers};
+
+/// What a shortcut does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shortcut {
+    /// Closes what is open, or goes back to the previous screen.
+    Back,
+    /// Opens the topic switcher.
+    SwitchTopic,
+    /// Opens the search of the messages in the chat room.
+    Search,
+    /// Copies the message whose menu is open.
+    Copy,
+}
+
+/// Every key binding with what it does, as listed in the settings.
+pub const BINDINGS: [(&str, &str); 11] = [
+    ("Esc", "Close menus and forms, or go back"),
+    ("Ctrl+K", "Switch to another topic"),
+    ("Ctrl+F", "Search the messages of the chat room"),
+    ("Ctrl+C", "Copy the message whose menu is open"),
+    ("Ctrl+V", "Paste text, or an image to send to the topic"),
+    ("Enter", "Send the message, unless Ctrl+Enter is chosen"),
+    ("Shift+Enter", "Start a new line of the message"),
+    ("Ctrl+Enter", "Send the message"),
+    ("Up / Down", "Recall the messages sent on the topic"),
+    (
+        "Tab",
+        "Complete the name of a member, or move to the next field",
+    ),
+    ("Shift+Tab", "Move to the previous field"),
+];
+
+/// The shortcut `key_code` pressed with `modifiers` stands for.
+pub fn shortcut(key_code: KeyCode, modifiers: Modifiers) -> Option<Shortcut> {
+    match key_code {
+        KeyCode::Escape => Some(Shortcut::Back),
+        KeyCode::K if modifiers.control() => Some(Shortcut::SwitchTopic),
+        KeyCode::F if modifiers.control() => Some(Shortcut::Search),
+        KeyCode::C if modifiers.control() => Some(Shortcut::Copy),
+        _ => None,
+    }
+}
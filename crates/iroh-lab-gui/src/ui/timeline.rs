@@ -0,0 +1,71 @@
+//! The rows of the message list: messages, and dividers between days.
+//!
+//! Consecutive messages from the same sender close together in time form a
+//! group, and only the first message of a group shows who sent it.
+//! Consecutive hidden messages, e.g. of muted members, collapse into a single
+//! row counting them.
+
+use chrono::{Duration, NaiveDate};
+use iroh_lab_core::client::ChatMessage;
+
+// Longest pause between two messages of a group
+const GROUP_WINDOW: Duration = Duration::minutes(5);
+
+/// A row of the message list.
+#[derive(Debug, Clone, Copy)]
+pub enum Row<'a> {
+    /// Divider before the first message of a day.
+    Day(NaiveDate),
+    /// A message, `grouped` with the one before if it continues its group.
+    Message {
+        message: &'a ChatMessage,
+        grouped: bool,
+    },
+    /// Stands for that many consecutive hidden messages.
+    Hidden(usize),
+}
+
+/// The rows showing `messages`, which are in display order, collapsing those
+/// `hidden`.
+pub fn rows<'a>(
+    messages: impl IntoIterator<Item = &'a ChatMessage>,
+    hidden: impl Fn(&ChatMessage) -> bool,
+) -> Vec<Row<'a>> {
+    let mut rows = Vec::new();
+    let mut previous: Option<&ChatMessage> = None;
+    for message in messages {
+        let day = message.timestamp.date_naive();
+        let new_day = previous.is_none_or(|previous| previous.timestamp.date_naive() != day);
+        if new_day {
+            rows.push(Row::Day(day));
+        }
+        if hidden(message) {
+            match rows.last_mut() {
+                Some(Row::Hidden(count)) => *count += 1,
+                _ => rows.push(Row::Hidden(1)),
+            }
+            previous = Some(message);
+            continue;
+        }
+        // The message after collapsed ones shows who sent it again
+        let grouped = !new_day
+            && !matches!(rows.last(), Some(Row::Hidden(_)))
+            && previous.is_some_and(|previous| {
+                same_sender(previous, message)
+                    && (message.timestamp - previous.timestamp).abs() <= GROUP_WINDOW
+            });
+        rows.push(Row::Message { message, grouped });
+        previous = Some(message);
+    }
+    rows
+}
+
+// Whether two messages come from the same sender, by the node that signed
+// them where both were signed
+fn same_sender(a: &ChatMessage, b: &ChatMessage) -> bool {
+    match (a.verified_sender, b.verified_sender) {
+        (Some(a), Some(b)) => a == b,
+        (None, None) => a.author == b.author,
+        _ => false,
+    }
+}
@@ -0,0 +1,69 @@
+//! Guided tour pointing out the main controls on first use.
+//!
+//! Every step belongs to a control on one of the screens, and is shown as a
+//! coach mark around that control while the screen is open. Steps can be
+//! acknowledged one by one in any order, or the whole tour skipped.
+
+/// A control explained by the tour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TourStep {
+    /// The button copying the ticket of a new topic.
+    CopyTicket,
+    /// The message composer of the chat room.
+    Composer,
+}
+
+impl TourStep {
+    // All steps, in the order they are usually reached
+    const ALL: [TourStep; 2] = [TourStep::CopyTicket, TourStep::Composer];
+
+    /// The explanation shown next to the control.
+    pub fn hint(self) -> &'static str {
+        match self {
+            TourStep::CopyTicket => {
+                "Copy the ticket and send it to the people you want to chat with. \
+                 Anyone holding it can join and read the topic."
+            }
+            TourStep::Composer => {
+                "Type your message here. It is signed and encrypted before it \
+                 leaves your machine."
+            }
+        }
+    }
+}
+
+/// Progress through the guided tour.
+#[derive(Debug, Clone)]
+pub struct Tour {
+    remaining: Vec<TourStep>,
+}
+
+impl Default for Tour {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tour {
+    /// A tour that still has all of its steps to show.
+    pub fn new() -> Self {
+        Self {
+            remaining: TourStep::ALL.to_vec(),
+        }
+    }
+
+    /// Whether `step` still has to be shown.
+    pub fn shows(&self, step: TourStep) -> bool {
+        self.remaining.contains(&step)
+    }
+
+    /// Marks `step` as seen.
+    pub fn dismiss(&mut self, step: TourStep) {
+        self.remaining.retain(|remaining| *remaining != step);
+    }
+
+    /// Whether every step has been seen.
+    pub fn is_finished(&self) -> bool {
+        self.remaining.is_empty()
+    }
+}
@@ -0,0 +1,34 @@
+//! Rendering only the part of a long list that is scrolled into view.
+//!
+//! Rows outside the viewport are replaced by empty space of their estimated
+//! height, so the scrollbar behaves as if every row was there while the
+//! widget tree only holds what can be seen, plus some rows on either side
+//! to scroll into without a gap.
+
+use std::ops::Range;
+
+/// Estimated height of a row including the spacing after it, in logical
+/// pixels.
+pub const ROW_HEIGHT: f32 = 48.0;
+
+// Rows rendered above and below the viewport
+const OVERSCAN: usize = 20;
+
+/// The rows of a list of `total` rows to render when it is scrolled `offset`
+/// pixels down in a viewport `height` pixels tall.
+pub fn visible_range(total: usize, offset: f32, height: f32) -> Range<usize> {
+    let shown = (height.max(0.0) / ROW_HEIGHT).ceil() as usize;
+    // Offsets past the end show the last rows
+    let first = ((offset.max(0.0) / ROW_HEIGHT).floor() as usize).min(total.saturating_sub(shown));
+    let start = first.saturating_sub(OVERSCAN).min(total);
+    let end = first
+        .saturating_add(shown)
+        .saturating_add(OVERSCAN)
+        .min(total);
+    start..end.max(start)
+}
+
+/// Height of the space standing in for `rows` rows that are not rendered.
+pub fn spacer_height(rows: usize) -> f32 {
+    rows as f32 * ROW_HEIGHT
+}
@@ -0,0 +1,213 @@
+//! Reusable widgets shared by the screens of the chat application.
+
+use iced::widget::{button, column, container, mouse_area, row, text, text_input};
+use iced::{theme, Element, Length, Theme};
+
+// Width of the popup listing the context menu entries
+const MENU_WIDTH: f32 = 200.0;
+
+// Emoji per row of the emoji picker
+const EMOJI_PER_ROW: usize = 12;
+
+// Width of the outline around the control a coach mark points out
+const HIGHLIGHT_WIDTH: f32 = 2.0;
+
+/// A single entry in a context menu.
+pub struct MenuItem<Message> {
+    label: String,
+    on_select: Message,
+}
+
+impl<Message> MenuItem<Message> {
+    pub fn new(label: impl Into<String>, on_select: Message) -> Self {
+        Self {
+            label: label.into(),
+            on_select,
+        }
+    }
+}
+
+/// Wraps `content` so that right-clicking it emits `on_open`.
+///
+/// Which menu is open is application state: when `open` is set the `items` are
+/// rendered directly below the content, and left-clicking the content emits
+/// `on_dismiss`. The application is expected to close the menu again once one
+/// of the items has been selected.
+pub fn context_menu<'a, Message: Clone + 'a>(
+    content: impl Into<Element<'a, Message>>,
+    items: Vec<MenuItem<Message>>,
+    open: bool,
+    on_open: Message,
+    on_dismiss: Message,
+) -> Element<'a, Message> {
+    let target = mouse_area(content).on_right_press(on_open);
+
+    if !open || items.is_empty() {
+        return target.into();
+    }
+
+    let entries = items
+        .into_iter()
+        .fold(column![].spacing(2), |entries, item| {
+            entries.push(
+                button(text(item.label).size(14))
+                    .on_press(item.on_select)
+                    .style(theme::Button::Text)
+                    .padding([4, 8])
+                    .width(Length::Fill),
+            )
+        });
+
+    let menu = container(entries)
+        .style(theme::Container::Box)
+        .padding(4)
+        .width(Length::Fixed(MENU_WIDTH));
+
+    column![target.on_press(on_dismiss), menu].spacing(4).into()
+}
+
+/// A panel to pick an emoji from, searched by typing into its input.
+///
+/// The `recent` emoji are offered first while nothing is searched, followed
+/// by the `found` ones. The input, identified by `search_id`, emits
+/// `on_search` as the `query` changes, and picking an emoji emits `on_pick`.
+pub fn emoji_picker<'a, Message: Clone + 'a>(
+    search_id: text_input::Id,
+    query: &str,
+    recent: &[String],
+    found: &[&str],
+    on_search: impl Fn(String) -> Message + 'a,
+    on_pick: impl Fn(String) -> Message,
+) -> Element<'a, Message> {
+    let grid = |emoji: &[String]| {
+        emoji
+            .chunks(EMOJI_PER_ROW)
+            .fold(column![].spacing(2), |grid, chunk| {
+                grid.push(chunk.iter().fold(row![].spacing(2), |line, emoji| {
+                    line.push(
+                        button(text(emoji).size(20))
+                            .on_press(on_pick(emoji.clone()))
+                            .style(theme::Button::Text)
+                            .padding(4),
+                    )
+                }))
+            })
+    };
+
+    let mut content = column![text_input("Search emoji", query)
+        .id(search_id)
+        .on_input(on_search)
+        .padding(6)]
+    .spacing(6);
+    if query.trim().is_empty() && !recent.is_empty() {
+        content = content
+            .push(text("Recent").size(12))
+            .push(grid(recent))
+            .push(text("All").size(12));
+    }
+    if found.is_empty() {
+        content = content.push(text("No emoji found").size(14));
+    } else {
+        let found: Vec<String> = found.iter().map(|emoji| emoji.to_string()).collect();
+        content = content.push(grid(&found));
+    }
+
+    container(content)
+        .style(theme::Container::Box)
+        .padding(8)
+        .width(Length::Fill)
+        .into()
+}
+
+/// A panel to switch to one of `topics`, given by name with their unread
+/// messages, filtered by typing into its input.
+///
+/// The input, identified by `query_id`, emits `on_query` as the `query`
+/// changes and `on_submit` on Enter. Picking a topic emits `on_pick` with
+/// its name.
+pub fn topic_switcher<'a, Message: Clone + 'a>(
+    query_id: text_input::Id,
+    query: &str,
+    topics: &[(String, usize)],
+    on_query: impl Fn(String) -> Message + 'a,
+    on_submit: Message,
+    on_pick: impl Fn(String) -> Message,
+) -> Element<'a, Message> {
+    let content = column![text_input("Switch to topic", query)
+        .id(query_id)
+        .on_input(on_query)
+        .on_submit(on_submit)
+        .padding(6)]
+    .spacing(4);
+    let content = if topics.is_empty() {
+        content.push(text("No topic found").size(14))
+    } else {
+        topics.iter().fold(content, |content, (name, unread)| {
+            let label = if *unread > 0 {
+                format!("{} ({})", name, unread)
+            } else {
+                name.clone()
+            };
+            content.push(
+                button(text(label))
+                    .on_press(on_pick(name.clone()))
+                    .style(theme::Button::Text)
+                    .width(Length::Fill)
+                    .padding(4),
+            )
+        })
+    };
+
+    container(content)
+        .style(theme::Container::Box)
+        .padding(8)
+        .width(Length::Fill)
+        .into()
+}
+
+/// Highlights `content` and explains it in a callout shown directly below.
+///
+/// Used by the guided tour: `on_next` acknowledges this explanation, while
+/// `on_skip` ends the whole tour.
+pub fn coach_mark<'a, Message: Clone + 'a>(
+    content: impl Into<Element<'a, Message>>,
+    hint: &str,
+    on_next: Message,
+    on_skip: Message,
+) -> Element<'a, Message> {
+    let highlighted = container(content)
+        .padding(4)
+        .width(Length::Fill)
+        .style(highlight as fn(&Theme) -> container::Appearance);
+
+    let callout = container(
+        column![
+            text(hint).size(14),
+            row![
+                button(text("Skip tour").size(14))
+                    .on_press(on_skip)
+                    .style(theme::Button::Text),
+                button(text("Got it").size(14))
+                    .on_press(on_next)
+                    .style(theme::Button::Primary),
+            ]
+            .spacing(8),
+        ]
+        .spacing(8),
+    )
+    .style(theme::Container::Box)
+    .padding(10)
+    .width(Length::Fill);
+
+    column![highlighted, callout].spacing(4).into()
+}
+
+// Outline in the primary color of the theme
+fn highlight(theme: &Theme) -> container::Appearance {
+    container::Appearance {
+        border_width: HIGHLIGHT_WIDTH,
+        border_color: theme.palette().primary,
+        border_radius: 4.0.into(),
+        ..Default::default()
+    }
+}
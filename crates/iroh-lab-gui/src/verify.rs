@@ -0,0 +1,69 @@
+//! The `iroh-lab verify` command, checking the hash chains of archives.
+
+use iroh_lab_core::archive::{self, Archive};
+use std::path::{Path, PathBuf};
+
+/// Verifies the archive given by `args`, or every archive without one, and
+/// returns the exit code of the command: 0 when all archives are intact, 1
+/// when one is not and 2 when an archive could not be read.
+pub fn run(args: &[String]) -> i32 {
+    let paths = match archives(args) {
+        Ok(paths) => paths,
+        Err(e) => {
+            eprintln!(
+                "{}\n\nUsage: iroh-lab verify [<topic hash or archive file>]",
+                e
+            );
+            return 2;
+        }
+    };
+    if paths.is_empty() {
+        println!("No archives to verify.");
+        return 0;
+    }
+
+    let mut code = 0;
+    for path in &paths {
+        match archive::verify(path) {
+            Ok(verification) => match verification.divergence {
+                None => println!(
+                    "[OK]     {} ({} entries)",
+                    path.display(),
+                    verification.intact
+                ),
+                Some(divergence) => {
+                    println!(
+                        "[BROKEN] {} ({} intact entries)",
+                        path.display(),
+                        verification.intact
+                    );
+                    println!("         {}", divergence);
+                    code = code.max(1);
+                }
+            },
+            Err(e) => {
+                println!("[ERROR]  {}", e);
+                code = 2;
+            }
+        }
+    }
+    code
+}
+
+// The archives named by the arguments of the command
+fn archives(args: &[String]) -> Result<Vec<PathBuf>, String> {
+    let dir = Archive::default_dir();
+    match args {
+        [] => match dir {
+            Some(dir) => Archive::list(&dir),
+            None => Err("This platform has no configuration directory".to_string()),
+        },
+        // An archive file, or the hash of an archived topic
+        [archive] if Path::new(archive).is_file() => Ok(vec![PathBuf::from(archive)]),
+        [topic_hash] => match dir {
+            Some(dir) => Ok(vec![Archive::path_in(&dir, topic_hash)]),
+            None => Err("This platform has no configuration directory".to_string()),
+        },
+        [_, unexpected, ..] => Err(format!("Unexpected argument {}", unexpected)),
+    }
+}
@@ -0,0 +1,75 @@
+use clap::Parser;
+use iroh_lab_core::config::LogLevel;
+use iroh_lab_gui::launch::{Args, Launch};
+use std::path::PathBuf;
+
+/// # Test: Options Go Straight to a Topic
+///
+/// ## Steps:
+/// 1. Parse a name with a ticket to join, and a topic to create
+/// 2. Parse a configuration file and log level
+/// 3. Parse no options, blank ones, and conflicting or invalid ones
+///
+/// ## Assertions:
+/// - The name and the topic to join or create are given
+/// - The configuration file and log level are given
+/// - Without options, or with blank ones, nothing is skipped
+/// - Joining and creating at once, and unknown log levels, are refused
+#[test]
+fn test_options_go_straight_to_a_topic() {
+    let args = Args::try_parse_from(["iroh-lab", "--username", "alice", "--join", "ticket-abc"])
+        .expect("Join options should parse");
+    assert_eq!(
+        args.username(),
+        Some("alice".to_string()),
+        "Name should be given"
+    );
+    assert_eq!(
+        args.launch(),
+        Some(Launch::Join("ticket-abc".to_string())),
+        "Topic should be joined"
+    );
+    let args = Args::try_parse_from(["iroh-lab", "--create", "book-club"])
+        .expect("Create options should parse");
+    assert_eq!(
+        args.launch(),
+        Some(Launch::Create("book-club".to_string())),
+        "Topic should be created"
+    );
+
+    let args = Args::try_parse_from([
+        "iroh-lab",
+        "--config",
+        "/tmp/second.json",
+        "--log-level",
+        "DEBUG",
+    ])
+    .expect("Config options should parse");
+    assert_eq!(
+        args.config,
+        Some(PathBuf::from("/tmp/second.json")),
+        "Configuration file should be given"
+    );
+    assert_eq!(
+        args.log_level,
+        Some(LogLevel::Debug),
+        "Log level should be given"
+    );
+
+    let args = Args::try_parse_from(["iroh-lab"]).expect("No options should parse");
+    assert_eq!(args, Args::default(), "Nothing should be given");
+    let args = Args::try_parse_from(["iroh-lab", "--username", " ", "--join", ""])
+        .expect("Blank options should parse");
+    assert_eq!(args.username(), None, "Blank name should not be given");
+    assert_eq!(args.launch(), None, "Blank ticket should not be joined");
+
+    assert!(
+        Args::try_parse_from(["iroh-lab", "--join", "ticket-abc", "--create", "book-club"])
+            .is_err(),
+        "Joining and creating at once should be refused"
+    );
+    assert!(
+        Args::try_parse_from(["iroh-lab", "--log-level", "loud"]).is_err(),
+        "Unknown log level should be refused"
+    );
+}
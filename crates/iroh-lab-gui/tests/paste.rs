@@ -0,0 +1,76 @@
+use chrono::{DateTime, Utc};
+use iroh_lab_core::bridge::MAX_FILE_SIZE;
+use iroh_lab_gui::paste;
+
+fn pasted_at() -> DateTime<Utc> {
+    "2026-10-16T09:05:30Z".parse().unwrap()
+}
+
+/// # Test: Pasted Images Are Encoded as PNG Files
+///
+/// ## Steps:
+/// 1. Paste a small image, and decode the file it is encoded as
+/// 2. Paste images without pixels or with missing pixels
+///
+/// ## Assertions:
+/// - The file is a PNG of the same size and pixels
+/// - The file is named after the time it was pasted
+/// - Empty and damaged images are refused
+#[test]
+fn test_pasted_images_are_encoded_as_png_files() {
+    let rgba: Vec<u8> = (0..3 * 2 * 4).map(|i| i as u8 * 10).collect();
+
+    let pasted = paste::from_rgba(3, 2, &rgba, pasted_at()).expect("Image should encode");
+    assert_eq!(
+        pasted.name, "pasted-20261016-090530.png",
+        "File should be named after the time of pasting"
+    );
+    assert_eq!((pasted.width, pasted.height), (3, 2), "Size should be kept");
+    let mut reader = png::Decoder::new(pasted.png.as_slice())
+        .read_info()
+        .expect("File should be a PNG");
+    let mut decoded = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut decoded)
+        .expect("Failed to decode the PNG");
+    assert_eq!(
+        (info.width, info.height),
+        (3, 2),
+        "PNG should keep the size"
+    );
+    assert_eq!(decoded, rgba, "PNG should keep the pixels");
+
+    assert!(
+        paste::from_rgba(0, 0, &[], pasted_at()).is_err(),
+        "Empty image should be refused"
+    );
+    assert!(
+        paste::from_rgba(3, 2, &rgba[1..], pasted_at()).is_err(),
+        "Image with missing pixels should be refused"
+    );
+}
+
+/// # Test: Large Pasted Images Are Scaled Down to Fit
+///
+/// ## Steps:
+/// 1. Paste an image of noise, which does not compress
+///
+/// ## Assertions:
+/// - The file fits into an attachment
+/// - The image is scaled down, keeping its aspect ratio
+#[test]
+fn test_large_pasted_images_are_scaled_down_to_fit() {
+    let rgba: Vec<u8> = (0..800 * 400 * 4).map(|_| rand::random()).collect();
+
+    let pasted = paste::from_rgba(800, 400, &rgba, pasted_at()).expect("Image should encode");
+    assert!(
+        pasted.png.len() <= MAX_FILE_SIZE,
+        "File should fit into an attachment"
+    );
+    assert!(pasted.width < 800, "Image should be scaled down");
+    assert_eq!(
+        pasted.width,
+        pasted.height * 2,
+        "Image should keep its aspect ratio"
+    );
+}
@@ -0,0 +1,406 @@
+//! Runs a node without a window, for other tools to chat through.
+//!
+//! Tools connect to a Unix socket, or a TCP port with `--listen`, and speak
+//! the JSON-RPC protocol of [`iroh_lab_core::rpc`], one message per line.
+//! They share the node and its topics: a topic joined by one tool can be
+//! sent to by another, and every subscriber sees what each of them sends.
+//! With `--websocket`, web pages chat on the topics through the
+//! [gateway](iroh_lab_core::gateway) too, and with `--http`, scripts use
+//! the [HTTP API](iroh_lab_core::rest).
+use clap::Parser;
+use futures::StreamExt;
+use iroh_lab_core::client::{ChatMessage, ClientEvent, IrohClient};
+use iroh_lab_core::config::{Config, MessageLimit};
+#[cfg(feature = "gateway")]
+use iroh_lab_core::gateway::Gateway;
+#[cfg(feature = "rest")]
+use iroh_lab_core::rest::{self, RestApi};
+use iroh_lab_core::roster::PresenceStatus;
+use iroh_lab_core::rpc::{self, Call, Notification, Response, RpcError, TopicInfo, CLIENT_ERROR};
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::broadcast;
+
+// Name of the socket in the configuration directory
+const SOCKET_FILE: &str = "chatd.sock";
+
+// Name of the file keeping the token of the HTTP API, in the configuration
+// directory
+#[cfg(feature = "rest")]
+const TOKEN_FILE: &str = "api.token";
+
+// Messages kept for subscribers that fall behind
+const SUBSCRIBER_BACKLOG: usize = 1024;
+
+/// Run a node without a window, controlled over a JSON-RPC socket.
+#[derive(Debug, Parser)]
+#[command(name = "chatd")]
+struct Args {
+    /// Unix socket to listen on, by default `chatd.sock` in the
+    /// configuration directory.
+    #[arg(long, value_name = "PATH", conflicts_with = "listen")]
+    socket: Option<PathBuf>,
+    /// TCP address to listen on instead of a Unix socket, e.g.
+    /// 127.0.0.1:7475. Anyone who can connect can chat as you.
+    #[arg(long, value_name = "ADDR")]
+    listen: Option<SocketAddr>,
+    /// Name to send messages under, unless a request gives another.
+    #[arg(long)]
+    username: Option<String>,
+    /// Configuration file to use instead of the one of the current user.
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Address to serve the WebSocket gateway on, e.g. 127.0.0.1:7476, for
+    /// web pages to chat through.
+    #[cfg(feature = "gateway")]
+    #[arg(long, value_name = "ADDR")]
+    websocket: Option<SocketAddr>,
+    /// Address to serve the HTTP API on, e.g. 127.0.0.1:7477, for scripts
+    /// to send and read messages with the token in `api.token` of the
+    /// configuration directory.
+    #[cfg(feature = "rest")]
+    #[arg(long, value_name = "ADDR")]
+    http: Option<SocketAddr>,
+}
+
+// The node shared by all connections
+struct Daemon {
+    client: IrohClient,
+    username: String,
+    message_limit: MessageLimit,
+    sequence: AtomicU64,
+    messages: broadcast::Sender<ChatMessage>,
+}
+
+// What a connection subscribed to: one topic, or all of them
+type Subscription = (Option<String>, broadcast::Receiver<ChatMessage>);
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run(Args::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: Args) -> Result<(), String> {
+    let config = match &args.config {
+        Some(path) => Config::load_from(path)?,
+        None => Config::load()?,
+    };
+    let username = args
+        .username
+        .or_else(|| Some(config.username.clone()))
+        .map(|username| username.trim().to_string())
+        .filter(|username| !username.is_empty())
+        .ok_or_else(|| "No name to chat under, pass one with --username".to_string())?;
+
+    let mut events = IrohClient::subscribe(None);
+
+    let mut client = IrohClient::new();
+    client.set_relay_mode(config.relay_mode);
+    client.set_binding(config.binding);
+    let node_id = client.initialize_network().await?;
+    client.set_presence(username.clone(), PresenceStatus::Online);
+    eprintln!("Node {} is up", node_id);
+
+    #[cfg(feature = "gateway")]
+    if let Some(addr) = args.websocket {
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .map_err(|e| format!("Failed to listen on {}: {}", addr, e))?;
+        let gateway = Arc::new(Gateway::new(
+            client.clone(),
+            username.clone(),
+            config.message_limit,
+        ));
+        eprintln!(
+            "Web pages connect to ws://{}/<topic hash>?token={}",
+            addr,
+            gateway.token()
+        );
+        tokio::spawn(async move {
+            if let Err(e) = gateway.serve(listener).await {
+                eprintln!("{}", e);
+            }
+        });
+    }
+
+    #[cfg(feature = "rest")]
+    if let Some(addr) = args.http {
+        let path = Config::directory()
+            .map(|dir| dir.join(TOKEN_FILE))
+            .ok_or_else(|| "No configuration directory for the API token".to_string())?;
+        let token = rest::load_token(&path)?;
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .map_err(|e| format!("Failed to listen on {}: {}", addr, e))?;
+        let api = Arc::new(RestApi::new(
+            client.clone(),
+            username.clone(),
+            config.message_limit,
+            token,
+        ));
+        eprintln!(
+            "Scripts reach the API on http://{} with the token in {}",
+            addr,
+            path.display()
+        );
+        tokio::spawn(async move {
+            if let Err(e) = api.serve(listener).await {
+                eprintln!("{}", e);
+            }
+        });
+    }
+
+    let (messages, _) = broadcast::channel(SUBSCRIBER_BACKLOG);
+    let daemon = Arc::new(Daemon {
+        client,
+        username,
+        message_limit: config.message_limit,
+        sequence: AtomicU64::new(0),
+        messages: messages.clone(),
+    });
+    tokio::spawn(async move {
+        while let Some(event) = events.next().await {
+            if let ClientEvent::Message(message) = event {
+                // Nobody may be subscribed
+                let _ = messages.send(message);
+            }
+        }
+    });
+
+    let node = daemon.client.clone();
+    let result = match args.listen {
+        Some(addr) => listen_tcp(daemon, addr).await,
+        None => {
+            let path = match args.socket {
+                Some(path) => path,
+                None => Config::directory()
+                    .map(|dir| dir.join(SOCKET_FILE))
+                    .ok_or_else(|| "No configuration directory, pass --socket".to_string())?,
+            };
+            listen_unix(daemon, path).await
+        }
+    };
+    node.shutdown().await;
+    result
+}
+
+async fn listen_tcp(daemon: Arc<Daemon>, addr: SocketAddr) -> Result<(), String> {
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("Failed to listen on {}: {}", addr, e))?;
+    eprintln!("Listening on {}", addr);
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(serve(daemon.clone(), stream));
+                }
+                Err(e) => eprintln!("Failed to accept a connection: {}", e),
+            },
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn listen_unix(daemon: Arc<Daemon>, path: PathBuf) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    // A socket left behind by a daemon that did not stop cleanly
+    if path.exists() {
+        std::fs::remove_file(&path)
+            .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let listener = tokio::net::UnixListener::bind(&path)
+        .map_err(|e| format!("Failed to listen on {}: {}", path.display(), e))?;
+    // Only the user may chat through the node
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("Failed to restrict {}: {}", path.display(), e))?;
+    eprintln!("Listening on {}", path.display());
+
+    let result = loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(serve(daemon.clone(), stream));
+                }
+                Err(e) => eprintln!("Failed to accept a connection: {}", e),
+            },
+            _ = tokio::signal::ctrl_c() => break Ok(()),
+        }
+    };
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+#[cfg(not(unix))]
+async fn listen_unix(_daemon: Arc<Daemon>, _path: PathBuf) -> Result<(), String> {
+    Err("Unix sockets are not available on this platform, pass --listen".to_string())
+}
+
+// Answers the requests of a connection, and sends it the messages it
+// subscribed to, until it closes
+async fn serve(daemon: Arc<Daemon>, stream: impl AsyncRead + AsyncWrite) {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+    let mut subscription: Option<Subscription> = None;
+    loop {
+        let reply = tokio::select! {
+            line = lines.next_line() => match line {
+                Ok(Some(line)) => match daemon.handle(&line, &mut subscription).await {
+                    Some(response) => rpc::encode(&response),
+                    None => continue,
+                },
+                _ => return,
+            },
+            Some(message) = next_message(&mut subscription) => {
+                rpc::encode(&Notification::message(&message))
+            }
+        };
+        if writer.write_all(reply.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+// The next message of the topics subscribed to, or never without a
+// subscription
+async fn next_message(subscription: &mut Option<Subscription>) -> Option<ChatMessage> {
+    let Some((topic_hash, messages)) = subscription else {
+        return std::future::pending().await;
+    };
+    loop {
+        match messages.recv().await {
+            Ok(message)
+                if topic_hash
+                    .as_ref()
+                    .is_none_or(|hash| *hash == message.topic_hash) =>
+            {
+                return Some(message);
+            }
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return None,
+        }
+    }
+}
+
+impl Daemon {
+    // Carries out a request, returning the response unless it is a
+    // notification
+    async fn handle(
+        &self,
+        line: &str,
+        subscription: &mut Option<Subscription>,
+    ) -> Option<Response> {
+        if line.trim().is_empty() {
+            return None;
+        }
+        let request = match rpc::parse(line) {
+            Ok(request) => request,
+            Err(response) => return Some(response),
+        };
+        let result = match request.call() {
+            Ok(call) => self.execute(call, subscription).await,
+            Err(e) => Err(e),
+        };
+        let id = request.id?;
+        Some(match result {
+            Ok(result) => Response::result(id, result),
+            Err(e) => Response::error(id, e),
+        })
+    }
+
+    async fn execute(
+        &self,
+        call: Call,
+        subscription: &mut Option<Subscription>,
+    ) -> Result<Value, RpcError> {
+        let failed = |e: String| RpcError::new(CLIENT_ERROR, e);
+        match call {
+            Call::Join { ticket } => {
+                let (topic_name, topic_hash) =
+                    self.client.join_topic(ticket).await.map_err(failed)?;
+                Ok(to_value(TopicInfo {
+                    topic_name,
+                    topic_hash,
+                    ticket: None,
+                }))
+            }
+            Call::Create { name } => {
+                let (topic_name, ticket, topic_hash) =
+                    self.client.create_topic(name).await.map_err(failed)?;
+                Ok(to_value(TopicInfo {
+                    topic_name,
+                    topic_hash,
+                    ticket: Some(ticket),
+                }))
+            }
+            Call::Send {
+                topic_hash,
+                content,
+                username,
+            } => {
+                self.message_limit.check(&content).map_err(failed)?;
+                let username = username.unwrap_or_else(|| self.username.clone());
+                let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+                // Sending to the topic directly leaves the active topic alone,
+                // without resubscribing as switching topics does
+                if !self
+                    .client
+                    .session()
+                    .subscribed_topics
+                    .values()
+                    .any(|hash| *hash == topic_hash)
+                {
+                    return Err(failed("Not subscribed to this topic".to_string()));
+                }
+                let message = self
+                    .client
+                    .send_message_to(&topic_hash, username, content, sequence)
+                    .await
+                    .map_err(failed)?;
+                // The other tools and web pages see what this one sent
+                IrohClient::broadcast_message(message.clone());
+                Ok(to_value(message))
+            }
+            Call::Subscribe { topic_hash } => {
+                *subscription = Some((topic_hash, self.messages.subscribe()));
+                Ok(Value::Bool(true))
+            }
+            Call::ListTopics => {
+                let mut topics: Vec<_> = self
+                    .client
+                    .session()
+                    .subscribed_topics
+                    .iter()
+                    .map(|(topic_name, topic_hash)| TopicInfo {
+                        topic_name: topic_name.clone(),
+                        topic_hash: topic_hash.clone(),
+                        ticket: None,
+                    })
+                    .collect();
+                topics.sort_by(|a, b| a.topic_name.cmp(&b.topic_name));
+                Ok(to_value(topics))
+            }
+        }
+    }
+}
+
+fn to_value(value: impl serde::Serialize) -> Value {
+    serde_json::to_value(value).unwrap_or_default()
+}
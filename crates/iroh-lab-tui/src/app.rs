@@ -0,0 +1,256 @@
+//! State of the terminal chat room, driven by the events of the client and
+//! the keys pressed.
+//!
+//! The state knows nothing about the network: what the user asks for comes
+//! back from [`App::key`] as an [`Action`], which [`App::perform`] carries
+//! out over any [`ChatTransport`].
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use iroh::NodeId;
+use iroh_lab_core::client::{ChatMessage, ClientEvent, Presence};
+use iroh_lab_core::commands::{self, SlashCommand};
+use iroh_lab_core::config::MessageLimit;
+use iroh_lab_core::conversation::Conversation;
+use iroh_lab_core::diagnostics::Connectivity;
+use iroh_lab_core::reconnect::ReconnectState;
+use iroh_lab_core::roster::{PresenceStatus, Roster};
+use iroh_lab_core::transport::ChatTransport;
+use std::collections::BTreeSet;
+use std::time::Instant;
+
+/// Lines scrolled by Page Up and Page Down.
+pub const PAGE_LINES: usize = 10;
+
+/// What the user asked for that needs the client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Send a message with this content.
+    Send(String),
+    /// Announce our presence under the new name.
+    Nick(String),
+    /// Show an invite to the topic.
+    Invite,
+    /// Leave the topic and exit.
+    Quit,
+}
+
+/// The chat room shown in the terminal.
+#[derive(Debug)]
+pub struct App {
+    pub topic_name: String,
+    pub topic_hash: String,
+    pub username: String,
+    pub own_node_id: Option<NodeId>,
+    /// Members of the topic, from their presence announcements.
+    pub roster: Roster,
+    /// Message being typed.
+    pub input: String,
+    /// Lines scrolled up from the latest message.
+    pub scroll: usize,
+    /// Result of the last command, or the last error.
+    pub notice: Option<String>,
+    pub connectivity: Connectivity,
+    /// How restoring the subscription goes, while it is lost.
+    pub reconnect: Option<ReconnectState>,
+    /// Events missed by falling behind the network.
+    pub dropped: u64,
+    /// Gossip neighbors on the topic.
+    pub peers: BTreeSet<NodeId>,
+    // Messages of the topic in the order of their Lamport timestamps
+    conversation: Conversation,
+}
+
+impl App {
+    pub fn new(
+        topic_name: String,
+        topic_hash: String,
+        username: String,
+        own_node_id: Option<NodeId>,
+    ) -> Self {
+        Self {
+            topic_name,
+            topic_hash,
+            username,
+            own_node_id,
+            roster: Roster::default(),
+            input: String::new(),
+            scroll: 0,
+            notice: None,
+            connectivity: Connectivity::default(),
+            reconnect: None,
+            dropped: 0,
+            peers: BTreeSet::new(),
+            conversation: Conversation::new(),
+        }
+    }
+
+    /// The messages of the topic, oldest first.
+    pub fn messages(&self) -> &[ChatMessage] {
+        self.conversation.messages()
+    }
+
+    /// Shows `message`, unless it is on another topic or already shown.
+    /// Returns whether it was new.
+    pub fn insert(&mut self, message: ChatMessage) -> bool {
+        message.topic_hash == self.topic_hash && self.conversation.insert(message)
+    }
+
+    /// Records that our node announced itself at `now`, as gossip does not
+    /// deliver our own announcements back to us.
+    pub fn announce(&mut self, now: Instant) {
+        if let Some(node_id) = self.own_node_id {
+            self.roster.update(
+                &Presence {
+                    topic_hash: self.topic_hash.clone(),
+                    author: self.username.clone(),
+                    node_id,
+                    status: PresenceStatus::Online,
+                },
+                now,
+            );
+        }
+    }
+
+    /// Applies an event of the client received at `now`, returning whether
+    /// anything shown changed.
+    pub fn apply(&mut self, event: ClientEvent, now: Instant) -> bool {
+        if !event.topic_hash().is_empty() && event.topic_hash() != self.topic_hash {
+            return false;
+        }
+        match event {
+            ClientEvent::Message(_) | ClientEvent::Edit { .. } | ClientEvent::Delete { .. } => {
+                self.conversation.apply(event)
+            }
+            ClientEvent::Presence(presence) => {
+                self.roster.update(&presence, now);
+                true
+            }
+            ClientEvent::Connectivity(connectivity) => {
+                self.connectivity = connectivity;
+                true
+            }
+            ClientEvent::Reconnect { state, .. } => {
+                self.reconnect = (state != ReconnectState::Connected).then_some(state);
+                true
+            }
+            ClientEvent::PeerJoined { node_id, .. } => self.peers.insert(node_id),
+            ClientEvent::PeerLeft { node_id, .. } => self.peers.remove(&node_id),
+            ClientEvent::Error { error, .. } => {
+                self.notice = Some(error);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Handles a key press, returning what the client should do about it.
+    pub fn key(&mut self, key: KeyEvent) -> Option<Action> {
+        if key.kind == KeyEventKind::Release {
+            return None;
+        }
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                Some(Action::Quit)
+            }
+            KeyCode::Esc => Some(Action::Quit),
+            KeyCode::Enter => self.submit(),
+            KeyCode::Backspace => {
+                self.input.pop();
+                None
+            }
+            KeyCode::Char(c) => {
+                self.input.push(c);
+                None
+            }
+            KeyCode::PageUp => {
+                self.scroll += PAGE_LINES;
+                None
+            }
+            KeyCode::PageDown => {
+                self.scroll = self.scroll.saturating_sub(PAGE_LINES);
+                None
+            }
+            KeyCode::End => {
+                self.scroll = 0;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    // Sends the message typed, or runs the command typed
+    fn submit(&mut self) -> Option<Action> {
+        let input = std::mem::take(&mut self.input);
+        self.notice = None;
+        let content = match commands::parse(&input) {
+            None => input,
+            Some(Ok(SlashCommand::Me(action))) => format!("*{} {}*", self.username, action),
+            Some(Ok(SlashCommand::Say(text))) => text,
+            Some(Ok(SlashCommand::Nick(name))) => {
+                self.username = name.clone();
+                return Some(Action::Nick(name));
+            }
+            Some(Ok(SlashCommand::Clear)) => {
+                self.conversation.clear();
+                self.scroll = 0;
+                return None;
+            }
+            Some(Ok(SlashCommand::Invite)) => return Some(Action::Invite),
+            Some(Ok(SlashCommand::Leave)) => return Some(Action::Quit),
+            Some(Ok(SlashCommand::Mute(_) | SlashCommand::Unmute(_))) => {
+                self.notice = Some("Muting only works in the desktop application".to_string());
+                return None;
+            }
+            Some(Err(e)) => {
+                self.notice = Some(e);
+                self.input = input;
+                return None;
+            }
+        };
+        if content.trim().is_empty() {
+            return None;
+        }
+        self.scroll = 0;
+        Some(Action::Send(content))
+    }
+
+    /// Carries out `action` over `transport`, keeping messages within
+    /// `limit`. Returns whether the user quit.
+    pub async fn perform<T: ChatTransport>(
+        &mut self,
+        action: Action,
+        transport: &T,
+        limit: MessageLimit,
+    ) -> bool {
+        match action {
+            Action::Send(content) => {
+                if let Err(e) = limit.check(&content) {
+                    self.notice = Some(e);
+                    self.input = content;
+                    return false;
+                }
+                let sequence = self.conversation.next_sequence();
+                match transport
+                    .send(&self.topic_hash, self.username.clone(), content, sequence)
+                    .await
+                {
+                    Ok(message) => {
+                        self.insert(message);
+                    }
+                    Err(e) => self.notice = Some(e),
+                }
+            }
+            Action::Nick(username) => {
+                transport.set_presence(username, PresenceStatus::Online);
+                self.announce(Instant::now());
+            }
+            Action::Invite => {
+                self.notice = Some(match transport.invite(&self.topic_hash) {
+                    Ok(invite) => format!("Invite others with {}", invite),
+                    Err(e) => e,
+                });
+            }
+            Action::Quit => return true,
+        }
+        false
+    }
+}
@@ -0,0 +1,3 @@
+//! The parts of the terminal application worth testing on their own. The
+//! application itself is the `iroh-lab-tui` binary.
+pub mod app;
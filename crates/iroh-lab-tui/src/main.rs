@@ -0,0 +1,143 @@
+//! Full-screen terminal chat, for when the desktop application cannot run,
+//! e.g. over SSH.
+//!
+//! Joins or creates a topic from the command line before taking over the
+//! terminal, so errors reaching the network are printed as usual.
+use clap::Parser;
+use crossterm::event::{Event, EventStream};
+use futures::StreamExt;
+use iroh_lab_core::client::{IrohClient, Subscription};
+use iroh_lab_core::config::Config;
+use iroh_lab_core::filter::Filters;
+use iroh_lab_core::roster::{PresenceStatus, PRESENCE_INTERVAL};
+use iroh_lab_core::transport::ChatTransport;
+use iroh_lab_tui::app::App;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+mod ui;
+
+// How often the screen is drawn while nothing happens, so members going
+// offline are shown
+const REDRAW_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Chat on a topic in a full-screen terminal interface.
+#[derive(Debug, Parser)]
+#[command(name = "iroh-lab-tui")]
+struct Args {
+    /// Name to chat under, instead of the one in the settings.
+    #[arg(long)]
+    username: Option<String>,
+    /// Ticket or invite of the topic to join.
+    #[arg(long, value_name = "TICKET", required_unless_present = "create")]
+    join: Option<String>,
+    /// Name of a topic to create.
+    #[arg(long, value_name = "TOPIC", conflicts_with = "join")]
+    create: Option<String>,
+    /// Configuration file to use instead of the one of the current user.
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run(Args::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: Args) -> Result<(), String> {
+    let config = match &args.config {
+        Some(path) => Config::load_from(path)?,
+        None => Config::load()?,
+    };
+    let username = args
+        .username
+        .or_else(|| Some(config.username.clone()))
+        .map(|username| username.trim().to_string())
+        .filter(|username| !username.is_empty())
+        .ok_or_else(|| "No name to chat under, pass one with --username".to_string())?;
+
+    let events = IrohClient::subscribe(None);
+
+    let mut client = IrohClient::new();
+    client.set_relay_mode(config.relay_mode);
+    client.set_binding(config.binding);
+    client.set_filters(Filters::from_config(&config)?);
+    eprintln!("Connecting...");
+    client.initialize_network().await?;
+
+    let (topic_name, topic_hash) = match (args.join, args.create) {
+        (Some(ticket), _) => client.join_topic(ticket).await?,
+        (None, Some(topic_name)) => {
+            let (topic_name, _, topic_hash) = client.create_topic(topic_name).await?;
+            (topic_name, topic_hash)
+        }
+        (None, None) => unreachable!("clap requires --join or --create"),
+    };
+    client.set_presence(username.clone(), PresenceStatus::Online);
+
+    let mut app = App::new(topic_name, topic_hash, username, client.own_node_id());
+    app.announce(Instant::now());
+    if let Ok(invite) = client.create_invite() {
+        app.notice = Some(format!("Invite others with {}", invite));
+    }
+
+    let mut terminal = ratatui::init();
+    let result = chat(&mut terminal, &client, &config, &mut app, events).await;
+    ratatui::restore();
+    client.shutdown().await;
+    result
+}
+
+// Draws the chat room until the user quits
+async fn chat(
+    terminal: &mut ratatui::DefaultTerminal,
+    client: &impl ChatTransport,
+    config: &Config,
+    app: &mut App,
+    mut events: Subscription,
+) -> Result<(), String> {
+    let mut keys = EventStream::new();
+    let mut redraw = tokio::time::interval(REDRAW_INTERVAL);
+    let mut presence = tokio::time::interval(PRESENCE_INTERVAL);
+    loop {
+        terminal
+            .draw(|frame| ui::draw(frame, app, Instant::now()))
+            .map_err(|e| format!("Failed to draw: {}", e))?;
+
+        tokio::select! {
+            key = keys.next() => {
+                // The terminal is gone once it has no more events
+                let Some(event) = key
+                    .transpose()
+                    .map_err(|e| format!("Failed to read the keyboard: {}", e))?
+                else {
+                    return Ok(());
+                };
+                let Event::Key(key) = event else {
+                    continue;
+                };
+                if let Some(action) = app.key(key) {
+                    if app.perform(action, client, config.message_limit).await {
+                        return Ok(());
+                    }
+                }
+            }
+            event = events.next() => {
+                let Some(event) = event else {
+                    return Err("The client stopped".to_string());
+                };
+                app.apply(event, Instant::now());
+                app.dropped = events.dropped();
+            }
+            _ = presence.tick() => app.announce(Instant::now()),
+            _ = redraw.tick() => {}
+        }
+    }
+}
@@ -0,0 +1,174 @@
+// Drawing of the chat room: the topic and connectivity on top, the messages
+// with the members beside them, and the message being typed at the bottom
+use chrono::Local;
+use iroh_lab_core::client::ChatMessage;
+use iroh_lab_core::diagnostics::Connectivity;
+use iroh_lab_core::reconnect::ReconnectState;
+use iroh_lab_core::roster::MemberState;
+use iroh_lab_tui::app::App;
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Wrap};
+use ratatui::Frame;
+use std::time::Instant;
+
+// Width of the member list
+const MEMBERS_WIDTH: u16 = 24;
+
+pub fn draw(frame: &mut Frame, app: &App, now: Instant) {
+    // Notices like invites may take more than a line
+    let notice_rows = app.notice.as_ref().map_or(0, |notice| {
+        notice.chars().count() as u16 / frame.area().width.max(1) + 1
+    });
+    let [header, body, notice, input] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(3),
+        Constraint::Length(notice_rows),
+        Constraint::Length(3),
+    ])
+    .areas(frame.area());
+    let [messages, members] =
+        Layout::horizontal([Constraint::Min(20), Constraint::Length(MEMBERS_WIDTH)]).areas(body);
+
+    draw_header(frame, app, header);
+    draw_messages(frame, app, messages);
+    draw_members(frame, app, members, now);
+    if let Some(text) = &app.notice {
+        frame.render_widget(
+            Paragraph::new(text.as_str())
+                .yellow()
+                .wrap(Wrap { trim: false }),
+            notice,
+        );
+    }
+    draw_input(frame, app, input);
+}
+
+fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
+    let color = match app.connectivity {
+        Connectivity::Connected => Color::Green,
+        Connectivity::RelayOnly | Connectivity::Reconnecting => Color::Yellow,
+        Connectivity::Offline => Color::Red,
+    };
+    let mut spans = vec![
+        Span::from(format!(" {} ", app.topic_name)).bold(),
+        Span::from(format!("● {}", app.connectivity)).fg(color),
+        Span::from(format!("  {} peers", app.peers.len())),
+    ];
+    match app.reconnect {
+        Some(ReconnectState::Retrying { attempt, delay }) => spans.push(Span::from(format!(
+            "  Connection lost, retry {} in {}s",
+            attempt,
+            delay.as_secs().max(1)
+        ))),
+        Some(ReconnectState::GaveUp) => spans.push(Span::from("  Connection lost")),
+        _ => {}
+    }
+    if app.dropped > 0 {
+        spans.push(Span::from(format!("  {} events dropped", app.dropped)));
+    }
+    spans.push(Span::from("  Esc quits, PgUp/PgDn scroll").dark_gray());
+    frame.render_widget(Paragraph::new(Line::from(spans)).reversed(), area);
+}
+
+fn draw_messages(frame: &mut Frame, app: &App, area: Rect) {
+    let lines: Vec<Line> = app
+        .messages()
+        .iter()
+        .flat_map(|message| message_lines(message, app))
+        .collect();
+    let block = Block::default().borders(Borders::RIGHT);
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+
+    // Show the latest messages, unless scrolled up
+    let rows = paragraph.line_count(area.width.saturating_sub(1));
+    let bottom = rows.saturating_sub(area.height as usize);
+    let top = bottom.saturating_sub(app.scroll);
+    frame.render_widget(paragraph.scroll((top as u16, 0)), area);
+}
+
+// The lines of a message, the first starting with when and by whom it was
+// sent
+fn message_lines<'a>(message: &'a ChatMessage, app: &App) -> Vec<Line<'a>> {
+    let time = message.timestamp.with_timezone(&Local).format("%H:%M ");
+    if message.author == "System" {
+        return vec![Line::from(format!("{}{}", time, message.content)).dark_gray()];
+    }
+
+    let own = message.verified_sender.is_some() && message.verified_sender == app.own_node_id;
+    let author = Span::from(message.author.as_str()).bold().fg(if own {
+        Color::Cyan
+    } else {
+        Color::Magenta
+    });
+    let content_style = if message.is_deleted() {
+        Style::default()
+            .add_modifier(Modifier::ITALIC)
+            .fg(Color::DarkGray)
+    } else {
+        Style::default()
+    };
+    let mut lines: Vec<Line> = message
+        .content
+        .lines()
+        .map(|line| Line::from(Span::styled(line, content_style)))
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::default());
+    }
+    let first = std::mem::take(&mut lines[0]);
+    let mut spans = vec![
+        Span::from(time.to_string()).dark_gray(),
+        author,
+        Span::from(": "),
+    ];
+    spans.extend(first.spans);
+    lines[0] = Line::from(spans);
+    if message.edited_at.is_some() && !message.is_deleted() {
+        if let Some(last) = lines.last_mut() {
+            last.push_span(Span::from(" (edited)").dark_gray());
+        }
+    }
+    lines
+}
+
+fn draw_members(frame: &mut Frame, app: &App, area: Rect, now: Instant) {
+    let members = app.roster.members(now);
+    let items: Vec<ListItem> = members
+        .iter()
+        .map(|(member, state)| {
+            let (dot, color) = match state {
+                MemberState::Online => ("●", Color::Green),
+                MemberState::Away => ("◐", Color::Yellow),
+                MemberState::Offline => ("○", Color::DarkGray),
+            };
+            let mut spans = vec![
+                Span::from(format!("{} ", dot)).fg(color),
+                Span::from(member.author.as_str()),
+            ];
+            if Some(member.node_id) == app.own_node_id {
+                spans.push(Span::from(" (you)").dark_gray());
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let title = format!(" Members ({}) ", app.roster.present(now));
+    frame.render_widget(List::new(items).block(Block::default().title(title)), area);
+}
+
+fn draw_input(frame: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Message as {} ", app.username));
+    let inner = block.inner(area);
+
+    // Keep the end of a long message in view
+    let typed = app.input.chars().count() as u16;
+    let skip = typed.saturating_sub(inner.width.saturating_sub(1));
+    let visible: String = app.input.chars().skip(skip as usize).collect();
+    frame.render_widget(Paragraph::new(visible).block(block), area);
+    frame.set_cursor_position(Position::new(inner.x + typed - skip, inner.y));
+}
@@ -0,0 +1,272 @@
+use chrono::{Duration, Utc};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use futures::{FutureExt, StreamExt};
+use iroh::SecretKey;
+use iroh_lab_core::client::{ChatMessage, ClientEvent, MessageEdit, Presence};
+use iroh_lab_core::config::MessageLimit;
+use iroh_lab_core::diagnostics::Connectivity;
+use iroh_lab_core::roster::{MemberState, PresenceStatus};
+use iroh_lab_core::testing::{ChatMessageBuilder, MemoryNetwork};
+use iroh_lab_core::transport::ChatTransport;
+use iroh_lab_tui::app::{Action, App, PAGE_LINES};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::time::Instant;
+
+const TOPIC_HASH: &str = "topic-hash";
+
+fn message(id: &str, lamport: u64, topic_hash: &str) -> ChatMessage {
+    ChatMessageBuilder::new(id)
+        .author("bob")
+        .content(format!("message {}", id))
+        .topic(topic_hash)
+        .sequence(lamport)
+        .lamport(lamport)
+        .build()
+}
+
+fn typed(app: &mut App, text: &str) -> Option<Action> {
+    for c in text.chars() {
+        app.key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    }
+    app.key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+}
+
+/// # Test: Events of the Topic Are Shown
+///
+/// ## Steps:
+/// 1. Apply messages out of order, again, and on another topic
+/// 2. Apply an edit by the sender, a presence and a connectivity change
+/// 3. Apply the sender joining and leaving as a neighbor, and an error
+///
+/// ## Assertions:
+/// - Messages are shown once, in Lamport order, and only of the topic
+/// - The edit replaces the content
+/// - The member and connectivity are shown
+/// - The neighbors are counted, and the error is shown
+#[test]
+fn test_events_of_the_topic_are_shown() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let sender = SecretKey::generate(&mut rng).public();
+    let mut app = App::new(
+        "book-club".to_string(),
+        TOPIC_HASH.to_string(),
+        "alice".to_string(),
+        None,
+    );
+    let now = Instant::now();
+
+    let mut edited = message("b", 2, TOPIC_HASH);
+    edited.verified_sender = Some(sender);
+    assert!(
+        app.apply(ClientEvent::Message(edited), now),
+        "New message should be shown"
+    );
+    app.apply(ClientEvent::Message(message("a", 1, TOPIC_HASH)), now);
+    assert!(
+        !app.apply(ClientEvent::Message(message("a", 1, TOPIC_HASH)), now),
+        "Message sent again should not be shown twice"
+    );
+    assert!(
+        !app.apply(ClientEvent::Message(message("c", 3, "other")), now),
+        "Message of another topic should not be shown"
+    );
+    let ids: Vec<_> = app.messages().iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, ["a", "b"], "Messages should be in Lamport order");
+
+    app.apply(
+        ClientEvent::Edit {
+            edit: MessageEdit {
+                target_id: "b".to_string(),
+                topic_hash: TOPIC_HASH.to_string(),
+                new_content: "fixed".to_string(),
+                edited_at: Utc::now() + Duration::seconds(1),
+            },
+            sender: Some(sender),
+        },
+        now,
+    );
+    assert_eq!(
+        app.messages()[1].content,
+        "fixed",
+        "Edit should replace the content"
+    );
+
+    app.apply(
+        ClientEvent::Presence(Presence {
+            topic_hash: TOPIC_HASH.to_string(),
+            author: "bob".to_string(),
+            node_id: sender,
+            status: PresenceStatus::Online,
+        }),
+        now,
+    );
+    let members = app.roster.members(now);
+    assert_eq!(members.len(), 1, "Member should be shown");
+    assert_eq!(members[0].1, MemberState::Online, "Member should be online");
+    app.apply(ClientEvent::Connectivity(Connectivity::RelayOnly), now);
+    assert_eq!(
+        app.connectivity,
+        Connectivity::RelayOnly,
+        "Connectivity should be shown"
+    );
+
+    app.apply(
+        ClientEvent::PeerJoined {
+            topic_hash: TOPIC_HASH.to_string(),
+            node_id: sender,
+        },
+        now,
+    );
+    assert_eq!(app.peers.len(), 1, "Neighbor should be counted");
+    app.apply(
+        ClientEvent::PeerLeft {
+            topic_hash: TOPIC_HASH.to_string(),
+            node_id: sender,
+        },
+        now,
+    );
+    assert!(app.peers.is_empty(), "Neighbor should no longer be counted");
+    app.apply(
+        ClientEvent::Error {
+            topic_hash: TOPIC_HASH.to_string(),
+            error: "Failed to send queued message".to_string(),
+        },
+        now,
+    );
+    assert_eq!(
+        app.notice.as_deref(),
+        Some("Failed to send queued message"),
+        "Error should be shown"
+    );
+}
+
+/// # Test: Typed Lines Become Actions
+///
+/// ## Steps:
+/// 1. Type a message, an action, a new name, a blank line and an unknown
+///    command
+/// 2. Scroll up and send again
+/// 3. Press Escape
+///
+/// ## Assertions:
+/// - Messages and actions are sent, and the name is changed
+/// - Blank lines send nothing, and unknown commands are kept with a notice
+/// - Sending scrolls back to the latest message
+/// - Escape quits
+#[test]
+fn test_typed_lines_become_actions() {
+    let mut app = App::new(
+        "book-club".to_string(),
+        TOPIC_HASH.to_string(),
+        "alice".to_string(),
+        None,
+    );
+
+    assert_eq!(
+        typed(&mut app, "hello"),
+        Some(Action::Send("hello".to_string())),
+        "Message should be sent"
+    );
+    assert!(app.input.is_empty(), "Input should be cleared");
+    assert_eq!(
+        typed(&mut app, "/me waves"),
+        Some(Action::Send("*alice waves*".to_string())),
+        "Action should be sent"
+    );
+    assert_eq!(
+        typed(&mut app, "/nick carol"),
+        Some(Action::Nick("carol".to_string())),
+        "Name should be changed"
+    );
+    assert_eq!(app.username, "carol", "New name should be used");
+    assert_eq!(
+        typed(&mut app, "  "),
+        None,
+        "Blank line should send nothing"
+    );
+    assert_eq!(
+        typed(&mut app, "/dance"),
+        None,
+        "Unknown command should send nothing"
+    );
+    assert!(app.notice.is_some(), "Unknown command should be reported");
+    assert_eq!(app.input, "/dance", "Unknown command should be kept");
+    app.input.clear();
+
+    app.key(KeyEvent::new(KeyCode::PageUp, KeyModifiers::NONE));
+    assert_eq!(app.scroll, PAGE_LINES, "Page Up should scroll up");
+    typed(&mut app, "again");
+    assert_eq!(app.scroll, 0, "Sending should scroll to the latest message");
+
+    assert_eq!(
+        app.key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)),
+        Some(Action::Quit),
+        "Escape should quit"
+    );
+}
+
+/// # Test: Actions Are Carried Out Over the Transport
+///
+/// ## Steps:
+/// 1. Put two members on a topic in memory, one of them in the app
+/// 2. Send a message too long, then one within the limit, and ask for an
+///    invite
+/// 3. Quit
+///
+/// ## Assertions:
+/// - The long message is kept with a notice, and not sent
+/// - The other member receives the message, which the app shows
+/// - The invite is shown
+/// - Quitting is reported
+#[tokio::test]
+async fn test_actions_are_carried_out_over_the_transport() {
+    let network = MemoryNetwork::new();
+    let alice = network.transport(1);
+    let bob = network.transport(2);
+    let topic = bob
+        .create("book-club".to_string())
+        .await
+        .expect("Topic should be created");
+    alice
+        .join(topic.ticket.clone())
+        .await
+        .expect("Topic should be joined");
+    let mut events = bob.events();
+    let mut app = App::new(topic.name, topic.hash, "alice".to_string(), None);
+
+    let long = "a".repeat(MessageLimit::Short.chars() + 1);
+    assert!(
+        !app.perform(Action::Send(long.clone()), &alice, MessageLimit::Short)
+            .await,
+        "Sending should not quit"
+    );
+    assert!(app.notice.is_some(), "Long message should be reported");
+    assert_eq!(app.input, long, "Long message should be kept");
+    app.input.clear();
+
+    app.perform(
+        Action::Send("hello".to_string()),
+        &alice,
+        MessageLimit::Short,
+    )
+    .await;
+    let Some(Some(ClientEvent::Message(received))) = events.next().now_or_never() else {
+        panic!("Bob should receive the message");
+    };
+    assert_eq!(received.content, "hello", "Message should be sent");
+    assert_eq!(app.messages().len(), 1, "Sent message should be shown once");
+
+    app.perform(Action::Invite, &alice, MessageLimit::Short)
+        .await;
+    assert!(
+        app.notice
+            .as_deref()
+            .is_some_and(|notice| notice.contains(&topic.ticket)),
+        "Invite should be shown"
+    );
+    assert!(
+        app.perform(Action::Quit, &alice, MessageLimit::Short).await,
+        "Quitting should be reported"
+    );
+}
@@ -1,3 +0,0 @@
-pub mod client;
-
-pub use client::{ChatMessage, IrohClient};
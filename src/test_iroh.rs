@@ -1,5 +0,0 @@
-use iroh::*;
-
-fn main() {
-    println!("Testing iroh API");
-} 
\ No newline at end of file
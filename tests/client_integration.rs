@@ -1,193 +0,0 @@
-use iroh_lab::client::IrohClient;
-use tokio::runtime::Runtime;
-
-/// # Test: Create Random Topic
-///
-/// This test verifies that a client can create a new randomly named topic using Iroh's
-/// decentralized document system.
-///
-/// ## Steps:
-/// 1. Initialize a new client with an Iroh node
-/// 2. Initialize the network
-/// 3. Create a topic with a specified name
-///
-/// ## Assertions:
-/// - The node ID should not be empty
-/// - The created topic name should match the requested name
-/// - The ticket and hash should not be empty
-/// - The ticket should be a valid Iroh document sharing ticket
-/// - The topic should be stored in the client's subscribed topics
-/// - The stored hash should match the returned hash
-#[test]
-fn test_create_random_topic() {
-    // Create a new runtime for async tests
-    let rt = Runtime::new().unwrap();
-
-    rt.block_on(async {
-        // Initialize a new client
-        let mut client = IrohClient::new();
-
-        // Initialize the network
-        let node_id = client
-            .initialize_network()
-            .await
-            .expect("Failed to initialize network");
-        assert!(!node_id.is_empty(), "Node ID should not be empty");
-
-        // Create a random topic
-        let topic_name = "test-topic".to_string();
-        let (created_name, ticket, hash) = client
-            .create_topic(topic_name.clone())
-            .await
-            .expect("Failed to create topic");
-
-        // Verify the topic was created successfully
-        assert_eq!(created_name, topic_name, "Topic name should match");
-        assert!(!ticket.is_empty(), "Ticket should not be empty");
-        assert!(!hash.is_empty(), "Topic hash should not be empty");
-        assert!(
-            ticket.starts_with("ticket-"),
-            "Ticket should start with 'ticket-'"
-        );
-
-        // Verify the topic is stored in the client's subscribed topics
-        assert!(
-            client.subscribed_topics.contains_key(&topic_name),
-            "Topic should be in subscribed topics"
-        );
-        assert_eq!(
-            client.subscribed_topics.get(&topic_name).unwrap(),
-            &hash,
-            "Topic hash should match"
-        );
-    });
-}
-
-/// # Test: Create Topic and Send Message
-///
-/// This test verifies that a client can create a topic and send a message to it
-/// using Iroh's peer-to-peer networking.
-///
-/// ## Steps:
-/// 1. Initialize a new client with an Iroh node
-/// 2. Create a topic
-/// 3. Send a message to the topic
-///
-/// ## Assertions:
-/// - The message should be successfully sent to the Iroh document
-/// - The message should be properly serialized and stored in the document
-#[test]
-fn test_create_topic_and_send_message() {
-    let rt = Runtime::new().unwrap();
-
-    rt.block_on(async {
-        // Initialize message channel - this is a simplified test that doesn't rely on
-        // the actual message receiving functionality, which is tested in the next test
-        let mut client = IrohClient::new();
-        client
-            .initialize_network()
-            .await
-            .expect("Failed to initialize network");
-
-        // Create a topic
-        let topic_name = "message-test-topic".to_string();
-        let (_created_name, _ticket, _hash) = client
-            .create_topic(topic_name.clone())
-            .await
-            .expect("Failed to create topic");
-
-        // Send a message - we'll just verify it doesn't error
-        let username = "test-user".to_string();
-        let message_content = "Hello, world!".to_string();
-        let sequence = 1;
-
-        let result = client
-            .send_message(username.clone(), message_content.clone(), sequence)
-            .await;
-        assert!(result.is_ok(), "Message should be sent successfully");
-    });
-}
-
-/// # Test: Two Clients Communication
-///
-/// This test verifies that two different client instances can communicate with each other
-/// using Iroh's peer-to-peer networking. It creates a scenario where one client creates a topic,
-/// another client joins it, and they exchange messages through Iroh documents.
-///
-/// ## Steps:
-/// 1. Initialize client A with an Iroh node
-/// 2. Create a topic with client A
-/// 3. Initialize client B with an Iroh node
-/// 4. Client B joins the topic created by client A
-/// 5. Client A sends a message to the shared document
-/// 6. Client B sends a message to the shared document
-///
-/// ## Assertions:
-/// - Both clients can successfully join the same topic
-/// - Messages from both clients can be sent successfully to the shared document
-/// - The topic hashes are non-empty and valid Iroh document IDs
-#[test]
-fn test_two_clients_communication() {
-    let rt = Runtime::new().unwrap();
-
-    rt.block_on(async {
-        // Initialize client A
-        let mut client_a = IrohClient::new();
-        client_a
-            .initialize_network()
-            .await
-            .expect("Failed to initialize network for client A");
-
-        // Create a topic with client A
-        let topic_name = "two-clients-test-topic".to_string();
-        let (_created_name, ticket, hash_a) = client_a
-            .create_topic(topic_name.clone())
-            .await
-            .expect("Failed to create topic");
-
-        // Initialize client B
-        let mut client_b = IrohClient::new();
-        client_b
-            .initialize_network()
-            .await
-            .expect("Failed to initialize network for client B");
-
-        // Client B joins the topic created by client A
-        let (_joined_name, hash_b) = client_b
-            .join_topic(ticket)
-            .await
-            .expect("Failed to join topic");
-
-        // Note: Due to the implementation of join_topic, hash_a and hash_b will be different
-        // hash_a is the full hash (topic_name-uuid), while hash_b is just the uuid part
-        // We'll verify they're both non-empty instead
-        assert!(!hash_a.is_empty(), "Topic hash A should not be empty");
-        assert!(!hash_b.is_empty(), "Topic hash B should not be empty");
-
-        // Client A sends a message
-        let username_a = "user-a".to_string();
-        let message_a = "Hello from client A".to_string();
-        let sequence_a = 1;
-
-        let result_a = client_a
-            .send_message(username_a.clone(), message_a.clone(), sequence_a)
-            .await;
-        assert!(
-            result_a.is_ok(),
-            "Message from client A should be sent successfully"
-        );
-
-        // Client B sends a message
-        let username_b = "user-b".to_string();
-        let message_b = "Hello from client B".to_string();
-        let sequence_b = 2;
-
-        let result_b = client_b
-            .send_message(username_b.clone(), message_b.clone(), sequence_b)
-            .await;
-        assert!(
-            result_b.is_ok(),
-            "Message from client B should be sent successfully"
-        );
-    });
-}